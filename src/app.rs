@@ -1,10 +1,18 @@
 use gtk4::prelude::*;
 use gtk4::{gio, Application};
 use std::cell::RefCell;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
+use crate::models::MediaType;
 use crate::ui::MainWindow;
 
+/// Reads the file list to show from standard input instead of a directory.
+pub const STDIN_FLAG: &str = "--stdin";
+/// Starts a separate instance instead of handing the path to a running one.
+pub const NEW_WINDOW_FLAG: &str = "--new-window";
+
 const APP_ID: &str = "lt.gtw.idxd";
 
 thread_local! {
@@ -16,29 +24,67 @@ pub struct IdxdApp {
 }
 
 impl IdxdApp {
-    pub fn new() -> Self {
+    /// With a `file_list`, the first window shows those files instead of a
+    /// directory.
+    ///
+    /// Launches normally hand their path to the instance that is already
+    /// running, which shows it in its window. A `new_window` launch, or one
+    /// with a file list (which cannot be forwarded), runs on its own instead.
+    pub fn new(file_list: Option<Vec<PathBuf>>, new_window: bool) -> Self {
+        let mut flags = gio::ApplicationFlags::HANDLES_OPEN;
+        if new_window || file_list.is_some() {
+            flags |= gio::ApplicationFlags::NON_UNIQUE;
+        }
         let app = Application::builder()
             .application_id(APP_ID)
-            .flags(gio::ApplicationFlags::HANDLES_OPEN)
+            .flags(flags)
             .build();
 
-        app.connect_activate(|app| {
-            Self::open_window(app, None);
+        app.connect_startup(|app| {
+            crate::thumbnails::cache::spawn_disk_cache_maintenance();
+            if let Some(connection) = app.dbus_connection() {
+                if let Err(err) = crate::dbus::register(&connection) {
+                    tracing::warn!("Failed to export DBus control interface: {}", err);
+                }
+            }
+        });
+        let file_list = RefCell::new(file_list);
+        app.connect_activate(move |app| {
+            if let Some(window) = active_window() {
+                window.present();
+                return;
+            }
+            Self::open_window(app, None, file_list.borrow_mut().take());
         });
         app.connect_open(|app, files, _hint| {
             let path = files.first().and_then(|f| f.path());
-            Self::open_window(app, path.as_deref());
+            match (active_window(), path) {
+                (Some(window), Some(path)) => {
+                    window.open_path(&path);
+                    window.present();
+                }
+                (Some(window), None) => window.present(),
+                (None, path) => Self::open_window(app, path.as_deref(), None),
+            }
         });
 
         Self { app }
     }
 
     pub fn run(&self) -> i32 {
-        self.app.run().into()
+        // GTK rejects options it does not know
+        let args: Vec<String> = std::env::args()
+            .filter(|arg| arg != STDIN_FLAG && arg != NEW_WINDOW_FLAG)
+            .collect();
+        self.app.run_with_args(&args).into()
     }
 
-    fn open_window(app: &Application, initial_path: Option<&std::path::Path>) {
-        let window = MainWindow::new(app, initial_path);
+    fn open_window(
+        app: &Application,
+        initial_path: Option<&Path>,
+        file_list: Option<Vec<PathBuf>>,
+    ) {
+        let window = MainWindow::new(app, initial_path, file_list);
         let window_id = Rc::as_ptr(&window) as usize;
         window.connect_close_request(move || {
             WINDOWS.with(|windows| {
@@ -52,8 +98,60 @@ impl IdxdApp {
     }
 }
 
+/// The most recently opened window that is still open.
+pub fn active_window() -> Option<Rc<MainWindow>> {
+    WINDOWS.with(|windows| windows.borrow().last().cloned())
+}
+
 impl Default for IdxdApp {
     fn default() -> Self {
-        Self::new()
+        Self::new(None, false)
+    }
+}
+
+/// Reads one path per line, as printed by `find` or `fd`. Relative paths are
+/// taken from the working directory; blank lines, missing files, and
+/// unsupported types are skipped.
+pub fn read_file_list<R: BufRead>(reader: R) -> Vec<PathBuf> {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    reader
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() {
+                return None;
+            }
+            let path = cwd.join(line);
+            let supported = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .and_then(MediaType::from_extension)
+                .is_some();
+            (supported && path.is_file()).then_some(path)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_file_list_keeps_existing_media_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let photo = dir.path().join("a b.jpg");
+        let notes = dir.path().join("notes.txt");
+        std::fs::write(&photo, b"").unwrap();
+        std::fs::write(&notes, b"").unwrap();
+
+        let input = format!(
+            "{}\r\n\n{}\n{}\n{}\n",
+            photo.display(),
+            notes.display(),
+            dir.path().join("gone.png").display(),
+            dir.path().display()
+        );
+        assert_eq!(read_file_list(input.as_bytes()), vec![photo]);
     }
 }
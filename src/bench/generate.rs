@@ -0,0 +1,292 @@
+//! Headless thumbnail pre-generation (`--generate-thumbnails`).
+//!
+//! Scans like the benchmark but writes into the real thumbnail cache and media
+//! database, so a media server can warm both before anyone opens the UI.
+
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use anyhow::{bail, Context, Result};
+
+use super::{run_thumbnail_phase, RunProgress, ThumbOffloadConfig, ThumbTask};
+use crate::models::MediaStore;
+use crate::scanner::file_scanner::{FileScanner, ScanConfig};
+use crate::thumbnails::cache::{CacheKey, ThumbnailCache};
+use crate::thumbnails::generator::{
+    ResizeMode, ThumbFormat, ThumbnailGenerator, DEFAULT_THUMB_HEIGHT, HIDPI_THUMB_HEIGHT,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenerateArgs {
+    pub paths: Vec<PathBuf>,
+    pub workers: usize,
+    /// Thumbnail heights to generate; the 1x and 2x grid variants unless
+    /// `--thumb-size` picks a single one.
+    pub thumb_sizes: Vec<u32>,
+}
+
+pub fn maybe_parse_args() -> Result<Option<GenerateArgs>> {
+    parse_args(env::args().skip(1))
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<GenerateArgs>> {
+    let mut generate = false;
+    let mut paths: Vec<PathBuf> = Vec::new();
+    let mut workers = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(2);
+    let mut thumb_sizes = vec![DEFAULT_THUMB_HEIGHT, HIDPI_THUMB_HEIGHT];
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--generate-thumbnails" => generate = true,
+            "--path" => {
+                let value = args
+                    .next()
+                    .context("Missing value for --path in thumbnail generation mode")?;
+                paths.extend(
+                    value
+                        .split(',')
+                        .filter(|p| !p.is_empty())
+                        .map(PathBuf::from),
+                );
+            }
+            "--workers" => {
+                let value = args
+                    .next()
+                    .context("Missing value for --workers in thumbnail generation mode")?;
+                workers = value
+                    .parse::<usize>()
+                    .context("Failed to parse --workers as a positive integer")?;
+            }
+            "--thumb-size" => {
+                let value = args
+                    .next()
+                    .context("Missing value for --thumb-size in thumbnail generation mode")?;
+                thumb_sizes = vec![value
+                    .parse::<u32>()
+                    .context("Failed to parse --thumb-size as a positive integer")?];
+            }
+            _ => {
+                if generate && !arg.starts_with('-') {
+                    paths.push(PathBuf::from(arg));
+                }
+            }
+        }
+    }
+
+    if !generate {
+        return Ok(None);
+    }
+    if workers == 0 {
+        bail!("--workers must be greater than 0");
+    }
+    if thumb_sizes.contains(&0) {
+        bail!("--thumb-size must be greater than 0");
+    }
+    if paths.is_empty() {
+        bail!("Thumbnail generation requires --path <directory> (or positional path)");
+    }
+    Ok(Some(GenerateArgs {
+        paths,
+        workers,
+        thumb_sizes,
+    }))
+}
+
+/// Generates missing thumbnails for every image under the given directories
+/// and records them, with their perceptual hashes, in the media database. Returns the process exit code,
+/// which is 1 if any thumbnail failed.
+pub fn run_generate_thumbnails(args: GenerateArgs) -> Result<i32> {
+    for path in &args.paths {
+        if !path.is_dir() {
+            bail!("Thumbnail path is not a directory: {}", path.display());
+        }
+    }
+
+    let thumbs_dir = ThumbnailCache::default_cache_dir()?;
+    std::fs::create_dir_all(&thumbs_dir).with_context(|| {
+        format!(
+            "Failed to create thumbnail directory {}",
+            thumbs_dir.display()
+        )
+    })?;
+    let format = ThumbFormat::from_env();
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .context("Failed to create tokio runtime for thumbnail generation")?;
+
+    let start = Instant::now();
+    let (mut generated, mut cached, mut failed) = (0usize, 0usize, 0usize);
+    for path in &args.paths {
+        println!("dir={} phase=scan start", path.display());
+        // Incremental, so unchanged files keep their stored rows (and the
+        // thumbnails recorded in them) instead of being rewritten
+        let scanner = FileScanner::with_config(ScanConfig {
+            incremental: true,
+            ..Default::default()
+        });
+        let (_items, scan_result) = runtime
+            .block_on(scanner.scan_directory(path, MediaStore::open_default()?))
+            .with_context(|| format!("Failed to scan {}", path.display()))?;
+        println!(
+            "dir={} phase=scan done total={} new={} cached={} errors={}",
+            path.display(),
+            scan_result.total_files,
+            scan_result.new_items,
+            scan_result.cached_items,
+            scan_result.error_count
+        );
+
+        let mut store = MediaStore::open_default()?;
+        let mut items = store
+            .get_media_batch(&scan_result.paths)
+            .context("Failed to load scanned media items")?;
+        items.retain(|item| ThumbnailGenerator::can_generate(&item.path));
+        items.sort_by(|a, b| a.path.cmp(&b.path));
+        let mut phashes: HashMap<PathBuf, u64> = HashMap::new();
+        for &thumb_size in &args.thumb_sizes {
+            let tasks = items
+                .iter()
+                .map(|item| {
+                    let key = CacheKey::for_source(
+                        &item.path,
+                        item.mtime,
+                        item.size,
+                        item.orientation.unwrap_or(1),
+                        thumb_size,
+                        format,
+                    );
+                    ThumbTask {
+                        path: item.path.clone(),
+                        thumb_path: thumbs_dir.join(key.disk_filename()),
+                        enqueued_at: Instant::now(),
+                    }
+                })
+                .collect();
+
+            let results = run_thumbnail_phase(
+                tasks,
+                args.workers,
+                0,
+                thumb_size,
+                format,
+                ResizeMode::Quality,
+                ThumbOffloadConfig {
+                    enabled: false,
+                    available: false,
+                },
+                &RunProgress::plain("generate"),
+                "all",
+            );
+            for result in &results {
+                if !result.success {
+                    failed += 1;
+                    continue;
+                }
+                if result.cache_hit {
+                    cached += 1;
+                } else {
+                    generated += 1;
+                }
+                let Some((width, height)) = result
+                    .dimensions
+                    .or_else(|| image::image_dimensions(&result.thumb_path).ok())
+                else {
+                    continue;
+                };
+                // Other heights only warm the disk cache; the rows track the
+                // two grid variants
+                let recorded = match thumb_size {
+                    DEFAULT_THUMB_HEIGHT => {
+                        store.update_thumbnail(&result.path, &result.thumb_path, width, height)
+                    }
+                    HIDPI_THUMB_HEIGHT => {
+                        store.update_thumbnail_2x(&result.path, &result.thumb_path, width, height)
+                    }
+                    _ => continue,
+                };
+                if let Err(err) = recorded {
+                    tracing::warn!(
+                        "Failed to record thumbnail for {}: {:#}",
+                        result.path.display(),
+                        err
+                    );
+                }
+                // Cache hits are hashed from the file on disk, once per source
+                if !phashes.contains_key(&result.path) {
+                    let phash = result.phash.or_else(|| {
+                        image::open(&result.thumb_path)
+                            .ok()
+                            .map(|img| ThumbnailGenerator::compute_phash(&img))
+                    });
+                    if let Some(phash) = phash {
+                        phashes.insert(result.path.clone(), phash);
+                    }
+                }
+            }
+        }
+        let phashes: Vec<(PathBuf, u64)> = phashes.into_iter().collect();
+        if let Err(err) = store.update_phashes(&phashes) {
+            tracing::warn!("Failed to record perceptual hashes: {:#}", err);
+        }
+    }
+
+    println!(
+        "Thumbnails complete: generated={} cached={} failed={} size={} workers={} elapsed_ms={} dir={}",
+        generated,
+        cached,
+        failed,
+        args
+            .thumb_sizes
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(","),
+        args.workers,
+        start.elapsed().as_millis(),
+        thumbs_dir.display()
+    );
+    Ok(if failed > 0 { 1 } else { 0 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Result<Option<GenerateArgs>> {
+        parse_args(list.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn parses_generate_mode_only_when_requested() {
+        assert_eq!(args(&["/photos"]).unwrap(), None);
+
+        let parsed = args(&[
+            "--generate-thumbnails",
+            "--path",
+            "/a,/b",
+            "--workers",
+            "3",
+            "--thumb-size",
+            "512",
+        ])
+        .unwrap()
+        .unwrap();
+        assert_eq!(parsed.paths, vec![PathBuf::from("/a"), PathBuf::from("/b")]);
+        assert_eq!((parsed.workers, parsed.thumb_sizes), (3, vec![512]));
+
+        let defaults = args(&["--generate-thumbnails", "/a"]).unwrap().unwrap();
+        assert_eq!(
+            defaults.thumb_sizes,
+            vec![DEFAULT_THUMB_HEIGHT, HIDPI_THUMB_HEIGHT]
+        );
+
+        assert!(args(&["--generate-thumbnails"]).is_err());
+        assert!(args(&["--generate-thumbnails", "/a", "--workers", "0"]).is_err());
+    }
+}
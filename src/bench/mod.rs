@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -9,28 +9,133 @@ use std::thread;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_json::json;
+
+pub mod generate;
 
 use crate::layout::justified::JustifiedLayout;
 use crate::models::{MediaItem, MediaStore};
-use crate::scanner::file_scanner::FileScanner;
+use crate::scanner::file_scanner::{FileScanner, ScanConfig};
 use crate::thumbnails::cache::CacheKey;
-use crate::thumbnails::generator::{ResizeMode, ThumbnailGenerator};
+use crate::thumbnails::generator::{
+    ResizeMode, ThumbFormat, ThumbnailGenerator, DEFAULT_THUMB_HEIGHT,
+};
 
 #[derive(Debug, Clone)]
 pub struct BenchmarkArgs {
-    pub path: PathBuf,
+    pub paths: Vec<PathBuf>,
     pub runs: usize,
+    pub warmup: usize,
     pub cold_cache: bool,
     pub thumb_limit: usize,
     pub thumb_timeout_ms: u64,
     pub thumb_workers: usize,
     pub thumb_visible_count: usize,
-    pub thumb_fast_resize: bool,
+    pub thumb_size: u32,
+    /// Lowercase extensions without the dot; empty means all supported files.
+    pub extensions: HashSet<String>,
+    pub thumb_resize: ResizeMode,
     pub thumb_nv_offload: bool,
     pub gpu_telemetry: bool,
     pub gpu_sample_ms: u64,
+    pub report_format: ReportFormat,
+    pub compare: Option<PathBuf>,
+    pub regression_threshold_pct: f64,
+    /// Report progress as JSON lines on stdout instead of the `run=...` lines.
+    pub progress_json: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Json,
+    Csv,
+    Both,
 }
 
+impl ReportFormat {
+    fn parse(value: &str) -> Result<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            "both" => Ok(Self::Both),
+            other => bail!(
+                "Unknown --report-format '{}' (expected json, csv or both)",
+                other
+            ),
+        }
+    }
+
+    fn writes_json(self) -> bool {
+        matches!(self, Self::Json | Self::Both)
+    }
+
+    fn writes_csv(self) -> bool {
+        matches!(self, Self::Csv | Self::Both)
+    }
+}
+
+/// Upper bucket edges (ms) for the thumbnail end-to-end latency histogram.
+/// Values at or above the last edge fall into a final open-ended bucket.
+const THUMB_LATENCY_BUCKETS_MS: [f64; 7] = [5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0];
+
+/// Column order of the CSV report. Keep this stable: new columns may only be
+/// appended so files from different versions can be concatenated.
+const CSV_COLUMNS: &[&str] = &[
+    "generated_at_unix_ms",
+    "path",
+    "run_index",
+    "elapsed_ms",
+    "total_files",
+    "new_items",
+    "cached_items",
+    "error_count",
+    "files_per_sec",
+    "load_items_ms",
+    "loaded_items",
+    "layout_rows",
+    "layout_total_ms",
+    "layout_frames_simulated",
+    "layout_frame_p50_ms",
+    "layout_frame_p95_ms",
+    "layout_frames_over_16ms",
+    "layout_frames_over_33ms",
+    "thumb_images_total",
+    "thumb_images_selected",
+    "thumb_images_visible",
+    "thumb_images_generated",
+    "thumb_images_skipped_cached",
+    "thumb_images_failed",
+    "thumb_total_ms",
+    "thumb_time_to_visible_ms",
+    "thumb_end_to_end_avg_ms",
+    "thumb_end_to_end_p95_ms",
+    "thumb_queue_wait_avg_ms",
+    "thumb_queue_wait_p95_ms",
+    "thumb_worker_avg_ms",
+    "thumb_worker_p95_ms",
+    "thumb_decode_avg_ms",
+    "thumb_decode_p95_ms",
+    "thumb_resize_avg_ms",
+    "thumb_resize_p95_ms",
+    "thumb_encode_avg_ms",
+    "thumb_encode_p95_ms",
+    "thumb_workers",
+    "thumb_resize_mode",
+    "thumb_nv_offload_enabled",
+    "thumb_nv_offload_available",
+    "thumb_nv_offload_attempted",
+    "thumb_nv_offload_used",
+    "thumb_size",
+    "max_rss_bytes",
+    "avg_rss_bytes",
+    "thumb_visible_max_rss_bytes",
+    "thumb_background_max_rss_bytes",
+    "io_read_bytes",
+    "io_rchar_bytes",
+    "io_avg_read_bytes_per_sec",
+];
+
 #[derive(Debug)]
 struct BenchmarkRun {
     run_index: usize,
@@ -59,6 +164,7 @@ struct BenchmarkRun {
     thumb_time_to_visible_ms: u128,
     thumb_end_to_end_avg_ms: f64,
     thumb_end_to_end_p95_ms: f64,
+    thumb_latency_histogram: Vec<usize>,
     thumb_queue_wait_avg_ms: f64,
     thumb_queue_wait_p95_ms: f64,
     thumb_worker_avg_ms: f64,
@@ -75,7 +181,13 @@ struct BenchmarkRun {
     thumb_nv_offload_available: bool,
     thumb_nv_offload_attempted: usize,
     thumb_nv_offload_used: usize,
+    max_rss_bytes: Option<u64>,
+    avg_rss_bytes: Option<u64>,
+    thumb_visible_max_rss_bytes: Option<u64>,
+    thumb_background_max_rss_bytes: Option<u64>,
     gpu: Option<GpuRunSummary>,
+    cpu: Option<CpuRunSummary>,
+    io: IoRunSummary,
 }
 
 #[derive(Debug)]
@@ -95,19 +207,30 @@ struct BenchmarkReport {
     schema_version: u32,
     generated_at_unix_ms: u128,
     benchmark: String,
-    path: String,
     runs_requested: usize,
+    warmup_runs: usize,
     cold_cache: bool,
     thumb_limit: usize,
     thumb_timeout_ms: u64,
     thumb_workers: usize,
     thumb_visible_count: usize,
+    thumb_size: u32,
+    extensions: Vec<String>,
     thumb_fast_resize: bool,
     thumb_nv_offload: bool,
     gpu_telemetry_enabled: bool,
     gpu_sample_ms: u64,
     db_path: String,
     thumbs_dir: String,
+    directories: Vec<DirectoryResult>,
+    /// Across all directories; identical to the single directory's aggregate
+    /// when only one path was benchmarked.
+    aggregate: BenchmarkAggregate,
+}
+
+#[derive(Debug)]
+struct DirectoryResult {
+    path: String,
     results: Vec<BenchmarkRun>,
     aggregate: BenchmarkAggregate,
 }
@@ -145,6 +268,74 @@ struct GpuRunSummary {
     collection_error: Option<String>,
 }
 
+/// Cumulative jiffies for one `/proc/stat` cpu line.
+#[derive(Debug, Clone, Copy)]
+struct CpuTimes {
+    busy: u64,
+    total: u64,
+}
+
+#[derive(Debug, Clone)]
+struct CpuSample {
+    /// The aggregate `cpu` line first, followed by `cpuN` lines.
+    cores: Vec<(String, CpuTimes)>,
+}
+
+#[derive(Debug)]
+struct CpuCoreSummary {
+    name: String,
+    avg_busy_percent: Option<f64>,
+    max_busy_percent: Option<f64>,
+}
+
+#[derive(Debug)]
+struct CpuRunSummary {
+    sample_count: usize,
+    avg_busy_percent: Option<f64>,
+    max_busy_percent: Option<f64>,
+    cores: Vec<CpuCoreSummary>,
+    collection_error: Option<String>,
+}
+
+#[derive(Debug)]
+struct MemoryRunSummary {
+    max_rss_bytes: Option<u64>,
+    avg_rss_bytes: Option<u64>,
+    visible_max_rss_bytes: Option<u64>,
+    background_max_rss_bytes: Option<u64>,
+}
+
+/// Cumulative read counters from `/proc/self/io`.
+#[derive(Debug, Clone, Copy)]
+struct IoSample {
+    at: Instant,
+    /// Bytes returned by read-like syscalls, page-cache hits included.
+    rchar: u64,
+    /// Bytes actually fetched from the block device.
+    read_bytes: u64,
+}
+
+#[derive(Debug)]
+struct IoPhaseSummary {
+    phase: &'static str,
+    elapsed_ms: f64,
+    read_bytes: u64,
+    rchar_bytes: u64,
+    avg_read_bytes_per_sec: Option<f64>,
+}
+
+#[derive(Debug)]
+struct IoRunSummary {
+    sample_count: usize,
+    read_bytes: Option<u64>,
+    rchar_bytes: Option<u64>,
+    avg_read_bytes_per_sec: Option<f64>,
+    /// Highest disk read rate between two consecutive samples.
+    max_read_bytes_per_sec: Option<f64>,
+    phases: Vec<IoPhaseSummary>,
+    collection_error: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 struct NvidiaSmiSample {
     gpu_util_percent: f64,
@@ -155,16 +346,21 @@ struct NvidiaSmiSample {
 #[derive(Debug, Clone)]
 struct ThumbTask {
     path: PathBuf,
-    mtime: i64,
-    size: i64,
+    /// Where the thumbnail is written; an existing file counts as a cache hit.
+    thumb_path: PathBuf,
     enqueued_at: Instant,
 }
 
 #[derive(Debug, Clone)]
 struct ThumbResult {
     path: PathBuf,
+    thumb_path: PathBuf,
     cache_hit: bool,
     success: bool,
+    /// Size of a freshly generated thumbnail; None for cache hits and failures.
+    dimensions: Option<(u32, u32)>,
+    /// Perceptual hash of a freshly generated thumbnail.
+    phash: Option<u64>,
     queue_wait_ms: f64,
     worker_ms: f64,
     end_to_end_ms: f64,
@@ -249,19 +445,230 @@ impl GpuTelemetryCollector {
     }
 }
 
+struct CpuTelemetryCollector {
+    stop: Arc<AtomicBool>,
+    samples: Arc<Mutex<Vec<CpuSample>>>,
+    error: Arc<Mutex<Option<String>>>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+/// Samples the resident set size of this process.
+struct MemoryCollector {
+    stop: Arc<AtomicBool>,
+    samples: Arc<Mutex<Vec<(Instant, u64)>>>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl MemoryCollector {
+    fn start(sample_ms: u64) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let samples = Arc::new(Mutex::new(Vec::new()));
+
+        let stop_ref = Arc::clone(&stop);
+        let samples_ref = Arc::clone(&samples);
+        let interval = Duration::from_millis(sample_ms.max(20));
+
+        let handle = thread::spawn(move || {
+            while !stop_ref.load(Ordering::Relaxed) {
+                // Platforms without /proc simply produce no samples.
+                if let Some(rss) = read_rss_bytes() {
+                    if let Ok(mut all) = samples_ref.lock() {
+                        all.push((Instant::now(), rss));
+                    }
+                }
+                thread::sleep(interval);
+            }
+        });
+
+        Self {
+            stop,
+            samples,
+            handle: Some(handle),
+        }
+    }
+
+    fn finish(mut self, thumbs: &ThumbMetrics) -> MemoryRunSummary {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+
+        let samples = self
+            .samples
+            .lock()
+            .map(|s| s.clone())
+            .unwrap_or_else(|_| Vec::new());
+        let values: Vec<u64> = samples.iter().map(|(_, rss)| *rss).collect();
+        let max_in = |from: Instant, to: Instant| {
+            samples
+                .iter()
+                .filter(|(at, _)| *at >= from && *at <= to)
+                .map(|(_, rss)| *rss)
+                .max()
+        };
+
+        MemoryRunSummary {
+            max_rss_bytes: values.iter().copied().max(),
+            avg_rss_bytes: if values.is_empty() {
+                None
+            } else {
+                Some(values.iter().sum::<u64>() / values.len() as u64)
+            },
+            visible_max_rss_bytes: max_in(thumbs.started_at, thumbs.visible_done_at),
+            background_max_rss_bytes: max_in(thumbs.visible_done_at, thumbs.done_at),
+        }
+    }
+}
+
+/// Samples the read counters of this process, with exact readings at each
+/// phase boundary so reads can be attributed to scan, layout and so on.
+struct IoCollector {
+    stop: Arc<AtomicBool>,
+    samples: Arc<Mutex<Vec<IoSample>>>,
+    error: Arc<Mutex<Option<String>>>,
+    handle: Option<thread::JoinHandle<()>>,
+    marks: Vec<(&'static str, Option<IoSample>)>,
+}
+
+impl IoCollector {
+    fn start(sample_ms: u64) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let samples = Arc::new(Mutex::new(Vec::new()));
+        let error = Arc::new(Mutex::new(None));
+
+        let stop_ref = Arc::clone(&stop);
+        let samples_ref = Arc::clone(&samples);
+        let error_ref = Arc::clone(&error);
+        let interval = Duration::from_millis(sample_ms.max(20));
+
+        let handle = thread::spawn(move || {
+            while !stop_ref.load(Ordering::Relaxed) {
+                match sample_proc_io() {
+                    Ok(sample) => {
+                        if let Ok(mut all) = samples_ref.lock() {
+                            all.push(sample);
+                        }
+                    }
+                    Err(e) => {
+                        if let Ok(mut err) = error_ref.lock() {
+                            if err.is_none() {
+                                *err = Some(e.to_string());
+                            }
+                        }
+                    }
+                }
+                thread::sleep(interval);
+            }
+        });
+
+        Self {
+            stop,
+            samples,
+            error,
+            handle: Some(handle),
+            marks: Vec::new(),
+        }
+    }
+
+    /// Starts `phase`, ending the previous one.
+    fn mark(&mut self, phase: &'static str) {
+        self.marks.push((phase, sample_proc_io().ok()));
+    }
+
+    fn finish(mut self) -> IoRunSummary {
+        let end = sample_proc_io().ok();
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+
+        let samples = self
+            .samples
+            .lock()
+            .map(|s| s.clone())
+            .unwrap_or_else(|_| Vec::new());
+        let error = self.error.lock().ok().and_then(|e| e.clone());
+
+        summarize_io_samples(&samples, &self.marks, end, error)
+    }
+}
+
+impl CpuTelemetryCollector {
+    fn start(sample_ms: u64) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let samples = Arc::new(Mutex::new(Vec::new()));
+        let error = Arc::new(Mutex::new(None));
+
+        let stop_ref = Arc::clone(&stop);
+        let samples_ref = Arc::clone(&samples);
+        let error_ref = Arc::clone(&error);
+        let interval = Duration::from_millis(sample_ms.max(20));
+
+        let handle = thread::spawn(move || {
+            while !stop_ref.load(Ordering::Relaxed) {
+                match sample_proc_stat() {
+                    Ok(sample) => {
+                        if let Ok(mut all) = samples_ref.lock() {
+                            all.push(sample);
+                        }
+                    }
+                    Err(e) => {
+                        if let Ok(mut err) = error_ref.lock() {
+                            if err.is_none() {
+                                *err = Some(e.to_string());
+                            }
+                        }
+                    }
+                }
+                thread::sleep(interval);
+            }
+        });
+
+        Self {
+            stop,
+            samples,
+            error,
+            handle: Some(handle),
+        }
+    }
+
+    fn finish(mut self) -> CpuRunSummary {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+
+        let samples = self
+            .samples
+            .lock()
+            .map(|s| s.clone())
+            .unwrap_or_else(|_| Vec::new());
+        let error = self.error.lock().ok().and_then(|e| e.clone());
+
+        summarize_cpu_samples(&samples, error)
+    }
+}
+
 pub fn maybe_parse_args() -> Result<Option<BenchmarkArgs>> {
     let mut benchmark = false;
-    let mut path: Option<PathBuf> = None;
+    let mut paths: Vec<PathBuf> = Vec::new();
     let mut runs: usize = 1;
+    let mut warmup: usize = 0;
     let mut cold_cache = false;
     let mut thumb_limit: usize = 0;
     let mut thumb_timeout_ms: u64 = 0;
     let mut thumb_workers: usize = 2;
     let mut thumb_visible_count: usize = 24;
-    let mut thumb_fast_resize = false;
+    let mut thumb_size: u32 = DEFAULT_THUMB_HEIGHT;
+    let mut extensions: HashSet<String> = HashSet::new();
+    let mut thumb_resize = ResizeMode::Quality;
     let mut thumb_nv_offload = false;
     let mut gpu_telemetry = false;
     let mut gpu_sample_ms: u64 = 200;
+    let mut report_format = ReportFormat::Json;
+    let mut compare: Option<PathBuf> = None;
+    let mut regression_threshold_pct: f64 = 10.0;
+    let mut progress_json = false;
 
     let mut args = env::args().skip(1);
     while let Some(arg) = args.next() {
@@ -271,7 +678,12 @@ pub fn maybe_parse_args() -> Result<Option<BenchmarkArgs>> {
                 let value = args
                     .next()
                     .context("Missing value for --path in benchmark mode")?;
-                path = Some(PathBuf::from(value));
+                paths.extend(
+                    value
+                        .split(',')
+                        .filter(|p| !p.is_empty())
+                        .map(PathBuf::from),
+                );
             }
             "--runs" => {
                 let value = args
@@ -281,6 +693,14 @@ pub fn maybe_parse_args() -> Result<Option<BenchmarkArgs>> {
                     .parse::<usize>()
                     .context("Failed to parse --runs as a positive integer")?;
             }
+            "--warmup" => {
+                let value = args
+                    .next()
+                    .context("Missing value for --warmup in benchmark mode")?;
+                warmup = value
+                    .parse::<usize>()
+                    .context("Failed to parse --warmup as a non-negative integer")?;
+            }
             "--thumb-limit" => {
                 let value = args
                     .next()
@@ -313,7 +733,37 @@ pub fn maybe_parse_args() -> Result<Option<BenchmarkArgs>> {
                     .parse::<usize>()
                     .context("Failed to parse --thumb-visible-count as a non-negative integer")?;
             }
-            "--thumb-fast-resize" => thumb_fast_resize = true,
+            "--thumb-size" => {
+                let value = args
+                    .next()
+                    .context("Missing value for --thumb-size in benchmark mode")?;
+                thumb_size = value
+                    .parse::<u32>()
+                    .context("Failed to parse --thumb-size as a positive integer")?;
+            }
+            "--ext" => {
+                let value = args
+                    .next()
+                    .context("Missing value for --ext in benchmark mode")?;
+                extensions.extend(
+                    value
+                        .split(',')
+                        .map(|ext| ext.trim().trim_start_matches('.').to_ascii_lowercase())
+                        .filter(|ext| !ext.is_empty()),
+                );
+            }
+            "--thumb-fast-resize" => thumb_resize = ResizeMode::Fast,
+            "--thumb-resize" => {
+                let value = args
+                    .next()
+                    .context("Missing value for --thumb-resize in benchmark mode")?;
+                thumb_resize = ResizeMode::parse(&value).with_context(|| {
+                    format!(
+                        "Unknown --thumb-resize '{}' (expected quality, fast or high)",
+                        value
+                    )
+                })?;
+            }
             "--thumb-nv-offload" => thumb_nv_offload = true,
             "--gpu-telemetry" => gpu_telemetry = true,
             "--gpu-sample-ms" => {
@@ -324,10 +774,32 @@ pub fn maybe_parse_args() -> Result<Option<BenchmarkArgs>> {
                     .parse::<u64>()
                     .context("Failed to parse --gpu-sample-ms as a positive integer")?;
             }
+            "--report-format" => {
+                let value = args
+                    .next()
+                    .context("Missing value for --report-format in benchmark mode")?;
+                report_format = ReportFormat::parse(&value)?;
+            }
+            "--compare" => {
+                let value = args
+                    .next()
+                    .context("Missing value for --compare in benchmark mode")?;
+                compare = Some(PathBuf::from(value));
+            }
+            "--regression-threshold" => {
+                let value = args
+                    .next()
+                    .context("Missing value for --regression-threshold in benchmark mode")?;
+                regression_threshold_pct = value
+                    .trim_end_matches('%')
+                    .parse::<f64>()
+                    .context("Failed to parse --regression-threshold as a percentage")?;
+            }
             "--cold-cache" => cold_cache = true,
+            "--progress-json" => progress_json = true,
             _ => {
-                if benchmark && path.is_none() && !arg.starts_with('-') {
-                    path = Some(PathBuf::from(arg));
+                if benchmark && paths.is_empty() && !arg.starts_with('-') {
+                    paths.push(PathBuf::from(arg));
                 }
             }
         }
@@ -345,46 +817,260 @@ pub fn maybe_parse_args() -> Result<Option<BenchmarkArgs>> {
     if thumb_workers == 0 {
         bail!("--thumb-workers must be greater than 0");
     }
+    if thumb_size == 0 {
+        bail!("--thumb-size must be greater than 0");
+    }
+    if !regression_threshold_pct.is_finite() || regression_threshold_pct < 0.0 {
+        bail!("--regression-threshold must be a non-negative percentage");
+    }
 
-    let path = path.context("Benchmark mode requires --path <directory> (or positional path)")?;
+    if paths.is_empty() {
+        bail!("Benchmark mode requires --path <directory> (or positional path)");
+    }
     Ok(Some(BenchmarkArgs {
-        path,
+        paths,
         runs,
+        warmup,
         cold_cache,
         thumb_limit,
         thumb_timeout_ms,
         thumb_workers,
         thumb_visible_count,
-        thumb_fast_resize,
+        thumb_size,
+        extensions,
+        thumb_resize,
         thumb_nv_offload,
         gpu_telemetry,
         gpu_sample_ms,
+        report_format,
+        compare,
+        regression_threshold_pct,
+        progress_json,
     }))
 }
 
 pub fn run_benchmark(args: BenchmarkArgs) -> Result<i32> {
-    if !args.path.exists() {
-        bail!("Benchmark path does not exist: {}", args.path.display());
-    }
-    if !args.path.is_dir() {
-        bail!("Benchmark path is not a directory: {}", args.path.display());
+    for path in &args.paths {
+        if !path.exists() {
+            bail!("Benchmark path does not exist: {}", path.display());
+        }
+        if !path.is_dir() {
+            bail!("Benchmark path is not a directory: {}", path.display());
+        }
     }
 
+    // Load the baseline up front so a bad path fails before the runs start.
+    let baseline = match &args.compare {
+        Some(path) => Some(load_baseline_aggregate(path)?),
+        None => None,
+    };
+
     let output_dir = PathBuf::from("target/idxd-bench");
     let thumbs_dir = output_dir.join("thumbs");
     fs::create_dir_all(&output_dir).context("Failed to create benchmark output directory")?;
     fs::create_dir_all(&thumbs_dir).context("Failed to create benchmark thumbnail directory")?;
 
     let db_path = output_dir.join("cache.sqlite");
+    let started = Instant::now();
+    let mut directories = Vec::with_capacity(args.paths.len());
+    for path in &args.paths {
+        if args.progress_json {
+            emit_progress_json(json!({
+                "event": "phase",
+                "dir": path.to_string_lossy(),
+                "phase": "dir",
+                "state": "begin",
+                "counts": {},
+                "elapsed_ms": started.elapsed().as_millis(),
+            }));
+        } else if args.paths.len() > 1 {
+            println!("dir={} phase=begin", path.display());
+        }
+        let runs = run_directory(&args, path, &db_path, &thumbs_dir)?;
+        let aggregate = build_aggregate(&runs.iter().collect::<Vec<_>>());
+        directories.push(DirectoryResult {
+            path: path.to_string_lossy().to_string(),
+            results: runs,
+            aggregate,
+        });
+    }
+
+    let aggregate = build_aggregate(
+        &directories
+            .iter()
+            .flat_map(|dir| dir.results.iter())
+            .collect::<Vec<_>>(),
+    );
+    let generated_at_unix_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .context("System clock appears to be before Unix epoch")?
+        .as_millis();
+    let output_path = output_dir.join(format!("scan-{}.json", generated_at_unix_ms));
+    let csv_output_path = output_dir.join(format!("scan-{}.csv", generated_at_unix_ms));
+
+    let report = BenchmarkReport {
+        schema_version: 4,
+        generated_at_unix_ms,
+        benchmark: "scan_layout_thumb_staged_gpu_v4".to_string(),
+        runs_requested: args.runs,
+        warmup_runs: args.warmup,
+        cold_cache: args.cold_cache,
+        thumb_limit: args.thumb_limit,
+        thumb_timeout_ms: args.thumb_timeout_ms,
+        thumb_workers: args.thumb_workers,
+        thumb_visible_count: args.thumb_visible_count,
+        thumb_size: args.thumb_size,
+        extensions: {
+            let mut extensions: Vec<String> = args.extensions.iter().cloned().collect();
+            extensions.sort();
+            extensions
+        },
+        thumb_fast_resize: args.thumb_resize == ResizeMode::Fast,
+        thumb_nv_offload: args.thumb_nv_offload,
+        gpu_telemetry_enabled: args.gpu_telemetry,
+        gpu_sample_ms: args.gpu_sample_ms,
+        db_path: db_path.to_string_lossy().to_string(),
+        thumbs_dir: thumbs_dir.to_string_lossy().to_string(),
+        directories,
+        aggregate,
+    };
+
+    let mut report_paths = Vec::new();
+    if args.report_format.writes_json() {
+        let json = render_report_json(&report);
+        fs::write(&output_path, json).with_context(|| {
+            format!(
+                "Failed to write benchmark report to {}",
+                output_path.display()
+            )
+        })?;
+        report_paths.push(output_path);
+    }
+    if args.report_format.writes_csv() {
+        let csv = render_report_csv(&report);
+        fs::write(&csv_output_path, csv).with_context(|| {
+            format!(
+                "Failed to write benchmark CSV to {}",
+                csv_output_path.display()
+            )
+        })?;
+        report_paths.push(csv_output_path);
+    }
+
+    let deltas = baseline
+        .as_ref()
+        .map(|baseline| compare_aggregates(baseline, &report.aggregate));
+    let regressions = deltas.as_ref().map_or(0, |deltas| {
+        deltas
+            .iter()
+            .filter(|delta| delta.regressed(args.regression_threshold_pct))
+            .count()
+    });
+
+    if args.progress_json {
+        emit_progress_json(json!({
+            "event": "phase",
+            "phase": "benchmark",
+            "state": "done",
+            "counts": {
+                "runs": report.aggregate.runs,
+                "regressions": deltas.as_ref().map(|_| regressions),
+            },
+            "reports": report_paths
+                .iter()
+                .map(|path| path.to_string_lossy())
+                .collect::<Vec<_>>(),
+            "elapsed_ms": started.elapsed().as_millis(),
+        }));
+        return Ok(if regressions > 0 { 1 } else { 0 });
+    }
+
+    for path in &report_paths {
+        println!("Benchmark complete: {}", path.display());
+    }
+    println!(
+        "runs={} avg_ms={:.2} min_ms={} max_ms={} avg_files_per_sec={:.2} avg_layout_p95_ms={:.2} avg_thumb_p95_ms={:.2} avg_thumb_visible_ms={:.2}",
+        report.aggregate.runs,
+        report.aggregate.avg_elapsed_ms,
+        report.aggregate.min_elapsed_ms,
+        report.aggregate.max_elapsed_ms,
+        report.aggregate.avg_files_per_sec,
+        report.aggregate.avg_layout_p95_ms,
+        report.aggregate.avg_thumb_p95_ms,
+        report.aggregate.avg_thumb_visible_ms
+    );
+
+    for dir in &report.directories {
+        if report.directories.len() > 1 {
+            println!(
+                "dir={} runs={} avg_ms={:.2} avg_files_per_sec={:.2} avg_thumb_p95_ms={:.2}",
+                dir.path,
+                dir.aggregate.runs,
+                dir.aggregate.avg_elapsed_ms,
+                dir.aggregate.avg_files_per_sec,
+                dir.aggregate.avg_thumb_p95_ms
+            );
+        }
+        for run in &dir.results {
+            println!(
+                "run={} elapsed_ms={} scan={}/{}/{}/{} thumbs(selected/vis/gen/cached/fail/workers/mode/visible_ms/p95)={}/{}/{}/{}/{}/{}/{}/{}/{:.2}",
+                run.run_index,
+                run.elapsed_ms,
+                run.total_files,
+                run.new_items,
+                run.cached_items,
+                run.error_count,
+                run.thumb_images_selected,
+                run.thumb_images_visible,
+                run.thumb_images_generated,
+                run.thumb_images_skipped_cached,
+                run.thumb_images_failed,
+                run.thumb_workers,
+                run.thumb_resize_mode,
+                run.thumb_time_to_visible_ms,
+                run.thumb_end_to_end_p95_ms
+            );
+        }
+    }
+
+    if let (Some(deltas), Some(baseline_path)) = (&deltas, &args.compare) {
+        println!("Comparison against {}", baseline_path.display());
+        print_comparison(deltas, args.regression_threshold_pct);
+        if regressions > 0 {
+            println!(
+                "Regression detected: {} metric(s) worse than {:.1}% threshold",
+                regressions, args.regression_threshold_pct
+            );
+            return Ok(1);
+        }
+    }
+
+    Ok(0)
+}
+
+/// Runs the warmup and measured passes for one directory.
+fn run_directory(
+    args: &BenchmarkArgs,
+    path: &Path,
+    db_path: &Path,
+    thumbs_dir: &Path,
+) -> Result<Vec<BenchmarkRun>> {
     let mut runs = Vec::with_capacity(args.runs);
 
-    for run_index in 0..args.runs {
-        let run_number = run_index + 1;
-        println!("run={} phase=begin", run_number);
+    for pass_index in 0..args.warmup + args.runs {
+        // Warmup passes run the full pipeline but are left out of the results.
+        let is_warmup = pass_index < args.warmup;
+        let run_number = if is_warmup {
+            pass_index + 1
+        } else {
+            pass_index - args.warmup + 1
+        };
+        let progress = RunProgress::for_run(path, run_number, is_warmup, args.progress_json);
+        progress.begin();
 
         if args.cold_cache {
-            clear_cache_files(&db_path)?;
-            clear_thumb_cache_dir(&thumbs_dir)?;
+            clear_cache_files(db_path)?;
+            clear_thumb_cache_dir(thumbs_dir)?;
         }
 
         let gpu_collector = if args.gpu_telemetry {
@@ -392,6 +1078,13 @@ pub fn run_benchmark(args: BenchmarkArgs) -> Result<i32> {
         } else {
             None
         };
+        let cpu_collector = if args.gpu_telemetry {
+            Some(CpuTelemetryCollector::start(args.gpu_sample_ms))
+        } else {
+            None
+        };
+        let memory_collector = MemoryCollector::start(args.gpu_sample_ms);
+        let mut io_collector = IoCollector::start(args.gpu_sample_ms);
 
         let start = Instant::now();
 
@@ -400,19 +1093,35 @@ pub fn run_benchmark(args: BenchmarkArgs) -> Result<i32> {
             .build()
             .context("Failed to create tokio runtime for benchmark")?;
 
-        println!("run={} phase=scan start", run_number);
-        let store = MediaStore::open(&db_path)?;
-        let scanner = FileScanner::new();
+        progress.start("scan");
+        io_collector.mark("scan");
+        let store = MediaStore::open(db_path)?;
+        // `--ext` applies to the scan itself, so every count covers the
+        // same files as the thumbnail pass
+        let scanner = FileScanner::with_config(ScanConfig {
+            extensions: args.extensions.iter().cloned().collect(),
+            ..Default::default()
+        });
         let (_items, scan_result) = runtime
-            .block_on(scanner.scan_directory(&args.path, store))
+            .block_on(scanner.scan_directory(path, store))
             .context("Scan benchmark failed")?;
-        println!(
-            "run={} phase=scan done total={} new={} cached={} errors={}",
-            run_number,
-            scan_result.total_files,
-            scan_result.new_items,
-            scan_result.cached_items,
-            scan_result.error_count
+        progress.done(
+            "scan",
+            json!({
+                "total": scan_result.total_files,
+                "new": scan_result.new_items,
+                "cached": scan_result.cached_items,
+                "errors": scan_result.error_count,
+            }),
+            || {
+                format!(
+                    "total={} new={} cached={} errors={}",
+                    scan_result.total_files,
+                    scan_result.new_items,
+                    scan_result.cached_items,
+                    scan_result.error_count
+                )
+            },
         );
 
         let files_per_sec = if start.elapsed().as_secs_f64() > 0.0 {
@@ -421,58 +1130,84 @@ pub fn run_benchmark(args: BenchmarkArgs) -> Result<i32> {
             scan_result.total_files as f64
         };
 
-        println!("run={} phase=load-items start", run_number);
+        progress.start("load-items");
+        io_collector.mark("load-items");
         let load_start = Instant::now();
-        let store = MediaStore::open(&db_path)?;
+        let store = MediaStore::open(db_path)?;
         let media_items = store
             .get_media_batch(&scan_result.paths)
             .context("Failed to load scanned media items from benchmark DB")?;
         let load_items_ms = load_start.elapsed().as_millis();
-        println!(
-            "run={} phase=load-items done loaded={} ms={}",
-            run_number,
-            media_items.len(),
-            load_items_ms
+        progress.done(
+            "load-items",
+            json!({ "loaded": media_items.len(), "ms": load_items_ms }),
+            || format!("loaded={} ms={}", media_items.len(), load_items_ms),
         );
 
-        println!("run={} phase=layout start", run_number);
+        progress.start("layout");
+        io_collector.mark("layout");
         let (layout_rows, layout_total_ms, frames_simulated, frame_p50, frame_p95, over16, over33) =
             simulate_layout_snappiness(&media_items);
-        println!(
-            "run={} phase=layout done rows={} frames={} p95_ms={:.2}",
-            run_number, layout_rows, frames_simulated, frame_p95
+        progress.done(
+            "layout",
+            json!({ "rows": layout_rows, "frames": frames_simulated, "p95_ms": frame_p95 }),
+            || {
+                format!(
+                    "rows={} frames={} p95_ms={:.2}",
+                    layout_rows, frames_simulated, frame_p95
+                )
+            },
         );
 
-        println!("run={} phase=thumbnails start", run_number);
-        let resize_mode = if args.thumb_fast_resize {
-            ResizeMode::Fast
-        } else {
-            ResizeMode::Quality
-        };
+        progress.start("thumbnails");
+        io_collector.mark("thumbnails");
         let thumb_metrics = run_thumbnail_pass(
             &media_items,
-            &thumbs_dir,
-            args.thumb_limit,
-            args.thumb_visible_count,
-            args.thumb_workers,
-            args.thumb_timeout_ms,
-            resize_mode,
-            args.thumb_nv_offload,
-            run_number,
+            &ThumbPassConfig {
+                thumbs_dir,
+                limit: args.thumb_limit,
+                visible_count: args.thumb_visible_count,
+                workers: args.thumb_workers,
+                timeout_ms: args.thumb_timeout_ms,
+                size: args.thumb_size,
+                resize_mode: args.thumb_resize,
+                nv_offload: args.thumb_nv_offload,
+            },
+            &progress,
         );
-        println!(
-            "run={} phase=thumbnails done selected={} generated={} cached={} failed={} visible_ms={} p95_ms={:.2}",
-            run_number,
-            thumb_metrics.images_selected,
-            thumb_metrics.images_generated,
-            thumb_metrics.images_skipped_cached,
-            thumb_metrics.images_failed,
-            thumb_metrics.time_to_visible_ms,
-            thumb_metrics.end_to_end_p95_ms
+        progress.done(
+            "thumbnails",
+            json!({
+                "selected": thumb_metrics.images_selected,
+                "generated": thumb_metrics.images_generated,
+                "cached": thumb_metrics.images_skipped_cached,
+                "failed": thumb_metrics.images_failed,
+                "visible_ms": thumb_metrics.time_to_visible_ms,
+                "p95_ms": thumb_metrics.end_to_end_p95_ms,
+            }),
+            || {
+                format!(
+                    "selected={} generated={} cached={} failed={} visible_ms={} p95_ms={:.2}",
+                    thumb_metrics.images_selected,
+                    thumb_metrics.images_generated,
+                    thumb_metrics.images_skipped_cached,
+                    thumb_metrics.images_failed,
+                    thumb_metrics.time_to_visible_ms,
+                    thumb_metrics.end_to_end_p95_ms
+                )
+            },
         );
 
         let elapsed_ms = start.elapsed().as_millis();
         let gpu_summary = gpu_collector.map(|collector| collector.finish());
+        let cpu_summary = cpu_collector.map(|collector| collector.finish());
+        let memory = memory_collector.finish(&thumb_metrics);
+        let io = io_collector.finish();
+
+        progress.end(elapsed_ms, is_warmup);
+        if is_warmup {
+            continue;
+        }
 
         runs.push(BenchmarkRun {
             run_index: run_number,
@@ -501,6 +1236,7 @@ pub fn run_benchmark(args: BenchmarkArgs) -> Result<i32> {
             thumb_time_to_visible_ms: thumb_metrics.time_to_visible_ms,
             thumb_end_to_end_avg_ms: thumb_metrics.end_to_end_avg_ms,
             thumb_end_to_end_p95_ms: thumb_metrics.end_to_end_p95_ms,
+            thumb_latency_histogram: thumb_metrics.latency_histogram,
             thumb_queue_wait_avg_ms: thumb_metrics.queue_wait_avg_ms,
             thumb_queue_wait_p95_ms: thumb_metrics.queue_wait_p95_ms,
             thumb_worker_avg_ms: thumb_metrics.worker_avg_ms,
@@ -512,92 +1248,280 @@ pub fn run_benchmark(args: BenchmarkArgs) -> Result<i32> {
             thumb_encode_avg_ms: thumb_metrics.encode_avg_ms,
             thumb_encode_p95_ms: thumb_metrics.encode_p95_ms,
             thumb_workers: args.thumb_workers,
-            thumb_resize_mode: match resize_mode {
-                ResizeMode::Quality => "quality".to_string(),
-                ResizeMode::Fast => "fast".to_string(),
-            },
+            thumb_resize_mode: args.thumb_resize.as_str().to_string(),
             thumb_nv_offload_enabled: args.thumb_nv_offload,
             thumb_nv_offload_available: thumb_metrics.offload_available,
             thumb_nv_offload_attempted: thumb_metrics.offload_attempted,
             thumb_nv_offload_used: thumb_metrics.offload_used,
+            max_rss_bytes: memory.max_rss_bytes,
+            avg_rss_bytes: memory.avg_rss_bytes,
+            thumb_visible_max_rss_bytes: memory.visible_max_rss_bytes,
+            thumb_background_max_rss_bytes: memory.background_max_rss_bytes,
             gpu: gpu_summary,
+            cpu: cpu_summary,
+            io,
         });
     }
 
-    let aggregate = build_aggregate(&runs);
-    let generated_at_unix_ms = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .context("System clock appears to be before Unix epoch")?
-        .as_millis();
-    let output_path = output_dir.join(format!("scan-{}.json", generated_at_unix_ms));
+    Ok(runs)
+}
 
-    let report = BenchmarkReport {
-        schema_version: 4,
-        generated_at_unix_ms,
-        benchmark: "scan_layout_thumb_staged_gpu_v4".to_string(),
-        path: args.path.to_string_lossy().to_string(),
-        runs_requested: args.runs,
-        cold_cache: args.cold_cache,
-        thumb_limit: args.thumb_limit,
-        thumb_timeout_ms: args.thumb_timeout_ms,
-        thumb_workers: args.thumb_workers,
-        thumb_visible_count: args.thumb_visible_count,
-        thumb_fast_resize: args.thumb_fast_resize,
-        thumb_nv_offload: args.thumb_nv_offload,
-        gpu_telemetry_enabled: args.gpu_telemetry,
-        gpu_sample_ms: args.gpu_sample_ms,
-        db_path: db_path.to_string_lossy().to_string(),
-        thumbs_dir: thumbs_dir.to_string_lossy().to_string(),
-        results: runs,
-        aggregate,
-    };
+/// Reports the phase transitions and thumbnail progress of one pass, either
+/// as `run=<label> phase=...` lines or, with `--progress-json`, as one JSON
+/// object per line with the directory, run index, phase, counts and the
+/// milliseconds since the pass began.
+struct RunProgress {
+    json: bool,
+    dir: String,
+    run_index: usize,
+    warmup: bool,
+    label: String,
+    started: Instant,
+}
 
-    let json = render_report_json(&report);
-    fs::write(&output_path, json).with_context(|| {
-        format!(
-            "Failed to write benchmark report to {}",
-            output_path.display()
-        )
-    })?;
+impl RunProgress {
+    fn for_run(dir: &Path, run_index: usize, warmup: bool, json: bool) -> Self {
+        Self {
+            json,
+            dir: dir.to_string_lossy().into_owned(),
+            run_index,
+            warmup,
+            label: if warmup {
+                format!("warmup-{}", run_index)
+            } else {
+                run_index.to_string()
+            },
+            started: Instant::now(),
+        }
+    }
+
+    /// Plain-text progress under `label` outside of a benchmark run.
+    fn plain(label: &str) -> Self {
+        Self {
+            json: false,
+            dir: String::new(),
+            run_index: 0,
+            warmup: false,
+            label: label.to_string(),
+            started: Instant::now(),
+        }
+    }
+
+    fn emit(&self, event: &str, phase: &str, state: &str, counts: serde_json::Value) {
+        emit_progress_json(json!({
+            "event": event,
+            "dir": self.dir,
+            "run": self.run_index,
+            "warmup": self.warmup,
+            "phase": phase,
+            "state": state,
+            "counts": counts,
+            "elapsed_ms": self.started.elapsed().as_millis(),
+        }));
+    }
+
+    fn begin(&self) {
+        if self.json {
+            self.emit("phase", "run", "begin", json!({}));
+        } else {
+            println!("run={} phase=begin", self.label);
+        }
+    }
+
+    fn start(&self, phase: &str) {
+        if self.json {
+            self.emit("phase", phase, "start", json!({}));
+        } else {
+            println!("run={} phase={} start", self.label, phase);
+        }
+    }
+
+    /// `details` renders the counts for the text line.
+    fn done(&self, phase: &str, counts: serde_json::Value, details: impl FnOnce() -> String) {
+        if self.json {
+            self.emit("phase", phase, "done", counts);
+        } else {
+            println!("run={} phase={} done {}", self.label, phase, details());
+        }
+    }
+
+    /// Measured runs are summarized once all have finished, so only warmups
+    /// get a text line here.
+    fn end(&self, elapsed_ms: u128, discarded: bool) {
+        let state = if discarded { "discarded" } else { "done" };
+        if self.json {
+            self.emit("phase", "run", state, json!({ "elapsed_ms": elapsed_ms }));
+        } else if discarded {
+            println!(
+                "run={} phase=end elapsed_ms={} discarded",
+                self.label, elapsed_ms
+            );
+        }
+    }
+
+    fn tick(&self, phase: &str, done: usize, total: usize) {
+        if self.json {
+            self.emit(
+                "progress",
+                &format!("thumbnails:{}", phase),
+                "running",
+                json!({ "done": done, "total": total }),
+            );
+        } else {
+            println!(
+                "run={} phase=thumbnails:{} progress={}/{}",
+                self.label, phase, done, total
+            );
+        }
+    }
+
+    fn timeout(&self, phase: &str, remaining: usize, timeout_ms: u64) {
+        if self.json {
+            self.emit(
+                "phase",
+                &format!("thumbnails:{}", phase),
+                "timeout",
+                json!({ "remaining": remaining, "timeout_ms": timeout_ms }),
+            );
+        } else {
+            println!(
+                "run={} phase=thumbnails:{} timeout remaining={} timeout_ms={}",
+                self.label, phase, remaining, timeout_ms
+            );
+        }
+    }
+}
+
+/// Prints `value` as a single line so each one parses on its own.
+fn emit_progress_json(value: serde_json::Value) {
+    println!("{}", value);
+}
+
+/// The part of a previously written JSON report that `--compare` reads.
+#[derive(Deserialize)]
+struct BaselineReport {
+    aggregate: BaselineAggregate,
+}
+
+/// Aggregate metrics read back from a previously written JSON report.
+#[derive(Debug, Deserialize)]
+struct BaselineAggregate {
+    avg_elapsed_ms: f64,
+    avg_files_per_sec: f64,
+    avg_thumb_p95_ms: f64,
+    avg_layout_p95_ms: f64,
+}
+
+fn load_baseline_aggregate(path: &Path) -> Result<BaselineAggregate> {
+    let raw = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read baseline report {}", path.display()))?;
+    let report: BaselineReport = serde_json::from_str(&raw)
+        .with_context(|| format!("Failed to parse baseline report {}", path.display()))?;
+    Ok(report.aggregate)
+}
+
+/// One aggregate metric of the current report next to the baseline.
+struct MetricDelta {
+    name: &'static str,
+    baseline: f64,
+    current: f64,
+    /// Signed change relative to the baseline.
+    delta_pct: f64,
+    /// Positive when the metric got worse, whichever direction that is.
+    worse_pct: f64,
+}
+
+impl MetricDelta {
+    fn regressed(&self, threshold_pct: f64) -> bool {
+        self.worse_pct > threshold_pct
+    }
+}
+
+fn compare_aggregates(
+    baseline: &BaselineAggregate,
+    current: &BenchmarkAggregate,
+) -> Vec<MetricDelta> {
+    // (name, baseline, current, higher_is_better)
+    let metrics = [
+        (
+            "avg_elapsed_ms",
+            baseline.avg_elapsed_ms,
+            current.avg_elapsed_ms,
+            false,
+        ),
+        (
+            "avg_files_per_sec",
+            baseline.avg_files_per_sec,
+            current.avg_files_per_sec,
+            true,
+        ),
+        (
+            "avg_thumb_p95_ms",
+            baseline.avg_thumb_p95_ms,
+            current.avg_thumb_p95_ms,
+            false,
+        ),
+        (
+            "avg_layout_p95_ms",
+            baseline.avg_layout_p95_ms,
+            current.avg_layout_p95_ms,
+            false,
+        ),
+    ];
+
+    metrics
+        .into_iter()
+        .map(|(name, base, now, higher_is_better)| {
+            let delta_pct = percent_delta(base, now);
+            MetricDelta {
+                name,
+                baseline: base,
+                current: now,
+                delta_pct,
+                worse_pct: if higher_is_better {
+                    -delta_pct
+                } else {
+                    delta_pct
+                },
+            }
+        })
+        .collect()
+}
 
-    println!("Benchmark complete: {}", output_path.display());
+/// Prints a metric diff table.
+fn print_comparison(deltas: &[MetricDelta], threshold_pct: f64) {
     println!(
-        "runs={} avg_ms={:.2} min_ms={} max_ms={} avg_files_per_sec={:.2} avg_layout_p95_ms={:.2} avg_thumb_p95_ms={:.2} avg_thumb_visible_ms={:.2}",
-        report.aggregate.runs,
-        report.aggregate.avg_elapsed_ms,
-        report.aggregate.min_elapsed_ms,
-        report.aggregate.max_elapsed_ms,
-        report.aggregate.avg_files_per_sec,
-        report.aggregate.avg_layout_p95_ms,
-        report.aggregate.avg_thumb_p95_ms,
-        report.aggregate.avg_thumb_visible_ms
+        "{:<20} {:>14} {:>14} {:>10}  status",
+        "metric", "baseline", "current", "delta"
     );
-
-    for run in &report.results {
+    for delta in deltas {
+        let status = if delta.regressed(threshold_pct) {
+            "REGRESSION"
+        } else if delta.worse_pct < -threshold_pct {
+            "improved"
+        } else {
+            "ok"
+        };
         println!(
-            "run={} elapsed_ms={} scan={}/{}/{}/{} thumbs(selected/vis/gen/cached/fail/workers/mode/visible_ms/p95)={}/{}/{}/{}/{}/{}/{}/{}/{:.2}",
-            run.run_index,
-            run.elapsed_ms,
-            run.total_files,
-            run.new_items,
-            run.cached_items,
-            run.error_count,
-            run.thumb_images_selected,
-            run.thumb_images_visible,
-            run.thumb_images_generated,
-            run.thumb_images_skipped_cached,
-            run.thumb_images_failed,
-            run.thumb_workers,
-            run.thumb_resize_mode,
-            run.thumb_time_to_visible_ms,
-            run.thumb_end_to_end_p95_ms
+            "{:<20} {:>14.3} {:>14.3} {:>+9.1}%  {}",
+            delta.name, delta.baseline, delta.current, delta.delta_pct, status
         );
     }
+}
 
-    Ok(0)
+fn percent_delta(baseline: f64, current: f64) -> f64 {
+    if baseline.abs() < f64::EPSILON {
+        if current.abs() < f64::EPSILON {
+            0.0
+        } else {
+            100.0
+        }
+    } else {
+        (current - baseline) / baseline * 100.0
+    }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 struct ThumbMetrics {
     images_total: usize,
     images_selected: usize,
@@ -609,6 +1533,7 @@ struct ThumbMetrics {
     time_to_visible_ms: u128,
     end_to_end_avg_ms: f64,
     end_to_end_p95_ms: f64,
+    latency_histogram: Vec<usize>,
     queue_wait_avg_ms: f64,
     queue_wait_p95_ms: f64,
     worker_avg_ms: f64,
@@ -622,18 +1547,29 @@ struct ThumbMetrics {
     offload_available: bool,
     offload_attempted: usize,
     offload_used: usize,
+    started_at: Instant,
+    visible_done_at: Instant,
+    done_at: Instant,
+}
+
+/// Thumbnail settings of one benchmark run, taken from `BenchmarkArgs`.
+struct ThumbPassConfig<'a> {
+    thumbs_dir: &'a Path,
+    /// Images to thumbnail; 0 means all.
+    limit: usize,
+    /// Leading images timed as the first screenful.
+    visible_count: usize,
+    workers: usize,
+    timeout_ms: u64,
+    size: u32,
+    resize_mode: ResizeMode,
+    nv_offload: bool,
 }
 
 fn run_thumbnail_pass(
     items: &[MediaItem],
-    thumbs_dir: &Path,
-    thumb_limit: usize,
-    thumb_visible_count: usize,
-    thumb_workers: usize,
-    thumb_timeout_ms: u64,
-    resize_mode: ResizeMode,
-    nv_offload_enabled: bool,
-    run_number: usize,
+    config: &ThumbPassConfig<'_>,
+    progress: &RunProgress,
 ) -> ThumbMetrics {
     let mut image_items: Vec<&MediaItem> = items
         .iter()
@@ -642,26 +1578,28 @@ fn run_thumbnail_pass(
     image_items.sort_by(|a, b| a.path.cmp(&b.path));
 
     let images_total = image_items.len();
-    let selected_count = if thumb_limit == 0 {
+    let selected_count = if config.limit == 0 {
         images_total
     } else {
-        thumb_limit.min(images_total)
+        config.limit.min(images_total)
     };
-    let visible_count = thumb_visible_count.min(selected_count);
+    let visible_count = config.visible_count.min(selected_count);
 
     let mut tasks = Vec::with_capacity(selected_count);
     for item in image_items.into_iter().take(selected_count) {
+        let key = CacheKey::with_thumb_height(&item.path, item.mtime, item.size, config.size);
         tasks.push(ThumbTask {
             path: item.path.clone(),
-            mtime: item.mtime,
-            size: item.size,
+            thumb_path: config.thumbs_dir.join(key.disk_filename()),
             enqueued_at: Instant::now(),
         });
     }
 
     let offload_cfg = ThumbOffloadConfig {
-        enabled: nv_offload_enabled,
-        available: nv_offload_enabled && nvidia_smi_available(),
+        enabled: config.nv_offload,
+        available: config.nv_offload
+            && nvidia_smi_available()
+            && ThumbnailGenerator::gpu_offload_available(),
     };
 
     let start_all = Instant::now();
@@ -669,26 +1607,29 @@ fn run_thumbnail_pass(
 
     let phase_visible = run_thumbnail_phase(
         tasks[..visible_count].to_vec(),
-        thumb_workers,
-        thumb_timeout_ms,
-        thumbs_dir.to_path_buf(),
-        resize_mode,
+        config.workers,
+        config.timeout_ms,
+        config.size,
+        ThumbFormat::Jpeg,
+        config.resize_mode,
         offload_cfg.clone(),
-        run_number,
+        progress,
         "visible",
     );
     let time_to_visible_ms = start_all.elapsed().as_millis();
+    let visible_done_at = Instant::now();
     results.extend(phase_visible);
 
     let phase_background = if visible_count < selected_count {
         run_thumbnail_phase(
             tasks[visible_count..].to_vec(),
-            thumb_workers,
-            thumb_timeout_ms,
-            thumbs_dir.to_path_buf(),
-            resize_mode,
+            config.workers,
+            config.timeout_ms,
+            config.size,
+            ThumbFormat::Jpeg,
+            config.resize_mode,
             offload_cfg.clone(),
-            run_number,
+            progress,
             "background",
         )
     } else {
@@ -697,6 +1638,7 @@ fn run_thumbnail_pass(
     results.extend(phase_background);
 
     let total_ms = start_all.elapsed().as_millis();
+    let done_at = Instant::now();
 
     let images_generated = results.iter().filter(|r| r.success && !r.cache_hit).count();
     let images_skipped_cached = results.iter().filter(|r| r.cache_hit).count();
@@ -728,6 +1670,7 @@ fn run_thumbnail_pass(
         time_to_visible_ms,
         end_to_end_avg_ms: average(&end_to_end_values),
         end_to_end_p95_ms: percentile_ms(&end_to_end_values, 0.95),
+        latency_histogram: latency_histogram(&end_to_end_values),
         queue_wait_avg_ms: average(&queue_wait_values),
         queue_wait_p95_ms: percentile_ms(&queue_wait_values, 0.95),
         worker_avg_ms: average(&worker_values),
@@ -741,17 +1684,23 @@ fn run_thumbnail_pass(
         offload_available: offload_cfg.available,
         offload_attempted,
         offload_used,
+        started_at: start_all,
+        visible_done_at,
+        done_at,
     }
 }
 
+/// Generates thumbnails for `tasks` on `workers` threads, reporting progress
+/// every ten results through `progress`.
 fn run_thumbnail_phase(
     mut tasks: Vec<ThumbTask>,
     workers: usize,
     timeout_ms: u64,
-    thumbs_dir: PathBuf,
+    thumb_size: u32,
+    format: ThumbFormat,
     resize_mode: ResizeMode,
     offload_cfg: ThumbOffloadConfig,
-    run_number: usize,
+    progress: &RunProgress,
     phase_name: &str,
 ) -> Vec<ThumbResult> {
     if tasks.is_empty() {
@@ -768,7 +1717,6 @@ fn run_thumbnail_phase(
     for _ in 0..worker_count {
         let rx = task_rx.clone();
         let tx = result_tx.clone();
-        let phase_thumbs_dir = thumbs_dir.clone();
         let phase_mode = resize_mode;
         let phase_offload = offload_cfg.clone();
 
@@ -781,27 +1729,61 @@ fn run_thumbnail_phase(
                 let mut encode_ms = 0.0;
                 let mut offload_attempted = false;
                 let mut offload_used = false;
+                let mut dimensions = None;
+                let mut phash = None;
 
-                let key = CacheKey::new(&task.path, task.mtime, task.size);
-                let thumb_path = phase_thumbs_dir.join(key.disk_filename());
+                let thumb_path = task.thumb_path;
                 let cache_hit = thumb_path.exists();
 
                 let success = if cache_hit {
                     true
                 } else {
-                    if phase_offload.enabled && phase_offload.available {
+                    let mut gpu_timings = None;
+                    if phase_offload.enabled
+                        && phase_offload.available
+                        && format == ThumbFormat::Jpeg
+                        && ThumbnailGenerator::can_generate_gpu(&task.path)
+                    {
                         offload_attempted = true;
-                        // Scaffolding: currently falls back to CPU path after capability check.
-                        offload_used = false;
+                        match ThumbnailGenerator::generate_thumbnail_gpu(
+                            &task.path,
+                            &thumb_path,
+                            thumb_size,
+                            phase_mode,
+                        ) {
+                            Ok((res, timings)) => {
+                                offload_used = true;
+                                dimensions = Some((res.width, res.height));
+                                phash = Some(res.phash);
+                                gpu_timings = Some(timings);
+                            }
+                            Err(e) => {
+                                tracing::debug!(
+                                    path = %task.path.display(),
+                                    error = %e,
+                                    "GPU thumbnail failed, falling back to CPU"
+                                );
+                            }
+                        }
                     }
 
-                    match ThumbnailGenerator::generate_thumbnail_with_mode(
-                        &task.path,
-                        &thumb_path,
-                        256,
-                        phase_mode,
-                    ) {
-                        Ok((_res, timings)) => {
+                    let timings = match gpu_timings {
+                        Some(timings) => Ok(timings),
+                        None => ThumbnailGenerator::generate_thumbnail_with_format(
+                            &task.path,
+                            &thumb_path,
+                            thumb_size,
+                            phase_mode,
+                            format,
+                        )
+                        .map(|(res, timings)| {
+                            dimensions = Some((res.width, res.height));
+                            phash = Some(res.phash);
+                            timings
+                        }),
+                    };
+                    match timings {
+                        Ok(timings) => {
                             decode_ms = timings.decode_ms;
                             resize_ms = timings.resize_ms;
                             encode_ms = timings.encode_ms;
@@ -814,8 +1796,11 @@ fn run_thumbnail_phase(
                 let worker_ms = worker_start.elapsed().as_secs_f64() * 1000.0;
                 let result = ThumbResult {
                     path: task.path,
+                    thumb_path,
                     cache_hit,
                     success,
+                    dimensions,
+                    phash,
                     queue_wait_ms,
                     worker_ms,
                     end_to_end_ms: queue_wait_ms + worker_ms,
@@ -851,21 +1836,12 @@ fn run_thumbnail_phase(
                 last_progress = Instant::now();
 
                 if done == phase_total || done % progress_every == 0 {
-                    println!(
-                        "run={} phase=thumbnails:{} progress={}/{}",
-                        run_number, phase_name, done, phase_total
-                    );
+                    progress.tick(phase_name, done, phase_total);
                 }
             }
             Err(flume::RecvTimeoutError::Timeout) => {
                 if timeout_ms > 0 && last_progress.elapsed() > Duration::from_millis(timeout_ms) {
-                    println!(
-                        "run={} phase=thumbnails:{} timeout remaining={} timeout_ms={}",
-                        run_number,
-                        phase_name,
-                        phase_total.saturating_sub(done),
-                        timeout_ms
-                    );
+                    progress.timeout(phase_name, phase_total.saturating_sub(done), timeout_ms);
                     break;
                 }
             }
@@ -1080,6 +2056,217 @@ fn nvidia_samples_as_devices(smi_samples: &[NvidiaSmiSample]) -> Vec<GpuDeviceSn
         .collect()
 }
 
+fn read_rss_bytes() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+    let kb = line
+        .trim_start_matches("VmRSS:")
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(kb * 1024)
+}
+
+fn sample_proc_io() -> Result<IoSample> {
+    let raw = fs::read_to_string("/proc/self/io").context("Failed to read /proc/self/io")?;
+    let (rchar, read_bytes) =
+        parse_proc_io(&raw).context("/proc/self/io has no rchar/read_bytes fields")?;
+    Ok(IoSample {
+        at: Instant::now(),
+        rchar,
+        read_bytes,
+    })
+}
+
+/// `(rchar, read_bytes)` from the contents of `/proc/<pid>/io`.
+fn parse_proc_io(raw: &str) -> Option<(u64, u64)> {
+    let field = |name: &str| {
+        raw.lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+            .and_then(|value| value.trim().parse::<u64>().ok())
+    };
+    Some((field("rchar")?, field("read_bytes")?))
+}
+
+fn sample_proc_stat() -> Result<CpuSample> {
+    let raw = fs::read_to_string("/proc/stat").context("Failed to read /proc/stat")?;
+    let cores = parse_proc_stat(&raw);
+    if cores.is_empty() {
+        bail!("/proc/stat contains no cpu lines");
+    }
+    Ok(CpuSample { cores })
+}
+
+fn parse_proc_stat(raw: &str) -> Vec<(String, CpuTimes)> {
+    let mut cores = Vec::new();
+    for line in raw.lines() {
+        let mut fields = line.split_whitespace();
+        let Some(name) = fields.next() else {
+            continue;
+        };
+        if !name.starts_with("cpu") {
+            continue;
+        }
+        // user nice system idle iowait irq softirq steal; guest time is
+        // already counted in user/nice.
+        let values: Vec<u64> = fields
+            .take(8)
+            .map(|v| v.parse::<u64>().unwrap_or(0))
+            .collect();
+        if values.len() < 4 {
+            continue;
+        }
+        let total: u64 = values.iter().sum();
+        let idle = values[3] + values.get(4).copied().unwrap_or(0);
+        cores.push((
+            name.to_string(),
+            CpuTimes {
+                busy: total.saturating_sub(idle),
+                total,
+            },
+        ));
+    }
+    cores
+}
+
+fn bytes_per_sec(bytes: u64, from: Instant, to: Instant) -> Option<f64> {
+    let secs = to.saturating_duration_since(from).as_secs_f64();
+    (secs > 0.0).then(|| bytes as f64 / secs)
+}
+
+/// Totals run from the first phase mark to `end`; each phase runs from its
+/// mark to the next one (or `end`) and is left out when either reading failed.
+fn summarize_io_samples(
+    samples: &[IoSample],
+    marks: &[(&'static str, Option<IoSample>)],
+    end: Option<IoSample>,
+    collection_error: Option<String>,
+) -> IoRunSummary {
+    let phases = marks
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, (phase, start))| {
+            let start = (*start)?;
+            let stop = match marks.get(idx + 1) {
+                Some((_, next)) => (*next)?,
+                None => end?,
+            };
+            let read_bytes = stop.read_bytes.saturating_sub(start.read_bytes);
+            Some(IoPhaseSummary {
+                phase,
+                elapsed_ms: stop.at.saturating_duration_since(start.at).as_secs_f64() * 1000.0,
+                read_bytes,
+                rchar_bytes: stop.rchar.saturating_sub(start.rchar),
+                avg_read_bytes_per_sec: bytes_per_sec(read_bytes, start.at, stop.at),
+            })
+        })
+        .collect();
+
+    let first = marks.iter().find_map(|(_, sample)| *sample);
+    let (read_bytes, rchar_bytes, avg_read_bytes_per_sec) = match (first, end) {
+        (Some(first), Some(end)) => {
+            let read_bytes = end.read_bytes.saturating_sub(first.read_bytes);
+            (
+                Some(read_bytes),
+                Some(end.rchar.saturating_sub(first.rchar)),
+                bytes_per_sec(read_bytes, first.at, end.at),
+            )
+        }
+        _ => (None, None, None),
+    };
+
+    let max_read_bytes_per_sec = samples
+        .windows(2)
+        .filter_map(|pair| {
+            bytes_per_sec(
+                pair[1].read_bytes.saturating_sub(pair[0].read_bytes),
+                pair[0].at,
+                pair[1].at,
+            )
+        })
+        .reduce(f64::max);
+
+    IoRunSummary {
+        sample_count: samples.len(),
+        read_bytes,
+        rchar_bytes,
+        avg_read_bytes_per_sec,
+        max_read_bytes_per_sec,
+        phases,
+        collection_error,
+    }
+}
+
+fn summarize_cpu_samples(samples: &[CpuSample], collection_error: Option<String>) -> CpuRunSummary {
+    #[derive(Default)]
+    struct Acc {
+        busy_sum: f64,
+        busy_count: usize,
+        busy_max: f64,
+    }
+
+    // Busy percent is only defined between two snapshots of the counters.
+    let mut map: BTreeMap<String, Acc> = BTreeMap::new();
+    for pair in samples.windows(2) {
+        for (name, current) in &pair[1].cores {
+            let Some((_, previous)) = pair[0].cores.iter().find(|(n, _)| n == name) else {
+                continue;
+            };
+            let total = current.total.saturating_sub(previous.total);
+            if total == 0 {
+                continue;
+            }
+            let busy = current.busy.saturating_sub(previous.busy) as f64 * 100.0 / total as f64;
+            let acc = map.entry(name.clone()).or_default();
+            acc.busy_sum += busy;
+            acc.busy_count += 1;
+            acc.busy_max = acc.busy_max.max(busy);
+        }
+    }
+
+    let summarize = |acc: &Acc| {
+        if acc.busy_count > 0 {
+            (
+                Some(acc.busy_sum / acc.busy_count as f64),
+                Some(acc.busy_max),
+            )
+        } else {
+            (None, None)
+        }
+    };
+
+    let (avg_busy_percent, max_busy_percent) =
+        map.get("cpu").map(summarize).unwrap_or((None, None));
+    let mut cores: Vec<CpuCoreSummary> = map
+        .iter()
+        .filter(|(name, _)| name.as_str() != "cpu")
+        .map(|(name, acc)| {
+            let (avg_busy_percent, max_busy_percent) = summarize(acc);
+            CpuCoreSummary {
+                name: name.clone(),
+                avg_busy_percent,
+                max_busy_percent,
+            }
+        })
+        .collect();
+    cores.sort_by_key(|core| {
+        core.name
+            .trim_start_matches("cpu")
+            .parse::<u32>()
+            .unwrap_or(u32::MAX)
+    });
+
+    CpuRunSummary {
+        sample_count: samples.len(),
+        avg_busy_percent,
+        max_busy_percent,
+        cores,
+        collection_error,
+    }
+}
+
 fn summarize_gpu_samples(samples: &[GpuSample], collection_error: Option<String>) -> GpuRunSummary {
     if samples.is_empty() {
         return GpuRunSummary {
@@ -1197,11 +2384,21 @@ fn render_report_json(report: &BenchmarkReport) -> String {
         "  \"benchmark\": \"{}\",\n",
         escape_json(&report.benchmark)
     ));
-    out.push_str(&format!("  \"path\": \"{}\",\n", escape_json(&report.path)));
+    if let [dir] = report.directories.as_slice() {
+        out.push_str(&format!("  \"path\": \"{}\",\n", escape_json(&dir.path)));
+    } else {
+        let paths: Vec<String> = report
+            .directories
+            .iter()
+            .map(|dir| format!("\"{}\"", escape_json(&dir.path)))
+            .collect();
+        out.push_str(&format!("  \"paths\": [{}],\n", paths.join(", ")));
+    }
     out.push_str(&format!(
         "  \"runs_requested\": {},\n",
         report.runs_requested
     ));
+    out.push_str(&format!("  \"warmup_runs\": {},\n", report.warmup_runs));
     out.push_str(&format!("  \"cold_cache\": {},\n", report.cold_cache));
     out.push_str(&format!("  \"thumb_limit\": {},\n", report.thumb_limit));
     out.push_str(&format!(
@@ -1213,6 +2410,13 @@ fn render_report_json(report: &BenchmarkReport) -> String {
         "  \"thumb_visible_count\": {},\n",
         report.thumb_visible_count
     ));
+    out.push_str(&format!("  \"thumb_size\": {},\n", report.thumb_size));
+    let extensions: Vec<String> = report
+        .extensions
+        .iter()
+        .map(|ext| format!("\"{}\"", escape_json(ext)))
+        .collect();
+    out.push_str(&format!("  \"extensions\": [{}],\n", extensions.join(", ")));
     out.push_str(&format!(
         "  \"thumb_fast_resize\": {},\n",
         report.thumb_fast_resize
@@ -1235,8 +2439,41 @@ fn render_report_json(report: &BenchmarkReport) -> String {
         escape_json(&report.thumbs_dir)
     ));
 
+    if let [dir] = report.directories.as_slice() {
+        render_results_json(&mut out, &dir.results);
+    } else {
+        out.push_str("  \"directories\": [\n");
+        for (idx, dir) in report.directories.iter().enumerate() {
+            out.push_str("    {\n");
+            out.push_str(&format!(
+                "      \"path\": \"{}\",\n",
+                escape_json(&dir.path)
+            ));
+            let mut nested = String::new();
+            render_results_json(&mut nested, &dir.results);
+            render_aggregate_json(&mut nested, &dir.aggregate);
+            for line in nested.lines() {
+                out.push_str("    ");
+                out.push_str(line);
+                out.push('\n');
+            }
+            out.push_str("    }");
+            if idx + 1 < report.directories.len() {
+                out.push(',');
+            }
+            out.push('\n');
+        }
+        out.push_str("  ],\n");
+    }
+    render_aggregate_json(&mut out, &report.aggregate);
+    out.push_str("}\n");
+    out
+}
+
+/// Writes the `results` array (with trailing comma) at top-level indentation.
+fn render_results_json(out: &mut String, runs: &[BenchmarkRun]) {
     out.push_str("  \"results\": [\n");
-    for (idx, run) in report.results.iter().enumerate() {
+    for (idx, run) in runs.iter().enumerate() {
         out.push_str("    {\n");
         out.push_str(&format!("      \"run_index\": {},\n", run.run_index));
         out.push_str(&format!("      \"elapsed_ms\": {},\n", run.elapsed_ms));
@@ -1319,6 +2556,14 @@ fn render_report_json(report: &BenchmarkReport) -> String {
             "      \"thumb_end_to_end_p95_ms\": {:.3},\n",
             run.thumb_end_to_end_p95_ms
         ));
+        out.push_str("      \"thumb_latency_histogram\": {");
+        for (bucket, count) in run.thumb_latency_histogram.iter().enumerate() {
+            if bucket > 0 {
+                out.push_str(", ");
+            }
+            out.push_str(&format!("\"{}\": {}", latency_bucket_label(bucket), count));
+        }
+        out.push_str("},\n");
         out.push_str(&format!(
             "      \"thumb_queue_wait_avg_ms\": {:.3},\n",
             run.thumb_queue_wait_avg_ms
@@ -1383,51 +2628,148 @@ fn render_report_json(report: &BenchmarkReport) -> String {
             "      \"thumb_nv_offload_used\": {},\n",
             run.thumb_nv_offload_used
         ));
+        write_optional_u64(out, "      \"max_rss_bytes\": ", run.max_rss_bytes, true);
+        write_optional_u64(out, "      \"avg_rss_bytes\": ", run.avg_rss_bytes, true);
+        write_optional_u64(
+            out,
+            "      \"thumb_visible_max_rss_bytes\": ",
+            run.thumb_visible_max_rss_bytes,
+            true,
+        );
+        write_optional_u64(
+            out,
+            "      \"thumb_background_max_rss_bytes\": ",
+            run.thumb_background_max_rss_bytes,
+            true,
+        );
 
         out.push_str("      \"gpu\": ");
-        render_gpu_run_json(&mut out, run.gpu.as_ref(), 6);
+        render_gpu_run_json(out, run.gpu.as_ref(), 6);
+        out.push_str(",\n");
+
+        out.push_str("      \"cpu\": ");
+        render_cpu_run_json(out, run.cpu.as_ref(), 6);
+        out.push_str(",\n");
+
+        out.push_str("      \"io\": ");
+        render_io_run_json(out, &run.io, 6);
         out.push('\n');
 
         out.push_str("    }");
-        if idx + 1 < report.results.len() {
+        if idx + 1 < runs.len() {
             out.push(',');
         }
         out.push('\n');
     }
 
     out.push_str("  ],\n");
+}
+
+/// Writes the `aggregate` object, which is always the last key of its parent.
+fn render_aggregate_json(out: &mut String, aggregate: &BenchmarkAggregate) {
     out.push_str("  \"aggregate\": {\n");
-    out.push_str(&format!("    \"runs\": {},\n", report.aggregate.runs));
+    out.push_str(&format!("    \"runs\": {},\n", aggregate.runs));
     out.push_str(&format!(
         "    \"avg_elapsed_ms\": {:.3},\n",
-        report.aggregate.avg_elapsed_ms
+        aggregate.avg_elapsed_ms
     ));
     out.push_str(&format!(
         "    \"min_elapsed_ms\": {},\n",
-        report.aggregate.min_elapsed_ms
+        aggregate.min_elapsed_ms
     ));
     out.push_str(&format!(
         "    \"max_elapsed_ms\": {},\n",
-        report.aggregate.max_elapsed_ms
+        aggregate.max_elapsed_ms
     ));
     out.push_str(&format!(
         "    \"avg_files_per_sec\": {:.3},\n",
-        report.aggregate.avg_files_per_sec
+        aggregate.avg_files_per_sec
     ));
     out.push_str(&format!(
         "    \"avg_layout_p95_ms\": {:.3},\n",
-        report.aggregate.avg_layout_p95_ms
+        aggregate.avg_layout_p95_ms
     ));
     out.push_str(&format!(
         "    \"avg_thumb_p95_ms\": {:.3},\n",
-        report.aggregate.avg_thumb_p95_ms
+        aggregate.avg_thumb_p95_ms
     ));
     out.push_str(&format!(
         "    \"avg_thumb_visible_ms\": {:.3}\n",
-        report.aggregate.avg_thumb_visible_ms
+        aggregate.avg_thumb_visible_ms
     ));
     out.push_str("  }\n");
-    out.push_str("}\n");
+}
+
+fn render_report_csv(report: &BenchmarkReport) -> String {
+    let mut out = CSV_COLUMNS.join(",");
+    out.push('\n');
+
+    for (dir, run) in report
+        .directories
+        .iter()
+        .flat_map(|dir| dir.results.iter().map(move |run| (dir, run)))
+    {
+        let row = [
+            report.generated_at_unix_ms.to_string(),
+            escape_csv(&dir.path),
+            run.run_index.to_string(),
+            run.elapsed_ms.to_string(),
+            run.total_files.to_string(),
+            run.new_items.to_string(),
+            run.cached_items.to_string(),
+            run.error_count.to_string(),
+            format!("{:.3}", run.files_per_sec),
+            run.load_items_ms.to_string(),
+            run.loaded_items.to_string(),
+            run.layout_rows.to_string(),
+            run.layout_total_ms.to_string(),
+            run.layout_frames_simulated.to_string(),
+            format!("{:.3}", run.layout_frame_p50_ms),
+            format!("{:.3}", run.layout_frame_p95_ms),
+            run.layout_frames_over_16ms.to_string(),
+            run.layout_frames_over_33ms.to_string(),
+            run.thumb_images_total.to_string(),
+            run.thumb_images_selected.to_string(),
+            run.thumb_images_visible.to_string(),
+            run.thumb_images_generated.to_string(),
+            run.thumb_images_skipped_cached.to_string(),
+            run.thumb_images_failed.to_string(),
+            run.thumb_total_ms.to_string(),
+            run.thumb_time_to_visible_ms.to_string(),
+            format!("{:.3}", run.thumb_end_to_end_avg_ms),
+            format!("{:.3}", run.thumb_end_to_end_p95_ms),
+            format!("{:.3}", run.thumb_queue_wait_avg_ms),
+            format!("{:.3}", run.thumb_queue_wait_p95_ms),
+            format!("{:.3}", run.thumb_worker_avg_ms),
+            format!("{:.3}", run.thumb_worker_p95_ms),
+            format!("{:.3}", run.thumb_decode_avg_ms),
+            format!("{:.3}", run.thumb_decode_p95_ms),
+            format!("{:.3}", run.thumb_resize_avg_ms),
+            format!("{:.3}", run.thumb_resize_p95_ms),
+            format!("{:.3}", run.thumb_encode_avg_ms),
+            format!("{:.3}", run.thumb_encode_p95_ms),
+            run.thumb_workers.to_string(),
+            escape_csv(&run.thumb_resize_mode),
+            run.thumb_nv_offload_enabled.to_string(),
+            run.thumb_nv_offload_available.to_string(),
+            run.thumb_nv_offload_attempted.to_string(),
+            run.thumb_nv_offload_used.to_string(),
+            report.thumb_size.to_string(),
+            optional_csv(run.max_rss_bytes),
+            optional_csv(run.avg_rss_bytes),
+            optional_csv(run.thumb_visible_max_rss_bytes),
+            optional_csv(run.thumb_background_max_rss_bytes),
+            optional_csv(run.io.read_bytes),
+            optional_csv(run.io.rchar_bytes),
+            run.io
+                .avg_read_bytes_per_sec
+                .map(|v| format!("{:.3}", v))
+                .unwrap_or_default(),
+        ];
+        debug_assert_eq!(row.len(), CSV_COLUMNS.len());
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
     out
 }
 
@@ -1498,7 +2840,139 @@ fn render_gpu_run_json(out: &mut String, gpu: Option<&GpuRunSummary>, indent: us
     }
 }
 
-fn build_aggregate(runs: &[BenchmarkRun]) -> BenchmarkAggregate {
+fn render_cpu_run_json(out: &mut String, cpu: Option<&CpuRunSummary>, indent: usize) {
+    let pad = " ".repeat(indent);
+    let pad2 = " ".repeat(indent + 2);
+    let pad3 = " ".repeat(indent + 4);
+
+    if let Some(cpu) = cpu {
+        out.push_str("{\n");
+        out.push_str(&format!("{pad2}\"sample_count\": {},\n", cpu.sample_count));
+
+        if let Some(err) = &cpu.collection_error {
+            out.push_str(&format!(
+                "{pad2}\"collection_error\": \"{}\",\n",
+                escape_json(err)
+            ));
+        } else {
+            out.push_str(&format!("{pad2}\"collection_error\": null,\n"));
+        }
+
+        write_optional_f64(
+            out,
+            &format!("{pad2}\"avg_busy_percent\": "),
+            cpu.avg_busy_percent,
+            true,
+        );
+        write_optional_f64(
+            out,
+            &format!("{pad2}\"max_busy_percent\": "),
+            cpu.max_busy_percent,
+            true,
+        );
+
+        out.push_str(&format!("{pad2}\"cores\": [\n"));
+        for (idx, core) in cpu.cores.iter().enumerate() {
+            out.push_str(&format!("{pad3}{{\n"));
+            out.push_str(&format!(
+                "{pad3}  \"name\": \"{}\",\n",
+                escape_json(&core.name)
+            ));
+            write_optional_f64(
+                out,
+                &format!("{pad3}  \"avg_busy_percent\": "),
+                core.avg_busy_percent,
+                true,
+            );
+            write_optional_f64(
+                out,
+                &format!("{pad3}  \"max_busy_percent\": "),
+                core.max_busy_percent,
+                false,
+            );
+            out.push_str(&format!("{pad3}}}"));
+            if idx + 1 < cpu.cores.len() {
+                out.push(',');
+            }
+            out.push('\n');
+        }
+        out.push_str(&format!("{pad2}]\n"));
+        out.push_str(&format!("{pad}}}"));
+    } else {
+        out.push_str("null");
+    }
+}
+
+fn render_io_run_json(out: &mut String, io: &IoRunSummary, indent: usize) {
+    let pad = " ".repeat(indent);
+    let pad2 = " ".repeat(indent + 2);
+    let pad3 = " ".repeat(indent + 4);
+
+    out.push_str("{\n");
+    out.push_str(&format!("{pad2}\"sample_count\": {},\n", io.sample_count));
+
+    if let Some(err) = &io.collection_error {
+        out.push_str(&format!(
+            "{pad2}\"collection_error\": \"{}\",\n",
+            escape_json(err)
+        ));
+    } else {
+        out.push_str(&format!("{pad2}\"collection_error\": null,\n"));
+    }
+
+    write_optional_u64(out, &format!("{pad2}\"read_bytes\": "), io.read_bytes, true);
+    write_optional_u64(
+        out,
+        &format!("{pad2}\"rchar_bytes\": "),
+        io.rchar_bytes,
+        true,
+    );
+    write_optional_f64(
+        out,
+        &format!("{pad2}\"avg_read_bytes_per_sec\": "),
+        io.avg_read_bytes_per_sec,
+        true,
+    );
+    write_optional_f64(
+        out,
+        &format!("{pad2}\"max_read_bytes_per_sec\": "),
+        io.max_read_bytes_per_sec,
+        true,
+    );
+
+    out.push_str(&format!("{pad2}\"phases\": [\n"));
+    for (idx, phase) in io.phases.iter().enumerate() {
+        out.push_str(&format!("{pad3}{{\n"));
+        out.push_str(&format!(
+            "{pad3}  \"phase\": \"{}\",\n",
+            escape_json(phase.phase)
+        ));
+        out.push_str(&format!(
+            "{pad3}  \"elapsed_ms\": {:.3},\n",
+            phase.elapsed_ms
+        ));
+        out.push_str(&format!("{pad3}  \"read_bytes\": {},\n", phase.read_bytes));
+        out.push_str(&format!(
+            "{pad3}  \"rchar_bytes\": {},\n",
+            phase.rchar_bytes
+        ));
+        write_optional_f64(
+            out,
+            &format!("{pad3}  \"avg_read_bytes_per_sec\": "),
+            phase.avg_read_bytes_per_sec,
+            false,
+        );
+        out.push_str(&format!("{pad3}}}"));
+        if idx + 1 < io.phases.len() {
+            out.push(',');
+        }
+        out.push('\n');
+    }
+    out.push_str(&format!("{pad2}]\n"));
+    out.push_str(&format!("{pad}}}"));
+}
+
+fn build_aggregate(runs: &[&BenchmarkRun]) -> BenchmarkAggregate {
     let elapsed_values: Vec<u128> = runs.iter().map(|r| r.elapsed_ms).collect();
     let files_per_sec_values: Vec<f64> = runs.iter().map(|r| r.files_per_sec).collect();
     let layout_p95_values: Vec<f64> = runs.iter().map(|r| r.layout_frame_p95_ms).collect();
@@ -1620,6 +3094,31 @@ fn percentile_ms(values: &[f64], p: f64) -> f64 {
     sorted[idx]
 }
 
+/// Counts values into `THUMB_LATENCY_BUCKETS_MS` buckets (one extra for overflow).
+fn latency_histogram(values: &[f64]) -> Vec<usize> {
+    let mut counts = vec![0; THUMB_LATENCY_BUCKETS_MS.len() + 1];
+    for value in values {
+        let bucket = THUMB_LATENCY_BUCKETS_MS
+            .iter()
+            .position(|edge| *value < *edge)
+            .unwrap_or(THUMB_LATENCY_BUCKETS_MS.len());
+        counts[bucket] += 1;
+    }
+    counts
+}
+
+fn latency_bucket_label(bucket: usize) -> String {
+    let lower = if bucket == 0 {
+        0.0
+    } else {
+        THUMB_LATENCY_BUCKETS_MS[bucket - 1]
+    };
+    match THUMB_LATENCY_BUCKETS_MS.get(bucket) {
+        Some(upper) => format!("{}-{}ms", lower, upper),
+        None => format!("{}+ms", lower),
+    }
+}
+
 fn write_optional_f64(out: &mut String, prefix: &str, value: Option<f64>, trailing_comma: bool) {
     out.push_str(prefix);
     if let Some(v) = value {
@@ -1646,6 +3145,18 @@ fn write_optional_u64(out: &mut String, prefix: &str, value: Option<u64>, traili
     out.push('\n');
 }
 
+fn optional_csv(value: Option<u64>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+fn escape_csv(input: &str) -> String {
+    if input.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", input.replace('"', "\"\""))
+    } else {
+        input.to_string()
+    }
+}
+
 fn escape_json(input: &str) -> String {
     let mut escaped = String::with_capacity(input.len());
     for ch in input.chars() {
@@ -1661,3 +3172,304 @@ fn escape_json(input: &str) -> String {
     }
     escaped
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latency_histogram_counts_every_value() {
+        let values = [0.0, 4.9, 5.0, 12.0, 99.9, 250.0, 499.0, 500.0, 10_000.0];
+        let counts = latency_histogram(&values);
+        assert_eq!(counts.len(), THUMB_LATENCY_BUCKETS_MS.len() + 1);
+        assert_eq!(counts.iter().sum::<usize>(), values.len());
+        assert_eq!(counts, vec![2, 1, 1, 0, 1, 0, 2, 2]);
+    }
+
+    #[test]
+    fn latency_bucket_labels() {
+        assert_eq!(latency_bucket_label(0), "0-5ms");
+        assert_eq!(latency_bucket_label(3), "25-50ms");
+        assert_eq!(
+            latency_bucket_label(THUMB_LATENCY_BUCKETS_MS.len()),
+            "500+ms"
+        );
+    }
+
+    #[test]
+    fn comparison_flags_metrics_that_got_worse() {
+        let baseline = BaselineAggregate {
+            avg_elapsed_ms: 100.0,
+            avg_files_per_sec: 1000.0,
+            avg_thumb_p95_ms: 20.0,
+            avg_layout_p95_ms: 4.0,
+        };
+        let current = BenchmarkAggregate {
+            runs: 1,
+            avg_elapsed_ms: 120.0,
+            min_elapsed_ms: 120,
+            max_elapsed_ms: 120,
+            avg_files_per_sec: 850.0,
+            avg_layout_p95_ms: 4.2,
+            avg_thumb_p95_ms: 10.0,
+            avg_thumb_visible_ms: 0.0,
+        };
+        let deltas = compare_aggregates(&baseline, &current);
+        let regressed: Vec<&str> = deltas
+            .iter()
+            .filter(|delta| delta.regressed(10.0))
+            .map(|delta| delta.name)
+            .collect();
+        assert_eq!(regressed, vec!["avg_elapsed_ms", "avg_files_per_sec"]);
+        assert_eq!(deltas[2].worse_pct, -50.0);
+    }
+
+    #[test]
+    fn baseline_reads_the_report_aggregate() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline.json");
+        fs::write(
+            &path,
+            r#"{"runs": [{"run_index": 1}], "aggregate": {"runs": 1,
+                "avg_elapsed_ms": 1200, "avg_files_per_sec": 350.5,
+                "avg_thumb_p95_ms": 18.25, "avg_layout_p95_ms": 2e0}}"#,
+        )
+        .unwrap();
+        let baseline = load_baseline_aggregate(&path).unwrap();
+        assert_eq!(baseline.avg_elapsed_ms, 1200.0);
+        assert_eq!(baseline.avg_files_per_sec, 350.5);
+        assert_eq!(baseline.avg_thumb_p95_ms, 18.25);
+        assert_eq!(baseline.avg_layout_p95_ms, 2.0);
+
+        fs::write(&path, r#"{"aggregate": {"avg_elapsed_ms": 1}}"#).unwrap();
+        assert!(load_baseline_aggregate(&path).is_err());
+    }
+
+    #[test]
+    fn csv_report_has_one_row_per_run() {
+        let aggregate = || BenchmarkAggregate {
+            runs: 1,
+            avg_elapsed_ms: 1500.0,
+            min_elapsed_ms: 1500,
+            max_elapsed_ms: 1500,
+            avg_files_per_sec: 6.667,
+            avg_layout_p95_ms: 1.25,
+            avg_thumb_p95_ms: 75.5,
+            avg_thumb_visible_ms: 300.0,
+        };
+        let run = BenchmarkRun {
+            run_index: 1,
+            elapsed_ms: 1500,
+            total_files: 10,
+            new_items: 8,
+            cached_items: 2,
+            error_count: 0,
+            files_per_sec: 20.0 / 3.0,
+            load_items_ms: 12,
+            loaded_items: 10,
+            layout_rows: 3,
+            layout_total_ms: 4,
+            layout_frames_simulated: 20,
+            layout_frame_p50_ms: 0.5,
+            layout_frame_p95_ms: 1.25,
+            layout_frames_over_16ms: 0,
+            layout_frames_over_33ms: 0,
+            thumb_images_total: 9,
+            thumb_images_selected: 9,
+            thumb_images_visible: 4,
+            thumb_images_generated: 7,
+            thumb_images_skipped_cached: 2,
+            thumb_images_failed: 0,
+            thumb_total_ms: 800,
+            thumb_time_to_visible_ms: 300,
+            thumb_end_to_end_avg_ms: 40.0,
+            thumb_end_to_end_p95_ms: 75.5,
+            thumb_latency_histogram: vec![0; THUMB_LATENCY_BUCKETS_MS.len() + 1],
+            thumb_queue_wait_avg_ms: 1.0,
+            thumb_queue_wait_p95_ms: 2.0,
+            thumb_worker_avg_ms: 30.0,
+            thumb_worker_p95_ms: 60.0,
+            thumb_decode_avg_ms: 10.0,
+            thumb_decode_p95_ms: 20.0,
+            thumb_resize_avg_ms: 5.0,
+            thumb_resize_p95_ms: 8.0,
+            thumb_encode_avg_ms: 3.0,
+            thumb_encode_p95_ms: 4.0,
+            thumb_workers: 4,
+            thumb_resize_mode: "quality".to_string(),
+            thumb_nv_offload_enabled: false,
+            thumb_nv_offload_available: false,
+            thumb_nv_offload_attempted: 0,
+            thumb_nv_offload_used: 0,
+            max_rss_bytes: Some(1_048_576),
+            avg_rss_bytes: None,
+            thumb_visible_max_rss_bytes: Some(2048),
+            thumb_background_max_rss_bytes: None,
+            gpu: None,
+            cpu: None,
+            io: IoRunSummary {
+                sample_count: 2,
+                read_bytes: Some(4096),
+                rchar_bytes: None,
+                avg_read_bytes_per_sec: Some(1024.5),
+                max_read_bytes_per_sec: None,
+                phases: Vec::new(),
+                collection_error: None,
+            },
+        };
+        let report = BenchmarkReport {
+            schema_version: 1,
+            generated_at_unix_ms: 1_700_000_000_000,
+            benchmark: "scan".to_string(),
+            runs_requested: 1,
+            warmup_runs: 0,
+            cold_cache: false,
+            thumb_limit: 0,
+            thumb_timeout_ms: 0,
+            thumb_workers: 4,
+            thumb_visible_count: 4,
+            thumb_size: 256,
+            extensions: Vec::new(),
+            thumb_fast_resize: false,
+            thumb_nv_offload: false,
+            gpu_telemetry_enabled: false,
+            gpu_sample_ms: 250,
+            db_path: "/tmp/bench.sqlite".to_string(),
+            thumbs_dir: "/tmp/thumbs".to_string(),
+            directories: vec![DirectoryResult {
+                path: "/media/a,b".to_string(),
+                results: vec![run],
+                aggregate: aggregate(),
+            }],
+            aggregate: aggregate(),
+        };
+
+        let csv = render_report_csv(&report);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], CSV_COLUMNS.join(","));
+        assert_eq!(
+            lines[1],
+            "1700000000000,\"/media/a,b\",1,1500,10,8,2,0,6.667,12,10,3,4,20,0.500,1.250,0,0,\
+             9,9,4,7,2,0,800,300,40.000,75.500,1.000,2.000,30.000,60.000,10.000,20.000,\
+             5.000,8.000,3.000,4.000,4,quality,false,false,0,0,256,1048576,,2048,,4096,,1024.500"
+        );
+    }
+
+    #[test]
+    fn parses_proc_stat_cpu_lines() {
+        let raw = "cpu  100 0 50 800 50 0 0 0 7 0\n\
+                   cpu0 60 0 20 400 20 0 0 0\n\
+                   cpu1 40 0 30 400 30 0 0 0\n\
+                   cpu2 1 2\n\
+                   intr 12345 1 2\n\
+                   ctxt 99\n";
+        let cores: Vec<(String, u64, u64)> = parse_proc_stat(raw)
+            .into_iter()
+            .map(|(name, times)| (name, times.busy, times.total))
+            .collect();
+        // Guest time (9th column) is left out; idle includes iowait.
+        assert_eq!(
+            cores,
+            vec![
+                ("cpu".to_string(), 150, 1000),
+                ("cpu0".to_string(), 80, 500),
+                ("cpu1".to_string(), 70, 500),
+            ]
+        );
+    }
+
+    #[test]
+    fn cpu_busy_percent_is_averaged_between_snapshots() {
+        let sample = |cores: &[(&str, u64, u64)]| CpuSample {
+            cores: cores
+                .iter()
+                .map(|&(name, busy, total)| (name.to_string(), CpuTimes { busy, total }))
+                .collect(),
+        };
+        let samples = [
+            sample(&[("cpu", 0, 0), ("cpu0", 0, 0), ("cpu10", 0, 0)]),
+            sample(&[
+                ("cpu", 50, 100),
+                ("cpu0", 25, 50),
+                ("cpu10", 25, 50),
+                ("cpu2", 0, 10),
+            ]),
+            sample(&[
+                ("cpu", 50, 200),
+                ("cpu0", 75, 100),
+                ("cpu10", 25, 100),
+                ("cpu2", 10, 20),
+            ]),
+        ];
+        let summary = summarize_cpu_samples(&samples, None);
+
+        assert_eq!(summary.sample_count, 3);
+        assert_eq!(summary.avg_busy_percent, Some(25.0));
+        assert_eq!(summary.max_busy_percent, Some(50.0));
+        let cores: Vec<(&str, Option<f64>, Option<f64>)> = summary
+            .cores
+            .iter()
+            .map(|core| {
+                (
+                    core.name.as_str(),
+                    core.avg_busy_percent,
+                    core.max_busy_percent,
+                )
+            })
+            .collect();
+        // Cores sort numerically; cpu2 only has one interval.
+        assert_eq!(
+            cores,
+            vec![
+                ("cpu0", Some(75.0), Some(100.0)),
+                ("cpu2", Some(100.0), Some(100.0)),
+                ("cpu10", Some(25.0), Some(50.0)),
+            ]
+        );
+
+        let single = summarize_cpu_samples(&samples[..1], Some("no /proc/stat".into()));
+        assert_eq!(single.avg_busy_percent, None);
+        assert!(single.cores.is_empty());
+        assert_eq!(single.collection_error.as_deref(), Some("no /proc/stat"));
+    }
+
+    #[test]
+    fn parses_proc_self_io() {
+        let raw = "rchar: 4096\nwchar: 12\nsyscr: 3\nsyscw: 1\n\
+                   read_bytes: 1024\nwrite_bytes: 0\ncancelled_write_bytes: 0\n";
+        assert_eq!(parse_proc_io(raw), Some((4096, 1024)));
+        assert_eq!(parse_proc_io("rchar: 1\n"), None);
+    }
+
+    #[test]
+    fn io_reads_are_split_at_phase_marks() {
+        let t0 = Instant::now();
+        let sample = |secs: u64, rchar: u64, read_bytes: u64| IoSample {
+            at: t0 + Duration::from_secs(secs),
+            rchar,
+            read_bytes,
+        };
+        let marks = [
+            ("scan", Some(sample(0, 100, 0))),
+            ("layout", Some(sample(2, 900, 4000))),
+            ("thumbnails", None),
+        ];
+        let periodic = [
+            sample(0, 100, 0),
+            sample(1, 500, 3000),
+            sample(2, 900, 4000),
+        ];
+        let io = summarize_io_samples(&periodic, &marks, Some(sample(4, 1900, 6000)), None);
+
+        assert_eq!(io.read_bytes, Some(6000));
+        assert_eq!(io.rchar_bytes, Some(1800));
+        assert_eq!(io.avg_read_bytes_per_sec, Some(1500.0));
+        assert_eq!(io.max_read_bytes_per_sec, Some(3000.0));
+        // The failed thumbnails reading drops both phases it bounds.
+        assert_eq!(io.phases.len(), 1);
+        assert_eq!(io.phases[0].phase, "scan");
+        assert_eq!(io.phases[0].read_bytes, 4000);
+        assert_eq!(io.phases[0].avg_read_bytes_per_sec, Some(2000.0));
+    }
+}
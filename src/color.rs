@@ -0,0 +1,174 @@
+//! Optional ICC color management.
+//!
+//! With `IDXD_COLOR_MANAGEMENT=1`, images that embed an ICC profile are
+//! converted to the display profile while decoding, so wide-gamut (e.g.
+//! Display-P3) photos are not shown oversaturated. The display profile is read
+//! from `IDXD_DISPLAY_ICC` and falls back to sRGB. Untagged images are assumed
+//! to be sRGB and left untouched. Off by default because the conversion adds
+//! decode cost.
+
+use std::io::Cursor;
+use std::sync::OnceLock;
+
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader};
+use moxcms::{ColorProfile, DataColorSpace, Layout, TransformOptions};
+use tracing::warn;
+use xxhash_rust::xxh3::xxh3_64;
+
+/// Target profile plus a hash identifying it in thumbnail cache keys.
+struct DisplayProfile {
+    profile: ColorProfile,
+    id: u64,
+}
+
+/// Color management is on when `IDXD_COLOR_MANAGEMENT` is `1`.
+pub fn color_management_enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| {
+        std::env::var("IDXD_COLOR_MANAGEMENT")
+            .map(|v| v.trim() == "1")
+            .unwrap_or(false)
+    })
+}
+
+fn display_profile() -> &'static DisplayProfile {
+    static PROFILE: OnceLock<DisplayProfile> = OnceLock::new();
+    PROFILE.get_or_init(|| {
+        if let Some(path) = std::env::var_os("IDXD_DISPLAY_ICC") {
+            match std::fs::read(&path).map(|icc| (ColorProfile::new_from_slice(&icc), icc)) {
+                Ok((Ok(profile), icc)) => {
+                    return DisplayProfile {
+                        profile,
+                        id: xxh3_64(&icc),
+                    }
+                }
+                Ok((Err(err), _)) => warn!("Ignoring display profile {:?}: {}", path, err),
+                Err(err) => warn!("Failed to read display profile {:?}: {}", path, err),
+            }
+        }
+        DisplayProfile {
+            profile: ColorProfile::new_srgb(),
+            id: 0,
+        }
+    })
+}
+
+/// Identifies the conversion target, so thumbnails made with color management
+/// are cached apart from unconverted ones. None when management is off.
+pub fn cache_tag() -> Option<u64> {
+    color_management_enabled().then(|| display_profile().id)
+}
+
+/// Reads the ICC profile embedded in encoded image bytes.
+pub fn read_icc_profile(bytes: &[u8], format: ImageFormat) -> Option<Vec<u8>> {
+    let mut decoder = ImageReader::with_format(Cursor::new(bytes), format)
+        .into_decoder()
+        .ok()?;
+    decoder.icc_profile().ok().flatten()
+}
+
+/// Converts `img` to the display profile if color management is on and the
+/// encoded `bytes` carry an RGB ICC profile; otherwise returns it unchanged.
+pub fn manage(img: DynamicImage, bytes: &[u8], format: ImageFormat) -> DynamicImage {
+    if !color_management_enabled() {
+        return img;
+    }
+    match read_icc_profile(bytes, format) {
+        Some(icc) => convert(img, &icc, &display_profile().profile),
+        None => img,
+    }
+}
+
+/// Converts RGB(A) pixels from the `icc` profile to `target`. Images with
+/// more than 8 bits per channel are converted at 16 bits and stay 16-bit.
+/// Grayscale and CMYK profiles, and profiles that fail to parse, leave the
+/// image as is.
+fn convert(img: DynamicImage, icc: &[u8], target: &ColorProfile) -> DynamicImage {
+    let source = match ColorProfile::new_from_slice(icc) {
+        Ok(profile) if profile.color_space == DataColorSpace::Rgb => profile,
+        Ok(_) => return img,
+        Err(err) => {
+            warn!("Ignoring embedded ICC profile: {}", err);
+            return img;
+        }
+    };
+
+    let color = img.color();
+    let has_alpha = color.has_alpha();
+    let deep = color.bytes_per_pixel() > color.channel_count();
+    let layout = if has_alpha { Layout::Rgba } else { Layout::Rgb };
+    let options = TransformOptions::default();
+    let converted = if deep {
+        source
+            .create_transform_16bit(layout, target, layout, options)
+            .map(|transform| {
+                if has_alpha {
+                    let src = img.to_rgba16();
+                    let mut dst = src.clone();
+                    let converted = transform.transform(&src, &mut dst);
+                    converted.ok().map(|()| DynamicImage::ImageRgba16(dst))
+                } else {
+                    let src = img.to_rgb16();
+                    let mut dst = src.clone();
+                    let converted = transform.transform(&src, &mut dst);
+                    converted.ok().map(|()| DynamicImage::ImageRgb16(dst))
+                }
+            })
+    } else {
+        source
+            .create_transform_8bit(layout, target, layout, options)
+            .map(|transform| {
+                if has_alpha {
+                    let src = img.to_rgba8();
+                    let mut dst = src.clone();
+                    let converted = transform.transform(&src, &mut dst);
+                    converted.ok().map(|()| DynamicImage::ImageRgba8(dst))
+                } else {
+                    let src = img.to_rgb8();
+                    let mut dst = src.clone();
+                    let converted = transform.transform(&src, &mut dst);
+                    converted.ok().map(|()| DynamicImage::ImageRgb8(dst))
+                }
+            })
+    };
+
+    match converted {
+        Ok(Some(converted)) => converted,
+        Ok(None) => img,
+        Err(err) => {
+            warn!("Cannot convert embedded ICC profile: {}", err);
+            img
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_tagged_pixels_and_keeps_srgb() {
+        let p3 = ColorProfile::new_display_p3().encode().unwrap();
+        let srgb = ColorProfile::new_srgb().encode().unwrap();
+        let green =
+            DynamicImage::ImageRgb8(image::RgbImage::from_pixel(1, 1, image::Rgb([40, 160, 90])));
+        let same = convert(green.clone(), &srgb, &ColorProfile::new_srgb()).to_rgb8();
+        let diff = same
+            .get_pixel(0, 0)
+            .0
+            .iter()
+            .zip(green.to_rgb8().get_pixel(0, 0).0)
+            .map(|(a, b)| a.abs_diff(b))
+            .max()
+            .unwrap();
+        assert!(diff <= 1);
+
+        let p3_green = convert(green.clone(), &p3, &ColorProfile::new_srgb()).to_rgb8();
+        assert_ne!(p3_green.get_pixel(0, 0), green.to_rgb8().get_pixel(0, 0));
+
+        let deep_green = DynamicImage::ImageRgb16(green.to_rgb16());
+        let converted = convert(deep_green.clone(), &p3, &ColorProfile::new_srgb());
+        assert!(matches!(converted, DynamicImage::ImageRgb16(_)));
+        assert_ne!(converted.to_rgb16(), deep_green.to_rgb16());
+    }
+}
@@ -0,0 +1,125 @@
+//! Session bus interface for scripting a running instance.
+//!
+//! The application already owns `lt.gtw.idxd` on the session bus; this adds a
+//! `lt.gtw.idxd.Control` object next to it so window-manager keybindings can
+//! drive the newest window, e.g.
+//! `gdbus call --session --dest lt.gtw.idxd --object-path /lt/gtw/idxd
+//! --method lt.gtw.idxd.Control.Next`. Calls arrive on the GTK main loop, so
+//! handlers talk to the window directly.
+
+use std::path::PathBuf;
+
+use gtk4::gio;
+
+use crate::app;
+
+pub const OBJECT_PATH: &str = "/lt/gtw/idxd";
+pub const INTERFACE_NAME: &str = "lt.gtw.idxd.Control";
+
+const INTERFACE_XML: &str = r#"
+<node>
+  <interface name="lt.gtw.idxd.Control">
+    <method name="OpenDirectory">
+      <arg type="s" name="path" direction="in"/>
+    </method>
+    <method name="OpenFile">
+      <arg type="s" name="path" direction="in"/>
+    </method>
+    <method name="Next">
+      <arg type="b" name="moved" direction="out"/>
+    </method>
+    <method name="Previous">
+      <arg type="b" name="moved" direction="out"/>
+    </method>
+    <method name="ToggleFullscreen"/>
+  </interface>
+</node>
+"#;
+
+const ERROR_INVALID_ARGS: &str = "org.freedesktop.DBus.Error.InvalidArgs";
+const ERROR_UNKNOWN_METHOD: &str = "org.freedesktop.DBus.Error.UnknownMethod";
+const ERROR_NO_WINDOW: &str = "lt.gtw.idxd.Error.NoWindow";
+
+fn interface_info() -> Result<gio::DBusInterfaceInfo, glib::Error> {
+    gio::DBusNodeInfo::for_xml(INTERFACE_XML)?
+        .lookup_interface(INTERFACE_NAME)
+        .ok_or_else(|| {
+            glib::Error::new(
+                gio::IOErrorEnum::NotFound,
+                "Control interface missing from introspection data",
+            )
+        })
+}
+
+/// Exports the control object on `connection`. It stays registered for the
+/// life of the connection.
+pub fn register(connection: &gio::DBusConnection) -> Result<gio::RegistrationId, glib::Error> {
+    let info = interface_info()?;
+    connection
+        .register_object(OBJECT_PATH, &info)
+        .method_call(
+            |_, _sender, _path, _interface, method, params, invocation| {
+                handle_method_call(method, params, invocation)
+            },
+        )
+        .build()
+}
+
+fn handle_method_call(method: &str, params: glib::Variant, invocation: gio::DBusMethodInvocation) {
+    let Some(window) = app::active_window() else {
+        invocation.return_dbus_error(ERROR_NO_WINDOW, "No idxd window is open");
+        return;
+    };
+    match method {
+        "OpenDirectory" | "OpenFile" => {
+            let Some((path,)) = params.get::<(String,)>() else {
+                invocation.return_dbus_error(ERROR_INVALID_ARGS, "Expected a path string");
+                return;
+            };
+            let path = PathBuf::from(path);
+            let (valid, kind) = if method == "OpenDirectory" {
+                (path.is_dir(), "directory")
+            } else {
+                (path.is_file(), "file")
+            };
+            if !valid {
+                let message = format!("No such {}: {}", kind, path.display());
+                invocation.return_dbus_error(ERROR_INVALID_ARGS, &message);
+                return;
+            }
+            tracing::info!("DBus {} {}", method, path.display());
+            window.open_path(&path);
+            window.present();
+            invocation.return_value(None);
+        }
+        "Next" | "Previous" => {
+            let step = if method == "Next" { 1 } else { -1 };
+            let moved = window.navigate_viewer(step);
+            invocation.return_value(Some(&(moved,).into()));
+        }
+        "ToggleFullscreen" => {
+            window.toggle_fullscreen();
+            invocation.return_value(None);
+        }
+        _ => invocation.return_dbus_error(ERROR_UNKNOWN_METHOD, method),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn introspection_data_describes_every_method() {
+        let info = interface_info().unwrap();
+        for method in [
+            "OpenDirectory",
+            "OpenFile",
+            "Next",
+            "Previous",
+            "ToggleFullscreen",
+        ] {
+            assert!(info.lookup_method(method).is_some(), "{method}");
+        }
+    }
+}
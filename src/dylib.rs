@@ -0,0 +1,31 @@
+//! Runtime loading of optional native libraries.
+//!
+//! Codecs and GPU offload are resolved with `dlopen` so the binary has no
+//! link-time dependency on them; a missing library just disables the feature.
+
+use std::ffi::{c_void, CString};
+use std::os::raw::c_char;
+
+use anyhow::{bail, Result};
+
+/// Opens the first library in `names` that loads.
+pub fn open_library(names: &[&str]) -> Result<*mut c_void> {
+    for name in names {
+        let c_name = CString::new(*name)?;
+        let handle = unsafe { libc::dlopen(c_name.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+        if !handle.is_null() {
+            return Ok(handle);
+        }
+    }
+    bail!("None of {:?} could be loaded", names)
+}
+
+/// Looks up `name` in a handle returned by [`open_library`].
+pub fn symbol(handle: *mut c_void, name: &str) -> Result<*mut c_void> {
+    let c_name = CString::new(name)?;
+    let sym = unsafe { libc::dlsym(handle, c_name.as_ptr() as *const c_char) };
+    if sym.is_null() {
+        bail!("Missing symbol {}", name);
+    }
+    Ok(sym)
+}
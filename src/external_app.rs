@@ -0,0 +1,180 @@
+//! Hands the current file to another program.
+//!
+//! `IDXD_EXTERNAL_EDITOR` names the command used for images and
+//! `IDXD_EXTERNAL_PLAYER` the one used for videos (falling back to the editor).
+//! A `{}` argument is replaced by the file path; without one the path is
+//! appended. Arguments are split on whitespace, so e.g.
+//! `IDXD_EXTERNAL_PLAYER="mpv --loop"` works but quoting does not. With
+//! nothing configured files go to `xdg-open`.
+//!
+//! GPS positions always open through `xdg-open` as an OpenStreetMap link, so
+//! they land in the desktop's browser or maps app.
+//!
+//! Revealing a file asks the desktop's file manager to show it selected over
+//! `org.freedesktop.FileManager1`, falling back to `xdg-open` on its directory.
+
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context, Result};
+use gio::prelude::*;
+
+pub const EDITOR_ENV: &str = "IDXD_EXTERNAL_EDITOR";
+pub const PLAYER_ENV: &str = "IDXD_EXTERNAL_PLAYER";
+const DEFAULT_COMMAND: &str = "xdg-open";
+const PATH_PLACEHOLDER: &str = "{}";
+/// Zoom level of map links, close enough to make out streets.
+const MAP_ZOOM: u32 = 16;
+const FILE_MANAGER_NAME: &str = "org.freedesktop.FileManager1";
+const FILE_MANAGER_PATH: &str = "/org/freedesktop/FileManager1";
+/// How long a file manager may take to start before the directory is
+/// opened instead.
+const FILE_MANAGER_TIMEOUT_MS: i32 = 5000;
+
+/// The configured command template for a file of this kind.
+fn configured_command(is_video: bool) -> String {
+    let env = |name| {
+        std::env::var(name)
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+    };
+    is_video
+        .then(|| env(PLAYER_ENV))
+        .flatten()
+        .or_else(|| env(EDITOR_ENV))
+        .unwrap_or_else(|| DEFAULT_COMMAND.to_string())
+}
+
+/// Splits `template` into program and arguments with `path` substituted.
+fn command_line(template: &str, path: &Path) -> Result<Vec<String>> {
+    let path = path.to_string_lossy();
+    let mut args: Vec<String> = template
+        .split_whitespace()
+        .map(|arg| arg.replace(PATH_PLACEHOLDER, &path))
+        .collect();
+    if args.is_empty() {
+        bail!("No external command configured");
+    }
+    if !template.contains(PATH_PLACEHOLDER) {
+        args.push(path.into_owned());
+    }
+    Ok(args)
+}
+
+/// Starts the configured program on `path` without waiting for it and
+/// returns the program name. Fails only if the program could not be started.
+pub fn open_external(path: &Path, is_video: bool) -> Result<String> {
+    let args = command_line(&configured_command(is_video), path)?;
+    spawn_detached(&args)
+}
+
+/// OpenStreetMap link with a marker at the given position.
+pub fn map_url(lat: f64, lon: f64) -> String {
+    format!(
+        "https://www.openstreetmap.org/?mlat={lat:.6}&mlon={lon:.6}#map={zoom}/{lat:.6}/{lon:.6}",
+        zoom = MAP_ZOOM
+    )
+}
+
+/// Opens `map_url` for the position with `xdg-open`.
+pub fn open_map(lat: f64, lon: f64) -> Result<String> {
+    spawn_detached(&[DEFAULT_COMMAND.to_string(), map_url(lat, lon)])
+}
+
+/// Shows `path` selected in the desktop's file manager, or opens its
+/// directory with `xdg-open` when no file manager answers on the session bus.
+/// `done` runs on the main loop with what handled the request.
+pub fn reveal_in_file_manager(path: &Path, done: impl FnOnce(Result<String>) + 'static) {
+    let uri = gio::File::for_path(path).uri().to_string();
+    let directory = path.parent().unwrap_or(path).to_string_lossy().into_owned();
+    let open_directory = move || spawn_detached(&[DEFAULT_COMMAND.to_string(), directory]);
+    let connection = match gio::bus_get_sync(gio::BusType::Session, gio::Cancellable::NONE) {
+        Ok(connection) => connection,
+        Err(err) => {
+            tracing::debug!("No session bus, opening the directory instead: {}", err);
+            done(open_directory());
+            return;
+        }
+    };
+    connection.call(
+        Some(FILE_MANAGER_NAME),
+        FILE_MANAGER_PATH,
+        FILE_MANAGER_NAME,
+        "ShowItems",
+        Some(&(vec![uri], "").to_variant()),
+        None,
+        gio::DBusCallFlags::NONE,
+        FILE_MANAGER_TIMEOUT_MS,
+        gio::Cancellable::NONE,
+        move |result| match result {
+            Ok(_) => done(Ok("the file manager".to_string())),
+            Err(err) => {
+                tracing::debug!(
+                    "{} failed, opening the directory instead: {}",
+                    FILE_MANAGER_NAME,
+                    err
+                );
+                done(open_directory());
+            }
+        },
+    );
+}
+
+/// Starts `args[0]` with the remaining arguments, through `flatpak-spawn`
+/// inside a sandbox, and returns the program name.
+fn spawn_detached(args: &[String]) -> Result<String> {
+    let program = args[0].clone();
+    let mut command = if std::env::var_os("FLATPAK_ID").is_some() {
+        let mut command = Command::new("flatpak-spawn");
+        command.arg("--host").args(args);
+        command
+    } else {
+        let mut command = Command::new(&program);
+        command.args(&args[1..]);
+        command
+    };
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // Own process group, so closing idxd or Ctrl+C in its terminal leaves
+    // the editor running
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    let mut child = command
+        .spawn()
+        .with_context(|| format!("Failed to start {}", program))?;
+    // Reap it when it exits so it does not linger as a zombie
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_line_substitutes_or_appends_the_path() {
+        let path = Path::new("/photos/a b.jpg");
+        assert_eq!(
+            command_line("gimp", path).unwrap(),
+            vec!["gimp", "/photos/a b.jpg"]
+        );
+        assert_eq!(
+            command_line("mpv --loop {} --mute", path).unwrap(),
+            vec!["mpv", "--loop", "/photos/a b.jpg", "--mute"]
+        );
+        assert!(command_line("  ", path).is_err());
+    }
+
+    #[test]
+    fn map_url_marks_the_position() {
+        assert_eq!(
+            map_url(-33.8568, 151.2153),
+            "https://www.openstreetmap.org/?mlat=-33.856800&mlon=151.215300\
+             #map=16/-33.856800/151.215300"
+        );
+    }
+}
@@ -0,0 +1,500 @@
+//! File operations that change what is on disk.
+//!
+//! Deletes go to the XDG trash so they can be undone within the session; the
+//! window only unlinks directly after the user confirms that trashing failed.
+//! Files that are rewritten in place go through a temporary sibling and a
+//! rename, so a failed write never leaves a truncated original behind.
+//! Copies and moves into another folder never overwrite: a taken name gets a
+//! numeric suffix instead.
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::HashSet;
+use std::fs::{File, Permissions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Moves files to the trash.
+pub fn move_to_trash(paths: &[PathBuf]) -> Result<()> {
+    trash::delete_all(paths).map_err(|err| anyhow!("Failed to move to trash: {}", err))
+}
+
+/// Restores the most recently trashed copy of each path and returns the paths
+/// that came back. Paths with no matching trash entry are skipped.
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn restore_from_trash(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let items =
+        trash::os_limited::list().map_err(|err| anyhow!("Failed to read trash: {}", err))?;
+    let matches = latest_trash_entries(items, paths);
+    let restored = matches.iter().map(|item| item.original_path()).collect();
+    if !matches.is_empty() {
+        trash::os_limited::restore_all(matches)
+            .map_err(|err| anyhow!("Failed to restore from trash: {}", err))?;
+    }
+    Ok(restored)
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+pub fn restore_from_trash(_paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    anyhow::bail!("Restoring from trash is not supported on this platform")
+}
+
+/// Whether [`transfer_files`] leaves the originals in place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferMode {
+    Copy,
+    Move,
+}
+
+/// Copies or moves each file into `dest_dir`, returning where every file
+/// ended up or why it could not go there. Destination names are claimed
+/// atomically, so a file that appears there meanwhile is never overwritten.
+/// Moves across filesystems fall back to copy and delete.
+pub fn transfer_files(
+    paths: &[PathBuf],
+    dest_dir: &Path,
+    mode: TransferMode,
+) -> Vec<(PathBuf, Result<PathBuf>)> {
+    paths
+        .iter()
+        .map(|path| (path.clone(), transfer_file(path, dest_dir, mode)))
+        .collect()
+}
+
+fn transfer_file(path: &Path, dest_dir: &Path, mode: TransferMode) -> Result<PathBuf> {
+    if !dest_dir.is_dir() {
+        bail!("Not a directory: {}", dest_dir.display());
+    }
+    let name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("No file name: {}", path.display()))?;
+    if mode == TransferMode::Move && path.parent() == Some(dest_dir) {
+        bail!("{} is already there", Path::new(name).display());
+    }
+    let name = Path::new(name);
+    let copy = |dest: &Path| copy_to_new(path, dest);
+    if mode == TransferMode::Copy {
+        return claim_destination(dest_dir, name, copy)
+            .with_context(|| format!("Failed to copy {}", path.display()));
+    }
+
+    // A hard link claims the name and fails instead of replacing a file;
+    // unlinking the source then completes the move.
+    let dest = match claim_destination(dest_dir, name, |dest| std::fs::hard_link(path, dest)) {
+        Ok(dest) => dest,
+        Err(err)
+            if matches!(
+                err.raw_os_error(),
+                Some(libc::EXDEV | libc::EPERM | libc::EOPNOTSUPP)
+            ) =>
+        {
+            claim_destination(dest_dir, name, copy)
+                .with_context(|| format!("Failed to copy {}", path.display()))?
+        }
+        Err(err) => return Err(err).with_context(|| format!("Failed to move {}", path.display())),
+    };
+    if let Err(err) = std::fs::remove_file(path) {
+        let _ = std::fs::remove_file(&dest);
+        return Err(err).with_context(|| format!("Failed to remove {}", path.display()));
+    }
+    Ok(dest)
+}
+
+/// Copies `src` to `dest`, failing with `AlreadyExists` rather than
+/// overwriting. Permissions are copied like `std::fs::copy` does.
+fn copy_to_new(src: &Path, dest: &Path) -> std::io::Result<()> {
+    let mut source = File::open(src)?;
+    let mut target = File::options().write(true).create_new(true).open(dest)?;
+    let copied = std::io::copy(&mut source, &mut target)
+        .and_then(|_| target.set_permissions(source.metadata()?.permissions()));
+    if copied.is_err() {
+        let _ = std::fs::remove_file(dest);
+    }
+    copied
+}
+
+/// Renames `path` within its folder to `input`, keeping the original
+/// extension when `input` has none. Refuses empty names, path separators and
+/// names that are already taken. Returns the new path.
+pub fn rename_file(path: &Path, input: &str) -> Result<PathBuf> {
+    let new_path = rename_target(path, input)?;
+    if new_path != path {
+        std::fs::rename(path, &new_path)
+            .with_context(|| format!("Failed to rename {}", path.display()))?;
+    }
+    Ok(new_path)
+}
+
+fn rename_target(path: &Path, input: &str) -> Result<PathBuf> {
+    let input = input.trim();
+    if input.is_empty() || input == "." || input == ".." {
+        bail!("Enter a file name");
+    }
+    if input.contains('/') || input.contains(std::path::MAIN_SEPARATOR) {
+        bail!("A file name cannot contain '/'");
+    }
+    let mut name = PathBuf::from(input);
+    if name.extension().is_none() {
+        if let Some(ext) = path.extension() {
+            name.set_extension(ext);
+        }
+    }
+    let new_path = path.with_file_name(name);
+    if new_path != path && std::fs::symlink_metadata(&new_path).is_ok() {
+        bail!(
+            "{} already exists",
+            new_path.file_name().unwrap_or_default().to_string_lossy()
+        );
+    }
+    Ok(new_path)
+}
+
+/// Tries `dir/name`, then `dir/stem (n).ext` for growing `n`, until `claim`
+/// creates one without hitting `AlreadyExists`. `claim` must fail rather than
+/// replace an existing file, so the check and the write are a single step.
+fn claim_destination<F>(dir: &Path, name: &Path, mut claim: F) -> std::io::Result<PathBuf>
+where
+    F: FnMut(&Path) -> std::io::Result<()>,
+{
+    let stem = name
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = name
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    let suffixed = (1u32..).map(|n| dir.join(format!("{} ({}){}", stem, n, ext)));
+    for candidate in std::iter::once(dir.join(name)).chain(suffixed) {
+        match claim(&candidate) {
+            Ok(()) => return Ok(candidate),
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    unreachable!("ran out of numeric suffixes")
+}
+
+/// Rewrites `path` with the bytes produced by `write`, keeping its permissions.
+pub fn replace_file<F>(path: &Path, write: F) -> Result<()>
+where
+    F: FnOnce(&mut BufWriter<File>) -> Result<()>,
+{
+    let original_permissions = std::fs::metadata(path)
+        .with_context(|| format!("Failed to stat {}", path.display()))?
+        .permissions();
+    write_through_temp(path, Some(original_permissions), write)
+}
+
+/// Writes `path` with the bytes produced by `write`, creating it if needed.
+pub fn write_file<F>(path: &Path, write: F) -> Result<()>
+where
+    F: FnOnce(&mut BufWriter<File>) -> Result<()>,
+{
+    let permissions = std::fs::metadata(path).ok().map(|m| m.permissions());
+    write_through_temp(path, permissions, write)
+}
+
+fn write_through_temp<F>(path: &Path, permissions: Option<Permissions>, write: F) -> Result<()>
+where
+    F: FnOnce(&mut BufWriter<File>) -> Result<()>,
+{
+    let tmp_path = temp_path_for(path);
+    let written = File::create(&tmp_path)
+        .with_context(|| format!("Failed to create {}", tmp_path.display()))
+        .and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            write(&mut writer)?;
+            writer.flush().context("Failed to flush")
+        });
+    if let Err(err) = written {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(err);
+    }
+    if let Some(permissions) = permissions {
+        let _ = std::fs::set_permissions(&tmp_path, permissions);
+    }
+    std::fs::rename(&tmp_path, path).with_context(|| {
+        let _ = std::fs::remove_file(&tmp_path);
+        format!("Failed to replace {}", path.display())
+    })
+}
+
+/// Bakes `rotation_cw` clockwise quarter turns into the file. JPEGs only get a
+/// new EXIF orientation; PNGs are re-encoded upright, keeping their metadata
+/// chunks.
+pub fn save_rotation(path: &Path, rotation_cw: u8) -> Result<()> {
+    let rotation_cw = rotation_cw % 4;
+    if rotation_cw == 0 {
+        return Ok(());
+    }
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string());
+    let metadata =
+        std::fs::metadata(path).with_context(|| format!("Failed to stat {}", path.display()))?;
+    if metadata.permissions().readonly() {
+        bail!("{} is read-only", name);
+    }
+
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "jpg" | "jpeg" => {
+            let current = crate::image_loader::read_exif_orientation(path).unwrap_or(1);
+            let orientation = crate::image_loader::rotate_orientation(current, rotation_cw);
+            crate::metadata_editor::write_orientation(path, orientation)
+        }
+        "png" => {
+            let original = std::fs::read(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let stored = crate::image_loader::open_image_unmanaged(path)?;
+            let current =
+                crate::image_loader::read_exif_orientation_from_bytes(&original).unwrap_or(1);
+            let orientation = crate::image_loader::rotate_orientation(current, rotation_cw);
+            let rotated = crate::image_loader::apply_exif_orientation(stored, orientation);
+            let mut encoded = Vec::new();
+            rotated
+                .write_to(
+                    &mut std::io::Cursor::new(&mut encoded),
+                    image::ImageFormat::Png,
+                )
+                .context("Failed to encode PNG")?;
+            let encoded = crate::metadata_editor::carry_png_metadata(&original, encoded)?;
+            replace_file(path, |writer| {
+                writer.write_all(&encoded).context("Failed to write PNG")
+            })
+        }
+        _ => bail!("Saving rotation is not supported for {}", name),
+    }
+}
+
+fn temp_path_for(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.idxd-edit", name))
+}
+
+/// Picks, for every wanted path, the trash entry deleted last. Older entries
+/// with the same original path belong to earlier deletes.
+fn latest_trash_entries(items: Vec<trash::TrashItem>, wanted: &[PathBuf]) -> Vec<trash::TrashItem> {
+    let wanted: HashSet<&PathBuf> = wanted.iter().collect();
+    let mut latest: Vec<trash::TrashItem> = Vec::new();
+    for item in items {
+        let original = item.original_path();
+        if !wanted.contains(&original) {
+            continue;
+        }
+        match latest
+            .iter_mut()
+            .find(|kept| kept.original_path() == original)
+        {
+            Some(kept) if kept.time_deleted < item.time_deleted => *kept = item,
+            Some(_) => {}
+            None => latest.push(item),
+        }
+    }
+    latest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsString;
+
+    fn entry(id: &str, path: &str, time_deleted: i64) -> trash::TrashItem {
+        let path = PathBuf::from(path);
+        trash::TrashItem {
+            id: OsString::from(id),
+            name: path.file_name().unwrap().to_os_string(),
+            original_parent: path.parent().unwrap().to_path_buf(),
+            time_deleted,
+        }
+    }
+
+    #[test]
+    fn test_latest_trash_entries_prefers_newest_copy() {
+        let items = vec![
+            entry("1", "/photos/a.jpg", 10),
+            entry("2", "/photos/a.jpg", 30),
+            entry("3", "/photos/b.jpg", 20),
+            entry("4", "/elsewhere/a.jpg", 40),
+        ];
+        let wanted = vec![
+            PathBuf::from("/photos/a.jpg"),
+            PathBuf::from("/photos/c.jpg"),
+        ];
+
+        let picked = latest_trash_entries(items, &wanted);
+        assert_eq!(picked.len(), 1);
+        assert_eq!(picked[0].id, OsString::from("2"));
+    }
+
+    #[test]
+    fn test_transfer_files_suffixes_taken_names() {
+        let src = tempfile::tempdir().unwrap();
+        let dest = tempfile::tempdir().unwrap();
+        let photo = src.path().join("a.jpg");
+        std::fs::write(&photo, b"new").unwrap();
+        std::fs::write(dest.path().join("a.jpg"), b"old").unwrap();
+
+        let copied = transfer_files(
+            std::slice::from_ref(&photo),
+            dest.path(),
+            TransferMode::Copy,
+        );
+        let copy = copied[0].1.as_ref().unwrap();
+        assert_eq!(copy, &dest.path().join("a (1).jpg"));
+        assert!(photo.exists());
+
+        let moved = transfer_files(
+            std::slice::from_ref(&photo),
+            dest.path(),
+            TransferMode::Move,
+        );
+        let moved_to = moved[0].1.as_ref().unwrap();
+        assert_eq!(moved_to, &dest.path().join("a (2).jpg"));
+        assert!(!photo.exists());
+        assert_eq!(std::fs::read(dest.path().join("a.jpg")).unwrap(), b"old");
+        assert_eq!(std::fs::read(moved_to).unwrap(), b"new");
+
+        let again = transfer_files(
+            std::slice::from_ref(moved_to),
+            dest.path(),
+            TransferMode::Move,
+        );
+        assert!(again[0].1.is_err());
+    }
+
+    #[test]
+    fn test_claim_destination_skips_a_name_taken_meanwhile() {
+        let dir = tempfile::tempdir().unwrap();
+        let photo = dir.path().join("photo.jpg");
+        std::fs::write(&photo, b"ours").unwrap();
+        let dest = dir.path().join("dest");
+        std::fs::create_dir(&dest).unwrap();
+
+        // Another program creates `a.jpg` right before the copy claims it.
+        let mut raced = false;
+        let claimed = claim_destination(&dest, Path::new("a.jpg"), |candidate| {
+            if !raced {
+                raced = true;
+                std::fs::write(candidate, b"theirs")?;
+            }
+            copy_to_new(&photo, candidate)
+        })
+        .unwrap();
+
+        assert_eq!(claimed, dest.join("a (1).jpg"));
+        assert_eq!(std::fs::read(dest.join("a.jpg")).unwrap(), b"theirs");
+        assert_eq!(std::fs::read(&claimed).unwrap(), b"ours");
+    }
+
+    #[test]
+    fn test_rename_file_keeps_extension_and_refuses_collisions() {
+        let dir = tempfile::tempdir().unwrap();
+        let photo = dir.path().join("IMG_0001.jpg");
+        std::fs::write(&photo, b"").unwrap();
+        std::fs::write(dir.path().join("taken.jpg"), b"").unwrap();
+
+        let renamed = rename_file(&photo, " beach ").unwrap();
+        assert_eq!(renamed, dir.path().join("beach.jpg"));
+        assert!(renamed.exists() && !photo.exists());
+
+        assert_eq!(rename_file(&renamed, "beach.jpg").unwrap(), renamed);
+        assert!(rename_file(&renamed, "taken").is_err());
+        assert!(rename_file(&renamed, "../beach").is_err());
+        assert!(rename_file(&renamed, "  ").is_err());
+        assert_eq!(
+            rename_file(&renamed, "beach.jpeg").unwrap(),
+            dir.path().join("beach.jpeg")
+        );
+    }
+
+    #[test]
+    fn test_save_rotation_reencodes_png_and_refuses_read_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wide.png");
+        image::RgbImage::new(6, 2).save(&path).unwrap();
+
+        save_rotation(&path, 1).unwrap();
+        assert_eq!(image::image_dimensions(&path).unwrap(), (2, 6));
+
+        let mut permissions = std::fs::metadata(&path).unwrap().permissions();
+        permissions.set_readonly(true);
+        std::fs::set_permissions(&path, permissions).unwrap();
+        let err = save_rotation(&path, 1).unwrap_err();
+        assert!(err.to_string().contains("read-only"));
+    }
+
+    #[test]
+    fn test_save_rotation_keeps_png_metadata_chunks() {
+        use exif::{Field, In, Tag, Value};
+        use img_parts::png::{Png, PngChunk};
+        use img_parts::{Bytes, ImageEXIF, ImageICC};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tagged.png");
+        let mut pixels = image::RgbImage::new(4, 2);
+        pixels.put_pixel(0, 0, image::Rgb([255, 0, 0]));
+        let mut encoded = Vec::new();
+        pixels
+            .write_to(
+                &mut std::io::Cursor::new(&mut encoded),
+                image::ImageFormat::Png,
+            )
+            .unwrap();
+
+        // Shown turned 90 degrees clockwise, with a profile and a comment.
+        let mut exif = exif::experimental::Writer::new();
+        let orientation = Field {
+            tag: Tag::Orientation,
+            ifd_num: In::PRIMARY,
+            value: Value::Short(vec![6]),
+        };
+        let description = Field {
+            tag: Tag::ImageDescription,
+            ifd_num: In::PRIMARY,
+            value: Value::Ascii(vec![b"Pier".to_vec()]),
+        };
+        exif.push_field(&orientation);
+        exif.push_field(&description);
+        let mut exif_block = std::io::Cursor::new(Vec::new());
+        exif.write(&mut exif_block, false).unwrap();
+
+        let mut png = Png::from_bytes(Bytes::from(encoded)).unwrap();
+        png.set_icc_profile(Some(Bytes::from_static(b"profile bytes")));
+        png.set_exif(Some(Bytes::from(exif_block.into_inner())));
+        png.chunks_mut().insert(
+            1,
+            PngChunk::new(*b"tEXt", Bytes::from_static(b"Comment\0harbour")),
+        );
+        let mut file = std::fs::File::create(&path).unwrap();
+        png.encoder().write_to(&mut file).unwrap();
+        drop(file);
+
+        save_rotation(&path, 1).unwrap();
+
+        // 90 + 90 degrees is a half turn, now baked into the pixels.
+        let saved = image::open(&path).unwrap().to_rgb8();
+        assert_eq!(saved.dimensions(), (4, 2));
+        assert_eq!(saved.get_pixel(3, 1), &image::Rgb([255, 0, 0]));
+        assert_eq!(crate::image_loader::read_exif_orientation(&path), Some(1));
+
+        let saved = Png::from_bytes(Bytes::from(std::fs::read(&path).unwrap())).unwrap();
+        assert_eq!(saved.icc_profile().as_deref(), Some(&b"profile bytes"[..]));
+        assert!(saved
+            .chunks_by_type(*b"tEXt")
+            .any(|chunk| chunk.contents().as_ref() == b"Comment\0harbour"));
+        let exif = exif::Reader::new()
+            .read_raw(saved.exif().unwrap().to_vec())
+            .unwrap();
+        let description = exif.get_field(Tag::ImageDescription, In::PRIMARY).unwrap();
+        assert_eq!(description.display_value().to_string(), "\"Pier\"");
+    }
+}
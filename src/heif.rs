@@ -0,0 +1,295 @@
+//! HEIF/HEIC and AVIF decoding through libheif.
+//!
+//! libheif is loaded at runtime with `dlopen`, like the NVIDIA offload, so the
+//! binary still starts on systems without it; those files then fail to decode
+//! and show the usual placeholder. libheif applies the container's rotation and
+//! mirror properties while decoding. HEIF stores orientation there rather than
+//! in EXIF, so no EXIF orientation is applied on top.
+
+use std::ffi::{c_void, CStr, CString};
+use std::os::raw::{c_char, c_int};
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::ptr;
+use std::sync::OnceLock;
+
+use anyhow::{anyhow, bail, Result};
+use image::{DynamicImage, RgbaImage};
+use tracing::{debug, info};
+
+use crate::dylib::{open_library, symbol};
+
+/// Extensions decoded by libheif. Sequences decode their primary image, which
+/// is the first frame.
+pub const HEIF_EXTENSIONS: &[&str] = &["heic", "heif", "hif", "avif"];
+
+const LIBHEIF_NAMES: &[&str] = &["libheif.so.1", "libheif.so"];
+
+const HEIF_COLORSPACE_RGB: c_int = 1;
+const HEIF_CHROMA_INTERLEAVED_RGBA: c_int = 11;
+const HEIF_CHANNEL_INTERLEAVED: c_int = 10;
+
+#[repr(C)]
+struct HeifError {
+    code: c_int,
+    subcode: c_int,
+    message: *const c_char,
+}
+
+type HeifInit = unsafe extern "C" fn(*mut c_void) -> HeifError;
+type HeifContextAlloc = unsafe extern "C" fn() -> *mut c_void;
+type HeifContextFree = unsafe extern "C" fn(*mut c_void);
+type HeifContextReadFromFile =
+    unsafe extern "C" fn(*mut c_void, *const c_char, *const c_void) -> HeifError;
+type HeifContextGetPrimaryImageHandle =
+    unsafe extern "C" fn(*mut c_void, *mut *mut c_void) -> HeifError;
+type HeifImageHandleRelease = unsafe extern "C" fn(*mut c_void);
+type HeifImageHandleGetSize = unsafe extern "C" fn(*const c_void) -> c_int;
+type HeifDecodeImage =
+    unsafe extern "C" fn(*const c_void, *mut *mut c_void, c_int, c_int, *const c_void) -> HeifError;
+type HeifImageGetPlaneReadonly =
+    unsafe extern "C" fn(*const c_void, c_int, *mut c_int) -> *const u8;
+type HeifImageGetSize = unsafe extern "C" fn(*const c_void, c_int) -> c_int;
+type HeifImageRelease = unsafe extern "C" fn(*mut c_void);
+
+/// Resolved libheif entry points.
+struct HeifLib {
+    context_alloc: HeifContextAlloc,
+    context_free: HeifContextFree,
+    context_read_from_file: HeifContextReadFromFile,
+    context_get_primary_image_handle: HeifContextGetPrimaryImageHandle,
+    image_handle_release: HeifImageHandleRelease,
+    image_handle_get_width: HeifImageHandleGetSize,
+    image_handle_get_height: HeifImageHandleGetSize,
+    decode_image: HeifDecodeImage,
+    image_get_plane_readonly: HeifImageGetPlaneReadonly,
+    image_get_width: HeifImageGetSize,
+    image_get_height: HeifImageGetSize,
+    image_release: HeifImageRelease,
+}
+
+fn lib() -> Option<&'static HeifLib> {
+    static LIB: OnceLock<Option<HeifLib>> = OnceLock::new();
+    LIB.get_or_init(|| match load_lib() {
+        Ok(lib) => {
+            info!("libheif available for HEIF/AVIF decoding");
+            Some(lib)
+        }
+        Err(e) => {
+            debug!(error = %e, "libheif unavailable; HEIF/AVIF files will not decode");
+            None
+        }
+    })
+    .as_ref()
+}
+
+fn load_lib() -> Result<HeifLib> {
+    // The handle is never closed; the entry points live for the process.
+    let handle = open_library(LIBHEIF_NAMES)?;
+
+    unsafe {
+        // Required since libheif 1.13 to register codec plugins; older
+        // versions do not export it.
+        if let Ok(init) = symbol(handle, "heif_init") {
+            let init = std::mem::transmute::<*mut c_void, HeifInit>(init);
+            check(init(ptr::null_mut()), "heif_init")?;
+        }
+
+        Ok(HeifLib {
+            context_alloc: std::mem::transmute::<*mut c_void, HeifContextAlloc>(symbol(
+                handle,
+                "heif_context_alloc",
+            )?),
+            context_free: std::mem::transmute::<*mut c_void, HeifContextFree>(symbol(
+                handle,
+                "heif_context_free",
+            )?),
+            context_read_from_file: std::mem::transmute::<*mut c_void, HeifContextReadFromFile>(
+                symbol(handle, "heif_context_read_from_file")?,
+            ),
+            context_get_primary_image_handle: std::mem::transmute::<
+                *mut c_void,
+                HeifContextGetPrimaryImageHandle,
+            >(symbol(
+                handle,
+                "heif_context_get_primary_image_handle",
+            )?),
+            image_handle_release: std::mem::transmute::<*mut c_void, HeifImageHandleRelease>(
+                symbol(handle, "heif_image_handle_release")?,
+            ),
+            image_handle_get_width: std::mem::transmute::<*mut c_void, HeifImageHandleGetSize>(
+                symbol(handle, "heif_image_handle_get_width")?,
+            ),
+            image_handle_get_height: std::mem::transmute::<*mut c_void, HeifImageHandleGetSize>(
+                symbol(handle, "heif_image_handle_get_height")?,
+            ),
+            decode_image: std::mem::transmute::<*mut c_void, HeifDecodeImage>(symbol(
+                handle,
+                "heif_decode_image",
+            )?),
+            image_get_plane_readonly: std::mem::transmute::<*mut c_void, HeifImageGetPlaneReadonly>(
+                symbol(handle, "heif_image_get_plane_readonly")?,
+            ),
+            image_get_width: std::mem::transmute::<*mut c_void, HeifImageGetSize>(symbol(
+                handle,
+                "heif_image_get_width",
+            )?),
+            image_get_height: std::mem::transmute::<*mut c_void, HeifImageGetSize>(symbol(
+                handle,
+                "heif_image_get_height",
+            )?),
+            image_release: std::mem::transmute::<*mut c_void, HeifImageRelease>(symbol(
+                handle,
+                "heif_image_release",
+            )?),
+        })
+    }
+}
+
+fn check(err: HeifError, what: &str) -> Result<()> {
+    if err.code == 0 {
+        return Ok(());
+    }
+    let message = if err.message.is_null() {
+        String::new()
+    } else {
+        unsafe { CStr::from_ptr(err.message) }
+            .to_string_lossy()
+            .into_owned()
+    };
+    bail!(
+        "{} failed ({}.{}): {}",
+        what,
+        err.code,
+        err.subcode,
+        message
+    )
+}
+
+pub fn is_heif_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| HEIF_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// A parsed file and its primary image handle, released on drop.
+struct PrimaryImage {
+    lib: &'static HeifLib,
+    context: *mut c_void,
+    handle: *mut c_void,
+}
+
+impl PrimaryImage {
+    fn open(path: &Path) -> Result<Self> {
+        let lib = lib().ok_or_else(|| anyhow!("libheif is not installed"))?;
+        let c_path = CString::new(path.as_os_str().as_bytes())?;
+        let context = unsafe { (lib.context_alloc)() };
+        if context.is_null() {
+            bail!("heif_context_alloc failed");
+        }
+        let mut image = Self {
+            lib,
+            context,
+            handle: ptr::null_mut(),
+        };
+        unsafe {
+            check(
+                (lib.context_read_from_file)(context, c_path.as_ptr(), ptr::null()),
+                "heif_context_read_from_file",
+            )?;
+            check(
+                (lib.context_get_primary_image_handle)(context, &mut image.handle),
+                "heif_context_get_primary_image_handle",
+            )?;
+        }
+        Ok(image)
+    }
+}
+
+impl Drop for PrimaryImage {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.handle.is_null() {
+                (self.lib.image_handle_release)(self.handle);
+            }
+            (self.lib.context_free)(self.context);
+        }
+    }
+}
+
+/// Displayed size of the primary image, read from the container without
+/// decoding. libheif reports it with rotation already applied.
+pub fn read_dimensions(path: &Path) -> Result<(u32, u32)> {
+    let image = PrimaryImage::open(path)?;
+    let (width, height) = unsafe {
+        (
+            (image.lib.image_handle_get_width)(image.handle),
+            (image.lib.image_handle_get_height)(image.handle),
+        )
+    };
+    if width <= 0 || height <= 0 {
+        bail!("Invalid HEIF dimensions {}x{}: {:?}", width, height, path);
+    }
+    Ok((width as u32, height as u32))
+}
+
+/// Decodes the primary image to RGBA, oriented for display.
+pub fn decode(path: &Path) -> Result<DynamicImage> {
+    let image = PrimaryImage::open(path)?;
+    let lib = image.lib;
+    let mut decoded = ptr::null_mut();
+    unsafe {
+        check(
+            (lib.decode_image)(
+                image.handle,
+                &mut decoded,
+                HEIF_COLORSPACE_RGB,
+                HEIF_CHROMA_INTERLEAVED_RGBA,
+                ptr::null(),
+            ),
+            "heif_decode_image",
+        )?;
+    }
+
+    let rgba = unsafe {
+        let width = (lib.image_get_width)(decoded, HEIF_CHANNEL_INTERLEAVED);
+        let height = (lib.image_get_height)(decoded, HEIF_CHANNEL_INTERLEAVED);
+        let mut stride: c_int = 0;
+        let plane = (lib.image_get_plane_readonly)(decoded, HEIF_CHANNEL_INTERLEAVED, &mut stride);
+        let rgba = if plane.is_null() || width <= 0 || height <= 0 || stride < width * 4 {
+            None
+        } else {
+            let row_len = width as usize * 4;
+            let mut pixels = Vec::with_capacity(row_len * height as usize);
+            for row in 0..height as usize {
+                let start = plane.add(row * stride as usize);
+                pixels.extend_from_slice(std::slice::from_raw_parts(start, row_len));
+            }
+            RgbaImage::from_raw(width as u32, height as u32, pixels)
+        };
+        (lib.image_release)(decoded);
+        rgba
+    };
+    rgba.map(DynamicImage::ImageRgba8)
+        .ok_or_else(|| anyhow!("libheif returned no pixels for {:?}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heif_extensions_and_invalid_files() {
+        assert!(is_heif_path(Path::new("IMG_0001.HEIC")));
+        assert!(is_heif_path(Path::new("clip.avif")));
+        assert!(!is_heif_path(Path::new("photo.jpg")));
+
+        // Fails cleanly whether or not libheif is installed.
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("broken.heic");
+        std::fs::write(&path, b"not a heif file").unwrap();
+        assert!(decode(&path).is_err());
+        assert!(read_dimensions(&path).is_err());
+    }
+}
@@ -1,22 +1,94 @@
 use std::fs::File;
 use std::io::{Cursor, Read};
 use std::path::Path;
+use std::time::Duration;
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use image::codecs::gif::GifDecoder;
+use image::codecs::webp::WebPDecoder;
 use image::AnimationDecoder;
-use image::{DynamicImage, ImageFormat, ImageReader};
+use image::{DynamicImage, Frame, ImageFormat, ImageReader, RgbaImage};
+
+/// Camera RAW extensions. These are shown through the JPEG preview the camera
+/// embeds in the file rather than by demosaicing the sensor data.
+pub const RAW_EXTENSIONS: &[&str] = &[
+    "arw", "cr2", "cr3", "dng", "nef", "nrw", "orf", "pef", "raf", "rw2", "srw",
+];
+
+/// How much of a RAW file `read_dimensions` reads. Cameras write their
+/// previews ahead of the sensor data, so the largest one starts well inside it.
+const RAW_HEADER_BYTES: u64 = 16 * 1024 * 1024;
+
+pub fn is_raw_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| RAW_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+        .unwrap_or(false)
+}
 
 pub fn open_image(path: &Path) -> Result<DynamicImage> {
     open_image_with_rotation(path, 0)
 }
 
 pub fn open_image_with_rotation(path: &Path, extra_rotation_cw: u8) -> Result<DynamicImage> {
+    open_image_with_limits(path, extra_rotation_cw, image::Limits::default())
+}
+
+/// Like `open_image_with_rotation`, but formats decoded by the `image` crate
+/// fail instead of allocating past `limits`.
+pub fn open_image_with_limits(
+    path: &Path,
+    extra_rotation_cw: u8,
+    limits: image::Limits,
+) -> Result<DynamicImage> {
+    if crate::heif::is_heif_path(path) {
+        let img = crate::heif::decode(path)
+            .with_context(|| format!("Failed to decode HEIF image: {:?}", path))?;
+        return Ok(apply_rotation_steps(img, extra_rotation_cw));
+    }
+    if crate::svg::is_svg_path(path) {
+        let (width, height) = crate::svg::read_dimensions(path)?;
+        return open_svg_with_rotation(path, width.max(height), extra_rotation_cw);
+    }
     let bytes = std::fs::read(path).with_context(|| format!("Failed to read image: {:?}", path))?;
+    if is_raw_path(path) {
+        let img = decode_raw_preview(&bytes)
+            .with_context(|| format!("Failed to decode RAW preview: {:?}", path))?;
+        return Ok(apply_rotation_steps(img, extra_rotation_cw));
+    }
     let format = image::guess_format(&bytes).ok();
+    let img = decode_stored(path, &bytes, format, limits)?;
 
+    let img = match format {
+        Some(fmt) => crate::color::manage(img, &bytes, fmt),
+        None => img,
+    };
+    let orientation = read_exif_orientation_from_bytes(&bytes).unwrap_or(1);
+    let img = apply_exif_orientation(img, orientation);
+    Ok(apply_rotation_steps(img, extra_rotation_cw))
+}
+
+/// Decodes the pixels exactly as stored: no color management, EXIF
+/// orientation or rotation. Use this for anything written back to disk, so
+/// saving never bakes the display profile into the original file.
+pub fn open_image_unmanaged(path: &Path) -> Result<DynamicImage> {
+    if crate::heif::is_heif_path(path) || crate::svg::is_svg_path(path) || is_raw_path(path) {
+        return Err(anyhow!("No stored pixels to decode for {:?}", path));
+    }
+    let bytes = std::fs::read(path).with_context(|| format!("Failed to read image: {:?}", path))?;
+    let format = image::guess_format(&bytes).ok();
+    decode_stored(path, &bytes, format, image::Limits::default())
+}
+
+/// Decodes a file handled by the `image` crate; GIFs yield their first frame.
+fn decode_stored(
+    path: &Path,
+    bytes: &[u8],
+    format: Option<ImageFormat>,
+    limits: image::Limits,
+) -> Result<DynamicImage> {
     let img = if format == Some(ImageFormat::Gif) {
-        let decoder = GifDecoder::new(Cursor::new(&bytes))
+        let decoder = GifDecoder::new(Cursor::new(bytes))
             .with_context(|| format!("Failed to decode GIF: {:?}", path))?;
         let mut frames = decoder.into_frames();
         if let Some(frame) = frames.next() {
@@ -27,19 +99,115 @@ pub fn open_image_with_rotation(path: &Path, extra_rotation_cw: u8) -> Result<Dy
         }
     } else {
         match format {
-            Some(fmt) => image::load_from_memory_with_format(&bytes, fmt)
-                .with_context(|| format!("Failed to decode image: {:?}", path))?,
-            None => image::load_from_memory(&bytes)
+            Some(fmt) => {
+                let mut reader = ImageReader::with_format(Cursor::new(bytes), fmt);
+                reader.limits(limits);
+                reader
+                    .decode()
+                    .with_context(|| format!("Failed to decode image: {:?}", path))?
+            }
+            None => image::load_from_memory(bytes)
                 .with_context(|| format!("Failed to decode image: {:?}", path))?,
         }
     };
+    Ok(img)
+}
 
-    let orientation = read_exif_orientation_from_bytes(&bytes).unwrap_or(1);
-    let img = apply_exif_orientation(img, orientation);
-    Ok(apply_rotation_steps(img, extra_rotation_cw))
+/// Upper bound on decoded frame data kept for one animation; frames past it
+/// are dropped so a huge GIF plays a prefix instead of exhausting memory.
+const MAX_ANIMATION_BYTES: usize = 128 * 1024 * 1024;
+/// Delays at or below this are treated as "as fast as possible" by encoders
+/// and played at `DEFAULT_FRAME_DELAY`, matching browsers.
+const MIN_FRAME_DELAY: Duration = Duration::from_millis(10);
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(100);
+
+/// One fully composited frame of an animated image.
+pub struct AnimationFrame {
+    pub image: RgbaImage,
+    pub delay: Duration,
+}
+
+pub fn is_animation_candidate(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("gif") || e.eq_ignore_ascii_case("webp"))
+}
+
+/// Decodes every frame of an animated GIF or WebP. Returns `Ok(None)` for
+/// still images, including single-frame GIFs, so callers keep the still path.
+pub fn decode_animation(path: &Path, extra_rotation_cw: u8) -> Result<Option<Vec<AnimationFrame>>> {
+    let bytes = std::fs::read(path).with_context(|| format!("Failed to read image: {:?}", path))?;
+    decode_animation_bytes(&bytes, extra_rotation_cw)
+        .with_context(|| format!("Failed to decode animation: {:?}", path))
+}
+
+fn decode_animation_bytes(
+    bytes: &[u8],
+    extra_rotation_cw: u8,
+) -> Result<Option<Vec<AnimationFrame>>> {
+    let frames: Box<dyn Iterator<Item = image::ImageResult<Frame>>> =
+        match image::guess_format(bytes).ok() {
+            Some(ImageFormat::Gif) => Box::new(GifDecoder::new(Cursor::new(bytes))?.into_frames()),
+            Some(ImageFormat::WebP) => {
+                let decoder = WebPDecoder::new(Cursor::new(bytes))?;
+                if !decoder.has_animation() {
+                    return Ok(None);
+                }
+                Box::new(decoder.into_frames())
+            }
+            _ => return Ok(None),
+        };
+    let orientation = read_exif_orientation_from_bytes(bytes).unwrap_or(1);
+
+    let mut decoded = Vec::new();
+    let mut total_bytes = 0usize;
+    for frame in frames {
+        let frame = frame?;
+        let (numer, denom) = frame.delay().numer_denom_ms();
+        let delay = Duration::from_millis(u64::from(numer) / u64::from(denom.max(1)));
+        let delay = if delay <= MIN_FRAME_DELAY {
+            DEFAULT_FRAME_DELAY
+        } else {
+            delay
+        };
+        let image = DynamicImage::ImageRgba8(frame.into_buffer());
+        let image = apply_exif_orientation(image, orientation);
+        let image = apply_rotation_steps(image, extra_rotation_cw).into_rgba8();
+        total_bytes += image.as_raw().len();
+        if total_bytes > MAX_ANIMATION_BYTES && !decoded.is_empty() {
+            tracing::warn!(
+                "Animation exceeds {} MiB; playing the first {} frames",
+                MAX_ANIMATION_BYTES / (1024 * 1024),
+                decoded.len()
+            );
+            break;
+        }
+        decoded.push(AnimationFrame { image, delay });
+    }
+    Ok((decoded.len() > 1).then_some(decoded))
 }
 
 pub fn read_dimensions(path: &Path) -> Result<(u32, u32)> {
+    if crate::heif::is_heif_path(path) {
+        return crate::heif::read_dimensions(path);
+    }
+    if crate::svg::is_svg_path(path) {
+        return crate::svg::read_dimensions(path);
+    }
+    if is_raw_path(path) {
+        let mut head = Vec::new();
+        File::open(path)
+            .and_then(|file| file.take(RAW_HEADER_BYTES).read_to_end(&mut head))
+            .with_context(|| format!("Failed to read image: {:?}", path))?;
+        let (preview, (width, height)) = largest_raw_preview(&head, true)
+            .ok_or_else(|| anyhow!("No embedded JPEG preview: {:?}", path))?;
+        return Ok(if needs_dimension_swap(raw_orientation(&head, preview)) {
+            (height, width)
+        } else {
+            (width, height)
+        });
+    }
+
     let reader = ImageReader::open(path)
         .with_context(|| format!("Failed to open image: {:?}", path))?
         .with_guessed_format()
@@ -64,7 +232,7 @@ pub fn read_dimensions(path: &Path) -> Result<(u32, u32)> {
             .with_context(|| format!("Failed to read dimensions: {:?}", path))?
     };
 
-    if let Some(orientation) = read_exif_orientation_from_path(path) {
+    if let Some(orientation) = read_exif_orientation(path) {
         if needs_dimension_swap(orientation) {
             dims = (dims.1, dims.0);
         }
@@ -74,11 +242,20 @@ pub fn read_dimensions(path: &Path) -> Result<(u32, u32)> {
 }
 
 /// Try to load the embedded EXIF JPEG thumbnail (if present) and apply orientation/rotation.
+/// For RAW files this is the largest embedded preview, which is also what the
+/// full decode shows.
 /// Returns (preview_image, original_width, original_height).
 pub fn open_embedded_jpeg_preview_with_rotation(
     path: &Path,
     extra_rotation_cw: u8,
 ) -> Option<(DynamicImage, u32, u32)> {
+    if is_raw_path(path) {
+        let bytes = std::fs::read(path).ok()?;
+        let img = decode_raw_preview(&bytes).ok()?;
+        let (orig_w, orig_h) = (img.width(), img.height());
+        return Some((apply_rotation_steps(img, extra_rotation_cw), orig_w, orig_h));
+    }
+
     let mut file = File::open(path).ok()?;
     let mut buf = vec![0u8; 256 * 1024];
     let read = file.read(&mut buf).ok()?;
@@ -89,6 +266,8 @@ pub fn open_embedded_jpeg_preview_with_rotation(
 
     let thumb_bytes = parse_jpeg_exif_thumbnail(bytes)?;
     let mut img = image::load_from_memory_with_format(thumb_bytes, ImageFormat::Jpeg).ok()?;
+    // The thumbnail shares the profile of the main image in the file header
+    img = crate::color::manage(img, bytes, ImageFormat::Jpeg);
 
     let orientation = read_exif_orientation_from_bytes(bytes).unwrap_or(1);
     img = apply_exif_orientation(img, orientation);
@@ -98,6 +277,121 @@ pub fn open_embedded_jpeg_preview_with_rotation(
     Some((img, orig_w, orig_h))
 }
 
+/// Rasterizes an SVG so its longer side is `max_size` pixels.
+pub fn open_svg_with_rotation(
+    path: &Path,
+    max_size: u32,
+    extra_rotation_cw: u8,
+) -> Result<DynamicImage> {
+    let img = crate::svg::render(path, max_size, max_size)?;
+    Ok(apply_rotation_steps(img, extra_rotation_cw))
+}
+
+/// Decodes the largest JPEG preview embedded in a RAW file, color managed
+/// and oriented.
+fn decode_raw_preview(bytes: &[u8]) -> Result<DynamicImage> {
+    let Some((preview, _)) = largest_raw_preview(bytes, false) else {
+        bail!("No embedded JPEG preview");
+    };
+    let img = image::load_from_memory_with_format(preview, ImageFormat::Jpeg)
+        .context("Failed to decode embedded JPEG preview")?;
+    let img = crate::color::manage(img, preview, ImageFormat::Jpeg);
+    Ok(apply_exif_orientation(img, raw_orientation(bytes, preview)))
+}
+
+/// Picks the embedded JPEG with the most pixels, judged from its header alone.
+/// With `allow_truncated`, `bytes` may be a prefix of the file and a preview
+/// cut off by its end still counts.
+fn largest_raw_preview(bytes: &[u8], allow_truncated: bool) -> Option<(&[u8], (u32, u32))> {
+    embedded_jpeg_streams(bytes, allow_truncated)
+        .into_iter()
+        .filter_map(|stream| {
+            let dims = ImageReader::with_format(Cursor::new(stream), ImageFormat::Jpeg)
+                .into_dimensions()
+                .ok()?;
+            Some((stream, dims))
+        })
+        .max_by_key(|(_, (w, h))| *w as u64 * *h as u64)
+}
+
+/// TIFF-based RAWs (CR2, NEF, ARW, DNG, ...) keep the orientation in IFD0;
+/// the others usually repeat it in the EXIF of their largest `preview`.
+fn raw_orientation(bytes: &[u8], preview: &[u8]) -> u16 {
+    read_exif_orientation_from_bytes(bytes)
+        .or_else(|| read_exif_orientation_from_bytes(preview))
+        .unwrap_or(1)
+}
+
+/// Finds complete JPEG streams inside a container. Thumbnails nested in a
+/// stream's own EXIF block are skipped along with the stream. With
+/// `allow_truncated`, a stream that does not end inside `bytes` is returned
+/// up to the end of the buffer as well.
+fn embedded_jpeg_streams(bytes: &[u8], allow_truncated: bool) -> Vec<&[u8]> {
+    let mut streams = Vec::new();
+    let mut start = 0usize;
+    while let Some(offset) = bytes[start..]
+        .windows(3)
+        .position(|w| w == [0xFF, 0xD8, 0xFF])
+    {
+        let soi = start + offset;
+        match jpeg_stream_len(&bytes[soi..]) {
+            Some(len) => {
+                streams.push(&bytes[soi..soi + len]);
+                start = soi + len;
+            }
+            None => {
+                if allow_truncated {
+                    streams.push(&bytes[soi..]);
+                }
+                start = soi + 2;
+            }
+        }
+    }
+    streams
+}
+
+/// Walks the segments of a JPEG starting at its SOI and returns the length up
+/// to and including EOI.
+fn jpeg_stream_len(bytes: &[u8]) -> Option<usize> {
+    let mut pos = 2usize;
+    loop {
+        if *bytes.get(pos)? != 0xFF {
+            return None;
+        }
+        while *bytes.get(pos)? == 0xFF {
+            pos += 1;
+        }
+        let marker = bytes[pos];
+        pos += 1;
+
+        if marker == 0xD9 {
+            return Some(pos);
+        }
+        if marker == 0x01 || (0xD0..=0xD7).contains(&marker) {
+            continue;
+        }
+
+        let segment_len = u16::from_be_bytes([*bytes.get(pos)?, *bytes.get(pos + 1)?]) as usize;
+        if segment_len < 2 {
+            return None;
+        }
+        pos += segment_len;
+
+        if marker == 0xDA {
+            // Entropy-coded data runs until the next marker that is neither
+            // a stuffed 0xFF00 nor a restart marker.
+            loop {
+                let byte = *bytes.get(pos)?;
+                let next = *bytes.get(pos + 1)?;
+                if byte == 0xFF && next != 0x00 && !(0xD0..=0xD7).contains(&next) {
+                    break;
+                }
+                pos += 1;
+            }
+        }
+    }
+}
+
 fn apply_rotation_steps(img: DynamicImage, extra_rotation_cw: u8) -> DynamicImage {
     match extra_rotation_cw % 4 {
         0 => img,
@@ -107,7 +401,7 @@ fn apply_rotation_steps(img: DynamicImage, extra_rotation_cw: u8) -> DynamicImag
     }
 }
 
-fn apply_exif_orientation(img: DynamicImage, orientation: u16) -> DynamicImage {
+pub fn apply_exif_orientation(img: DynamicImage, orientation: u16) -> DynamicImage {
     match orientation {
         2 => img.fliph(),
         3 => img.rotate180(),
@@ -122,18 +416,52 @@ fn apply_exif_orientation(img: DynamicImage, orientation: u16) -> DynamicImage {
     }
 }
 
-fn needs_dimension_swap(orientation: u16) -> bool {
+/// Returns the EXIF orientation that shows an image `orientation` would show,
+/// turned a further `rotation_cw` quarter turns clockwise.
+pub fn rotate_orientation(orientation: u16, rotation_cw: u8) -> u16 {
+    // (clockwise quarter turns, mirrored) in the order `apply_exif_orientation`
+    // applies them: rotate first, then flip horizontally.
+    const TRANSFORMS: [(u8, bool, u16); 8] = [
+        (0, false, 1),
+        (0, true, 2),
+        (2, false, 3),
+        (2, true, 4),
+        (1, true, 5),
+        (1, false, 6),
+        (3, true, 7),
+        (3, false, 8),
+    ];
+    let (turns, mirrored) = TRANSFORMS
+        .iter()
+        .find(|(_, _, value)| *value == orientation)
+        .map(|(turns, mirrored, _)| (*turns, *mirrored))
+        .unwrap_or((0, false));
+    // A horizontal flip reverses the direction of any later rotation.
+    let turns = if mirrored {
+        (turns + 4 - rotation_cw % 4) % 4
+    } else {
+        (turns + rotation_cw) % 4
+    };
+    TRANSFORMS
+        .iter()
+        .find(|(t, m, _)| *t == turns && *m == mirrored)
+        .map(|(_, _, value)| *value)
+        .unwrap_or(1)
+}
+
+pub fn needs_dimension_swap(orientation: u16) -> bool {
     matches!(orientation, 5..=8)
 }
 
-fn read_exif_orientation_from_path(path: &Path) -> Option<u16> {
+/// Reads the EXIF orientation tag from the head of a file, if present.
+pub fn read_exif_orientation(path: &Path) -> Option<u16> {
     let mut file = File::open(path).ok()?;
     let mut buf = vec![0u8; 256 * 1024];
     let read = file.read(&mut buf).ok()?;
     read_exif_orientation_from_bytes(&buf[..read])
 }
 
-fn read_exif_orientation_from_bytes(bytes: &[u8]) -> Option<u16> {
+pub fn read_exif_orientation_from_bytes(bytes: &[u8]) -> Option<u16> {
     if bytes.len() < 4 {
         return None;
     }
@@ -142,6 +470,10 @@ fn read_exif_orientation_from_bytes(bytes: &[u8]) -> Option<u16> {
         return parse_jpeg_exif_orientation(bytes);
     }
 
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return parse_png_exif_orientation(bytes);
+    }
+
     if bytes.starts_with(b"II*\0") || bytes.starts_with(b"MM\0*") {
         return parse_tiff_orientation(bytes);
     }
@@ -199,6 +531,23 @@ fn parse_jpeg_exif_orientation(bytes: &[u8]) -> Option<u16> {
     None
 }
 
+/// Reads the orientation from a PNG `eXIf` chunk, which holds a bare TIFF block.
+fn parse_png_exif_orientation(bytes: &[u8]) -> Option<u16> {
+    let mut pos = 8usize;
+    while pos + 8 <= bytes.len() {
+        let len = u32::from_be_bytes(bytes[pos..pos + 4].try_into().ok()?) as usize;
+        let kind = &bytes[pos + 4..pos + 8];
+        let start = pos + 8;
+        let end = start.checked_add(len)?;
+        match kind {
+            b"eXIf" => return parse_tiff_orientation(bytes.get(start..end)?),
+            b"IEND" => break,
+            _ => pos = end.checked_add(4)?,
+        }
+    }
+    None
+}
+
 fn parse_jpeg_exif_thumbnail(bytes: &[u8]) -> Option<&[u8]> {
     let mut pos = 2usize;
 
@@ -402,6 +751,7 @@ fn read_u32(data: &[u8], offset: usize, endian: Endian) -> Option<u32> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use image::GenericImageView;
 
     #[test]
     fn parses_tiff_orientation() {
@@ -419,4 +769,104 @@ mod tests {
         assert_eq!(parse_tiff_orientation(&tiff), Some(6));
         assert!(needs_dimension_swap(6));
     }
+
+    fn encode_jpeg(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        DynamicImage::ImageRgb8(image::RgbImage::new(width, height))
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Jpeg)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn raw_files_decode_their_largest_embedded_preview() {
+        // TIFF header with Orientation=6, followed by a small and a large preview.
+        let mut raw = vec![
+            b'I', b'I', 42, 0, 8, 0, 0, 0, 1, 0, 0x12, 0x01, 3, 0, 1, 0, 0, 0, 6, 0, 0, 0, 0, 0, 0,
+            0,
+        ];
+        raw.extend_from_slice(&[0u8; 64]);
+        raw.extend(encode_jpeg(8, 4));
+        raw.extend_from_slice(&[0xFF; 16]);
+        raw.extend(encode_jpeg(64, 32));
+        raw.extend_from_slice(&[0u8; 64]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("shot.NEF");
+        std::fs::write(&path, &raw).unwrap();
+
+        assert!(is_raw_path(&path));
+        assert_eq!(embedded_jpeg_streams(&raw, false).len(), 2);
+        // A prefix ending inside the large preview still measures it.
+        let head = &raw[..raw.len() - 100];
+        assert_eq!(largest_raw_preview(head, false).unwrap().1, (8, 4));
+        assert_eq!(largest_raw_preview(head, true).unwrap().1, (64, 32));
+        assert_eq!(read_dimensions(&path).unwrap(), (32, 64));
+        assert_eq!(open_image(&path).unwrap().dimensions(), (32, 64));
+        let (preview, w, h) = open_embedded_jpeg_preview_with_rotation(&path, 1).unwrap();
+        assert_eq!(preview.dimensions(), (64, 32));
+        assert_eq!((w, h), (32, 64));
+    }
+
+    #[test]
+    fn rotate_orientation_matches_rotating_pixels() {
+        let mut source = image::RgbImage::new(3, 2);
+        for (i, pixel) in source.pixels_mut().enumerate() {
+            *pixel = image::Rgb([i as u8, 0, 0]);
+        }
+        let source = DynamicImage::ImageRgb8(source);
+
+        for orientation in 1..=8 {
+            for turns in 0..4 {
+                let expected = apply_rotation_steps(
+                    apply_exif_orientation(source.clone(), orientation),
+                    turns,
+                );
+                let combined =
+                    apply_exif_orientation(source.clone(), rotate_orientation(orientation, turns));
+                assert_eq!(
+                    combined.to_rgb8(),
+                    expected.to_rgb8(),
+                    "orientation {} + {} turns",
+                    orientation,
+                    turns
+                );
+            }
+        }
+    }
+
+    fn encode_gif(delays_ms: &[u32]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = image::codecs::gif::GifEncoder::new(&mut bytes);
+            for &delay in delays_ms {
+                let frame = Frame::from_parts(
+                    RgbaImage::new(4, 2),
+                    0,
+                    0,
+                    image::Delay::from_numer_denom_ms(delay, 1),
+                );
+                encoder.encode_frame(frame).unwrap();
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn only_multi_frame_images_decode_as_animations() {
+        let frames = decode_animation_bytes(&encode_gif(&[50, 0]), 1)
+            .unwrap()
+            .unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].image.dimensions(), (2, 4));
+        assert_eq!(frames[0].delay, Duration::from_millis(50));
+        assert_eq!(frames[1].delay, DEFAULT_FRAME_DELAY);
+
+        assert!(decode_animation_bytes(&encode_gif(&[50]), 0)
+            .unwrap()
+            .is_none());
+        assert!(decode_animation_bytes(&encode_jpeg(4, 2), 0)
+            .unwrap()
+            .is_none());
+    }
 }
@@ -0,0 +1,126 @@
+use crate::models::{MediaItem, RowItem, RowModel};
+
+/// Configuration for the uniform grid layout.
+///
+/// Every item, folder or media, gets the same square cell regardless of its
+/// aspect ratio. The column count follows from the viewport width and the
+/// cells are stretched so the columns fill the row exactly.
+#[derive(Debug, Clone)]
+pub struct GridLayout {
+    /// Target cell edge in pixels (default: 220)
+    pub cell_size: f32,
+    /// Spacing between cells and above each row in pixels (default: 0)
+    pub gutter_px: f32,
+}
+
+impl Default for GridLayout {
+    fn default() -> Self {
+        Self {
+            cell_size: 220.0,
+            gutter_px: 0.0,
+        }
+    }
+}
+
+impl GridLayout {
+    /// Number of columns that fit `viewport_width` (at least one).
+    pub fn columns(&self, viewport_width: f32) -> usize {
+        let cell = self.cell_size.max(1.0) + self.gutter_px;
+        (((viewport_width.max(1.0) + self.gutter_px) / cell).floor() as usize).max(1)
+    }
+
+    /// Edge length of each cell once the columns are stretched to the width.
+    fn cell_edge(&self, viewport_width: f32, columns: usize) -> f32 {
+        let gaps = self.gutter_px * columns.saturating_sub(1) as f32;
+        ((viewport_width - gaps) / columns as f32).floor().max(1.0)
+    }
+
+    /// Computes rows of uniform square cells for a list of media items.
+    pub fn compute(&self, items: &[MediaItem], viewport_width: f32) -> Vec<RowModel> {
+        if items.is_empty() || viewport_width <= 0.0 {
+            return Vec::new();
+        }
+
+        let columns = self.columns(viewport_width);
+        let edge = self.cell_edge(viewport_width, columns);
+        let gutter = self.gutter_px.max(0.0);
+        items
+            .chunks(columns)
+            .enumerate()
+            .map(|(row_index, chunk)| {
+                let row_items = chunk
+                    .iter()
+                    .map(|item| RowItem {
+                        media_path: item.path.clone(),
+                        display_w: edge,
+                        display_h: edge,
+                        offset_top: gutter,
+                        is_folder: item.is_folder(),
+                    })
+                    .collect();
+                RowModel::new(row_index as u32, edge + gutter, row_items).with_gutter(gutter)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn items(count: usize) -> Vec<MediaItem> {
+        (0..count)
+            .map(|i| {
+                if i == 0 {
+                    MediaItem::new_folder(PathBuf::from("/dir"))
+                } else {
+                    MediaItem::new(PathBuf::from(format!("/{}.jpg", i)), 1000 + i as u32, 300)
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_columns_follow_width() {
+        let layout = GridLayout::default();
+        assert_eq!(layout.columns(100.0), 1);
+        assert_eq!(layout.columns(439.0), 1);
+        assert_eq!(layout.columns(440.0), 2);
+        assert_eq!(layout.columns(1200.0), 5);
+    }
+
+    #[test]
+    fn test_uniform_cells_fill_width() {
+        let layout = GridLayout::default();
+        let rows = layout.compute(&items(12), 1200.0);
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].items.len(), 5);
+        assert_eq!(rows[2].items.len(), 2);
+        assert!(rows[0].items[0].is_folder);
+        for row in &rows {
+            assert_eq!(row.height_px, 240.0);
+            for item in &row.items {
+                assert_eq!((item.display_w, item.display_h), (240.0, 240.0));
+            }
+        }
+        let total: f32 = rows[0].items.iter().map(|i| i.display_w).sum();
+        assert!(total <= 1200.0);
+    }
+
+    #[test]
+    fn test_gutter_is_left_between_cells() {
+        let layout = GridLayout {
+            cell_size: 100.0,
+            gutter_px: 10.0,
+        };
+        // 4 * 100 + 3 * 10 = 430
+        assert_eq!(layout.columns(430.0), 4);
+        let rows = layout.compute(&items(4), 430.0);
+        assert_eq!(rows[0].items[0].display_w, 100.0);
+        assert_eq!(rows[0].items[0].offset_top, 10.0);
+        assert_eq!(rows[0].height_px, 110.0);
+        assert_eq!(rows[0].gutter_px, 10.0);
+    }
+}
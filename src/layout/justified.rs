@@ -2,8 +2,8 @@ use crate::models::{MediaItem, RowItem, RowModel};
 
 /// Configuration for the dense flow layout algorithm.
 ///
-/// Items are placed left-to-right with fixed tile height and zero spacing.
-/// Width follows source aspect ratio.
+/// Items are placed left-to-right with fixed tile height, separated by an
+/// optional gutter. Width follows source aspect ratio.
 #[derive(Debug, Clone)]
 pub struct JustifiedLayout {
     /// Target row height in pixels (default: 220)
@@ -12,8 +12,8 @@ pub struct JustifiedLayout {
     pub min_height: f32,
     /// Maximum allowed row height in pixels (default: 10000)
     pub max_height: f32,
-    /// Gap between items in a row in pixels (default: 0)
-    pub gap: f32,
+    /// Spacing between items and above each row in pixels (default: 0)
+    pub gutter_px: f32,
 }
 
 impl Default for JustifiedLayout {
@@ -22,7 +22,7 @@ impl Default for JustifiedLayout {
             target_height: 220.0,
             min_height: 1.0,
             max_height: 10_000.0,
-            gap: 0.0,
+            gutter_px: 0.0,
         }
     }
 }
@@ -48,17 +48,30 @@ impl JustifiedLayout {
     }
 
     fn tile_offset_top(&self) -> f32 {
-        0.0
+        self.gutter_px.max(0.0)
+    }
+
+    /// Creates the default layout with a different target row height.
+    pub fn with_target_height(target_height: f32) -> Self {
+        Self {
+            target_height,
+            ..Self::default()
+        }
+    }
+
+    /// Returns this layout with `gutter_px` of spacing between items and rows.
+    pub fn with_gutter(self, gutter_px: f32) -> Self {
+        Self { gutter_px, ..self }
     }
 
     /// Creates a new JustifiedLayout with custom parameters.
     #[cfg(test)]
-    pub fn new(target_height: f32, min_height: f32, max_height: f32, gap: f32) -> Self {
+    pub fn new(target_height: f32, min_height: f32, max_height: f32, gutter_px: f32) -> Self {
         Self {
             target_height,
             min_height,
             max_height,
-            gap,
+            gutter_px,
         }
     }
 
@@ -67,7 +80,8 @@ impl JustifiedLayout {
     /// # Algorithm
     /// 1. Compute per-item tile dimensions using a shared tile height.
     /// 2. Stream items left-to-right with wrap at full viewport width.
-    /// 3. Keep inter-item spacing at zero for a continuous packed chunk.
+    /// 3. Leave `gutter_px` between items and above each row (zero packs the
+    ///    tiles into one continuous chunk).
     ///
     /// # Arguments
     /// * `items` - Slice of MediaItems to layout
@@ -90,13 +104,12 @@ impl JustifiedLayout {
         for item in items {
             let (item_w, item_h) = self.tile_dimensions(item);
             if !pending_items.is_empty() {
-                let required_width = row_width + self.gap + item_w;
+                let required_width = row_width + self.gutter_px + item_w;
                 if required_width > row_wrap_limit {
-                    rows.push(RowModel::new(
-                        row_index,
-                        row_height,
-                        std::mem::take(&mut pending_items),
-                    ));
+                    rows.push(
+                        RowModel::new(row_index, row_height, std::mem::take(&mut pending_items))
+                            .with_gutter(self.gutter_px),
+                    );
                     row_index += 1;
                     row_width = 0.0;
                     row_height = 1.0;
@@ -105,7 +118,7 @@ impl JustifiedLayout {
 
             let offset_top = self.tile_offset_top();
             if !pending_items.is_empty() {
-                row_width += self.gap;
+                row_width += self.gutter_px;
             }
             pending_items.push(RowItem {
                 media_path: item.path.clone(),
@@ -119,7 +132,9 @@ impl JustifiedLayout {
         }
 
         if !pending_items.is_empty() {
-            rows.push(RowModel::new(row_index, row_height, pending_items));
+            rows.push(
+                RowModel::new(row_index, row_height, pending_items).with_gutter(self.gutter_px),
+            );
         }
 
         rows
@@ -148,7 +163,6 @@ impl JustifiedLayout {
 
     /// Reconstructs rows from cached breaks without re-running the layout algorithm.
     /// This is O(n) in the number of items but avoids the layout computation.
-    #[cfg(test)]
     pub fn rows_from_breaks(&self, items: &[MediaItem], breaks: &[RowBreak]) -> Vec<RowModel> {
         breaks
             .iter()
@@ -172,6 +186,7 @@ impl JustifiedLayout {
                     .collect();
 
                 RowModel::new(row_idx as u32, row_height.max(brk.row_height), row_items)
+                    .with_gutter(self.gutter_px)
             })
             .collect()
     }
@@ -192,7 +207,6 @@ impl JustifiedLayout {
 
 /// Represents a row break for caching purposes.
 /// Contains only the indices and height, not the actual items.
-#[cfg(test)]
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct RowBreak {
     /// Start index in the items array (inclusive)
@@ -221,6 +235,11 @@ mod tests {
             thumb_path: None,
             thumb_w: None,
             thumb_h: None,
+            blurhash: None,
+            date_taken: None,
+            camera: None,
+            gps: None,
+            orientation: None,
             last_seen: 0,
         }
     }
@@ -355,7 +374,7 @@ mod tests {
             target_height: 220.0,
             min_height: 140.0,
             max_height: 320.0,
-            gap: 2.0,
+            gutter_px: 2.0,
         };
 
         // Create many small aspect ratio items (tall images)
@@ -385,4 +404,25 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_gutters_fit_viewport() {
+        let layout = JustifiedLayout::with_target_height(120.0).with_gutter(8.0);
+        let items: Vec<MediaItem> = (0..40)
+            .map(|i| make_item(&format!("{}.jpg", i), 800 + (i % 7) * 150, 600))
+            .collect();
+        let viewport = 1000.0;
+
+        let rows = layout.compute(&items, viewport);
+        assert!(rows.len() > 1);
+        for row in &rows {
+            let widths: f32 = row.items.iter().map(|item| item.display_w).sum();
+            let gutters = row.gutter_px * row.items.len().saturating_sub(1) as f32;
+            assert!(widths + gutters <= viewport + 0.01);
+            assert!((row.height_px - (120.0 + 8.0)).abs() < 0.01);
+            for item in &row.items {
+                assert!((item.offset_top - 8.0).abs() < 0.01);
+            }
+        }
+    }
 }
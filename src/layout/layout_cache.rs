@@ -300,6 +300,11 @@ mod tests {
             thumb_path: None,
             thumb_w: None,
             thumb_h: None,
+            blurhash: None,
+            date_taken: None,
+            camera: None,
+            gps: None,
+            orientation: None,
             last_seen: 0,
         }
     }
@@ -1,6 +1,94 @@
+pub mod grid;
 pub mod justified;
 
 #[cfg(test)]
 pub mod layout_cache;
 
-pub use justified::JustifiedLayout;
+pub use grid::GridLayout;
+pub use justified::{JustifiedLayout, RowBreak};
+
+use crate::models::MediaItem;
+use xxhash_rust::xxh3::Xxh3;
+
+/// Which layout engine arranges the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LayoutMode {
+    /// Rows of tiles sized by aspect ratio (`JustifiedLayout`).
+    #[default]
+    Justified,
+    /// Uniform square cells (`GridLayout`).
+    Grid,
+}
+
+/// Hash of everything the layout depends on: item order, paths, dimensions
+/// and kind. Used to validate layouts persisted in the media store.
+pub fn layout_list_hash(items: &[MediaItem]) -> String {
+    let mut hasher = Xxh3::new();
+    for item in items {
+        hasher.update(item.path.as_os_str().as_encoded_bytes());
+        hasher.update(&item.width.to_le_bytes());
+        hasher.update(&item.height.to_le_bytes());
+        hasher.update(&[item.is_video() as u8, item.is_folder() as u8]);
+    }
+    format!("{:016x}", hasher.digest())
+}
+
+/// Rebuilds rows from persisted breaks, or `None` if they do not cover
+/// `items` exactly.
+pub fn rows_from_cached_breaks(
+    layout: &JustifiedLayout,
+    items: &[MediaItem],
+    breaks: &[RowBreak],
+) -> Option<Vec<crate::models::RowModel>> {
+    let mut expected_start = 0;
+    for brk in breaks {
+        if brk.start_index != expected_start || brk.end_index <= brk.start_index {
+            return None;
+        }
+        expected_start = brk.end_index;
+    }
+    if expected_start != items.len() {
+        return None;
+    }
+    Some(layout.rows_from_breaks(items, breaks))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_list_hash_tracks_order_and_dimensions() {
+        let a = MediaItem::new(PathBuf::from("/a.jpg"), 100, 50);
+        let b = MediaItem::new(PathBuf::from("/b.jpg"), 80, 80);
+        let base = layout_list_hash(&[a.clone(), b.clone()]);
+        assert_eq!(base, layout_list_hash(&[a.clone(), b.clone()]));
+        assert_ne!(base, layout_list_hash(&[b.clone(), a.clone()]));
+
+        let mut resized = b.clone();
+        resized.width = 81;
+        assert_ne!(base, layout_list_hash(&[a, resized]));
+    }
+
+    #[test]
+    fn test_rows_from_cached_breaks_rejects_mismatched_breaks() {
+        let layout = JustifiedLayout::default();
+        let items: Vec<MediaItem> = (0..4)
+            .map(|i| MediaItem::new(PathBuf::from(format!("/{}.jpg", i)), 100, 100))
+            .collect();
+        let brk = |start, end| RowBreak {
+            start_index: start,
+            end_index: end,
+            row_height: 220.0,
+        };
+
+        let rows = rows_from_cached_breaks(&layout, &items, &[brk(0, 3), brk(3, 4)]).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].items[0].media_path, PathBuf::from("/3.jpg"));
+
+        assert!(rows_from_cached_breaks(&layout, &items, &[brk(0, 3)]).is_none());
+        assert!(rows_from_cached_breaks(&layout, &items, &[brk(0, 2), brk(3, 4)]).is_none());
+        assert!(rows_from_cached_breaks(&layout, &items, &[brk(0, 5)]).is_none());
+    }
+}
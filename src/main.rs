@@ -2,10 +2,19 @@
 
 mod app;
 mod bench;
+mod color;
+mod dbus;
+mod dylib;
+mod external_app;
+mod file_ops;
+mod heif;
 mod image_loader;
 mod layout;
+mod media_info;
+mod metadata_editor;
 mod models;
 mod scanner;
+mod svg;
 mod thumbnails;
 mod ui;
 
@@ -24,6 +33,7 @@ fn main() {
                 .add_directive("idxd=info".parse().unwrap()),
         )
         .init();
+    models::register_extra_extensions();
 
     match bench::maybe_parse_args() {
         Ok(Some(args)) => match bench::run_benchmark(args) {
@@ -40,6 +50,23 @@ fn main() {
         }
     }
 
-    let app = IdxdApp::new();
+    match bench::generate::maybe_parse_args() {
+        Ok(Some(args)) => match bench::generate::run_generate_thumbnails(args) {
+            Ok(code) => std::process::exit(code),
+            Err(e) => {
+                eprintln!("Thumbnail generation failed: {e:#}");
+                std::process::exit(1);
+            }
+        },
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("Invalid thumbnail generation arguments: {e:#}");
+            std::process::exit(2);
+        }
+    }
+
+    let has_flag = |flag: &str| std::env::args().skip(1).any(|arg| arg == flag);
+    let file_list = has_flag(app::STDIN_FLAG).then(|| app::read_file_list(std::io::stdin().lock()));
+    let app = IdxdApp::new(file_list, has_flag(app::NEW_WINDOW_FLAG));
     std::process::exit(app.run());
 }
@@ -0,0 +1,296 @@
+//! Read-only capture details shown in the viewer's info panel.
+//!
+//! Parsing happens off the UI thread; the viewer caches results by path so
+//! toggling the panel or revisiting a file does not touch the disk again.
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use exif::{Exif, In, Reader as ExifReader, Tag, Value};
+
+/// File facts plus the common EXIF tags photographers cull by.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MediaInfo {
+    pub file_size: Option<u64>,
+    pub dimensions: Option<(u32, u32)>,
+    pub camera: Option<String>,
+    pub lens: Option<String>,
+    pub iso: Option<u32>,
+    pub exposure: Option<String>,
+    pub aperture: Option<String>,
+    pub focal_length: Option<String>,
+    pub date_taken: Option<String>,
+    /// Latitude and longitude in decimal degrees, north and east positive.
+    pub gps: Option<(f64, f64)>,
+}
+
+/// The EXIF details the scanner stores with each file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CaptureDetails {
+    pub date_taken: Option<String>,
+    pub camera: Option<String>,
+    pub gps: Option<(f64, f64)>,
+}
+
+impl MediaInfo {
+    /// Returns true if any EXIF tag was found.
+    pub fn has_exif(&self) -> bool {
+        self.camera.is_some()
+            || self.lens.is_some()
+            || self.iso.is_some()
+            || self.exposure.is_some()
+            || self.aperture.is_some()
+            || self.focal_length.is_some()
+            || self.date_taken.is_some()
+            || self.gps.is_some()
+    }
+
+    /// Label/value pairs in display order, skipping missing values.
+    pub fn rows(&self) -> Vec<(&'static str, String)> {
+        let mut rows = Vec::new();
+        if let Some(size) = self.file_size {
+            rows.push(("Size", format_file_size(size)));
+        }
+        if let Some((w, h)) = self.dimensions {
+            rows.push(("Dimensions", format!("{}x{}", w, h)));
+        }
+        let tags = [
+            ("Date", self.date_taken.clone()),
+            ("Camera", self.camera.clone()),
+            ("Lens", self.lens.clone()),
+            ("ISO", self.iso.map(|iso| iso.to_string())),
+            ("Shutter", self.exposure.clone()),
+            ("Aperture", self.aperture.clone()),
+            ("Focal length", self.focal_length.clone()),
+            (
+                "Location",
+                self.gps.map(|(lat, lon)| format_coordinates(lat, lon)),
+            ),
+        ];
+        rows.extend(
+            tags.into_iter()
+                .filter_map(|(label, value)| value.map(|v| (label, v))),
+        );
+        rows
+    }
+}
+
+/// Reads file size, pixel dimensions, and EXIF tags. Missing pieces are left
+/// empty; this never fails.
+pub fn read_media_info(path: &Path, is_video: bool) -> MediaInfo {
+    let mut info = MediaInfo {
+        file_size: std::fs::metadata(path).ok().map(|m| m.len()),
+        ..Default::default()
+    };
+    if is_video {
+        return info;
+    }
+    info.dimensions = crate::image_loader::read_dimensions(path).ok();
+
+    let Some(exif) = read_exif(path) else {
+        return info;
+    };
+    let field = |tag: Tag| exif.get_field(tag, In::PRIMARY).map(|f| &f.value);
+
+    info.camera = camera_name(&exif);
+    info.lens = field(Tag::LensModel).and_then(ascii_value);
+    info.iso = field(Tag::PhotographicSensitivity).and_then(|v| v.get_uint(0));
+    info.exposure = field(Tag::ExposureTime)
+        .and_then(first_rational)
+        .and_then(|(num, denom)| format_exposure(num, denom));
+    info.aperture = field(Tag::FNumber)
+        .and_then(first_rational)
+        .filter(|(_, denom)| *denom > 0)
+        .map(|(num, denom)| format!("f/{:.1}", num as f64 / denom as f64));
+    info.focal_length = field(Tag::FocalLength)
+        .and_then(first_rational)
+        .filter(|(_, denom)| *denom > 0)
+        .map(|(num, denom)| format!("{:.0} mm", num as f64 / denom as f64));
+    info.date_taken = date_taken(&exif);
+    info.gps = gps_position(&exif);
+    info
+}
+
+/// Capture date, camera name and GPS position only, for the scanner to store
+/// with each file. All are `None` when the file carries no EXIF.
+pub fn read_capture_details(path: &Path) -> CaptureDetails {
+    match read_exif(path) {
+        Some(exif) => CaptureDetails {
+            date_taken: date_taken(&exif),
+            camera: camera_name(&exif),
+            gps: gps_position(&exif),
+        },
+        None => CaptureDetails::default(),
+    }
+}
+
+fn read_exif(path: &Path) -> Option<Exif> {
+    let file = File::open(path).ok()?;
+    ExifReader::new()
+        .read_from_container(&mut BufReader::new(file))
+        .ok()
+}
+
+fn camera_name(exif: &Exif) -> Option<String> {
+    let field = |tag: Tag| exif.get_field(tag, In::PRIMARY).map(|f| &f.value);
+    let make = field(Tag::Make).and_then(ascii_value);
+    let model = field(Tag::Model).and_then(ascii_value);
+    match (make, model) {
+        // Many models already start with the maker's name.
+        (Some(make), Some(model)) if model.starts_with(&make) => Some(model),
+        (Some(make), Some(model)) => Some(format!("{} {}", make, model)),
+        (make, model) => make.or(model),
+    }
+}
+
+fn date_taken(exif: &Exif) -> Option<String> {
+    exif.get_field(Tag::DateTimeOriginal, In::PRIMARY)
+        .and_then(|f| ascii_value(&f.value))
+        .map(|date| crate::metadata_editor::display_date_taken(&date))
+}
+
+/// Reads the GPS latitude and longitude. Files without a fix, or whose
+/// receiver wrote zeros for both, report `None`.
+fn gps_position(exif: &Exif) -> Option<(f64, f64)> {
+    let field = |tag: Tag| exif.get_field(tag, In::PRIMARY).map(|f| &f.value);
+    let lat = dms_degrees(field(Tag::GPSLatitude)?, field(Tag::GPSLatitudeRef), b'S')?;
+    let lon = dms_degrees(field(Tag::GPSLongitude)?, field(Tag::GPSLongitudeRef), b'W')?;
+    let valid = lat.abs() <= 90.0 && lon.abs() <= 180.0 && (lat, lon) != (0.0, 0.0);
+    valid.then_some((lat, lon))
+}
+
+/// Converts EXIF degrees/minutes/seconds rationals to decimal degrees,
+/// negated when the reference is the `negative` hemisphere letter.
+fn dms_degrees(value: &Value, reference: Option<&Value>, negative: u8) -> Option<f64> {
+    let Value::Rational(parts) = value else {
+        return None;
+    };
+    if parts.is_empty() {
+        return None;
+    }
+    let mut degrees = 0.0;
+    for (part, scale) in parts.iter().zip([1.0, 60.0, 3600.0]) {
+        if part.denom == 0 {
+            return None;
+        }
+        degrees += part.num as f64 / part.denom as f64 / scale;
+    }
+    let southern_or_western = matches!(
+        reference,
+        Some(Value::Ascii(refs)) if refs.first().and_then(|r| r.first()) == Some(&negative)
+    );
+    Some(if southern_or_western {
+        -degrees
+    } else {
+        degrees
+    })
+}
+
+/// `48.85837° N, 2.29448° E`
+pub fn format_coordinates(lat: f64, lon: f64) -> String {
+    format!(
+        "{:.5}° {}, {:.5}° {}",
+        lat.abs(),
+        if lat < 0.0 { 'S' } else { 'N' },
+        lon.abs(),
+        if lon < 0.0 { 'W' } else { 'E' }
+    )
+}
+
+/// Formats an exposure time as photographers write it: `1/250 s` below one
+/// second, `2.5 s` above.
+fn format_exposure(num: u32, denom: u32) -> Option<String> {
+    if num == 0 || denom == 0 {
+        return None;
+    }
+    if num < denom {
+        Some(format!("1/{} s", (denom as f64 / num as f64).round()))
+    } else {
+        let secs = num as f64 / denom as f64;
+        Some(format!("{} s", (secs * 10.0).round() / 10.0))
+    }
+}
+
+pub fn format_file_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+fn first_rational(value: &Value) -> Option<(u32, u32)> {
+    match value {
+        Value::Rational(values) => values.first().map(|r| (r.num, r.denom)),
+        _ => None,
+    }
+}
+
+fn ascii_value(value: &Value) -> Option<String> {
+    match value {
+        Value::Ascii(parts) => parts
+            .first()
+            .map(|bytes| String::from_utf8_lossy(bytes).trim().to_string())
+            .filter(|s| !s.is_empty()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_exposure_uses_fractions_below_one_second() {
+        assert_eq!(format_exposure(1, 250).as_deref(), Some("1/250 s"));
+        assert_eq!(format_exposure(10, 600).as_deref(), Some("1/60 s"));
+        assert_eq!(format_exposure(5, 2).as_deref(), Some("2.5 s"));
+        assert_eq!(format_exposure(0, 1), None);
+    }
+
+    #[test]
+    fn dms_degrees_applies_hemisphere() {
+        let dms = |d, m, s| Value::Rational(vec![(d, 1).into(), (m, 1).into(), (s, 100).into()]);
+        let reference = |r: &str| Value::Ascii(vec![r.as_bytes().to_vec()]);
+
+        let north = dms_degrees(&dms(48, 51, 2940), Some(&reference("N")), b'S').unwrap();
+        assert!((north - 48.858167).abs() < 1e-6);
+        let west = dms_degrees(&dms(2, 17, 4000), Some(&reference("W")), b'W').unwrap();
+        assert!((west + 2.294444).abs() < 1e-6);
+        assert_eq!(dms_degrees(&dms(0, 0, 0), None, b'S'), Some(0.0));
+        let no_fix = Value::Rational(vec![(0, 0).into(), (0, 0).into(), (0, 0).into()]);
+        assert_eq!(dms_degrees(&no_fix, None, b'S'), None);
+        assert_eq!(
+            format_coordinates(-33.856, 151.2153),
+            "33.85600° S, 151.21530° E"
+        );
+    }
+
+    #[test]
+    fn format_file_size_picks_unit() {
+        assert_eq!(format_file_size(512), "512 B");
+        assert_eq!(format_file_size(1536), "1.5 KB");
+        assert_eq!(format_file_size(3 * 1024 * 1024), "3.0 MB");
+    }
+
+    #[test]
+    fn png_without_exif_reports_only_file_facts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("plain.png");
+        image::RgbImage::new(4, 3).save(&path).unwrap();
+
+        let info = read_media_info(&path, false);
+        assert!(!info.has_exif());
+        assert_eq!(info.dimensions, Some((4, 3)));
+        assert!(info.file_size.is_some_and(|size| size > 0));
+        assert_eq!(info.rows().len(), 2);
+    }
+}
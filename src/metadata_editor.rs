@@ -0,0 +1,632 @@
+//! Opt-in editing of EXIF/XMP metadata fields.
+//!
+//! Only the metadata containers are rewritten: compressed image data is copied
+//! through byte-for-byte, so an edit never re-encodes pixels. Which fields are
+//! offered depends on what can be stored safely for the file's format.
+
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use exif::experimental::Writer as ExifWriter;
+use exif::{Field, In, Reader as ExifReader, Tag, Value};
+use img_parts::jpeg::{markers, Jpeg, JpegSegment};
+use img_parts::png::{Png, PngChunk};
+use img_parts::{Bytes, DynImage, ImageEXIF};
+use tracing::{debug, info};
+
+/// Namespace header that prefixes an XMP packet inside a JPEG APP1 segment.
+const XMP_JPEG_PREFIX: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+/// Largest payload a single JPEG marker segment can carry.
+const MAX_JPEG_SEGMENT_PAYLOAD: usize = 65533;
+/// PNG chunks that describe how the pixels are encoded. A re-encoded image
+/// brings its own, so these are never carried over from the original.
+const PNG_LAYOUT_CHUNKS: &[[u8; 4]] = &[
+    *b"IHDR", *b"PLTE", *b"IDAT", *b"IEND", *b"tRNS", *b"bKGD", *b"sBIT", *b"hIST", *b"acTL",
+    *b"fcTL", *b"fdAT",
+];
+
+/// A metadata field that can be written back to a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditableField {
+    /// EXIF `DateTimeOriginal`.
+    DateTaken,
+    /// EXIF `ImageDescription`.
+    Description,
+    /// XMP `dc:subject` keyword bag.
+    Keywords,
+}
+
+impl EditableField {
+    pub fn label(self) -> &'static str {
+        match self {
+            EditableField::DateTaken => "Date taken",
+            EditableField::Description => "Description",
+            EditableField::Keywords => "Keywords",
+        }
+    }
+}
+
+/// Editable metadata values for a single file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EditableMetadata {
+    /// Capture date in EXIF form (`YYYY:MM:DD HH:MM:SS`).
+    pub date_taken: Option<String>,
+    pub description: Option<String>,
+    pub keywords: Vec<String>,
+}
+
+/// Returns the fields that can be written safely for the given file.
+///
+/// JPEG carries both an EXIF and an XMP segment; PNG and WebP only get the
+/// EXIF chunk. Every other format is read-only.
+pub fn editable_fields(path: &Path) -> &'static [EditableField] {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "jpg" | "jpeg" => &[
+            EditableField::DateTaken,
+            EditableField::Description,
+            EditableField::Keywords,
+        ],
+        "png" | "webp" => &[EditableField::DateTaken, EditableField::Description],
+        _ => &[],
+    }
+}
+
+/// Returns true if any metadata field can be written for the given file.
+pub fn can_edit(path: &Path) -> bool {
+    !editable_fields(path).is_empty()
+}
+
+/// Reads the current values of the editable fields from the file.
+pub fn read_metadata(path: &Path) -> Result<EditableMetadata> {
+    let image = load_container(path)?;
+    let mut metadata = EditableMetadata::default();
+
+    if let Some(raw) = image.exif() {
+        if let Ok(exif) = ExifReader::new().read_raw(raw.to_vec()) {
+            metadata.date_taken = exif
+                .get_field(Tag::DateTimeOriginal, In::PRIMARY)
+                .and_then(|f| ascii_value(&f.value));
+            metadata.description = exif
+                .get_field(Tag::ImageDescription, In::PRIMARY)
+                .and_then(|f| ascii_value(&f.value));
+        }
+    }
+
+    if let DynImage::Jpeg(jpeg) = &image {
+        if let Some(packet) = find_xmp_packet(jpeg) {
+            metadata.keywords = parse_xmp_keywords(&packet);
+        }
+    }
+
+    Ok(metadata)
+}
+
+/// Writes the editable fields back to the file, replacing it atomically.
+///
+/// Fields that the format cannot carry are ignored. Existing EXIF tags other
+/// than the edited ones are preserved.
+pub fn write_metadata(path: &Path, metadata: &EditableMetadata) -> Result<()> {
+    let fields = editable_fields(path);
+    if fields.is_empty() {
+        bail!("Metadata editing is not supported for {}", path.display());
+    }
+
+    let mut image = load_container(path)?;
+    let mut edited: Vec<Field> = Vec::new();
+    if let Some(date) = metadata.date_taken.as_deref() {
+        edited.push(Field {
+            tag: Tag::DateTimeOriginal,
+            ifd_num: In::PRIMARY,
+            value: Value::Ascii(vec![date.as_bytes().to_vec()]),
+        });
+    }
+    if let Some(description) = metadata.description.as_deref() {
+        edited.push(Field {
+            tag: Tag::ImageDescription,
+            ifd_num: In::PRIMARY,
+            value: Value::Ascii(vec![description.as_bytes().to_vec()]),
+        });
+    }
+    let exif = build_exif(
+        image.exif(),
+        &[Tag::DateTimeOriginal, Tag::ImageDescription],
+        &edited,
+    )?;
+
+    match &mut image {
+        DynImage::Jpeg(jpeg) => {
+            if let Some(exif) = &exif {
+                if exif.len() + 6 > MAX_JPEG_SEGMENT_PAYLOAD {
+                    bail!("EXIF block too large for a JPEG segment");
+                }
+            }
+            if jpeg.segments().len() < 3 {
+                bail!("JPEG has too few segments to place metadata safely");
+            }
+            jpeg.set_exif(exif);
+            if fields.contains(&EditableField::Keywords) {
+                set_xmp_keywords(jpeg, &metadata.keywords)?;
+            }
+        }
+        other => other.set_exif(exif),
+    }
+
+    crate::file_ops::replace_file(path, |writer| {
+        image
+            .encoder()
+            .write_to(writer)
+            .context("Failed to encode metadata")?;
+        Ok(())
+    })?;
+
+    info!("Wrote metadata to {}", path.display());
+    Ok(())
+}
+
+/// Replaces the EXIF orientation tag of a JPEG, leaving the pixels untouched.
+pub fn write_orientation(path: &Path, orientation: u16) -> Result<()> {
+    let mut image = load_container(path)?;
+    let DynImage::Jpeg(jpeg) = &mut image else {
+        bail!("Orientation can only be written to JPEG files");
+    };
+    let field = Field {
+        tag: Tag::Orientation,
+        ifd_num: In::PRIMARY,
+        value: Value::Short(vec![orientation]),
+    };
+    let exif = build_exif(jpeg.exif(), &[Tag::Orientation], &[field])?;
+    if let Some(exif) = &exif {
+        if exif.len() + 6 > MAX_JPEG_SEGMENT_PAYLOAD {
+            bail!("EXIF block too large for a JPEG segment");
+        }
+    }
+    if jpeg.segments().len() < 3 {
+        bail!("JPEG has too few segments to place metadata safely");
+    }
+    jpeg.set_exif(exif);
+
+    crate::file_ops::replace_file(path, |writer| {
+        image
+            .encoder()
+            .write_to(writer)
+            .context("Failed to encode orientation")?;
+        Ok(())
+    })?;
+
+    info!("Wrote orientation {} to {}", orientation, path.display());
+    Ok(())
+}
+
+/// Copies the ancillary chunks of `original` (ICC profile, EXIF, text, ...)
+/// into `encoded`, a re-encoding of its pixels that was written upright. The
+/// EXIF orientation is reset to 1 to match.
+pub fn carry_png_metadata(original: &[u8], encoded: Vec<u8>) -> Result<Vec<u8>> {
+    let original =
+        Png::from_bytes(Bytes::copy_from_slice(original)).context("Failed to parse PNG")?;
+    let mut png = Png::from_bytes(Bytes::from(encoded)).context("Failed to parse encoded PNG")?;
+
+    let mut carried: Vec<PngChunk> = Vec::new();
+    for chunk in original.chunks() {
+        if PNG_LAYOUT_CHUNKS.contains(&chunk.kind()) {
+            continue;
+        }
+        if chunk.kind() == *b"eXIf" {
+            let upright = Field {
+                tag: Tag::Orientation,
+                ifd_num: In::PRIMARY,
+                value: Value::Short(vec![1]),
+            };
+            if let Some(exif) = build_exif(
+                Some(chunk.contents().clone()),
+                &[Tag::Orientation],
+                &[upright],
+            )? {
+                carried.push(PngChunk::new(*b"eXIf", exif));
+            }
+            continue;
+        }
+        carried.push(chunk.clone());
+    }
+
+    // Color and EXIF chunks must precede the image data, so everything goes
+    // straight after IHDR, replacing whatever the encoder wrote itself.
+    let chunks = png.chunks_mut();
+    chunks.retain(|chunk| !carried.iter().any(|c| c.kind() == chunk.kind()));
+    let after_header = chunks.len().min(1);
+    chunks.splice(after_header..after_header, carried);
+
+    let mut out = Vec::new();
+    png.encoder()
+        .write_to(&mut out)
+        .context("Failed to encode PNG")?;
+    Ok(out)
+}
+
+/// Normalises user input into an EXIF date string.
+///
+/// Accepts `YYYY-MM-DD`, `YYYY-MM-DD HH:MM[:SS]`, the ISO `T` separator and the
+/// EXIF colon form. Returns `None` for anything out of range.
+pub fn parse_date_taken(input: &str) -> Option<String> {
+    let input = input.trim();
+    let (date, time) = match input.split_once([' ', 'T']) {
+        Some((date, time)) => (date, time.trim()),
+        None => (input, "00:00:00"),
+    };
+
+    let date_parts: Vec<&str> = date.split(['-', ':']).collect();
+    let time_parts: Vec<&str> = time.split(':').collect();
+    if date_parts.len() != 3 || !(2..=3).contains(&time_parts.len()) {
+        return None;
+    }
+
+    let year: u32 = date_parts[0].parse().ok()?;
+    let month: u32 = date_parts[1].parse().ok()?;
+    let day: u32 = date_parts[2].parse().ok()?;
+    let hour: u32 = time_parts[0].parse().ok()?;
+    let minute: u32 = time_parts[1].parse().ok()?;
+    let second: u32 = match time_parts.get(2) {
+        Some(s) => s.parse().ok()?,
+        None => 0,
+    };
+
+    if !(1..=9999).contains(&year)
+        || !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return None;
+    }
+
+    Some(format!(
+        "{:04}:{:02}:{:02} {:02}:{:02}:{:02}",
+        year, month, day, hour, minute, second
+    ))
+}
+
+/// Formats an EXIF date string for display (`YYYY-MM-DD HH:MM:SS`).
+pub fn display_date_taken(exif_date: &str) -> String {
+    match exif_date.split_once(' ') {
+        Some((date, time)) => format!("{} {}", date.replace(':', "-"), time),
+        None => exif_date.replace(':', "-"),
+    }
+}
+
+/// Splits a comma-separated keyword list, dropping blanks and duplicates.
+pub fn parse_keywords(input: &str) -> Vec<String> {
+    let mut keywords: Vec<String> = Vec::new();
+    for keyword in input.split(',').map(str::trim).filter(|k| !k.is_empty()) {
+        if !keywords.iter().any(|existing| existing == keyword) {
+            keywords.push(keyword.to_string());
+        }
+    }
+    keywords
+}
+
+fn load_container(path: &Path) -> Result<DynImage> {
+    let data = std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    DynImage::from_bytes(Bytes::from(data))
+        .with_context(|| format!("Failed to parse {}", path.display()))?
+        .with_context(|| format!("Unsupported container: {}", path.display()))
+}
+
+fn ascii_value(value: &Value) -> Option<String> {
+    match value {
+        Value::Ascii(parts) => parts
+            .first()
+            .map(|bytes| String::from_utf8_lossy(bytes).trim().to_string())
+            .filter(|s| !s.is_empty()),
+        _ => None,
+    }
+}
+
+/// Rebuilds the EXIF block, dropping the `replaced` primary tags and adding
+/// the `edited` fields in their place.
+fn build_exif(
+    existing: Option<Bytes>,
+    replaced: &[Tag],
+    edited: &[Field],
+) -> Result<Option<Bytes>> {
+    let parsed = existing.and_then(|raw| ExifReader::new().read_raw(raw.to_vec()).ok());
+    let little_endian = parsed.as_ref().map(|e| e.little_endian()).unwrap_or(false);
+
+    let mut thumbnail: Option<&[u8]> = None;
+    let mut has_strip_thumbnail = false;
+    if let Some(exif) = &parsed {
+        let offset = exif
+            .get_field(Tag::JPEGInterchangeFormat, In::THUMBNAIL)
+            .and_then(|f| f.value.get_uint(0));
+        let length = exif
+            .get_field(Tag::JPEGInterchangeFormatLength, In::THUMBNAIL)
+            .and_then(|f| f.value.get_uint(0));
+        if let (Some(offset), Some(length)) = (offset, length) {
+            let start = offset as usize;
+            let end = start.saturating_add(length as usize);
+            thumbnail = exif.buf().get(start..end);
+        }
+        has_strip_thumbnail = exif.get_field(Tag::StripOffsets, In::THUMBNAIL).is_some();
+    }
+
+    let mut kept: Vec<&Field> = Vec::new();
+    if let Some(exif) = &parsed {
+        for field in exif.fields() {
+            if field.ifd_num == In::PRIMARY && replaced.contains(&field.tag) {
+                continue;
+            }
+            // Uncompressed or unreadable thumbnails cannot be carried over.
+            if field.ifd_num == In::THUMBNAIL && (thumbnail.is_none() || has_strip_thumbnail) {
+                continue;
+            }
+            if field.ifd_num != In::PRIMARY && field.ifd_num != In::THUMBNAIL {
+                continue;
+            }
+            kept.push(field);
+        }
+    }
+
+    if kept.is_empty() && edited.is_empty() {
+        return Ok(None);
+    }
+
+    let mut writer = ExifWriter::new();
+    for field in kept.iter().copied().chain(edited.iter()) {
+        writer.push_field(field);
+    }
+    if let Some(jpeg) = thumbnail.filter(|_| !has_strip_thumbnail) {
+        writer.set_jpeg(jpeg, In::THUMBNAIL);
+    }
+
+    let mut buf = std::io::Cursor::new(Vec::new());
+    writer
+        .write(&mut buf, little_endian)
+        .context("Failed to encode EXIF block")?;
+    debug!("Encoded EXIF block ({} bytes)", buf.get_ref().len());
+    Ok(Some(Bytes::from(buf.into_inner())))
+}
+
+fn find_xmp_packet(jpeg: &Jpeg) -> Option<String> {
+    jpeg.segments_by_marker(markers::APP1)
+        .find(|segment| segment.contents().starts_with(XMP_JPEG_PREFIX))
+        .map(|segment| {
+            String::from_utf8_lossy(&segment.contents()[XMP_JPEG_PREFIX.len()..]).to_string()
+        })
+}
+
+/// Replaces the keyword bag in the JPEG's XMP packet, keeping other XMP data.
+fn set_xmp_keywords(jpeg: &mut Jpeg, keywords: &[String]) -> Result<()> {
+    let existing = find_xmp_packet(jpeg);
+    let packet = match existing {
+        Some(packet) => replace_xmp_keywords(&packet, keywords),
+        None if keywords.is_empty() => return Ok(()),
+        None => new_xmp_packet(keywords),
+    };
+
+    let mut contents = Vec::with_capacity(XMP_JPEG_PREFIX.len() + packet.len());
+    contents.extend_from_slice(XMP_JPEG_PREFIX);
+    contents.extend_from_slice(packet.as_bytes());
+    if contents.len() > MAX_JPEG_SEGMENT_PAYLOAD {
+        bail!("XMP packet too large for a JPEG segment");
+    }
+
+    let segments = jpeg.segments_mut();
+    let position = segments
+        .iter()
+        .position(|s| s.marker() == markers::APP1 && s.contents().starts_with(XMP_JPEG_PREFIX));
+    let segment = JpegSegment::new_with_contents(markers::APP1, Bytes::from(contents));
+    match position {
+        Some(index) => segments[index] = segment,
+        None => {
+            // Keep XMP right after the EXIF segment (or APP0) as readers expect.
+            let index = segments
+                .iter()
+                .rposition(|s| s.marker() == markers::APP0 || s.marker() == markers::APP1)
+                .map(|i| i + 1)
+                .unwrap_or(0);
+            segments.insert(index, segment);
+        }
+    }
+    Ok(())
+}
+
+fn keyword_bag(keywords: &[String]) -> String {
+    let mut bag = String::from("<dc:subject><rdf:Bag>");
+    for keyword in keywords {
+        bag.push_str("<rdf:li>");
+        bag.push_str(&escape_xml(keyword));
+        bag.push_str("</rdf:li>");
+    }
+    bag.push_str("</rdf:Bag></dc:subject>");
+    bag
+}
+
+fn new_xmp_packet(keywords: &[String]) -> String {
+    format!(
+        "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\
+<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\
+<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\
+<rdf:Description rdf:about=\"\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\">{}</rdf:Description>\
+</rdf:RDF></x:xmpmeta><?xpacket end=\"w\"?>",
+        keyword_bag(keywords)
+    )
+}
+
+fn replace_xmp_keywords(packet: &str, keywords: &[String]) -> String {
+    let bag = if keywords.is_empty() {
+        String::new()
+    } else {
+        keyword_bag(keywords)
+    };
+
+    if let (Some(start), Some(end)) = (packet.find("<dc:subject"), packet.find("</dc:subject>")) {
+        if start < end {
+            let end = end + "</dc:subject>".len();
+            return format!("{}{}{}", &packet[..start], bag, &packet[end..]);
+        }
+    }
+    if bag.is_empty() {
+        return packet.to_string();
+    }
+    match packet.find("</rdf:RDF>") {
+        Some(index) => format!(
+            "{}<rdf:Description rdf:about=\"\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\">{}</rdf:Description>{}",
+            &packet[..index],
+            bag,
+            &packet[index..]
+        ),
+        None => new_xmp_packet(keywords),
+    }
+}
+
+fn parse_xmp_keywords(packet: &str) -> Vec<String> {
+    let Some(start) = packet.find("<dc:subject") else {
+        return Vec::new();
+    };
+    let Some(end) = packet[start..].find("</dc:subject>") else {
+        return Vec::new();
+    };
+    let subject = &packet[start..start + end];
+
+    let mut keywords = Vec::new();
+    let mut rest = subject;
+    while let Some(open) = rest.find("<rdf:li") {
+        let after_open = &rest[open..];
+        let Some(tag_end) = after_open.find('>') else {
+            break;
+        };
+        let body = &after_open[tag_end + 1..];
+        let Some(close) = body.find("</rdf:li>") else {
+            break;
+        };
+        let keyword = unescape_xml(body[..close].trim());
+        if !keyword.is_empty() {
+            keywords.push(keyword);
+        }
+        rest = &body[close..];
+    }
+    keywords
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn unescape_xml(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn parse_date_taken_accepts_common_forms() {
+        assert_eq!(
+            parse_date_taken("2024-03-09 14:05:00").as_deref(),
+            Some("2024:03:09 14:05:00")
+        );
+        assert_eq!(
+            parse_date_taken("2024:03:09 14:05:07").as_deref(),
+            Some("2024:03:09 14:05:07")
+        );
+        assert_eq!(
+            parse_date_taken("2024-03-09T14:05").as_deref(),
+            Some("2024:03:09 14:05:00")
+        );
+        assert_eq!(
+            parse_date_taken("2024-03-09").as_deref(),
+            Some("2024:03:09 00:00:00")
+        );
+        assert!(parse_date_taken("2024-13-09").is_none());
+        assert!(parse_date_taken("yesterday").is_none());
+    }
+
+    #[test]
+    fn parse_keywords_trims_and_dedupes() {
+        assert_eq!(
+            parse_keywords(" beach, sunset ,,beach"),
+            vec!["beach".to_string(), "sunset".to_string()]
+        );
+    }
+
+    #[test]
+    fn editable_fields_depend_on_format() {
+        assert_eq!(editable_fields(Path::new("a.JPG")).len(), 3);
+        assert!(!editable_fields(Path::new("a.png")).contains(&EditableField::Keywords));
+        assert!(!can_edit(Path::new("a.tiff")));
+        assert!(!can_edit(Path::new("a.mp4")));
+    }
+
+    #[test]
+    fn xmp_keywords_roundtrip_keeps_other_properties() {
+        let packet = "<x:xmpmeta><rdf:RDF><rdf:Description xmp:Rating=\"3\">\
+<dc:subject><rdf:Bag><rdf:li>old</rdf:li></rdf:Bag></dc:subject>\
+</rdf:Description></rdf:RDF></x:xmpmeta>";
+        let updated = replace_xmp_keywords(packet, &["a & b".to_string(), "c".to_string()]);
+        assert!(updated.contains("xmp:Rating=\"3\""));
+        assert_eq!(
+            parse_xmp_keywords(&updated),
+            vec!["a & b".to_string(), "c".to_string()]
+        );
+    }
+
+    #[test]
+    fn write_metadata_roundtrip_keeps_pixels() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("photo.jpg");
+        image::RgbImage::from_pixel(16, 8, image::Rgb([200, 10, 10]))
+            .save(&path)
+            .unwrap();
+
+        let edit = EditableMetadata {
+            date_taken: parse_date_taken("2020-01-02 03:04:05"),
+            description: Some("Harbour at dusk".to_string()),
+            keywords: vec!["harbour".to_string(), "dusk".to_string()],
+        };
+        write_metadata(&path, &edit).unwrap();
+
+        assert_eq!(read_metadata(&path).unwrap(), edit);
+        let decoded = image::open(&path).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (16, 8));
+    }
+
+    #[test]
+    fn write_orientation_keeps_other_tags() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("photo.jpg");
+        image::RgbImage::from_pixel(16, 8, image::Rgb([10, 200, 10]))
+            .save(&path)
+            .unwrap();
+        let edit = EditableMetadata {
+            description: Some("Pier".to_string()),
+            ..Default::default()
+        };
+        write_metadata(&path, &edit).unwrap();
+
+        write_orientation(&path, 6).unwrap();
+        assert_eq!(crate::image_loader::read_exif_orientation(&path), Some(6));
+        assert_eq!(
+            read_metadata(&path).unwrap().description.as_deref(),
+            Some("Pier")
+        );
+
+        write_orientation(&path, 1).unwrap();
+        assert_eq!(crate::image_loader::read_exif_orientation(&path), Some(1));
+    }
+}
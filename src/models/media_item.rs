@@ -1,4 +1,24 @@
-use std::path::PathBuf;
+use std::cmp::Reverse;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Image extensions decoded by the `image` crate. RAW, HEIF and SVG files
+/// are images too; their extensions live with their decoders.
+pub const IMAGE_EXTENSIONS: [&str; 8] = ["jpg", "jpeg", "png", "webp", "gif", "bmp", "tiff", "tif"];
+
+/// Video extensions, played by mpv and thumbnailed with ffmpeg. Every check
+/// for "is this a video" goes through this list.
+pub const VIDEO_EXTENSIONS: [&str; 6] = ["webm", "mp4", "m4v", "mkv", "avi", "mov"];
+
+/// Comma-separated extensions to browse on top of the built-in ones, e.g.
+/// `jxl,tga,flv:video`. A bare extension is an image.
+pub const EXTRA_EXTENSIONS_ENV: &str = "IDXD_EXTRA_EXTENSIONS";
+
+/// User extensions from `EXTRA_EXTENSIONS_ENV`, set once at startup.
+static EXTRA_EXTENSIONS: OnceLock<HashMap<String, MediaType>> = OnceLock::new();
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MediaType {
@@ -8,16 +28,229 @@ pub enum MediaType {
 }
 
 impl MediaType {
+    /// Classifies an extension using the built-in list, then the extensions
+    /// registered by `register_extra_extensions`.
     pub fn from_extension(ext: &str) -> Option<Self> {
-        match ext.to_lowercase().as_str() {
-            "jpg" | "jpeg" | "png" | "webp" | "gif" | "bmp" | "tiff" | "tif" => Some(Self::Image),
-            "webm" | "mp4" | "mkv" | "avi" | "mov" => Some(Self::Video),
+        let ext = ext.to_lowercase();
+        Self::builtin(&ext).or_else(|| EXTRA_EXTENSIONS.get()?.get(&ext).copied())
+    }
+
+    /// `from_extension` for a path's extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(Self::from_extension)
+    }
+
+    fn builtin(ext: &str) -> Option<Self> {
+        match ext {
+            ext if IMAGE_EXTENSIONS.contains(&ext) => Some(Self::Image),
+            ext if VIDEO_EXTENSIONS.contains(&ext) => Some(Self::Video),
+            ext if crate::image_loader::RAW_EXTENSIONS.contains(&ext)
+                || crate::heif::HEIF_EXTENSIONS.contains(&ext)
+                || crate::svg::SVG_EXTENSIONS.contains(&ext) =>
+            {
+                Some(Self::Image)
+            }
             _ => None,
         }
     }
 }
 
-#[derive(Debug, Clone)]
+/// Whether `path` has a video extension, built-in or extra.
+pub fn is_video_path(path: &Path) -> bool {
+    MediaType::from_path(path) == Some(MediaType::Video)
+}
+
+/// Parses an `EXTRA_EXTENSIONS_ENV` value into extension → type pairs,
+/// returning a warning for each entry skipped. Built-in extensions keep
+/// their type so decoders and the viewer never disagree about them.
+pub fn parse_extra_extensions(text: &str) -> (HashMap<String, MediaType>, Vec<String>) {
+    let mut extensions = HashMap::new();
+    let mut warnings = Vec::new();
+    for entry in text.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (ext, kind) = entry.split_once(':').unwrap_or((entry, "image"));
+        let ext = ext.trim().trim_start_matches('.').to_lowercase();
+        let media_type = match kind.trim().to_lowercase().as_str() {
+            "image" => MediaType::Image,
+            "video" => MediaType::Video,
+            other => {
+                warnings.push(format!("unknown media type \"{}\" for \"{}\"", other, ext));
+                continue;
+            }
+        };
+        if ext.is_empty() || ext.contains(['/', '.']) {
+            warnings.push(format!("invalid extension \"{}\"", entry));
+        } else if MediaType::builtin(&ext).is_some() {
+            warnings.push(format!("\"{}\" is already recognized", ext));
+        } else {
+            extensions.insert(ext, media_type);
+        }
+    }
+    (extensions, warnings)
+}
+
+/// Reads `EXTRA_EXTENSIONS_ENV` so every later `MediaType::from_extension`
+/// call sees the extra extensions. Call once at startup, before scanning.
+pub fn register_extra_extensions() {
+    let Ok(text) = std::env::var(EXTRA_EXTENSIONS_ENV) else {
+        return;
+    };
+    let (extensions, warnings) = parse_extra_extensions(&text);
+    for warning in warnings {
+        tracing::warn!("{}: {}", EXTRA_EXTENSIONS_ENV, warning);
+    }
+    if !extensions.is_empty() {
+        tracing::info!(
+            "Recognizing extra extensions: {}",
+            extensions.keys().cloned().collect::<Vec<_>>().join(", ")
+        );
+    }
+    let _ = EXTRA_EXTENSIONS.set(extensions);
+}
+
+/// Order in which folders and files are listed.
+///
+/// Folders always come before files; the key orders each group, with the path
+/// as a tie-breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortKey {
+    #[default]
+    Name,
+    MtimeAsc,
+    MtimeDesc,
+    SizeDesc,
+    Random,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 5] = [
+        SortKey::Name,
+        SortKey::MtimeAsc,
+        SortKey::MtimeDesc,
+        SortKey::SizeDesc,
+        SortKey::Random,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SortKey::Name => "name",
+            SortKey::MtimeAsc => "mtime-asc",
+            SortKey::MtimeDesc => "mtime-desc",
+            SortKey::SizeDesc => "size-desc",
+            SortKey::Random => "random",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortKey::Name => "Name",
+            SortKey::MtimeAsc => "Oldest first",
+            SortKey::MtimeDesc => "Newest first",
+            SortKey::SizeDesc => "Largest first",
+            SortKey::Random => "Random",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|key| key.as_str() == value)
+    }
+
+    /// Sort `items` in place. `seed` only affects `Random`.
+    pub fn sort_items(self, items: &mut [MediaItem], seed: u64) {
+        let folders_first = |item: &MediaItem| !item.is_folder();
+        match self {
+            SortKey::Name => items.sort_by(|a, b| {
+                folders_first(a)
+                    .cmp(&folders_first(b))
+                    .then_with(|| a.path.cmp(&b.path))
+            }),
+            SortKey::MtimeAsc => {
+                items.sort_by_cached_key(|i| (folders_first(i), i.mtime, i.path.clone()))
+            }
+            SortKey::MtimeDesc => {
+                items.sort_by_cached_key(|i| (folders_first(i), Reverse(i.mtime), i.path.clone()))
+            }
+            SortKey::SizeDesc => {
+                items.sort_by_cached_key(|i| (folders_first(i), Reverse(i.size), i.path.clone()))
+            }
+            SortKey::Random => items.sort_by_cached_key(|i| {
+                let mut hasher = DefaultHasher::new();
+                seed.hash(&mut hasher);
+                i.path.hash(&mut hasher);
+                (folders_first(i), hasher.finish(), i.path.clone())
+            }),
+        }
+    }
+}
+
+/// How shuffle mode orders items for a given seed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShuffleMethod {
+    /// Each item's place comes from `hash(seed, path)`, so adding or
+    /// removing files leaves the rest in the same relative order.
+    #[default]
+    Stable,
+    /// A Fisher-Yates permutation of the path-sorted items. Every order is
+    /// equally likely, but any change to the set of files reorders them all.
+    Uniform,
+}
+
+impl ShuffleMethod {
+    pub const ALL: [ShuffleMethod; 2] = [ShuffleMethod::Stable, ShuffleMethod::Uniform];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ShuffleMethod::Stable => "stable",
+            ShuffleMethod::Uniform => "uniform",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ShuffleMethod::Stable => "Stable",
+            ShuffleMethod::Uniform => "Uniform",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|method| method.as_str() == value)
+    }
+
+    /// Shuffle `items` in place; the same seed and files give the same order.
+    pub fn shuffle(self, items: &mut [MediaItem], seed: u64) {
+        match self {
+            ShuffleMethod::Stable => items.sort_by_cached_key(|i| {
+                let mut hasher = DefaultHasher::new();
+                seed.hash(&mut hasher);
+                i.path.hash(&mut hasher);
+                (hasher.finish(), i.path.clone())
+            }),
+            ShuffleMethod::Uniform => {
+                // Start from path order so the scan order does not matter
+                items.sort_by(|a, b| a.path.cmp(&b.path));
+                let mut state = seed;
+                for i in (1..items.len()).rev() {
+                    let j = ((splitmix64(&mut state) as u128 * (i as u128 + 1)) >> 64) as usize;
+                    items.swap(i, j);
+                }
+            }
+        }
+    }
+}
+
+/// SplitMix64 step: a small, well-mixed generator for seeded shuffles.
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct MediaItem {
     pub path: PathBuf,
     pub media_type: MediaType,
@@ -29,6 +262,17 @@ pub struct MediaItem {
     pub thumb_path: Option<PathBuf>,
     pub thumb_w: Option<u32>,
     pub thumb_h: Option<u32>,
+    /// BlurHash placeholder painted until the thumbnail has loaded.
+    pub blurhash: Option<String>,
+    /// EXIF capture date (`YYYY-MM-DD HH:MM:SS`), read at scan time.
+    pub date_taken: Option<String>,
+    /// Camera make and model from EXIF, read at scan time.
+    pub camera: Option<String>,
+    /// EXIF GPS latitude and longitude in decimal degrees, read at scan time.
+    pub gps: Option<(f64, f64)>,
+    /// EXIF orientation (1-8), read at scan time; part of the thumbnail cache
+    /// key. `None` until the file's metadata has been read.
+    pub orientation: Option<u16>,
     pub last_seen: i64,
 }
 
@@ -46,6 +290,11 @@ impl MediaItem {
             thumb_path: None,
             thumb_w: None,
             thumb_h: None,
+            blurhash: None,
+            date_taken: None,
+            camera: None,
+            gps: None,
+            orientation: None,
             last_seen: 0,
         };
         // Read fields to satisfy the compiler (optimized away in release builds)
@@ -75,6 +324,11 @@ impl MediaItem {
             thumb_path: None,
             thumb_w: None,
             thumb_h: None,
+            blurhash: None,
+            date_taken: None,
+            camera: None,
+            gps: None,
+            orientation: None,
             last_seen: 0,
         }
     }
@@ -135,3 +389,117 @@ impl MediaItem {
         self.last_seen
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extension_lists_and_video_checks_agree() {
+        for ext in VIDEO_EXTENSIONS {
+            assert_eq!(MediaType::from_extension(ext), Some(MediaType::Video));
+            let upper = PathBuf::from(format!("clip.{}", ext.to_uppercase()));
+            assert!(is_video_path(&upper), "{} is not a video path", ext);
+        }
+        for ext in IMAGE_EXTENSIONS {
+            assert_eq!(MediaType::from_extension(ext), Some(MediaType::Image));
+            assert!(!is_video_path(&PathBuf::from(format!("photo.{}", ext))));
+            assert!(!VIDEO_EXTENSIONS.contains(&ext));
+        }
+    }
+
+    #[test]
+    fn extra_extensions_extend_but_never_override_builtins() {
+        let (extensions, warnings) =
+            parse_extra_extensions(" .JXL, tga:image, flv:Video, mp4:image, ogg:audio, ,a/b");
+        assert_eq!(extensions.len(), 3);
+        assert_eq!(extensions["jxl"], MediaType::Image);
+        assert_eq!(extensions["tga"], MediaType::Image);
+        assert_eq!(extensions["flv"], MediaType::Video);
+        assert_eq!(warnings.len(), 3);
+        assert_eq!(MediaType::from_extension("MP4"), Some(MediaType::Video));
+        assert_eq!(
+            MediaType::from_path(Path::new("/a/b.JPG")),
+            Some(MediaType::Image)
+        );
+        assert_eq!(MediaType::from_path(Path::new("/a/notes")), None);
+    }
+
+    fn item(path: &str, mtime: i64, size: i64) -> MediaItem {
+        let mut item = MediaItem::new(PathBuf::from(path), 1, 1);
+        item.mtime = mtime;
+        item.size = size;
+        item
+    }
+
+    fn names(items: &[MediaItem]) -> Vec<&str> {
+        items.iter().map(|i| i.path.to_str().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_sort_keys_keep_folders_first() {
+        let mut folder_b = MediaItem::new_folder(PathBuf::from("b"));
+        folder_b.mtime = 5;
+        let mut folder_a = MediaItem::new_folder(PathBuf::from("a"));
+        folder_a.mtime = 1;
+        let mut items = vec![
+            item("z.jpg", 10, 100),
+            folder_b,
+            item("m.jpg", 30, 300),
+            folder_a,
+            item("c.jpg", 20, 300),
+        ];
+
+        SortKey::Name.sort_items(&mut items, 0);
+        assert_eq!(names(&items), ["a", "b", "c.jpg", "m.jpg", "z.jpg"]);
+
+        SortKey::MtimeDesc.sort_items(&mut items, 0);
+        assert_eq!(names(&items), ["b", "a", "m.jpg", "c.jpg", "z.jpg"]);
+
+        SortKey::MtimeAsc.sort_items(&mut items, 0);
+        assert_eq!(names(&items), ["a", "b", "z.jpg", "c.jpg", "m.jpg"]);
+
+        // Equal sizes fall back to path order
+        SortKey::SizeDesc.sort_items(&mut items, 0);
+        assert_eq!(names(&items), ["a", "b", "c.jpg", "m.jpg", "z.jpg"]);
+
+        SortKey::Random.sort_items(&mut items, 42);
+        assert!(items[..2].iter().all(|i| i.is_folder()));
+        let first = names(&items).join(",");
+        SortKey::Name.sort_items(&mut items, 0);
+        SortKey::Random.sort_items(&mut items, 42);
+        assert_eq!(names(&items).join(","), first);
+    }
+
+    #[test]
+    fn test_shuffle_methods_are_seeded_permutations() {
+        let mut items: Vec<MediaItem> = (0..20)
+            .map(|n| item(&format!("{:02}.jpg", n), 0, 0))
+            .collect();
+        let sorted = names(&items).join(",");
+        for method in ShuffleMethod::ALL {
+            assert_eq!(ShuffleMethod::parse(method.as_str()), Some(method));
+
+            method.shuffle(&mut items, 7);
+            let first = names(&items).join(",");
+            assert_ne!(first, sorted);
+            let mut check: Vec<&str> = names(&items);
+            check.sort();
+            assert_eq!(check.join(","), sorted);
+
+            items.reverse();
+            method.shuffle(&mut items, 7);
+            assert_eq!(names(&items).join(","), first);
+            method.shuffle(&mut items, 8);
+            assert_ne!(names(&items).join(","), first);
+        }
+    }
+
+    #[test]
+    fn test_sort_key_parse_roundtrip() {
+        for key in SortKey::ALL {
+            assert_eq!(SortKey::parse(key.as_str()), Some(key));
+        }
+        assert_eq!(SortKey::parse("bogus"), None);
+    }
+}
@@ -2,19 +2,166 @@
 //!
 //! This module provides the `MediaStore` struct which manages all database operations
 //! for the idxd media browser, including:
-//! - Media item metadata (path, dimensions, mtime, thumbnail info)
+//! - Media item metadata (path, dimensions, mtime, thumbnail info, EXIF capture
+//!   date, camera and GPS position)
 //! - Layout cache (row breaks and heights for different viewport widths)
-
+//! - Metadata fields edited from within the app (date taken, description, keywords)
+//! - Perceptual hashes of grid previews, used to find near-duplicate images
+//! - Favorites, albums, and free-form tags, all keyed by path
+//! - 1-5 star ratings, keyed by path like favorites
+//! - JSON export/import of albums, favorites and ratings, for backups and sharing
+//! - A capped history of files opened in the viewer
+//! - Bookmarked directories, in a user-chosen order
+//! - Maintenance that forgets files gone from disk and compacts the database
+//! - Path updates that carry everything above along when a file is moved
+
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use directories::ProjectDirs;
 use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::{Deserialize, Serialize};
 use tracing::{debug, info, warn};
 
 use crate::models::{MediaItem, MediaType};
 
+/// Columns read by `media_item_from_row`, in its order.
+const MEDIA_ITEM_COLUMNS: &str = "path, media_type, mtime, size, width, height, \
+    duration_ms, thumb_path, thumb_w, thumb_h, last_seen, blurhash, date_taken, camera, \
+    latitude, longitude, orientation";
+
+/// Generation of the metadata the scanner extracts into `media` rows. Bump it
+/// when the scanner starts extracting a new field: rows written by an older
+/// version no longer count as unchanged, so the next scan re-extracts them.
+///
+/// 1: capture date and camera. 2: GPS position. 3: EXIF orientation.
+pub const METADATA_VERSION: i64 = 3;
+
+/// Inserts or updates one `MediaItem`, shared by the single and batch upserts.
+/// Scanner cache hits send placeholder items without derived data, so while
+/// a file's mtime and size are unchanged a missing value keeps what is
+/// stored; once the file changes the stored value is stale and replaced.
+const UPSERT_MEDIA_SQL: &str = "
+    INSERT INTO media (
+        path, media_type, mtime, size, width, height,
+        duration_ms, thumb_path, thumb_w, thumb_h, last_seen, blurhash,
+        date_taken, camera, latitude, longitude, meta_version, orientation
+    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)
+    ON CONFLICT(path) DO UPDATE SET
+        media_type = excluded.media_type,
+        mtime = excluded.mtime,
+        size = excluded.size,
+        width = CASE
+            WHEN media.mtime = excluded.mtime AND media.size = excluded.size
+                AND excluded.width = 0
+            THEN media.width
+            ELSE excluded.width
+        END,
+        height = CASE
+            WHEN media.mtime = excluded.mtime AND media.size = excluded.size
+                AND excluded.height = 0
+            THEN media.height
+            ELSE excluded.height
+        END,
+        duration_ms = CASE
+            WHEN media.mtime = excluded.mtime AND media.size = excluded.size
+            THEN COALESCE(excluded.duration_ms, media.duration_ms)
+            ELSE excluded.duration_ms
+        END,
+        thumb_path = CASE
+            WHEN media.mtime = excluded.mtime AND media.size = excluded.size
+            THEN COALESCE(excluded.thumb_path, media.thumb_path)
+            ELSE excluded.thumb_path
+        END,
+        thumb_w = CASE
+            WHEN media.mtime = excluded.mtime AND media.size = excluded.size
+            THEN COALESCE(excluded.thumb_w, media.thumb_w)
+            ELSE excluded.thumb_w
+        END,
+        thumb_h = CASE
+            WHEN media.mtime = excluded.mtime AND media.size = excluded.size
+            THEN COALESCE(excluded.thumb_h, media.thumb_h)
+            ELSE excluded.thumb_h
+        END,
+        last_seen = excluded.last_seen,
+        blurhash = CASE
+            WHEN media.mtime = excluded.mtime AND media.size = excluded.size
+            THEN COALESCE(excluded.blurhash, media.blurhash)
+            ELSE excluded.blurhash
+        END,
+        date_taken = CASE
+            WHEN media.mtime = excluded.mtime AND media.size = excluded.size
+            THEN COALESCE(excluded.date_taken, media.date_taken)
+            ELSE excluded.date_taken
+        END,
+        camera = CASE
+            WHEN media.mtime = excluded.mtime AND media.size = excluded.size
+            THEN COALESCE(excluded.camera, media.camera)
+            ELSE excluded.camera
+        END,
+        meta_version = excluded.meta_version,
+        latitude = CASE
+            WHEN media.mtime = excluded.mtime AND media.size = excluded.size
+            THEN COALESCE(excluded.latitude, media.latitude)
+            ELSE excluded.latitude
+        END,
+        longitude = CASE
+            WHEN media.mtime = excluded.mtime AND media.size = excluded.size
+            THEN COALESCE(excluded.longitude, media.longitude)
+            ELSE excluded.longitude
+        END,
+        orientation = CASE
+            WHEN media.mtime = excluded.mtime AND media.size = excluded.size
+            THEN COALESCE(excluded.orientation, media.orientation)
+            ELSE excluded.orientation
+        END,
+        phash = CASE
+            WHEN media.mtime = excluded.mtime AND media.size = excluded.size
+            THEN media.phash
+        END,
+        thumb_path_2x = CASE
+            WHEN media.mtime = excluded.mtime AND media.size = excluded.size
+            THEN media.thumb_path_2x
+        END,
+        thumb_w_2x = CASE
+            WHEN media.mtime = excluded.mtime AND media.size = excluded.size
+            THEN media.thumb_w_2x
+        END,
+        thumb_h_2x = CASE
+            WHEN media.mtime = excluded.mtime AND media.size = excluded.size
+            THEN media.thumb_h_2x
+        END
+";
+
+/// Batches above this size are looked up through a temporary table instead
+/// of one query per path.
+const MEDIA_BATCH_TEMP_TABLE_THRESHOLD: usize = 256;
+
+/// Number of persisted layouts kept by `upsert_layout`.
+const MAX_CACHED_LAYOUTS: i64 = 64;
+
+/// Adds `?2` to album `?1` ahead of its current first item.
+const ADD_TO_ALBUM_SQL: &str = "
+    INSERT OR IGNORE INTO album_items (album_id, path, created_at, position)
+    VALUES (
+        ?1, ?2, ?3,
+        (SELECT COALESCE(MIN(position), 0) - 1 FROM album_items WHERE album_id = ?1)
+    )
+";
+
+/// Distinct paths kept in the view history.
+const MAX_VIEW_HISTORY: i64 = 500;
+
+/// How far the history may grow past `MAX_VIEW_HISTORY` before it is trimmed,
+/// so trimming runs once per batch of views rather than on every one.
+const VIEW_HISTORY_TRIM_SLACK: i64 = 50;
+
+/// Schema version written by `export_collections`. Imports accept this
+/// version and older ones; bump it when the document changes shape.
+pub const COLLECTIONS_FORMAT_VERSION: u32 = 1;
+
 /// SQLite-backed storage for media metadata and layout cache.
 ///
 /// The database is stored at `XDG_CONFIG_HOME/idxd/cache.sqlite` and uses
@@ -44,12 +191,51 @@ pub struct LayoutRow {
     pub end_index: i32,
 }
 
+/// A generated thumbnail recorded for a media item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThumbnailInfo {
+    pub path: PathBuf,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// The 1x and HiDPI (2x) thumbnails recorded for a media item.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ThumbnailVariants {
+    pub thumb_1x: Option<ThumbnailInfo>,
+    pub thumb_2x: Option<ThumbnailInfo>,
+}
+
+impl ThumbnailVariants {
+    /// The variant best suited to a display scale factor.
+    ///
+    /// Scale factors of 2 and above prefer the 2x variant and fall back to the
+    /// 1x thumbnail when only that one has been generated.
+    pub fn for_scale(&self, scale_factor: i32) -> Option<&ThumbnailInfo> {
+        if scale_factor >= 2 {
+            self.thumb_2x.as_ref().or(self.thumb_1x.as_ref())
+        } else {
+            self.thumb_1x.as_ref()
+        }
+    }
+}
+
 /// Information needed to check if a cached media item is still valid.
 #[derive(Debug, Clone)]
 pub struct CacheEntry {
     pub path: PathBuf,
     pub mtime: i64,
     pub size: i64,
+    /// `METADATA_VERSION` of the scan that last extracted the row.
+    pub meta_version: i64,
+}
+
+impl CacheEntry {
+    /// Whether the stored row still describes a file with this mtime and
+    /// size, with every field the current scanner extracts.
+    pub fn is_current(&self, mtime: i64, size: i64) -> bool {
+        self.mtime == mtime && self.size == size && self.meta_version >= METADATA_VERSION
+    }
 }
 
 impl MediaStore {
@@ -105,93 +291,40 @@ impl MediaStore {
         .context("Failed to configure SQLite pragmas")?;
 
         let mut store = Self { conn };
-        store.create_tables()?;
+        store.migrate()?;
 
         info!("Opened media store at {:?}", path);
         Ok(store)
     }
 
-    /// Creates the database schema if it doesn't exist.
-    fn create_tables(&mut self) -> Result<()> {
-        self.conn
-            .execute_batch(
-                "
-            -- Media items table
-            CREATE TABLE IF NOT EXISTS media (
-                path TEXT PRIMARY KEY NOT NULL,
-                media_type INTEGER NOT NULL,
-                mtime INTEGER NOT NULL,
-                size INTEGER NOT NULL,
-                width INTEGER NOT NULL,
-                height INTEGER NOT NULL,
-                duration_ms INTEGER,
-                thumb_path TEXT,
-                thumb_w INTEGER,
-                thumb_h INTEGER,
-                last_seen INTEGER NOT NULL
-            );
-
-            -- Index for scanning/cleanup operations
-            CREATE INDEX IF NOT EXISTS idx_media_last_seen ON media(last_seen);
-            CREATE INDEX IF NOT EXISTS idx_media_mtime ON media(mtime);
-
-            -- Layout metadata table (tracks validity of cached layouts)
-            CREATE TABLE IF NOT EXISTS layout_meta (
-                width_bucket INTEGER NOT NULL,
-                sort_key TEXT NOT NULL,
-                item_count INTEGER NOT NULL,
-                list_hash TEXT NOT NULL,
-                updated_at INTEGER NOT NULL,
-                PRIMARY KEY (width_bucket, sort_key)
-            );
-
-            -- Layout rows table (cached row breaks and heights)
-            CREATE TABLE IF NOT EXISTS layout_rows (
-                width_bucket INTEGER NOT NULL,
-                sort_key TEXT NOT NULL,
-                row_index INTEGER NOT NULL,
-                row_height REAL NOT NULL,
-                start_index INTEGER NOT NULL,
-                end_index INTEGER NOT NULL,
-                PRIMARY KEY (width_bucket, sort_key, row_index),
-                FOREIGN KEY (width_bucket, sort_key)
-                    REFERENCES layout_meta(width_bucket, sort_key)
-                    ON DELETE CASCADE
-            );
-
-            -- Index for efficient row retrieval
-            CREATE INDEX IF NOT EXISTS idx_layout_rows_bucket_sort
-                ON layout_rows(width_bucket, sort_key);
-
-            -- Favorites table
-            CREATE TABLE IF NOT EXISTS favorites (
-                path TEXT PRIMARY KEY NOT NULL,
-                created_at INTEGER NOT NULL
-            );
-
-            -- Albums table
-            CREATE TABLE IF NOT EXISTS albums (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                name TEXT NOT NULL UNIQUE,
-                created_at INTEGER NOT NULL,
-                updated_at INTEGER NOT NULL
-            );
-
-            -- Album items join table
-            CREATE TABLE IF NOT EXISTS album_items (
-                album_id INTEGER NOT NULL,
-                path TEXT NOT NULL,
-                created_at INTEGER NOT NULL,
-                PRIMARY KEY (album_id, path),
-                FOREIGN KEY (album_id) REFERENCES albums(id) ON DELETE CASCADE
+    /// Brings the schema up to date by running every migration the database
+    /// has not seen yet, each in its own transaction. Refuses databases
+    /// written by a newer build rather than guessing at their layout.
+    fn migrate(&mut self) -> Result<()> {
+        let current: i64 = self
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .context("Failed to read schema version")?;
+        let current = current.max(0) as usize;
+        if current > SCHEMA_VERSION {
+            bail!(
+                "Database schema version {} is newer than this build supports ({}); \
+                 update idxd or move the cache database aside",
+                current,
+                SCHEMA_VERSION
             );
-
-            CREATE INDEX IF NOT EXISTS idx_album_items_path ON album_items(path);
-            ",
-            )
-            .context("Failed to create database tables")?;
-
-        debug!("Database tables created/verified");
+        }
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(current) {
+            let version = index + 1;
+            let tx = self.conn.transaction()?;
+            migration(&tx)
+                .with_context(|| format!("Failed to migrate database to version {}", version))?;
+            tx.pragma_update(None, "user_version", version as i64)?;
+            tx.commit()
+                .with_context(|| format!("Failed to commit schema version {}", version))?;
+            info!("Migrated database to schema version {}", version);
+        }
+        debug!("Database schema at version {}", SCHEMA_VERSION);
         Ok(())
     }
 
@@ -203,23 +336,7 @@ impl MediaStore {
     pub fn upsert_media(&self, item: &MediaItem) -> Result<()> {
         self.conn
             .execute(
-                "
-            INSERT INTO media (
-                path, media_type, mtime, size, width, height,
-                duration_ms, thumb_path, thumb_w, thumb_h, last_seen
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
-            ON CONFLICT(path) DO UPDATE SET
-                media_type = excluded.media_type,
-                mtime = excluded.mtime,
-                size = excluded.size,
-                width = excluded.width,
-                height = excluded.height,
-                duration_ms = excluded.duration_ms,
-                thumb_path = excluded.thumb_path,
-                thumb_w = excluded.thumb_w,
-                thumb_h = excluded.thumb_h,
-                last_seen = excluded.last_seen
-            ",
+                UPSERT_MEDIA_SQL,
                 params![
                     item.path.to_string_lossy(),
                     media_type_to_int(item.media_type),
@@ -234,6 +351,13 @@ impl MediaStore {
                     item.thumb_w,
                     item.thumb_h,
                     item.last_seen,
+                    item.blurhash,
+                    item.date_taken,
+                    item.camera,
+                    item.gps.map(|(lat, _)| lat),
+                    item.gps.map(|(_, lon)| lon),
+                    METADATA_VERSION,
+                    item.orientation,
                 ],
             )
             .context("Failed to upsert media item")?;
@@ -259,25 +383,7 @@ impl MediaStore {
 
     /// Internal batch upsert within a transaction.
     fn upsert_media_batch_in_tx(tx: &Transaction, items: &[MediaItem]) -> Result<usize> {
-        let mut stmt = tx.prepare_cached(
-            "
-            INSERT INTO media (
-                path, media_type, mtime, size, width, height,
-                duration_ms, thumb_path, thumb_w, thumb_h, last_seen
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
-            ON CONFLICT(path) DO UPDATE SET
-                media_type = excluded.media_type,
-                mtime = excluded.mtime,
-                size = excluded.size,
-                width = excluded.width,
-                height = excluded.height,
-                duration_ms = excluded.duration_ms,
-                thumb_path = excluded.thumb_path,
-                thumb_w = excluded.thumb_w,
-                thumb_h = excluded.thumb_h,
-                last_seen = excluded.last_seen
-            ",
-        )?;
+        let mut stmt = tx.prepare_cached(UPSERT_MEDIA_SQL)?;
 
         let mut count = 0;
         for item in items {
@@ -295,6 +401,13 @@ impl MediaStore {
                 item.thumb_w,
                 item.thumb_h,
                 item.last_seen,
+                item.blurhash,
+                item.date_taken,
+                item.camera,
+                item.gps.map(|(lat, _)| lat),
+                item.gps.map(|(_, lon)| lon),
+                METADATA_VERSION,
+                item.orientation,
             ])?;
             count += 1;
         }
@@ -309,27 +422,9 @@ impl MediaStore {
         let result = self
             .conn
             .query_row(
-                "
-            SELECT path, media_type, mtime, size, width, height,
-                   duration_ms, thumb_path, thumb_w, thumb_h, last_seen
-            FROM media WHERE path = ?1
-            ",
+                &format!("SELECT {} FROM media WHERE path = ?1", MEDIA_ITEM_COLUMNS),
                 params![path_str.as_ref()],
-                |row| {
-                    Ok(MediaItem {
-                        path: PathBuf::from(row.get::<_, String>(0)?),
-                        media_type: int_to_media_type(row.get(1)?),
-                        mtime: row.get(2)?,
-                        size: row.get(3)?,
-                        width: row.get(4)?,
-                        height: row.get(5)?,
-                        duration_ms: row.get(6)?,
-                        thumb_path: row.get::<_, Option<String>>(7)?.map(PathBuf::from),
-                        thumb_w: row.get(8)?,
-                        thumb_h: row.get(9)?,
-                        last_seen: row.get(10)?,
-                    })
-                },
+                media_item_from_row,
             )
             .optional()
             .context("Failed to query media item")?;
@@ -341,46 +436,78 @@ impl MediaStore {
     ///
     /// Returns items in the same order as the input paths (missing items are skipped).
     pub fn get_media_batch(&self, paths: &[PathBuf]) -> Result<Vec<MediaItem>> {
-        if paths.is_empty() {
-            return Ok(Vec::new());
+        if paths.len() > MEDIA_BATCH_TEMP_TABLE_THRESHOLD {
+            self.get_media_batch_joined(paths)
+        } else {
+            self.get_media_batch_each(paths)
         }
+    }
 
+    /// One indexed lookup per path; cheapest for small batches.
+    fn get_media_batch_each(&self, paths: &[PathBuf]) -> Result<Vec<MediaItem>> {
         let mut items = Vec::with_capacity(paths.len());
-
-        // For small batches, use individual queries
-        // For large batches, this could be optimized with temp tables
-        let mut stmt = self.conn.prepare_cached(
-            "
-            SELECT path, media_type, mtime, size, width, height,
-                   duration_ms, thumb_path, thumb_w, thumb_h, last_seen
-            FROM media WHERE path = ?1
-            ",
-        )?;
-
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT {} FROM media WHERE path = ?1",
+            MEDIA_ITEM_COLUMNS
+        ))?;
         for path in paths {
             let path_str = path.to_string_lossy();
             if let Some(item) = stmt
-                .query_row(params![path_str.as_ref()], |row| {
-                    Ok(MediaItem {
-                        path: PathBuf::from(row.get::<_, String>(0)?),
-                        media_type: int_to_media_type(row.get(1)?),
-                        mtime: row.get(2)?,
-                        size: row.get(3)?,
-                        width: row.get(4)?,
-                        height: row.get(5)?,
-                        duration_ms: row.get(6)?,
-                        thumb_path: row.get::<_, Option<String>>(7)?.map(PathBuf::from),
-                        thumb_w: row.get(8)?,
-                        thumb_h: row.get(9)?,
-                        last_seen: row.get(10)?,
-                    })
-                })
+                .query_row(params![path_str.as_ref()], media_item_from_row)
                 .optional()?
             {
                 items.push(item);
             }
         }
+        Ok(items)
+    }
+
+    /// Loads the paths into the `batch_paths` temporary table, so one query
+    /// can join them against another table. The transaction is rolled back
+    /// when dropped, which also empties the temp table.
+    fn batch_paths_transaction(&self, paths: &[PathBuf]) -> Result<Transaction<'_>> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "
+            CREATE TEMP TABLE IF NOT EXISTS batch_paths (
+                position INTEGER PRIMARY KEY,
+                path TEXT NOT NULL
+            )
+            ",
+            [],
+        )?;
+        {
+            let mut insert =
+                tx.prepare_cached("INSERT INTO batch_paths (position, path) VALUES (?1, ?2)")?;
+            for (position, path) in paths.iter().enumerate() {
+                insert.execute(params![position as i64, path.to_string_lossy().as_ref()])?;
+            }
+        }
+        Ok(tx)
+    }
 
+    /// Loads the paths into a temporary table and joins it against `media`
+    /// in one query, ordered by input position.
+    fn get_media_batch_joined(&self, paths: &[PathBuf]) -> Result<Vec<MediaItem>> {
+        let tx = self.batch_paths_transaction(paths)?;
+        let columns = MEDIA_ITEM_COLUMNS
+            .split(',')
+            .map(|column| format!("media.{}", column.trim()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut stmt = tx.prepare(&format!(
+            "
+            SELECT {}
+            FROM batch_paths
+            JOIN media ON media.path = batch_paths.path
+            ORDER BY batch_paths.position
+            ",
+            columns
+        ))?;
+        let items = stmt
+            .query_map([], media_item_from_row)?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to query media batch")?;
         Ok(items)
     }
 
@@ -422,6 +549,50 @@ impl MediaStore {
         Ok(false)
     }
 
+    /// Marks or unmarks every path as favorite in one transaction.
+    /// Returns how many rows changed.
+    pub fn set_favorites(&mut self, paths: &[PathBuf], favorite: bool) -> Result<usize> {
+        let now = Self::now();
+        let tx = self.conn.transaction()?;
+        let mut count = 0;
+        {
+            let mut insert = tx.prepare_cached(
+                "INSERT OR IGNORE INTO favorites (path, created_at) VALUES (?1, ?2)",
+            )?;
+            let mut delete = tx.prepare_cached("DELETE FROM favorites WHERE path = ?1")?;
+            for path in paths {
+                let path_str = path.to_string_lossy();
+                count += if favorite {
+                    insert.execute(params![path_str.as_ref(), now])?
+                } else {
+                    delete.execute(params![path_str.as_ref()])?
+                };
+            }
+        }
+        tx.commit().context("Failed to update favorites")?;
+        Ok(count)
+    }
+
+    /// Returns which of `paths` are favorites, with a single query.
+    pub fn favorites_for_paths(
+        &self,
+        paths: &[PathBuf],
+    ) -> Result<std::collections::HashSet<PathBuf>> {
+        let tx = self.batch_paths_transaction(paths)?;
+        let mut stmt = tx.prepare(
+            "
+            SELECT favorites.path
+            FROM batch_paths
+            JOIN favorites ON favorites.path = batch_paths.path
+            ",
+        )?;
+        let favorites = stmt
+            .query_map([], |row| Ok(PathBuf::from(row.get::<_, String>(0)?)))?
+            .collect::<Result<_, _>>()
+            .context("Failed to query favorites batch")?;
+        Ok(favorites)
+    }
+
     /// Returns favorite paths ordered by newest first.
     pub fn list_favorite_paths(&self) -> Result<Vec<PathBuf>> {
         let mut stmt = self
@@ -463,17 +634,81 @@ impl MediaStore {
         Ok(id)
     }
 
+    /// Renames an album. Fails without changing anything if another album
+    /// already has the name.
+    pub fn rename_album(&self, album_id: i64, new_name: &str) -> Result<()> {
+        let taken: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT id FROM albums WHERE name = ?1 AND id != ?2",
+                params![new_name, album_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if taken.is_some() {
+            bail!("An album named \"{}\" already exists", new_name);
+        }
+        let updated = self.conn.execute(
+            "UPDATE albums SET name = ?1, updated_at = ?2 WHERE id = ?3",
+            params![new_name, Self::now(), album_id],
+        )?;
+        if updated == 0 {
+            bail!("Album {} does not exist", album_id);
+        }
+        Ok(())
+    }
+
+    /// Deletes an album and its memberships. The files are not touched.
+    pub fn delete_album(&self, album_id: i64) -> Result<bool> {
+        let deleted = self
+            .conn
+            .execute("DELETE FROM albums WHERE id = ?1", params![album_id])
+            .context("Failed to delete album")?;
+        Ok(deleted > 0)
+    }
+
     /// Adds a path to an album (no-op if already present).
     pub fn add_to_album(&self, album_id: i64, path: &Path) -> Result<bool> {
         let path_str = path.to_string_lossy();
         let now = Self::now();
-        let inserted = self.conn.execute(
-            "INSERT OR IGNORE INTO album_items (album_id, path, created_at) VALUES (?1, ?2, ?3)",
-            params![album_id, path_str.as_ref(), now],
-        )?;
+        let inserted = self
+            .conn
+            .execute(ADD_TO_ALBUM_SQL, params![album_id, path_str.as_ref(), now])?;
         Ok(inserted > 0)
     }
 
+    /// Adds several paths to an album in one transaction.
+    /// Returns how many were not already present.
+    pub fn add_to_album_batch(&mut self, album_id: i64, paths: &[PathBuf]) -> Result<usize> {
+        let now = Self::now();
+        let tx = self.conn.transaction()?;
+        let mut count = 0;
+        {
+            let mut stmt = tx.prepare_cached(ADD_TO_ALBUM_SQL)?;
+            for path in paths {
+                count += stmt.execute(params![album_id, path.to_string_lossy().as_ref(), now])?;
+            }
+        }
+        tx.commit().context("Failed to add items to album")?;
+        Ok(count)
+    }
+
+    /// Removes several paths from an album in one transaction.
+    /// Returns how many were in it.
+    pub fn remove_from_album_batch(&mut self, album_id: i64, paths: &[PathBuf]) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+        let mut count = 0;
+        {
+            let mut stmt =
+                tx.prepare_cached("DELETE FROM album_items WHERE album_id = ?1 AND path = ?2")?;
+            for path in paths {
+                count += stmt.execute(params![album_id, path.to_string_lossy().as_ref()])?;
+            }
+        }
+        tx.commit().context("Failed to remove items from album")?;
+        Ok(count)
+    }
+
     /// Returns album ids containing the given path.
     pub fn album_ids_for_path(&self, path: &Path) -> Result<Vec<i64>> {
         let path_str = path.to_string_lossy();
@@ -488,14 +723,47 @@ impl MediaStore {
         Ok(album_ids)
     }
 
-    /// Returns paths in an album ordered by newest first.
+    /// Returns the albums each of `paths` belongs to, with a single query.
+    /// Paths in no album are left out.
+    pub fn album_membership_for_paths(
+        &self,
+        paths: &[PathBuf],
+    ) -> Result<std::collections::HashMap<PathBuf, Vec<i64>>> {
+        let tx = self.batch_paths_transaction(paths)?;
+        let mut stmt = tx.prepare(
+            "
+            SELECT album_items.path, album_items.album_id
+            FROM batch_paths
+            JOIN album_items ON album_items.path = batch_paths.path
+            ORDER BY album_items.album_id
+            ",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                PathBuf::from(row.get::<_, String>(0)?),
+                row.get::<_, i64>(1)?,
+            ))
+        })?;
+        let mut membership = std::collections::HashMap::new();
+        for row in rows {
+            let (path, album_id) = row?;
+            membership
+                .entry(path)
+                .or_insert_with(Vec::new)
+                .push(album_id);
+        }
+        Ok(membership)
+    }
+
+    /// Returns paths in an album in their curated order. Items added later
+    /// go first until moved.
     pub fn list_album_paths(&self, album_id: i64) -> Result<Vec<PathBuf>> {
         let mut stmt = self.conn.prepare_cached(
             "
             SELECT path
             FROM album_items
             WHERE album_id = ?1
-            ORDER BY created_at DESC
+            ORDER BY position, created_at DESC
             ",
         )?;
         let rows = stmt.query_map(params![album_id], |row| row.get::<_, String>(0))?;
@@ -506,97 +774,637 @@ impl MediaStore {
         Ok(paths)
     }
 
-    /// Retrieves all media items from the database.
-    pub fn get_all_media(&self) -> Result<Vec<MediaItem>> {
-        let mut stmt = self.conn.prepare(
+    /// Returns all tag names ordered by name.
+    pub fn list_tags(&self) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT name FROM tags ORDER BY name COLLATE NOCASE")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        let mut tags = Vec::new();
+        for row in rows {
+            tags.push(row?);
+        }
+        Ok(tags)
+    }
+
+    /// Tags every path with `name`, creating the tag if needed.
+    /// Returns how many paths did not have it yet.
+    pub fn add_tag(&mut self, paths: &[PathBuf], name: &str) -> Result<usize> {
+        let now = Self::now();
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT OR IGNORE INTO tags (name, created_at) VALUES (?1, ?2)",
+            params![name, now],
+        )?;
+        let tag_id: i64 = tx.query_row(
+            "SELECT id FROM tags WHERE name = ?1",
+            params![name],
+            |row| row.get(0),
+        )?;
+        let mut count = 0;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT OR IGNORE INTO media_tags (tag_id, path, created_at) VALUES (?1, ?2, ?3)",
+            )?;
+            for path in paths {
+                count += stmt.execute(params![tag_id, path.to_string_lossy().as_ref(), now])?;
+            }
+        }
+        tx.commit().context("Failed to add tag")?;
+        Ok(count)
+    }
+
+    /// Removes `name` from every path. A tag left with no paths is deleted.
+    /// Returns how many paths had it.
+    pub fn remove_tag(&mut self, paths: &[PathBuf], name: &str) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+        let mut count = 0;
+        {
+            let mut stmt = tx.prepare_cached(
+                "
+                DELETE FROM media_tags
+                WHERE path = ?1 AND tag_id = (SELECT id FROM tags WHERE name = ?2)
+                ",
+            )?;
+            for path in paths {
+                count += stmt.execute(params![path.to_string_lossy().as_ref(), name])?;
+            }
+        }
+        tx.execute(
             "
-            SELECT path, media_type, mtime, size, width, height,
-                   duration_ms, thumb_path, thumb_w, thumb_h, last_seen
-            FROM media
-            ORDER BY path
+            DELETE FROM tags
+            WHERE name = ?1 AND NOT EXISTS (SELECT 1 FROM media_tags WHERE tag_id = tags.id)
             ",
+            params![name],
         )?;
+        tx.commit().context("Failed to remove tag")?;
+        Ok(count)
+    }
 
-        let items = stmt
-            .query_map([], |row| {
-                Ok(MediaItem {
-                    path: PathBuf::from(row.get::<_, String>(0)?),
-                    media_type: int_to_media_type(row.get(1)?),
-                    mtime: row.get(2)?,
-                    size: row.get(3)?,
-                    width: row.get(4)?,
-                    height: row.get(5)?,
-                    duration_ms: row.get(6)?,
-                    thumb_path: row.get::<_, Option<String>>(7)?.map(PathBuf::from),
-                    thumb_w: row.get(8)?,
-                    thumb_h: row.get(9)?,
-                    last_seen: row.get(10)?,
-                })
-            })?
-            .collect::<Result<Vec<_>, _>>()
-            .context("Failed to query all media items")?;
+    /// Returns the tag names on the given path, ordered by name.
+    pub fn tags_for_path(&self, path: &Path) -> Result<Vec<String>> {
+        let path_str = path.to_string_lossy();
+        let mut stmt = self.conn.prepare_cached(
+            "
+            SELECT tags.name
+            FROM media_tags
+            JOIN tags ON tags.id = media_tags.tag_id
+            WHERE media_tags.path = ?1
+            ORDER BY tags.name COLLATE NOCASE
+            ",
+        )?;
+        let rows = stmt.query_map(params![path_str.as_ref()], |row| row.get::<_, String>(0))?;
+        let mut tags = Vec::new();
+        for row in rows {
+            tags.push(row?);
+        }
+        Ok(tags)
+    }
 
-        Ok(items)
+    /// Returns paths carrying the tag, newest first.
+    pub fn paths_for_tag(&self, name: &str) -> Result<Vec<PathBuf>> {
+        let mut stmt = self.conn.prepare_cached(
+            "
+            SELECT media_tags.path
+            FROM media_tags
+            JOIN tags ON tags.id = media_tags.tag_id
+            WHERE tags.name = ?1
+            ORDER BY media_tags.created_at DESC
+            ",
+        )?;
+        let rows = stmt.query_map(params![name], |row| row.get::<_, String>(0))?;
+        let mut paths = Vec::new();
+        for row in rows {
+            paths.push(PathBuf::from(row?));
+        }
+        Ok(paths)
     }
 
-    /// Checks if a cached media item is still valid based on mtime.
-    ///
-    /// Returns `true` if the cached mtime matches the provided mtime.
-    pub fn is_cache_valid(&self, path: &Path, current_mtime: i64) -> Result<bool> {
-        let path_str = path.to_string_lossy();
+    // =========================================================================
+    // Ratings
+    // =========================================================================
 
-        let cached_mtime: Option<i64> = self
-            .conn
+    /// Sets a 1-5 star rating on every path, or clears it with `None`.
+    /// Ratings live in their own table, so they survive rescans and can be
+    /// given to files the scanner has not reached yet. Returns how many paths
+    /// were updated.
+    pub fn set_rating(&mut self, paths: &[PathBuf], rating: Option<u8>) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = match rating {
+                Some(_) => tx.prepare_cached(
+                    "
+                    INSERT INTO ratings (path, rating) VALUES (?1, ?2)
+                    ON CONFLICT(path) DO UPDATE SET rating = excluded.rating
+                    ",
+                )?,
+                None => tx.prepare_cached("DELETE FROM ratings WHERE path = ?1")?,
+            };
+            for path in paths {
+                let path = path.to_string_lossy();
+                match rating {
+                    Some(stars) => stmt.execute(params![path.as_ref(), stars.clamp(1, 5)])?,
+                    None => stmt.execute(params![path.as_ref()])?,
+                };
+            }
+        }
+        tx.commit().context("Failed to update ratings")?;
+        Ok(paths.len())
+    }
+
+    /// Returns the rating of a path, if it has one.
+    pub fn get_rating(&self, path: &Path) -> Result<Option<u8>> {
+        let path_str = path.to_string_lossy();
+        self.conn
             .query_row(
-                "SELECT mtime FROM media WHERE path = ?1",
+                "SELECT rating FROM ratings WHERE path = ?1",
                 params![path_str.as_ref()],
                 |row| row.get(0),
             )
             .optional()
-            .context("Failed to check cache validity")?;
-
-        Ok(cached_mtime == Some(current_mtime))
+            .context("Failed to query rating")
     }
 
-    /// Gets cache entries (path, mtime, size) for checking validity.
-    ///
-    /// This is useful for scanning: compare filesystem mtime with cached mtime
-    /// to determine which items need metadata refresh.
-    pub fn get_cache_entries(&self) -> Result<Vec<CacheEntry>> {
-        let mut stmt = self.conn.prepare("SELECT path, mtime, size FROM media")?;
-
-        let entries = stmt
-            .query_map([], |row| {
-                Ok(CacheEntry {
-                    path: PathBuf::from(row.get::<_, String>(0)?),
-                    mtime: row.get(1)?,
-                    size: row.get(2)?,
-                })
-            })?
-            .collect::<Result<Vec<_>, _>>()
-            .context("Failed to get cache entries")?;
-
-        Ok(entries)
+    /// Ratings of the given paths; unrated paths are left out. Reads every
+    /// rated row once, which stays small next to a directory listing.
+    pub fn ratings_for_paths(
+        &self,
+        paths: &[PathBuf],
+    ) -> Result<std::collections::HashMap<PathBuf, u8>> {
+        let wanted: std::collections::HashSet<&PathBuf> = paths.iter().collect();
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT path, rating FROM ratings")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                PathBuf::from(row.get::<_, String>(0)?),
+                row.get::<_, u8>(1)?,
+            ))
+        })?;
+        let mut ratings = std::collections::HashMap::new();
+        for row in rows {
+            let (path, rating) = row?;
+            if wanted.contains(&path) {
+                ratings.insert(path, rating);
+            }
+        }
+        Ok(ratings)
     }
 
-    /// Gets cache entries as a map from path to (mtime, size) for fast lookup.
-    pub fn get_cache_map(&self) -> Result<std::collections::HashMap<PathBuf, (i64, i64)>> {
-        let entries = self.get_cache_entries()?;
-        let map = entries
-            .into_iter()
-            .map(|e| (e.path, (e.mtime, e.size)))
-            .collect();
-        Ok(map)
+    /// Returns paths rated at least `min_rating` stars, best first.
+    pub fn list_rated_paths(&self, min_rating: u8) -> Result<Vec<PathBuf>> {
+        let mut stmt = self.conn.prepare_cached(
+            "
+            SELECT path
+            FROM ratings
+            WHERE rating >= ?1
+            ORDER BY rating DESC, path
+            ",
+        )?;
+        let rows = stmt.query_map(params![min_rating], |row| row.get::<_, String>(0))?;
+        let mut paths = Vec::new();
+        for row in rows {
+            paths.push(PathBuf::from(row?));
+        }
+        Ok(paths)
     }
 
-    /// Updates the thumbnail information for a media item.
-    pub fn update_thumbnail(
-        &self,
-        path: &Path,
-        thumb_path: &Path,
-        thumb_w: u32,
-        thumb_h: u32,
-    ) -> Result<bool> {
+    /// Pins `path` as the album's cover.
+    pub fn set_album_cover(&self, album_id: i64, path: &Path) -> Result<()> {
+        let path_str = path.to_string_lossy();
+        self.conn
+            .execute(
+                "UPDATE albums SET cover_path = ?1, updated_at = ?2 WHERE id = ?3",
+                params![path_str.as_ref(), Self::now(), album_id],
+            )
+            .context("Failed to set album cover")?;
+        Ok(())
+    }
+
+    /// The pinned cover while it is still in the album, otherwise the first
+    /// item. None for an empty album.
+    pub fn album_cover(&self, album_id: i64) -> Result<Option<PathBuf>> {
+        let cover: Option<String> = self
+            .conn
+            .query_row(
+                "
+                SELECT COALESCE(
+                    (SELECT albums.cover_path
+                     FROM albums
+                     JOIN album_items
+                       ON album_items.album_id = albums.id
+                      AND album_items.path = albums.cover_path
+                     WHERE albums.id = ?1),
+                    (SELECT path
+                     FROM album_items
+                     WHERE album_id = ?1
+                     ORDER BY position, created_at DESC
+                     LIMIT 1)
+                )
+                ",
+                params![album_id],
+                |row| row.get(0),
+            )
+            .context("Failed to query album cover")?;
+        Ok(cover.map(PathBuf::from))
+    }
+
+    /// Moves `path` to `new_index` within the album's order, clamped to the
+    /// end. Returns false if the path is not in the album.
+    pub fn move_item_in_album(
+        &mut self,
+        album_id: i64,
+        path: &Path,
+        new_index: usize,
+    ) -> Result<bool> {
+        let mut paths = self.list_album_paths(album_id)?;
+        let Some(old_index) = paths.iter().position(|p| p == path) else {
+            return Ok(false);
+        };
+        let moved = paths.remove(old_index);
+        paths.insert(new_index.min(paths.len()), moved);
+
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "UPDATE album_items SET position = ?1 WHERE album_id = ?2 AND path = ?3",
+            )?;
+            for (position, path) in paths.iter().enumerate() {
+                stmt.execute(params![
+                    position as i64,
+                    album_id,
+                    path.to_string_lossy().as_ref()
+                ])?;
+            }
+        }
+        tx.commit().context("Failed to reorder album")?;
+        Ok(true)
+    }
+
+    /// Returns bookmarked directories (path, label) in their sidebar order.
+    pub fn list_bookmarks(&self) -> Result<Vec<(PathBuf, String)>> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT path, label FROM bookmarks ORDER BY position, path")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((PathBuf::from(row.get::<_, String>(0)?), row.get(1)?))
+        })?;
+        let mut bookmarks = Vec::new();
+        for row in rows {
+            bookmarks.push(row?);
+        }
+        Ok(bookmarks)
+    }
+
+    /// Bookmarks a directory after the existing ones. Returns false if it is
+    /// already bookmarked.
+    pub fn add_bookmark(&self, path: &Path, label: &str) -> Result<bool> {
+        let inserted = self
+            .conn
+            .execute(
+                "
+                INSERT OR IGNORE INTO bookmarks (path, label, position)
+                VALUES (?1, ?2, (SELECT COALESCE(MAX(position) + 1, 0) FROM bookmarks))
+                ",
+                params![path.to_string_lossy().as_ref(), label],
+            )
+            .context("Failed to add bookmark")?;
+        Ok(inserted > 0)
+    }
+
+    /// Removes a bookmark. The directory itself is not touched.
+    pub fn remove_bookmark(&self, path: &Path) -> Result<bool> {
+        let deleted = self
+            .conn
+            .execute(
+                "DELETE FROM bookmarks WHERE path = ?1",
+                params![path.to_string_lossy().as_ref()],
+            )
+            .context("Failed to remove bookmark")?;
+        Ok(deleted > 0)
+    }
+
+    /// Moves a bookmark to `new_index` in the sidebar order, clamped to the
+    /// end. Returns false if the path is not bookmarked.
+    pub fn move_bookmark(&mut self, path: &Path, new_index: usize) -> Result<bool> {
+        let mut paths: Vec<PathBuf> = self
+            .list_bookmarks()?
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        let Some(old_index) = paths.iter().position(|p| p == path) else {
+            return Ok(false);
+        };
+        let moved = paths.remove(old_index);
+        paths.insert(new_index.min(paths.len()), moved);
+
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt =
+                tx.prepare_cached("UPDATE bookmarks SET position = ?1 WHERE path = ?2")?;
+            for (position, path) in paths.iter().enumerate() {
+                stmt.execute(params![position as i64, path.to_string_lossy().as_ref()])?;
+            }
+        }
+        tx.commit().context("Failed to reorder bookmarks")?;
+        Ok(true)
+    }
+
+    /// Records that `path` was opened at `viewed_at`, replacing any earlier
+    /// visit. Trims the oldest entries once the history outgrows its cap.
+    pub fn record_view(&self, path: &Path, viewed_at: i64) -> Result<()> {
+        let path_str = path.to_string_lossy();
+        self.conn
+            .execute(
+                "
+                INSERT INTO view_history (path, viewed_at) VALUES (?1, ?2)
+                ON CONFLICT(path) DO UPDATE SET viewed_at = excluded.viewed_at
+                ",
+                params![path_str.as_ref(), viewed_at],
+            )
+            .context("Failed to record view")?;
+
+        let count: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM view_history", [], |row| row.get(0))?;
+        if count > MAX_VIEW_HISTORY + VIEW_HISTORY_TRIM_SLACK {
+            let trimmed = self
+                .conn
+                .execute(
+                    "
+                    DELETE FROM view_history
+                    WHERE path NOT IN (
+                        SELECT path FROM view_history
+                        ORDER BY viewed_at DESC
+                        LIMIT ?1
+                    )
+                    ",
+                    params![MAX_VIEW_HISTORY],
+                )
+                .context("Failed to trim view history")?;
+            debug!("Trimmed {} view history entries", trimmed);
+        }
+        Ok(())
+    }
+
+    /// Returns up to `limit` viewed paths, most recently viewed first.
+    pub fn list_recent_paths(&self, limit: usize) -> Result<Vec<PathBuf>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT path FROM view_history ORDER BY viewed_at DESC, rowid DESC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(params![limit as i64], |row| row.get::<_, String>(0))?;
+        let mut paths = Vec::new();
+        for row in rows {
+            paths.push(PathBuf::from(row?));
+        }
+        Ok(paths)
+    }
+
+    /// Writes every album (with its ordered members), all favorites and all
+    /// ratings as a versioned JSON document.
+    pub fn export_collections<W: Write>(&self, writer: W) -> Result<()> {
+        let favorites = self
+            .list_favorite_paths()?
+            .iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+        let ratings = {
+            let mut stmt = self
+                .conn
+                .prepare_cached("SELECT path, rating FROM ratings ORDER BY path")?;
+            let rows = stmt.query_map([], |row| {
+                Ok(RatingDocument {
+                    path: row.get(0)?,
+                    rating: row.get(1)?,
+                })
+            })?;
+            rows.collect::<Result<Vec<_>, _>>()
+                .context("Failed to read ratings")?
+        };
+        let mut albums = Vec::new();
+        for (album_id, name) in self.list_albums()? {
+            let cover: Option<String> = self
+                .conn
+                .query_row(
+                    "SELECT cover_path FROM albums WHERE id = ?1",
+                    params![album_id],
+                    |row| row.get(0),
+                )
+                .context("Failed to query album cover")?;
+            let items = self
+                .list_album_paths(album_id)?
+                .iter()
+                .map(|path| path.to_string_lossy().into_owned())
+                .collect();
+            albums.push(AlbumDocument { name, cover, items });
+        }
+        let document = CollectionsDocument {
+            version: COLLECTIONS_FORMAT_VERSION,
+            favorites,
+            albums,
+            ratings,
+        };
+        serde_json::to_writer_pretty(writer, &document).context("Failed to write collections")
+    }
+
+    /// Reads a document written by `export_collections`. Albums are matched
+    /// by name and created when missing; members, favorites and ratings are
+    /// only added if the file still exists, and the rest are reported as
+    /// missing. Nothing is removed and existing ratings win, so importing
+    /// twice is harmless.
+    pub fn import_collections<R: Read>(&mut self, reader: R) -> Result<ImportReport> {
+        let document: CollectionsDocument =
+            serde_json::from_reader(reader).context("Failed to parse collections")?;
+        if document.version > COLLECTIONS_FORMAT_VERSION {
+            bail!(
+                "Collections file is version {}, newer than supported version {}",
+                document.version,
+                COLLECTIONS_FORMAT_VERSION
+            );
+        }
+
+        let mut report = ImportReport::default();
+        let mut missing = |path: &str| {
+            let path = PathBuf::from(path);
+            if path.exists() {
+                return false;
+            }
+            if !report.missing.contains(&path) {
+                report.missing.push(path);
+            }
+            true
+        };
+
+        let now = Self::now();
+        let tx = self.conn.transaction()?;
+        let mut albums = 0;
+        let mut album_items_added = 0;
+        let mut favorites_added = 0;
+        let mut ratings_added = 0;
+        {
+            let mut create = tx.prepare_cached(
+                "INSERT OR IGNORE INTO albums (name, created_at, updated_at) VALUES (?1, ?2, ?2)",
+            )?;
+            let mut find = tx.prepare_cached("SELECT id FROM albums WHERE name = ?1")?;
+            let mut add = tx.prepare_cached(ADD_TO_ALBUM_SQL)?;
+            let mut set_cover = tx.prepare_cached(
+                "UPDATE albums SET cover_path = ?1 WHERE id = ?2 AND cover_path IS NULL",
+            )?;
+            for album in &document.albums {
+                let name = album.name.trim();
+                if name.is_empty() {
+                    continue;
+                }
+                create.execute(params![name, now])?;
+                let album_id: i64 = find.query_row(params![name], |row| row.get(0))?;
+                albums += 1;
+                // New members go first, so add from the back to keep the order
+                for path in album.items.iter().rev() {
+                    if !missing(path) {
+                        album_items_added += add.execute(params![album_id, path, now])?;
+                    }
+                }
+                if let Some(cover) = album.cover.as_deref() {
+                    if album.items.iter().any(|item| item == cover) && !missing(cover) {
+                        set_cover.execute(params![cover, album_id])?;
+                    }
+                }
+            }
+
+            let mut favorite = tx.prepare_cached(
+                "INSERT OR IGNORE INTO favorites (path, created_at) VALUES (?1, ?2)",
+            )?;
+            for path in &document.favorites {
+                if !missing(path) {
+                    favorites_added += favorite.execute(params![path, now])?;
+                }
+            }
+
+            let mut rate =
+                tx.prepare_cached("INSERT OR IGNORE INTO ratings (path, rating) VALUES (?1, ?2)")?;
+            for entry in &document.ratings {
+                if !missing(&entry.path) {
+                    ratings_added += rate.execute(params![entry.path, entry.rating.clamp(1, 5)])?;
+                }
+            }
+        }
+        tx.commit().context("Failed to import collections")?;
+
+        report.albums = albums;
+        report.album_items_added = album_items_added;
+        report.favorites_added = favorites_added;
+        report.ratings_added = ratings_added;
+        Ok(report)
+    }
+
+    /// Retrieves the stored media items below `dir`, keyed by path.
+    ///
+    /// With `recursive == false` only direct children are returned.
+    pub fn get_media_under(
+        &self,
+        dir: &Path,
+        recursive: bool,
+    ) -> Result<std::collections::HashMap<PathBuf, MediaItem>> {
+        // Range scan on the primary key: every path starting with "<dir>/"
+        // sorts between "<dir>/" and "<dir>0" ('0' follows '/').
+        let prefix = dir.to_string_lossy();
+        let prefix = prefix.trim_end_matches('/');
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT {} FROM media WHERE path >= ?1 AND path < ?2",
+            MEDIA_ITEM_COLUMNS
+        ))?;
+
+        let items = stmt
+            .query_map(
+                params![format!("{}/", prefix), format!("{}0", prefix)],
+                media_item_from_row,
+            )?
+            .filter(|item| {
+                recursive
+                    || item
+                        .as_ref()
+                        .map_or(true, |item| item.path.parent() == Some(dir))
+            })
+            .map(|item| item.map(|item| (item.path.clone(), item)))
+            .collect::<Result<_, _>>()
+            .context("Failed to query media items under directory")?;
+
+        Ok(items)
+    }
+
+    /// Retrieves all media items from the database.
+    pub fn get_all_media(&self) -> Result<Vec<MediaItem>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM media ORDER BY path",
+            MEDIA_ITEM_COLUMNS
+        ))?;
+
+        let items = stmt
+            .query_map([], media_item_from_row)?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to query all media items")?;
+
+        Ok(items)
+    }
+
+    /// Checks if a cached media item is still valid based on mtime.
+    ///
+    /// Returns `true` if the cached mtime matches the provided mtime.
+    pub fn is_cache_valid(&self, path: &Path, current_mtime: i64) -> Result<bool> {
+        let path_str = path.to_string_lossy();
+
+        let cached_mtime: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT mtime FROM media WHERE path = ?1",
+                params![path_str.as_ref()],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to check cache validity")?;
+
+        Ok(cached_mtime == Some(current_mtime))
+    }
+
+    /// Gets cache entries (path, mtime, size) for checking validity.
+    ///
+    /// This is useful for scanning: compare filesystem mtime with cached mtime
+    /// to determine which items need metadata refresh.
+    pub fn get_cache_entries(&self) -> Result<Vec<CacheEntry>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT path, mtime, size, meta_version FROM media")?;
+
+        let entries = stmt
+            .query_map([], |row| {
+                Ok(CacheEntry {
+                    path: PathBuf::from(row.get::<_, String>(0)?),
+                    mtime: row.get(1)?,
+                    size: row.get(2)?,
+                    meta_version: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to get cache entries")?;
+
+        Ok(entries)
+    }
+
+    /// Gets cache entries as a map keyed by path for fast lookup.
+    pub fn get_cache_map(&self) -> Result<std::collections::HashMap<PathBuf, CacheEntry>> {
+        let entries = self.get_cache_entries()?;
+        let map = entries.into_iter().map(|e| (e.path.clone(), e)).collect();
+        Ok(map)
+    }
+
+    /// Updates the thumbnail information for a media item.
+    pub fn update_thumbnail(
+        &self,
+        path: &Path,
+        thumb_path: &Path,
+        thumb_w: u32,
+        thumb_h: u32,
+    ) -> Result<bool> {
         let path_str = path.to_string_lossy();
         let thumb_str = thumb_path.to_string_lossy();
 
@@ -615,6 +1423,174 @@ impl MediaStore {
         Ok(rows_affected > 0)
     }
 
+    /// Updates the HiDPI (2x) thumbnail information for a media item.
+    pub fn update_thumbnail_2x(
+        &self,
+        path: &Path,
+        thumb_path: &Path,
+        thumb_w: u32,
+        thumb_h: u32,
+    ) -> Result<bool> {
+        let path_str = path.to_string_lossy();
+        let thumb_str = thumb_path.to_string_lossy();
+
+        let rows_affected = self
+            .conn
+            .execute(
+                "
+            UPDATE media
+            SET thumb_path_2x = ?1, thumb_w_2x = ?2, thumb_h_2x = ?3
+            WHERE path = ?4
+            ",
+                params![thumb_str.as_ref(), thumb_w, thumb_h, path_str.as_ref()],
+            )
+            .context("Failed to update 2x thumbnail info")?;
+
+        Ok(rows_affected > 0)
+    }
+
+    /// Forgets both thumbnail variants, the BlurHash and the perceptual hash of
+    /// a media item, e.g. after its pixels changed on disk.
+    pub fn clear_thumbnails(&self, path: &Path) -> Result<bool> {
+        let path_str = path.to_string_lossy();
+        let rows_affected = self
+            .conn
+            .execute(
+                "
+            UPDATE media
+            SET thumb_path = NULL, thumb_w = NULL, thumb_h = NULL,
+                thumb_path_2x = NULL, thumb_w_2x = NULL, thumb_h_2x = NULL,
+                blurhash = NULL, phash = NULL
+            WHERE path = ?1
+            ",
+                params![path_str.as_ref()],
+            )
+            .context("Failed to clear thumbnail info")?;
+
+        Ok(rows_affected > 0)
+    }
+
+    /// Gets the recorded 1x and 2x thumbnails for a media item.
+    pub fn get_thumbnail_variants(&self, path: &Path) -> Result<Option<ThumbnailVariants>> {
+        fn info(path: Option<String>, w: Option<u32>, h: Option<u32>) -> Option<ThumbnailInfo> {
+            Some(ThumbnailInfo {
+                path: PathBuf::from(path?),
+                width: w?,
+                height: h?,
+            })
+        }
+
+        let path_str = path.to_string_lossy();
+        self.conn
+            .query_row(
+                "
+            SELECT thumb_path, thumb_w, thumb_h, thumb_path_2x, thumb_w_2x, thumb_h_2x
+            FROM media WHERE path = ?1
+            ",
+                params![path_str.as_ref()],
+                |row| {
+                    Ok(ThumbnailVariants {
+                        thumb_1x: info(row.get(0)?, row.get(1)?, row.get(2)?),
+                        thumb_2x: info(row.get(3)?, row.get(4)?, row.get(5)?),
+                    })
+                },
+            )
+            .optional()
+            .context("Failed to get thumbnail info")
+    }
+
+    /// Recorded 2x thumbnails of the given paths; paths without one are left
+    /// out. Reads every row that has a 2x variant once, like `ratings_for_paths`.
+    pub fn thumbnails_2x_for_paths(
+        &self,
+        paths: &[PathBuf],
+    ) -> Result<std::collections::HashMap<PathBuf, ThumbnailInfo>> {
+        let wanted: std::collections::HashSet<&PathBuf> = paths.iter().collect();
+        let mut stmt = self.conn.prepare_cached(
+            "
+            SELECT path, thumb_path_2x, thumb_w_2x, thumb_h_2x
+            FROM media
+            WHERE thumb_path_2x IS NOT NULL AND thumb_w_2x IS NOT NULL AND thumb_h_2x IS NOT NULL
+            ",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                PathBuf::from(row.get::<_, String>(0)?),
+                ThumbnailInfo {
+                    path: PathBuf::from(row.get::<_, String>(1)?),
+                    width: row.get(2)?,
+                    height: row.get(3)?,
+                },
+            ))
+        })?;
+        let mut thumbnails = std::collections::HashMap::new();
+        for row in rows {
+            let (path, info) = row?;
+            if wanted.contains(&path) {
+                thumbnails.insert(path, info);
+            }
+        }
+        Ok(thumbnails)
+    }
+
+    /// Stores BlurHash placeholders for media items in a single transaction.
+    pub fn update_blurhashes(&mut self, hashes: &[(PathBuf, String)]) -> Result<usize> {
+        if hashes.is_empty() {
+            return Ok(0);
+        }
+
+        let tx = self.conn.transaction()?;
+        let mut count = 0;
+        {
+            let mut stmt = tx.prepare_cached("UPDATE media SET blurhash = ?1 WHERE path = ?2")?;
+            for (path, hash) in hashes {
+                count += stmt.execute(params![hash, path.to_string_lossy().as_ref()])?;
+            }
+        }
+        tx.commit().context("Failed to store blurhashes")?;
+
+        Ok(count)
+    }
+
+    /// Stores 64-bit perceptual hashes for media items in a single transaction.
+    pub fn update_phashes(&mut self, hashes: &[(PathBuf, u64)]) -> Result<usize> {
+        if hashes.is_empty() {
+            return Ok(0);
+        }
+
+        let tx = self.conn.transaction()?;
+        let mut count = 0;
+        {
+            let mut stmt = tx.prepare_cached("UPDATE media SET phash = ?1 WHERE path = ?2")?;
+            for (path, hash) in hashes {
+                // SQLite integers are signed; keep the bit pattern.
+                count += stmt.execute(params![*hash as i64, path.to_string_lossy().as_ref()])?;
+            }
+        }
+        tx.commit().context("Failed to store perceptual hashes")?;
+
+        Ok(count)
+    }
+
+    /// Groups hashed media whose perceptual hashes differ in at most
+    /// `max_hamming` bits. Each cluster has at least two paths, sorted; clusters
+    /// are ordered by their first path.
+    pub fn find_duplicates(&self, max_hamming: u32) -> Result<Vec<Vec<PathBuf>>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT path, phash FROM media WHERE phash IS NOT NULL ORDER BY path")?;
+        let hashes = stmt
+            .query_map([], |row| {
+                Ok((
+                    PathBuf::from(row.get::<_, String>(0)?),
+                    row.get::<_, i64>(1)? as u64,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to read perceptual hashes")?;
+        Ok(cluster_by_hamming(&hashes, max_hamming))
+    }
+
     /// Updates the last_seen timestamp for items, used during scanning.
     pub fn touch_last_seen(&self, paths: &[PathBuf], timestamp: i64) -> Result<usize> {
         if paths.is_empty() {
@@ -676,6 +1652,59 @@ impl MediaStore {
         Ok(rows > 0)
     }
 
+    /// Deletes several media items by path in a single transaction.
+    pub fn delete_media_batch(&mut self, paths: &[PathBuf]) -> Result<usize> {
+        if paths.is_empty() {
+            return Ok(0);
+        }
+
+        let tx = self.conn.transaction()?;
+        let mut count = 0;
+        {
+            let mut stmt = tx.prepare_cached("DELETE FROM media WHERE path = ?1")?;
+            for path in paths {
+                count += stmt.execute(params![path.to_string_lossy().as_ref()])?;
+            }
+        }
+        tx.commit()?;
+
+        Ok(count)
+    }
+
+    /// Points every row that refers to `old` at `new` after the file was moved
+    /// or renamed, so its thumbnail, favorite, albums, tags, rating and
+    /// history follow it. Rows already stored for `new` are replaced. Returns
+    /// false if `old` had no media row. The thumbnail files are still named
+    /// for `old`; `thumbnails::cache::update_moved_path` renames them too.
+    pub fn update_path(&mut self, old: &Path, new: &Path) -> Result<bool> {
+        let old_str = old.to_string_lossy();
+        let new_str = new.to_string_lossy();
+        let tx = self.conn.transaction()?;
+        let moved = tx.execute(
+            "UPDATE OR REPLACE media SET path = ?2 WHERE path = ?1",
+            params![old_str.as_ref(), new_str.as_ref()],
+        )?;
+        for table in [
+            "favorites",
+            "ratings",
+            "album_items",
+            "media_tags",
+            "media_metadata",
+            "view_history",
+        ] {
+            tx.execute(
+                &format!("UPDATE OR REPLACE {table} SET path = ?2 WHERE path = ?1"),
+                params![old_str.as_ref(), new_str.as_ref()],
+            )?;
+        }
+        tx.execute(
+            "UPDATE albums SET cover_path = ?2 WHERE cover_path = ?1",
+            params![old_str.as_ref(), new_str.as_ref()],
+        )?;
+        tx.commit().context("Failed to update path")?;
+        Ok(moved > 0)
+    }
+
     /// Returns the total count of media items in the database.
     pub fn count_media(&self) -> Result<i64> {
         let count: i64 = self
@@ -684,6 +1713,108 @@ impl MediaStore {
         Ok(count)
     }
 
+    // =========================================================================
+    // Edited Metadata
+    // =========================================================================
+
+    /// Records metadata fields that were written back to a file.
+    ///
+    /// Keywords are stored newline-separated.
+    pub fn set_edited_metadata(
+        &self,
+        path: &Path,
+        date_taken: Option<&str>,
+        description: Option<&str>,
+        keywords: &[String],
+    ) -> Result<()> {
+        let path_str = path.to_string_lossy();
+        let keywords = if keywords.is_empty() {
+            None
+        } else {
+            Some(keywords.join("\n"))
+        };
+        self.conn
+            .execute(
+                "
+                INSERT INTO media_metadata (path, date_taken, description, keywords, updated_at)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                ON CONFLICT(path) DO UPDATE SET
+                    date_taken = excluded.date_taken,
+                    description = excluded.description,
+                    keywords = excluded.keywords,
+                    updated_at = excluded.updated_at
+                ",
+                params![
+                    path_str.as_ref(),
+                    date_taken,
+                    description,
+                    keywords,
+                    Self::now()
+                ],
+            )
+            .with_context(|| format!("Failed to store edited metadata for {:?}", path))?;
+        Ok(())
+    }
+
+    /// Paths whose edited description or keywords contain `needle_lower`,
+    /// compared case-insensitively, for the grid's filter. Matching happens
+    /// here rather than in SQL because SQLite's `lower()` is ASCII-only.
+    pub fn edited_metadata_matching(
+        &self,
+        needle_lower: &str,
+    ) -> Result<std::collections::HashSet<PathBuf>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT path, description, keywords FROM media_metadata")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+            ))
+        })?;
+
+        let mut matches = std::collections::HashSet::new();
+        for row in rows {
+            let (path, description, keywords) = row.context("Failed to query edited metadata")?;
+            let found = description
+                .iter()
+                .chain(keywords.iter())
+                .any(|text| text.to_lowercase().contains(needle_lower));
+            if found {
+                matches.insert(PathBuf::from(path));
+            }
+        }
+        Ok(matches)
+    }
+
+    // =========================================================================
+    // Settings
+    // =========================================================================
+
+    /// Returns the stored value for a settings key.
+    pub fn get_setting(&self, key: &str) -> Result<Option<String>> {
+        self.conn
+            .query_row(
+                "SELECT value FROM settings WHERE key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()
+            .with_context(|| format!("Failed to read setting {}", key))
+    }
+
+    /// Stores a settings value, replacing any previous one.
+    pub fn set_setting(&self, key: &str, value: &str) -> Result<()> {
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+                params![key, value],
+            )
+            .with_context(|| format!("Failed to store setting {}", key))?;
+        Ok(())
+    }
+
     // =========================================================================
     // Layout Cache Operations
     // =========================================================================
@@ -844,6 +1975,47 @@ impl MediaStore {
         Ok(())
     }
 
+    /// Returns the cached rows for a layout if its stored hash and item count
+    /// still match; `None` means the layout must be recomputed.
+    pub fn get_layout(
+        &self,
+        width_bucket: i32,
+        sort_key: &str,
+        list_hash: &str,
+        item_count: i32,
+    ) -> Result<Option<Vec<LayoutRow>>> {
+        if !self.is_layout_valid(width_bucket, sort_key, list_hash, item_count)? {
+            return Ok(None);
+        }
+        let rows = self.get_layout_rows(width_bucket, sort_key)?;
+        Ok((!rows.is_empty()).then_some(rows))
+    }
+
+    /// Stores a computed layout, replacing any previous rows for the same
+    /// width bucket and sort key, and drops the least recently updated
+    /// layouts beyond `MAX_CACHED_LAYOUTS`.
+    pub fn upsert_layout(&mut self, meta: &LayoutMeta, rows: &[LayoutRow]) -> Result<()> {
+        self.set_layout_meta(meta)?;
+        if rows.is_empty() {
+            self.conn.execute(
+                "DELETE FROM layout_rows WHERE width_bucket = ?1 AND sort_key = ?2",
+                params![meta.width_bucket, meta.sort_key],
+            )?;
+        } else {
+            self.set_layout_rows(rows)?;
+        }
+        self.conn.execute(
+            "
+            DELETE FROM layout_meta
+            WHERE rowid NOT IN (
+                SELECT rowid FROM layout_meta ORDER BY updated_at DESC, rowid DESC LIMIT ?1
+            )
+            ",
+            params![MAX_CACHED_LAYOUTS],
+        )?;
+        Ok(())
+    }
+
     /// Deletes all cached layouts (useful when the item list changes significantly).
     pub fn clear_all_layouts(&self) -> Result<()> {
         self.conn.execute("DELETE FROM layout_meta", [])?;
@@ -879,6 +2051,87 @@ impl MediaStore {
         Ok(())
     }
 
+    /// Forgets files that no longer exist. Compacting the file afterwards is
+    /// left to `compact`.
+    ///
+    /// View history goes whenever its file is missing. Media rows, favorites,
+    /// ratings, album members, and tags are only dropped when the file's
+    /// directory still exists; entries on an unmounted drive survive until it
+    /// comes back, so its rows need no rescan.
+    pub fn maintenance(&mut self) -> Result<MaintenanceReport> {
+        let tx = self.conn.transaction()?;
+
+        fn missing_paths(
+            tx: &Transaction,
+            sql: &str,
+            is_gone: impl Fn(&Path) -> bool,
+        ) -> Result<Vec<String>> {
+            let mut stmt = tx.prepare(sql)?;
+            let paths = stmt
+                .query_map([], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(paths
+                .into_iter()
+                .filter(|path| is_gone(Path::new(path)))
+                .collect())
+        }
+        fn delete_paths(tx: &Transaction, table: &str, paths: &[String]) -> Result<usize> {
+            let mut stmt = tx.prepare(&format!("DELETE FROM {} WHERE path = ?1", table))?;
+            let mut deleted = 0;
+            for path in paths {
+                deleted += stmt.execute(params![path])?;
+            }
+            Ok(deleted)
+        }
+        let file_gone = |path: &Path| !path.exists();
+        let file_deleted = |path: &Path| source_deleted(path);
+
+        let mut report = MaintenanceReport::default();
+        let media = missing_paths(&tx, "SELECT path FROM media", file_deleted)?;
+        report.media_removed = delete_paths(&tx, "media", &media)?;
+        let history = missing_paths(&tx, "SELECT path FROM view_history", file_gone)?;
+        report.history_removed = delete_paths(&tx, "view_history", &history)?;
+        let favorites = missing_paths(&tx, "SELECT path FROM favorites", file_deleted)?;
+        report.favorites_removed = delete_paths(&tx, "favorites", &favorites)?;
+        let rated = missing_paths(&tx, "SELECT path FROM ratings", file_deleted)?;
+        report.ratings_removed = delete_paths(&tx, "ratings", &rated)?;
+        let album_items =
+            missing_paths(&tx, "SELECT DISTINCT path FROM album_items", file_deleted)?;
+        report.album_items_removed = delete_paths(&tx, "album_items", &album_items)?;
+        let tagged = missing_paths(&tx, "SELECT DISTINCT path FROM media_tags", file_deleted)?;
+        report.tags_removed = delete_paths(&tx, "media_tags", &tagged)?;
+        tx.execute(
+            "DELETE FROM tags WHERE id NOT IN (SELECT tag_id FROM media_tags)",
+            [],
+        )?;
+        tx.commit().context("Failed to remove missing files")?;
+        info!(
+            "Database maintenance removed {} rows",
+            report.rows_removed()
+        );
+        Ok(report)
+    }
+
+    /// Rewrites the database file without free pages and returns how many
+    /// bytes that reclaimed. VACUUM holds the write lock until it finishes,
+    /// so call it on the connection that does the writing, and only when
+    /// the user asked for it.
+    pub fn compact(&mut self) -> Result<u64> {
+        let size_before = self.database_size()?;
+        self.conn
+            .execute_batch("PRAGMA wal_checkpoint(TRUNCATE); VACUUM;")
+            .context("Failed to vacuum database")?;
+        let reclaimed = size_before.saturating_sub(self.database_size()?);
+        info!("Database compaction reclaimed {} bytes", reclaimed);
+        Ok(reclaimed)
+    }
+
+    fn database_size(&self) -> Result<u64> {
+        let page_count: i64 = self.conn.query_row("PRAGMA page_count", [], |r| r.get(0))?;
+        let page_size: i64 = self.conn.query_row("PRAGMA page_size", [], |r| r.get(0))?;
+        Ok((page_count * page_size).max(0) as u64)
+    }
+
     /// Runs ANALYZE to update query planner statistics.
     pub fn analyze(&self) -> Result<()> {
         self.conn.execute("ANALYZE", [])?;
@@ -927,24 +2180,468 @@ impl MediaStore {
             warn!("Backed up corrupted database to {:?}", backup_path);
         }
 
-        // Create a fresh database
-        Self::open(path)
+        // Create a fresh database
+        Self::open(path)
+    }
+}
+
+/// A schema change, run inside a transaction that also bumps the version.
+type Migration = fn(&Transaction) -> Result<()>;
+
+/// Schema changes in the order they shipped. Running entry `i` takes the
+/// database to version `i + 1`, which is recorded in `PRAGMA user_version`.
+/// Append new entries; never edit or reorder released ones.
+const MIGRATIONS: &[Migration] = &[
+    migrate_v1_baseline,
+    migrate_v2_view_history,
+    migrate_v3_bookmarks,
+    migrate_v4_capture_details,
+    migrate_v5_gps,
+    migrate_v6_metadata_version,
+    migrate_v7_ratings,
+    migrate_v8_orientation,
+];
+
+/// Version of a database with every migration applied.
+pub const SCHEMA_VERSION: usize = MIGRATIONS.len();
+
+/// The schema as it stood when versioning was introduced. Databases from
+/// before then report version 0 but may already hold any of these tables and
+/// columns, so every step here is idempotent.
+fn migrate_v1_baseline(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "
+        -- Media items table
+        CREATE TABLE IF NOT EXISTS media (
+            path TEXT PRIMARY KEY NOT NULL,
+            media_type INTEGER NOT NULL,
+            mtime INTEGER NOT NULL,
+            size INTEGER NOT NULL,
+            width INTEGER NOT NULL,
+            height INTEGER NOT NULL,
+            duration_ms INTEGER,
+            thumb_path TEXT,
+            thumb_w INTEGER,
+            thumb_h INTEGER,
+            last_seen INTEGER NOT NULL,
+            thumb_path_2x TEXT,
+            thumb_w_2x INTEGER,
+            thumb_h_2x INTEGER,
+            blurhash TEXT,
+            phash BIGINT,
+            rating INTEGER
+        );
+
+        -- Index for scanning/cleanup operations
+        CREATE INDEX IF NOT EXISTS idx_media_last_seen ON media(last_seen);
+        CREATE INDEX IF NOT EXISTS idx_media_mtime ON media(mtime);
+
+        -- Layout metadata table (tracks validity of cached layouts)
+        CREATE TABLE IF NOT EXISTS layout_meta (
+            width_bucket INTEGER NOT NULL,
+            sort_key TEXT NOT NULL,
+            item_count INTEGER NOT NULL,
+            list_hash TEXT NOT NULL,
+            updated_at INTEGER NOT NULL,
+            PRIMARY KEY (width_bucket, sort_key)
+        );
+
+        -- Layout rows table (cached row breaks and heights)
+        CREATE TABLE IF NOT EXISTS layout_rows (
+            width_bucket INTEGER NOT NULL,
+            sort_key TEXT NOT NULL,
+            row_index INTEGER NOT NULL,
+            row_height REAL NOT NULL,
+            start_index INTEGER NOT NULL,
+            end_index INTEGER NOT NULL,
+            PRIMARY KEY (width_bucket, sort_key, row_index),
+            FOREIGN KEY (width_bucket, sort_key)
+                REFERENCES layout_meta(width_bucket, sort_key)
+                ON DELETE CASCADE
+        );
+
+        -- Index for efficient row retrieval
+        CREATE INDEX IF NOT EXISTS idx_layout_rows_bucket_sort
+            ON layout_rows(width_bucket, sort_key);
+
+        -- Favorites table
+        CREATE TABLE IF NOT EXISTS favorites (
+            path TEXT PRIMARY KEY NOT NULL,
+            created_at INTEGER NOT NULL
+        );
+
+        -- Albums table
+        CREATE TABLE IF NOT EXISTS albums (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            cover_path TEXT
+        );
+
+        -- Album items join table
+        CREATE TABLE IF NOT EXISTS album_items (
+            album_id INTEGER NOT NULL,
+            path TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            position INTEGER,
+            PRIMARY KEY (album_id, path),
+            FOREIGN KEY (album_id) REFERENCES albums(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_album_items_path ON album_items(path);
+
+        -- Free-form tags; names compare case-insensitively
+        CREATE TABLE IF NOT EXISTS tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            created_at INTEGER NOT NULL
+        );
+
+        -- Tag assignments join table
+        CREATE TABLE IF NOT EXISTS media_tags (
+            tag_id INTEGER NOT NULL,
+            path TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            PRIMARY KEY (tag_id, path),
+            FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_media_tags_path ON media_tags(path);
+
+        -- Metadata written back to files by the metadata editor
+        CREATE TABLE IF NOT EXISTS media_metadata (
+            path TEXT PRIMARY KEY NOT NULL,
+            date_taken TEXT,
+            description TEXT,
+            keywords TEXT,
+            updated_at INTEGER NOT NULL
+        );
+
+        -- User preferences that outlive a session
+        CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY NOT NULL,
+            value TEXT NOT NULL
+        );
+        ",
+    )
+    .context("Failed to create database tables")?;
+
+    // Columns added after the initial schema; older databases gain them in place.
+    add_column_if_missing(tx, "media", "thumb_path_2x", "TEXT")?;
+    add_column_if_missing(tx, "media", "thumb_w_2x", "INTEGER")?;
+    add_column_if_missing(tx, "media", "thumb_h_2x", "INTEGER")?;
+    add_column_if_missing(tx, "media", "blurhash", "TEXT")?;
+    add_column_if_missing(tx, "media", "phash", "BIGINT")?;
+    add_column_if_missing(tx, "media", "rating", "INTEGER")?;
+    add_column_if_missing(tx, "album_items", "position", "INTEGER")?;
+    add_column_if_missing(tx, "albums", "cover_path", "TEXT")?;
+
+    // Albums predating manual ordering keep their newest-first order.
+    tx.execute(
+        "
+        UPDATE album_items
+        SET position = (
+            SELECT COUNT(*) FROM album_items AS other
+            WHERE other.album_id = album_items.album_id
+              AND (other.created_at > album_items.created_at
+                   OR (other.created_at = album_items.created_at
+                       AND other.path < album_items.path))
+        )
+        WHERE position IS NULL
+        ",
+        [],
+    )
+    .context("Failed to backfill album order")?;
+    Ok(())
+}
+
+/// One row per viewed path, holding when it was last opened.
+fn migrate_v2_view_history(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "
+        CREATE TABLE view_history (
+            path TEXT PRIMARY KEY NOT NULL,
+            viewed_at INTEGER NOT NULL
+        );
+
+        CREATE INDEX idx_view_history_viewed_at ON view_history(viewed_at);
+        ",
+    )
+    .context("Failed to create view history table")
+}
+
+/// Directories pinned to the sidebar, ordered by `position`.
+fn migrate_v3_bookmarks(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "
+        CREATE TABLE bookmarks (
+            path TEXT PRIMARY KEY NOT NULL,
+            label TEXT NOT NULL,
+            position INTEGER NOT NULL
+        );
+        ",
+    )
+    .context("Failed to create bookmarks table")
+}
+
+/// EXIF capture date and camera for the grid captions. Filled in by the
+/// scanner; rows indexed before this are re-extracted through
+/// `migrate_v6_metadata_version`.
+fn migrate_v4_capture_details(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "
+        ALTER TABLE media ADD COLUMN date_taken TEXT;
+        ALTER TABLE media ADD COLUMN camera TEXT;
+        ",
+    )
+    .context("Failed to add capture detail columns")
+}
+
+/// EXIF GPS position in decimal degrees, filled in by the scanner. Rows
+/// indexed before this are re-extracted once `METADATA_VERSION` passes 2.
+fn migrate_v5_gps(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "
+        ALTER TABLE media ADD COLUMN latitude REAL;
+        ALTER TABLE media ADD COLUMN longitude REAL;
+        ",
+    )
+    .context("Failed to add GPS columns")
+}
+
+/// Records which `METADATA_VERSION` extracted each row. Existing rows start
+/// at 0, so the next scan re-extracts them and fills in the capture details
+/// they were indexed without.
+fn migrate_v6_metadata_version(tx: &Transaction) -> Result<()> {
+    tx.execute_batch("ALTER TABLE media ADD COLUMN meta_version INTEGER NOT NULL DEFAULT 0;")
+        .context("Failed to add metadata version column")
+}
+
+/// Ratings keyed by path, so files can be rated before the scanner stores
+/// them. Moves ratings off the legacy `media.rating` column, which is left
+/// unused.
+fn migrate_v7_ratings(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "
+        CREATE TABLE ratings (
+            path TEXT PRIMARY KEY NOT NULL,
+            rating INTEGER NOT NULL
+        );
+
+        CREATE INDEX idx_ratings_rating ON ratings(rating);
+
+        INSERT INTO ratings (path, rating)
+            SELECT path, rating FROM media WHERE rating IS NOT NULL;
+        UPDATE media SET rating = NULL WHERE rating IS NOT NULL;
+        ",
+    )
+    .context("Failed to create ratings table")
+}
+
+/// EXIF orientation, filled in by the scanner so thumbnail cache keys can be
+/// built without opening the file. Rows indexed before this are re-extracted
+/// once `METADATA_VERSION` passes 3.
+fn migrate_v8_orientation(tx: &Transaction) -> Result<()> {
+    tx.execute_batch("ALTER TABLE media ADD COLUMN orientation INTEGER;")
+        .context("Failed to add orientation column")
+}
+
+/// Adds a nullable column to an existing table unless it is already present.
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, decl: &str) -> Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<Result<Vec<_>, _>>()?
+        .iter()
+        .any(|name| name == column);
+    if !exists {
+        conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, decl),
+            [],
+        )
+        .with_context(|| format!("Failed to add column {}.{}", table, column))?;
+        info!("Added column {}.{}", table, column);
+    }
+    Ok(())
+}
+
+/// Albums, favorites and ratings as written by `export_collections`.
+#[derive(Debug, Serialize, Deserialize)]
+struct CollectionsDocument {
+    version: u32,
+    #[serde(default)]
+    favorites: Vec<String>,
+    #[serde(default)]
+    albums: Vec<AlbumDocument>,
+    #[serde(default)]
+    ratings: Vec<RatingDocument>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RatingDocument {
+    path: String,
+    rating: u8,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AlbumDocument {
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cover: Option<String>,
+    /// Member paths in the album's curated order.
+    #[serde(default)]
+    items: Vec<String>,
+}
+
+/// What `import_collections` changed, plus the paths it skipped because they
+/// no longer exist on disk.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub albums: usize,
+    pub album_items_added: usize,
+    pub favorites_added: usize,
+    pub ratings_added: usize,
+    pub missing: Vec<PathBuf>,
+}
+
+/// Rows removed by `maintenance`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MaintenanceReport {
+    pub media_removed: usize,
+    pub favorites_removed: usize,
+    pub ratings_removed: usize,
+    pub album_items_removed: usize,
+    pub tags_removed: usize,
+    pub history_removed: usize,
+}
+
+impl MaintenanceReport {
+    /// Total rows removed across all tables.
+    pub fn rows_removed(&self) -> usize {
+        self.media_removed
+            + self.favorites_removed
+            + self.ratings_removed
+            + self.album_items_removed
+            + self.tags_removed
+            + self.history_removed
+    }
+}
+
+/// Database statistics for debugging and monitoring.
+#[derive(Debug, Clone)]
+pub struct DbStats {
+    pub media_count: i64,
+    pub layout_meta_count: i64,
+    pub layout_rows_count: i64,
+    pub db_size_bytes: i64,
+}
+
+// =========================================================================
+// Helper Functions
+// =========================================================================
+
+/// Whether `path` was deleted rather than sitting on a drive that is not
+/// mounted: the file is missing but its directory is still there.
+pub fn source_deleted(path: &Path) -> bool {
+    !path.exists() && path.parent().is_some_and(|parent| parent.is_dir())
+}
+
+/// Single-linkage clustering. Each hash is looked up in a BK-tree of the
+/// hashes before it, so only candidates within `max_hamming` of some tree
+/// node are compared instead of every pair.
+fn cluster_by_hamming(hashes: &[(PathBuf, u64)], max_hamming: u32) -> Vec<Vec<PathBuf>> {
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    let distance = |a: usize, b: usize| (hashes[a].1 ^ hashes[b].1).count_ones();
+
+    let mut parent: Vec<usize> = (0..hashes.len()).collect();
+    // BK-tree nodes: the hash index plus children keyed by their distance.
+    let mut nodes: Vec<(usize, Vec<(u32, usize)>)> = Vec::with_capacity(hashes.len());
+    for i in 0..hashes.len() {
+        if nodes.is_empty() {
+            nodes.push((i, Vec::new()));
+            continue;
+        }
+
+        let mut stack = vec![0];
+        while let Some(node) = stack.pop() {
+            let d = distance(i, nodes[node].0);
+            if d <= max_hamming {
+                let (a, b) = (root(&mut parent, nodes[node].0), root(&mut parent, i));
+                if a != b {
+                    parent[b] = a;
+                }
+            }
+            for &(edge, child) in &nodes[node].1 {
+                if edge + max_hamming >= d && edge <= d + max_hamming {
+                    stack.push(child);
+                }
+            }
+        }
+
+        let mut node = 0;
+        loop {
+            let d = distance(i, nodes[node].0);
+            match nodes[node].1.iter().find(|(edge, _)| *edge == d) {
+                Some(&(_, child)) => node = child,
+                None => {
+                    let child = nodes.len();
+                    nodes[node].1.push((d, child));
+                    nodes.push((i, Vec::new()));
+                    break;
+                }
+            }
+        }
+    }
+
+    let mut clusters: std::collections::BTreeMap<usize, Vec<PathBuf>> = Default::default();
+    for (i, (path, _)) in hashes.iter().enumerate() {
+        let r = root(&mut parent, i);
+        clusters.entry(r).or_default().push(path.clone());
     }
+    let mut clusters: Vec<Vec<PathBuf>> = clusters
+        .into_values()
+        .filter(|cluster| cluster.len() > 1)
+        .map(|mut cluster| {
+            cluster.sort();
+            cluster
+        })
+        .collect();
+    clusters.sort();
+    clusters
 }
 
-/// Database statistics for debugging and monitoring.
-#[derive(Debug, Clone)]
-pub struct DbStats {
-    pub media_count: i64,
-    pub layout_meta_count: i64,
-    pub layout_rows_count: i64,
-    pub db_size_bytes: i64,
+/// Builds a `MediaItem` from a row selecting `MEDIA_ITEM_COLUMNS`.
+fn media_item_from_row(row: &rusqlite::Row) -> rusqlite::Result<MediaItem> {
+    Ok(MediaItem {
+        path: PathBuf::from(row.get::<_, String>(0)?),
+        media_type: int_to_media_type(row.get(1)?),
+        mtime: row.get(2)?,
+        size: row.get(3)?,
+        width: row.get(4)?,
+        height: row.get(5)?,
+        duration_ms: row.get(6)?,
+        thumb_path: row.get::<_, Option<String>>(7)?.map(PathBuf::from),
+        thumb_w: row.get(8)?,
+        thumb_h: row.get(9)?,
+        last_seen: row.get(10)?,
+        blurhash: row.get(11)?,
+        date_taken: row.get(12)?,
+        camera: row.get(13)?,
+        gps: match (row.get(14)?, row.get(15)?) {
+            (Some(lat), Some(lon)) => Some((lat, lon)),
+            _ => None,
+        },
+        orientation: row.get(16)?,
+    })
 }
 
-// =========================================================================
-// Helper Functions
-// =========================================================================
-
 /// Converts MediaType enum to integer for storage.
 fn media_type_to_int(media_type: MediaType) -> i32 {
     match media_type {
@@ -981,6 +2678,11 @@ mod tests {
             thumb_path: None,
             thumb_w: None,
             thumb_h: None,
+            blurhash: None,
+            date_taken: None,
+            camera: None,
+            gps: None,
+            orientation: None,
             last_seen: SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .unwrap()
@@ -989,175 +2691,1121 @@ mod tests {
     }
 
     #[test]
-    fn test_open_and_create() {
+    fn test_open_and_create() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.sqlite");
+
+        let store = MediaStore::open(&db_path).unwrap();
+        assert!(db_path.exists());
+
+        let stats = store.get_stats().unwrap();
+        assert_eq!(stats.media_count, 0);
+    }
+
+    #[test]
+    fn test_upsert_and_get_media() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.sqlite");
+        let store = MediaStore::open(&db_path).unwrap();
+
+        let item = test_media_item("/test/image.jpg");
+        store.upsert_media(&item).unwrap();
+
+        let retrieved = store.get_media(&item.path).unwrap().unwrap();
+        assert_eq!(retrieved.path, item.path);
+        assert_eq!(retrieved.width, 1920);
+        assert_eq!(retrieved.height, 1080);
+    }
+
+    #[test]
+    fn test_batch_upsert() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.sqlite");
+        let mut store = MediaStore::open(&db_path).unwrap();
+
+        let items: Vec<MediaItem> = (0..100)
+            .map(|i| test_media_item(&format!("/test/image_{}.jpg", i)))
+            .collect();
+
+        let count = store.upsert_media_batch(&items).unwrap();
+        assert_eq!(count, 100);
+
+        let stats = store.get_stats().unwrap();
+        assert_eq!(stats.media_count, 100);
+    }
+
+    #[test]
+    fn test_cache_validity() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.sqlite");
+        let store = MediaStore::open(&db_path).unwrap();
+
+        let item = test_media_item("/test/image.jpg");
+        store.upsert_media(&item).unwrap();
+
+        // Same mtime should be valid
+        assert!(store.is_cache_valid(&item.path, item.mtime).unwrap());
+
+        // Different mtime should be invalid
+        assert!(!store.is_cache_valid(&item.path, item.mtime + 1).unwrap());
+
+        // Non-existent path should be invalid
+        assert!(!store
+            .is_cache_valid(&PathBuf::from("/nonexistent"), 0)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_layout_cache() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.sqlite");
+        let mut store = MediaStore::open(&db_path).unwrap();
+
+        let meta = LayoutMeta {
+            width_bucket: 1200,
+            sort_key: "name_asc".to_string(),
+            item_count: 50,
+            list_hash: "abc123".to_string(),
+            updated_at: MediaStore::now(),
+        };
+        store.set_layout_meta(&meta).unwrap();
+
+        let rows = vec![
+            LayoutRow {
+                width_bucket: 1200,
+                sort_key: "name_asc".to_string(),
+                row_index: 0,
+                row_height: 200.0,
+                start_index: 0,
+                end_index: 4,
+            },
+            LayoutRow {
+                width_bucket: 1200,
+                sort_key: "name_asc".to_string(),
+                row_index: 1,
+                row_height: 220.0,
+                start_index: 5,
+                end_index: 9,
+            },
+        ];
+        store.set_layout_rows(&rows).unwrap();
+
+        // Check validity
+        assert!(store
+            .is_layout_valid(1200, "name_asc", "abc123", 50)
+            .unwrap());
+        assert!(!store
+            .is_layout_valid(1200, "name_asc", "different", 50)
+            .unwrap());
+        assert!(!store
+            .is_layout_valid(1200, "name_asc", "abc123", 100)
+            .unwrap());
+
+        // Retrieve rows
+        let retrieved = store.get_layout_rows(1200, "name_asc").unwrap();
+        assert_eq!(retrieved.len(), 2);
+        assert_eq!(retrieved[0].row_height, 200.0);
+        assert_eq!(retrieved[1].start_index, 5);
+    }
+
+    #[test]
+    fn test_upsert_and_get_layout() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.sqlite");
+        let mut store = MediaStore::open(&db_path).unwrap();
+
+        let layout = |key: &str, hash: &str, updated_at: i64| {
+            let meta = LayoutMeta {
+                width_bucket: 1200,
+                sort_key: key.to_string(),
+                item_count: 3,
+                list_hash: hash.to_string(),
+                updated_at,
+            };
+            let rows = vec![
+                LayoutRow {
+                    width_bucket: 1200,
+                    sort_key: key.to_string(),
+                    row_index: 0,
+                    row_height: 220.0,
+                    start_index: 0,
+                    end_index: 2,
+                },
+                LayoutRow {
+                    width_bucket: 1200,
+                    sort_key: key.to_string(),
+                    row_index: 1,
+                    row_height: 220.0,
+                    start_index: 2,
+                    end_index: 3,
+                },
+            ];
+            (meta, rows)
+        };
+
+        let (meta, rows) = layout("dir:/a|name", "h1", 1);
+        store.upsert_layout(&meta, &rows).unwrap();
+        let cached = store.get_layout(1200, "dir:/a|name", "h1", 3).unwrap();
+        assert_eq!(cached.map(|r| r.len()), Some(2));
+        assert!(store
+            .get_layout(1200, "dir:/a|name", "h2", 3)
+            .unwrap()
+            .is_none());
+        assert!(store
+            .get_layout(1200, "dir:/a|name", "h1", 4)
+            .unwrap()
+            .is_none());
+
+        // Replacing keeps a single set of rows
+        let (meta, mut rows) = layout("dir:/a|name", "h2", 2);
+        rows.truncate(1);
+        rows[0].end_index = 3;
+        store.upsert_layout(&meta, &rows).unwrap();
+        let cached = store.get_layout(1200, "dir:/a|name", "h2", 3).unwrap();
+        assert_eq!(cached.map(|r| r.len()), Some(1));
+
+        // Oldest layouts are evicted past the cap
+        for i in 0..MAX_CACHED_LAYOUTS {
+            let (meta, rows) = layout(&format!("dir:/{}|name", i), "h", 10 + i);
+            store.upsert_layout(&meta, &rows).unwrap();
+        }
+        assert!(store
+            .get_layout_meta(1200, "dir:/a|name")
+            .unwrap()
+            .is_none());
+        assert!(store
+            .get_layout_rows(1200, "dir:/a|name")
+            .unwrap()
+            .is_empty());
+        assert!(store
+            .get_layout_meta(1200, "dir:/0|name")
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn test_width_bucket() {
+        assert_eq!(MediaStore::width_to_bucket(1920), 1900);
+        assert_eq!(MediaStore::width_to_bucket(1280), 1200);
+        assert_eq!(MediaStore::width_to_bucket(1350), 1300);
+        assert_eq!(MediaStore::width_to_bucket(99), 0);
+    }
+
+    #[test]
+    fn test_delete_stale() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.sqlite");
+        let store = MediaStore::open(&db_path).unwrap();
+
+        let now = MediaStore::now();
+
+        // Insert items with different last_seen times
+        let mut old_item = test_media_item("/test/old.jpg");
+        old_item.last_seen = now - 1000;
+        store.upsert_media(&old_item).unwrap();
+
+        let mut new_item = test_media_item("/test/new.jpg");
+        new_item.last_seen = now;
+        store.upsert_media(&new_item).unwrap();
+
+        // Delete items older than now - 500
+        let deleted = store.delete_stale(now - 500).unwrap();
+        assert_eq!(deleted.len(), 1);
+        assert_eq!(deleted[0], old_item.path);
+
+        // Check that only new item remains
+        assert!(store.get_media(&old_item.path).unwrap().is_none());
+        assert!(store.get_media(&new_item.path).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_get_media_under_and_delete_batch() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.sqlite");
+        let mut store = MediaStore::open(&db_path).unwrap();
+
+        let items: Vec<MediaItem> = [
+            "/photos/a.jpg",
+            "/photos/trip/b.jpg",
+            "/photos-old/c.jpg",
+            "/photos0.jpg",
+            "/other/d.jpg",
+        ]
+        .iter()
+        .map(|p| test_media_item(p))
+        .collect();
+        store.upsert_media_batch(&items).unwrap();
+
+        let recursive = store.get_media_under(Path::new("/photos"), true).unwrap();
+        let mut paths: Vec<_> = recursive.keys().cloned().collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            [
+                PathBuf::from("/photos/a.jpg"),
+                PathBuf::from("/photos/trip/b.jpg")
+            ]
+        );
+        assert_eq!(recursive[Path::new("/photos/a.jpg")].width, 1920);
+
+        let flat = store.get_media_under(Path::new("/photos/"), false).unwrap();
+        assert_eq!(flat.len(), 1);
+        assert!(flat.contains_key(Path::new("/photos/a.jpg")));
+
+        let removed = store
+            .delete_media_batch(&[
+                PathBuf::from("/photos/a.jpg"),
+                PathBuf::from("/photos/missing.jpg"),
+            ])
+            .unwrap();
+        assert_eq!(removed, 1);
+        assert_eq!(store.count_media().unwrap(), 4);
+    }
+
+    #[test]
+    fn test_update_path_carries_collections_along() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.sqlite");
+        let mut store = MediaStore::open(&db_path).unwrap();
+
+        let old = PathBuf::from("/photos/a.jpg");
+        let new = PathBuf::from("/keep/a.jpg");
+        store
+            .upsert_media(&test_media_item("/photos/a.jpg"))
+            .unwrap();
+        store
+            .update_thumbnail(&old, Path::new("/cache/a.jpg"), 256, 144)
+            .unwrap();
+        store.toggle_favorite(&old).unwrap();
+        let album = store.create_album("Keepers").unwrap();
+        store.add_to_album(album, &old).unwrap();
+        store.set_album_cover(album, &old).unwrap();
+        store.add_tag(std::slice::from_ref(&old), "trip").unwrap();
+        store
+            .set_rating(std::slice::from_ref(&old), Some(4))
+            .unwrap();
+
+        assert!(store.update_path(&old, &new).unwrap());
+        assert!(store.get_media(&old).unwrap().is_none());
+        let moved = store.get_media(&new).unwrap().unwrap();
+        assert_eq!(moved.thumb_path, Some(PathBuf::from("/cache/a.jpg")));
+        assert!(store.is_favorite(&new).unwrap());
+        assert_eq!(store.list_album_paths(album).unwrap(), vec![new.clone()]);
+        assert_eq!(store.album_cover(album).unwrap(), Some(new.clone()));
+        assert_eq!(store.tags_for_path(&new).unwrap(), vec!["trip".to_string()]);
+        assert_eq!(store.get_rating(&new).unwrap(), Some(4));
+
+        assert!(!store.update_path(&old, &new).unwrap());
+    }
+
+    #[test]
+    fn test_update_thumbnail() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.sqlite");
+        let store = MediaStore::open(&db_path).unwrap();
+
+        let item = test_media_item("/test/image.jpg");
+        store.upsert_media(&item).unwrap();
+
+        let thumb_path = PathBuf::from("/cache/thumb_abc.jpg");
+        store
+            .update_thumbnail(&item.path, &thumb_path, 256, 144)
+            .unwrap();
+
+        let updated = store.get_media(&item.path).unwrap().unwrap();
+        assert_eq!(updated.thumb_path, Some(thumb_path));
+        assert_eq!(updated.thumb_w, Some(256));
+        assert_eq!(updated.thumb_h, Some(144));
+    }
+
+    #[test]
+    fn test_thumbnail_variants() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.sqlite");
+        let store = MediaStore::open(&db_path).unwrap();
+
+        let item = test_media_item("/test/image.jpg");
+        store.upsert_media(&item).unwrap();
+        let thumb_1x = ThumbnailInfo {
+            path: PathBuf::from("/cache/thumb_1x.jpg"),
+            width: 256,
+            height: 144,
+        };
+        let thumb_2x = ThumbnailInfo {
+            path: PathBuf::from("/cache/thumb_2x.jpg"),
+            width: 512,
+            height: 288,
+        };
+        store
+            .update_thumbnail(&item.path, &thumb_1x.path, 256, 144)
+            .unwrap();
+
+        // Only the 1x variant exists yet
+        let variants = store.get_thumbnail_variants(&item.path).unwrap().unwrap();
+        assert_eq!(variants.thumb_2x, None);
+        assert_eq!(variants.for_scale(2), Some(&thumb_1x));
+
+        store
+            .update_thumbnail_2x(&item.path, &thumb_2x.path, 512, 288)
+            .unwrap();
+        let variants = store.get_thumbnail_variants(&item.path).unwrap().unwrap();
+        assert_eq!(variants.for_scale(1), Some(&thumb_1x));
+        assert_eq!(variants.for_scale(2), Some(&thumb_2x));
+        let batch = store
+            .thumbnails_2x_for_paths(&[item.path.clone(), PathBuf::from("/test/other.jpg")])
+            .unwrap();
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch.get(&item.path), Some(&thumb_2x));
+
+        // A rescan of the unchanged file keeps the 2x variant
+        store.upsert_media(&item).unwrap();
+        let variants = store.get_thumbnail_variants(&item.path).unwrap().unwrap();
+        assert_eq!(variants.thumb_2x, Some(thumb_2x.clone()));
+
+        // An edited file drops it
+        let edited = MediaItem {
+            mtime: item.mtime + 1,
+            ..item.clone()
+        };
+        store.upsert_media(&edited).unwrap();
+        let variants = store.get_thumbnail_variants(&item.path).unwrap().unwrap();
+        assert_eq!(variants.thumb_2x, None);
+        store
+            .update_thumbnail_2x(&item.path, &thumb_2x.path, 512, 288)
+            .unwrap();
+
+        assert!(store.clear_thumbnails(&item.path).unwrap());
+        let variants = store.get_thumbnail_variants(&item.path).unwrap().unwrap();
+        assert_eq!(variants.for_scale(1), None);
+    }
+
+    #[test]
+    fn test_migrates_legacy_media_table() {
         let dir = tempdir().unwrap();
         let db_path = dir.path().join("test.sqlite");
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE media (
+                    path TEXT PRIMARY KEY NOT NULL,
+                    media_type INTEGER NOT NULL,
+                    mtime INTEGER NOT NULL,
+                    size INTEGER NOT NULL,
+                    width INTEGER NOT NULL,
+                    height INTEGER NOT NULL,
+                    duration_ms INTEGER,
+                    thumb_path TEXT,
+                    thumb_w INTEGER,
+                    thumb_h INTEGER,
+                    last_seen INTEGER NOT NULL
+                );
+                INSERT INTO media VALUES ('/test/old.jpg', 0, 1, 2, 3, 4, NULL,
+                    '/cache/old.jpg', 256, 192, 5);",
+            )
+            .unwrap();
+        }
 
         let store = MediaStore::open(&db_path).unwrap();
-        assert!(db_path.exists());
-
-        let stats = store.get_stats().unwrap();
-        assert_eq!(stats.media_count, 0);
+        let path = PathBuf::from("/test/old.jpg");
+        let variants = store.get_thumbnail_variants(&path).unwrap().unwrap();
+        assert_eq!(variants.thumb_2x, None);
+        assert_eq!(
+            variants.for_scale(2).map(|t| t.path.clone()),
+            Some(PathBuf::from("/cache/old.jpg"))
+        );
+        assert!(store
+            .update_thumbnail_2x(&path, Path::new("/cache/old@2x.jpg"), 512, 384)
+            .unwrap());
     }
 
     #[test]
-    fn test_upsert_and_get_media() {
+    fn test_blurhash_roundtrip() {
         let dir = tempdir().unwrap();
         let db_path = dir.path().join("test.sqlite");
-        let store = MediaStore::open(&db_path).unwrap();
+        let mut store = MediaStore::open(&db_path).unwrap();
 
         let item = test_media_item("/test/image.jpg");
         store.upsert_media(&item).unwrap();
+        assert_eq!(store.get_media(&item.path).unwrap().unwrap().blurhash, None);
 
-        let retrieved = store.get_media(&item.path).unwrap().unwrap();
-        assert_eq!(retrieved.path, item.path);
-        assert_eq!(retrieved.width, 1920);
-        assert_eq!(retrieved.height, 1080);
+        let hash = "LKO2?U%2Tw=w]~RBVZRi};RPxuwH".to_string();
+        let updated = store
+            .update_blurhashes(&[(item.path.clone(), hash.clone())])
+            .unwrap();
+        assert_eq!(updated, 1);
+        assert_eq!(
+            store.get_media(&item.path).unwrap().unwrap().blurhash,
+            Some(hash.clone())
+        );
+
+        // A cache-hit placeholder for the unchanged file keeps the hash
+        store.upsert_media(&item).unwrap();
+        assert_eq!(
+            store.get_media(&item.path).unwrap().unwrap().blurhash,
+            Some(hash)
+        );
+
+        // An edited file drops it
+        let mut edited = item.clone();
+        edited.mtime += 1;
+        store.upsert_media_batch(&[edited]).unwrap();
+        assert_eq!(store.get_media(&item.path).unwrap().unwrap().blurhash, None);
     }
 
     #[test]
-    fn test_batch_upsert() {
+    fn test_capture_details_roundtrip() {
+        let dir = tempdir().unwrap();
+        let mut store = MediaStore::open(&dir.path().join("test.sqlite")).unwrap();
+
+        let mut item = test_media_item("/test/dir/image.jpg");
+        item.date_taken = Some("2024-05-01 12:34:56".to_string());
+        item.camera = Some("FUJIFILM X-T4".to_string());
+        item.gps = Some((-33.8568, 151.2153));
+        item.orientation = Some(6);
+        item.thumb_path = Some(PathBuf::from("/cache/thumb.jpg"));
+        item.thumb_w = Some(455);
+        item.thumb_h = Some(256);
+        store
+            .upsert_media_batch(std::slice::from_ref(&item))
+            .unwrap();
+
+        let under = store
+            .get_media_under(Path::new("/test/dir"), false)
+            .unwrap();
+        assert_eq!(under[&item.path], item);
+        assert!(store.get_cache_map().unwrap()[&item.path].is_current(item.mtime, item.size));
+
+        // A cache-hit placeholder keeps the details of the unchanged file
+        let mut placeholder = item.clone();
+        placeholder.width = 0;
+        placeholder.height = 0;
+        placeholder.thumb_path = None;
+        placeholder.thumb_w = None;
+        placeholder.thumb_h = None;
+        placeholder.date_taken = None;
+        placeholder.camera = None;
+        placeholder.gps = None;
+        placeholder.orientation = None;
+        store.upsert_media(&placeholder).unwrap();
+        assert_eq!(store.get_media(&item.path).unwrap().unwrap(), item);
+
+        // Rows extracted before orientation support are due for another pass
+        store
+            .conn
+            .execute("UPDATE media SET meta_version = 2", [])
+            .unwrap();
+        assert!(!store.get_cache_map().unwrap()[&item.path].is_current(item.mtime, item.size));
+    }
+
+    #[test]
+    fn test_find_duplicates_clusters_close_hashes() {
         let dir = tempdir().unwrap();
         let db_path = dir.path().join("test.sqlite");
         let mut store = MediaStore::open(&db_path).unwrap();
 
-        let items: Vec<MediaItem> = (0..100)
-            .map(|i| test_media_item(&format!("/test/image_{}.jpg", i)))
+        let paths = ["/a.jpg", "/b.jpg", "/c.jpg", "/d.jpg", "/e.jpg"];
+        for path in paths {
+            store.upsert_media(&test_media_item(path)).unwrap();
+        }
+        // a~b (2 bits), b~c (3 bits) chain into one cluster; d is far away;
+        // e has the top bit set to check the signed round trip.
+        let hashes = [
+            (PathBuf::from("/a.jpg"), 0b0000_0000u64),
+            (PathBuf::from("/b.jpg"), 0b0000_0011),
+            (PathBuf::from("/c.jpg"), 0b0001_1111),
+            (PathBuf::from("/d.jpg"), u64::MAX >> 1),
+            (PathBuf::from("/e.jpg"), u64::MAX),
+        ];
+        assert_eq!(store.update_phashes(&hashes).unwrap(), 5);
+
+        let clusters = store.find_duplicates(3).unwrap();
+        assert_eq!(
+            clusters,
+            vec![
+                vec![
+                    PathBuf::from("/a.jpg"),
+                    PathBuf::from("/b.jpg"),
+                    PathBuf::from("/c.jpg")
+                ],
+                vec![PathBuf::from("/d.jpg"), PathBuf::from("/e.jpg")],
+            ]
+        );
+
+        // A modified file loses its stale hash on the next scan.
+        let mut changed = test_media_item("/a.jpg");
+        changed.mtime += 1;
+        store.upsert_media(&changed).unwrap();
+        assert_eq!(store.find_duplicates(3).unwrap().len(), 2);
+        assert!(store.clear_thumbnails(Path::new("/b.jpg")).unwrap());
+        assert_eq!(
+            store.find_duplicates(3).unwrap(),
+            vec![vec![PathBuf::from("/d.jpg"), PathBuf::from("/e.jpg")]]
+        );
+    }
+
+    #[test]
+    fn test_cluster_by_hamming_matches_pairwise_comparison() {
+        // Hashes a few bits apart from a handful of bases, with exact repeats.
+        let mut state = 0x9E37_79B9_7F4A_7C15u64;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        let bases: Vec<u64> = (0..8).map(|_| next()).collect();
+        let hashes: Vec<(PathBuf, u64)> = (0..400)
+            .map(|i| {
+                let flips = next() & next() & next() & next();
+                let hash = bases[i % bases.len()] ^ if i % 7 == 0 { 0 } else { flips };
+                (PathBuf::from(format!("/{:03}.jpg", i)), hash)
+            })
             .collect();
 
-        let count = store.upsert_media_batch(&items).unwrap();
-        assert_eq!(count, 100);
+        for max_hamming in [0, 3, 6] {
+            let mut parent: Vec<usize> = (0..hashes.len()).collect();
+            fn root(parent: &[usize], mut i: usize) -> usize {
+                while parent[i] != i {
+                    i = parent[i];
+                }
+                i
+            }
+            for i in 0..hashes.len() {
+                for j in (i + 1)..hashes.len() {
+                    if (hashes[i].1 ^ hashes[j].1).count_ones() <= max_hamming {
+                        let (a, b) = (root(&parent, i), root(&parent, j));
+                        parent[b] = a;
+                    }
+                }
+            }
+            let mut expected: std::collections::HashMap<usize, Vec<PathBuf>> = Default::default();
+            for (i, (path, _)) in hashes.iter().enumerate() {
+                expected
+                    .entry(root(&parent, i))
+                    .or_default()
+                    .push(path.clone());
+            }
+            let mut expected: Vec<Vec<PathBuf>> = expected
+                .into_values()
+                .filter(|cluster| cluster.len() > 1)
+                .collect();
+            expected.sort();
 
-        let stats = store.get_stats().unwrap();
-        assert_eq!(stats.media_count, 100);
+            assert_eq!(cluster_by_hamming(&hashes, max_hamming), expected);
+        }
     }
 
     #[test]
-    fn test_cache_validity() {
+    fn test_edited_metadata_roundtrip() {
         let dir = tempdir().unwrap();
         let db_path = dir.path().join("test.sqlite");
         let store = MediaStore::open(&db_path).unwrap();
+        let path = PathBuf::from("/test/photo.jpg");
 
-        let item = test_media_item("/test/image.jpg");
-        store.upsert_media(&item).unwrap();
-
-        // Same mtime should be valid
-        assert!(store.is_cache_valid(&item.path, item.mtime).unwrap());
+        assert!(store.edited_metadata_matching("dusk").unwrap().is_empty());
 
-        // Different mtime should be invalid
-        assert!(!store.is_cache_valid(&item.path, item.mtime + 1).unwrap());
+        let keywords = vec!["Beach".to_string(), "sunset".to_string()];
+        store
+            .set_edited_metadata(&path, Some("2020:01:02 03:04:05"), None, &keywords)
+            .unwrap();
+        store
+            .set_edited_metadata(&path, Some("2021:01:02 03:04:05"), Some("Dusk"), &keywords)
+            .unwrap();
+        store
+            .set_edited_metadata(Path::new("/test/other.jpg"), None, Some("Noon"), &[])
+            .unwrap();
 
-        // Non-existent path should be invalid
-        assert!(!store
-            .is_cache_valid(&PathBuf::from("/nonexistent"), 0)
-            .unwrap());
+        let only_photo = std::collections::HashSet::from([path.clone()]);
+        assert_eq!(store.edited_metadata_matching("dusk").unwrap(), only_photo);
+        assert_eq!(store.edited_metadata_matching("beach").unwrap(), only_photo);
+        assert_eq!(store.edited_metadata_matching("no").unwrap().len(), 1);
+        // Dates are not searched
+        assert!(store.edited_metadata_matching("2021").unwrap().is_empty());
     }
 
     #[test]
-    fn test_layout_cache() {
+    fn test_batch_favorites_and_album_items() {
         let dir = tempdir().unwrap();
         let db_path = dir.path().join("test.sqlite");
         let mut store = MediaStore::open(&db_path).unwrap();
+        let paths: Vec<PathBuf> = (0..3)
+            .map(|i| PathBuf::from(format!("/test/{}.jpg", i)))
+            .collect();
 
-        let meta = LayoutMeta {
-            width_bucket: 1200,
-            sort_key: "name_asc".to_string(),
-            item_count: 50,
-            list_hash: "abc123".to_string(),
-            updated_at: MediaStore::now(),
-        };
-        store.set_layout_meta(&meta).unwrap();
+        store.toggle_favorite(&paths[0]).unwrap();
+        assert_eq!(store.set_favorites(&paths, true).unwrap(), 2);
+        assert_eq!(store.list_favorite_paths().unwrap().len(), 3);
+        assert_eq!(store.set_favorites(&paths[..2], false).unwrap(), 2);
+        assert_eq!(store.list_favorite_paths().unwrap(), vec![paths[2].clone()]);
+        assert_eq!(
+            store.favorites_for_paths(&paths[1..]).unwrap(),
+            std::collections::HashSet::from([paths[2].clone()])
+        );
+        assert!(store.favorites_for_paths(&paths[..2]).unwrap().is_empty());
+
+        let album = store.create_album("Trip").unwrap();
+        store.add_to_album(album, &paths[1]).unwrap();
+        assert_eq!(store.add_to_album_batch(album, &paths).unwrap(), 2);
+        assert_eq!(store.list_album_paths(album).unwrap().len(), 3);
+        let other = store.create_album("Family").unwrap();
+        store.add_to_album(other, &paths[0]).unwrap();
+        let membership = store.album_membership_for_paths(&paths[..2]).unwrap();
+        assert_eq!(membership.len(), 2);
+        assert_eq!(membership[&paths[0]], vec![album, other]);
+        assert_eq!(membership[&paths[1]], vec![album]);
+        assert_eq!(
+            store.remove_from_album_batch(album, &paths[..2]).unwrap(),
+            2
+        );
+        assert_eq!(
+            store.list_album_paths(album).unwrap(),
+            vec![paths[2].clone()]
+        );
+    }
 
-        let rows = vec![
-            LayoutRow {
-                width_bucket: 1200,
-                sort_key: "name_asc".to_string(),
-                row_index: 0,
-                row_height: 200.0,
-                start_index: 0,
-                end_index: 4,
-            },
-            LayoutRow {
-                width_bucket: 1200,
-                sort_key: "name_asc".to_string(),
-                row_index: 1,
-                row_height: 220.0,
-                start_index: 5,
-                end_index: 9,
-            },
-        ];
-        store.set_layout_rows(&rows).unwrap();
+    #[test]
+    fn test_bookmarks_keep_their_order() {
+        let dir = tempdir().unwrap();
+        let mut store = MediaStore::open(&dir.path().join("test.sqlite")).unwrap();
+        let paths: Vec<PathBuf> = ["/photos", "/photos/2024", "/scans"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        for path in &paths {
+            let label = path.file_name().unwrap().to_string_lossy();
+            assert!(store.add_bookmark(path, &label).unwrap());
+        }
+        assert!(!store.add_bookmark(&paths[0], "again").unwrap());
+        let listed = store.list_bookmarks().unwrap();
+        assert_eq!(listed[0], (paths[0].clone(), "photos".to_string()));
+        assert_eq!(listed.len(), 3);
+
+        assert!(store.move_bookmark(&paths[2], 0).unwrap());
+        assert!(!store.move_bookmark(Path::new("/other"), 0).unwrap());
+        assert!(store.remove_bookmark(&paths[1]).unwrap());
+        let order: Vec<PathBuf> = store
+            .list_bookmarks()
+            .unwrap()
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(order, vec![paths[2].clone(), paths[0].clone()]);
+    }
 
-        // Check validity
-        assert!(store
-            .is_layout_valid(1200, "name_asc", "abc123", 50)
-            .unwrap());
-        assert!(!store
-            .is_layout_valid(1200, "name_asc", "different", 50)
-            .unwrap());
+    #[test]
+    fn test_move_item_in_album() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.sqlite");
+        let mut store = MediaStore::open(&db_path).unwrap();
+        let album = store.create_album("Show").unwrap();
+        let paths: Vec<PathBuf> = (0..4)
+            .map(|i| PathBuf::from(format!("/test/{}.jpg", i)))
+            .collect();
+        for path in &paths {
+            store.add_to_album(album, path).unwrap();
+        }
+        // Newest first until reordered
+        let listed = store.list_album_paths(album).unwrap();
+        assert_eq!(listed, paths.iter().rev().cloned().collect::<Vec<_>>());
+
+        assert!(store.move_item_in_album(album, &paths[0], 0).unwrap());
+        assert!(store.move_item_in_album(album, &paths[3], 99).unwrap());
         assert!(!store
-            .is_layout_valid(1200, "name_asc", "abc123", 100)
+            .move_item_in_album(album, Path::new("/test/other.jpg"), 0)
             .unwrap());
+        let expected = vec![
+            paths[0].clone(),
+            paths[2].clone(),
+            paths[1].clone(),
+            paths[3].clone(),
+        ];
+        assert_eq!(store.list_album_paths(album).unwrap(), expected);
 
-        // Retrieve rows
-        let retrieved = store.get_layout_rows(1200, "name_asc").unwrap();
-        assert_eq!(retrieved.len(), 2);
-        assert_eq!(retrieved[0].row_height, 200.0);
-        assert_eq!(retrieved[1].start_index, 5);
+        // Reopening keeps the curated order
+        drop(store);
+        let store = MediaStore::open(&db_path).unwrap();
+        assert_eq!(store.list_album_paths(album).unwrap(), expected);
     }
 
     #[test]
-    fn test_width_bucket() {
-        assert_eq!(MediaStore::width_to_bucket(1920), 1900);
-        assert_eq!(MediaStore::width_to_bucket(1280), 1200);
-        assert_eq!(MediaStore::width_to_bucket(1350), 1300);
-        assert_eq!(MediaStore::width_to_bucket(99), 0);
+    fn test_migration_moves_ratings_off_media_rows() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("rated.sqlite");
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute_batch(
+                "
+                CREATE TABLE media (
+                    path TEXT PRIMARY KEY NOT NULL,
+                    media_type INTEGER NOT NULL,
+                    mtime INTEGER NOT NULL,
+                    size INTEGER NOT NULL,
+                    width INTEGER NOT NULL,
+                    height INTEGER NOT NULL,
+                    duration_ms INTEGER,
+                    thumb_path TEXT,
+                    thumb_w INTEGER,
+                    thumb_h INTEGER,
+                    last_seen INTEGER NOT NULL,
+                    rating INTEGER
+                );
+                INSERT INTO media VALUES ('/test/rated.jpg', 0, 1, 2, 30, 40, NULL, NULL, NULL, NULL, 5, 4);
+                ",
+            )
+            .unwrap();
+        }
+
+        let mut store = MediaStore::open(&db_path).unwrap();
+        let rated = PathBuf::from("/test/rated.jpg");
+        assert_eq!(store.get_rating(&rated).unwrap(), Some(4));
+
+        // The rating no longer depends on the media row
+        store
+            .delete_media_batch(std::slice::from_ref(&rated))
+            .unwrap();
+        assert_eq!(store.get_rating(&rated).unwrap(), Some(4));
     }
 
     #[test]
-    fn test_delete_stale() {
+    fn test_migrations_upgrade_unversioned_database() {
         let dir = tempdir().unwrap();
-        let db_path = dir.path().join("test.sqlite");
-        let store = MediaStore::open(&db_path).unwrap();
+        let db_path = dir.path().join("old.sqlite");
+        {
+            // A cache from before versioning: version 0, early columns only
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute_batch(
+                "
+                CREATE TABLE media (
+                    path TEXT PRIMARY KEY NOT NULL,
+                    media_type INTEGER NOT NULL,
+                    mtime INTEGER NOT NULL,
+                    size INTEGER NOT NULL,
+                    width INTEGER NOT NULL,
+                    height INTEGER NOT NULL,
+                    duration_ms INTEGER,
+                    thumb_path TEXT,
+                    thumb_w INTEGER,
+                    thumb_h INTEGER,
+                    last_seen INTEGER NOT NULL
+                );
+                INSERT INTO media VALUES ('/test/old.jpg', 0, 1, 2, 30, 40, NULL, NULL, NULL, NULL, 5);
+                ",
+            )
+            .unwrap();
+        }
 
-        let now = MediaStore::now();
+        let mut store = MediaStore::open(&db_path).unwrap();
+        let version: i64 = store
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version as usize, SCHEMA_VERSION);
+        let old = PathBuf::from("/test/old.jpg");
+        assert_eq!(store.get_media(&old).unwrap().unwrap().width, 30);
+        // Indexed before capture details existed, so due for re-extraction
+        assert!(!store.get_cache_map().unwrap()[&old].is_current(1, 2));
+        store
+            .set_rating(std::slice::from_ref(&old), Some(4))
+            .unwrap();
+        assert_eq!(store.get_rating(&old).unwrap(), Some(4));
+        drop(store);
 
-        // Insert items with different last_seen times
-        let mut old_item = test_media_item("/test/old.jpg");
-        old_item.last_seen = now - 1000;
-        store.upsert_media(&old_item).unwrap();
+        // Reopening runs nothing and keeps the data
+        let store = MediaStore::open(&db_path).unwrap();
+        assert_eq!(store.get_rating(&old).unwrap(), Some(4));
+    }
 
-        let mut new_item = test_media_item("/test/new.jpg");
-        new_item.last_seen = now;
-        store.upsert_media(&new_item).unwrap();
+    #[test]
+    fn test_view_history_is_distinct_recent_first_and_capped() {
+        let dir = tempdir().unwrap();
+        let store = MediaStore::open(&dir.path().join("test.sqlite")).unwrap();
+        let a = PathBuf::from("/test/a.jpg");
+        let b = PathBuf::from("/test/b.jpg");
+        store.record_view(&a, 10).unwrap();
+        store.record_view(&b, 20).unwrap();
+        store.record_view(&a, 30).unwrap();
+        assert_eq!(store.list_recent_paths(10).unwrap(), vec![a.clone(), b]);
+        assert_eq!(store.list_recent_paths(1).unwrap(), vec![a]);
+
+        for i in 0..(MAX_VIEW_HISTORY + VIEW_HISTORY_TRIM_SLACK + 1) {
+            store
+                .record_view(Path::new(&format!("/test/{}.jpg", i)), 100 + i)
+                .unwrap();
+        }
+        let recent = store.list_recent_paths(usize::MAX >> 1).unwrap();
+        assert!(recent.len() as i64 <= MAX_VIEW_HISTORY + VIEW_HISTORY_TRIM_SLACK);
+        assert!(!recent.contains(&PathBuf::from("/test/a.jpg")));
+    }
 
-        // Delete items older than now - 500
-        let deleted = store.delete_stale(now - 500).unwrap();
-        assert_eq!(deleted.len(), 1);
-        assert_eq!(deleted[0], old_item.path);
+    #[test]
+    fn test_maintenance_forgets_deleted_files_only() {
+        let dir = tempdir().unwrap();
+        let mut store = MediaStore::open(&dir.path().join("test.sqlite")).unwrap();
+        let kept = dir.path().join("kept.jpg");
+        let deleted = dir.path().join("deleted.jpg");
+        let unmounted = PathBuf::from("/no/such/volume/photo.jpg");
+        std::fs::write(&kept, b"").unwrap();
+        let all = vec![kept.clone(), deleted.clone(), unmounted.clone()];
+        for path in &all {
+            store
+                .upsert_media(&test_media_item(&path.to_string_lossy()))
+                .unwrap();
+            store.record_view(path, 1).unwrap();
+        }
+        store.set_favorites(&all, true).unwrap();
+        let album = store.create_album("Trip").unwrap();
+        store.add_to_album_batch(album, &all).unwrap();
+        store.add_tag(&all, "beach").unwrap();
+        store.set_rating(&all, Some(3)).unwrap();
+
+        let report = store.maintenance().unwrap();
+        assert_eq!(report.media_removed, 1);
+        assert_eq!(report.history_removed, 2);
+        assert_eq!(report.favorites_removed, 1);
+        assert_eq!(report.ratings_removed, 1);
+        assert_eq!(report.album_items_removed, 1);
+        assert_eq!(report.tags_removed, 1);
+
+        assert!(store.get_media(&kept).unwrap().is_some());
+        assert!(store.get_media(&deleted).unwrap().is_none());
+        assert!(store.get_media(&unmounted).unwrap().is_some());
+        assert_eq!(store.list_recent_paths(10).unwrap(), vec![kept.clone()]);
+        assert!(store.is_favorite(&unmounted).unwrap());
+        assert!(!store.is_favorite(&deleted).unwrap());
+        assert_eq!(store.list_album_paths(album).unwrap().len(), 2);
+        assert_eq!(store.paths_for_tag("beach").unwrap().len(), 2);
+
+        store.compact().unwrap();
+        assert!(store.get_media(&kept).unwrap().is_some());
+    }
 
-        // Check that only new item remains
-        assert!(store.get_media(&old_item.path).unwrap().is_none());
-        assert!(store.get_media(&new_item.path).unwrap().is_some());
+    #[test]
+    fn test_media_batch_temp_table_matches_per_path_lookup() {
+        let dir = tempdir().unwrap();
+        let mut store = MediaStore::open(&dir.path().join("test.sqlite")).unwrap();
+        let items: Vec<MediaItem> = (0..3000)
+            .map(|i| test_media_item(&format!("/test/{:04}.jpg", i)))
+            .collect();
+        store.upsert_media_batch(&items).unwrap();
+
+        // Reverse order, unknown paths, and a repeat all have to survive
+        let mut paths: Vec<PathBuf> = items.iter().rev().map(|item| item.path.clone()).collect();
+        paths.insert(10, PathBuf::from("/test/missing.jpg"));
+        paths.push(items[5].path.clone());
+        assert!(paths.len() > MEDIA_BATCH_TEMP_TABLE_THRESHOLD);
+
+        let joined = store.get_media_batch_joined(&paths).unwrap();
+        let each = store.get_media_batch_each(&paths).unwrap();
+        assert_eq!(joined.len(), 3001);
+        assert_eq!(joined, each);
+        assert_eq!(joined[0].path, items[2999].path);
+        assert_eq!(joined[3000].path, items[5].path);
+
+        // The temp table is empty again for the next call
+        assert_eq!(store.get_media_batch(&paths).unwrap(), each);
     }
 
     #[test]
-    fn test_update_thumbnail() {
+    fn test_newer_schema_is_refused() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("newer.sqlite");
+        drop(MediaStore::open(&db_path).unwrap());
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            conn.pragma_update(None, "user_version", SCHEMA_VERSION as i64 + 1)
+                .unwrap();
+        }
+        let err = MediaStore::open(&db_path).err().unwrap();
+        assert!(format!("{:#}", err).contains("newer than this build supports"));
+    }
+
+    #[test]
+    fn test_album_cover_prefers_pinned_member() {
         let dir = tempdir().unwrap();
         let db_path = dir.path().join("test.sqlite");
         let store = MediaStore::open(&db_path).unwrap();
+        let album = store.create_album("Covers").unwrap();
+        assert_eq!(store.album_cover(album).unwrap(), None);
 
-        let item = test_media_item("/test/image.jpg");
-        store.upsert_media(&item).unwrap();
+        let a = PathBuf::from("/test/a.jpg");
+        let b = PathBuf::from("/test/b.jpg");
+        store.add_to_album(album, &a).unwrap();
+        store.add_to_album(album, &b).unwrap();
+        assert_eq!(store.album_cover(album).unwrap(), Some(b.clone()));
 
-        let thumb_path = PathBuf::from("/cache/thumb_abc.jpg");
+        store.set_album_cover(album, &a).unwrap();
+        assert_eq!(store.album_cover(album).unwrap(), Some(a.clone()));
+
+        // A cover that left the album falls back to the first item
         store
-            .update_thumbnail(&item.path, &thumb_path, 256, 144)
+            .set_album_cover(album, Path::new("/test/gone.jpg"))
             .unwrap();
+        assert_eq!(store.album_cover(album).unwrap(), Some(b));
+    }
 
-        let updated = store.get_media(&item.path).unwrap().unwrap();
-        assert_eq!(updated.thumb_path, Some(thumb_path));
-        assert_eq!(updated.thumb_w, Some(256));
-        assert_eq!(updated.thumb_h, Some(144));
+    #[test]
+    fn test_collections_round_trip_reports_missing_files() {
+        let dir = tempdir().unwrap();
+        let files: Vec<PathBuf> = ["a.jpg", "b.jpg", "c.jpg"]
+            .iter()
+            .map(|name| {
+                let path = dir.path().join(name);
+                std::fs::write(&path, b"").unwrap();
+                path
+            })
+            .collect();
+
+        let mut source = MediaStore::open(&dir.path().join("source.sqlite")).unwrap();
+        let album = source.create_album("Trip").unwrap();
+        source.add_to_album_batch(album, &files).unwrap();
+        source.move_item_in_album(album, &files[0], 0).unwrap();
+        source.set_album_cover(album, &files[1]).unwrap();
+        source.set_favorites(&files[1..], true).unwrap();
+        source.set_rating(&files[..1], Some(4)).unwrap();
+        source.set_rating(&files[2..], Some(2)).unwrap();
+        let order = source.list_album_paths(album).unwrap();
+
+        let mut exported = Vec::new();
+        source.export_collections(&mut exported).unwrap();
+        std::fs::remove_file(&files[2]).unwrap();
+
+        let mut target = MediaStore::open(&dir.path().join("target.sqlite")).unwrap();
+        let report = target.import_collections(exported.as_slice()).unwrap();
+        assert_eq!(report.albums, 1);
+        assert_eq!(report.album_items_added, 2);
+        assert_eq!(report.favorites_added, 1);
+        assert_eq!(report.ratings_added, 1);
+        assert_eq!(report.missing, vec![files[2].clone()]);
+
+        let (imported, _) = target.list_albums().unwrap().remove(0);
+        let expected: Vec<PathBuf> = order.into_iter().filter(|p| *p != files[2]).collect();
+        assert_eq!(target.list_album_paths(imported).unwrap(), expected);
+        assert_eq!(
+            target.album_cover(imported).unwrap(),
+            Some(files[1].clone())
+        );
+        assert_eq!(
+            target.list_favorite_paths().unwrap(),
+            vec![files[1].clone()]
+        );
+        assert_eq!(target.get_rating(&files[0]).unwrap(), Some(4));
+
+        // Importing again changes nothing, and local ratings win
+        target.set_rating(&files[..1], Some(1)).unwrap();
+        let again = target.import_collections(exported.as_slice()).unwrap();
+        assert_eq!(
+            again.album_items_added + again.favorites_added + again.ratings_added,
+            0
+        );
+        assert_eq!(target.get_rating(&files[0]).unwrap(), Some(1));
+
+        let newer = format!("{{\"version\": {}}}", COLLECTIONS_FORMAT_VERSION + 1);
+        assert!(target.import_collections(newer.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_rename_and_delete_album() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.sqlite");
+        let store = MediaStore::open(&db_path).unwrap();
+        let trip = store.create_album("Trip").unwrap();
+        let work = store.create_album("Work").unwrap();
+        store.add_to_album(trip, Path::new("/test/a.jpg")).unwrap();
+
+        let err = store.rename_album(trip, "Work").unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+        store.rename_album(trip, "Trip 2026").unwrap();
+        assert_eq!(
+            store.list_albums().unwrap(),
+            vec![(trip, "Trip 2026".to_string()), (work, "Work".to_string())]
+        );
+
+        assert!(store.delete_album(trip).unwrap());
+        assert!(!store.delete_album(trip).unwrap());
+        assert_eq!(store.list_albums().unwrap().len(), 1);
+        assert!(store.list_album_paths(trip).unwrap().is_empty());
+        assert!(store
+            .album_ids_for_path(Path::new("/test/a.jpg"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_ratings_survive_rescans() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.sqlite");
+        let mut store = MediaStore::open(&db_path).unwrap();
+        let items: Vec<MediaItem> = (0..3)
+            .map(|i| MediaItem::new(PathBuf::from(format!("/test/{}.jpg", i)), 10, 10))
+            .collect();
+        let paths: Vec<PathBuf> = items.iter().map(|item| item.path.clone()).collect();
+        store.upsert_media_batch(&items).unwrap();
+
+        assert_eq!(store.set_rating(&paths[..2], Some(4)).unwrap(), 2);
+        store.set_rating(&paths[1..2], Some(9)).unwrap();
+        let unscanned = PathBuf::from("/test/unscanned.jpg");
+        assert_eq!(
+            store
+                .set_rating(std::slice::from_ref(&unscanned), Some(3))
+                .unwrap(),
+            1
+        );
+
+        store.upsert_media_batch(&items).unwrap();
+        assert_eq!(store.get_rating(&paths[0]).unwrap(), Some(4));
+        assert_eq!(store.get_rating(&paths[1]).unwrap(), Some(5));
+        assert_eq!(store.get_rating(&unscanned).unwrap(), Some(3));
+        assert_eq!(store.list_rated_paths(5).unwrap(), vec![paths[1].clone()]);
+
+        store.set_rating(&paths[..1], None).unwrap();
+        let ratings = store.ratings_for_paths(&paths).unwrap();
+        assert_eq!(ratings.len(), 1);
+        assert_eq!(ratings.get(&paths[1]), Some(&5));
+    }
+
+    #[test]
+    fn test_tags_add_remove_and_lookup() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.sqlite");
+        let mut store = MediaStore::open(&db_path).unwrap();
+        let paths: Vec<PathBuf> = (0..3)
+            .map(|i| PathBuf::from(format!("/test/{}.jpg", i)))
+            .collect();
+
+        assert_eq!(store.add_tag(&paths[..2], "portrait").unwrap(), 2);
+        assert_eq!(store.add_tag(&paths, "Portrait").unwrap(), 1);
+        store.add_tag(&paths[..1], "export").unwrap();
+        assert_eq!(store.list_tags().unwrap(), vec!["export", "portrait"]);
+        assert_eq!(
+            store.tags_for_path(&paths[0]).unwrap(),
+            vec!["export", "portrait"]
+        );
+        assert_eq!(store.paths_for_tag("PORTRAIT").unwrap().len(), 3);
+
+        assert_eq!(store.remove_tag(&paths[1..], "portrait").unwrap(), 2);
+        assert_eq!(
+            store.paths_for_tag("portrait").unwrap(),
+            vec![paths[0].clone()]
+        );
+        store.remove_tag(&paths[..1], "export").unwrap();
+        assert_eq!(store.list_tags().unwrap(), vec!["portrait"]);
+    }
+
+    #[test]
+    fn test_settings_roundtrip() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.sqlite");
+        {
+            let store = MediaStore::open(&db_path).unwrap();
+            assert!(store.get_setting("grid_row_height").unwrap().is_none());
+            store.set_setting("grid_row_height", "180").unwrap();
+            store.set_setting("grid_row_height", "140").unwrap();
+        }
+
+        let store = MediaStore::open(&db_path).unwrap();
+        assert_eq!(
+            store.get_setting("grid_row_height").unwrap().as_deref(),
+            Some("140")
+        );
     }
 }
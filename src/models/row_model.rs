@@ -14,6 +14,10 @@ pub struct RowModel {
     pub row_index: u32,
     pub height_px: f32,
     pub items: Vec<RowItem>,
+    /// Horizontal spacing between adjacent items.
+    pub gutter_px: f32,
+    /// Draw file names under thumbnails; used at the smallest grid sizes.
+    pub show_captions: bool,
 }
 
 impl RowModel {
@@ -22,6 +26,18 @@ impl RowModel {
             row_index,
             height_px,
             items,
+            gutter_px: 0.0,
+            show_captions: false,
         }
     }
+
+    pub fn with_gutter(mut self, gutter_px: f32) -> Self {
+        self.gutter_px = gutter_px.max(0.0);
+        self
+    }
+
+    pub fn with_captions(mut self, show_captions: bool) -> Self {
+        self.show_captions = show_captions;
+        self
+    }
 }
@@ -2,27 +2,41 @@
 //!
 //! This module provides the `FileScanner` struct which handles:
 //! - Recursive directory scanning using walkdir
+//! - Gitignore-style exclude patterns (`.git`, `node_modules`, ...)
 //! - Media type detection by file extension
 //! - Cache-aware scanning (skip unchanged files based on mtime)
+//! - Incremental rescans that reuse stored items for unchanged files
 //! - Async metadata extraction with batched SQLite writes
-//! - Progress reporting via channels
+//! - Progress reporting via channels or a rate-limited callback
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::UNIX_EPOCH;
+use std::time::{Duration, Instant, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
 use parking_lot::Mutex;
 use tokio::sync::mpsc;
 use tokio::task;
 use tracing::{debug, info, trace, warn};
 use walkdir::WalkDir;
 
-use crate::models::media_store::MediaStore;
-use crate::models::{MediaItem, MediaType};
+use crate::models::media_store::{CacheEntry, MediaStore};
+use crate::models::{MediaItem, MediaType, SortKey};
 use crate::scanner::metadata::MetadataExtractor;
 
+/// Stored rows keyed by path, as loaded at the start of a scan.
+type CacheMap = HashMap<PathBuf, CacheEntry>;
+
+/// Files between clock checks when rate-limiting progress callbacks.
+const PROGRESS_CHECK_EVERY: usize = 64;
+/// Minimum time between two progress callbacks.
+const PROGRESS_MIN_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Exclude patterns applied by `ScanConfig::default()`.
+pub const DEFAULT_EXCLUDE_GLOBS: &[&str] = &[".git", "node_modules", "target"];
+
 /// Configuration for the file scanner.
 #[derive(Debug, Clone)]
 pub struct ScanConfig {
@@ -34,6 +48,20 @@ pub struct ScanConfig {
     pub batch_size: usize,
     /// Whether to follow symbolic links.
     pub follow_symlinks: bool,
+    /// Gitignore-style patterns matched against paths relative to the scan
+    /// root. A pattern without `/` matches at any depth, a leading `/` anchors
+    /// it to the root, and a trailing `/` matches directories only. Matching
+    /// directories are not descended into.
+    pub exclude_globs: Vec<String>,
+    /// Reuse the stored `MediaItem` (dimensions, thumbnails, placeholders) for
+    /// files whose mtime and size still match, instead of re-extracting
+    /// metadata and rewriting their rows.
+    pub incremental: bool,
+    /// Order of the returned items and `ScanResult::paths`.
+    pub sort_key: SortKey,
+    /// Only files with these extensions (without the dot, any case) are
+    /// scanned; empty scans every supported type.
+    pub extensions: Vec<String>,
 }
 
 impl Default for ScanConfig {
@@ -43,7 +71,58 @@ impl Default for ScanConfig {
             max_depth: 0, // unlimited
             batch_size: 100,
             follow_symlinks: false,
+            exclude_globs: DEFAULT_EXCLUDE_GLOBS
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            incremental: false,
+            sort_key: SortKey::Name,
+            extensions: Vec::new(),
+        }
+    }
+}
+
+/// Compiled form of `ScanConfig::exclude_globs`.
+struct ExcludeMatcher {
+    any: GlobSet,
+    dirs_only: GlobSet,
+}
+
+impl ExcludeMatcher {
+    fn new(patterns: &[String]) -> Result<Self> {
+        let mut any = GlobSetBuilder::new();
+        let mut dirs_only = GlobSetBuilder::new();
+        for raw in patterns {
+            let (pattern, dir_only) = match raw.strip_suffix('/') {
+                Some(p) => (p, true),
+                None => (raw.as_str(), false),
+            };
+            let pattern = match pattern.strip_prefix('/') {
+                Some(anchored) => anchored.to_string(),
+                None if !pattern.contains('/') => format!("**/{}", pattern),
+                None => pattern.to_string(),
+            };
+            if pattern.is_empty() {
+                continue;
+            }
+            let glob = GlobBuilder::new(&pattern)
+                .literal_separator(true)
+                .build()
+                .with_context(|| format!("Invalid exclude pattern {:?}", raw))?;
+            if dir_only {
+                dirs_only.add(glob);
+            } else {
+                any.add(glob);
+            }
         }
+        Ok(Self {
+            any: any.build()?,
+            dirs_only: dirs_only.build()?,
+        })
+    }
+
+    fn is_excluded(&self, relative: &Path, is_dir: bool) -> bool {
+        self.any.is_match(relative) || (is_dir && self.dirs_only.is_match(relative))
     }
 }
 
@@ -54,6 +133,13 @@ pub enum ScanProgress {
     Started { path: PathBuf },
     /// A file was discovered (before metadata extraction).
     Discovered { count: usize },
+    /// Periodic update from a long scan. `total` is `None` while the
+    /// directory tree is still being walked.
+    Scanning {
+        seen: usize,
+        total: Option<usize>,
+        current: PathBuf,
+    },
     /// A batch of items was processed and saved.
     BatchSaved { processed: usize, total: usize },
     /// Metadata was extracted for an item.
@@ -69,6 +155,36 @@ pub enum ScanProgress {
     },
 }
 
+/// Forwards `ScanProgress::Scanning` updates at most every
+/// `PROGRESS_MIN_INTERVAL`, checking the clock once per
+/// `PROGRESS_CHECK_EVERY` files.
+struct ProgressThrottle<'a> {
+    on_progress: &'a dyn Fn(ScanProgress),
+    last: Instant,
+}
+
+impl<'a> ProgressThrottle<'a> {
+    fn new(on_progress: &'a dyn Fn(ScanProgress)) -> Self {
+        Self {
+            on_progress,
+            last: Instant::now(),
+        }
+    }
+
+    fn tick(&mut self, seen: usize, total: Option<usize>, current: &Path) {
+        if !seen.is_multiple_of(PROGRESS_CHECK_EVERY) || self.last.elapsed() < PROGRESS_MIN_INTERVAL
+        {
+            return;
+        }
+        self.last = Instant::now();
+        (self.on_progress)(ScanProgress::Scanning {
+            seen,
+            total,
+            current: current.to_path_buf(),
+        });
+    }
+}
+
 /// Result of a completed scan operation.
 #[derive(Debug, Clone)]
 pub struct ScanResult {
@@ -82,6 +198,9 @@ pub struct ScanResult {
     pub error_count: usize,
     /// Paths of all discovered media items (in scan order).
     pub paths: Vec<PathBuf>,
+    /// Stored paths in the scanned scope that no longer exist on disk. The
+    /// scanner leaves them in the store; callers can prune them.
+    pub removed_paths: Vec<PathBuf>,
 }
 
 /// Async file scanner for media directories.
@@ -119,16 +238,32 @@ impl FileScanner {
     pub async fn scan_directory(
         &self,
         dir: &Path,
-        mut store: MediaStore,
+        store: MediaStore,
     ) -> Result<(Vec<MediaItem>, ScanResult)> {
+        self.scan_directory_with_callback(dir, store, |_| {}).await
+    }
+
+    /// Like `scan_directory`, but calls `on_progress` from the scan thread
+    /// with `ScanProgress::Scanning` updates. Calls are rate-limited, so the
+    /// callback only needs to be cheap, not fast.
+    pub async fn scan_directory_with_callback<F>(
+        &self,
+        dir: &Path,
+        mut store: MediaStore,
+        on_progress: F,
+    ) -> Result<(Vec<MediaItem>, ScanResult)>
+    where
+        F: Fn(ScanProgress) + Send + 'static,
+    {
         let dir = dir.to_path_buf();
         let config = self.config.clone();
 
         // Run the scan in a blocking task to avoid blocking the async runtime
-        let result =
-            task::spawn_blocking(move || Self::scan_directory_sync(&dir, &config, &mut store))
-                .await
-                .context("Scan task panicked")??;
+        let result = task::spawn_blocking(move || {
+            Self::scan_directory_sync(&dir, &config, &mut store, &on_progress)
+        })
+        .await
+        .context("Scan task panicked")??;
 
         Ok(result)
     }
@@ -164,26 +299,36 @@ impl FileScanner {
         dir: &Path,
         config: &ScanConfig,
         store: &mut MediaStore,
+        on_progress: &dyn Fn(ScanProgress),
     ) -> Result<(Vec<MediaItem>, ScanResult)> {
         info!("Starting scan of {:?}", dir);
         let scan_time = MediaStore::now();
+        let mut throttle = ProgressThrottle::new(on_progress);
 
         // Get existing cache entries for quick lookup
         let cache_map = store.get_cache_map()?;
         debug!("Loaded {} cached entries", cache_map.len());
 
         // Discover all media files
-        let discovered = Self::discover_files(dir, config)?;
+        let discovered = Self::discover_files_with_progress(dir, config, &mut throttle)?;
         info!("Discovered {} media files", discovered.len());
+        let removed_paths = Self::removed_paths(dir, config, &cache_map, &discovered);
+
+        // Reuse stored items for unchanged files
+        let (mut items, discovered, cache_map) =
+            Self::reuse_cached(dir, config, store, cache_map, discovered, scan_time)?;
+        let reused = items.len();
 
         // Process files and extract metadata
-        let mut items = Vec::with_capacity(discovered.len());
+        let total = reused + discovered.len();
+        items.reserve(discovered.len());
         let mut batch = Vec::with_capacity(config.batch_size);
         let mut new_count = 0;
-        let mut cached_count = 0;
+        let mut cached_count = reused;
         let mut error_count = 0;
 
-        for entry in discovered {
+        for (index, entry) in discovered.into_iter().enumerate() {
+            throttle.tick(reused + index + 1, Some(total), &entry.path);
             match Self::process_entry(&entry, &cache_map, scan_time) {
                 Ok((item, from_cache)) => {
                     if from_cache {
@@ -211,6 +356,7 @@ impl FileScanner {
         if !batch.is_empty() {
             store.upsert_media_batch(&batch)?;
         }
+        config.sort_key.sort_items(&mut items, scan_time as u64);
 
         // Update last_seen for all discovered paths
         let paths: Vec<PathBuf> = items.iter().map(|i| i.path.clone()).collect();
@@ -222,11 +368,16 @@ impl FileScanner {
             cached_items: cached_count,
             error_count,
             paths,
+            removed_paths,
         };
 
         info!(
-            "Scan complete: {} total, {} new, {} cached, {} errors",
-            result.total_files, result.new_items, result.cached_items, result.error_count
+            "Scan complete: {} total, {} new, {} cached, {} errors, {} removed",
+            result.total_files,
+            result.new_items,
+            result.cached_items,
+            result.error_count,
+            result.removed_paths.len()
         );
 
         Ok((items, result))
@@ -252,16 +403,28 @@ impl FileScanner {
         // Discover files
         let discovered = Self::discover_files(dir, config)?;
         let total = discovered.len();
+        let removed_paths = Self::removed_paths(dir, config, &cache_map, &discovered);
 
         let _ = tx.blocking_send(ScanProgress::Discovered { count: total });
 
+        // Reuse stored items for unchanged files
+        let (mut items, discovered, cache_map) =
+            Self::reuse_cached(dir, config, store, cache_map, discovered, scan_time)?;
+        let reused = items.len();
+        for item in &items {
+            let _ = tx.blocking_send(ScanProgress::Extracted {
+                path: item.path.clone(),
+                cached: true,
+            });
+        }
+
         // Process files
-        let mut items = Vec::with_capacity(total);
+        items.reserve(discovered.len());
         let mut batch = Vec::with_capacity(config.batch_size);
         let mut new_count = 0;
-        let mut cached_count = 0;
+        let mut cached_count = reused;
         let mut error_count = 0;
-        let mut processed = 0;
+        let mut processed = reused;
 
         for entry in discovered {
             match Self::process_entry(&entry, &cache_map, scan_time) {
@@ -305,6 +468,7 @@ impl FileScanner {
             store.upsert_media_batch(&batch)?;
             let _ = tx.blocking_send(ScanProgress::BatchSaved { processed, total });
         }
+        config.sort_key.sort_items(&mut items, scan_time as u64);
 
         // Update last_seen
         let paths: Vec<PathBuf> = items.iter().map(|i| i.path.clone()).collect();
@@ -316,6 +480,7 @@ impl FileScanner {
             cached_items: cached_count,
             error_count,
             paths,
+            removed_paths,
         };
 
         let _ = tx.blocking_send(ScanProgress::Completed {
@@ -330,6 +495,14 @@ impl FileScanner {
 
     /// Discovers all media files in a directory.
     fn discover_files(dir: &Path, config: &ScanConfig) -> Result<Vec<DiscoveredEntry>> {
+        Self::discover_files_with_progress(dir, config, &mut ProgressThrottle::new(&|_| {}))
+    }
+
+    fn discover_files_with_progress(
+        dir: &Path,
+        config: &ScanConfig,
+        throttle: &mut ProgressThrottle<'_>,
+    ) -> Result<Vec<DiscoveredEntry>> {
         let mut walker = WalkDir::new(dir).follow_links(config.follow_symlinks);
 
         if !config.recursive {
@@ -338,9 +511,17 @@ impl FileScanner {
             walker = walker.max_depth(config.max_depth);
         }
 
+        let excludes = ExcludeMatcher::new(&config.exclude_globs)?;
         let mut entries = Vec::new();
 
-        for entry in walker.into_iter().filter_map(|e| e.ok()) {
+        let walker = walker.into_iter().filter_entry(|entry| {
+            entry.depth() == 0
+                || entry.path().strip_prefix(dir).map_or(true, |relative| {
+                    !excludes.is_excluded(relative, entry.file_type().is_dir())
+                })
+        });
+
+        for entry in walker.filter_map(|e| e.ok()) {
             // Skip directories
             if entry.file_type().is_dir() {
                 continue;
@@ -355,6 +536,14 @@ impl FileScanner {
                 Some(t) => t,
                 None => continue, // Skip non-media files
             };
+            if !config.extensions.is_empty()
+                && !config
+                    .extensions
+                    .iter()
+                    .any(|e| e.eq_ignore_ascii_case(ext))
+            {
+                continue;
+            }
 
             // Get file metadata
             let metadata = match entry.metadata() {
@@ -380,6 +569,7 @@ impl FileScanner {
                 mtime,
                 size,
             });
+            throttle.tick(entries.len(), None, path);
         }
 
         // Sort by path for consistent ordering
@@ -388,15 +578,88 @@ impl FileScanner {
         Ok(entries)
     }
 
+    /// In incremental mode, splits off the entries whose stored row is still
+    /// current and returns those rows as-is (with `last_seen` refreshed),
+    /// along with the entries that still need processing and the cache map
+    /// to process them against.
+    ///
+    /// Entries left over in incremental mode were refused reuse (stale, or a
+    /// row without dimensions), so the returned map is empty there: they are
+    /// extracted afresh instead of answered with cache-hit placeholders.
+    fn reuse_cached(
+        dir: &Path,
+        config: &ScanConfig,
+        store: &MediaStore,
+        cache_map: CacheMap,
+        discovered: Vec<DiscoveredEntry>,
+        scan_time: i64,
+    ) -> Result<(Vec<MediaItem>, Vec<DiscoveredEntry>, CacheMap)> {
+        if !config.incremental {
+            return Ok((Vec::new(), discovered, cache_map));
+        }
+
+        let mut stored = store.get_media_under(dir, config.recursive)?;
+        let mut reused = Vec::new();
+        let mut remaining = Vec::new();
+        for entry in discovered {
+            match stored.remove(&entry.path) {
+                Some(mut item)
+                    if cache_map
+                        .get(&entry.path)
+                        .is_some_and(|cached| cached.is_current(entry.mtime, entry.size))
+                        && item.width > 0
+                        && item.height > 0 =>
+                {
+                    item.last_seen = scan_time;
+                    reused.push(item);
+                }
+                _ => remaining.push(entry),
+            }
+        }
+        debug!(
+            "Incremental scan: {} unchanged, {} to process",
+            reused.len(),
+            remaining.len()
+        );
+
+        Ok((reused, remaining, HashMap::new()))
+    }
+
+    /// Stored paths within the scanned scope that were not discovered and are
+    /// gone from disk. Files skipped by depth or exclude rules still exist, so
+    /// they are not reported.
+    fn removed_paths(
+        dir: &Path,
+        config: &ScanConfig,
+        cache_map: &CacheMap,
+        discovered: &[DiscoveredEntry],
+    ) -> Vec<PathBuf> {
+        let found: HashSet<&Path> = discovered.iter().map(|e| e.path.as_path()).collect();
+        let mut removed: Vec<PathBuf> = cache_map
+            .keys()
+            .filter(|path| {
+                if config.recursive {
+                    path.starts_with(dir)
+                } else {
+                    path.parent() == Some(dir)
+                }
+            })
+            .filter(|path| !found.contains(path.as_path()) && !path.exists())
+            .cloned()
+            .collect();
+        removed.sort();
+        removed
+    }
+
     /// Processes a discovered entry, using cache when possible.
     fn process_entry(
         entry: &DiscoveredEntry,
-        cache_map: &HashMap<PathBuf, (i64, i64)>,
+        cache_map: &CacheMap,
         scan_time: i64,
     ) -> Result<(MediaItem, bool)> {
         // Check if we have a valid cached entry
-        if let Some(&(cached_mtime, cached_size)) = cache_map.get(&entry.path) {
-            if cached_mtime == entry.mtime && cached_size == entry.size {
+        if let Some(cached) = cache_map.get(&entry.path) {
+            if cached.is_current(entry.mtime, entry.size) {
                 // Cache hit - we still need to return a MediaItem
                 // but we don't need to re-extract metadata
                 trace!("Cache hit for {:?}", entry.path);
@@ -415,6 +678,11 @@ impl FileScanner {
                         thumb_path: None,
                         thumb_w: None,
                         thumb_h: None,
+                        blurhash: None,
+                        date_taken: None,
+                        camera: None,
+                        gps: None,
+                        orientation: None,
                         last_seen: scan_time,
                     },
                     true, // from cache
@@ -437,6 +705,11 @@ impl FileScanner {
             thumb_path: None,
             thumb_w: None,
             thumb_h: None,
+            blurhash: None,
+            date_taken: metadata.date_taken,
+            camera: metadata.camera,
+            gps: metadata.gps,
+            orientation: metadata.orientation,
             last_seen: scan_time,
         };
 
@@ -520,22 +793,34 @@ impl ParallelScanner {
         let scan_time = MediaStore::now();
 
         // Get cache map
-        let cache_map = Arc::new(store.get_cache_map()?);
+        let cache_map = store.get_cache_map()?;
 
         // Discover files
         let discovered = FileScanner::discover_files(dir, config)?;
+        info!("Discovered {} media files", discovered.len());
+        let removed_paths = FileScanner::removed_paths(dir, config, &cache_map, &discovered);
+
+        // Reuse stored items for unchanged files
+        let (reused, discovered, cache_map) =
+            FileScanner::reuse_cached(dir, config, store, cache_map, discovered, scan_time)?;
+        let cache_map = Arc::new(cache_map);
+        let reused_count = reused.len();
         let total = discovered.len();
-        info!("Discovered {} media files", total);
 
         if total == 0 {
+            let mut reused = reused;
+            config.sort_key.sort_items(&mut reused, scan_time as u64);
+            let paths: Vec<PathBuf> = reused.iter().map(|i| i.path.clone()).collect();
+            store.touch_last_seen(&paths, scan_time)?;
             return Ok((
-                Vec::new(),
+                reused,
                 ScanResult {
-                    total_files: 0,
+                    total_files: reused_count,
                     new_items: 0,
-                    cached_items: 0,
+                    cached_items: reused_count,
                     error_count: 0,
-                    paths: Vec::new(),
+                    paths,
+                    removed_paths,
                 },
             ));
         }
@@ -543,7 +828,7 @@ impl ParallelScanner {
         // Shared results
         let items = Arc::new(Mutex::new(Vec::with_capacity(total)));
         let new_count = Arc::new(Mutex::new(0usize));
-        let cached_count = Arc::new(Mutex::new(0usize));
+        let cached_count = Arc::new(Mutex::new(reused_count));
         let error_count = Arc::new(Mutex::new(0usize));
 
         // Split work among workers
@@ -615,14 +900,15 @@ impl ParallelScanner {
         let cached_count = *cached_count.lock();
         let error_count = *error_count.lock();
 
-        // Sort items by path for consistent ordering
-        items.sort_by(|a, b| a.path.cmp(&b.path));
-
         // Batch write to database
         for chunk in items.chunks(config.batch_size) {
             store.upsert_media_batch(chunk)?;
         }
 
+        // Sort items for consistent ordering
+        items.extend(reused);
+        config.sort_key.sort_items(&mut items, scan_time as u64);
+
         // Update last_seen
         let paths: Vec<PathBuf> = items.iter().map(|i| i.path.clone()).collect();
         store.touch_last_seen(&paths, scan_time)?;
@@ -633,6 +919,7 @@ impl ParallelScanner {
             cached_items: cached_count,
             error_count,
             paths,
+            removed_paths,
         };
 
         info!(
@@ -676,6 +963,9 @@ mod tests {
         assert_eq!(config.max_depth, 0);
         assert_eq!(config.batch_size, 100);
         assert!(!config.follow_symlinks);
+        assert_eq!(config.exclude_globs, [".git", "node_modules", "target"]);
+        assert!(!config.incremental);
+        assert_eq!(config.sort_key, SortKey::Name);
     }
 
     #[test]
@@ -730,6 +1020,127 @@ mod tests {
         assert_eq!(entries.len(), 1);
     }
 
+    #[test]
+    fn test_discover_files_excludes() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+
+        for sub in [
+            "node_modules/pkg",
+            "app/node_modules/pkg/assets",
+            "app/src",
+            "target/debug",
+            "build/out",
+            "photos/build",
+        ] {
+            fs::create_dir_all(root.join(sub)).unwrap();
+        }
+        create_test_image(&root.join("keep.png"));
+        create_test_image(&root.join("app/src/icon.png"));
+        create_test_image(&root.join("node_modules/pkg/logo.png"));
+        create_test_image(&root.join("app/node_modules/pkg/assets/deep.png"));
+        create_test_image(&root.join("target/debug/out.png"));
+        create_test_image(&root.join("build/out/render.png"));
+        create_test_image(&root.join("photos/build/shot.png"));
+        create_test_image(&root.join("photos/draft.tmp.png"));
+
+        let relative = |entries: Vec<DiscoveredEntry>| -> Vec<String> {
+            entries
+                .iter()
+                .map(|e| {
+                    e.path
+                        .strip_prefix(root)
+                        .unwrap()
+                        .to_string_lossy()
+                        .replace('\\', "/")
+                })
+                .collect()
+        };
+
+        let config = ScanConfig::default();
+        let found = relative(FileScanner::discover_files(root, &config).unwrap());
+        assert_eq!(
+            found,
+            [
+                "app/src/icon.png",
+                "build/out/render.png",
+                "keep.png",
+                "photos/build/shot.png",
+                "photos/draft.tmp.png",
+            ]
+        );
+
+        // Anchored directory pattern only prunes the top-level `build`
+        let config = ScanConfig {
+            exclude_globs: vec!["/build/".into(), "*.tmp.png".into()],
+            ..Default::default()
+        };
+        let found = relative(FileScanner::discover_files(root, &config).unwrap());
+        assert!(found.contains(&"node_modules/pkg/logo.png".to_string()));
+        assert!(found.contains(&"photos/build/shot.png".to_string()));
+        assert!(!found.contains(&"build/out/render.png".to_string()));
+        assert!(!found.contains(&"photos/draft.tmp.png".to_string()));
+
+        // Clearing the defaults scans everything
+        let config = ScanConfig {
+            exclude_globs: Vec::new(),
+            ..Default::default()
+        };
+        assert_eq!(FileScanner::discover_files(root, &config).unwrap().len(), 8);
+    }
+
+    #[test]
+    fn test_discover_files_extensions() {
+        let dir = tempdir().unwrap();
+        create_test_image(&dir.path().join("a.png"));
+        fs::write(dir.path().join("b.JPG"), b"").unwrap();
+        fs::write(dir.path().join("c.webp"), b"").unwrap();
+
+        let config = ScanConfig {
+            extensions: vec!["jpg".into(), "webp".into()],
+            ..Default::default()
+        };
+        let entries = FileScanner::discover_files(dir.path(), &config).unwrap();
+        let names: Vec<_> = entries
+            .iter()
+            .map(|e| e.path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["b.JPG", "c.webp"]);
+    }
+
+    #[test]
+    fn test_invalid_exclude_pattern() {
+        let dir = tempdir().unwrap();
+        let config = ScanConfig {
+            exclude_globs: vec!["[unclosed".into()],
+            ..Default::default()
+        };
+        assert!(FileScanner::discover_files(dir.path(), &config).is_err());
+    }
+
+    #[test]
+    fn test_progress_throttle() {
+        let calls = std::cell::RefCell::new(Vec::new());
+        let record = |p: ScanProgress| {
+            if let ScanProgress::Scanning { seen, total, .. } = p {
+                calls.borrow_mut().push((seen, total));
+            }
+        };
+        let mut throttle = ProgressThrottle::new(&record);
+        throttle.last -= PROGRESS_MIN_INTERVAL;
+
+        let path = Path::new("/a.png");
+        throttle.tick(1, None, path); // not on a check boundary
+        throttle.tick(PROGRESS_CHECK_EVERY, None, path);
+        throttle.tick(PROGRESS_CHECK_EVERY * 2, Some(500), path); // too soon
+        assert_eq!(*calls.borrow(), [(PROGRESS_CHECK_EVERY, None)]);
+
+        throttle.last -= PROGRESS_MIN_INTERVAL;
+        throttle.tick(PROGRESS_CHECK_EVERY * 3, Some(500), path);
+        assert_eq!(calls.borrow().len(), 2);
+        assert_eq!(calls.borrow()[1], (PROGRESS_CHECK_EVERY * 3, Some(500)));
+    }
+
     #[tokio::test]
     async fn test_file_scanner_basic() {
         let dir = tempdir().unwrap();
@@ -780,6 +1191,64 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_incremental_rescan() {
+        let dir = tempdir().unwrap();
+        let db_dir = tempdir().unwrap();
+        let db_path = db_dir.path().join("test.sqlite");
+
+        create_test_image(&dir.path().join("a.png"));
+        create_test_image(&dir.path().join("b.png"));
+        create_test_image(&dir.path().join("c.png"));
+
+        let scanner = FileScanner::with_config(ScanConfig {
+            incremental: true,
+            ..Default::default()
+        });
+        let store = MediaStore::open(&db_path).unwrap();
+        let (_, first) = scanner.scan_directory(dir.path(), store).await.unwrap();
+        assert_eq!((first.new_items, first.cached_items), (3, 0));
+        assert!(first.removed_paths.is_empty());
+
+        // Record a thumbnail so reuse can be told apart from re-extraction
+        let store = MediaStore::open(&db_path).unwrap();
+        store
+            .update_thumbnail(&dir.path().join("a.png"), Path::new("/thumbs/a.jpg"), 1, 1)
+            .unwrap();
+        drop(store);
+
+        fs::remove_file(dir.path().join("c.png")).unwrap();
+        create_test_image(&dir.path().join("d.png"));
+
+        let store = MediaStore::open(&db_path).unwrap();
+        let (items, second) = scanner.scan_directory(dir.path(), store).await.unwrap();
+        assert_eq!(second.total_files, 3);
+        assert_eq!((second.new_items, second.cached_items), (1, 2));
+        assert_eq!(second.removed_paths, [dir.path().join("c.png")]);
+
+        let names: Vec<_> = items
+            .iter()
+            .map(|i| i.path.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(names, ["a.png", "b.png", "d.png"]);
+        assert_eq!(items[0].width, 1);
+        assert_eq!(
+            items[0].thumb_path.as_deref(),
+            Some(Path::new("/thumbs/a.jpg"))
+        );
+
+        // A full scan's cache-hit placeholders leave the stored rows intact
+        let store = MediaStore::open(&db_path).unwrap();
+        FileScanner::new()
+            .scan_directory(dir.path(), store)
+            .await
+            .unwrap();
+        let store = MediaStore::open(&db_path).unwrap();
+        let a = store.get_media(&dir.path().join("a.png")).unwrap().unwrap();
+        assert_eq!((a.width, a.height), (1, 1));
+        assert_eq!(a.thumb_path.as_deref(), Some(Path::new("/thumbs/a.jpg")));
+    }
+
     #[test]
     fn test_parallel_scanner_creation() {
         let scanner = ParallelScanner::new(4);
@@ -21,6 +21,14 @@ pub struct MediaMetadata {
     pub height: u32,
     /// Duration in milliseconds (only for videos).
     pub duration_ms: Option<u32>,
+    /// EXIF capture date (only for images).
+    pub date_taken: Option<String>,
+    /// EXIF camera make and model (only for images).
+    pub camera: Option<String>,
+    /// EXIF GPS latitude and longitude in decimal degrees (only for images).
+    pub gps: Option<(f64, f64)>,
+    /// EXIF orientation, 1 when the file has none.
+    pub orientation: Option<u16>,
     /// Whether extraction encountered non-fatal issues.
     pub has_warnings: bool,
 }
@@ -67,10 +75,17 @@ impl MetadataExtractor {
         match MediaType::from_extension(&ext) {
             Some(MediaType::Image) => {
                 let (width, height) = Self::extract_image_dimensions(path)?;
+                let details = crate::media_info::read_capture_details(path);
                 Ok(MediaMetadata {
                     width,
                     height,
                     duration_ms: None,
+                    date_taken: details.date_taken,
+                    camera: details.camera,
+                    gps: details.gps,
+                    orientation: Some(
+                        crate::image_loader::read_exif_orientation(path).unwrap_or(1),
+                    ),
                     has_warnings: width == ERROR_DIMENSION,
                 })
             }
@@ -81,6 +96,10 @@ impl MetadataExtractor {
                     width: ERROR_DIMENSION,
                     height: ERROR_DIMENSION,
                     duration_ms: None,
+                    date_taken: None,
+                    camera: None,
+                    gps: None,
+                    orientation: None,
                     has_warnings: true,
                 })
             }
@@ -122,7 +141,7 @@ impl MetadataExtractor {
         // Try format-specific parsing
         let result = match ext.as_str() {
             "webm" | "mkv" => Self::parse_matroska_dimensions(path),
-            "mp4" | "mov" => Self::parse_mp4_dimensions(path),
+            "mp4" | "m4v" | "mov" => Self::parse_mp4_dimensions(path),
             "avi" => Self::parse_avi_dimensions(path),
             _ => {
                 debug!("No specific parser for video format: {}", ext);
@@ -158,6 +177,10 @@ impl MetadataExtractor {
             width,
             height,
             duration_ms: None,
+            date_taken: None,
+            camera: None,
+            gps: None,
+            orientation: Some(1),
             has_warnings: width == ERROR_DIMENSION,
         })
     }
@@ -1,2 +1,3 @@
 pub mod file_scanner;
 pub mod metadata;
+pub mod watcher;
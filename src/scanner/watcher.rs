@@ -0,0 +1,135 @@
+//! Live filesystem watcher for the directory currently shown in the grid.
+//!
+//! Wraps a `notify` watcher (inotify on Linux) and coalesces bursts of events
+//! so that copying a hundred files triggers one reload rather than a hundred.
+//! Dropping the `DirectoryWatcher` removes the watches and stops the debounce
+//! thread.
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tracing::{debug, warn};
+
+/// Quiet period after the last event before a change is reported.
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(400);
+
+/// Watches one directory and invokes a callback after changes settle.
+pub struct DirectoryWatcher {
+    path: PathBuf,
+    recursive: bool,
+    _watcher: RecommendedWatcher,
+}
+
+impl DirectoryWatcher {
+    /// Starts watching `path`, calling `on_change` from a background thread
+    /// once no further events have arrived for `debounce`.
+    pub fn new<F>(path: &Path, recursive: bool, debounce: Duration, on_change: F) -> Result<Self>
+    where
+        F: Fn() + Send + 'static,
+    {
+        let (event_tx, event_rx) = mpsc::channel::<()>();
+        let mut watcher =
+            notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+                Ok(event) if is_relevant(&event.kind) => {
+                    let _ = event_tx.send(());
+                }
+                Ok(_) => {}
+                Err(err) => warn!("Filesystem watch error: {}", err),
+            })
+            .context("Failed to create filesystem watcher")?;
+
+        let mode = if recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        watcher
+            .watch(path, mode)
+            .with_context(|| format!("Failed to watch {:?}", path))?;
+
+        // The watcher owns `event_tx`; once it is dropped `recv` fails and the
+        // thread exits.
+        std::thread::spawn(move || {
+            while event_rx.recv().is_ok() {
+                loop {
+                    match event_rx.recv_timeout(debounce) {
+                        Ok(()) => continue,
+                        Err(mpsc::RecvTimeoutError::Timeout) => break,
+                        Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+                on_change();
+            }
+        });
+
+        debug!("Watching {:?} (recursive: {})", path, recursive);
+        Ok(Self {
+            path: path.to_path_buf(),
+            recursive,
+            _watcher: watcher,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn recursive(&self) -> bool {
+        self.recursive
+    }
+}
+
+/// Live watching is on unless `IDXD_WATCH=0`.
+pub fn watching_enabled_from_env() -> bool {
+    std::env::var("IDXD_WATCH")
+        .map(|v| v.trim() != "0")
+        .unwrap_or(true)
+}
+
+/// Reads and other non-mutating events do not change the listing.
+fn is_relevant(kind: &EventKind) -> bool {
+    matches!(
+        kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) | EventKind::Any
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_burst_of_changes_reports_once() {
+        let dir = tempdir().unwrap();
+        let (tx, rx) = mpsc::channel();
+        let watcher =
+            DirectoryWatcher::new(dir.path(), false, Duration::from_millis(100), move || {
+                let _ = tx.send(());
+            })
+            .unwrap();
+        assert_eq!(watcher.path(), dir.path());
+
+        for i in 0..5 {
+            fs::write(dir.path().join(format!("{}.png", i)), b"x").unwrap();
+        }
+
+        rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(rx.recv_timeout(Duration::from_millis(300)).is_err());
+
+        drop(watcher);
+        fs::write(dir.path().join("late.png"), b"x").unwrap();
+        assert!(rx.recv_timeout(Duration::from_millis(300)).is_err());
+    }
+
+    #[test]
+    fn test_access_events_are_ignored() {
+        use notify::event::{AccessKind, CreateKind};
+        assert!(!is_relevant(&EventKind::Access(AccessKind::Any)));
+        assert!(is_relevant(&EventKind::Create(CreateKind::File)));
+    }
+}
@@ -0,0 +1,127 @@
+//! SVG rasterization with resvg.
+//!
+//! Vector files have no fixed resolution, so callers ask for the pixel size
+//! they are about to show: thumbnails render at the cache size and the viewer
+//! renders at its viewport, re-rendering when zoomed past 1:1. Rendering is
+//! done in-process, so it does not depend on gdk-pixbuf having an SVG loader
+//! installed.
+
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+use anyhow::{anyhow, Context, Result};
+use image::{DynamicImage, RgbaImage};
+use resvg::{tiny_skia, usvg};
+
+pub const SVG_EXTENSIONS: &[&str] = &["svg", "svgz"];
+
+pub fn is_svg_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| SVG_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// System fonts for `<text>` elements, loaded once per process.
+fn font_database() -> Arc<usvg::fontdb::Database> {
+    static FONTS: OnceLock<Arc<usvg::fontdb::Database>> = OnceLock::new();
+    FONTS
+        .get_or_init(|| {
+            let mut fonts = usvg::fontdb::Database::new();
+            fonts.load_system_fonts();
+            Arc::new(fonts)
+        })
+        .clone()
+}
+
+/// Parses an SVG or gzip-compressed SVGZ file. Relative references such as
+/// linked images resolve against the file's directory.
+fn load_tree(path: &Path) -> Result<usvg::Tree> {
+    let data = std::fs::read(path).with_context(|| format!("Failed to read SVG: {:?}", path))?;
+    let options = usvg::Options {
+        resources_dir: path.parent().map(Path::to_path_buf),
+        fontdb: font_database(),
+        ..Default::default()
+    };
+    usvg::Tree::from_data(&data, &options)
+        .with_context(|| format!("Failed to parse SVG: {:?}", path))
+}
+
+/// Intrinsic size from the document's width/height or viewBox.
+pub fn read_dimensions(path: &Path) -> Result<(u32, u32)> {
+    let size = load_tree(path)?.size();
+    let (width, height) = (size.width().ceil(), size.height().ceil());
+    if width < 1.0 || height < 1.0 {
+        return Err(anyhow!("SVG has no intrinsic size: {:?}", path));
+    }
+    Ok((width as u32, height as u32))
+}
+
+/// Renders to fit within `max_width` x `max_height`, keeping the aspect ratio.
+/// Unlike raster resizing this scales up as well as down.
+pub fn render(path: &Path, max_width: u32, max_height: u32) -> Result<DynamicImage> {
+    let tree = load_tree(path)?;
+    let size = tree.size();
+    let scale =
+        (max_width.max(1) as f32 / size.width()).min(max_height.max(1) as f32 / size.height());
+    let width = (size.width() * scale).round().max(1.0) as u32;
+    let height = (size.height() * scale).round().max(1.0) as u32;
+
+    let mut pixmap = tiny_skia::Pixmap::new(width, height).ok_or_else(|| {
+        anyhow!(
+            "SVG render size {}x{} is too large: {:?}",
+            width,
+            height,
+            path
+        )
+    })?;
+    let transform = tiny_skia::Transform::from_scale(
+        width as f32 / size.width(),
+        height as f32 / size.height(),
+    );
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+    Ok(DynamicImage::ImageRgba8(pixmap_to_image(&pixmap)))
+}
+
+/// Copies premultiplied pixmap pixels into straight-alpha RGBA.
+fn pixmap_to_image(pixmap: &tiny_skia::Pixmap) -> RgbaImage {
+    let mut pixels = Vec::with_capacity(pixmap.data().len());
+    for pixel in pixmap.pixels() {
+        let color = pixel.demultiply();
+        pixels.extend_from_slice(&[color.red(), color.green(), color.blue(), color.alpha()]);
+    }
+    RgbaImage::from_raw(pixmap.width(), pixmap.height(), pixels)
+        .expect("pixmap holds width x height pixels")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::GenericImageView;
+
+    const SQUARE_ON_WIDE: &str = r##"<svg xmlns="http://www.w3.org/2000/svg" width="40" height="20">
+<rect x="20" width="20" height="20" fill="#ff0000"/>
+</svg>"##;
+
+    #[test]
+    fn renders_to_fit_and_keeps_transparency() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wide.svg");
+        std::fs::write(&path, SQUARE_ON_WIDE).unwrap();
+
+        assert_eq!(read_dimensions(&path).unwrap(), (40, 20));
+
+        let img = render(&path, 200, 200).unwrap();
+        assert_eq!(img.dimensions(), (200, 100));
+        assert_eq!(img.get_pixel(10, 50).0, [0, 0, 0, 0]);
+        assert_eq!(img.get_pixel(150, 50).0, [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn rejects_non_svg_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("broken.svg");
+        std::fs::write(&path, b"not an svg").unwrap();
+        assert!(read_dimensions(&path).is_err());
+    }
+}
@@ -4,20 +4,28 @@
 //! - Memory cache: LRU cache of GdkTexture with configurable size limit
 //!
 //! Filenames are based on xxhash of (path + mtime + size) for fast invalidation.
+//! Non-default thumbnail heights and EXIF orientations are mixed into the hash so
+//! sizes never collide and a changed orientation tag regenerates the thumbnail.
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
 use directories::ProjectDirs;
-use gdk4::prelude::TextureExt;
+use gdk4::prelude::{Cast, TextureExt};
 use gdk4::Texture;
 use lru::LruCache;
 use parking_lot::RwLock;
-use tracing::{debug, trace, warn};
+use tracing::{debug, info, trace, warn};
 use xxhash_rust::xxh3::xxh3_64;
 
-use super::generator::{ThumbnailGenerator, DEFAULT_THUMB_HEIGHT};
+use super::generator::{
+    thumb_height_for_scale, ResizeMode, ThumbFormat, ThumbnailGenerator, DEFAULT_THUMB_HEIGHT,
+    HIDPI_THUMB_HEIGHT,
+};
+use crate::models::{source_deleted, CacheEntry, MediaStore};
 
 /// Default memory cache size in megabytes.
 const DEFAULT_MAX_MEMORY_MB: usize = 192;
@@ -36,6 +44,16 @@ const THUMB_CACHE_VERSION: u8 = 2;
 /// Default capacity for the LRU cache (number of entries).
 const DEFAULT_LRU_CAPACITY: usize = 2048;
 
+/// Default on-disk thumbnail cache cap in megabytes.
+const DEFAULT_MAX_DISK_MB: u64 = 2048;
+
+/// Time budget for the startup orphan sweep.
+const ORPHAN_PRUNE_BUDGET: Duration = Duration::from_secs(2);
+
+/// Unreferenced thumbnails newer than this are kept; their source may still be
+/// waiting to be written to the media table by an in-flight scan.
+const ORPHAN_GRACE: Duration = Duration::from_secs(600);
+
 /// A cached thumbnail entry containing the texture and metadata.
 #[derive(Clone)]
 pub struct CachedThumbnail {
@@ -66,6 +84,8 @@ impl CachedThumbnail {
 pub struct CacheKey {
     /// Hash of path + mtime + size.
     hash: u64,
+    /// Encoding of the on-disk file; only affects the filename extension.
+    format: ThumbFormat,
     /// Original path for debugging.
     #[cfg(debug_assertions)]
     path: PathBuf,
@@ -74,29 +94,255 @@ pub struct CacheKey {
 impl CacheKey {
     /// Create a new cache key from file metadata.
     pub fn new(path: &Path, mtime: i64, size: i64) -> Self {
-        let hash = Self::compute_hash(path, mtime, size);
+        Self::with_thumb_height(path, mtime, size, DEFAULT_THUMB_HEIGHT)
+    }
+
+    /// Key of the `thumb_height` thumbnail of a source file with this EXIF
+    /// orientation, stored as `format`. Everything that writes the shared
+    /// cache names its files through this, so they find each other's output.
+    pub fn for_source(
+        path: &Path,
+        mtime: i64,
+        size: i64,
+        orientation: u16,
+        thumb_height: u32,
+        format: ThumbFormat,
+    ) -> Self {
+        Self::with_thumb_height(path, mtime, size, thumb_height)
+            .with_orientation(orientation)
+            .with_format(format)
+    }
+
+    /// Create a cache key for a thumbnail generated at a specific height.
+    pub fn with_thumb_height(path: &Path, mtime: i64, size: i64, thumb_height: u32) -> Self {
+        let hash = Self::compute_hash(path, mtime, size, thumb_height);
         Self {
             hash,
+            format: ThumbFormat::Jpeg,
             #[cfg(debug_assertions)]
             path: path.to_path_buf(),
         }
     }
 
+    /// Mix the source's EXIF orientation into the key.
+    ///
+    /// Orientation 1 (and a missing tag) leaves the key unchanged.
+    pub fn with_orientation(mut self, orientation: u16) -> Self {
+        if orientation > 1 {
+            let mut data = [0u8; 10];
+            data[..8].copy_from_slice(&self.hash.to_le_bytes());
+            data[8..].copy_from_slice(&orientation.to_le_bytes());
+            self.hash = xxh3_64(&data);
+        }
+        self
+    }
+
+    /// Store the thumbnail on disk in `format` instead of JPEG.
+    pub fn with_format(mut self, format: ThumbFormat) -> Self {
+        self.format = format;
+        self
+    }
+
     /// Compute the xxhash of the key components.
-    fn compute_hash(path: &Path, mtime: i64, size: i64) -> u64 {
+    fn compute_hash(path: &Path, mtime: i64, size: i64, thumb_height: u32) -> u64 {
         // Combine path, mtime, and size into a single buffer for hashing
         let path_str = path.to_string_lossy();
-        let mut data = Vec::with_capacity(path_str.len() + 17);
+        let mut data = Vec::with_capacity(path_str.len() + 21);
         data.push(THUMB_CACHE_VERSION);
         data.extend_from_slice(path_str.as_bytes());
         data.extend_from_slice(&mtime.to_le_bytes());
         data.extend_from_slice(&size.to_le_bytes());
+        // Leave default-height keys unchanged so existing disk caches stay valid
+        if thumb_height != DEFAULT_THUMB_HEIGHT {
+            data.extend_from_slice(&thumb_height.to_le_bytes());
+        }
+        // Likewise only color-managed thumbnails carry the target profile
+        if let Some(tag) = crate::color::cache_tag() {
+            data.extend_from_slice(&tag.to_le_bytes());
+        }
         xxh3_64(&data)
     }
 
     /// Get the filename for disk cache storage.
     pub fn disk_filename(&self) -> String {
-        format!("{:016x}.jpg", self.hash)
+        format!("{:016x}.{}", self.hash, self.format.extension())
+    }
+}
+
+/// Whether `thumb_path` is named for `source` at this mtime and size, at
+/// either the standard or the HiDPI height and under any EXIF orientation.
+/// A thumbnail made before the source was last modified does not match.
+pub fn is_current_thumbnail(thumb_path: &Path, source: &Path, mtime: i64, size: i64) -> bool {
+    thumbnail_key_params(thumb_path, source, mtime, size).is_some()
+}
+
+/// The height and EXIF orientation `thumb_path` was keyed with, if it is
+/// named for `source` at this mtime and size.
+fn thumbnail_key_params(
+    thumb_path: &Path,
+    source: &Path,
+    mtime: i64,
+    size: i64,
+) -> Option<(u32, u16)> {
+    let hash = parse_disk_filename(thumb_path)?;
+    [DEFAULT_THUMB_HEIGHT, HIDPI_THUMB_HEIGHT]
+        .iter()
+        .find_map(|&height| {
+            let base = CacheKey::with_thumb_height(source, mtime, size, height);
+            (1..=8)
+                .find(|&orientation| base.clone().with_orientation(orientation).hash == hash)
+                .map(|orientation| (height, orientation))
+        })
+}
+
+/// Renames a thumbnail named for `old` so it is named for `new` instead,
+/// keeping its height, orientation and format. Cache keys hash the source
+/// path, so a moved file would otherwise never find its thumbnail again.
+/// Returns the new path, or `None` if `thumb_path` is not a current
+/// thumbnail of `old` or could not be renamed.
+pub fn rekey_thumbnail(
+    thumb_path: &Path,
+    old: &Path,
+    new: &Path,
+    mtime: i64,
+    size: i64,
+) -> Option<PathBuf> {
+    let (height, orientation) = thumbnail_key_params(thumb_path, old, mtime, size)?;
+    let hash = CacheKey::with_thumb_height(new, mtime, size, height)
+        .with_orientation(orientation)
+        .hash;
+    let ext = thumb_path.extension()?.to_str()?;
+    let target = thumb_path.with_file_name(format!("{:016x}.{}", hash, ext));
+    match std::fs::rename(thumb_path, &target) {
+        Ok(()) => Some(target),
+        Err(e) => {
+            warn!(
+                "Failed to rename thumbnail {} for moved file: {}",
+                thumb_path.display(),
+                e
+            );
+            None
+        }
+    }
+}
+
+/// Points the database rows of a moved or renamed file at `new` and renames
+/// its recorded thumbnails to match. A thumbnail that cannot be renamed is
+/// deleted and the variants forgotten, so nothing keeps pointing at a file
+/// named for the old path. Returns false if `old` had no media row.
+pub fn update_moved_path(store: &mut MediaStore, old: &Path, new: &Path) -> Result<bool> {
+    if !store.update_path(old, new)? {
+        return Ok(false);
+    }
+    let (Some(item), Some(variants)) = (store.get_media(new)?, store.get_thumbnail_variants(new)?)
+    else {
+        return Ok(true);
+    };
+    let mut stale = false;
+    for (info, is_2x) in [(&variants.thumb_1x, false), (&variants.thumb_2x, true)] {
+        let Some(info) = info else {
+            continue;
+        };
+        match rekey_thumbnail(&info.path, old, new, item.mtime, item.size) {
+            Some(thumb_path) if is_2x => {
+                store.update_thumbnail_2x(new, &thumb_path, info.width, info.height)?;
+            }
+            Some(thumb_path) => {
+                store.update_thumbnail(new, &thumb_path, info.width, info.height)?;
+            }
+            None => {
+                let _ = std::fs::remove_file(&info.path);
+                stale = true;
+            }
+        }
+    }
+    if stale {
+        store.clear_thumbnails(new)?;
+    }
+    Ok(true)
+}
+
+/// Parse a disk cache filename (`{hash:016x}.{ext}`) back to its key hash.
+fn parse_disk_filename(path: &Path) -> Option<u64> {
+    let ext = path.extension()?.to_str()?;
+    if !matches!(ext, "jpg" | "png" | "webp") {
+        return None;
+    }
+    let stem = path.file_stem()?.to_str()?;
+    if stem.len() != 16 {
+        return None;
+    }
+    u64::from_str_radix(stem, 16).ok()
+}
+
+/// Takes the thumbnails of `entries`, keyed at any of `heights`, out of
+/// `files`, removing those whose source was deleted. Returns false if
+/// `deadline` passed before every entry was checked.
+fn match_cache_entries(
+    entries: &[CacheEntry],
+    heights: &[u32],
+    files: &mut HashMap<u64, Vec<(PathBuf, u64, SystemTime)>>,
+    summary: &mut PruneSummary,
+    deadline: Instant,
+) -> bool {
+    for entry in entries {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        let hashes: Vec<u64> = heights
+            .iter()
+            .flat_map(|&height| {
+                let base =
+                    CacheKey::with_thumb_height(&entry.path, entry.mtime, entry.size, height);
+                (1..=8).map(move |orientation| base.clone().with_orientation(orientation).hash)
+            })
+            .filter(|hash| files.contains_key(hash))
+            .collect();
+        if hashes.is_empty() {
+            continue;
+        }
+        let deleted = source_deleted(&entry.path);
+        for hash in hashes {
+            let Some(thumbs) = files.remove(&hash) else {
+                continue;
+            };
+            if deleted {
+                for (path, len, _) in thumbs {
+                    summary.remove(&path, len);
+                }
+            }
+        }
+    }
+    true
+}
+
+/// Outcome of an orphan thumbnail sweep.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PruneSummary {
+    /// Thumbnail files found in the cache directory.
+    pub scanned: usize,
+    /// Files removed because their source is gone.
+    pub removed: usize,
+    /// Bytes freed by the removed files.
+    pub removed_bytes: u64,
+    /// False if the time budget ran out before every media row was checked.
+    pub completed: bool,
+}
+
+impl PruneSummary {
+    fn remove(&mut self, path: &Path, len: u64) {
+        match std::fs::remove_file(path) {
+            Ok(()) => {
+                self.removed += 1;
+                self.removed_bytes += len;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!(
+                "Failed to remove orphan thumbnail {}: {}",
+                path.display(),
+                e
+            ),
+        }
     }
 }
 
@@ -112,6 +358,8 @@ pub struct ThumbnailCache {
     memory_cache: Arc<RwLock<LruCache<u64, CachedThumbnail>>>,
     /// Target thumbnail height.
     thumb_height: u32,
+    /// Encoding for newly generated thumbnails.
+    format: ThumbFormat,
 }
 
 impl ThumbnailCache {
@@ -135,6 +383,7 @@ impl ThumbnailCache {
                 std::num::NonZeroUsize::new(DEFAULT_LRU_CAPACITY).unwrap(),
             ))),
             thumb_height: DEFAULT_THUMB_HEIGHT,
+            format: ThumbFormat::from_env(),
         }
     }
 
@@ -151,6 +400,11 @@ impl ThumbnailCache {
         Ok(proj_dirs.cache_dir().join("thumbs"))
     }
 
+    /// Set the on-disk thumbnail format.
+    pub fn set_format(&mut self, format: ThumbFormat) {
+        self.format = format;
+    }
+
     /// Set the target thumbnail height.
     pub fn set_thumb_height(&mut self, height: u32) {
         self.thumb_height = height;
@@ -160,8 +414,14 @@ impl ThumbnailCache {
     ///
     /// This is the main entry point for retrieving thumbnails.
     /// It checks the memory cache first, then disk cache, and generates if needed.
-    pub fn get_or_generate(&self, path: &Path, mtime: i64, size: i64) -> Result<CachedThumbnail> {
-        let key = CacheKey::new(path, mtime, size);
+    pub fn get_or_generate(
+        &self,
+        path: &Path,
+        mtime: i64,
+        size: i64,
+        orientation: u16,
+    ) -> Result<CachedThumbnail> {
+        let key = self.key_for(path, mtime, size, orientation);
 
         // Try memory cache first
         if let Some(cached) = self.get_from_memory(&key) {
@@ -186,8 +446,8 @@ impl ThumbnailCache {
     }
 
     /// Check if a thumbnail exists in cache (memory or disk).
-    pub fn exists(&self, path: &Path, mtime: i64, size: i64) -> bool {
-        let key = CacheKey::new(path, mtime, size);
+    pub fn exists(&self, path: &Path, mtime: i64, size: i64, orientation: u16) -> bool {
+        let key = self.key_for(path, mtime, size, orientation);
 
         // Check memory cache
         if self.memory_cache.read().contains(&key.hash) {
@@ -198,6 +458,18 @@ impl ThumbnailCache {
         self.disk_path(&key).exists()
     }
 
+    /// Build the cache key for a source file at this cache's thumbnail height.
+    fn key_for(&self, path: &Path, mtime: i64, size: i64, orientation: u16) -> CacheKey {
+        CacheKey::for_source(
+            path,
+            mtime,
+            size,
+            orientation,
+            self.thumb_height,
+            self.format,
+        )
+    }
+
     /// Get a thumbnail from memory cache only.
     pub fn get_from_memory(&self, key: &CacheKey) -> Option<CachedThumbnail> {
         self.memory_cache.write().get(&key.hash).cloned()
@@ -210,8 +482,7 @@ impl ThumbnailCache {
 
     /// Load a thumbnail from disk and add to memory cache.
     fn load_from_disk(&self, key: &CacheKey, disk_path: &Path) -> Result<CachedThumbnail> {
-        let texture = Texture::from_filename(disk_path)
-            .map_err(|e| anyhow::anyhow!("Failed to load texture: {}", e))?;
+        let texture = load_texture(disk_path)?;
 
         let width = texture.width() as u32;
         let height = texture.height() as u32;
@@ -228,11 +499,17 @@ impl ThumbnailCache {
         let disk_path = self.disk_path(key);
 
         // Generate the thumbnail
-        let (width, height) = ThumbnailGenerator::generate(path, &disk_path, self.thumb_height)?;
+        let (result, _) = ThumbnailGenerator::generate_thumbnail_with_format(
+            path,
+            &disk_path,
+            self.thumb_height,
+            ResizeMode::Quality,
+            self.format,
+        )?;
+        let (width, height) = (result.width, result.height);
 
         // Load as texture
-        let texture = Texture::from_filename(&disk_path)
-            .map_err(|e| anyhow::anyhow!("Failed to load generated thumbnail: {}", e))?;
+        let texture = load_texture(&disk_path)?;
 
         let cached = CachedThumbnail::new(texture, width, height);
 
@@ -314,6 +591,170 @@ impl ThumbnailCache {
         Ok(())
     }
 
+    /// Trim the disk cache to at most `limit` bytes, deleting the least
+    /// recently accessed thumbnails first (atime, falling back to mtime).
+    ///
+    /// Safe to run while thumbnails are being generated: files that vanish
+    /// mid-sweep are skipped. Returns the number of files and bytes removed.
+    pub fn enforce_max_bytes(&self, limit: u64) -> Result<(usize, u64)> {
+        if !self.cache_dir.exists() {
+            return Ok((0, 0));
+        }
+
+        let mut entries = Vec::new();
+        let mut total: u64 = 0;
+        for entry in std::fs::read_dir(&self.cache_dir)?.flatten() {
+            let path = entry.path();
+            let is_thumb = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| matches!(e, "jpg" | "png" | "webp"));
+            if !is_thumb {
+                continue;
+            }
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            if !meta.is_file() {
+                continue;
+            }
+            let last_used = meta
+                .accessed()
+                .or_else(|_| meta.modified())
+                .unwrap_or(std::time::UNIX_EPOCH);
+            total += meta.len();
+            entries.push((last_used, meta.len(), path));
+        }
+
+        if total <= limit {
+            return Ok((0, 0));
+        }
+
+        entries.sort_by_key(|(last_used, _, _)| *last_used);
+
+        let mut removed_files = 0;
+        let mut removed_bytes: u64 = 0;
+        for (_, len, path) in entries {
+            if total <= limit {
+                break;
+            }
+            match std::fs::remove_file(&path) {
+                Ok(()) => {
+                    removed_files += 1;
+                    removed_bytes += len;
+                    total -= len;
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    total -= len;
+                }
+                Err(e) => warn!("Failed to evict thumbnail {}: {}", path.display(), e),
+            }
+        }
+
+        debug!(
+            "Evicted {} thumbnails ({} bytes) from disk cache, {} bytes remain",
+            removed_files, removed_bytes, total
+        );
+        Ok((removed_files, removed_bytes))
+    }
+
+    /// Remove thumbnails whose source file was deleted or left the media table.
+    ///
+    /// Filenames only carry the key hash, so the sweep works backwards: every
+    /// media row is re-keyed (1x, 2x and configured height, all orientations)
+    /// and matched against the directory listing. Files left over are read for
+    /// their height and matched again, which finds thumbnails generated at
+    /// other sizes (`--generate-thumbnails --thumb-size`). Thumbnails of rows
+    /// whose file was deleted are removed as they are found, using the same
+    /// rule as `MediaStore::maintenance`, so an unmounted drive keeps its
+    /// thumbnails. Files matching no row at all are only removed once every row
+    /// has been checked within `budget`.
+    pub fn prune_orphan_thumbnails(
+        &self,
+        store: &MediaStore,
+        budget: Duration,
+    ) -> Result<PruneSummary> {
+        let deadline = Instant::now() + budget;
+        let mut summary = PruneSummary::default();
+        if !self.cache_dir.exists() {
+            summary.completed = true;
+            return Ok(summary);
+        }
+
+        let mut files: HashMap<u64, Vec<(PathBuf, u64, SystemTime)>> = HashMap::new();
+        for entry in std::fs::read_dir(&self.cache_dir)?.flatten() {
+            let path = entry.path();
+            let Some(hash) = parse_disk_filename(&path) else {
+                continue;
+            };
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            if !meta.is_file() {
+                continue;
+            }
+            summary.scanned += 1;
+            let modified = meta.modified().unwrap_or(UNIX_EPOCH);
+            files
+                .entry(hash)
+                .or_default()
+                .push((path, meta.len(), modified));
+        }
+
+        let mut heights = vec![DEFAULT_THUMB_HEIGHT, HIDPI_THUMB_HEIGHT];
+        if !heights.contains(&self.thumb_height) {
+            heights.push(self.thumb_height);
+        }
+
+        let entries = store.get_cache_entries()?;
+        summary.completed =
+            match_cache_entries(&entries, &heights, &mut files, &mut summary, deadline);
+        if summary.completed && !files.is_empty() {
+            let mut other_heights: Vec<u32> = files
+                .values()
+                .flatten()
+                .filter_map(|(path, _, _)| image::image_dimensions(path).ok())
+                .map(|(_, height)| height)
+                .filter(|height| !heights.contains(height))
+                .collect();
+            other_heights.sort_unstable();
+            other_heights.dedup();
+            if !other_heights.is_empty() {
+                summary.completed = match_cache_entries(
+                    &entries,
+                    &other_heights,
+                    &mut files,
+                    &mut summary,
+                    deadline,
+                );
+            }
+        }
+
+        if summary.completed {
+            let cutoff = SystemTime::now()
+                .checked_sub(ORPHAN_GRACE)
+                .unwrap_or(UNIX_EPOCH);
+            for (path, len, modified) in files.into_values().flatten() {
+                if modified < cutoff {
+                    summary.remove(&path, len);
+                }
+            }
+        }
+
+        info!(
+            "Pruned {} orphan thumbnails ({} bytes) of {} scanned{}",
+            summary.removed,
+            summary.removed_bytes,
+            summary.scanned,
+            if summary.completed {
+                ""
+            } else {
+                " (time budget exhausted)"
+            }
+        );
+        Ok(summary)
+    }
+
     /// Get the current memory usage in bytes.
     pub fn memory_usage(&self) -> usize {
         *self.current_memory_bytes.read()
@@ -336,8 +777,8 @@ impl ThumbnailCache {
 
     /// Preload a thumbnail into memory cache from disk (if it exists).
     /// Returns true if the thumbnail was loaded, false otherwise.
-    pub fn preload(&self, path: &Path, mtime: i64, size: i64) -> bool {
-        let key = CacheKey::new(path, mtime, size);
+    pub fn preload(&self, path: &Path, mtime: i64, size: i64, orientation: u16) -> bool {
+        let key = self.key_for(path, mtime, size, orientation);
 
         // Already in memory?
         if self.memory_cache.read().contains(&key.hash) {
@@ -354,8 +795,8 @@ impl ThumbnailCache {
     }
 
     /// Remove a specific thumbnail from cache.
-    pub fn remove(&self, path: &Path, mtime: i64, size: i64) -> bool {
-        let key = CacheKey::new(path, mtime, size);
+    pub fn remove(&self, path: &Path, mtime: i64, size: i64, orientation: u16) -> bool {
+        let key = self.key_for(path, mtime, size, orientation);
 
         // Remove from memory
         let memory_removed = if let Some(evicted) = self.memory_cache.write().pop(&key.hash) {
@@ -385,10 +826,122 @@ impl Clone for ThumbnailCache {
             current_memory_bytes: Arc::clone(&self.current_memory_bytes),
             memory_cache: Arc::clone(&self.memory_cache),
             thumb_height: self.thumb_height,
+            format: self.format,
         }
     }
 }
 
+/// Disk path of the default-size thumbnail for `path` in the shared cache
+/// directory, whether or not it has been generated yet.
+pub fn default_thumbnail_path(path: &Path) -> Option<PathBuf> {
+    thumbnail_path_for_height(path, DEFAULT_THUMB_HEIGHT)
+}
+
+/// Disk path of the `thumb_height` thumbnail for `path` in the shared cache directory.
+pub fn thumbnail_path_for_height(path: &Path, thumb_height: u32) -> Option<PathBuf> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let mtime = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let key = CacheKey::with_thumb_height(path, mtime, metadata.len() as i64, thumb_height)
+        .with_format(ThumbFormat::from_env());
+    let cache_dir = ThumbnailCache::default_cache_dir().ok()?;
+    Some(cache_dir.join(key.disk_filename()))
+}
+
+/// Return the cached thumbnail for a video at a display scale factor,
+/// extracting a frame if needed.
+///
+/// The 2x variant is generated together with the 1x one so the frame is
+/// only extracted once.
+pub fn ensure_video_thumbnail(path: &Path, scale_factor: i32) -> Result<PathBuf> {
+    let thumb_height = thumb_height_for_scale(scale_factor);
+    let thumb_path = thumbnail_path_for_height(path, thumb_height)
+        .with_context(|| format!("No thumbnail cache location for {:?}", path))?;
+    if thumb_path.exists() {
+        return Ok(thumb_path);
+    }
+    if thumb_height == DEFAULT_THUMB_HEIGHT {
+        ThumbnailGenerator::generate_thumbnail_with_format(
+            path,
+            &thumb_path,
+            DEFAULT_THUMB_HEIGHT,
+            ResizeMode::Quality,
+            ThumbFormat::from_env(),
+        )?;
+    } else {
+        let thumb_path_1x = default_thumbnail_path(path)
+            .with_context(|| format!("No thumbnail cache location for {:?}", path))?;
+        ThumbnailGenerator::generate_thumbnail_variants(
+            path,
+            &thumb_path_1x,
+            &thumb_path,
+            ResizeMode::Quality,
+            ThumbFormat::from_env(),
+        )?;
+    }
+    Ok(thumb_path)
+}
+
+/// Load a cached thumbnail as a texture.
+///
+/// GDK's loaders cover JPEG and PNG, but WebP depends on an optional
+/// gdk-pixbuf plugin, so fall back to decoding with the image crate.
+pub fn load_texture(path: &Path) -> Result<Texture> {
+    if let Ok(texture) = Texture::from_filename(path) {
+        return Ok(texture);
+    }
+    let rgba = image::open(path)
+        .with_context(|| format!("Failed to load thumbnail: {:?}", path))?
+        .to_rgba8();
+    let (width, height) = rgba.dimensions();
+    let bytes = gdk4::glib::Bytes::from_owned(rgba.into_raw());
+    let texture = gdk4::MemoryTexture::new(
+        width as i32,
+        height as i32,
+        gdk4::MemoryFormat::R8g8b8a8,
+        &bytes,
+        (width * 4) as usize,
+    );
+    Ok(texture.upcast())
+}
+
+/// Disk cache cap in bytes from `IDXD_THUMB_CACHE_MB`; `0` disables the cap.
+pub fn max_disk_bytes_from_env() -> Option<u64> {
+    let mb = std::env::var("IDXD_THUMB_CACHE_MB")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_MAX_DISK_MB);
+    (mb > 0).then(|| mb * 1024 * 1024)
+}
+
+/// Prune orphaned thumbnails and trim the shared thumbnail directory to the
+/// configured cap on a background thread.
+pub fn spawn_disk_cache_maintenance() {
+    std::thread::spawn(|| {
+        let cache = match ThumbnailCache::new_default(MIN_MEMORY_MB) {
+            Ok(cache) => cache,
+            Err(e) => {
+                warn!("Thumbnail disk cache maintenance skipped: {}", e);
+                return;
+            }
+        };
+        let pruned = MediaStore::open_default()
+            .and_then(|store| cache.prune_orphan_thumbnails(&store, ORPHAN_PRUNE_BUDGET));
+        if let Err(e) = pruned {
+            warn!("Orphan thumbnail cleanup failed: {}", e);
+        }
+        if let Some(limit) = max_disk_bytes_from_env() {
+            if let Err(e) = cache.enforce_max_bytes(limit) {
+                warn!("Thumbnail disk cache trim failed: {}", e);
+            }
+        }
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -405,6 +958,31 @@ mod tests {
         assert_ne!(key1.hash, key3.hash);
     }
 
+    #[test]
+    fn test_cache_key_thumb_height() {
+        let path = Path::new("/test/image.jpg");
+        let default = CacheKey::new(path, 1234567890, 1024);
+        let explicit = CacheKey::with_thumb_height(path, 1234567890, 1024, DEFAULT_THUMB_HEIGHT);
+        let larger = CacheKey::with_thumb_height(path, 1234567890, 1024, 512);
+
+        assert_eq!(default.hash, explicit.hash);
+        assert_ne!(default.hash, larger.hash);
+    }
+
+    #[test]
+    fn test_cache_key_orientation() {
+        let base = CacheKey::new(Path::new("/test/image.jpg"), 1234567890, 1024);
+
+        assert_eq!(base.clone().with_orientation(1).hash, base.hash);
+        let hashes: Vec<u64> = (2..=8)
+            .map(|o| base.clone().with_orientation(o).hash)
+            .collect();
+        for (i, hash) in hashes.iter().enumerate() {
+            assert_ne!(*hash, base.hash);
+            assert!(!hashes[i + 1..].contains(hash));
+        }
+    }
+
     #[test]
     fn test_disk_filename() {
         let key = CacheKey::new(Path::new("/test/image.jpg"), 1234567890, 1024);
@@ -413,6 +991,51 @@ mod tests {
         // Should be a 16-character hex string with .jpg extension
         assert!(filename.ends_with(".jpg"));
         assert_eq!(filename.len(), 20); // 16 hex + ".jpg"
+
+        let webp = key.clone().with_format(ThumbFormat::WebP).disk_filename();
+        assert_eq!(webp, filename.replace(".jpg", ".webp"));
+    }
+
+    #[test]
+    fn current_thumbnail_must_match_source_metadata() {
+        let source = Path::new("/test/image.jpg");
+        let key = CacheKey::with_thumb_height(source, 1234567890, 1024, HIDPI_THUMB_HEIGHT)
+            .with_orientation(6)
+            .with_format(ThumbFormat::WebP);
+        let thumb = PathBuf::from("/cache").join(key.disk_filename());
+
+        assert!(is_current_thumbnail(&thumb, source, 1234567890, 1024));
+        assert!(!is_current_thumbnail(&thumb, source, 1234567891, 1024));
+        assert!(!is_current_thumbnail(&thumb, source, 1234567890, 2048));
+        assert!(!is_current_thumbnail(
+            Path::new("/cache/other.jpg"),
+            source,
+            1234567890,
+            1024
+        ));
+    }
+
+    #[test]
+    fn rekeyed_thumbnail_belongs_to_the_new_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = Path::new("/photos/a.jpg");
+        let new = Path::new("/photos/b.jpg");
+        let key = CacheKey::with_thumb_height(old, 1234567890, 1024, HIDPI_THUMB_HEIGHT)
+            .with_orientation(6)
+            .with_format(ThumbFormat::WebP);
+        let thumb = dir.path().join(key.disk_filename());
+        std::fs::write(&thumb, b"x").unwrap();
+
+        let moved = rekey_thumbnail(&thumb, old, new, 1234567890, 1024).unwrap();
+        assert!(!thumb.exists());
+        assert!(moved.exists());
+        assert_eq!(moved.extension().unwrap(), "webp");
+        assert!(is_current_thumbnail(&moved, new, 1234567890, 1024));
+        assert!(!is_current_thumbnail(&moved, old, 1234567890, 1024));
+
+        // A thumbnail of another file is left alone
+        assert_eq!(rekey_thumbnail(&moved, old, new, 1234567890, 1024), None);
+        assert!(moved.exists());
     }
 
     #[test]
@@ -432,4 +1055,118 @@ mod tests {
         let cache = ThumbnailCache::new(temp_dir, 200);
         assert_eq!(cache.max_memory(), 200 * 1024 * 1024);
     }
+
+    #[test]
+    fn test_enforce_max_bytes_evicts_least_recently_accessed() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ThumbnailCache::new(dir.path().to_path_buf(), MIN_MEMORY_MB);
+        let base = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000);
+
+        for (i, name) in ["old.jpg", "mid.webp", "new.png"].iter().enumerate() {
+            let path = dir.path().join(name);
+            std::fs::write(&path, vec![0u8; 100]).unwrap();
+            let when = base + std::time::Duration::from_secs(i as u64 * 60);
+            let times = std::fs::FileTimes::new()
+                .set_accessed(when)
+                .set_modified(when);
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_times(times)
+                .unwrap();
+        }
+        std::fs::write(dir.path().join("notes.txt"), vec![0u8; 500]).unwrap();
+
+        assert_eq!(cache.enforce_max_bytes(1000).unwrap(), (0, 0));
+        assert_eq!(cache.enforce_max_bytes(150).unwrap(), (2, 200));
+        assert!(!dir.path().join("old.jpg").exists());
+        assert!(!dir.path().join("mid.webp").exists());
+        assert!(dir.path().join("new.png").exists());
+        assert!(dir.path().join("notes.txt").exists());
+    }
+
+    #[test]
+    fn test_prune_orphan_thumbnails() {
+        let dir = tempfile::tempdir().unwrap();
+        let thumbs = dir.path().join("thumbs");
+        std::fs::create_dir_all(&thumbs).unwrap();
+        let cache = ThumbnailCache::new(thumbs.clone(), MIN_MEMORY_MB);
+        let store = MediaStore::open(&dir.path().join("test.sqlite")).unwrap();
+
+        let live = dir.path().join("live.jpg");
+        let gone = dir.path().join("gone.jpg");
+        let offline = dir.path().join("unmounted").join("away.jpg");
+        std::fs::write(&live, b"x").unwrap();
+        for path in [&live, &gone, &offline] {
+            store
+                .upsert_media(&crate::models::MediaItem {
+                    path: path.clone(),
+                    media_type: crate::models::MediaType::Image,
+                    mtime: 1234567890,
+                    size: 1024,
+                    width: 64,
+                    height: 64,
+                    duration_ms: None,
+                    thumb_path: None,
+                    thumb_w: None,
+                    thumb_h: None,
+                    blurhash: None,
+                    date_taken: None,
+                    camera: None,
+                    gps: None,
+                    orientation: None,
+                    last_seen: 0,
+                })
+                .unwrap();
+        }
+
+        let live_thumb = thumbs.join(CacheKey::new(&live, 1234567890, 1024).disk_filename());
+        let gone_thumb = thumbs.join(
+            CacheKey::new(&gone, 1234567890, 1024)
+                .with_orientation(6)
+                .with_format(ThumbFormat::WebP)
+                .disk_filename(),
+        );
+        let offline_thumb = thumbs.join(CacheKey::new(&offline, 1234567890, 1024).disk_filename());
+        // Generated with a `--thumb-size` the app itself never uses.
+        let sized_thumb =
+            thumbs.join(CacheKey::with_thumb_height(&live, 1234567890, 1024, 400).disk_filename());
+        image::RgbImage::new(2, 400).save(&sized_thumb).unwrap();
+        let unknown_thumb = thumbs.join("00000000deadbeef.jpg");
+        let old = std::fs::FileTimes::new().set_modified(UNIX_EPOCH + Duration::from_secs(1));
+        for path in [
+            &live_thumb,
+            &gone_thumb,
+            &offline_thumb,
+            &sized_thumb,
+            &unknown_thumb,
+        ] {
+            if !path.exists() {
+                std::fs::write(path, vec![0u8; 10]).unwrap();
+            }
+            std::fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_times(old)
+                .unwrap();
+        }
+        let fresh_unknown = thumbs.join("00000000cafef00d.png");
+        std::fs::write(&fresh_unknown, vec![0u8; 10]).unwrap();
+
+        let summary = cache
+            .prune_orphan_thumbnails(&store, Duration::from_secs(60))
+            .unwrap();
+
+        assert!(summary.completed);
+        assert_eq!(summary.scanned, 6);
+        assert_eq!(summary.removed, 2);
+        assert!(live_thumb.exists());
+        assert!(offline_thumb.exists());
+        assert!(sized_thumb.exists());
+        assert!(!gone_thumb.exists());
+        assert!(!unknown_thumb.exists());
+        assert!(fresh_unknown.exists());
+    }
 }
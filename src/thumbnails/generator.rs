@@ -2,17 +2,36 @@
 //!
 //! Generates thumbnails at approximately 256px height while preserving aspect ratio.
 //! Stores exact generated dimensions to avoid re-scaling in the UI.
+//! Video thumbnails use a frame extracted with the `ffmpeg` CLI when available.
 
-use std::path::Path;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::time::{Duration, Instant};
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use image::imageops::FilterType;
 use image::{DynamicImage, GenericImageView, ImageFormat};
 use tracing::debug;
 
+use super::nvidia;
+use crate::models::MediaType;
+
 /// Default target height for thumbnails in pixels.
 pub const DEFAULT_THUMB_HEIGHT: u32 = 256;
 
+/// Thumbnail height for 2x (HiDPI) displays.
+pub const HIDPI_THUMB_HEIGHT: u32 = DEFAULT_THUMB_HEIGHT * 2;
+
+/// Thumbnail height matching a GTK widget scale factor.
+pub fn thumb_height_for_scale(scale_factor: i32) -> u32 {
+    if scale_factor >= 2 {
+        HIDPI_THUMB_HEIGHT
+    } else {
+        DEFAULT_THUMB_HEIGHT
+    }
+}
+
 /// Minimum width for thumbnails (to handle extreme aspect ratios).
 const MIN_THUMB_WIDTH: u32 = 64;
 
@@ -22,27 +41,152 @@ const MAX_THUMB_WIDTH: u32 = 1024;
 /// JPEG quality for thumbnail encoding (0-100).
 const JPEG_QUALITY: u8 = 85;
 
+/// WebP quality for thumbnail encoding (0-100).
+const WEBP_QUALITY: f32 = 80.0;
+
+/// BlurHash component counts (x, y); 4x3 suits the typical landscape tile.
+const BLURHASH_COMPONENTS: (u32, u32) = (4, 3);
+
+/// Longest edge an image is reduced to before computing its BlurHash.
+/// The hash only keeps a few low frequencies, so more pixels add nothing.
+const BLURHASH_SAMPLE_SIZE: u32 = 32;
+
+/// Seek offsets (seconds) tried in order; the first skips fade-in black frames.
+const VIDEO_FRAME_SEEK_SECS: [&str; 2] = ["1", "0"];
+
+/// Longest one ffmpeg frame extraction may run before it is killed.
+const VIDEO_FRAME_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// ffmpeg was killed after `VIDEO_FRAME_TIMEOUT`. The file is likely on a
+/// stalled mount or a pathological stream, so callers should show a
+/// placeholder rather than retry it with another decoder.
+#[derive(Debug)]
+pub struct VideoFrameTimeout {
+    pub path: PathBuf,
+}
+
+impl std::fmt::Display for VideoFrameTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ffmpeg took longer than {}s on {:?}",
+            VIDEO_FRAME_TIMEOUT.as_secs(),
+            self.path
+        )
+    }
+}
+
+impl std::error::Error for VideoFrameTimeout {}
+
 /// Thumbnail generator that creates resized images for caching.
 pub struct ThumbnailGenerator;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ResizeMode {
+    /// Catmull-Rom; the default balance of sharpness and speed.
     Quality,
+    /// Bilinear through `fast_image_resize`'s SIMD kernels; noticeably
+    /// softer but the cheapest resize.
     Fast,
+    /// Lanczos3; the sharpest downscale, at roughly twice the resize cost of `Quality`.
+    HighQuality,
+}
+
+impl ResizeMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "quality" => Some(Self::Quality),
+            "fast" => Some(Self::Fast),
+            "high" | "high-quality" | "lanczos3" => Some(Self::HighQuality),
+            _ => None,
+        }
+    }
+
+    /// Name used in benchmark reports.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Quality => "quality",
+            Self::Fast => "fast",
+            Self::HighQuality => "high",
+        }
+    }
+
+    fn filter(self) -> FilterType {
+        match self {
+            Self::Quality => FilterType::CatmullRom,
+            Self::Fast => FilterType::Triangle,
+            Self::HighQuality => FilterType::Lanczos3,
+        }
+    }
+}
+
+/// On-disk encoding for generated thumbnails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ThumbFormat {
+    #[default]
+    Jpeg,
+    Png,
+    WebP,
+}
+
+impl ThumbFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "jpeg" | "jpg" => Some(Self::Jpeg),
+            "png" => Some(Self::Png),
+            "webp" => Some(Self::WebP),
+            _ => None,
+        }
+    }
+
+    /// Format selected by `IDXD_THUMB_FORMAT`, defaulting to JPEG.
+    pub fn from_env() -> Self {
+        std::env::var("IDXD_THUMB_FORMAT")
+            .ok()
+            .and_then(|v| Self::parse(&v))
+            .unwrap_or_default()
+    }
+
+    /// File extension (without the dot) used for cached thumbnails.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Jpeg => "jpg",
+            Self::Png => "png",
+            Self::WebP => "webp",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, Default)]
 pub struct ThumbnailStageTimings {
     pub decode_ms: f64,
+    /// Depends heavily on [`ResizeMode`]: Lanczos3 (`HighQuality`) samples a
+    /// 6-tap kernel against Catmull-Rom's 4 and bilinear's 2, so expect it to
+    /// dominate this stage for large sources.
     pub resize_ms: f64,
     pub encode_ms: f64,
 }
 
+/// Outcome of [`ThumbnailGenerator::generate_one`].
+#[derive(Debug, Clone)]
+pub struct ThumbOutcome {
+    pub width: u32,
+    pub height: u32,
+    /// True when an up-to-date thumbnail already existed and nothing was generated.
+    pub cache_hit: bool,
+    /// Per-stage timings; all zero on a cache hit.
+    pub timings: ThumbnailStageTimings,
+}
+
 /// Result of thumbnail generation containing dimensions.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct ThumbnailResult {
     pub width: u32,
     pub height: u32,
+    /// BlurHash of the thumbnail, used as a placeholder until it loads.
+    pub blurhash: Option<String>,
+    /// Perceptual hash of the thumbnail, used to find near-duplicates.
+    pub phash: u64,
 }
 
 impl ThumbnailGenerator {
@@ -72,17 +216,31 @@ impl ThumbnailGenerator {
     }
 
     /// Generate a thumbnail with selectable quality/speed mode and stage timings.
+    ///
+    /// The EXIF orientation is baked into the saved pixels, so the thumbnail
+    /// displays upright without any further rotation.
     pub fn generate_thumbnail_with_mode(
         src: &Path,
         dst: &Path,
         target_height: u32,
         mode: ResizeMode,
+    ) -> Result<(ThumbnailResult, ThumbnailStageTimings)> {
+        Self::generate_thumbnail_with_format(src, dst, target_height, mode, ThumbFormat::Jpeg)
+    }
+
+    /// Generate a thumbnail encoded as `format`.
+    pub fn generate_thumbnail_with_format(
+        src: &Path,
+        dst: &Path,
+        target_height: u32,
+        mode: ResizeMode,
+        format: ThumbFormat,
     ) -> Result<(ThumbnailResult, ThumbnailStageTimings)> {
         let decode_start = std::time::Instant::now();
         debug!(?src, ?dst, target_height, "Generating thumbnail");
 
         // Load the source image
-        let img = Self::load_image(src)?;
+        let img = Self::load_image(src, target_height)?;
         let decode_ms = decode_start.elapsed().as_secs_f64() * 1000.0;
         let (src_width, src_height) = img.dimensions();
 
@@ -95,13 +253,9 @@ impl ThumbnailGenerator {
             src_height, thumb_width, thumb_height, "Calculated thumbnail dimensions"
         );
 
-        // Resize the image using a high-quality filter
+        // Resize the image using the filter for this mode
         let resize_start = std::time::Instant::now();
-        let filter = match mode {
-            ResizeMode::Quality => FilterType::CatmullRom,
-            ResizeMode::Fast => FilterType::Triangle,
-        };
-        let thumbnail = img.resize_exact(thumb_width, thumb_height, filter);
+        let thumbnail = Self::resize(&img, thumb_width, thumb_height, mode);
         let resize_ms = resize_start.elapsed().as_secs_f64() * 1000.0;
 
         // Ensure the parent directory exists
@@ -112,13 +266,194 @@ impl ThumbnailGenerator {
 
         // Save the thumbnail
         let encode_start = std::time::Instant::now();
-        Self::save_thumbnail(&thumbnail, dst)?;
+        Self::save_thumbnail(&thumbnail, dst, format)?;
+        let encode_ms = encode_start.elapsed().as_secs_f64() * 1000.0;
+
+        Ok((
+            ThumbnailResult {
+                width: thumb_width,
+                height: thumb_height,
+                blurhash: Self::compute_blurhash(&thumbnail),
+                phash: Self::compute_phash(&thumbnail),
+            },
+            ThumbnailStageTimings {
+                decode_ms,
+                resize_ms,
+                encode_ms,
+            },
+        ))
+    }
+
+    /// Generate a single thumbnail, reusing `out` if it is newer than `path`
+    /// and has the dimensions `size` would produce.
+    ///
+    /// The encoding follows the extension of `out` (JPEG when unrecognized).
+    /// Intended for diagnostics that need to know which stage is slow.
+    pub fn generate_one(
+        path: &Path,
+        out: &Path,
+        size: u32,
+        mode: ResizeMode,
+    ) -> Result<ThumbOutcome> {
+        let src_modified = std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .with_context(|| format!("Failed to stat source: {:?}", path))?;
+        let out_modified = std::fs::metadata(out).and_then(|m| m.modified()).ok();
+        if out_modified.is_some_and(|t| t >= src_modified) {
+            if let Some((width, height)) = image::image_dimensions(out)
+                .ok()
+                .filter(|&dims| Self::fits_size(path, dims, size))
+            {
+                return Ok(ThumbOutcome {
+                    width,
+                    height,
+                    cache_hit: true,
+                    timings: ThumbnailStageTimings::default(),
+                });
+            }
+        }
+
+        let format = out
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(ThumbFormat::parse)
+            .unwrap_or_default();
+        let (result, timings) =
+            Self::generate_thumbnail_with_format(path, out, size, mode, format)?;
+        Ok(ThumbOutcome {
+            width: result.width,
+            height: result.height,
+            cache_hit: false,
+            timings,
+        })
+    }
+
+    /// Whether a cached thumbnail of `dims` is what `size` would produce for
+    /// `path`. The source header gives the exact answer, in either
+    /// orientation since thumbnails are stored upright; for sources the image
+    /// crate cannot read (RAW, HEIF, video) only a full-height one counts.
+    fn fits_size(path: &Path, dims: (u32, u32), size: u32) -> bool {
+        match image::image_dimensions(path) {
+            Ok((w, h)) => {
+                Self::calculate_dimensions(w, h, size) == dims
+                    || Self::calculate_dimensions(h, w, size) == dims
+            }
+            Err(_) => dims.1 == size,
+        }
+    }
+
+    /// Generate the 1x and 2x (HiDPI) thumbnails from a single decode.
+    pub fn generate_thumbnail_variants(
+        src: &Path,
+        dst_1x: &Path,
+        dst_2x: &Path,
+        mode: ResizeMode,
+        format: ThumbFormat,
+    ) -> Result<(ThumbnailResult, ThumbnailResult)> {
+        debug!(?src, ?dst_1x, ?dst_2x, "Generating 1x/2x thumbnails");
+        let img = Self::load_image(src, HIDPI_THUMB_HEIGHT)?;
+        let (src_width, src_height) = img.dimensions();
+
+        let mut results = Vec::with_capacity(2);
+        for (dst, target_height) in [(dst_1x, DEFAULT_THUMB_HEIGHT), (dst_2x, HIDPI_THUMB_HEIGHT)] {
+            let (width, height) = Self::calculate_dimensions(src_width, src_height, target_height);
+            if let Some(parent) = dst.parent() {
+                std::fs::create_dir_all(parent).with_context(|| {
+                    format!("Failed to create thumbnail directory: {:?}", parent)
+                })?;
+            }
+            let thumbnail = Self::resize(&img, width, height, mode);
+            Self::save_thumbnail(&thumbnail, dst, format)?;
+            results.push(ThumbnailResult {
+                width,
+                height,
+                blurhash: Self::compute_blurhash(&thumbnail),
+                phash: Self::compute_phash(&thumbnail),
+            });
+        }
+        let large = results.pop().expect("two variants generated");
+        let small = results.pop().expect("two variants generated");
+        Ok((small, large))
+    }
+
+    /// Generate a thumbnail with decode and resize offloaded to an NVIDIA GPU.
+    ///
+    /// JPEG is decoded on the device with nvJPEG; PNG is decoded on the CPU and
+    /// resized on the device. Encoding stays on the CPU. Returns an error (without
+    /// touching `dst`) when the GPU path cannot handle the file, so callers can
+    /// fall back to [`Self::generate_thumbnail_with_mode`].
+    pub fn generate_thumbnail_gpu(
+        src: &Path,
+        dst: &Path,
+        target_height: u32,
+        mode: ResizeMode,
+    ) -> Result<(ThumbnailResult, ThumbnailStageTimings)> {
+        if !Self::can_generate_gpu(src) {
+            anyhow::bail!("GPU offload does not support {:?}", src);
+        }
+        if !nvidia::is_available() {
+            anyhow::bail!("NVIDIA GPU offload is not available");
+        }
+
+        let decode_start = std::time::Instant::now();
+        debug!(?src, ?dst, target_height, "Generating thumbnail on GPU");
+        let bytes =
+            std::fs::read(src).with_context(|| format!("Failed to read image: {:?}", src))?;
+        let format = image::guess_format(&bytes).ok();
+        let device_img = match format {
+            Some(ImageFormat::Jpeg) => nvidia::decode_jpeg(&bytes)?,
+            Some(ImageFormat::Png) => {
+                let img = image::load_from_memory_with_format(&bytes, ImageFormat::Png)
+                    .with_context(|| format!("Failed to decode image: {:?}", src))?;
+                nvidia::upload_rgb(&img.to_rgb8())?
+            }
+            other => anyhow::bail!("GPU offload does not support format {:?}", other),
+        };
+        let decode_ms = decode_start.elapsed().as_secs_f64() * 1000.0;
+
+        // Resize in stored orientation, then apply EXIF orientation to the small result.
+        let orientation =
+            crate::image_loader::read_exif_orientation_from_bytes(&bytes).unwrap_or(1);
+        let swap = crate::image_loader::needs_dimension_swap(orientation);
+        let (src_width, src_height) = if swap {
+            (device_img.height(), device_img.width())
+        } else {
+            (device_img.width(), device_img.height())
+        };
+        let (thumb_width, thumb_height) =
+            Self::calculate_dimensions(src_width, src_height, target_height);
+        let (resize_width, resize_height) = if swap {
+            (thumb_height, thumb_width)
+        } else {
+            (thumb_width, thumb_height)
+        };
+
+        let resize_start = std::time::Instant::now();
+        let resized = device_img.resize(resize_width, resize_height, mode)?;
+        drop(device_img);
+        let host = resized.download()?;
+        let mut thumbnail = DynamicImage::ImageRgb8(host);
+        if let Some(format) = format {
+            thumbnail = crate::color::manage(thumbnail, &bytes, format);
+        }
+        let thumbnail = crate::image_loader::apply_exif_orientation(thumbnail, orientation);
+        let resize_ms = resize_start.elapsed().as_secs_f64() * 1000.0;
+
+        if let Some(parent) = dst.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create thumbnail directory: {:?}", parent))?;
+        }
+
+        let encode_start = std::time::Instant::now();
+        Self::save_thumbnail(&thumbnail, dst, ThumbFormat::Jpeg)?;
         let encode_ms = encode_start.elapsed().as_secs_f64() * 1000.0;
 
         Ok((
             ThumbnailResult {
                 width: thumb_width,
                 height: thumb_height,
+                blurhash: Self::compute_blurhash(&thumbnail),
+                phash: Self::compute_phash(&thumbnail),
             },
             ThumbnailStageTimings {
                 decode_ms,
@@ -128,13 +463,102 @@ impl ThumbnailGenerator {
         ))
     }
 
-    /// Load an image from disk, handling various formats.
-    fn load_image(path: &Path) -> Result<DynamicImage> {
+    /// Compute the BlurHash placeholder string for an image.
+    pub fn compute_blurhash(img: &DynamicImage) -> Option<String> {
+        let (width, height) = img.dimensions();
+        let sample = if width > BLURHASH_SAMPLE_SIZE || height > BLURHASH_SAMPLE_SIZE {
+            img.resize(
+                BLURHASH_SAMPLE_SIZE,
+                BLURHASH_SAMPLE_SIZE,
+                FilterType::Triangle,
+            )
+        } else {
+            img.clone()
+        };
+        let rgba = sample.to_rgba8();
+        let (components_x, components_y) = BLURHASH_COMPONENTS;
+        blurhash::encode(
+            components_x,
+            components_y,
+            rgba.width(),
+            rgba.height(),
+            rgba.as_raw(),
+        )
+        .ok()
+    }
+
+    /// Compute a 64-bit difference hash (dHash) for near-duplicate detection.
+    ///
+    /// Each bit records whether a pixel of a 9x8 grayscale reduction is
+    /// brighter than its right neighbour, so re-encodes and resizes of the same
+    /// picture land within a few bits of each other.
+    pub fn compute_phash(img: &DynamicImage) -> u64 {
+        let small = img.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+        let mut hash = 0u64;
+        for y in 0..8 {
+            for x in 0..8 {
+                let bit = small.get_pixel(x, y)[0] > small.get_pixel(x + 1, y)[0];
+                hash = (hash << 1) | bit as u64;
+            }
+        }
+        hash
+    }
+
+    /// Decode a BlurHash into `width` x `height` RGBA pixels.
+    pub fn decode_blurhash(hash: &str, width: u32, height: u32) -> Option<Vec<u8>> {
+        blurhash::decode(hash, width.max(1), height.max(1), 1.0).ok()
+    }
+
+    /// Check if a source file is eligible for the GPU offload path.
+    pub fn can_generate_gpu(path: &Path) -> bool {
+        matches!(
+            Self::format_from_extension(path),
+            Some(ImageFormat::Jpeg) | Some(ImageFormat::Png)
+        )
+    }
+
+    /// Check whether the NVIDIA offload libraries and a CUDA device are usable.
+    pub fn gpu_offload_available() -> bool {
+        nvidia::is_available()
+    }
+
+    /// Load an image from disk, handling various formats. Vector images are
+    /// rendered to fit `target_height` instead of at their intrinsic size.
+    fn load_image(path: &Path, target_height: u32) -> Result<DynamicImage> {
+        if Self::can_generate_video(path) {
+            return Self::load_video_frame(path);
+        }
+        if crate::svg::is_svg_path(path) {
+            return crate::svg::render(path, MAX_THUMB_WIDTH, target_height);
+        }
+
         let img = crate::image_loader::open_image(path)?;
 
         Ok(img)
     }
 
+    /// Extract a representative video frame with the `ffmpeg` CLI.
+    fn load_video_frame(path: &Path) -> Result<DynamicImage> {
+        for seek in VIDEO_FRAME_SEEK_SECS {
+            let mut command = Command::new("ffmpeg");
+            command
+                .args(["-v", "error", "-ss", seek, "-i"])
+                .arg(path)
+                .args(["-frames:v", "1", "-f", "image2pipe", "-vcodec", "png", "-"]);
+            let output = output_with_timeout(&mut command, VIDEO_FRAME_TIMEOUT)
+                .context("Failed to run ffmpeg for video thumbnail")?
+                .ok_or_else(|| VideoFrameTimeout {
+                    path: path.to_path_buf(),
+                })?;
+            // Seeking past the end of short clips succeeds with no output.
+            if output.status.success() && !output.stdout.is_empty() {
+                return image::load_from_memory_with_format(&output.stdout, ImageFormat::Png)
+                    .with_context(|| format!("Failed to decode ffmpeg frame: {:?}", path));
+            }
+        }
+        bail!("ffmpeg produced no frame for {:?}", path)
+    }
+
     /// Determine image format from file extension.
     fn format_from_extension(path: &Path) -> Option<ImageFormat> {
         let ext = path.extension()?.to_str()?.to_lowercase();
@@ -149,6 +573,23 @@ impl ThumbnailGenerator {
         }
     }
 
+    /// Resize to exactly `width`x`height` with the filter for `mode`. `Fast`
+    /// uses SIMD bilinear resampling and falls back to the `image` crate for
+    /// pixel layouts it does not handle (e.g. 32-bit float).
+    fn resize(img: &DynamicImage, width: u32, height: u32, mode: ResizeMode) -> DynamicImage {
+        if mode == ResizeMode::Fast {
+            let mut resized = DynamicImage::new(width, height, img.color());
+            let options = fast_image_resize::ResizeOptions::new().resize_alg(
+                fast_image_resize::ResizeAlg::Convolution(fast_image_resize::FilterType::Bilinear),
+            );
+            match fast_image_resize::Resizer::new().resize(img, &mut resized, &options) {
+                Ok(()) => return resized,
+                Err(err) => debug!(error = %err, "SIMD resize unavailable, using image"),
+            }
+        }
+        img.resize_exact(width, height, mode.filter())
+    }
+
     /// Calculate thumbnail dimensions preserving aspect ratio.
     ///
     /// The target height is used as the base, with width calculated proportionally.
@@ -178,33 +619,56 @@ impl ThumbnailGenerator {
         (final_width.max(1), final_height.max(1))
     }
 
-    /// Save thumbnail to disk as JPEG for optimal size/quality balance.
-    fn save_thumbnail(img: &DynamicImage, dst: &Path) -> Result<()> {
+    /// Save thumbnail to disk in the requested format.
+    ///
+    /// JPEG is the default for its size/quality balance; lossy WebP is smaller
+    /// still, and PNG is lossless.
+    fn save_thumbnail(img: &DynamicImage, dst: &Path, format: ThumbFormat) -> Result<()> {
         use image::codecs::jpeg::JpegEncoder;
         use std::fs::File;
-        use std::io::BufWriter;
+        use std::io::{BufWriter, Write};
 
         let file = File::create(dst)
             .with_context(|| format!("Failed to create thumbnail file: {:?}", dst))?;
 
         let mut writer = BufWriter::new(file);
 
-        // Convert to RGB8 for JPEG (no alpha channel)
+        // Convert to RGB8 (thumbnails carry no alpha channel)
         let rgb_img = img.to_rgb8();
 
-        let encoder = JpegEncoder::new_with_quality(&mut writer, JPEG_QUALITY);
-        rgb_img
-            .write_with_encoder(encoder)
-            .with_context(|| format!("Failed to encode thumbnail: {:?}", dst))?;
+        match format {
+            ThumbFormat::Jpeg => {
+                let encoder = JpegEncoder::new_with_quality(&mut writer, JPEG_QUALITY);
+                rgb_img
+                    .write_with_encoder(encoder)
+                    .with_context(|| format!("Failed to encode thumbnail: {:?}", dst))?;
+            }
+            ThumbFormat::Png => {
+                rgb_img
+                    .write_to(&mut writer, ImageFormat::Png)
+                    .with_context(|| format!("Failed to encode thumbnail: {:?}", dst))?;
+            }
+            ThumbFormat::WebP => {
+                let encoded =
+                    webp::Encoder::from_rgb(rgb_img.as_raw(), rgb_img.width(), rgb_img.height())
+                        .encode(WEBP_QUALITY);
+                writer
+                    .write_all(&encoded)
+                    .with_context(|| format!("Failed to write thumbnail: {:?}", dst))?;
+            }
+        }
+        writer
+            .flush()
+            .with_context(|| format!("Failed to write thumbnail: {:?}", dst))?;
 
-        debug!(?dst, "Saved thumbnail");
+        debug!(?dst, ?format, "Saved thumbnail");
         Ok(())
     }
 
     /// Generate a thumbnail and return the image data without saving to disk.
     /// Useful for in-memory processing or when the caller wants to handle storage.
     pub fn generate_in_memory(src: &Path, target_height: u32) -> Result<(Vec<u8>, u32, u32)> {
-        let img = Self::load_image(src)?;
+        let img = Self::load_image(src, target_height)?;
         let (src_width, src_height) = img.dimensions();
 
         let (thumb_width, thumb_height) =
@@ -229,10 +693,53 @@ impl ThumbnailGenerator {
 
     /// Check if a source file can be processed as an image.
     pub fn can_generate(path: &Path) -> bool {
-        Self::format_from_extension(path).is_some()
+        MediaType::from_path(path) == Some(MediaType::Image)
+    }
+
+    /// Check if a source file is a video whose frame can be thumbnailed.
+    pub fn can_generate_video(path: &Path) -> bool {
+        crate::models::is_video_path(path)
     }
 }
 
+/// Runs `command` like `Command::output`, but kills it once `timeout` has
+/// passed and returns `None`. Stdout is drained on a thread so a large frame
+/// cannot fill the pipe and stall the child; stderr is discarded.
+fn output_with_timeout(
+    command: &mut Command,
+    timeout: Duration,
+) -> std::io::Result<Option<Output>> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stdout.read_to_end(&mut buf);
+        buf
+    });
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            let _ = reader.join();
+            return Ok(None);
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+    Ok(Some(Output {
+        status,
+        stdout: reader.join().unwrap_or_default(),
+        stderr: Vec::new(),
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -270,6 +777,227 @@ mod tests {
         assert_eq!(w, MIN_THUMB_WIDTH);
     }
 
+    #[test]
+    fn test_can_generate_gpu() {
+        assert!(ThumbnailGenerator::can_generate_gpu(Path::new("a.JPG")));
+        assert!(ThumbnailGenerator::can_generate_gpu(Path::new("a.png")));
+        assert!(!ThumbnailGenerator::can_generate_gpu(Path::new("a.webp")));
+        assert!(!ThumbnailGenerator::can_generate_gpu(Path::new("a.gif")));
+    }
+
+    #[test]
+    fn test_can_generate_video() {
+        assert!(ThumbnailGenerator::can_generate_video(Path::new(
+            "clip.MP4"
+        )));
+        assert!(ThumbnailGenerator::can_generate_video(Path::new(
+            "clip.mkv"
+        )));
+        assert!(!ThumbnailGenerator::can_generate_video(Path::new(
+            "photo.jpg"
+        )));
+        assert!(!ThumbnailGenerator::can_generate(Path::new("clip.mp4")));
+        assert!(ThumbnailGenerator::can_generate(Path::new("shot.CR3")));
+        assert!(ThumbnailGenerator::can_generate(Path::new("IMG_0001.heic")));
+        assert!(ThumbnailGenerator::can_generate(Path::new("logo.svg")));
+    }
+
+    #[test]
+    fn test_thumb_format_parse() {
+        assert_eq!(ThumbFormat::parse("WebP"), Some(ThumbFormat::WebP));
+        assert_eq!(ThumbFormat::parse(" jpg "), Some(ThumbFormat::Jpeg));
+        assert_eq!(ThumbFormat::parse("png"), Some(ThumbFormat::Png));
+        assert_eq!(ThumbFormat::parse("avif"), None);
+    }
+
+    #[test]
+    fn test_generate_with_format_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src.jpg");
+        write_oriented_jpeg(&src, 1);
+        for format in [ThumbFormat::Jpeg, ThumbFormat::Png, ThumbFormat::WebP] {
+            let dst = dir.path().join(format!("thumb.{}", format.extension()));
+            ThumbnailGenerator::generate_thumbnail_with_format(
+                &src,
+                &dst,
+                DEFAULT_THUMB_HEIGHT,
+                ResizeMode::Fast,
+                format,
+            )
+            .unwrap();
+            let decoded = image::open(&dst).unwrap();
+            assert_eq!(decoded.dimensions(), (128, 64), "{format:?}");
+        }
+    }
+
+    #[test]
+    fn test_generate_thumbnail_variants() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src.png");
+        image::RgbImage::from_pixel(1200, 800, image::Rgb([10, 20, 30]))
+            .save(&src)
+            .unwrap();
+        let dst_1x = dir.path().join("thumb.jpg");
+        let dst_2x = dir.path().join("thumb@2x.jpg");
+
+        let (small, large) = ThumbnailGenerator::generate_thumbnail_variants(
+            &src,
+            &dst_1x,
+            &dst_2x,
+            ResizeMode::Fast,
+            ThumbFormat::Jpeg,
+        )
+        .unwrap();
+
+        assert_eq!((small.width, small.height), (384, 256));
+        assert_eq!((large.width, large.height), (768, 512));
+        assert_eq!(image::open(&dst_2x).unwrap().dimensions(), (768, 512));
+        assert!(small.blurhash.is_some());
+        assert_eq!(thumb_height_for_scale(1), DEFAULT_THUMB_HEIGHT);
+        assert_eq!(thumb_height_for_scale(2), HIDPI_THUMB_HEIGHT);
+    }
+
+    #[test]
+    fn fast_resize_matches_image_resampling() {
+        let gradient = image::RgbImage::from_fn(640, 480, |x, y| {
+            image::Rgb([(x * 255 / 639) as u8, (y * 255 / 479) as u8, 128])
+        });
+        let faded = image::RgbaImage::from_fn(640, 480, |x, _| {
+            image::Rgba([200, 80, 40, (x * 255 / 639) as u8])
+        });
+        let rings = image::GrayImage::from_fn(640, 480, |x, y| {
+            let (dx, dy) = (x as f32 - 320.0, y as f32 - 240.0);
+            image::Luma([((dx.hypot(dy) / 8.0).sin() * 100.0 + 128.0) as u8])
+        });
+        for img in [
+            DynamicImage::ImageRgb8(gradient),
+            DynamicImage::ImageRgba8(faded),
+            DynamicImage::ImageLuma8(rings),
+        ] {
+            let fast = ThumbnailGenerator::resize(&img, 160, 120, ResizeMode::Fast);
+            assert_eq!(fast.color(), img.color());
+            let fast = fast.to_rgba8();
+            let reference = img.resize_exact(160, 120, FilterType::Triangle).to_rgba8();
+            let total: u64 = fast
+                .as_raw()
+                .iter()
+                .zip(reference.as_raw())
+                .map(|(a, b)| a.abs_diff(*b) as u64)
+                .sum();
+            let mean = total as f64 / fast.as_raw().len() as f64;
+            assert!(mean < 3.0, "{:?} mean difference {}", img.color(), mean);
+        }
+    }
+
+    #[test]
+    fn test_phash_matches_resized_copies_only() {
+        let gradient = image::RgbImage::from_fn(320, 240, |x, y| {
+            image::Rgb([
+                (x * 255 / 319) as u8,
+                (y * 255 / 239) as u8,
+                ((x + y) % 256) as u8,
+            ])
+        });
+        let img = DynamicImage::ImageRgb8(gradient);
+        let hash = ThumbnailGenerator::compute_phash(&img);
+
+        let smaller = img.resize_exact(160, 120, FilterType::CatmullRom);
+        let distance = (hash ^ ThumbnailGenerator::compute_phash(&smaller)).count_ones();
+        assert!(distance <= 4, "distance {}", distance);
+
+        let mirrored = img.fliph();
+        let distance = (hash ^ ThumbnailGenerator::compute_phash(&mirrored)).count_ones();
+        assert!(distance > 16, "distance {}", distance);
+    }
+
+    #[test]
+    fn test_blurhash_round_trip() {
+        let img = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(
+            300,
+            200,
+            image::Rgb([200, 40, 40]),
+        ));
+        let hash = ThumbnailGenerator::compute_blurhash(&img).unwrap();
+        // 4x3 components: size flag, max AC, 4-char DC and 2 chars per AC term
+        assert_eq!(hash.len(), 4 + 2 * 4 * 3);
+
+        let pixels = ThumbnailGenerator::decode_blurhash(&hash, 8, 6).unwrap();
+        assert_eq!(pixels.len(), 8 * 6 * 4);
+        let [r, g, b, a] = [pixels[0], pixels[1], pixels[2], pixels[3]];
+        assert!(
+            r > 180 && g < 70 && b < 70 && a == 255,
+            "{:?}",
+            (r, g, b, a)
+        );
+    }
+
+    #[test]
+    fn test_resize_mode_parse() {
+        assert_eq!(ResizeMode::parse("high"), Some(ResizeMode::HighQuality));
+        assert_eq!(ResizeMode::parse("Fast"), Some(ResizeMode::Fast));
+        assert_eq!(ResizeMode::parse("nearest"), None);
+        assert_eq!(ResizeMode::HighQuality.filter(), FilterType::Lanczos3);
+        for mode in [
+            ResizeMode::Quality,
+            ResizeMode::Fast,
+            ResizeMode::HighQuality,
+        ] {
+            assert_eq!(ResizeMode::parse(mode.as_str()), Some(mode));
+        }
+    }
+
+    #[test]
+    fn test_generate_one_reports_cache_hit() {
+        fn assert_send<T: Send>() {}
+        assert_send::<ThumbOutcome>();
+
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src.jpg");
+        write_oriented_jpeg(&src, 1);
+        let out = dir.path().join("thumb.png");
+
+        let first =
+            ThumbnailGenerator::generate_one(&src, &out, DEFAULT_THUMB_HEIGHT, ResizeMode::Fast)
+                .unwrap();
+        assert!(!first.cache_hit);
+        assert_eq!((first.width, first.height), (128, 64));
+        assert_eq!(
+            image::guess_format(&std::fs::read(&out).unwrap()).unwrap(),
+            ImageFormat::Png
+        );
+
+        let second =
+            ThumbnailGenerator::generate_one(&src, &out, DEFAULT_THUMB_HEIGHT, ResizeMode::Fast)
+                .unwrap();
+        assert!(second.cache_hit);
+        assert_eq!((second.width, second.height), (128, 64));
+        assert_eq!(second.timings.decode_ms, 0.0);
+
+        // A thumbnail made at another size is not a hit
+        let smaller = ThumbnailGenerator::generate_one(&src, &out, 32, ResizeMode::Fast).unwrap();
+        assert!(!smaller.cache_hit);
+        assert_eq!((smaller.width, smaller.height), (64, 32));
+    }
+
+    #[test]
+    fn test_output_with_timeout_kills_slow_commands() {
+        let mut echo = Command::new("echo");
+        echo.arg("frame");
+        let output = output_with_timeout(&mut echo, Duration::from_secs(5))
+            .unwrap()
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"frame\n");
+
+        let started = Instant::now();
+        let mut sleep = Command::new("sleep");
+        sleep.arg("5");
+        assert!(output_with_timeout(&mut sleep, Duration::from_millis(100))
+            .unwrap()
+            .is_none());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
     #[test]
     fn test_format_from_extension() {
         assert_eq!(
@@ -285,4 +1013,91 @@ mod tests {
             None
         );
     }
+
+    /// Minimal APP1 segment carrying only an EXIF Orientation tag.
+    fn exif_orientation_segment(orientation: u16) -> Vec<u8> {
+        let mut payload = b"Exif\0\0".to_vec();
+        payload.extend_from_slice(&[
+            b'I', b'I', 42, 0, 8, 0, 0, 0, // TIFF header + IFD0 offset
+            1, 0, // entry count
+            0x12, 0x01, // tag 0x0112
+            3, 0, // type SHORT
+            1, 0, 0, 0, // count
+        ]);
+        payload.extend_from_slice(&orientation.to_le_bytes());
+        payload.extend_from_slice(&[0, 0, 0, 0, 0, 0]); // value padding + next IFD
+        let mut segment = vec![0xFF, 0xE1];
+        segment.extend_from_slice(&((payload.len() + 2) as u16).to_be_bytes());
+        segment.extend_from_slice(&payload);
+        segment
+    }
+
+    /// 128x64 JPEG that is blue except for a red top-left quadrant.
+    fn write_oriented_jpeg(path: &Path, orientation: u16) {
+        let img = image::RgbImage::from_fn(128, 64, |x, y| {
+            if x < 64 && y < 32 {
+                image::Rgb([255, 0, 0])
+            } else {
+                image::Rgb([0, 0, 255])
+            }
+        });
+        let mut jpeg = Vec::new();
+        img.write_with_encoder(image::codecs::jpeg::JpegEncoder::new_with_quality(
+            &mut jpeg, 95,
+        ))
+        .unwrap();
+        let mut bytes = jpeg[..2].to_vec();
+        bytes.extend_from_slice(&exif_orientation_segment(orientation));
+        bytes.extend_from_slice(&jpeg[2..]);
+        std::fs::write(path, bytes).unwrap();
+    }
+
+    #[test]
+    fn test_generate_applies_exif_orientation() {
+        let dir = tempfile::tempdir().unwrap();
+        // (orientation, quadrant holding the red block after orientation: (right, bottom))
+        let cases = [
+            (1, (false, false)),
+            (2, (true, false)),
+            (3, (true, true)),
+            (4, (false, true)),
+            (5, (false, false)),
+            (6, (true, false)),
+            (7, (true, true)),
+            (8, (false, true)),
+        ];
+        for (orientation, (right, bottom)) in cases {
+            let src = dir.path().join(format!("src-{orientation}.jpg"));
+            let dst = dir.path().join(format!("thumb-{orientation}.jpg"));
+            write_oriented_jpeg(&src, orientation);
+
+            let (result, _) = ThumbnailGenerator::generate_thumbnail_with_mode(
+                &src,
+                &dst,
+                DEFAULT_THUMB_HEIGHT,
+                ResizeMode::Quality,
+            )
+            .unwrap();
+            let expected_dims = if orientation >= 5 {
+                (64, 128)
+            } else {
+                (128, 64)
+            };
+            assert_eq!(
+                (result.width, result.height),
+                expected_dims,
+                "{orientation}"
+            );
+
+            let thumb = image::open(&dst).unwrap().to_rgb8();
+            let (w, h) = thumb.dimensions();
+            let x = if right { w * 3 / 4 } else { w / 4 };
+            let y = if bottom { h * 3 / 4 } else { h / 4 };
+            let pixel = thumb.get_pixel(x, y);
+            assert!(
+                pixel[0] > 200 && pixel[2] < 80,
+                "orientation {orientation}: expected red at ({x}, {y}), got {pixel:?}"
+            );
+        }
+    }
 }
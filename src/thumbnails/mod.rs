@@ -4,7 +4,9 @@
 //! - `ThumbnailGenerator` - Generates thumbnails from source images
 //! - `ThumbnailCache` - Disk and memory caching with LRU eviction
 //! - `ThumbnailQueue` - Worker queue for async generation
+//! - `nvidia` - Optional NVIDIA GPU decode/resize offload
 
 pub mod cache;
 pub mod generator;
+pub mod nvidia;
 pub mod queue;
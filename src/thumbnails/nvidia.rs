@@ -0,0 +1,430 @@
+//! NVIDIA GPU offload for thumbnail generation.
+//!
+//! The CUDA runtime, nvJPEG and NPP are loaded at runtime with `dlopen`, so the
+//! binary has no link-time dependency on the CUDA toolkit. JPEG files are decoded
+//! on the device with nvJPEG; other inputs are decoded on the CPU and uploaded.
+//! Resizing always runs on the device through NPP.
+
+use std::cell::RefCell;
+use std::ffi::c_void;
+use std::os::raw::{c_int, c_uchar, c_uint};
+use std::ptr;
+use std::sync::OnceLock;
+
+use anyhow::{bail, Result};
+use image::RgbImage;
+use tracing::{debug, info};
+
+use super::generator::ResizeMode;
+use crate::dylib::{open_library, symbol};
+
+const CUDA_MEMCPY_HOST_TO_DEVICE: c_int = 1;
+const CUDA_MEMCPY_DEVICE_TO_HOST: c_int = 2;
+
+const NVJPEG_OUTPUT_RGBI: c_int = 5;
+const NVJPEG_CSS_UNKNOWN: c_int = -1;
+const NVJPEG_MAX_COMPONENT: usize = 4;
+
+const NPPI_INTER_LINEAR: c_int = 2;
+const NPPI_INTER_SUPER: c_int = 8;
+const NPPI_INTER_LANCZOS: c_int = 16;
+
+const CUDART_NAMES: &[&str] = &["libcudart.so", "libcudart.so.12", "libcudart.so.11.0"];
+const NVJPEG_NAMES: &[&str] = &["libnvjpeg.so", "libnvjpeg.so.12", "libnvjpeg.so.11"];
+const NPPIG_NAMES: &[&str] = &["libnppig.so", "libnppig.so.12", "libnppig.so.11"];
+
+type CudaGetDeviceCount = unsafe extern "C" fn(*mut c_int) -> c_int;
+type CudaMalloc = unsafe extern "C" fn(*mut *mut c_void, usize) -> c_int;
+type CudaFree = unsafe extern "C" fn(*mut c_void) -> c_int;
+type CudaMemcpy = unsafe extern "C" fn(*mut c_void, *const c_void, usize, c_int) -> c_int;
+type CudaStreamSynchronize = unsafe extern "C" fn(*mut c_void) -> c_int;
+
+type NvjpegCreateSimple = unsafe extern "C" fn(*mut *mut c_void) -> c_int;
+type NvjpegDestroy = unsafe extern "C" fn(*mut c_void) -> c_int;
+type NvjpegJpegStateCreate = unsafe extern "C" fn(*mut c_void, *mut *mut c_void) -> c_int;
+type NvjpegJpegStateDestroy = unsafe extern "C" fn(*mut c_void) -> c_int;
+type NvjpegGetImageInfo = unsafe extern "C" fn(
+    *mut c_void,
+    *const c_uchar,
+    usize,
+    *mut c_int,
+    *mut c_int,
+    *mut c_int,
+    *mut c_int,
+) -> c_int;
+type NvjpegDecode = unsafe extern "C" fn(
+    *mut c_void,
+    *mut c_void,
+    *const c_uchar,
+    usize,
+    c_int,
+    *mut NvjpegImage,
+    *mut c_void,
+) -> c_int;
+
+type NppiResize8uC3R = unsafe extern "C" fn(
+    *const c_uchar,
+    c_int,
+    NppiSize,
+    NppiRect,
+    *mut c_uchar,
+    c_int,
+    NppiSize,
+    NppiRect,
+    c_int,
+) -> c_int;
+
+#[repr(C)]
+struct NvjpegImage {
+    channel: [*mut c_uchar; NVJPEG_MAX_COMPONENT],
+    pitch: [c_uint; NVJPEG_MAX_COMPONENT],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct NppiSize {
+    width: c_int,
+    height: c_int,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct NppiRect {
+    x: c_int,
+    y: c_int,
+    width: c_int,
+    height: c_int,
+}
+
+/// Resolved entry points of the CUDA runtime, nvJPEG and NPP.
+struct NvidiaLibs {
+    cuda_malloc: CudaMalloc,
+    cuda_free: CudaFree,
+    cuda_memcpy: CudaMemcpy,
+    cuda_stream_synchronize: CudaStreamSynchronize,
+    nvjpeg_create_simple: NvjpegCreateSimple,
+    nvjpeg_destroy: NvjpegDestroy,
+    nvjpeg_state_create: NvjpegJpegStateCreate,
+    nvjpeg_state_destroy: NvjpegJpegStateDestroy,
+    nvjpeg_get_image_info: NvjpegGetImageInfo,
+    nvjpeg_decode: NvjpegDecode,
+    nppi_resize: NppiResize8uC3R,
+}
+
+fn libs() -> Option<&'static NvidiaLibs> {
+    static LIBS: OnceLock<Option<NvidiaLibs>> = OnceLock::new();
+    LIBS.get_or_init(|| match load_libs() {
+        Ok(libs) => {
+            info!("NVIDIA thumbnail offload available (nvJPEG + NPP)");
+            Some(libs)
+        }
+        Err(e) => {
+            debug!(error = %e, "NVIDIA thumbnail offload unavailable");
+            None
+        }
+    })
+    .as_ref()
+}
+
+fn load_libs() -> Result<NvidiaLibs> {
+    // Library handles are intentionally never closed; the entry points live for
+    // the rest of the process.
+    let cudart = open_library(CUDART_NAMES)?;
+    let nvjpeg = open_library(NVJPEG_NAMES)?;
+    let nppig = open_library(NPPIG_NAMES)?;
+
+    unsafe {
+        let get_device_count = std::mem::transmute::<*mut c_void, CudaGetDeviceCount>(symbol(
+            cudart,
+            "cudaGetDeviceCount",
+        )?);
+        let mut count: c_int = 0;
+        let status = get_device_count(&mut count);
+        if status != 0 || count <= 0 {
+            bail!("No usable CUDA device (status={}, count={})", status, count);
+        }
+
+        Ok(NvidiaLibs {
+            cuda_malloc: std::mem::transmute::<*mut c_void, CudaMalloc>(symbol(
+                cudart,
+                "cudaMalloc",
+            )?),
+            cuda_free: std::mem::transmute::<*mut c_void, CudaFree>(symbol(cudart, "cudaFree")?),
+            cuda_memcpy: std::mem::transmute::<*mut c_void, CudaMemcpy>(symbol(
+                cudart,
+                "cudaMemcpy",
+            )?),
+            cuda_stream_synchronize: std::mem::transmute::<*mut c_void, CudaStreamSynchronize>(
+                symbol(cudart, "cudaStreamSynchronize")?,
+            ),
+            nvjpeg_create_simple: std::mem::transmute::<*mut c_void, NvjpegCreateSimple>(symbol(
+                nvjpeg,
+                "nvjpegCreateSimple",
+            )?),
+            nvjpeg_destroy: std::mem::transmute::<*mut c_void, NvjpegDestroy>(symbol(
+                nvjpeg,
+                "nvjpegDestroy",
+            )?),
+            nvjpeg_state_create: std::mem::transmute::<*mut c_void, NvjpegJpegStateCreate>(symbol(
+                nvjpeg,
+                "nvjpegJpegStateCreate",
+            )?),
+            nvjpeg_state_destroy: std::mem::transmute::<*mut c_void, NvjpegJpegStateDestroy>(
+                symbol(nvjpeg, "nvjpegJpegStateDestroy")?,
+            ),
+            nvjpeg_get_image_info: std::mem::transmute::<*mut c_void, NvjpegGetImageInfo>(symbol(
+                nvjpeg,
+                "nvjpegGetImageInfo",
+            )?),
+            nvjpeg_decode: std::mem::transmute::<*mut c_void, NvjpegDecode>(symbol(
+                nvjpeg,
+                "nvjpegDecode",
+            )?),
+            nppi_resize: std::mem::transmute::<*mut c_void, NppiResize8uC3R>(symbol(
+                nppig,
+                "nppiResize_8u_C3R",
+            )?),
+        })
+    }
+}
+
+/// Returns true if the NVIDIA libraries loaded and a CUDA device is present.
+pub fn is_available() -> bool {
+    libs().is_some()
+}
+
+/// Per-thread nvJPEG handle and decoder state.
+struct NvjpegContext {
+    handle: *mut c_void,
+    state: *mut c_void,
+}
+
+impl NvjpegContext {
+    fn new(libs: &NvidiaLibs) -> Result<Self> {
+        let mut handle = ptr::null_mut();
+        let status = unsafe { (libs.nvjpeg_create_simple)(&mut handle) };
+        if status != 0 {
+            bail!("nvjpegCreateSimple failed with status {}", status);
+        }
+        let mut state = ptr::null_mut();
+        let status = unsafe { (libs.nvjpeg_state_create)(handle, &mut state) };
+        if status != 0 {
+            unsafe { (libs.nvjpeg_destroy)(handle) };
+            bail!("nvjpegJpegStateCreate failed with status {}", status);
+        }
+        Ok(Self { handle, state })
+    }
+}
+
+impl Drop for NvjpegContext {
+    fn drop(&mut self) {
+        if let Some(libs) = libs() {
+            unsafe {
+                (libs.nvjpeg_state_destroy)(self.state);
+                (libs.nvjpeg_destroy)(self.handle);
+            }
+        }
+    }
+}
+
+thread_local! {
+    static NVJPEG_CONTEXT: RefCell<Option<NvjpegContext>> = const { RefCell::new(None) };
+}
+
+/// Interleaved RGB8 image in device memory, freed on drop.
+pub struct DeviceImage {
+    ptr: *mut c_uchar,
+    width: u32,
+    height: u32,
+}
+
+impl DeviceImage {
+    fn alloc(libs: &NvidiaLibs, width: u32, height: u32) -> Result<Self> {
+        if width == 0 || height == 0 {
+            bail!("Cannot allocate empty device image");
+        }
+        let mut ptr = ptr::null_mut();
+        let bytes = width as usize * height as usize * 3;
+        let status = unsafe { (libs.cuda_malloc)(&mut ptr, bytes) };
+        if status != 0 || ptr.is_null() {
+            bail!(
+                "cudaMalloc of {} bytes failed with status {}",
+                bytes,
+                status
+            );
+        }
+        Ok(Self {
+            ptr: ptr as *mut c_uchar,
+            width,
+            height,
+        })
+    }
+
+    fn pitch(&self) -> usize {
+        self.width as usize * 3
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Resizes to the given dimensions on the device.
+    pub fn resize(&self, width: u32, height: u32, mode: ResizeMode) -> Result<DeviceImage> {
+        let libs = libs().ok_or_else(|| anyhow::anyhow!("NVIDIA offload unavailable"))?;
+        let dst = DeviceImage::alloc(libs, width, height)?;
+        let src_size = NppiSize {
+            width: self.width as c_int,
+            height: self.height as c_int,
+        };
+        let dst_size = NppiSize {
+            width: width as c_int,
+            height: height as c_int,
+        };
+        let interpolation = match mode {
+            // Super-sampling gives the cleanest result when shrinking.
+            ResizeMode::Quality => NPPI_INTER_SUPER,
+            ResizeMode::Fast => NPPI_INTER_LINEAR,
+            ResizeMode::HighQuality => NPPI_INTER_LANCZOS,
+        };
+        let status = unsafe {
+            (libs.nppi_resize)(
+                self.ptr,
+                self.pitch() as c_int,
+                src_size,
+                NppiRect {
+                    x: 0,
+                    y: 0,
+                    width: src_size.width,
+                    height: src_size.height,
+                },
+                dst.ptr,
+                dst.pitch() as c_int,
+                dst_size,
+                NppiRect {
+                    x: 0,
+                    y: 0,
+                    width: dst_size.width,
+                    height: dst_size.height,
+                },
+                interpolation,
+            )
+        };
+        // NPP reports warnings as positive codes; only negative codes are errors.
+        if status < 0 {
+            bail!("nppiResize_8u_C3R failed with status {}", status);
+        }
+        Ok(dst)
+    }
+
+    /// Copies the image back to host memory.
+    pub fn download(&self) -> Result<RgbImage> {
+        let libs = libs().ok_or_else(|| anyhow::anyhow!("NVIDIA offload unavailable"))?;
+        let mut host = vec![0u8; self.pitch() * self.height as usize];
+        let status = unsafe {
+            (libs.cuda_memcpy)(
+                host.as_mut_ptr() as *mut c_void,
+                self.ptr as *const c_void,
+                host.len(),
+                CUDA_MEMCPY_DEVICE_TO_HOST,
+            )
+        };
+        if status != 0 {
+            bail!("cudaMemcpy device->host failed with status {}", status);
+        }
+        RgbImage::from_raw(self.width, self.height, host)
+            .ok_or_else(|| anyhow::anyhow!("Downloaded buffer has unexpected size"))
+    }
+}
+
+impl Drop for DeviceImage {
+    fn drop(&mut self) {
+        if let Some(libs) = libs() {
+            unsafe {
+                (libs.cuda_free)(self.ptr as *mut c_void);
+            }
+        }
+    }
+}
+
+/// Decodes a JPEG bitstream directly into device memory with nvJPEG.
+pub fn decode_jpeg(data: &[u8]) -> Result<DeviceImage> {
+    let libs = libs().ok_or_else(|| anyhow::anyhow!("NVIDIA offload unavailable"))?;
+
+    NVJPEG_CONTEXT.with(|cell| {
+        let mut slot = cell.borrow_mut();
+        if slot.is_none() {
+            *slot = Some(NvjpegContext::new(libs)?);
+        }
+        let ctx = slot.as_ref().expect("nvJPEG context initialised above");
+
+        let mut components: c_int = 0;
+        let mut subsampling: c_int = NVJPEG_CSS_UNKNOWN;
+        let mut widths = [0 as c_int; NVJPEG_MAX_COMPONENT];
+        let mut heights = [0 as c_int; NVJPEG_MAX_COMPONENT];
+        let status = unsafe {
+            (libs.nvjpeg_get_image_info)(
+                ctx.handle,
+                data.as_ptr(),
+                data.len(),
+                &mut components,
+                &mut subsampling,
+                widths.as_mut_ptr(),
+                heights.as_mut_ptr(),
+            )
+        };
+        if status != 0 {
+            bail!("nvjpegGetImageInfo failed with status {}", status);
+        }
+        if subsampling == NVJPEG_CSS_UNKNOWN || widths[0] <= 0 || heights[0] <= 0 {
+            bail!("JPEG layout not supported by nvJPEG");
+        }
+
+        let image = DeviceImage::alloc(libs, widths[0] as u32, heights[0] as u32)?;
+        let mut destination = NvjpegImage {
+            channel: [image.ptr, ptr::null_mut(), ptr::null_mut(), ptr::null_mut()],
+            pitch: [image.pitch() as c_uint, 0, 0, 0],
+        };
+        let status = unsafe {
+            (libs.nvjpeg_decode)(
+                ctx.handle,
+                ctx.state,
+                data.as_ptr(),
+                data.len(),
+                NVJPEG_OUTPUT_RGBI,
+                &mut destination,
+                ptr::null_mut(),
+            )
+        };
+        if status != 0 {
+            bail!("nvjpegDecode failed with status {}", status);
+        }
+        let status = unsafe { (libs.cuda_stream_synchronize)(ptr::null_mut()) };
+        if status != 0 {
+            bail!("cudaStreamSynchronize failed with status {}", status);
+        }
+        Ok(image)
+    })
+}
+
+/// Uploads a host RGB image to device memory.
+pub fn upload_rgb(img: &RgbImage) -> Result<DeviceImage> {
+    let libs = libs().ok_or_else(|| anyhow::anyhow!("NVIDIA offload unavailable"))?;
+    let image = DeviceImage::alloc(libs, img.width(), img.height())?;
+    let raw = img.as_raw();
+    let status = unsafe {
+        (libs.cuda_memcpy)(
+            image.ptr as *mut c_void,
+            raw.as_ptr() as *const c_void,
+            raw.len(),
+            CUDA_MEMCPY_HOST_TO_DEVICE,
+        )
+    };
+    if status != 0 {
+        bail!("cudaMemcpy host->device failed with status {}", status);
+    }
+    Ok(image)
+}
@@ -2,6 +2,7 @@
 //!
 //! - Bounded worker pool (2-3 threads) for thumbnail generation
 //! - Generate on-demand for visible rows + prefetch margin
+//! - Visible items jump ahead of prefetch work via a two-lane channel
 //! - Update GTK textures in batches on main thread
 //! - Uses flume for communication between workers and main thread
 
@@ -35,6 +36,127 @@ const MAX_QUEUE_SIZE: usize = 256;
 /// Number of rows to prefetch ahead of the visible area.
 const PREFETCH_MARGIN: usize = 3;
 
+/// Every Nth receive checks the prefetch lane first so sustained visible
+/// traffic cannot starve it.
+const PREFETCH_LANE_EVERY: usize = 4;
+
+/// Scheduling lane for a thumbnail request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    /// On screen or selected; drained first.
+    Visible,
+    /// Near the viewport; serviced when the visible lane is idle.
+    Prefetch,
+}
+
+/// Sending half of a two-lane priority channel.
+pub struct PrioritySender<T> {
+    visible: Sender<T>,
+    prefetch: Sender<T>,
+}
+
+impl<T> Clone for PrioritySender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            visible: self.visible.clone(),
+            prefetch: self.prefetch.clone(),
+        }
+    }
+}
+
+impl<T> PrioritySender<T> {
+    pub fn try_send(&self, item: T, priority: Priority) -> Result<(), flume::TrySendError<T>> {
+        match priority {
+            Priority::Visible => self.visible.try_send(item),
+            Priority::Prefetch => self.prefetch.try_send(item),
+        }
+    }
+}
+
+/// Receiving half of a two-lane priority channel.
+///
+/// Each worker should own its own clone; the anti-starvation counter is per
+/// receiver.
+pub struct PriorityReceiver<T> {
+    visible: Receiver<T>,
+    prefetch: Receiver<T>,
+    picks: usize,
+}
+
+impl<T> Clone for PriorityReceiver<T> {
+    fn clone(&self) -> Self {
+        Self {
+            visible: self.visible.clone(),
+            prefetch: self.prefetch.clone(),
+            picks: 0,
+        }
+    }
+}
+
+impl<T> PriorityReceiver<T> {
+    /// Take the next queued item, preferring the visible lane.
+    fn try_lanes(&mut self) -> Option<T> {
+        self.picks = self.picks.wrapping_add(1);
+        let lanes = if self.picks.is_multiple_of(PREFETCH_LANE_EVERY) {
+            [&self.prefetch, &self.visible]
+        } else {
+            [&self.visible, &self.prefetch]
+        };
+        lanes.into_iter().find_map(|lane| lane.try_recv().ok())
+    }
+
+    /// Block until an item is available, preferring the visible lane.
+    pub fn recv(&mut self) -> Result<T, flume::RecvError> {
+        if let Some(item) = self.try_lanes() {
+            return Ok(item);
+        }
+        flume::Selector::new()
+            .recv(&self.visible, |r| r)
+            .recv(&self.prefetch, |r| r)
+            .wait()
+    }
+
+    /// Receive the next item, preferring the visible lane.
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<T, flume::RecvTimeoutError> {
+        if let Some(item) = self.try_lanes() {
+            return Ok(item);
+        }
+
+        // Both lanes empty: block until either has work
+        flume::Selector::new()
+            .recv(&self.visible, |r| r)
+            .recv(&self.prefetch, |r| r)
+            .wait_timeout(timeout)
+            .map_err(|_| flume::RecvTimeoutError::Timeout)?
+            .map_err(|_| flume::RecvTimeoutError::Disconnected)
+    }
+
+    /// Take any queued item without blocking, visible lane first.
+    pub fn try_recv(&self) -> Option<T> {
+        self.visible
+            .try_recv()
+            .or_else(|_| self.prefetch.try_recv())
+            .ok()
+    }
+}
+
+/// Create a two-lane channel; each lane holds up to `capacity` items.
+pub fn priority_channel<T>(capacity: usize) -> (PrioritySender<T>, PriorityReceiver<T>) {
+    let (visible_tx, visible_rx) = flume::bounded(capacity);
+    let (prefetch_tx, prefetch_rx) = flume::bounded(capacity);
+    (
+        PrioritySender {
+            visible: visible_tx,
+            prefetch: prefetch_tx,
+        },
+        PriorityReceiver {
+            visible: visible_rx,
+            prefetch: prefetch_rx,
+            picks: 0,
+        },
+    )
+}
+
 /// A request to generate a thumbnail.
 #[derive(Debug, Clone)]
 pub struct ThumbnailRequest {
@@ -44,6 +166,8 @@ pub struct ThumbnailRequest {
     pub mtime: i64,
     /// File size in bytes.
     pub size: i64,
+    /// EXIF orientation stored for the file at scan time.
+    pub orientation: u16,
     /// Priority (lower = higher priority, used for visible items).
     pub priority: u32,
     /// Row index for this item (used for batching).
@@ -56,11 +180,17 @@ impl ThumbnailRequest {
             path,
             mtime,
             size,
+            orientation: 1,
             priority: 100,
             row_index: 0,
         }
     }
 
+    pub fn with_orientation(mut self, orientation: u16) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
     pub fn with_priority(mut self, priority: u32) -> Self {
         self.priority = priority;
         self
@@ -71,6 +201,15 @@ impl ThumbnailRequest {
         self
     }
 
+    /// Lane for this request: priority 0 is visible, anything else is prefetch.
+    pub fn lane(&self) -> Priority {
+        if self.priority == 0 {
+            Priority::Visible
+        } else {
+            Priority::Prefetch
+        }
+    }
+
     /// Get the cache key for this request.
     pub fn cache_key(&self) -> CacheKey {
         CacheKey::new(&self.path, self.mtime, self.size)
@@ -95,13 +234,38 @@ pub struct ThumbnailResult {
     pub error: Option<String>,
 }
 
+/// Snapshot of how far the queue is through its current batch of work.
+///
+/// Counters restart whenever a request arrives while the queue is idle, so the
+/// totals describe the current fill (e.g. one cold directory) rather than the
+/// lifetime of the queue.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ThumbnailProgress {
+    /// Requests finished (successfully or not) in the current batch.
+    pub completed: usize,
+    /// Requests submitted in the current batch.
+    pub total: usize,
+}
+
+impl ThumbnailProgress {
+    /// Number of requests still waiting or in flight.
+    pub fn remaining(&self) -> usize {
+        self.total.saturating_sub(self.completed)
+    }
+
+    /// True when every submitted request has completed.
+    pub fn is_drained(&self) -> bool {
+        self.remaining() == 0
+    }
+}
+
 /// Callback type for handling completed thumbnails.
 pub type ThumbnailCallback = Box<dyn Fn(ThumbnailResult) + Send + Sync>;
 
 /// Worker queue for thumbnail generation.
 pub struct ThumbnailQueue {
     /// Sender for new requests.
-    request_tx: Sender<ThumbnailRequest>,
+    request_tx: PrioritySender<ThumbnailRequest>,
     /// Receiver for completed results (main thread reads this).
     result_rx: Receiver<ThumbnailResult>,
     /// Worker thread handles.
@@ -112,6 +276,10 @@ pub struct ThumbnailQueue {
     active_workers: Arc<AtomicUsize>,
     /// Set of paths currently being processed (to avoid duplicates).
     pending: Arc<RwLock<HashSet<PathBuf>>>,
+    /// Requests submitted in the current batch.
+    submitted: Arc<AtomicUsize>,
+    /// Requests completed in the current batch.
+    completed: Arc<AtomicUsize>,
     /// The thumbnail cache shared with workers.
     cache: ThumbnailCache,
     /// Currently visible row range for prioritization.
@@ -133,12 +301,13 @@ impl ThumbnailQueue {
     pub fn with_cache(workers: usize, cache: ThumbnailCache) -> Self {
         let num_workers = workers.clamp(1, MAX_WORKERS);
 
-        let (request_tx, request_rx) = flume::bounded(MAX_QUEUE_SIZE);
+        let (request_tx, request_rx) = priority_channel(MAX_QUEUE_SIZE);
         let (result_tx, result_rx) = flume::unbounded();
 
         let shutdown = Arc::new(AtomicBool::new(false));
         let active_workers = Arc::new(AtomicUsize::new(0));
         let pending = Arc::new(RwLock::new(HashSet::new()));
+        let completed = Arc::new(AtomicUsize::new(0));
 
         let mut worker_handles = Vec::with_capacity(num_workers);
 
@@ -149,12 +318,15 @@ impl ThumbnailQueue {
             let shutdown = Arc::clone(&shutdown);
             let active = Arc::clone(&active_workers);
             let pending = Arc::clone(&pending);
+            let completed = Arc::clone(&completed);
             let cache = cache.clone();
 
             let handle = thread::Builder::new()
                 .name(format!("thumb-worker-{}", worker_id))
                 .spawn(move || {
-                    worker_loop(worker_id, rx, tx, shutdown, active, pending, cache);
+                    worker_loop(
+                        worker_id, rx, tx, shutdown, active, pending, completed, cache,
+                    );
                 })
                 .expect("Failed to spawn thumbnail worker");
 
@@ -170,6 +342,8 @@ impl ThumbnailQueue {
             shutdown,
             active_workers,
             pending,
+            submitted: Arc::new(AtomicUsize::new(0)),
+            completed,
             cache,
             visible_range: Arc::new(RwLock::new((0, 0))),
             callbacks: Arc::new(Mutex::new(Vec::new())),
@@ -177,10 +351,18 @@ impl ThumbnailQueue {
         }
     }
 
-    /// Submit a request for thumbnail generation.
+    /// Submit a request for thumbnail generation in the lane given by its priority.
     ///
     /// Returns false if the queue is full or the item is already pending.
     pub fn request(&self, req: ThumbnailRequest) -> bool {
+        let lane = req.lane();
+        self.enqueue(req, lane)
+    }
+
+    /// Submit a request for thumbnail generation in an explicit lane.
+    ///
+    /// Returns false if the queue is full or the item is already pending.
+    pub fn enqueue(&self, req: ThumbnailRequest, priority: Priority) -> bool {
         // Check if already pending
         {
             let pending = self.pending.read();
@@ -191,17 +373,30 @@ impl ThumbnailQueue {
         }
 
         // Check if already cached
-        if self.cache.exists(&req.path, req.mtime, req.size) {
+        if self
+            .cache
+            .exists(&req.path, req.mtime, req.size, req.orientation)
+        {
             trace!(?req.path, "Thumbnail already cached");
             return false;
         }
 
-        // Add to pending set
-        self.pending.write().insert(req.path.clone());
+        // Add to pending set, starting a new progress batch if the queue was idle
+        {
+            let mut pending = self.pending.write();
+            if pending.is_empty() && self.progress().is_drained() {
+                self.submitted.store(0, Ordering::Relaxed);
+                self.completed.store(0, Ordering::Relaxed);
+            }
+            pending.insert(req.path.clone());
+        }
 
         // Send to workers
-        match self.request_tx.try_send(req) {
-            Ok(_) => true,
+        match self.request_tx.try_send(req, priority) {
+            Ok(_) => {
+                self.submitted.fetch_add(1, Ordering::Relaxed);
+                true
+            }
             Err(flume::TrySendError::Full(req)) => {
                 warn!("Thumbnail queue full, dropping request");
                 self.pending.write().remove(&req.path);
@@ -334,6 +529,16 @@ impl ThumbnailQueue {
         self.pending.read().len()
     }
 
+    /// Get completed/total counts for the current batch of requests.
+    pub fn progress(&self) -> ThumbnailProgress {
+        let total = self.submitted.load(Ordering::Relaxed);
+        let completed = self.completed.load(Ordering::Relaxed);
+        ThumbnailProgress {
+            completed: completed.min(total),
+            total,
+        }
+    }
+
     /// Get the number of active workers currently processing.
     pub fn active_worker_count(&self) -> usize {
         self.active_workers.load(Ordering::Relaxed)
@@ -351,6 +556,8 @@ impl ThumbnailQueue {
     pub fn cancel_all(&self) {
         // Clear pending set to allow re-submission
         self.pending.write().clear();
+        self.submitted.store(0, Ordering::Relaxed);
+        self.completed.store(0, Ordering::Relaxed);
 
         debug!("Cancelled all pending thumbnail requests");
     }
@@ -383,13 +590,15 @@ impl Drop for ThumbnailQueue {
 }
 
 /// Worker thread loop.
+#[allow(clippy::too_many_arguments)]
 fn worker_loop(
     worker_id: usize,
-    rx: Receiver<ThumbnailRequest>,
+    mut rx: PriorityReceiver<ThumbnailRequest>,
     tx: Sender<ThumbnailResult>,
     shutdown: Arc<AtomicBool>,
     active: Arc<AtomicUsize>,
     pending: Arc<RwLock<HashSet<PathBuf>>>,
+    completed: Arc<AtomicUsize>,
     cache: ThumbnailCache,
 ) {
     debug!(worker_id, "Thumbnail worker started");
@@ -407,8 +616,10 @@ fn worker_loop(
 
                 let result = process_request(&req, &cache);
 
-                // Remove from pending
-                pending.write().remove(&req.path);
+                // Remove from pending; requests cleared by cancel_all don't count
+                if pending.write().remove(&req.path) {
+                    completed.fetch_add(1, Ordering::Relaxed);
+                }
 
                 // Send result
                 if let Err(e) = tx.send(result) {
@@ -435,7 +646,7 @@ fn worker_loop(
 fn process_request(req: &ThumbnailRequest, cache: &ThumbnailCache) -> ThumbnailResult {
     trace!(?req.path, "Processing thumbnail request");
 
-    match cache.get_or_generate(&req.path, req.mtime, req.size) {
+    match cache.get_or_generate(&req.path, req.mtime, req.size, req.orientation) {
         Ok(cached) => ThumbnailResult {
             path: req.path.clone(),
             mtime: req.mtime,
@@ -522,10 +733,47 @@ mod tests {
         assert_eq!(req.row_index, 5);
     }
 
+    #[test]
+    fn test_priority_lanes_prefer_visible_without_starving_prefetch() {
+        let (tx, mut rx) = priority_channel::<u32>(16);
+        for i in 0..4 {
+            tx.try_send(100 + i, Priority::Prefetch).unwrap();
+        }
+        for i in 0..8 {
+            tx.try_send(i, Priority::Visible).unwrap();
+        }
+
+        let timeout = Duration::from_millis(10);
+        let order: Vec<u32> = (0..12).map(|_| rx.recv_timeout(timeout).unwrap()).collect();
+        assert_eq!(order, vec![0, 1, 2, 100, 3, 4, 5, 101, 6, 7, 102, 103]);
+        assert!(matches!(
+            rx.recv_timeout(timeout),
+            Err(flume::RecvTimeoutError::Timeout)
+        ));
+    }
+
+    #[test]
+    fn test_request_lane() {
+        let req = ThumbnailRequest::new("/test/image.jpg".into(), 123, 456);
+        assert_eq!(req.clone().with_priority(0).lane(), Priority::Visible);
+        assert_eq!(req.with_priority(2).lane(), Priority::Prefetch);
+    }
+
     #[test]
     fn test_cache_key_from_request() {
         let req = ThumbnailRequest::new("/test/image.jpg".into(), 123, 456);
         let key = req.cache_key();
         assert!(!key.disk_filename().is_empty());
     }
+
+    #[test]
+    fn test_progress_remaining() {
+        let progress = ThumbnailProgress {
+            completed: 1204,
+            total: 5000,
+        };
+        assert_eq!(progress.remaining(), 3796);
+        assert!(!progress.is_drained());
+        assert!(ThumbnailProgress::default().is_drained());
+    }
 }
@@ -0,0 +1,96 @@
+//! RGB and luminance histogram drawn over the viewer for exposure checks.
+
+use gtk4::cairo;
+
+pub const BINS: usize = 256;
+
+/// Per-channel pixel counts of an image.
+pub struct Histogram {
+    pub red: [u32; BINS],
+    pub green: [u32; BINS],
+    pub blue: [u32; BINS],
+    pub luma: [u32; BINS],
+}
+
+impl Histogram {
+    /// Counts tightly packed RGBA8 pixels. Fully transparent pixels are
+    /// skipped so a PNG's empty background does not swamp the black end.
+    pub fn from_rgba(data: &[u8]) -> Self {
+        let mut histogram = Self {
+            red: [0; BINS],
+            green: [0; BINS],
+            blue: [0; BINS],
+            luma: [0; BINS],
+        };
+        for pixel in data.chunks_exact(4) {
+            if pixel[3] == 0 {
+                continue;
+            }
+            let (r, g, b) = (pixel[0] as usize, pixel[1] as usize, pixel[2] as usize);
+            histogram.red[r] += 1;
+            histogram.green[g] += 1;
+            histogram.blue[b] += 1;
+            // Rec. 709 weights scaled to sum to 256
+            histogram.luma[(54 * r + 183 * g + 19 * b) >> 8] += 1;
+        }
+        histogram
+    }
+
+    fn peak(&self) -> u32 {
+        [&self.red, &self.green, &self.blue, &self.luma]
+            .iter()
+            .flat_map(|channel| channel.iter())
+            .copied()
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Luminance as a filled area with the colour channels outlined on top.
+    pub fn draw(&self, cr: &cairo::Context, width: f64, height: f64) {
+        let peak = self.peak().max(1) as f64;
+        let channels = [
+            (&self.luma, (0.85, 0.85, 0.85, 0.45)),
+            (&self.red, (1.0, 0.3, 0.3, 0.9)),
+            (&self.green, (0.3, 1.0, 0.3, 0.9)),
+            (&self.blue, (0.4, 0.5, 1.0, 0.9)),
+        ];
+        for (index, (counts, (r, g, b, a))) in channels.into_iter().enumerate() {
+            cr.set_source_rgba(r, g, b, a);
+            cr.move_to(0.0, height);
+            for (bin, &count) in counts.iter().enumerate() {
+                let x = bin as f64 * width / (BINS - 1) as f64;
+                cr.line_to(x, height - count as f64 / peak * height);
+            }
+            if index == 0 {
+                cr.line_to(width, height);
+                cr.close_path();
+                let _ = cr.fill();
+            } else {
+                cr.set_line_width(1.0);
+                let _ = cr.stroke();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_channels_and_skips_transparent_pixels() {
+        let data = [
+            255, 0, 0, 255, // red
+            255, 255, 255, 255, // white
+            10, 20, 30, 0, // transparent
+        ];
+        let histogram = Histogram::from_rgba(&data);
+        assert_eq!(histogram.red[255], 2);
+        assert_eq!(histogram.green[0], 1);
+        assert_eq!(histogram.blue[255], 1);
+        assert_eq!(histogram.luma[255], 1);
+        assert_eq!(histogram.luma[53], 1);
+        assert_eq!(histogram.red.iter().sum::<u32>(), 2);
+        assert_eq!(histogram.peak(), 2);
+    }
+}
@@ -1,25 +1,48 @@
 // Keybindings for idxd media browser
 // Handles grid navigation, viewer controls, and keyboard shortcuts
 //
-// Keybindings:
-// - Arrow keys / hjkl: Navigate grid
+// Default keybindings (remappable through the keymap file, see keymap.rs):
+// - Arrow keys / hjkl: Navigate grid; a count first (e.g. 5j) repeats the move
+// - g / Home, G / End: First / last item in grid; a count before G or % jumps
+//   that many percent through the grid
+// - 0-5: Rate the selection (in the grid a digit waits briefly for G or %)
 // - Enter: Open viewer for selected item
-// - Escape: Close viewer, return to grid
+// - Escape: Close viewer, return to grid; clear the multi-selection in grid
+// - v: Start/stop visual selection in grid (movement extends the selection)
 // - [ / ]: Rotate image 90 degrees in viewer
+// - Left / Right, h / l, n / N: Previous / next item in viewer
+// - p: Start/pause slideshow in viewer
+// - i: Toggle file info panel in viewer
+// - w: Save viewer rotation to the file
+// - y / Ctrl+C: Copy the viewed image to the clipboard
 // - Space: Play/pause (video) or toggle UI visibility
 // - f: Toggle fullscreen
 // - o: Open directory
 // - r: Toggle recursive scan
 // - s: Toggle shuffle
-// - +: Toggle favorite
-// - Delete: Delete file
-
-use gdk4::Key;
+// - Ctrl+S: Reshuffle with a new seed
+// - + / -: Grid thumbnail size (grid), toggle favorite with + (viewer)
+// - *: Toggle favorite
+// - Alt+F: Show only favorites in the grid
+// - a: Pick albums for the selected item(s) from the keyboard
+// - Delete: Move file to trash
+// - u: Undo the last delete, favorite, album or rating change
+
+use gdk4::{Key, ModifierType};
 use gtk4::prelude::*;
 use gtk4::{EventControllerKey, PropagationPhase, Widget};
 use std::cell::{Cell, RefCell};
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::time::Duration;
+
+use super::keymap::{Action, KeyMap};
+
+/// How long a typed count waits for the key it applies to. A lone `1`-`5`
+/// that is not followed by one rates the selection when this runs out.
+const COUNT_TIMEOUT: Duration = Duration::from_millis(600);
+/// Upper bound for a typed count.
+const MAX_COUNT: u32 = 99_999;
 
 /// Navigation direction for grid movement
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -30,6 +53,27 @@ pub enum Direction {
     Right,
 }
 
+/// Grid jump requested from the keyboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GridJump {
+    First,
+    Last,
+    /// Percentage through the flattened item list, clamped to 100
+    Percent(u32),
+}
+
+impl GridJump {
+    /// Flat index this jump lands on in a list of `len` items.
+    pub fn target_index(self, len: usize) -> Option<usize> {
+        let last = len.checked_sub(1)?;
+        Some(match self {
+            GridJump::First => 0,
+            GridJump::Last => last,
+            GridJump::Percent(percent) => last * percent.min(100) as usize / 100,
+        })
+    }
+}
+
 /// Current view mode
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ViewMode {
@@ -73,6 +117,19 @@ impl GridSelection {
         }
     }
 
+    /// Move selection `count` times in the given direction, stopping at the
+    /// edges of the grid
+    pub fn move_selection_by(&mut self, direction: Direction, count: u32) -> bool {
+        let mut moved = false;
+        for _ in 0..count {
+            if !self.move_selection(direction) {
+                break;
+            }
+            moved = true;
+        }
+        moved
+    }
+
     /// Move selection in the given direction
     pub fn move_selection(&mut self, direction: Direction) -> bool {
         if self.row_count == 0 {
@@ -173,18 +230,82 @@ pub type OpenDirectoryCallback = Box<dyn Fn()>;
 pub type ToggleRecursiveCallback = Box<dyn Fn()>;
 /// Callback type for toggling shuffle
 pub type ToggleShuffleCallback = Box<dyn Fn()>;
+/// Callback type for reshuffling with a new seed
+pub type ReshuffleCallback = Box<dyn Fn()>;
 /// Callback type for toggling favorite
 pub type ToggleFavoriteCallback = Box<dyn Fn()>;
+/// Callback type for toggling the favorites-only filter
+pub type ToggleFavoritesOnlyCallback = Box<dyn Fn()>;
 /// Callback type for deleting selected file
 pub type DeleteSelectedCallback = Box<dyn Fn()>;
 /// Callback type for rotating the viewer image
 pub type RotateViewerCallback = Box<dyn Fn()>;
+/// Callback type for grid thumbnail zoom; receives +1 (larger) or -1 (smaller)
+pub type GridZoomCallback = Box<dyn Fn(i32)>;
+/// Callback type for entering (true) or leaving (false) visual selection
+pub type VisualModeCallback = Box<dyn Fn(bool)>;
+/// Callback type for clearing the multi-selection
+pub type ClearMarksCallback = Box<dyn Fn()>;
+/// Callback type for undoing the last reversible change
+pub type UndoCallback = Box<dyn Fn()>;
+/// Callback type for starting or pausing the viewer slideshow
+pub type ToggleSlideshowCallback = Box<dyn Fn()>;
+/// Callback type for moving the viewer by +1 (next) or -1 (previous) item
+pub type ViewerStepCallback = Box<dyn Fn(i32)>;
+/// Callback type for showing or hiding the viewer's file info panel
+pub type ToggleInfoPanelCallback = Box<dyn Fn()>;
+/// Callback type for writing the viewer rotation back to the file
+pub type SaveRotationCallback = Box<dyn Fn()>;
+/// Callback type for copying the viewed image to the clipboard
+pub type CopyImageCallback = Box<dyn Fn()>;
+/// Callback type for opening quick tag entry for the selected item(s)
+pub type EditTagsCallback = Box<dyn Fn()>;
+/// Callback type for opening the album picker for the selected item(s)
+pub type PickAlbumsCallback = Box<dyn Fn()>;
+/// Callback type for rating the selected item(s) 1-5, or 0 to clear; returns
+/// false to leave the key to the viewer (e.g. while zoomed in)
+pub type SetRatingCallback = Box<dyn Fn(u8) -> bool>;
+/// Callback type for moving the selected item later (+1) or earlier (-1) in
+/// a manually ordered source; returns false when the source has no such order
+pub type MoveItemCallback = Box<dyn Fn(i32) -> bool>;
+/// Callback type for copying (false) or moving (true) the selected item(s)
+/// into another folder
+pub type TransferFilesCallback = Box<dyn Fn(bool)>;
+/// Callback type for renaming the selected file
+pub type RenameCallback = Box<dyn Fn()>;
+/// Callback type for bookmarking the current directory
+pub type BookmarkDirectoryCallback = Box<dyn Fn()>;
+/// Callback type for jumping to the first, last, or a percentage of the grid
+pub type GridJumpCallback = Box<dyn Fn(GridJump)>;
+/// Callback type for a count being typed (Some) or dropped unused (None)
+pub type PendingCountCallback = Box<dyn Fn(Option<u32>)>;
+/// Called to open the viewed or selected file in an external program.
+pub type OpenExternalCallback = Box<dyn Fn()>;
+/// Called to show where the viewed or selected photo was taken on a map.
+pub type OpenLocationCallback = Box<dyn Fn()>;
+/// Called to show the viewed or selected file in the file manager.
+pub type RevealCallback = Box<dyn Fn()>;
+/// Called to mute or unmute the video in the viewer.
+pub type ToggleMuteCallback = Box<dyn Fn()>;
+/// Called to turn video looping on or off in the viewer.
+pub type ToggleLoopCallback = Box<dyn Fn()>;
+/// Called to show or hide the viewer's histogram overlay.
+pub type ToggleHistogramCallback = Box<dyn Fn()>;
+/// Called to start or leave side-by-side comparison.
+pub type CompareCallback = Box<dyn Fn()>;
+/// Called to fit the viewed image to the viewport width (true) or height
+/// (false).
+pub type FitAxisCallback = Box<dyn Fn(bool)>;
 
 /// Keybinding manager for the media browser
 pub struct Keybindings {
     controller: EventControllerKey,
     view_mode: Rc<Cell<ViewMode>>,
     selection: Rc<RefCell<GridSelection>>,
+    visual_mode: Rc<Cell<bool>>,
+    /// Count typed ahead of a jump key in the grid
+    pending_count: Rc<Cell<Option<u32>>>,
+    count_timeout: Rc<RefCell<Option<glib::SourceId>>>,
     // Callbacks
     on_selection_changed: Rc<RefCell<Option<SelectionChangedCallback>>>,
     on_open_viewer: Rc<RefCell<Option<OpenViewerCallback>>>,
@@ -195,22 +316,53 @@ pub struct Keybindings {
     on_open_directory: Rc<RefCell<Option<OpenDirectoryCallback>>>,
     on_toggle_recursive: Rc<RefCell<Option<ToggleRecursiveCallback>>>,
     on_toggle_shuffle: Rc<RefCell<Option<ToggleShuffleCallback>>>,
+    on_reshuffle: Rc<RefCell<Option<ReshuffleCallback>>>,
     on_toggle_favorite: Rc<RefCell<Option<ToggleFavoriteCallback>>>,
+    on_toggle_favorites_only: Rc<RefCell<Option<ToggleFavoritesOnlyCallback>>>,
     on_delete_selected: Rc<RefCell<Option<DeleteSelectedCallback>>>,
     on_rotate_ccw: Rc<RefCell<Option<RotateViewerCallback>>>,
     on_rotate_cw: Rc<RefCell<Option<RotateViewerCallback>>>,
+    on_grid_zoom: Rc<RefCell<Option<GridZoomCallback>>>,
+    on_visual_mode: Rc<RefCell<Option<VisualModeCallback>>>,
+    on_clear_marks: Rc<RefCell<Option<ClearMarksCallback>>>,
+    on_undo: Rc<RefCell<Option<UndoCallback>>>,
+    on_toggle_slideshow: Rc<RefCell<Option<ToggleSlideshowCallback>>>,
+    on_viewer_step: Rc<RefCell<Option<ViewerStepCallback>>>,
+    on_toggle_info_panel: Rc<RefCell<Option<ToggleInfoPanelCallback>>>,
+    on_save_rotation: Rc<RefCell<Option<SaveRotationCallback>>>,
+    on_copy_image: Rc<RefCell<Option<CopyImageCallback>>>,
+    on_edit_tags: Rc<RefCell<Option<EditTagsCallback>>>,
+    on_pick_albums: Rc<RefCell<Option<PickAlbumsCallback>>>,
+    on_set_rating: Rc<RefCell<Option<SetRatingCallback>>>,
+    on_move_item: Rc<RefCell<Option<MoveItemCallback>>>,
+    on_transfer_files: Rc<RefCell<Option<TransferFilesCallback>>>,
+    on_rename: Rc<RefCell<Option<RenameCallback>>>,
+    on_bookmark_directory: Rc<RefCell<Option<BookmarkDirectoryCallback>>>,
+    on_grid_jump: Rc<RefCell<Option<GridJumpCallback>>>,
+    on_pending_count: Rc<RefCell<Option<PendingCountCallback>>>,
+    on_open_external: Rc<RefCell<Option<OpenExternalCallback>>>,
+    on_open_location: Rc<RefCell<Option<OpenLocationCallback>>>,
+    on_reveal: Rc<RefCell<Option<RevealCallback>>>,
+    on_toggle_mute: Rc<RefCell<Option<ToggleMuteCallback>>>,
+    on_toggle_loop: Rc<RefCell<Option<ToggleLoopCallback>>>,
+    on_toggle_histogram: Rc<RefCell<Option<ToggleHistogramCallback>>>,
+    on_compare: Rc<RefCell<Option<CompareCallback>>>,
+    on_fit_axis: Rc<RefCell<Option<FitAxisCallback>>>,
     // Path lookup function
     get_path: Rc<RefCell<Option<Box<dyn Fn(u32, u32) -> Option<PathBuf>>>>>,
 }
 
 impl Keybindings {
-    /// Create a new keybinding manager
-    pub fn new() -> Self {
+    /// Create a new keybinding manager using `keymap` for every shortcut
+    pub fn new(keymap: KeyMap) -> Self {
         let controller = EventControllerKey::new();
         controller.set_propagation_phase(PropagationPhase::Capture);
 
         let view_mode = Rc::new(Cell::new(ViewMode::Grid));
         let selection = Rc::new(RefCell::new(GridSelection::new(|_| 4))); // Default 4 items per row
+        let visual_mode = Rc::new(Cell::new(false));
+        let pending_count = Rc::new(Cell::new(None));
+        let count_timeout = Rc::new(RefCell::new(None));
 
         let on_selection_changed: Rc<RefCell<Option<SelectionChangedCallback>>> =
             Rc::new(RefCell::new(None));
@@ -226,18 +378,59 @@ impl Keybindings {
             Rc::new(RefCell::new(None));
         let on_toggle_shuffle: Rc<RefCell<Option<ToggleShuffleCallback>>> =
             Rc::new(RefCell::new(None));
+        let on_reshuffle: Rc<RefCell<Option<ReshuffleCallback>>> = Rc::new(RefCell::new(None));
         let on_toggle_favorite: Rc<RefCell<Option<ToggleFavoriteCallback>>> =
             Rc::new(RefCell::new(None));
+        let on_toggle_favorites_only: Rc<RefCell<Option<ToggleFavoritesOnlyCallback>>> =
+            Rc::new(RefCell::new(None));
         let on_delete_selected: Rc<RefCell<Option<DeleteSelectedCallback>>> =
             Rc::new(RefCell::new(None));
         let on_rotate_ccw: Rc<RefCell<Option<RotateViewerCallback>>> = Rc::new(RefCell::new(None));
         let on_rotate_cw: Rc<RefCell<Option<RotateViewerCallback>>> = Rc::new(RefCell::new(None));
+        let on_grid_zoom: Rc<RefCell<Option<GridZoomCallback>>> = Rc::new(RefCell::new(None));
+        let on_visual_mode: Rc<RefCell<Option<VisualModeCallback>>> = Rc::new(RefCell::new(None));
+        let on_clear_marks: Rc<RefCell<Option<ClearMarksCallback>>> = Rc::new(RefCell::new(None));
+        let on_undo: Rc<RefCell<Option<UndoCallback>>> = Rc::new(RefCell::new(None));
+        let on_toggle_slideshow: Rc<RefCell<Option<ToggleSlideshowCallback>>> =
+            Rc::new(RefCell::new(None));
+        let on_viewer_step: Rc<RefCell<Option<ViewerStepCallback>>> = Rc::new(RefCell::new(None));
+        let on_toggle_info_panel: Rc<RefCell<Option<ToggleInfoPanelCallback>>> =
+            Rc::new(RefCell::new(None));
+        let on_save_rotation: Rc<RefCell<Option<SaveRotationCallback>>> =
+            Rc::new(RefCell::new(None));
+        let on_copy_image: Rc<RefCell<Option<CopyImageCallback>>> = Rc::new(RefCell::new(None));
+        let on_edit_tags: Rc<RefCell<Option<EditTagsCallback>>> = Rc::new(RefCell::new(None));
+        let on_pick_albums: Rc<RefCell<Option<PickAlbumsCallback>>> = Rc::new(RefCell::new(None));
+        let on_set_rating: Rc<RefCell<Option<SetRatingCallback>>> = Rc::new(RefCell::new(None));
+        let on_move_item: Rc<RefCell<Option<MoveItemCallback>>> = Rc::new(RefCell::new(None));
+        let on_transfer_files: Rc<RefCell<Option<TransferFilesCallback>>> =
+            Rc::new(RefCell::new(None));
+        let on_rename: Rc<RefCell<Option<RenameCallback>>> = Rc::new(RefCell::new(None));
+        let on_bookmark_directory: Rc<RefCell<Option<BookmarkDirectoryCallback>>> =
+            Rc::new(RefCell::new(None));
+        let on_grid_jump: Rc<RefCell<Option<GridJumpCallback>>> = Rc::new(RefCell::new(None));
+        let on_pending_count: Rc<RefCell<Option<PendingCountCallback>>> =
+            Rc::new(RefCell::new(None));
+        let on_open_external: Rc<RefCell<Option<OpenExternalCallback>>> =
+            Rc::new(RefCell::new(None));
+        let on_open_location: Rc<RefCell<Option<OpenLocationCallback>>> =
+            Rc::new(RefCell::new(None));
+        let on_reveal: Rc<RefCell<Option<RevealCallback>>> = Rc::new(RefCell::new(None));
+        let on_toggle_mute: Rc<RefCell<Option<ToggleMuteCallback>>> = Rc::new(RefCell::new(None));
+        let on_toggle_loop: Rc<RefCell<Option<ToggleLoopCallback>>> = Rc::new(RefCell::new(None));
+        let on_toggle_histogram: Rc<RefCell<Option<ToggleHistogramCallback>>> =
+            Rc::new(RefCell::new(None));
+        let on_compare: Rc<RefCell<Option<CompareCallback>>> = Rc::new(RefCell::new(None));
+        let on_fit_axis: Rc<RefCell<Option<FitAxisCallback>>> = Rc::new(RefCell::new(None));
         let get_path: Rc<RefCell<Option<Box<dyn Fn(u32, u32) -> Option<PathBuf>>>>> =
             Rc::new(RefCell::new(None));
 
         // Clone references for the closure
         let view_mode_clone = view_mode.clone();
         let selection_clone = selection.clone();
+        let visual_mode_clone = visual_mode.clone();
+        let pending_count_clone = pending_count.clone();
+        let count_timeout_clone = count_timeout.clone();
         let on_selection_changed_clone = on_selection_changed.clone();
         let on_open_viewer_clone = on_open_viewer.clone();
         let on_close_viewer_clone = on_close_viewer.clone();
@@ -247,17 +440,60 @@ impl Keybindings {
         let on_open_directory_clone = on_open_directory.clone();
         let on_toggle_recursive_clone = on_toggle_recursive.clone();
         let on_toggle_shuffle_clone = on_toggle_shuffle.clone();
+        let on_reshuffle_clone = on_reshuffle.clone();
         let on_toggle_favorite_clone = on_toggle_favorite.clone();
+        let on_toggle_favorites_only_clone = on_toggle_favorites_only.clone();
         let on_delete_selected_clone = on_delete_selected.clone();
         let on_rotate_ccw_clone = on_rotate_ccw.clone();
         let on_rotate_cw_clone = on_rotate_cw.clone();
+        let on_grid_zoom_clone = on_grid_zoom.clone();
+        let on_visual_mode_clone = on_visual_mode.clone();
+        let on_clear_marks_clone = on_clear_marks.clone();
+        let on_undo_clone = on_undo.clone();
+        let on_toggle_slideshow_clone = on_toggle_slideshow.clone();
+        let on_viewer_step_clone = on_viewer_step.clone();
+        let on_toggle_info_panel_clone = on_toggle_info_panel.clone();
+        let on_save_rotation_clone = on_save_rotation.clone();
+        let on_copy_image_clone = on_copy_image.clone();
+        let on_edit_tags_clone = on_edit_tags.clone();
+        let on_pick_albums_clone = on_pick_albums.clone();
+        let on_set_rating_clone = on_set_rating.clone();
+        let on_move_item_clone = on_move_item.clone();
+        let on_transfer_files_clone = on_transfer_files.clone();
+        let on_rename_clone = on_rename.clone();
+        let on_bookmark_directory_clone = on_bookmark_directory.clone();
+        let on_grid_jump_clone = on_grid_jump.clone();
+        let on_pending_count_clone = on_pending_count.clone();
+        let on_open_external_clone = on_open_external.clone();
+        let on_open_location_clone = on_open_location.clone();
+        let on_reveal_clone = on_reveal.clone();
+        let on_toggle_mute_clone = on_toggle_mute.clone();
+        let on_toggle_loop_clone = on_toggle_loop.clone();
+        let on_toggle_histogram_clone = on_toggle_histogram.clone();
+        let on_compare_clone = on_compare.clone();
+        let on_fit_axis_clone = on_fit_axis.clone();
         let get_path_clone = get_path.clone();
 
-        controller.connect_key_pressed(move |_controller, keyval, _keycode, _state| {
+        controller.connect_key_pressed(move |controller, keyval, _keycode, state| {
+            // Let text fields (e.g. the name filter) receive typed characters
+            let editing = controller
+                .widget()
+                .and_then(|widget| widget.root())
+                .and_then(|root| root.focus())
+                .is_some_and(|focus| focus.is::<gtk4::Editable>());
+            if editing {
+                return glib::Propagation::Proceed;
+            }
+
             let handled = Self::handle_key_press(
                 keyval,
+                state,
+                &keymap,
                 &view_mode_clone,
                 &selection_clone,
+                &visual_mode_clone,
+                &pending_count_clone,
+                &count_timeout_clone,
                 &on_selection_changed_clone,
                 &on_open_viewer_clone,
                 &on_close_viewer_clone,
@@ -267,10 +503,38 @@ impl Keybindings {
                 &on_open_directory_clone,
                 &on_toggle_recursive_clone,
                 &on_toggle_shuffle_clone,
+                &on_reshuffle_clone,
                 &on_toggle_favorite_clone,
+                &on_toggle_favorites_only_clone,
                 &on_delete_selected_clone,
                 &on_rotate_ccw_clone,
                 &on_rotate_cw_clone,
+                &on_grid_zoom_clone,
+                &on_visual_mode_clone,
+                &on_clear_marks_clone,
+                &on_undo_clone,
+                &on_toggle_slideshow_clone,
+                &on_viewer_step_clone,
+                &on_toggle_info_panel_clone,
+                &on_save_rotation_clone,
+                &on_copy_image_clone,
+                &on_edit_tags_clone,
+                &on_pick_albums_clone,
+                &on_set_rating_clone,
+                &on_move_item_clone,
+                &on_transfer_files_clone,
+                &on_rename_clone,
+                &on_bookmark_directory_clone,
+                &on_grid_jump_clone,
+                &on_pending_count_clone,
+                &on_open_external_clone,
+                &on_open_location_clone,
+                &on_reveal_clone,
+                &on_toggle_mute_clone,
+                &on_toggle_loop_clone,
+                &on_toggle_histogram_clone,
+                &on_compare_clone,
+                &on_fit_axis_clone,
                 &get_path_clone,
             );
 
@@ -285,6 +549,9 @@ impl Keybindings {
             controller,
             view_mode,
             selection,
+            visual_mode,
+            pending_count,
+            count_timeout,
             on_selection_changed,
             on_open_viewer,
             on_close_viewer,
@@ -294,10 +561,38 @@ impl Keybindings {
             on_open_directory,
             on_toggle_recursive,
             on_toggle_shuffle,
+            on_reshuffle,
             on_toggle_favorite,
+            on_toggle_favorites_only,
             on_delete_selected,
             on_rotate_ccw,
             on_rotate_cw,
+            on_grid_zoom,
+            on_visual_mode,
+            on_clear_marks,
+            on_undo,
+            on_toggle_slideshow,
+            on_viewer_step,
+            on_toggle_info_panel,
+            on_save_rotation,
+            on_copy_image,
+            on_edit_tags,
+            on_pick_albums,
+            on_set_rating,
+            on_move_item,
+            on_transfer_files,
+            on_rename,
+            on_bookmark_directory,
+            on_grid_jump,
+            on_pending_count,
+            on_open_external,
+            on_open_location,
+            on_reveal,
+            on_toggle_mute,
+            on_toggle_loop,
+            on_toggle_histogram,
+            on_compare,
+            on_fit_axis,
             get_path,
         }
     }
@@ -359,6 +654,16 @@ impl Keybindings {
         self.view_mode.set(mode);
     }
 
+    /// Whether grid movement currently extends a visual selection
+    pub fn visual_mode(&self) -> bool {
+        self.visual_mode.get()
+    }
+
+    /// Leave or enter visual selection without firing callbacks
+    pub fn set_visual_mode(&self, active: bool) {
+        self.visual_mode.set(active);
+    }
+
     /// Connect callback for selection changes
     pub fn connect_selection_changed<F>(&self, callback: F)
     where
@@ -431,6 +736,14 @@ impl Keybindings {
         *self.on_toggle_shuffle.borrow_mut() = Some(Box::new(callback));
     }
 
+    /// Connect callback for reshuffling with a new seed
+    pub fn connect_reshuffle<F>(&self, callback: F)
+    where
+        F: Fn() + 'static,
+    {
+        *self.on_reshuffle.borrow_mut() = Some(Box::new(callback));
+    }
+
     /// Connect callback for toggling favorite
     pub fn connect_toggle_favorite<F>(&self, callback: F)
     where
@@ -439,6 +752,14 @@ impl Keybindings {
         *self.on_toggle_favorite.borrow_mut() = Some(Box::new(callback));
     }
 
+    /// Connect callback for toggling the favorites-only filter
+    pub fn connect_toggle_favorites_only<F>(&self, callback: F)
+    where
+        F: Fn() + 'static,
+    {
+        *self.on_toggle_favorites_only.borrow_mut() = Some(Box::new(callback));
+    }
+
     /// Connect callback for deleting selected file
     pub fn connect_delete_selected<F>(&self, callback: F)
     where
@@ -463,12 +784,226 @@ impl Keybindings {
         *self.on_rotate_cw.borrow_mut() = Some(Box::new(callback));
     }
 
+    /// Connect callback for visual selection being started or stopped.
+    pub fn connect_visual_mode<F>(&self, callback: F)
+    where
+        F: Fn(bool) + 'static,
+    {
+        *self.on_visual_mode.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect callback for clearing the multi-selection.
+    pub fn connect_clear_marks<F>(&self, callback: F)
+    where
+        F: Fn() + 'static,
+    {
+        *self.on_clear_marks.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect callback for reverting the most recent delete, favorite, album
+    /// or rating change.
+    pub fn connect_undo<F>(&self, callback: F)
+    where
+        F: Fn() + 'static,
+    {
+        *self.on_undo.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect callback for starting or pausing the slideshow in the viewer.
+    pub fn connect_toggle_slideshow<F>(&self, callback: F)
+    where
+        F: Fn() + 'static,
+    {
+        *self.on_toggle_slideshow.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect callback for next/previous navigation inside the viewer.
+    pub fn connect_viewer_step<F>(&self, callback: F)
+    where
+        F: Fn(i32) + 'static,
+    {
+        *self.on_viewer_step.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect callback for toggling the file info panel in the viewer.
+    pub fn connect_toggle_info_panel<F>(&self, callback: F)
+    where
+        F: Fn() + 'static,
+    {
+        *self.on_toggle_info_panel.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect callback for saving the viewer rotation to the file.
+    pub fn connect_save_rotation<F>(&self, callback: F)
+    where
+        F: Fn() + 'static,
+    {
+        *self.on_save_rotation.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect callback for copying the viewed image to the clipboard.
+    pub fn connect_copy_image<F>(&self, callback: F)
+    where
+        F: Fn() + 'static,
+    {
+        *self.on_copy_image.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect callback for opening quick tag entry.
+    pub fn connect_edit_tags<F>(&self, callback: F)
+    where
+        F: Fn() + 'static,
+    {
+        *self.on_edit_tags.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect callback for opening the album picker.
+    pub fn connect_pick_albums<F>(&self, callback: F)
+    where
+        F: Fn() + 'static,
+    {
+        *self.on_pick_albums.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect callback for rating keys `0`-`5`.
+    pub fn connect_set_rating<F>(&self, callback: F)
+    where
+        F: Fn(u8) -> bool + 'static,
+    {
+        *self.on_set_rating.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect callback for `Shift+J`/`Shift+K` reordering in the grid.
+    pub fn connect_move_item<F>(&self, callback: F)
+    where
+        F: Fn(i32) -> bool + 'static,
+    {
+        *self.on_move_item.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect callback for `c` (copy to folder) and `m` (move to folder).
+    pub fn connect_transfer_files<F>(&self, callback: F)
+    where
+        F: Fn(bool) + 'static,
+    {
+        *self.on_transfer_files.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect callback for `F2` (rename the selected file).
+    pub fn connect_rename<F>(&self, callback: F)
+    where
+        F: Fn() + 'static,
+    {
+        *self.on_rename.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect callback for `b` (bookmark the current directory).
+    pub fn connect_bookmark_directory<F>(&self, callback: F)
+    where
+        F: Fn() + 'static,
+    {
+        *self.on_bookmark_directory.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect callback for `g`/`Home`, `G`/`End`, and count-prefixed `G`/`%`.
+    pub fn connect_grid_jump<F>(&self, callback: F)
+    where
+        F: Fn(GridJump) + 'static,
+    {
+        *self.on_grid_jump.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect callback for the count typed ahead of a movement or jump key.
+    pub fn connect_pending_count<F>(&self, callback: F)
+    where
+        F: Fn(Option<u32>) + 'static,
+    {
+        *self.on_pending_count.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect callback for opening the current file in an external program.
+    pub fn connect_open_external<F>(&self, callback: F)
+    where
+        F: Fn() + 'static,
+    {
+        *self.on_open_external.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect callback for opening the current photo's GPS position on a map.
+    pub fn connect_open_location<F>(&self, callback: F)
+    where
+        F: Fn() + 'static,
+    {
+        *self.on_open_location.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect callback for showing the current file in the file manager.
+    pub fn connect_reveal<F>(&self, callback: F)
+    where
+        F: Fn() + 'static,
+    {
+        *self.on_reveal.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect callback for muting or unmuting the viewer's video.
+    pub fn connect_toggle_mute<F>(&self, callback: F)
+    where
+        F: Fn() + 'static,
+    {
+        *self.on_toggle_mute.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect callback for toggling video looping in the viewer.
+    pub fn connect_toggle_loop<F>(&self, callback: F)
+    where
+        F: Fn() + 'static,
+    {
+        *self.on_toggle_loop.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect callback for showing or hiding the viewer histogram.
+    pub fn connect_toggle_histogram<F>(&self, callback: F)
+    where
+        F: Fn() + 'static,
+    {
+        *self.on_toggle_histogram.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect callback for comparing two images side by side.
+    pub fn connect_compare<F>(&self, callback: F)
+    where
+        F: Fn() + 'static,
+    {
+        *self.on_compare.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect callback for fitting the viewed image to the width or height.
+    pub fn connect_fit_axis<F>(&self, callback: F)
+    where
+        F: Fn(bool) + 'static,
+    {
+        *self.on_fit_axis.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect callback for growing or shrinking grid thumbnails.
+    pub fn connect_grid_zoom<F>(&self, callback: F)
+    where
+        F: Fn(i32) + 'static,
+    {
+        *self.on_grid_zoom.borrow_mut() = Some(Box::new(callback));
+    }
+
     /// Handle a key press event
     #[allow(clippy::too_many_arguments)]
     fn handle_key_press(
         keyval: Key,
+        state: ModifierType,
+        keymap: &KeyMap,
         view_mode: &Rc<Cell<ViewMode>>,
         selection: &Rc<RefCell<GridSelection>>,
+        visual_mode: &Rc<Cell<bool>>,
+        pending_count: &Rc<Cell<Option<u32>>>,
+        count_timeout: &Rc<RefCell<Option<glib::SourceId>>>,
         on_selection_changed: &Rc<RefCell<Option<SelectionChangedCallback>>>,
         on_open_viewer: &Rc<RefCell<Option<OpenViewerCallback>>>,
         on_close_viewer: &Rc<RefCell<Option<CloseViewerCallback>>>,
@@ -478,16 +1013,126 @@ impl Keybindings {
         on_open_directory: &Rc<RefCell<Option<OpenDirectoryCallback>>>,
         on_toggle_recursive: &Rc<RefCell<Option<ToggleRecursiveCallback>>>,
         on_toggle_shuffle: &Rc<RefCell<Option<ToggleShuffleCallback>>>,
+        on_reshuffle: &Rc<RefCell<Option<ReshuffleCallback>>>,
         on_toggle_favorite: &Rc<RefCell<Option<ToggleFavoriteCallback>>>,
+        on_toggle_favorites_only: &Rc<RefCell<Option<ToggleFavoritesOnlyCallback>>>,
         on_delete_selected: &Rc<RefCell<Option<DeleteSelectedCallback>>>,
         on_rotate_ccw: &Rc<RefCell<Option<RotateViewerCallback>>>,
         on_rotate_cw: &Rc<RefCell<Option<RotateViewerCallback>>>,
+        on_grid_zoom: &Rc<RefCell<Option<GridZoomCallback>>>,
+        on_visual_mode: &Rc<RefCell<Option<VisualModeCallback>>>,
+        on_clear_marks: &Rc<RefCell<Option<ClearMarksCallback>>>,
+        on_undo: &Rc<RefCell<Option<UndoCallback>>>,
+        on_toggle_slideshow: &Rc<RefCell<Option<ToggleSlideshowCallback>>>,
+        on_viewer_step: &Rc<RefCell<Option<ViewerStepCallback>>>,
+        on_toggle_info_panel: &Rc<RefCell<Option<ToggleInfoPanelCallback>>>,
+        on_save_rotation: &Rc<RefCell<Option<SaveRotationCallback>>>,
+        on_copy_image: &Rc<RefCell<Option<CopyImageCallback>>>,
+        on_edit_tags: &Rc<RefCell<Option<EditTagsCallback>>>,
+        on_pick_albums: &Rc<RefCell<Option<PickAlbumsCallback>>>,
+        on_set_rating: &Rc<RefCell<Option<SetRatingCallback>>>,
+        on_move_item: &Rc<RefCell<Option<MoveItemCallback>>>,
+        on_transfer_files: &Rc<RefCell<Option<TransferFilesCallback>>>,
+        on_rename: &Rc<RefCell<Option<RenameCallback>>>,
+        on_bookmark_directory: &Rc<RefCell<Option<BookmarkDirectoryCallback>>>,
+        on_grid_jump: &Rc<RefCell<Option<GridJumpCallback>>>,
+        on_pending_count: &Rc<RefCell<Option<PendingCountCallback>>>,
+        on_open_external: &Rc<RefCell<Option<OpenExternalCallback>>>,
+        on_open_location: &Rc<RefCell<Option<OpenLocationCallback>>>,
+        on_reveal: &Rc<RefCell<Option<RevealCallback>>>,
+        on_toggle_mute: &Rc<RefCell<Option<ToggleMuteCallback>>>,
+        on_toggle_loop: &Rc<RefCell<Option<ToggleLoopCallback>>>,
+        on_toggle_histogram: &Rc<RefCell<Option<ToggleHistogramCallback>>>,
+        on_compare: &Rc<RefCell<Option<CompareCallback>>>,
+        on_fit_axis: &Rc<RefCell<Option<FitAxisCallback>>>,
         get_path: &Rc<RefCell<Option<Box<dyn Fn(u32, u32) -> Option<PathBuf>>>>>,
     ) -> bool {
         let mode = view_mode.get();
+        let pressed = |action| keymap.matches(action, keyval, state);
+
+        // Digits typed in the grid build a count for the next movement or
+        // jump. A leading 0 is not a count, so `0` still clears the rating
+        // right away.
+        if mode == ViewMode::Grid && !state.contains(ModifierType::CONTROL_MASK) {
+            if let Some(digit) = digit_value(keyval) {
+                if digit != 0 || pending_count.get().is_some() {
+                    let count = pending_count
+                        .get()
+                        .unwrap_or(0)
+                        .saturating_mul(10)
+                        .saturating_add(digit)
+                        .min(MAX_COUNT);
+                    pending_count.set(Some(count));
+                    Self::restart_count_timeout(
+                        pending_count,
+                        count_timeout,
+                        on_set_rating,
+                        on_pending_count,
+                    );
+                    if let Some(ref callback) = *on_pending_count.borrow() {
+                        callback(Some(count));
+                    }
+                    return true;
+                }
+            }
+        }
+        let count = Self::take_pending_count(pending_count, count_timeout);
+        if pressed(Action::Close) && count.is_some() {
+            if let Some(ref callback) = *on_pending_count.borrow() {
+                callback(None);
+            }
+            return true;
+        }
+
+        // Handle first/last/percentage jumps in the grid
+        if mode == ViewMode::Grid {
+            let jump = if pressed(Action::First) {
+                Some(GridJump::First)
+            } else if pressed(Action::Last) {
+                Some(count.map_or(GridJump::Last, GridJump::Percent))
+            } else if pressed(Action::Percent) {
+                count.map(GridJump::Percent)
+            } else {
+                None
+            };
+            if let Some(jump) = jump {
+                if let Some(ref callback) = *on_grid_jump.borrow() {
+                    callback(jump);
+                    return true;
+                }
+            }
+
+            let direction = [
+                (Action::MoveUp, Direction::Up),
+                (Action::MoveDown, Direction::Down),
+                (Action::MoveLeft, Direction::Left),
+                (Action::MoveRight, Direction::Right),
+            ]
+            .into_iter()
+            .find_map(|(action, direction)| pressed(action).then_some(direction));
+            if let Some(direction) = direction {
+                let mut sel = selection.borrow_mut();
+                if sel.move_selection_by(direction, count.unwrap_or(1)) {
+                    let (row, col) = sel.position();
+                    drop(sel); // Release borrow before callback
+                    if let Some(ref callback) = *on_selection_changed.borrow() {
+                        callback(row, col);
+                    }
+                }
+                return true;
+            }
+        }
+
+        // Any other key drops the count; only the timeout turns a lone digit
+        // into a rating, so `3f` just goes fullscreen.
+        if count.is_some() {
+            if let Some(ref callback) = *on_pending_count.borrow() {
+                callback(None);
+            }
+        }
 
         // Handle Escape - close viewer
-        if keyval == Key::Escape {
+        if pressed(Action::Close) {
             if mode == ViewMode::Viewer {
                 view_mode.set(ViewMode::Grid);
                 if let Some(ref callback) = *on_close_viewer.borrow() {
@@ -495,11 +1140,26 @@ impl Keybindings {
                 }
                 return true;
             }
+            if let Some(ref callback) = *on_clear_marks.borrow() {
+                visual_mode.set(false);
+                callback();
+                return true;
+            }
             return false;
         }
 
+        // Handle visual selection toggle
+        if pressed(Action::VisualMode) && mode == ViewMode::Grid {
+            if let Some(ref callback) = *on_visual_mode.borrow() {
+                let active = !visual_mode.get();
+                visual_mode.set(active);
+                callback(active);
+                return true;
+            }
+        }
+
         // Handle Enter - open viewer
-        if keyval == Key::Return || keyval == Key::KP_Enter {
+        if pressed(Action::Open) {
             if mode == ViewMode::Grid {
                 let sel = selection.borrow();
                 let (row, col) = sel.position();
@@ -520,7 +1180,7 @@ impl Keybindings {
         }
 
         // Handle Space - play/pause or toggle UI
-        if keyval == Key::space {
+        if pressed(Action::PlayPause) {
             if mode == ViewMode::Viewer {
                 // In viewer: play/pause for video
                 if let Some(ref callback) = *on_play_pause.borrow() {
@@ -536,7 +1196,7 @@ impl Keybindings {
         }
 
         // Handle fullscreen toggle
-        if keyval == Key::f || keyval == Key::F {
+        if pressed(Action::Fullscreen) {
             if let Some(ref callback) = *on_toggle_fullscreen.borrow() {
                 callback();
             }
@@ -544,7 +1204,7 @@ impl Keybindings {
         }
 
         // Handle open directory prompt
-        if keyval == Key::o || keyval == Key::O {
+        if pressed(Action::OpenDirectory) {
             if let Some(ref callback) = *on_open_directory.borrow() {
                 callback();
                 return true;
@@ -552,102 +1212,243 @@ impl Keybindings {
         }
 
         // Handle recursive toggle
-        if keyval == Key::r || keyval == Key::R {
+        if pressed(Action::Recursive) {
             if let Some(ref callback) = *on_toggle_recursive.borrow() {
                 callback();
                 return true;
             }
         }
 
+        // Handle reshuffle
+        if pressed(Action::Reshuffle) {
+            if let Some(ref callback) = *on_reshuffle.borrow() {
+                callback();
+                return true;
+            }
+        }
+
         // Handle shuffle toggle
-        if keyval == Key::s || keyval == Key::S {
+        if pressed(Action::Shuffle) {
             if let Some(ref callback) = *on_toggle_shuffle.borrow() {
                 callback();
                 return true;
             }
         }
 
+        // Handle grid thumbnail zoom (the viewer keeps + for favorites)
+        if mode == ViewMode::Grid {
+            let step = if pressed(Action::ZoomIn) {
+                Some(1)
+            } else if pressed(Action::ZoomOut) {
+                Some(-1)
+            } else {
+                None
+            };
+            if let Some(step) = step {
+                if let Some(ref callback) = *on_grid_zoom.borrow() {
+                    callback(step);
+                    return true;
+                }
+            }
+        }
+
         // Handle favorite toggle
-        if keyval == Key::plus || keyval == Key::equal || keyval == Key::KP_Add {
+        if pressed(Action::Favorite) {
             if let Some(ref callback) = *on_toggle_favorite.borrow() {
                 callback();
                 return true;
             }
         }
 
+        // Handle the favorites-only filter
+        if mode == ViewMode::Grid && pressed(Action::FavoritesOnly) {
+            if let Some(ref callback) = *on_toggle_favorites_only.borrow() {
+                callback();
+                return true;
+            }
+        }
+
+        // Handle rating keys
+        if let Some(rating @ 0..=5) = digit_value(keyval) {
+            if let Some(ref callback) = *on_set_rating.borrow() {
+                if callback(rating as u8) {
+                    return true;
+                }
+            }
+        }
+
+        // Handle quick tag entry
+        if pressed(Action::Tag) {
+            if let Some(ref callback) = *on_edit_tags.borrow() {
+                callback();
+                return true;
+            }
+        }
+
+        // Handle the album picker
+        if pressed(Action::Album) {
+            if let Some(ref callback) = *on_pick_albums.borrow() {
+                callback();
+                return true;
+            }
+        }
+
+        // Handle undo of the last reversible change
+        if pressed(Action::Undo) {
+            if let Some(ref callback) = *on_undo.borrow() {
+                callback();
+                return true;
+            }
+        }
+
+        // Handle copy/move to another folder
+        if pressed(Action::CopyTo) || pressed(Action::MoveTo) {
+            if let Some(ref callback) = *on_transfer_files.borrow() {
+                callback(pressed(Action::MoveTo));
+                return true;
+            }
+        }
+
+        // Handle rename
+        if pressed(Action::Rename) {
+            if let Some(ref callback) = *on_rename.borrow() {
+                callback();
+                return true;
+            }
+        }
+
+        // Handle opening in an external program
+        if pressed(Action::OpenExternal) {
+            if let Some(ref callback) = *on_open_external.borrow() {
+                callback();
+                return true;
+            }
+        }
+
+        // Handle showing the GPS position on a map
+        if pressed(Action::OpenLocation) {
+            if let Some(ref callback) = *on_open_location.borrow() {
+                callback();
+                return true;
+            }
+        }
+
+        // Handle showing the file in the file manager
+        if pressed(Action::Reveal) {
+            if let Some(ref callback) = *on_reveal.borrow() {
+                callback();
+                return true;
+            }
+        }
+
+        // Handle side-by-side comparison
+        if pressed(Action::Compare) {
+            if let Some(ref callback) = *on_compare.borrow() {
+                callback();
+                return true;
+            }
+        }
+
+        // Handle bookmarking the current directory
+        if pressed(Action::Bookmark) {
+            if let Some(ref callback) = *on_bookmark_directory.borrow() {
+                callback();
+                return true;
+            }
+        }
+
         // Handle delete
-        if keyval == Key::Delete {
+        if pressed(Action::Delete) {
             if let Some(ref callback) = *on_delete_selected.borrow() {
                 callback();
                 return true;
             }
         }
 
-        // Handle navigation keys (only in grid mode)
-        if mode == ViewMode::Grid {
-            let direction = match keyval {
-                // Arrow keys
-                Key::Up => Some(Direction::Up),
-                Key::Down => Some(Direction::Down),
-                Key::Left => Some(Direction::Left),
-                Key::Right => Some(Direction::Right),
-                // Vim-style keys (hjkl)
-                Key::h => Some(Direction::Left),
-                Key::j => Some(Direction::Down),
-                Key::k => Some(Direction::Up),
-                Key::l => Some(Direction::Right),
-                _ => None,
-            };
-
-            if let Some(dir) = direction {
-                let mut sel = selection.borrow_mut();
-                if sel.move_selection(dir) {
-                    let (row, col) = sel.position();
-                    drop(sel); // Release borrow before callback
-                    if let Some(ref callback) = *on_selection_changed.borrow() {
-                        callback(row, col);
-                    }
+        // Handle manual reordering (Shift+J/K)
+        let later = pressed(Action::MoveItemLater);
+        if mode == ViewMode::Grid && (later || pressed(Action::MoveItemEarlier)) {
+            if let Some(ref callback) = *on_move_item.borrow() {
+                if callback(if later { 1 } else { -1 }) {
+                    return true;
                 }
-                return true;
             }
         }
 
         // Handle viewer navigation (left/right for prev/next)
         if mode == ViewMode::Viewer {
-            if keyval == Key::bracketleft {
+            if pressed(Action::RotateCcw) {
                 if let Some(ref callback) = *on_rotate_ccw.borrow() {
                     callback();
                     return true;
                 }
             }
-            if keyval == Key::bracketright {
+            if pressed(Action::RotateCw) {
                 if let Some(ref callback) = *on_rotate_cw.borrow() {
                     callback();
                     return true;
                 }
             }
+            if pressed(Action::Slideshow) {
+                if let Some(ref callback) = *on_toggle_slideshow.borrow() {
+                    callback();
+                    return true;
+                }
+            }
+            if pressed(Action::InfoPanel) {
+                if let Some(ref callback) = *on_toggle_info_panel.borrow() {
+                    callback();
+                    return true;
+                }
+            }
+            if pressed(Action::SaveRotation) {
+                if let Some(ref callback) = *on_save_rotation.borrow() {
+                    callback();
+                    return true;
+                }
+            }
+            if pressed(Action::CopyImage) {
+                if let Some(ref callback) = *on_copy_image.borrow() {
+                    callback();
+                    return true;
+                }
+            }
+            if pressed(Action::Mute) {
+                if let Some(ref callback) = *on_toggle_mute.borrow() {
+                    callback();
+                    return true;
+                }
+            }
+            if pressed(Action::Loop) {
+                if let Some(ref callback) = *on_toggle_loop.borrow() {
+                    callback();
+                    return true;
+                }
+            }
+            if pressed(Action::Histogram) {
+                if let Some(ref callback) = *on_toggle_histogram.borrow() {
+                    callback();
+                    return true;
+                }
+            }
+            if pressed(Action::FitWidth) || pressed(Action::FitHeight) {
+                if let Some(ref callback) = *on_fit_axis.borrow() {
+                    callback(pressed(Action::FitWidth));
+                    return true;
+                }
+            }
 
-            let direction = match keyval {
-                Key::Left | Key::h => Some(Direction::Left),
-                Key::Right | Key::l => Some(Direction::Right),
-                _ => None,
+            let step = if pressed(Action::Previous) {
+                Some(-1)
+            } else if pressed(Action::Next) {
+                Some(1)
+            } else {
+                None
             };
 
-            if let Some(dir) = direction {
-                let mut sel = selection.borrow_mut();
-                if sel.move_selection(dir) {
-                    let (row, col) = sel.position();
-
-                    // Get the path for the new selection
-                    if let Some(ref get_path_fn) = *get_path.borrow() {
-                        if let Some(path) = get_path_fn(row, col) {
-                            drop(sel); // Release borrow before callback
-                            if let Some(ref callback) = *on_open_viewer.borrow() {
-                                callback(row, col, path);
-                            }
-                            return true;
-                        }
-                    }
+            if let Some(step) = step {
+                if let Some(ref callback) = *on_viewer_step.borrow() {
+                    callback(step);
                 }
                 return true;
             }
@@ -655,11 +1456,73 @@ impl Keybindings {
 
         false
     }
+
+    /// (Re)arms the timer that drops a pending count, rating the selection if
+    /// the count was a lone `1`-`5`.
+    fn restart_count_timeout(
+        pending_count: &Rc<Cell<Option<u32>>>,
+        count_timeout: &Rc<RefCell<Option<glib::SourceId>>>,
+        on_set_rating: &Rc<RefCell<Option<SetRatingCallback>>>,
+        on_pending_count: &Rc<RefCell<Option<PendingCountCallback>>>,
+    ) {
+        if let Some(source) = count_timeout.borrow_mut().take() {
+            source.remove();
+        }
+        let pending_count = pending_count.clone();
+        let timeout = count_timeout.clone();
+        let on_set_rating = on_set_rating.clone();
+        let on_pending_count = on_pending_count.clone();
+        let source = glib::timeout_add_local_once(COUNT_TIMEOUT, move || {
+            timeout.borrow_mut().take();
+            match pending_count.take() {
+                Some(rating @ 1..=5) => {
+                    if let Some(ref callback) = *on_set_rating.borrow() {
+                        callback(rating as u8);
+                    }
+                }
+                Some(_) => {
+                    if let Some(ref callback) = *on_pending_count.borrow() {
+                        callback(None);
+                    }
+                }
+                None => {}
+            }
+        });
+        *count_timeout.borrow_mut() = Some(source);
+    }
+
+    /// Takes the pending count, if any, and stops its timer.
+    fn take_pending_count(
+        pending_count: &Rc<Cell<Option<u32>>>,
+        count_timeout: &Rc<RefCell<Option<glib::SourceId>>>,
+    ) -> Option<u32> {
+        if let Some(source) = count_timeout.borrow_mut().take() {
+            source.remove();
+        }
+        pending_count.take()
+    }
+}
+
+/// Value of a digit key on the main row or the keypad.
+fn digit_value(keyval: Key) -> Option<u32> {
+    match keyval {
+        Key::_0 | Key::KP_0 => Some(0),
+        Key::_1 | Key::KP_1 => Some(1),
+        Key::_2 | Key::KP_2 => Some(2),
+        Key::_3 | Key::KP_3 => Some(3),
+        Key::_4 | Key::KP_4 => Some(4),
+        Key::_5 | Key::KP_5 => Some(5),
+        Key::_6 | Key::KP_6 => Some(6),
+        Key::_7 | Key::KP_7 => Some(7),
+        Key::_8 | Key::KP_8 => Some(8),
+        Key::_9 | Key::KP_9 => Some(9),
+        _ => None,
+    }
 }
 
 impl Default for Keybindings {
     fn default() -> Self {
-        Self::new()
+        Self::new(KeyMap::default())
     }
 }
 
@@ -733,6 +1596,29 @@ mod tests {
         assert_eq!(selection.position(), (1, 3));
     }
 
+    #[test]
+    fn test_counted_movement_clamps_at_edges() {
+        let mut selection = GridSelection::new(|row| if row == 2 { 2 } else { 4 });
+        selection.set_row_count(3);
+
+        assert!(selection.move_selection_by(Direction::Right, 5));
+        assert_eq!(selection.position(), (1, 1));
+        assert!(selection.move_selection_by(Direction::Down, 10));
+        assert_eq!(selection.position(), (2, 1));
+        assert!(!selection.move_selection_by(Direction::Right, 3));
+        assert!(selection.move_selection_by(Direction::Up, 99));
+        assert_eq!(selection.position(), (0, 1));
+    }
+
+    #[test]
+    fn test_grid_jump_targets() {
+        assert_eq!(GridJump::First.target_index(10), Some(0));
+        assert_eq!(GridJump::Last.target_index(10), Some(9));
+        assert_eq!(GridJump::Percent(50).target_index(11), Some(5));
+        assert_eq!(GridJump::Percent(250).target_index(11), Some(10));
+        assert_eq!(GridJump::Last.target_index(0), None);
+    }
+
     #[test]
     fn test_variable_items_per_row() {
         // Simulate rows with different item counts
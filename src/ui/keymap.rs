@@ -0,0 +1,440 @@
+//! User-remappable keys for `Keybindings`.
+//!
+//! Every shortcut is a named action with built-in default keys. At startup
+//! `XDG_CONFIG_HOME/idxd/keys.json` may override any of them, e.g.
+//!
+//! ```json
+//! { "move_left": ["Left", "n"], "move_down": "e", "delete": ["Delete", "x"] }
+//! ```
+//!
+//! A value is one key or a list of keys and replaces that action's defaults;
+//! an empty list unbinds it. Key names are GDK names (`Home`, `bracketleft`,
+//! `F2`) or single characters, optionally prefixed with `<Ctrl>`, `<Alt>`, or
+//! `<Super>`. Letters are case-sensitive, so `G` means Shift+g. Unknown
+//! actions and keys are logged and skipped. Digits are not remappable: they
+//! type counts and ratings.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use directories::ProjectDirs;
+use gdk4::{Key, ModifierType};
+
+/// File name of the keymap inside the config directory.
+const KEYMAP_FILE: &str = "keys.json";
+
+/// Modifiers a binding must match exactly; Shift is part of the key itself.
+const BINDING_MODIFIERS: ModifierType = ModifierType::CONTROL_MASK
+    .union(ModifierType::ALT_MASK)
+    .union(ModifierType::SUPER_MASK);
+
+/// A remappable shortcut.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    MoveLeft,
+    MoveDown,
+    MoveUp,
+    MoveRight,
+    First,
+    Last,
+    Percent,
+    Open,
+    Close,
+    VisualMode,
+    PlayPause,
+    Mute,
+    Loop,
+    Histogram,
+    Compare,
+    Fullscreen,
+    OpenDirectory,
+    Recursive,
+    Shuffle,
+    Reshuffle,
+    ZoomIn,
+    ZoomOut,
+    FitWidth,
+    FitHeight,
+    Favorite,
+    FavoritesOnly,
+    Tag,
+    Album,
+    Undo,
+    CopyTo,
+    MoveTo,
+    Rename,
+    OpenExternal,
+    OpenLocation,
+    Reveal,
+    Bookmark,
+    Delete,
+    MoveItemLater,
+    MoveItemEarlier,
+    RotateCcw,
+    RotateCw,
+    Slideshow,
+    InfoPanel,
+    SaveRotation,
+    CopyImage,
+    Previous,
+    Next,
+}
+
+impl Action {
+    pub const ALL: [Action; 47] = [
+        Action::MoveLeft,
+        Action::MoveDown,
+        Action::MoveUp,
+        Action::MoveRight,
+        Action::First,
+        Action::Last,
+        Action::Percent,
+        Action::Open,
+        Action::Close,
+        Action::VisualMode,
+        Action::PlayPause,
+        Action::Mute,
+        Action::Loop,
+        Action::Histogram,
+        Action::Compare,
+        Action::Fullscreen,
+        Action::OpenDirectory,
+        Action::Recursive,
+        Action::Shuffle,
+        Action::Reshuffle,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::FitWidth,
+        Action::FitHeight,
+        Action::Favorite,
+        Action::FavoritesOnly,
+        Action::Tag,
+        Action::Album,
+        Action::Undo,
+        Action::CopyTo,
+        Action::MoveTo,
+        Action::Rename,
+        Action::OpenExternal,
+        Action::OpenLocation,
+        Action::Reveal,
+        Action::Bookmark,
+        Action::Delete,
+        Action::MoveItemLater,
+        Action::MoveItemEarlier,
+        Action::RotateCcw,
+        Action::RotateCw,
+        Action::Slideshow,
+        Action::InfoPanel,
+        Action::SaveRotation,
+        Action::CopyImage,
+        Action::Previous,
+        Action::Next,
+    ];
+
+    /// Identifier used in the keymap file.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "move_left",
+            Action::MoveDown => "move_down",
+            Action::MoveUp => "move_up",
+            Action::MoveRight => "move_right",
+            Action::First => "first",
+            Action::Last => "last",
+            Action::Percent => "percent",
+            Action::Open => "open",
+            Action::Close => "close",
+            Action::VisualMode => "visual_mode",
+            Action::PlayPause => "play_pause",
+            Action::Mute => "mute",
+            Action::Loop => "loop",
+            Action::Histogram => "histogram",
+            Action::Compare => "compare",
+            Action::Fullscreen => "fullscreen",
+            Action::OpenDirectory => "open_directory",
+            Action::Recursive => "recursive",
+            Action::Shuffle => "shuffle",
+            Action::Reshuffle => "reshuffle",
+            Action::ZoomIn => "zoom_in",
+            Action::ZoomOut => "zoom_out",
+            Action::FitWidth => "fit_width",
+            Action::FitHeight => "fit_height",
+            Action::Favorite => "favorite",
+            Action::FavoritesOnly => "favorites_only",
+            Action::Tag => "tag",
+            Action::Album => "album",
+            Action::Undo => "undo",
+            Action::CopyTo => "copy_to",
+            Action::MoveTo => "move_to",
+            Action::Rename => "rename",
+            Action::OpenExternal => "open_external",
+            Action::OpenLocation => "open_location",
+            Action::Reveal => "reveal",
+            Action::Bookmark => "bookmark",
+            Action::Delete => "delete",
+            Action::MoveItemLater => "move_item_later",
+            Action::MoveItemEarlier => "move_item_earlier",
+            Action::RotateCcw => "rotate_ccw",
+            Action::RotateCw => "rotate_cw",
+            Action::Slideshow => "slideshow",
+            Action::InfoPanel => "info_panel",
+            Action::SaveRotation => "save_rotation",
+            Action::CopyImage => "copy_image",
+            Action::Previous => "previous",
+            Action::Next => "next",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    /// Keys bound when the keymap file does not mention the action.
+    fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            Action::MoveLeft => &["Left", "h"],
+            Action::MoveDown => &["Down", "j"],
+            Action::MoveUp => &["Up", "k"],
+            Action::MoveRight => &["Right", "l"],
+            Action::First => &["g", "Home", "KP_Home"],
+            Action::Last => &["G", "End", "KP_End"],
+            Action::Percent => &["percent"],
+            Action::Open => &["Return", "KP_Enter"],
+            Action::Close => &["Escape"],
+            Action::VisualMode => &["v"],
+            Action::PlayPause => &["space"],
+            Action::Mute => &["M"],
+            Action::Loop => &["L"],
+            Action::Histogram => &["H"],
+            Action::Compare => &["C"],
+            Action::Fullscreen => &["f", "F"],
+            Action::OpenDirectory => &["o", "O"],
+            Action::Recursive => &["r", "R"],
+            Action::Shuffle => &["s", "S"],
+            Action::Reshuffle => &["<Ctrl>s"],
+            Action::ZoomIn => &["plus", "equal", "KP_Add"],
+            Action::ZoomOut => &["minus", "KP_Subtract"],
+            Action::FitWidth => &["W"],
+            Action::FitHeight => &["V"],
+            Action::Favorite => &["plus", "equal", "KP_Add", "asterisk", "KP_Multiply"],
+            Action::FavoritesOnly => &["<Alt>f"],
+            Action::Tag => &["t"],
+            Action::Album => &["a"],
+            Action::Undo => &["u"],
+            Action::CopyTo => &["c"],
+            Action::MoveTo => &["m"],
+            Action::Rename => &["F2"],
+            Action::OpenExternal => &["e"],
+            Action::OpenLocation => &["P"],
+            Action::Reveal => &["E"],
+            Action::Bookmark => &["b"],
+            Action::Delete => &["Delete"],
+            Action::MoveItemLater => &["J"],
+            Action::MoveItemEarlier => &["K"],
+            Action::RotateCcw => &["bracketleft"],
+            Action::RotateCw => &["bracketright"],
+            Action::Slideshow => &["p"],
+            Action::InfoPanel => &["i"],
+            Action::SaveRotation => &["w"],
+            Action::CopyImage => &["y", "<Ctrl>c"],
+            Action::Previous => &["Left", "h", "N"],
+            Action::Next => &["Right", "l", "n"],
+        }
+    }
+}
+
+/// A key plus the Ctrl/Alt/Super modifiers that must be held with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    key: BoundKey,
+    modifiers: ModifierType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BoundKey {
+    /// A GDK key name such as `Home` or `h`
+    Named(Key),
+    /// A character without a key name of its own, e.g. `%`
+    Char(char),
+}
+
+impl KeyBinding {
+    /// Parses `h`, `Home`, `%`, or `<Ctrl>c` style names.
+    pub fn parse(name: &str) -> Option<KeyBinding> {
+        let mut rest = name.trim();
+        let mut modifiers = ModifierType::empty();
+        while let Some(stripped) = rest.strip_prefix('<') {
+            let (modifier, tail) = stripped.split_once('>')?;
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => ModifierType::CONTROL_MASK,
+                "alt" => ModifierType::ALT_MASK,
+                "super" => ModifierType::SUPER_MASK,
+                _ => return None,
+            };
+            rest = tail;
+        }
+        let key = match Key::from_name(rest).filter(|key| *key != Key::VoidSymbol) {
+            Some(key) => BoundKey::Named(key),
+            None => {
+                let mut chars = rest.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => BoundKey::Char(c),
+                    _ => return None,
+                }
+            }
+        };
+        Some(KeyBinding { key, modifiers })
+    }
+
+    fn matches(&self, keyval: Key, state: ModifierType) -> bool {
+        let key_matches = match self.key {
+            BoundKey::Named(key) => key == keyval,
+            BoundKey::Char(c) => keyval.to_unicode() == Some(c),
+        };
+        key_matches && state & BINDING_MODIFIERS == self.modifiers
+    }
+}
+
+/// Keys bound to each action.
+#[derive(Debug, Clone)]
+pub struct KeyMap {
+    bindings: Vec<Vec<KeyBinding>>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        let bindings = Action::ALL
+            .iter()
+            .map(|action| {
+                action
+                    .default_keys()
+                    .iter()
+                    .filter_map(|name| KeyBinding::parse(name))
+                    .collect()
+            })
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl KeyMap {
+    /// Path of the user keymap file.
+    pub fn default_path() -> Result<PathBuf> {
+        let proj_dirs =
+            ProjectDirs::from("", "", "idxd").context("Failed to determine project directories")?;
+        Ok(proj_dirs.config_dir().join(KEYMAP_FILE))
+    }
+
+    /// Loads the user keymap, falling back to the defaults when the file is
+    /// missing or unreadable. Problems are logged, never fatal.
+    pub fn load() -> Self {
+        let path = match Self::default_path() {
+            Ok(path) => path,
+            Err(err) => {
+                tracing::warn!("Using default keys: {:#}", err);
+                return Self::default();
+            }
+        };
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+            Err(err) => {
+                tracing::warn!(
+                    "Using default keys, cannot read {}: {}",
+                    path.display(),
+                    err
+                );
+                return Self::default();
+            }
+        };
+        match Self::from_json(&text) {
+            Ok((keymap, warnings)) => {
+                for warning in warnings {
+                    tracing::warn!("{}: {}", path.display(), warning);
+                }
+                tracing::info!("Loaded keymap from {}", path.display());
+                keymap
+            }
+            Err(err) => {
+                tracing::warn!(
+                    "Using default keys, {} is invalid: {:#}",
+                    path.display(),
+                    err
+                );
+                Self::default()
+            }
+        }
+    }
+
+    /// Applies a JSON object of `action: key | [keys]` overrides to the
+    /// defaults. Returns the keymap plus a warning for every entry skipped.
+    pub fn from_json(text: &str) -> Result<(Self, Vec<String>)> {
+        let overrides: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(text).context("Expected a JSON object of action names")?;
+        let mut keymap = Self::default();
+        let mut warnings = Vec::new();
+        for (name, value) in overrides {
+            let Some(action) = Action::from_name(&name) else {
+                warnings.push(format!("unknown action \"{}\"", name));
+                continue;
+            };
+            let names: Vec<&str> = match &value {
+                serde_json::Value::String(key) => vec![key.as_str()],
+                serde_json::Value::Array(keys) => keys.iter().filter_map(|k| k.as_str()).collect(),
+                _ => {
+                    warnings.push(format!("\"{}\" needs a key name or a list of them", name));
+                    continue;
+                }
+            };
+            let mut bindings = Vec::new();
+            for key in &names {
+                match KeyBinding::parse(key) {
+                    Some(binding) => bindings.push(binding),
+                    None => warnings.push(format!("unknown key \"{}\" for \"{}\"", key, name)),
+                }
+            }
+            if bindings.is_empty() && !names.is_empty() {
+                warnings.push(format!("keeping the default keys for \"{}\"", name));
+                continue;
+            }
+            keymap.bindings[action as usize] = bindings;
+        }
+        Ok((keymap, warnings))
+    }
+
+    /// Whether `keyval` with `state` triggers `action`.
+    pub fn matches(&self, action: Action, keyval: Key, state: ModifierType) -> bool {
+        self.bindings[action as usize]
+            .iter()
+            .any(|binding| binding.matches(keyval, state))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn action_names_round_trip() {
+        for action in Action::ALL {
+            assert_eq!(Action::from_name(action.name()), Some(action));
+        }
+        assert_eq!(Action::ALL.len(), Action::Next as usize + 1);
+    }
+
+    #[test]
+    fn overrides_replace_defaults_and_skip_bad_entries() {
+        let (keymap, warnings) = KeyMap::from_json(
+            r#"{ "move_left": ["n", "Left"], "delete": "<Ctrl>x", "fly": "q", "tag": ["nope"] }"#,
+        )
+        .unwrap();
+        let none = ModifierType::empty();
+        assert!(keymap.matches(Action::MoveLeft, Key::n, none));
+        assert!(!keymap.matches(Action::MoveLeft, Key::h, none));
+        assert!(keymap.matches(Action::Delete, Key::x, ModifierType::CONTROL_MASK));
+        assert!(!keymap.matches(Action::Delete, Key::Delete, none));
+        assert!(keymap.matches(Action::Tag, Key::t, none));
+        assert!(keymap.matches(Action::Percent, Key::percent, ModifierType::SHIFT_MASK));
+        assert_eq!(warnings.len(), 3);
+        assert!(KeyMap::from_json("[]").is_err());
+    }
+}
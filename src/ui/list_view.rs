@@ -9,12 +9,29 @@ use gtk4::{
     SignalListItemFactory, Widget,
 };
 use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::rc::Rc;
 
-use super::row_widget::RowWidget;
+use super::row_widget::{MarkGesture, RowWidget, TileMembership};
 use crate::models::RowModel;
 
+/// Rows overlapping the adjustment's page, given each row's top offset
+/// plus the total height as the last entry.
+fn rows_in_view(adj: &gtk4::Adjustment, count: u32, offsets: &[f64]) -> Option<(u32, u32)> {
+    if count == 0 || offsets.len() < (count as usize + 1) {
+        return None;
+    }
+    let find_row = |y: f64| -> u32 {
+        let idx = offsets.partition_point(|off| *off <= y);
+        idx.saturating_sub(1).min(count as usize - 1) as u32
+    };
+    Some((
+        find_row(adj.value()),
+        find_row(adj.value() + adj.page_size()),
+    ))
+}
+
 // GObject wrapper for RowModel to use in ListStore
 mod row_model_object {
     use super::*;
@@ -66,6 +83,9 @@ mod row_model_object {
 
 pub use row_model_object::RowModelObject;
 
+/// Callback for ctrl/shift clicks on a tile
+type ItemMarkedCallback = Box<dyn Fn(u32, u32, PathBuf, MarkGesture)>;
+
 /// MediaListView wraps a GTK ListView with virtualization support
 /// for displaying rows of media items
 pub struct MediaListView {
@@ -75,9 +95,16 @@ pub struct MediaListView {
     // Track visible range for thumbnail loading optimization
     visible_range: Rc<RefCell<(u32, u32)>>,
     selection: Rc<RefCell<(u32, u32)>>,
+    /// Paths in the multi-selection (ctrl/shift click, visual mode)
+    marked: Rc<RefCell<HashSet<PathBuf>>>,
+    /// Star ratings shown as tile badges
+    ratings: Rc<RefCell<HashMap<PathBuf, u8>>>,
+    /// Favorite/album membership of the tiles annotated so far
+    memberships: Rc<RefCell<HashMap<PathBuf, TileMembership>>>,
     row_widgets: Rc<RefCell<Vec<glib::WeakRef<RowWidget>>>>,
     row_offsets: Rc<RefCell<Vec<f64>>>,
     on_item_activated: Rc<RefCell<Option<Box<dyn Fn(u32, u32, PathBuf)>>>>,
+    on_item_marked: Rc<RefCell<Option<ItemMarkedCallback>>>,
     on_item_context_menu:
         Rc<RefCell<Option<Box<dyn Fn(u32, u32, PathBuf, Widget, gdk::Rectangle)>>>>,
 }
@@ -97,6 +124,8 @@ impl MediaListView {
         let on_item_activated: Rc<RefCell<Option<Box<dyn Fn(u32, u32, PathBuf)>>>> =
             Rc::new(RefCell::new(None));
         let on_item_activated_setup = on_item_activated.clone();
+        let on_item_marked: Rc<RefCell<Option<ItemMarkedCallback>>> = Rc::new(RefCell::new(None));
+        let on_item_marked_setup = on_item_marked.clone();
         let on_item_context_menu: Rc<
             RefCell<Option<Box<dyn Fn(u32, u32, PathBuf, Widget, gdk::Rectangle)>>>,
         > = Rc::new(RefCell::new(None));
@@ -106,6 +135,13 @@ impl MediaListView {
         let row_widgets_setup = row_widgets.clone();
         let selection: Rc<RefCell<(u32, u32)>> = Rc::new(RefCell::new((0, 0)));
         let selection_bind = selection.clone();
+        let marked: Rc<RefCell<HashSet<PathBuf>>> = Rc::new(RefCell::new(HashSet::new()));
+        let marked_bind = marked.clone();
+        let ratings: Rc<RefCell<HashMap<PathBuf, u8>>> = Rc::new(RefCell::new(HashMap::new()));
+        let ratings_bind = ratings.clone();
+        let memberships: Rc<RefCell<HashMap<PathBuf, TileMembership>>> =
+            Rc::new(RefCell::new(HashMap::new()));
+        let memberships_bind = memberships.clone();
 
         factory.connect_setup(move |_factory, list_item| {
             let list_item = list_item
@@ -115,11 +151,17 @@ impl MediaListView {
             row_widgets_setup.borrow_mut().push(row_widget.downgrade());
             let on_item_activated = on_item_activated_setup.clone();
             let on_item_context_menu = on_item_context_menu_setup.clone();
+            let on_item_marked = on_item_marked_setup.clone();
             row_widget.connect_item_activated(move |row, col, path| {
                 if let Some(ref callback) = *on_item_activated.borrow() {
                     callback(row, col, path);
                 }
             });
+            row_widget.connect_item_marked(move |row, col, path, gesture| {
+                if let Some(ref callback) = *on_item_marked.borrow() {
+                    callback(row, col, path, gesture);
+                }
+            });
             row_widget.connect_item_context_menu(move |row, col, path, widget, rect| {
                 if let Some(ref callback) = *on_item_context_menu.borrow() {
                     callback(row, col, path, widget, rect);
@@ -149,6 +191,9 @@ impl MediaListView {
             }
             let (row, col) = *selection_bind.borrow();
             row_widget.update_selection(row, col);
+            row_widget.update_marked(&marked_bind.borrow());
+            row_widget.update_ratings(&ratings_bind.borrow());
+            row_widget.update_memberships(&memberships_bind.borrow());
         });
 
         // Unbind: clean up when data is unbound
@@ -200,9 +245,13 @@ impl MediaListView {
             model,
             visible_range,
             selection,
+            marked,
+            ratings,
+            memberships,
             row_widgets,
             row_offsets,
             on_item_activated,
+            on_item_marked,
             on_item_context_menu,
         }
     }
@@ -279,6 +328,88 @@ impl MediaListView {
         });
     }
 
+    /// Replace the multi-selection and refresh its highlight.
+    pub fn set_marked(&self, paths: HashSet<PathBuf>) {
+        *self.marked.borrow_mut() = paths;
+        let marked = self.marked.borrow();
+        let mut widgets = self.row_widgets.borrow_mut();
+        widgets.retain(|weak| {
+            if let Some(widget) = weak.upgrade() {
+                widget.update_marked(&marked);
+                true
+            } else {
+                false
+            }
+        });
+    }
+
+    /// Replace the ratings shown on tiles.
+    pub fn set_ratings(&self, ratings: HashMap<PathBuf, u8>) {
+        *self.ratings.borrow_mut() = ratings;
+        self.refresh_ratings();
+    }
+
+    /// Set or clear (`None`) the rating of some paths without a relayout.
+    pub fn update_ratings(&self, paths: &[PathBuf], rating: Option<u8>) {
+        {
+            let mut ratings = self.ratings.borrow_mut();
+            for path in paths {
+                match rating {
+                    Some(rating) => ratings.insert(path.clone(), rating),
+                    None => ratings.remove(path),
+                };
+            }
+        }
+        self.refresh_ratings();
+    }
+
+    fn refresh_ratings(&self) {
+        let ratings = self.ratings.borrow();
+        let mut widgets = self.row_widgets.borrow_mut();
+        widgets.retain(|weak| {
+            if let Some(widget) = weak.upgrade() {
+                widget.update_ratings(&ratings);
+                true
+            } else {
+                false
+            }
+        });
+    }
+
+    /// Record the membership of `paths` (missing entries mean neither
+    /// favorite nor in an album) and redraw the badges of their tiles in
+    /// place. Paths not passed keep what they were last annotated with.
+    pub fn annotate_memberships(
+        &self,
+        paths: &[PathBuf],
+        memberships: &HashMap<PathBuf, TileMembership>,
+    ) {
+        {
+            let mut known = self.memberships.borrow_mut();
+            for path in paths {
+                match memberships.get(path) {
+                    Some(&membership) => known.insert(path.clone(), membership),
+                    None => known.remove(path),
+                };
+            }
+        }
+        let memberships = self.memberships.borrow();
+        let mut widgets = self.row_widgets.borrow_mut();
+        widgets.retain(|weak| {
+            if let Some(widget) = weak.upgrade() {
+                widget.update_memberships(&memberships);
+                true
+            } else {
+                false
+            }
+        });
+    }
+
+    /// Paths currently in the multi-selection, in no particular order.
+    pub fn marked(&self) -> HashSet<PathBuf> {
+        self.marked.borrow().clone()
+    }
+
     /// Get the number of rows
     pub fn row_count(&self) -> u32 {
         self.model.n_items()
@@ -309,6 +440,16 @@ impl MediaListView {
         vadj.set_value(clamped);
     }
 
+    /// First and last row inside the viewport, or `None` while there are
+    /// no rows or their offsets are stale.
+    pub fn rows_in_view(&self) -> Option<(u32, u32)> {
+        rows_in_view(
+            &self.scrolled_window.vadjustment(),
+            self.model.n_items(),
+            &self.row_offsets.borrow(),
+        )
+    }
+
     /// Set up a callback for when visible range changes
     pub fn connect_visible_range_changed<F>(&self, callback: F)
     where
@@ -320,22 +461,11 @@ impl MediaListView {
 
         let vadj = self.scrolled_window.vadjustment();
         vadj.connect_value_changed(move |adj| {
-            let value = adj.value();
-            let page_size = adj.page_size();
-            let count = model.n_items();
-            if count == 0 {
+            let Some((first_visible, last_visible)) =
+                rows_in_view(adj, model.n_items(), &row_offsets.borrow())
+            else {
                 return;
-            }
-            let offsets = row_offsets.borrow();
-            if offsets.len() < (count as usize + 1) {
-                return;
-            }
-            let find_row = |y: f64| -> u32 {
-                let idx = offsets.partition_point(|off| *off <= y);
-                idx.saturating_sub(1).min(count as usize - 1) as u32
             };
-            let first_visible = find_row(value);
-            let last_visible = find_row(value + page_size);
 
             let mut range = visible_range.borrow_mut();
             if *range != (first_visible, last_visible) {
@@ -364,6 +494,13 @@ impl MediaListView {
         *self.on_item_activated.borrow_mut() = Some(Box::new(callback));
     }
 
+    pub fn connect_item_marked<F>(&self, callback: F)
+    where
+        F: Fn(u32, u32, PathBuf, MarkGesture) + 'static,
+    {
+        *self.on_item_marked.borrow_mut() = Some(Box::new(callback));
+    }
+
     pub fn connect_item_context_menu<F>(&self, callback: F)
     where
         F: Fn(u32, u32, PathBuf, Widget, gdk::Rectangle) + 'static,
@@ -1,4 +1,6 @@
+pub mod histogram;
 pub mod keybindings;
+pub mod keymap;
 pub mod list_view;
 pub mod row_widget;
 pub mod viewer;
@@ -23,7 +23,11 @@ use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::OnceLock;
 use std::time::Duration;
 
-use crate::models::RowModel;
+use crate::models::{is_video_path, RowModel};
+use crate::thumbnails::generator::{ThumbnailGenerator, VideoFrameTimeout};
+use crate::thumbnails::queue::{
+    priority_channel, Priority, PriorityReceiver, PrioritySender, ThumbnailProgress,
+};
 
 const ROW_PREVIEW_SIZE: u32 = 512;
 const ROW_LOADER_MAX_THREADS: usize = 8;
@@ -32,6 +36,10 @@ const ROW_LOADER_QUEUE: usize = 512;
 const ROW_CACHE_ENTRIES: usize = 1024;
 const ROW_RESULTS_PER_TICK: usize = 12;
 const VIDEO_PREVIEW_START_SECS: [f64; 2] = [1.0, 0.0];
+/// Width BlurHash placeholders are decoded at; GTK smooths the upscale.
+const BLURHASH_TEXTURE_WIDTH: u32 = 32;
+/// Tile style while its thumbnail is queued or being decoded.
+const PENDING_CLASS: &str = "thumb-pending";
 
 fn row_loader_threads() -> usize {
     std::thread::available_parallelism()
@@ -168,6 +176,10 @@ fn folder_texture() -> &'static Texture {
 struct RowDecodeRequest {
     path: PathBuf,
     generation: u64,
+    /// Widget scale factor, selecting the 1x or 2x cached thumbnail.
+    scale_factor: i32,
+    /// Up-to-date disk thumbnail to decode instead of the original.
+    thumbnail: Option<PathBuf>,
 }
 
 #[derive(Debug)]
@@ -176,6 +188,8 @@ struct RowDecodeResult {
     rgba: Option<Vec<u8>>,
     width: u32,
     height: u32,
+    blurhash: Option<String>,
+    phash: Option<u64>,
 }
 
 #[derive(Clone)]
@@ -189,13 +203,33 @@ struct RowLoaderState {
     pending_paths: HashSet<PathBuf>,
     waiters: HashMap<PathBuf, Vec<RowWaiter>>,
     cache: lru::LruCache<PathBuf, Texture>,
+    progress: ThumbnailProgress,
+    /// Known BlurHash per path, painted until the preview decode lands.
+    placeholders: HashMap<PathBuf, String>,
+    /// Hashes computed by the decoders that have not been persisted yet.
+    new_placeholders: Vec<(PathBuf, String)>,
+    /// Paths whose perceptual hash was already handed out this session.
+    phashed: HashSet<PathBuf>,
+    /// Perceptual hashes computed by the decoders that have not been persisted yet.
+    new_phashes: Vec<(PathBuf, u64)>,
+    /// Rows currently on screen; their decodes use the visible lane.
+    visible_rows: (u32, u32),
+    /// Current disk thumbnails (1x and 2x) and their heights per path, from
+    /// the database.
+    thumbnails: HashMap<PathBuf, Vec<(PathBuf, u32)>>,
+    /// Thumbnail chosen for each pending decode, reused when it is promoted.
+    pending_thumbnails: HashMap<PathBuf, PathBuf>,
 }
 
 struct RowImageLoader {
-    request_tx: flume::Sender<RowDecodeRequest>,
-    request_rx: flume::Receiver<RowDecodeRequest>,
+    request_tx: PrioritySender<RowDecodeRequest>,
+    request_rx: PriorityReceiver<RowDecodeRequest>,
     result_rx: flume::Receiver<RowDecodeResult>,
     generation: std::sync::Arc<AtomicU64>,
+    /// Paths sent to the decoders but not yet picked up. A promoted path sits
+    /// in both lanes; whichever copy is taken first removes it, so the other
+    /// is skipped.
+    queued: std::sync::Arc<parking_lot::Mutex<HashSet<PathBuf>>>,
     state: RefCell<RowLoaderState>,
 }
 
@@ -203,33 +237,52 @@ static NEXT_LOAD_TOKEN: AtomicU64 = AtomicU64::new(1);
 
 thread_local! {
     static ROW_IMAGE_LOADER: Rc<RowImageLoader> = RowImageLoader::new();
+    /// Date/camera captions by path; empty while the overlay is turned off.
+    static CAPTURE_CAPTIONS: RefCell<HashMap<PathBuf, String>> = RefCell::new(HashMap::new());
 }
 
 impl RowImageLoader {
     fn new() -> Rc<Self> {
-        let (request_tx, request_rx) = flume::bounded::<RowDecodeRequest>(ROW_LOADER_QUEUE);
+        let (request_tx, request_rx) = priority_channel::<RowDecodeRequest>(ROW_LOADER_QUEUE);
         let (result_tx, result_rx) = flume::unbounded::<RowDecodeResult>();
         let generation = std::sync::Arc::new(AtomicU64::new(1));
+        let queued = std::sync::Arc::new(parking_lot::Mutex::new(HashSet::new()));
 
         for _ in 0..row_loader_threads() {
-            let rx = request_rx.clone();
+            let mut rx = request_rx.clone();
             let tx = result_tx.clone();
             let generation = generation.clone();
+            let queued = queued.clone();
             std::thread::spawn(move || {
                 while let Ok(req) = rx.recv() {
                     if req.generation != generation.load(Ordering::Acquire) {
                         continue;
                     }
-                    let decoded = decode_row_preview(&req.path);
-                    let (rgba, width, height) = match decoded {
-                        Some((data, w, h)) => (Some(data), w, h),
-                        None => (None, 0, 0),
+                    if !queued.lock().remove(&req.path) {
+                        continue;
+                    }
+                    let decoded = req
+                        .thumbnail
+                        .as_deref()
+                        .and_then(decode_thumbnail_preview)
+                        .or_else(|| decode_row_preview(&req.path, req.scale_factor));
+                    let (rgba, width, height, blurhash, phash) = match decoded {
+                        Some(preview) => (
+                            Some(preview.rgba),
+                            preview.width,
+                            preview.height,
+                            preview.blurhash,
+                            preview.phash,
+                        ),
+                        None => (None, 0, 0, None, None),
                     };
                     let _ = tx.send(RowDecodeResult {
                         path: req.path,
                         rgba,
                         width,
                         height,
+                        blurhash,
+                        phash,
                     });
                 }
             });
@@ -240,10 +293,19 @@ impl RowImageLoader {
             request_rx,
             result_rx,
             generation,
+            queued,
             state: RefCell::new(RowLoaderState {
                 pending_paths: HashSet::new(),
                 waiters: HashMap::new(),
                 cache: lru::LruCache::new(NonZeroUsize::new(ROW_CACHE_ENTRIES).unwrap()),
+                progress: ThumbnailProgress::default(),
+                placeholders: HashMap::new(),
+                new_placeholders: Vec::new(),
+                phashed: HashSet::new(),
+                new_phashes: Vec::new(),
+                visible_rows: (0, 0),
+                thumbnails: HashMap::new(),
+                pending_thumbnails: HashMap::new(),
             }),
         });
 
@@ -260,7 +322,17 @@ impl RowImageLoader {
         loader
     }
 
-    fn request(&self, row_widget: &RowWidget, index: usize, path: &Path, token: u64) {
+    /// Asks for the preview of `path` for a tile `min_height` device pixels
+    /// tall; returns false if it could not be queued, so nothing will arrive
+    /// for this waiter.
+    fn request(
+        &self,
+        row_widget: &RowWidget,
+        index: usize,
+        path: &Path,
+        token: u64,
+        min_height: u32,
+    ) -> bool {
         let mut state = self.state.borrow_mut();
 
         if let Some(texture) = state.cache.get(path).cloned() {
@@ -271,7 +343,7 @@ impl RowImageLoader {
                     row_widget.apply_async_texture(index, token, &path, Some(&texture));
                 }
             });
-            return;
+            return true;
         }
 
         state
@@ -284,33 +356,129 @@ impl RowImageLoader {
                 token,
             });
 
+        if state.pending_paths.is_empty() {
+            state.progress = ThumbnailProgress::default();
+        }
         if state.pending_paths.insert(path.to_path_buf()) {
-            let generation = self.generation.load(Ordering::Acquire);
-            if self
-                .request_tx
-                .try_send(RowDecodeRequest {
-                    path: path.to_path_buf(),
-                    generation,
-                })
-                .is_err()
-            {
+            let (first, last) = state.visible_rows;
+            let row = row_widget.imp().row_index.get();
+            let priority = if (first..=last).contains(&row) {
+                Priority::Visible
+            } else {
+                Priority::Prefetch
+            };
+            // The smallest cached thumbnail tall enough for the tile skips
+            // decoding the original
+            let thumbnail = state.thumbnails.get(path).and_then(|variants| {
+                variants
+                    .iter()
+                    .filter(|(_, height)| *height >= min_height)
+                    .min_by_key(|(_, height)| *height)
+                    .map(|(thumb_path, _)| thumb_path.clone())
+            });
+            if self.send(path, row_widget.scale_factor(), priority, thumbnail.clone()) {
+                state.progress.total += 1;
+                if let Some(thumbnail) = thumbnail {
+                    state
+                        .pending_thumbnails
+                        .insert(path.to_path_buf(), thumbnail);
+                }
+            } else {
                 state.pending_paths.remove(path);
                 state.waiters.remove(path);
+                return false;
             }
         }
+        true
+    }
+
+    fn send(
+        &self,
+        path: &Path,
+        scale_factor: i32,
+        priority: Priority,
+        thumbnail: Option<PathBuf>,
+    ) -> bool {
+        let fresh = self.queued.lock().insert(path.to_path_buf());
+        let sent = self
+            .request_tx
+            .try_send(
+                RowDecodeRequest {
+                    path: path.to_path_buf(),
+                    generation: self.generation.load(Ordering::Acquire),
+                    scale_factor,
+                    thumbnail,
+                },
+                priority,
+            )
+            .is_ok();
+        if !sent && fresh {
+            self.queued.lock().remove(path);
+        }
+        sent
+    }
+
+    /// Re-send queued decodes on the visible lane; the stale copy is skipped.
+    fn promote(&self, path: &Path, scale_factor: i32, thumbnail: Option<PathBuf>) {
+        if self.queued.lock().contains(path) {
+            self.send(path, scale_factor, Priority::Visible, thumbnail);
+        }
+    }
+
+    fn set_visible_rows(&self, first: u32, last: u32) {
+        let mut state = self.state.borrow_mut();
+        state.visible_rows = (first, last);
+        for (path, waiters) in &state.waiters {
+            let visible = waiters.iter().find_map(|waiter| {
+                let widget = waiter.widget.upgrade()?;
+                (first..=last)
+                    .contains(&widget.imp().row_index.get())
+                    .then(|| widget.scale_factor())
+            });
+            if let Some(scale_factor) = visible {
+                let thumbnail = state.pending_thumbnails.get(path).cloned();
+                self.promote(path, scale_factor, thumbnail);
+            }
+        }
+    }
+
+    fn prioritize(&self, path: &Path) {
+        let state = self.state.borrow();
+        let scale_factor = state
+            .waiters
+            .get(path)
+            .and_then(|waiters| waiters.iter().find_map(|w| w.widget.upgrade()))
+            .map(|widget| widget.scale_factor());
+        if let Some(scale_factor) = scale_factor {
+            let thumbnail = state.pending_thumbnails.get(path).cloned();
+            self.promote(path, scale_factor, thumbnail);
+        }
     }
 
     fn cached_texture(&self, path: &Path) -> Option<Texture> {
         self.state.borrow_mut().cache.get(path).cloned()
     }
 
+    fn placeholder_for(&self, path: &Path, display_w: f32, display_h: f32) -> Option<Texture> {
+        let state = self.state.borrow();
+        let hash = state.placeholders.get(path)?;
+        let width = BLURHASH_TEXTURE_WIDTH;
+        let height =
+            ((width as f32 * display_h / display_w.max(1.0)).round() as u32).clamp(1, width * 4);
+        let rgba = ThumbnailGenerator::decode_blurhash(hash, width, height)?;
+        create_texture_from_rgba(rgba, width, height)
+    }
+
     fn reschedule(&self) {
         self.generation.fetch_add(1, Ordering::AcqRel);
         let mut state = self.state.borrow_mut();
         state.pending_paths.clear();
+        state.pending_thumbnails.clear();
         state.waiters.clear();
+        state.progress = ThumbnailProgress::default();
         drop(state);
-        while self.request_rx.try_recv().is_ok() {}
+        self.queued.lock().clear();
+        while self.request_rx.try_recv().is_some() {}
     }
 
     fn process_results(&self) {
@@ -324,7 +492,21 @@ impl RowImageLoader {
 
             let waiters = {
                 let mut state = self.state.borrow_mut();
-                state.pending_paths.remove(&result.path);
+                if state.pending_paths.remove(&result.path) {
+                    state.progress.completed += 1;
+                }
+                state.pending_thumbnails.remove(&result.path);
+                if let Some(hash) = result.blurhash {
+                    if !state.placeholders.contains_key(&result.path) {
+                        state.placeholders.insert(result.path.clone(), hash.clone());
+                        state.new_placeholders.push((result.path.clone(), hash));
+                    }
+                }
+                if let Some(phash) = result.phash {
+                    if state.phashed.insert(result.path.clone()) {
+                        state.new_phashes.push((result.path.clone(), phash));
+                    }
+                }
                 if let Some(ref texture) = texture {
                     state.cache.put(result.path.clone(), texture.clone());
                 }
@@ -349,16 +531,152 @@ pub fn reschedule_row_previews() {
     ROW_IMAGE_LOADER.with(|loader| loader.reschedule());
 }
 
+/// Completed/total preview decodes since the loader last went idle.
+pub fn row_preview_progress() -> ThumbnailProgress {
+    ROW_IMAGE_LOADER.with(|loader| loader.state.borrow().progress)
+}
+
 pub fn cached_row_preview_texture(path: &Path) -> Option<Texture> {
     ROW_IMAGE_LOADER.with(|loader| loader.cached_texture(path))
 }
 
-fn decode_row_preview(path: &Path) -> Option<(Vec<u8>, u32, u32)> {
+/// Drop the decoded preview and BlurHash of a file whose pixels changed.
+pub fn forget_row_preview(path: &Path) {
+    ROW_IMAGE_LOADER.with(|loader| {
+        let mut state = loader.state.borrow_mut();
+        state.cache.pop(path);
+        state.placeholders.remove(path);
+        state.thumbnails.remove(path);
+        state.phashed.remove(path);
+    });
+}
+
+/// Tell the loader which rows are on screen so their decodes jump the queue.
+pub fn set_row_preview_visible_range(first: u32, last: u32) {
+    ROW_IMAGE_LOADER.with(|loader| loader.set_visible_rows(first, last));
+}
+
+/// Move a still-queued preview decode (e.g. the selected item) to the visible lane.
+pub fn prioritize_row_preview(path: &Path) {
+    ROW_IMAGE_LOADER.with(|loader| loader.prioritize(path));
+}
+
+/// Register up-to-date disk thumbnails (path, thumbnail, thumbnail height)
+/// for the current items, one entry per variant; tiles they are tall enough
+/// for load the smallest such variant instead of decoding the original.
+pub fn set_row_thumbnails(thumbnails: impl IntoIterator<Item = (PathBuf, PathBuf, u32)>) {
+    let mut by_path: HashMap<PathBuf, Vec<(PathBuf, u32)>> = HashMap::new();
+    for (path, thumb_path, height) in thumbnails {
+        by_path.entry(path).or_default().push((thumb_path, height));
+    }
+    ROW_IMAGE_LOADER.with(|loader| loader.state.borrow_mut().thumbnails.extend(by_path));
+}
+
+/// Register BlurHash placeholders already stored for the current items.
+pub fn set_row_placeholder_hashes(hashes: impl IntoIterator<Item = (PathBuf, String)>) {
+    ROW_IMAGE_LOADER.with(|loader| loader.state.borrow_mut().placeholders.extend(hashes));
+}
+
+/// Drain BlurHashes computed since the last call so they can be persisted.
+pub fn take_new_row_placeholder_hashes() -> Vec<(PathBuf, String)> {
+    ROW_IMAGE_LOADER.with(|loader| std::mem::take(&mut loader.state.borrow_mut().new_placeholders))
+}
+
+/// Replace the date/camera captions drawn on tiles; rows pick them up when
+/// next bound.
+pub fn set_row_capture_captions(captions: HashMap<PathBuf, String>) {
+    CAPTURE_CAPTIONS.with(|cell| *cell.borrow_mut() = captions);
+}
+
+/// Formats a tile caption as `2024-05-01 | X-T4`, dropping whichever half is
+/// missing. The time of day is left out to keep it short.
+pub fn capture_caption(date_taken: Option<&str>, camera: Option<&str>) -> Option<String> {
+    let day = date_taken.map(|date| date.split(' ').next().unwrap_or(date));
+    match (day, camera) {
+        (Some(day), Some(camera)) => Some(format!("{} | {}", day, camera)),
+        (Some(part), None) | (None, Some(part)) => Some(part.to_string()),
+        (None, None) => None,
+    }
+}
+
+/// Renders a 1-5 star rating as `[***--]`.
+pub fn rating_stars(rating: u8) -> String {
+    let stars = rating.min(5) as usize;
+    format!("[{}{}]", "*".repeat(stars), "-".repeat(5 - stars))
+}
+
+/// Album dots drawn on a tile before the rest are cut to `…`.
+const MAX_ALBUM_DOTS: usize = 4;
+
+/// Whether a tile's file is a favorite and how many albums it is in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TileMembership {
+    pub favorite: bool,
+    pub albums: usize,
+}
+
+/// Renders a tile's membership badge: `+` for a favorite and a dot per
+/// album, e.g. `+ ••`. `None` when the file is in neither.
+pub fn membership_badge(membership: TileMembership) -> Option<String> {
+    let mut badge = String::new();
+    if membership.favorite {
+        badge.push('+');
+    }
+    if membership.albums > 0 {
+        if !badge.is_empty() {
+            badge.push(' ');
+        }
+        badge.push_str(&"•".repeat(membership.albums.min(MAX_ALBUM_DOTS)));
+        if membership.albums > MAX_ALBUM_DOTS {
+            badge.push('…');
+        }
+    }
+    (!badge.is_empty()).then_some(badge)
+}
+
+/// Drain perceptual hashes computed since the last call so they can be persisted.
+pub fn take_new_row_phashes() -> Vec<(PathBuf, u64)> {
+    ROW_IMAGE_LOADER.with(|loader| std::mem::take(&mut loader.state.borrow_mut().new_phashes))
+}
+
+/// A decoded grid preview plus the hashes derived from it.
+struct RowPreview {
+    rgba: Vec<u8>,
+    width: u32,
+    height: u32,
+    blurhash: Option<String>,
+    phash: Option<u64>,
+}
+
+/// Preview from a cached disk thumbnail. Decoded here rather than handed to
+/// GDK so the BlurHash and perceptual hash still get computed.
+fn decode_thumbnail_preview(thumb_path: &Path) -> Option<RowPreview> {
+    match image::open(thumb_path) {
+        Ok(img) => Some(row_preview_from_image(img)),
+        Err(err) => {
+            tracing::debug!(
+                path = %thumb_path.display(),
+                error = %err,
+                "Cached thumbnail unreadable, decoding the original"
+            );
+            None
+        }
+    }
+}
+
+fn decode_row_preview(path: &Path, scale_factor: i32) -> Option<RowPreview> {
     let img = if is_video_path(path) {
-        decode_video_preview(path)?
+        match decode_video_preview(path, scale_factor)? {
+            VideoPreview::Frame(img) => img,
+            VideoPreview::TimedOut => return Some(video_placeholder_preview()),
+        }
     } else {
         crate::image_loader::open_image(path).ok()?
     };
+    Some(row_preview_from_image(img))
+}
+
+fn row_preview_from_image(img: image::DynamicImage) -> RowPreview {
     let (src_w, src_h) = img.dimensions();
     let resized = if src_w <= ROW_PREVIEW_SIZE && src_h <= ROW_PREVIEW_SIZE {
         img
@@ -372,27 +690,62 @@ fn decode_row_preview(path: &Path) -> Option<(Vec<u8>, u32, u32)> {
         img.resize_exact(new_w, new_h, FilterType::Triangle)
     };
     let (width, height) = resized.dimensions();
+    let blurhash = ThumbnailGenerator::compute_blurhash(&resized);
+    let phash = ThumbnailGenerator::compute_phash(&resized);
     let rgba = resized.to_rgba8().into_raw();
-    Some((rgba, width.max(1), height.max(1)))
+    RowPreview {
+        rgba,
+        width: width.max(1),
+        height: height.max(1),
+        blurhash,
+        phash: Some(phash),
+    }
 }
 
-fn is_video_path(path: &Path) -> bool {
-    path.extension()
-        .and_then(|e| e.to_str())
-        .map(|e| {
-            matches!(
-                e.to_ascii_lowercase().as_str(),
-                "mp4" | "webm" | "mkv" | "avi" | "mov"
-            )
-        })
-        .unwrap_or(false)
+enum VideoPreview {
+    Frame(image::DynamicImage),
+    /// ffmpeg hung on the file; mpv would most likely hang too.
+    TimedOut,
 }
 
-fn decode_video_preview(path: &Path) -> Option<image::DynamicImage> {
+/// Flat 16:9 tile for a video whose frame extraction timed out. It carries
+/// no hashes, so nothing derived from it is persisted.
+fn video_placeholder_preview() -> RowPreview {
+    let (width, height) = (320, 180);
+    RowPreview {
+        rgba: [0x30, 0x30, 0x30, 0xff].repeat((width * height) as usize),
+        width,
+        height,
+        blurhash: None,
+        phash: None,
+    }
+}
+
+fn decode_video_preview(path: &Path, scale_factor: i32) -> Option<VideoPreview> {
+    // Reuse (or create) the persistent ffmpeg thumbnail before spawning mpv.
+    // On HiDPI displays the 2x variant keeps GTK from upscaling a 256px frame.
+    match crate::thumbnails::cache::ensure_video_thumbnail(path, scale_factor) {
+        Ok(thumb_path) => {
+            if let Ok(img) = image::open(&thumb_path) {
+                return Some(VideoPreview::Frame(img));
+            }
+        }
+        Err(err) if err.downcast_ref::<VideoFrameTimeout>().is_some() => {
+            tracing::warn!(path = %path.display(), error = %err, "Video thumbnail timed out");
+            return Some(VideoPreview::TimedOut);
+        }
+        Err(err) => tracing::debug!(
+            path = %path.display(),
+            error = %err,
+            "Cached video thumbnail unavailable, falling back to mpv"
+        ),
+    }
+
     VIDEO_PREVIEW_START_SECS
         .iter()
         .copied()
         .find_map(|start_seconds| mpv_extract_frame(path, start_seconds))
+        .map(VideoPreview::Frame)
 }
 
 struct VideoPreviewDir {
@@ -598,7 +951,7 @@ fn run_gst_thumbnail_command_impl(
     }
 }
 
-fn create_texture_from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Option<Texture> {
+pub fn create_texture_from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Option<Texture> {
     if width == 0 || height == 0 {
         return None;
     }
@@ -619,10 +972,21 @@ fn create_texture_from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Option<Te
     Some(texture.upcast())
 }
 
+/// How a modified click changes the multi-selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkGesture {
+    /// Ctrl-click: add or remove a single item.
+    Toggle,
+    /// Shift-click: select everything from the anchor to this item.
+    Range,
+}
+
 // GObject subclass for RowWidget
 mod imp {
     use super::*;
 
+    pub type ItemMarkedHandler = Rc<dyn Fn(u32, u32, PathBuf, MarkGesture)>;
+
     /// Wrapper for a single item slot (either a plain Picture or an Overlay with folder name)
     pub struct ItemSlot {
         pub widget: gtk4::Widget,
@@ -630,6 +994,9 @@ mod imp {
         pub overlay: Option<Overlay>,
         pub label: Option<Label>,
         pub video_badge: Option<Label>,
+        pub rating_badge: Option<Label>,
+        pub membership_badge: Option<Label>,
+        pub capture_label: Option<Label>,
     }
 
     #[derive(Default)]
@@ -641,6 +1008,7 @@ mod imp {
         pub item_is_folder: RefCell<Vec<bool>>,
         pub row_index: Cell<u32>,
         pub on_item_activated: RefCell<Option<Rc<dyn Fn(u32, u32, PathBuf)>>>,
+        pub on_item_marked: RefCell<Option<ItemMarkedHandler>>,
         pub on_item_context_menu:
             RefCell<Option<Rc<dyn Fn(u32, u32, PathBuf, Widget, gdk::Rectangle)>>>,
     }
@@ -716,6 +1084,7 @@ impl RowWidget {
         is_folder_vec.clear();
         paths.extend(items.iter().map(|item| item.media_path.clone()));
         is_folder_vec.extend(items.iter().map(|item| item.is_folder));
+        self.set_spacing(row_model.gutter_px.round() as i32);
 
         // Update item dimensions and content
         for (i, item) in items.iter().enumerate() {
@@ -724,11 +1093,23 @@ impl RowWidget {
             let height = (item.display_h.round() as i32).max(1);
 
             slot.widget.set_size_request(width, height);
+            slot.widget.set_margin_top(item.offset_top.round() as i32);
             slot.picture.set_size_request(width, height);
+            if let Some(ref badge) = slot.rating_badge {
+                // Keep the stars clear of the caption strip
+                let (halign, valign) = if row_model.show_captions {
+                    (Align::End, Align::Start)
+                } else {
+                    (Align::Start, Align::End)
+                };
+                badge.set_halign(halign);
+                badge.set_valign(valign);
+            }
 
             if item.is_folder {
                 // Display folder with icon and name
                 slot.picture.set_paintable(Some(folder_texture()));
+                slot.widget.remove_css_class(PENDING_CLASS);
                 load_tokens[i] = 0;
 
                 // Update or show the label with folder name
@@ -739,25 +1120,66 @@ impl RowWidget {
                         .and_then(|n| n.to_str())
                         .unwrap_or("[folder]");
                     label.set_text(folder_name);
+                    label.remove_css_class("file-caption");
+                    label.set_margin_bottom(8);
                     label.set_visible(true);
                 }
                 if let Some(ref badge) = slot.video_badge {
                     badge.set_visible(false);
                 }
+                if let Some(ref label) = slot.capture_label {
+                    label.set_visible(false);
+                }
             } else {
-                // Hide label for non-folder items
+                // Files only get a name label when tiles are small
                 if let Some(ref label) = slot.label {
-                    label.set_visible(false);
+                    if row_model.show_captions {
+                        let file_name = item
+                            .media_path
+                            .file_name()
+                            .map(|n| n.to_string_lossy())
+                            .unwrap_or_default();
+                        label.set_text(&file_name);
+                        label.add_css_class("file-caption");
+                        label.set_margin_bottom(2);
+                        label.set_visible(true);
+                    } else {
+                        label.set_visible(false);
+                    }
                 }
                 if let Some(ref badge) = slot.video_badge {
                     badge.set_visible(is_video_path(&item.media_path));
                 }
+                if let Some(ref label) = slot.capture_label {
+                    // File name captions take over on small tiles
+                    let caption = CAPTURE_CAPTIONS
+                        .with(|cell| cell.borrow().get(&item.media_path).cloned())
+                        .filter(|_| !row_model.show_captions);
+                    label.set_visible(caption.is_some());
+                    label.set_text(caption.as_deref().unwrap_or(""));
+                }
 
-                slot.picture.set_paintable(Some(placeholder_texture()));
                 let token = NEXT_LOAD_TOKEN.fetch_add(1, Ordering::Relaxed);
                 load_tokens[i] = token;
                 ROW_IMAGE_LOADER.with(|loader| {
-                    loader.request(self, i, &item.media_path, token);
+                    // Paint a decoded preview or its BlurHash right away; the
+                    // request below swaps in the real thumbnail when ready.
+                    let cached = loader.cached_texture(&item.media_path);
+                    let initial = cached.clone().or_else(|| {
+                        loader.placeholder_for(&item.media_path, item.display_w, item.display_h)
+                    });
+                    match initial {
+                        Some(texture) => slot.picture.set_paintable(Some(&texture)),
+                        None => slot.picture.set_paintable(Some(placeholder_texture())),
+                    }
+                    let min_height = (item.display_h * self.scale_factor() as f32).ceil() as u32;
+                    let queued = loader.request(self, i, &item.media_path, token, min_height);
+                    // Pulse the tile until its thumbnail lands
+                    if cached.is_none() && queued {
+                        slot.widget.add_css_class(PENDING_CLASS);
+                    } else {
+                        slot.widget.remove_css_class(PENDING_CLASS);
+                    }
                 });
             }
         }
@@ -773,12 +1195,23 @@ impl RowWidget {
             slot.picture.set_paintable(Some(placeholder_texture()));
             slot.widget.set_visible(false);
             slot.widget.remove_css_class("selected");
+            slot.widget.remove_css_class("marked");
+            slot.widget.remove_css_class(PENDING_CLASS);
             if let Some(ref label) = slot.label {
                 label.set_visible(false);
             }
             if let Some(ref badge) = slot.video_badge {
                 badge.set_visible(false);
             }
+            if let Some(ref badge) = slot.rating_badge {
+                badge.set_visible(false);
+            }
+            if let Some(ref badge) = slot.membership_badge {
+                badge.set_visible(false);
+            }
+            if let Some(ref label) = slot.capture_label {
+                label.set_visible(false);
+            }
         }
         for token in imp.load_tokens.borrow_mut().iter_mut() {
             *token = 0;
@@ -803,9 +1236,6 @@ impl RowWidget {
         expected_path: &Path,
         texture: Option<&Texture>,
     ) {
-        if texture.is_none() {
-            return;
-        }
         let imp = self.imp();
         let tokens = imp.load_tokens.borrow();
         if tokens.get(index).copied() != Some(token) {
@@ -816,7 +1246,12 @@ impl RowWidget {
             return;
         }
         let slots = imp.slots.borrow();
-        if let (Some(slot), Some(texture)) = (slots.get(index), texture) {
+        let Some(slot) = slots.get(index) else {
+            return;
+        };
+        // A failed decode ends the wait too; the placeholder stays
+        slot.widget.remove_css_class(PENDING_CLASS);
+        if let Some(texture) = texture {
             slot.picture.set_paintable(Some(texture));
         }
     }
@@ -882,6 +1317,78 @@ impl RowWidget {
         *self.imp().on_item_context_menu.borrow_mut() = Some(Rc::new(callback));
     }
 
+    /// Connect callback for ctrl/shift clicks that change the multi-selection
+    pub fn connect_item_marked<F>(&self, callback: F)
+    where
+        F: Fn(u32, u32, PathBuf, MarkGesture) + 'static,
+    {
+        *self.imp().on_item_marked.borrow_mut() = Some(Rc::new(callback));
+    }
+
+    /// Highlight the items whose paths are in the multi-selection
+    pub fn update_marked(&self, marked: &HashSet<PathBuf>) {
+        let imp = self.imp();
+        let slots = imp.slots.borrow();
+        let paths = imp.item_paths.borrow();
+        for (i, slot) in slots.iter().enumerate() {
+            let is_marked =
+                slot.widget.is_visible() && paths.get(i).is_some_and(|p| marked.contains(p));
+            if is_marked {
+                slot.widget.add_css_class("marked");
+            } else {
+                slot.widget.remove_css_class("marked");
+            }
+        }
+    }
+
+    /// Show each tile's star rating; unrated tiles hide the badge.
+    pub fn update_ratings(&self, ratings: &HashMap<PathBuf, u8>) {
+        let imp = self.imp();
+        let slots = imp.slots.borrow();
+        let paths = imp.item_paths.borrow();
+        for (i, slot) in slots.iter().enumerate() {
+            let Some(ref badge) = slot.rating_badge else {
+                continue;
+            };
+            let rating = paths
+                .get(i)
+                .filter(|_| slot.widget.is_visible())
+                .and_then(|path| ratings.get(path));
+            match rating {
+                Some(&rating) => {
+                    badge.set_text(&rating_stars(rating));
+                    badge.set_visible(true);
+                }
+                None => badge.set_visible(false),
+            }
+        }
+    }
+
+    /// Draw each tile's favorite/album badge in its bottom-right corner;
+    /// tiles in neither hide it.
+    pub fn update_memberships(&self, memberships: &HashMap<PathBuf, TileMembership>) {
+        let imp = self.imp();
+        let slots = imp.slots.borrow();
+        let paths = imp.item_paths.borrow();
+        for (i, slot) in slots.iter().enumerate() {
+            let Some(ref badge) = slot.membership_badge else {
+                continue;
+            };
+            let text = paths
+                .get(i)
+                .filter(|_| slot.widget.is_visible())
+                .and_then(|path| memberships.get(path))
+                .and_then(|&membership| membership_badge(membership));
+            match text {
+                Some(text) => {
+                    badge.set_text(&text);
+                    badge.set_visible(true);
+                }
+                None => badge.set_visible(false),
+            }
+        }
+    }
+
     fn create_item_slot(&self, index: u32) -> imp::ItemSlot {
         let picture = Picture::new();
         // Allow the widget to shrink to the allocated size; otherwise large
@@ -917,12 +1424,51 @@ impl RowWidget {
         video_badge.set_visible(false);
         overlay.add_overlay(&video_badge);
 
+        let rating_badge = Label::new(None);
+        rating_badge.set_halign(Align::Start);
+        rating_badge.set_valign(Align::End);
+        rating_badge.set_margin_start(6);
+        rating_badge.set_margin_end(6);
+        rating_badge.set_margin_top(4);
+        rating_badge.set_margin_bottom(4);
+        rating_badge.add_css_class("rating-badge");
+        rating_badge.set_visible(false);
+        overlay.add_overlay(&rating_badge);
+
+        let membership_badge = Label::new(None);
+        membership_badge.set_halign(Align::End);
+        membership_badge.set_valign(Align::End);
+        membership_badge.set_margin_end(6);
+        membership_badge.set_margin_bottom(4);
+        membership_badge.add_css_class("membership-badge");
+        membership_badge.set_visible(false);
+        overlay.add_overlay(&membership_badge);
+
+        // Capture date + camera, top right so it clears the [V] badge
+        let capture_label = Label::new(None);
+        capture_label.set_halign(Align::End);
+        capture_label.set_valign(Align::Start);
+        capture_label.set_margin_start(32);
+        capture_label.set_margin_end(6);
+        capture_label.set_margin_top(4);
+        capture_label.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+        capture_label.add_css_class("capture-caption");
+        capture_label.set_visible(false);
+        overlay.add_overlay(&capture_label);
+
         // Add click handler to the overlay
         let row_widget = self.clone();
         let click = GestureClick::new();
         click.set_button(1);
-        click.connect_pressed(move |_, _n, _x, _y| {
-            row_widget.emit_item_activated(index);
+        click.connect_pressed(move |gesture, _n, _x, _y| {
+            let state = gesture.current_event_state();
+            if state.contains(gdk::ModifierType::SHIFT_MASK) {
+                row_widget.emit_item_marked(index, MarkGesture::Range);
+            } else if state.contains(gdk::ModifierType::CONTROL_MASK) {
+                row_widget.emit_item_marked(index, MarkGesture::Toggle);
+            } else {
+                row_widget.emit_item_activated(index);
+            }
         });
         overlay.add_controller(click);
 
@@ -943,6 +1489,9 @@ impl RowWidget {
             overlay: Some(overlay),
             label: Some(label),
             video_badge: Some(video_badge),
+            rating_badge: Some(rating_badge),
+            membership_badge: Some(membership_badge),
+            capture_label: Some(capture_label),
         }
     }
 
@@ -956,6 +1505,16 @@ impl RowWidget {
         }
     }
 
+    fn emit_item_marked(&self, index: u32, gesture: MarkGesture) {
+        let imp = self.imp();
+        let row = imp.row_index.get();
+        if let Some(path) = imp.item_paths.borrow().get(index as usize).cloned() {
+            if let Some(ref callback) = *imp.on_item_marked.borrow() {
+                callback(row, index, path, gesture);
+            }
+        }
+    }
+
     fn emit_item_context_menu(&self, index: u32, anchor: &Widget, rect: gdk::Rectangle) {
         let imp = self.imp();
         let row = imp.row_index.get();
@@ -1031,4 +1590,33 @@ mod tests {
         // let texture = placeholder_texture();
         // assert!(texture.width() > 0);
     }
+
+    #[test]
+    fn test_rating_stars() {
+        assert_eq!(rating_stars(3), "[***--]");
+        assert_eq!(rating_stars(9), "[*****]");
+    }
+
+    #[test]
+    fn test_membership_badge() {
+        let badge = |favorite, albums| membership_badge(TileMembership { favorite, albums });
+        assert_eq!(badge(false, 0), None);
+        assert_eq!(badge(true, 0).as_deref(), Some("+"));
+        assert_eq!(badge(false, 2).as_deref(), Some("••"));
+        assert_eq!(badge(true, 6).as_deref(), Some("+ ••••…"));
+    }
+
+    #[test]
+    fn test_capture_caption() {
+        assert_eq!(
+            capture_caption(Some("2024-05-01 12:34:56"), Some("X-T4")).as_deref(),
+            Some("2024-05-01 | X-T4")
+        );
+        assert_eq!(
+            capture_caption(Some("2024-05-01"), None).as_deref(),
+            Some("2024-05-01")
+        );
+        assert_eq!(capture_caption(None, Some("X-T4")).as_deref(), Some("X-T4"));
+        assert_eq!(capture_caption(None, None), None);
+    }
 }
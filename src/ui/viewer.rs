@@ -1,16 +1,22 @@
 // Image viewer overlay for idxd media browser
 // Features:
 // - Fast preview using thumbnail, then background-load full resolution
+// - Animated GIF/WebP playback with the same zoom/pan as stills
 // - Zoom/pan with smooth scaling (GestureZoom, GestureDrag)
 // - Overlay mode covering the main grid view
 // - Terminal aesthetic: no rounded corners, no shadows, outlined buttons
 
-use gdk4::{MemoryFormat, MemoryTexture, Rectangle, Texture};
+use super::histogram::Histogram;
+use super::row_widget::rating_stars;
+use crate::image_loader::AnimationFrame;
+use crate::media_info::{read_media_info, MediaInfo};
+use crate::models::is_video_path;
+use gdk4::{ContentProvider, MemoryFormat, MemoryTexture, Rectangle, Texture};
 use gtk4::gdk::Key;
 use gtk4::prelude::*;
 use gtk4::subclass::prelude::*;
 use gtk4::{
-    glib, Align, Box as GtkBox, Button, EventControllerKey, EventControllerMotion,
+    glib, Align, Box as GtkBox, Button, DrawingArea, EventControllerKey, EventControllerMotion,
     EventControllerScroll, EventControllerScrollFlags, Fixed, GestureClick, GestureDrag,
     GestureZoom, GraphicsOffloadEnabled, Label, MediaFile, MediaStream, Orientation, Overlay,
     Picture, Scale, Stack, StackTransitionType, Video, Widget, Window,
@@ -44,7 +50,35 @@ const VIEWPORT_DECODE_SCALE: f64 = 1.5;
 const VIEWPORT_DECODE_FALLBACK: u32 = 2048;
 /// Idle delay before promoting to full-resolution decode.
 const FULL_DECODE_IDLE_DELAY_MS: u64 = 140;
-const DEFAULT_PREFETCH_MB: usize = 256;
+/// Budget of the decoded image cache that prefetching fills.
+pub const DEFAULT_PREFETCH_MB: usize = 256;
+/// Full-resolution decodes above this many pixels are halved until they fit
+/// (64 MP is 256 MB as RGBA).
+const DEFAULT_FULL_DECODE_MAX_PIXELS: u64 = 64_000_000;
+const MIN_FULL_DECODE_MAX_PIXELS: u64 = 4_000_000;
+pub const MIN_PREFETCH_MB: usize = 32;
+pub const MAX_PREFETCH_MB: usize = 64 * 1024;
+/// Longest side an SVG is rasterized at when zooming in.
+const SVG_MAX_RENDER_SIZE: u32 = 8192;
+/// Seconds each item stays on screen during a slideshow.
+const DEFAULT_SLIDESHOW_SECS: u32 = 5;
+/// How often a running slideshow checks whether the current video has ended.
+const SLIDESHOW_VIDEO_POLL_MS: u64 = 500;
+/// Number of parsed metadata entries kept for the info panel.
+const INFO_CACHE_CAPACITY: usize = 512;
+/// Number of computed histograms kept so toggling the overlay is instant.
+const HISTOGRAM_CACHE_CAPACITY: usize = 64;
+/// Longer side of the minimap overview in pixels.
+const MINIMAP_SIZE: f64 = 160.0;
+/// Share of pan velocity kept per 60 Hz frame after a drag is released;
+/// `IDXD_PAN_FRICTION` overrides it and 0 turns inertia off.
+const DEFAULT_PAN_FRICTION: f64 = 0.92;
+/// Inertial panning stops below this speed, in pixels per second.
+const INERTIA_MIN_SPEED: f64 = 30.0;
+/// A drag that rests this long before release does not fling.
+const INERTIA_RELEASE_WINDOW_US: i64 = 60_000;
+/// Ctrl+drag rectangles smaller than this (px) are treated as a slip.
+const MIN_ZOOM_BAND: f64 = 8.0;
 
 fn video_offload_enabled() -> bool {
     std::env::var("IDXD_VIDEO_OFFLOAD")
@@ -58,25 +92,34 @@ fn video_offload_enabled() -> bool {
         .unwrap_or(false)
 }
 
-fn prefetch_cache_bytes() -> usize {
-    std::env::var("IDXD_PREFETCH_MB")
+/// Pixel budget of a full-resolution decode, from `IDXD_FULL_DECODE_MP`
+/// (megapixels).
+fn full_decode_max_pixels() -> u64 {
+    std::env::var("IDXD_FULL_DECODE_MP")
         .ok()
-        .and_then(|v| v.parse::<usize>().ok())
-        .filter(|v| *v > 0)
-        .map(|mb| mb * 1024 * 1024)
-        .unwrap_or(DEFAULT_PREFETCH_MB * 1024 * 1024)
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(|mp| mp.saturating_mul(1_000_000).max(MIN_FULL_DECODE_MAX_PIXELS))
+        .unwrap_or(DEFAULT_FULL_DECODE_MAX_PIXELS)
 }
 
-fn is_video_path(path: &Path) -> bool {
-    path.extension()
-        .and_then(|e| e.to_str())
-        .map(|e| {
-            matches!(
-                e.to_ascii_lowercase().as_str(),
-                "mp4" | "webm" | "mkv" | "avi" | "mov"
-            )
-        })
-        .unwrap_or(false)
+fn pan_friction() -> f64 {
+    std::env::var("IDXD_PAN_FRICTION")
+        .ok()
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .filter(|v| v.is_finite())
+        .map(|v| v.clamp(0.0, 0.99))
+        .unwrap_or(DEFAULT_PAN_FRICTION)
+}
+
+/// What an automatic fit matches to the viewport.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FitMode {
+    /// The whole image
+    Window,
+    /// The image width; taller images pan vertically
+    Width,
+    /// The image height; wider images pan horizontally
+    Height,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -100,6 +143,8 @@ struct FullDecodeRequest {
     path: PathBuf,
     generation: u64,
     rotation_steps: u8,
+    /// Longer side to rasterize vector images at; rasters decode at full size.
+    max_size: u32,
 }
 
 /// Result of background image loading - must be Send for cross-thread transfer
@@ -132,7 +177,46 @@ pub(super) struct CachedTexture {
     kind: PrefetchKind,
 }
 
+/// Frames of the animated GIF/WebP on screen, advanced by a main-loop timer.
+pub(super) struct AnimationPlayback {
+    frames: Vec<(AnimationTexture, std::time::Duration)>,
+    index: usize,
+    playing: bool,
+}
+
+/// A frame's decoded pixels until it is first shown, then its texture, so
+/// starting playback uploads one frame rather than all of them.
+enum AnimationTexture {
+    Pending(image::RgbaImage),
+    Uploaded(Texture),
+}
+
+impl AnimationPlayback {
+    /// Texture of the current frame, uploading its pixels on first use.
+    fn current_texture(&mut self) -> Option<Texture> {
+        let (frame, _) = &mut self.frames[self.index];
+        if let AnimationTexture::Pending(image) = frame {
+            let (width, height) = image.dimensions();
+            let texture = MediaViewer::create_texture_from_rgba(image.as_raw(), width, height)?;
+            *frame = AnimationTexture::Uploaded(texture);
+        }
+        match frame {
+            AnimationTexture::Uploaded(texture) => Some(texture.clone()),
+            AnimationTexture::Pending(_) => None,
+        }
+    }
+}
+
 type ToggleFavoriteCallback = Rc<dyn Fn()>;
+/// Asks the owner to write the viewer rotation (clockwise quarter turns) to the file.
+type SaveRotationCallback = Rc<dyn Fn(PathBuf, u8)>;
+/// Asks the owner to show the item `step` positions away, wrapping at the ends
+/// when the flag is set; returns false when there is none.
+type AdvanceCallback = Rc<dyn Fn(i32, bool) -> bool>;
+/// Reports a new video volume (0.0-1.0) and mute state so the owner can keep it.
+type VolumeChangedCallback = Rc<dyn Fn(f64, bool)>;
+/// Reports whether videos now loop so the owner can keep the preference.
+type LoopChangedCallback = Rc<dyn Fn(bool)>;
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 struct ViewerCacheKey {
@@ -182,7 +266,15 @@ impl TextureCache {
             self.bytes = self.bytes.saturating_sub(existing.bytes);
         }
         self.bytes = self.bytes.saturating_add(entry.bytes);
+        self.evict_to_budget();
+    }
+
+    fn set_max_bytes(&mut self, max_bytes: usize) {
+        self.max_bytes = max_bytes;
+        self.evict_to_budget();
+    }
 
+    fn evict_to_budget(&mut self) {
         while self.bytes > self.max_bytes {
             if let Some((_key, evicted)) = self.entries.pop_lru() {
                 self.bytes = self.bytes.saturating_sub(evicted.bytes);
@@ -192,6 +284,16 @@ impl TextureCache {
         }
     }
 
+    /// Drop every rotation of `path`, e.g. after the file was rewritten.
+    fn remove_path(&mut self, path: &Path) {
+        for rotation_steps in 0..4 {
+            let key = ViewerCacheKey::new(path, rotation_steps);
+            if let Some(evicted) = self.entries.pop(&key) {
+                self.bytes = self.bytes.saturating_sub(evicted.bytes);
+            }
+        }
+    }
+
     fn contains(&mut self, path: &Path, rotation_steps: u8) -> bool {
         let key = ViewerCacheKey::new(path, rotation_steps);
         self.entries.get(&key).is_some()
@@ -238,9 +340,24 @@ mod imp {
         pub video_play_btn: RefCell<Option<Button>>,
         pub video_seek_scale: RefCell<Option<Scale>>,
         pub video_seek_syncing: Cell<bool>,
+        // Volume and mute, carried from one video to the next
+        pub video_mute_btn: RefCell<Option<Button>>,
+        pub video_volume_scale: RefCell<Option<Scale>>,
+        pub video_volume_syncing: Cell<bool>,
+        pub video_volume: Cell<f64>,
+        pub video_muted: Cell<bool>,
+        pub on_volume_changed: RefCell<Option<VolumeChangedCallback>>,
+        // Whether videos restart at the end; applies to every video opened
+        pub video_loop_btn: RefCell<Option<Button>>,
+        pub video_loop: Cell<bool>,
+        pub on_loop_changed: RefCell<Option<LoopChangedCallback>>,
+        // Playing animation, if the current image has more than one frame
+        pub(super) animation: RefCell<Option<AnimationPlayback>>,
+        pub animation_timer: RefCell<Option<glib::SourceId>>,
         pub favorite_btn: RefCell<Option<Button>>,
         pub favorite_indicator: RefCell<Option<Label>>,
         pub is_favorite: Cell<bool>,
+        pub rating_indicator: RefCell<Option<Label>>,
         // Info label
         pub info_label: RefCell<Option<Label>>,
         // Zoom label
@@ -249,11 +366,53 @@ mod imp {
         pub is_loading: Cell<bool>,
         // Track if user interacted (zoom/pan) to avoid overriding scale
         pub user_interacted: Cell<bool>,
+        // Zoom chosen by the user, carried across next/previous; None means fit
+        pub zoom_preference: Cell<Option<f64>>,
+        // Fit re-applied on load and resize while the user has not zoomed
+        pub fit_mode: Cell<FitMode>,
+        // Last seen overlay size, to notice window resizes
+        pub viewport_size: Cell<(i32, i32)>,
+        // Compare mode: a reference image in a pane left of the main one,
+        // drawn with the same scale and pan
+        pub compare_fixed: RefCell<Option<Fixed>>,
+        pub compare_picture: RefCell<Option<Picture>>,
+        pub compare_path: RefCell<Option<PathBuf>>,
+        pub compare_width: Cell<u32>,
+        pub compare_height: Cell<u32>,
+        // Image marked with `C`, compared with the next one `C` is pressed on
+        pub compare_anchor: RefCell<Option<PathBuf>>,
         // Closure to call when viewer is closed
         pub on_close: RefCell<Option<Rc<dyn Fn()>>>,
         // Context menu callback
         pub on_context_menu: RefCell<Option<Rc<dyn Fn(PathBuf, Widget, Rectangle)>>>,
         pub on_toggle_favorite: RefCell<Option<ToggleFavoriteCallback>>,
+        pub on_save_rotation: RefCell<Option<SaveRotationCallback>>,
+        // Slideshow state
+        pub on_advance: RefCell<Option<AdvanceCallback>>,
+        pub slideshow_btn: RefCell<Option<Button>>,
+        pub slideshow_active: Cell<bool>,
+        pub slideshow_interval_secs: Cell<u32>,
+        pub slideshow_timer: RefCell<Option<glib::SourceId>>,
+        // File info side panel and its parsed metadata, keyed by path
+        pub info_panel: RefCell<Option<GtkBox>>,
+        pub info_panel_visible: Cell<bool>,
+        pub info_cache: RefCell<LruCache<PathBuf, MediaInfo>>,
+        // Histogram overlay, the histogram it draws, and computed histograms
+        pub histogram_area: RefCell<Option<DrawingArea>>,
+        pub histogram_visible: Cell<bool>,
+        pub histogram: RefCell<Option<Rc<Histogram>>>,
+        pub(super) histogram_cache: RefCell<LruCache<ViewerCacheKey, Rc<Histogram>>>,
+        // Overview of the whole image, shown while it is zoomed past the
+        // viewport; the visible part is (x, y, w, h) as fractions of the image
+        pub minimap: RefCell<Option<Overlay>>,
+        pub minimap_picture: RefCell<Option<Picture>>,
+        pub minimap_frame: RefCell<Option<DrawingArea>>,
+        pub minimap_view: Cell<Option<(f64, f64, f64, f64)>>,
+        // Ctrl+drag zoom rectangle: its start and current corners in overlay
+        // coordinates, and the layer it is drawn on
+        pub band_start: Cell<Option<(f64, f64)>>,
+        pub band_end: Cell<(f64, f64)>,
+        pub band_area: RefCell<Option<DrawingArea>>,
         // Loading generation counter (to ignore stale results)
         pub load_generation: Cell<u64>,
         pub load_generation_atomic: Arc<AtomicU64>,
@@ -271,6 +430,11 @@ mod imp {
         // Track pan at drag start
         pub drag_start_pan_x: Cell<f64>,
         pub drag_start_pan_y: Cell<f64>,
+        // Last drag offset and its monotonic time (us), and the smoothed
+        // drag velocity in px/s, for the fling after release
+        pub drag_last: Cell<(f64, f64, i64)>,
+        pub drag_velocity: Cell<(f64, f64)>,
+        pub inertia_tick: RefCell<Option<gtk4::TickCallbackId>>,
         // Fractional scroll accumulator for stable wheel/touchpad zoom stepping.
         pub scroll_accum: Cell<f64>,
         // Scale at pinch gesture start; GestureZoom scale is relative to this baseline.
@@ -307,16 +471,61 @@ mod imp {
                 video_play_btn: RefCell::new(None),
                 video_seek_scale: RefCell::new(None),
                 video_seek_syncing: Cell::new(false),
+                video_mute_btn: RefCell::new(None),
+                video_volume_scale: RefCell::new(None),
+                video_volume_syncing: Cell::new(false),
+                video_volume: Cell::new(1.0),
+                video_muted: Cell::new(false),
+                on_volume_changed: RefCell::new(None),
+                video_loop_btn: RefCell::new(None),
+                video_loop: Cell::new(false),
+                on_loop_changed: RefCell::new(None),
+                animation: RefCell::new(None),
+                animation_timer: RefCell::new(None),
                 favorite_btn: RefCell::new(None),
                 favorite_indicator: RefCell::new(None),
                 is_favorite: Cell::new(false),
+                rating_indicator: RefCell::new(None),
                 info_label: RefCell::new(None),
                 zoom_label: RefCell::new(None),
                 is_loading: Cell::new(false),
+                compare_fixed: RefCell::new(None),
+                compare_picture: RefCell::new(None),
+                compare_path: RefCell::new(None),
+                compare_width: Cell::new(0),
+                compare_height: Cell::new(0),
+                compare_anchor: RefCell::new(None),
                 user_interacted: Cell::new(false),
+                zoom_preference: Cell::new(None),
+                fit_mode: Cell::new(FitMode::Window),
+                viewport_size: Cell::new((0, 0)),
                 on_close: RefCell::new(None),
                 on_context_menu: RefCell::new(None),
                 on_toggle_favorite: RefCell::new(None),
+                on_save_rotation: RefCell::new(None),
+                on_advance: RefCell::new(None),
+                slideshow_btn: RefCell::new(None),
+                slideshow_active: Cell::new(false),
+                slideshow_interval_secs: Cell::new(DEFAULT_SLIDESHOW_SECS),
+                slideshow_timer: RefCell::new(None),
+                info_panel: RefCell::new(None),
+                info_panel_visible: Cell::new(false),
+                info_cache: RefCell::new(LruCache::new(
+                    NonZeroUsize::new(INFO_CACHE_CAPACITY).unwrap(),
+                )),
+                histogram_area: RefCell::new(None),
+                histogram_visible: Cell::new(false),
+                histogram: RefCell::new(None),
+                histogram_cache: RefCell::new(LruCache::new(
+                    NonZeroUsize::new(HISTOGRAM_CACHE_CAPACITY).unwrap(),
+                )),
+                minimap: RefCell::new(None),
+                minimap_picture: RefCell::new(None),
+                minimap_frame: RefCell::new(None),
+                minimap_view: Cell::new(None),
+                band_start: Cell::new(None),
+                band_end: Cell::new((0.0, 0.0)),
+                band_area: RefCell::new(None),
                 load_generation: Cell::new(0),
                 load_generation_atomic: Arc::new(AtomicU64::new(0)),
                 load_sender: RefCell::new(None),
@@ -325,11 +534,14 @@ mod imp {
                 prefetch_generation: Arc::new(AtomicU64::new(0)),
                 full_decode_request_tx: RefCell::new(None),
                 full_decode_idle_timer: RefCell::new(None),
-                preview_cache: RefCell::new(TextureCache::new(prefetch_cache_bytes())),
+                preview_cache: RefCell::new(TextureCache::new(DEFAULT_PREFETCH_MB * 1024 * 1024)),
                 pointer_x: Cell::new(0.0),
                 pointer_y: Cell::new(0.0),
                 drag_start_pan_x: Cell::new(0.0),
                 drag_start_pan_y: Cell::new(0.0),
+                drag_last: Cell::new((0.0, 0.0, 0)),
+                drag_velocity: Cell::new((0.0, 0.0)),
+                inertia_tick: RefCell::new(None),
                 scroll_accum: Cell::new(0.0),
                 pinch_start_scale: Cell::new(1.0),
                 last_req_w: Cell::new(-1),
@@ -417,9 +629,7 @@ impl MediaViewer {
                             decode_image_downscaled(&item.path, PREVIEW_SIZE, 0)
                                 .map(|(data, w, h, ow, oh)| (data, w, h, ow, oh))
                         }
-                        PrefetchKind::Full => {
-                            decode_image_full(&item.path, 0).map(|(data, w, h)| (data, w, h, w, h))
-                        }
+                        PrefetchKind::Full => decode_image_full(&item.path, 0),
                     };
 
                     if work.generation != generation.load(Ordering::Acquire) {
@@ -457,9 +667,12 @@ impl MediaViewer {
                         continue;
                     }
 
-                    if let Some((data, width, height)) =
+                    let decoded = if crate::svg::is_svg_path(&req.path) {
+                        decode_svg(&req.path, req.max_size, req.rotation_steps)
+                    } else {
                         decode_image_full(&req.path, req.rotation_steps)
-                    {
+                    };
+                    if let Some((data, width, height, orig_width, orig_height)) = decoded {
                         if req.generation != load_generation_guard.load(Ordering::Acquire) {
                             continue;
                         }
@@ -467,8 +680,8 @@ impl MediaViewer {
                             data,
                             width,
                             height,
-                            orig_width: width,
-                            orig_height: height,
+                            orig_width,
+                            orig_height,
                             is_preview: false,
                             cache_kind: PrefetchKind::Full,
                         };
@@ -488,6 +701,11 @@ impl MediaViewer {
             return;
         }
 
+        // Still decodes only carry the first frame of a playing animation.
+        if imp.animation.borrow().is_some() {
+            return;
+        }
+
         // Ignore stale preview if a full-res image has already been applied.
         if result.is_preview && !imp.is_loading.get() {
             return;
@@ -523,7 +741,9 @@ impl MediaViewer {
             }
 
             if !imp.user_interacted.get() {
-                self.fit_to_window();
+                self.apply_fit();
+            } else {
+                self.update_transform();
             }
 
             self.update_info_label(
@@ -569,6 +789,7 @@ impl MediaViewer {
         let fixed = Fixed::new();
         fixed.set_hexpand(true);
         fixed.set_vexpand(true);
+        fixed.set_overflow(gtk4::Overflow::Hidden);
 
         let content_stack = Stack::new();
         content_stack.set_hexpand(true);
@@ -606,7 +827,26 @@ impl MediaViewer {
 
         // Add picture to fixed at initial position (0,0)
         fixed.put(&picture, 0.0, 0.0);
-        content_stack.add_named(&fixed, Some("image"));
+
+        // Reference pane for compare mode, hidden until it has an image
+        let compare_picture = Picture::new();
+        compare_picture.set_can_shrink(true);
+        compare_picture.set_content_fit(gtk4::ContentFit::Fill);
+        compare_picture.add_css_class("viewer-image");
+        let compare_fixed = Fixed::new();
+        compare_fixed.set_hexpand(true);
+        compare_fixed.set_vexpand(true);
+        compare_fixed.set_overflow(gtk4::Overflow::Hidden);
+        compare_fixed.add_css_class("viewer-compare-pane");
+        compare_fixed.put(&compare_picture, 0.0, 0.0);
+        compare_fixed.set_visible(false);
+
+        // Equal widths, so both panes share one viewport geometry
+        let image_panes = GtkBox::new(Orientation::Horizontal, 0);
+        image_panes.set_homogeneous(true);
+        image_panes.append(&compare_fixed);
+        image_panes.append(&fixed);
+        content_stack.add_named(&image_panes, Some("image"));
         content_stack.add_named(&video_area, Some("video"));
         content_stack.set_visible_child_name("image");
 
@@ -644,10 +884,26 @@ impl MediaViewer {
         let actual_btn = Button::with_label("[1:1]");
         actual_btn.set_tooltip_text(Some("Actual size"));
 
+        // Write the current rotation into the file
+        let save_rotation_btn = Button::with_label("[SAVE ROT]");
+        save_rotation_btn.set_tooltip_text(Some("Save rotation to file (w)"));
+
         // Favourite toggle button
         let favorite_btn = Button::with_label("[FAV -]");
         favorite_btn.set_tooltip_text(Some("Toggle favourite"));
 
+        // Slideshow toggle button
+        let slideshow_btn = Button::with_label("[> SHOW]");
+        slideshow_btn.set_tooltip_text(Some("Start/pause slideshow (p)"));
+
+        // File info panel toggle
+        let info_btn = Button::with_label("[INFO]");
+        info_btn.set_tooltip_text(Some("Show file info (i)"));
+
+        // Histogram overlay toggle
+        let histogram_btn = Button::with_label("[HIST]");
+        histogram_btn.set_tooltip_text(Some("Show histogram (H)"));
+
         // Video controls
         let seek_back_btn = Button::with_label("[<< 5s]");
         seek_back_btn.set_tooltip_text(Some("Seek backward 5 seconds"));
@@ -663,6 +919,22 @@ impl MediaViewer {
         seek_scale.set_draw_value(false);
         seek_scale.set_sensitive(false);
         seek_scale.set_tooltip_text(Some("Seek within video"));
+        // Neither scale takes keyboard focus, so arrows and Space keep
+        // driving navigation and playback after a click
+        seek_scale.set_focusable(false);
+
+        let loop_btn = Button::with_label(loop_label(false));
+        loop_btn.set_tooltip_text(Some("Loop videos (L)"));
+
+        let mute_btn = Button::with_label("[VOL 100%]");
+        mute_btn.set_tooltip_text(Some("Mute/unmute (M)"));
+
+        let volume_scale = Scale::with_range(Orientation::Horizontal, 0.0, 1.0, 0.05);
+        volume_scale.set_draw_value(false);
+        volume_scale.set_size_request(90, -1);
+        volume_scale.set_value(1.0);
+        volume_scale.set_focusable(false);
+        volume_scale.set_tooltip_text(Some("Volume"));
 
         // Info label (filename, dimensions)
         let info_label = Label::new(None);
@@ -677,12 +949,17 @@ impl MediaViewer {
         image_controls.append(&zoom_in_btn);
         image_controls.append(&fit_btn);
         image_controls.append(&actual_btn);
+        image_controls.append(&save_rotation_btn);
+        image_controls.append(&histogram_btn);
 
         let video_controls = GtkBox::new(Orientation::Horizontal, 8);
         video_controls.append(&seek_back_btn);
         video_controls.append(&play_pause_btn);
         video_controls.append(&seek_scale);
         video_controls.append(&seek_fwd_btn);
+        video_controls.append(&loop_btn);
+        video_controls.append(&mute_btn);
+        video_controls.append(&volume_scale);
         video_controls.set_visible(false);
 
         // Add controls to bar
@@ -690,6 +967,8 @@ impl MediaViewer {
         controls.append(&gtk4::Separator::new(Orientation::Vertical));
         controls.append(&image_controls);
         controls.append(&favorite_btn);
+        controls.append(&slideshow_btn);
+        controls.append(&info_btn);
         controls.append(&video_controls);
         controls.append(&gtk4::Separator::new(Orientation::Vertical));
         controls.append(&info_label);
@@ -701,34 +980,172 @@ impl MediaViewer {
         favorite_indicator.set_margin_end(18);
         favorite_indicator.add_css_class("viewer-favorite-indicator");
 
+        // Sits left of the favourite marker; hidden for unrated files.
+        let rating_indicator = Label::new(None);
+        rating_indicator.set_halign(Align::End);
+        rating_indicator.set_valign(Align::Start);
+        rating_indicator.set_margin_top(14);
+        rating_indicator.set_margin_end(52);
+        rating_indicator.add_css_class("viewer-rating-indicator");
+        rating_indicator.set_visible(false);
+
+        // Floats over the content so it never shifts the picture transform.
+        let info_panel = GtkBox::new(Orientation::Vertical, 4);
+        info_panel.set_halign(Align::End);
+        info_panel.set_valign(Align::Start);
+        info_panel.set_margin_top(VIEWPORT_TOP_INSET as i32 + 8);
+        info_panel.set_margin_end(8);
+        info_panel.set_can_target(false);
+        info_panel.set_visible(false);
+        info_panel.add_css_class("viewer-info-panel");
+
+        let histogram_area = DrawingArea::new();
+        histogram_area.set_content_width(256);
+        histogram_area.set_content_height(100);
+        histogram_area.set_halign(Align::Start);
+        histogram_area.set_valign(Align::End);
+        histogram_area.set_margin_start(8);
+        histogram_area.set_margin_bottom(8);
+        histogram_area.set_can_target(false);
+        histogram_area.set_visible(false);
+        histogram_area.add_css_class("viewer-histogram");
+        let viewer_weak = self.downgrade();
+        histogram_area.set_draw_func(move |_, cr, width, height| {
+            if let Some(viewer) = viewer_weak.upgrade() {
+                if let Some(histogram) = viewer.imp().histogram.borrow().as_ref() {
+                    histogram.draw(cr, width as f64, height as f64);
+                }
+            }
+        });
+
+        // Minimap: the current texture scaled down, with the viewport drawn
+        // over it. Clicking or dragging on it recentres the view.
+        let minimap_picture = Picture::new();
+        minimap_picture.set_can_shrink(true);
+        minimap_picture.set_content_fit(gtk4::ContentFit::Fill);
+        let minimap_frame = DrawingArea::new();
+        let viewer_weak = self.downgrade();
+        minimap_frame.set_draw_func(move |_, cr, width, height| {
+            let Some(viewer) = viewer_weak.upgrade() else {
+                return;
+            };
+            let Some((x, y, w, h)) = viewer.imp().minimap_view.get() else {
+                return;
+            };
+            let (width, height) = (width as f64, height as f64);
+            let (x, y, w, h) = (x * width, y * height, w * width, h * height);
+            // Dim everything outside the visible part
+            cr.set_fill_rule(gtk4::cairo::FillRule::EvenOdd);
+            cr.rectangle(0.0, 0.0, width, height);
+            cr.rectangle(x, y, w, h);
+            cr.set_source_rgba(0.0, 0.0, 0.0, 0.45);
+            let _ = cr.fill();
+            cr.set_source_rgba(1.0, 1.0, 1.0, 0.9);
+            cr.set_line_width(1.0);
+            cr.rectangle(x + 0.5, y + 0.5, (w - 1.0).max(1.0), (h - 1.0).max(1.0));
+            let _ = cr.stroke();
+        });
+        let minimap = Overlay::new();
+        minimap.set_child(Some(&minimap_picture));
+        minimap.add_overlay(&minimap_frame);
+        minimap.set_halign(Align::End);
+        minimap.set_valign(Align::End);
+        minimap.set_margin_end(8);
+        minimap.set_margin_bottom(8);
+        minimap.set_visible(false);
+        minimap.add_css_class("viewer-minimap");
+        let minimap_drag = GestureDrag::new();
+        minimap_drag.set_button(1);
+        let viewer_weak = self.downgrade();
+        minimap_drag.connect_drag_begin(move |gesture, x, y| {
+            // Keep the overlay's pan gesture from also seeing this drag
+            gesture.set_state(gtk4::EventSequenceState::Claimed);
+            if let Some(viewer) = viewer_weak.upgrade() {
+                viewer.recenter_from_minimap(x, y);
+            }
+        });
+        let viewer_weak = self.downgrade();
+        minimap_drag.connect_drag_update(move |gesture, offset_x, offset_y| {
+            if let (Some(viewer), Some((x, y))) = (viewer_weak.upgrade(), gesture.start_point()) {
+                viewer.recenter_from_minimap(x + offset_x, y + offset_y);
+            }
+        });
+        minimap.add_controller(minimap_drag);
+
         // Set up overlay with stack as main child
         overlay.set_child(Some(&content_stack));
         overlay.add_overlay(&controls);
         overlay.add_overlay(&favorite_indicator);
+        overlay.add_overlay(&rating_indicator);
+        overlay.add_overlay(&info_panel);
+        overlay.add_overlay(&histogram_area);
+        overlay.add_overlay(&minimap);
+
+        let band_area = DrawingArea::new();
+        band_area.set_can_target(false);
+        let viewer_weak = self.downgrade();
+        band_area.set_draw_func(move |_, cr, _, _| {
+            let Some(viewer) = viewer_weak.upgrade() else {
+                return;
+            };
+            let imp = viewer.imp();
+            let Some((start_x, start_y)) = imp.band_start.get() else {
+                return;
+            };
+            let (end_x, end_y) = imp.band_end.get();
+            cr.rectangle(
+                start_x.min(end_x) + 0.5,
+                start_y.min(end_y) + 0.5,
+                (end_x - start_x).abs(),
+                (end_y - start_y).abs(),
+            );
+            cr.set_source_rgba(1.0, 1.0, 1.0, 0.12);
+            let _ = cr.fill_preserve();
+            cr.set_source_rgba(1.0, 1.0, 1.0, 0.9);
+            cr.set_line_width(1.0);
+            let _ = cr.stroke();
+        });
+        overlay.add_overlay(&band_area);
 
         // Store references
         *imp.overlay.borrow_mut() = Some(overlay.clone());
         *imp.content_stack.borrow_mut() = Some(content_stack.clone());
         *imp.fixed.borrow_mut() = Some(fixed.clone());
         *imp.picture.borrow_mut() = Some(picture.clone());
+        *imp.compare_fixed.borrow_mut() = Some(compare_fixed.clone());
+        *imp.compare_picture.borrow_mut() = Some(compare_picture);
         *imp.video_area.borrow_mut() = Some(video_area.clone());
         *imp.controls.borrow_mut() = Some(controls);
         *imp.image_controls.borrow_mut() = Some(image_controls.clone());
         *imp.video_controls.borrow_mut() = Some(video_controls.clone());
         *imp.video_play_btn.borrow_mut() = Some(play_pause_btn.clone());
         *imp.video_seek_scale.borrow_mut() = Some(seek_scale.clone());
+        *imp.video_mute_btn.borrow_mut() = Some(mute_btn.clone());
+        *imp.video_loop_btn.borrow_mut() = Some(loop_btn.clone());
+        *imp.video_volume_scale.borrow_mut() = Some(volume_scale.clone());
         *imp.favorite_btn.borrow_mut() = Some(favorite_btn.clone());
+        *imp.slideshow_btn.borrow_mut() = Some(slideshow_btn.clone());
+        *imp.info_panel.borrow_mut() = Some(info_panel);
+        *imp.histogram_area.borrow_mut() = Some(histogram_area);
+        *imp.minimap.borrow_mut() = Some(minimap);
+        *imp.minimap_picture.borrow_mut() = Some(minimap_picture);
+        *imp.minimap_frame.borrow_mut() = Some(minimap_frame);
+        *imp.band_area.borrow_mut() = Some(band_area);
         *imp.favorite_indicator.borrow_mut() = Some(favorite_indicator);
+        *imp.rating_indicator.borrow_mut() = Some(rating_indicator);
         *imp.info_label.borrow_mut() = Some(info_label.clone());
         *imp.zoom_label.borrow_mut() = Some(zoom_label.clone());
         imp.scale.set(1.0);
         self.set_favorite_state(false);
 
         // Set up gestures
-        self.setup_gestures(&picture, &overlay, &fixed);
+        self.setup_gestures(&overlay);
+        self.setup_pane_gestures(&fixed);
+        self.setup_pane_gestures(&compare_fixed);
 
         // Set up keyboard controls
         self.setup_keyboard(&overlay);
+        self.setup_resize_observer(&overlay);
 
         // Connect button signals
         let viewer_weak = self.downgrade();
@@ -766,6 +1183,13 @@ impl MediaViewer {
             }
         });
 
+        let viewer_weak = self.downgrade();
+        save_rotation_btn.connect_clicked(move |_| {
+            if let Some(viewer) = viewer_weak.upgrade() {
+                viewer.request_save_rotation();
+            }
+        });
+
         let viewer_weak = self.downgrade();
         favorite_btn.connect_clicked(move |_| {
             if let Some(viewer) = viewer_weak.upgrade() {
@@ -773,6 +1197,27 @@ impl MediaViewer {
             }
         });
 
+        let viewer_weak = self.downgrade();
+        slideshow_btn.connect_clicked(move |_| {
+            if let Some(viewer) = viewer_weak.upgrade() {
+                viewer.toggle_slideshow();
+            }
+        });
+
+        let viewer_weak = self.downgrade();
+        info_btn.connect_clicked(move |_| {
+            if let Some(viewer) = viewer_weak.upgrade() {
+                viewer.toggle_info_panel();
+            }
+        });
+
+        let viewer_weak = self.downgrade();
+        histogram_btn.connect_clicked(move |_| {
+            if let Some(viewer) = viewer_weak.upgrade() {
+                viewer.toggle_histogram();
+            }
+        });
+
         let viewer_weak = self.downgrade();
         seek_back_btn.connect_clicked(move |_| {
             if let Some(viewer) = viewer_weak.upgrade() {
@@ -794,6 +1239,29 @@ impl MediaViewer {
             }
         });
 
+        let viewer_weak = self.downgrade();
+        loop_btn.connect_clicked(move |_| {
+            if let Some(viewer) = viewer_weak.upgrade() {
+                viewer.toggle_loop();
+            }
+        });
+
+        let viewer_weak = self.downgrade();
+        mute_btn.connect_clicked(move |_| {
+            if let Some(viewer) = viewer_weak.upgrade() {
+                viewer.toggle_mute();
+            }
+        });
+
+        let viewer_weak = self.downgrade();
+        volume_scale.connect_value_changed(move |scale| {
+            if let Some(viewer) = viewer_weak.upgrade() {
+                if !viewer.imp().video_volume_syncing.get() {
+                    viewer.set_volume(scale.value());
+                }
+            }
+        });
+
         let viewer_weak = self.downgrade();
         seek_scale.connect_value_changed(move |scale| {
             let Some(viewer) = viewer_weak.upgrade() else {
@@ -821,27 +1289,7 @@ impl MediaViewer {
     }
 
     /// Set up zoom and drag gestures
-    fn setup_gestures(&self, _picture: &Picture, overlay: &Overlay, fixed: &Fixed) {
-        // Track pointer position on the fixed container (same coord space as picture positioning)
-        let motion_controller = EventControllerMotion::new();
-        let viewer_weak = self.downgrade();
-        motion_controller.connect_motion(move |_, x, y| {
-            if let Some(viewer) = viewer_weak.upgrade() {
-                let imp = viewer.imp();
-                imp.pointer_x.set(x);
-                imp.pointer_y.set(y);
-            }
-        });
-        let viewer_weak = self.downgrade();
-        motion_controller.connect_enter(move |_, x, y| {
-            if let Some(viewer) = viewer_weak.upgrade() {
-                let imp = viewer.imp();
-                imp.pointer_x.set(x);
-                imp.pointer_y.set(y);
-            }
-        });
-        fixed.add_controller(motion_controller);
-
+    fn setup_gestures(&self, overlay: &Overlay) {
         // Zoom gesture (pinch) on overlay
         let zoom_gesture = GestureZoom::new();
         let viewer_weak = self.downgrade();
@@ -865,16 +1313,27 @@ impl MediaViewer {
         });
         overlay.add_controller(zoom_gesture);
 
-        // Drag gesture (pan) on overlay - only with mouse button 1 (left click)
+        // Drag gesture on overlay - pans, or with Ctrl draws a zoom rectangle;
+        // only with mouse button 1 (left click)
         let drag_gesture = GestureDrag::new();
         drag_gesture.set_button(1); // Left mouse button only
 
         let viewer_weak = self.downgrade();
-        drag_gesture.connect_drag_begin(move |_, _x, _y| {
+        drag_gesture.connect_drag_begin(move |gesture, x, y| {
             if let Some(viewer) = viewer_weak.upgrade() {
+                viewer.stop_inertia();
                 let imp = viewer.imp();
+                // Ctrl+drag draws a zoom rectangle instead of panning
+                let zoom_band = !imp.is_video.get()
+                    && gesture
+                        .current_event_state()
+                        .contains(gtk4::gdk::ModifierType::CONTROL_MASK);
+                imp.band_start.set(zoom_band.then_some((x, y)));
+                imp.band_end.set((x, y));
                 imp.drag_start_pan_x.set(imp.pan_x.get());
                 imp.drag_start_pan_y.set(imp.pan_y.get());
+                imp.drag_last.set((0.0, 0.0, glib::monotonic_time()));
+                imp.drag_velocity.set((0.0, 0.0));
             }
         });
 
@@ -882,6 +1341,13 @@ impl MediaViewer {
         drag_gesture.connect_drag_update(move |_, offset_x, offset_y| {
             if let Some(viewer) = viewer_weak.upgrade() {
                 let imp = viewer.imp();
+                if let Some((start_x, start_y)) = imp.band_start.get() {
+                    imp.band_end.set((start_x + offset_x, start_y + offset_y));
+                    if let Some(band_area) = imp.band_area.borrow().as_ref() {
+                        band_area.queue_draw();
+                    }
+                    return;
+                }
                 if imp.is_video.get() {
                     return;
                 }
@@ -891,6 +1357,38 @@ impl MediaViewer {
                 imp.pan_y.set(start_y + offset_y);
                 imp.user_interacted.set(true);
                 viewer.update_transform();
+
+                let now = glib::monotonic_time();
+                let (last_x, last_y, last_time) = imp.drag_last.get();
+                let dt = (now - last_time) as f64 / 1_000_000.0;
+                if dt > 0.0 {
+                    // Weight recent motion so the fling follows the release
+                    let (vx, vy) = imp.drag_velocity.get();
+                    imp.drag_velocity.set((
+                        0.2 * vx + 0.8 * (offset_x - last_x) / dt,
+                        0.2 * vy + 0.8 * (offset_y - last_y) / dt,
+                    ));
+                    imp.drag_last.set((offset_x, offset_y, now));
+                }
+            }
+        });
+
+        let viewer_weak = self.downgrade();
+        drag_gesture.connect_drag_end(move |_, offset_x, offset_y| {
+            if let Some(viewer) = viewer_weak.upgrade() {
+                let imp = viewer.imp();
+                if let Some((start_x, start_y)) = imp.band_start.take() {
+                    if let Some(band_area) = imp.band_area.borrow().as_ref() {
+                        band_area.queue_draw();
+                    }
+                    viewer
+                        .zoom_to_band((start_x, start_y), (start_x + offset_x, start_y + offset_y));
+                    return;
+                }
+                let (_, _, last_time) = imp.drag_last.get();
+                if glib::monotonic_time() - last_time <= INERTIA_RELEASE_WINDOW_US {
+                    viewer.start_inertia(imp.drag_velocity.get());
+                }
             }
         });
 
@@ -908,6 +1406,44 @@ impl MediaViewer {
             }
         });
         overlay.add_controller(context_click);
+    }
+
+    /// Pointer tracking, double-click and wheel zoom for one image pane; its
+    /// coordinates are the space the pane's picture is positioned in.
+    fn setup_pane_gestures(&self, fixed: &Fixed) {
+        // Track pointer position on the fixed container (same coord space as picture positioning)
+        let motion_controller = EventControllerMotion::new();
+        let viewer_weak = self.downgrade();
+        motion_controller.connect_motion(move |_, x, y| {
+            if let Some(viewer) = viewer_weak.upgrade() {
+                let imp = viewer.imp();
+                imp.pointer_x.set(x);
+                imp.pointer_y.set(y);
+            }
+        });
+        let viewer_weak = self.downgrade();
+        motion_controller.connect_enter(move |_, x, y| {
+            if let Some(viewer) = viewer_weak.upgrade() {
+                let imp = viewer.imp();
+                imp.pointer_x.set(x);
+                imp.pointer_y.set(y);
+            }
+        });
+        fixed.add_controller(motion_controller);
+
+        // Double-click toggles between fit and a zoom centred on the click
+        let double_click = GestureClick::new();
+        double_click.set_button(1);
+        let viewer_weak = self.downgrade();
+        double_click.connect_pressed(move |_, n_press, x, y| {
+            if n_press != 2 {
+                return;
+            }
+            if let Some(viewer) = viewer_weak.upgrade() {
+                viewer.toggle_zoom_at(x, y);
+            }
+        });
+        fixed.add_controller(double_click);
 
         // Scroll wheel for zoom on fixed container (same coord space as motion tracking)
         let scroll_controller = EventControllerScroll::new(EventControllerScrollFlags::VERTICAL);
@@ -975,13 +1511,18 @@ impl MediaViewer {
 
         key_controller.connect_key_pressed(move |_, key, _code, _state| {
             if let Some(viewer) = viewer_weak.upgrade() {
+                viewer.stop_inertia();
                 match key {
+                    Key::Escape if viewer.is_comparing() => {
+                        viewer.exit_compare();
+                        glib::Propagation::Stop
+                    }
                     Key::Escape | Key::q => {
                         viewer.hide();
                         glib::Propagation::Stop
                     }
                     Key::space => {
-                        if viewer.imp().is_video.get() {
+                        if viewer.imp().is_video.get() || viewer.is_animated() {
                             viewer.toggle_video_play_pause();
                             glib::Propagation::Stop
                         } else {
@@ -1084,6 +1625,11 @@ impl MediaViewer {
         if let Some(video_controls) = imp.video_controls.borrow().as_ref() {
             video_controls.set_visible(is_video);
         }
+        if is_video {
+            if let Some(minimap) = imp.minimap.borrow().as_ref() {
+                minimap.set_visible(false);
+            }
+        }
         if let Some(picture) = imp.picture.borrow().as_ref() {
             picture.set_visible(!is_video);
         }
@@ -1205,10 +1751,11 @@ impl MediaViewer {
 
             let pos = format_timestamp(position);
             let dur = format_timestamp(duration);
+            let looping = if imp.video_loop.get() { " [LOOP]" } else { "" };
             if duration > 0.0 {
-                label.set_text(&format!("> {} [{} / {}]", filename, pos, dur));
+                label.set_text(&format!("> {} [{} / {}]{}", filename, pos, dur, looping));
             } else {
-                label.set_text(&format!("> {} [{}]", filename, pos));
+                label.set_text(&format!("> {} [{}]{}", filename, pos, looping));
             }
         }
         if let Some(zoom_label) = imp.zoom_label.borrow().as_ref() {
@@ -1338,8 +1885,16 @@ impl MediaViewer {
         self.imp().is_video.get()
     }
 
+    pub fn is_animated(&self) -> bool {
+        self.imp().animation.borrow().is_some()
+    }
+
     pub fn toggle_video_play_pause(&self) {
         let imp = self.imp();
+        if self.is_animated() {
+            self.toggle_animation_playback();
+            return;
+        }
         if !imp.is_video.get() {
             return;
         }
@@ -1353,6 +1908,76 @@ impl MediaViewer {
         }
     }
 
+    /// Sets the video volume (0.0-1.0); changing it also unmutes.
+    pub fn set_volume(&self, volume: f64) {
+        self.apply_volume(volume, false, true);
+    }
+
+    pub fn toggle_mute(&self) {
+        let imp = self.imp();
+        self.apply_volume(imp.video_volume.get(), !imp.video_muted.get(), true);
+    }
+
+    /// Restores a saved volume and mute state without reporting it back.
+    pub fn restore_volume(&self, volume: f64, muted: bool) {
+        self.apply_volume(volume, muted, false);
+    }
+
+    fn apply_volume(&self, volume: f64, muted: bool, notify: bool) {
+        let imp = self.imp();
+        let volume = volume.clamp(0.0, 1.0);
+        imp.video_volume.set(volume);
+        imp.video_muted.set(muted);
+        if let Some(stream) = imp.video_stream.borrow().as_ref() {
+            stream.set_volume(volume);
+            stream.set_muted(muted);
+        }
+        if let Some(scale) = imp.video_volume_scale.borrow().as_ref() {
+            imp.video_volume_syncing.set(true);
+            scale.set_value(volume);
+            imp.video_volume_syncing.set(false);
+        }
+        if let Some(button) = imp.video_mute_btn.borrow().as_ref() {
+            button.set_label(&volume_label(volume, muted));
+        }
+        if notify {
+            let callback = imp.on_volume_changed.borrow().clone();
+            if let Some(callback) = callback {
+                callback(volume, muted);
+            }
+        }
+    }
+
+    pub fn toggle_loop(&self) {
+        self.apply_loop(!self.imp().video_loop.get(), true);
+    }
+
+    /// Restores the saved loop preference without reporting it back.
+    pub fn restore_loop(&self, looping: bool) {
+        self.apply_loop(looping, false);
+    }
+
+    fn apply_loop(&self, looping: bool, notify: bool) {
+        let imp = self.imp();
+        imp.video_loop.set(looping);
+        if let Some(video) = imp.video_area.borrow().as_ref() {
+            video.set_loop(looping);
+        }
+        if let Some(stream) = imp.video_stream.borrow().as_ref() {
+            stream.set_loop(looping);
+        }
+        if let Some(button) = imp.video_loop_btn.borrow().as_ref() {
+            button.set_label(loop_label(looping));
+        }
+        self.update_video_info(0.0, 0.0);
+        if notify {
+            let callback = imp.on_loop_changed.borrow().clone();
+            if let Some(callback) = callback {
+                callback(looping);
+            }
+        }
+    }
+
     pub fn seek_video_relative(&self, seconds: f64) {
         let imp = self.imp();
         if !imp.is_video.get() {
@@ -1379,38 +2004,161 @@ impl MediaViewer {
         self.show(&path, None);
     }
 
-    /// Show the viewer with an image or video
-    pub fn show(&self, image_path: &Path, thumbnail_path: Option<&Path>) {
+    /// Ask the owner to write the pending rotation of the current image to disk.
+    pub fn request_save_rotation(&self) {
         let imp = self.imp();
-        let is_new_path = imp.current_path.borrow().as_deref() != Some(image_path);
-        if is_new_path {
+        if imp.is_video.get() {
+            return;
+        }
+        let Some(path) = imp.current_path.borrow().clone() else {
+            return;
+        };
+        let rotation = imp.manual_rotation_cw.get();
+        if rotation == 0 {
+            self.show_message("> No rotation to save");
+            return;
+        }
+        let callback = imp.on_save_rotation.borrow().clone();
+        if let Some(callback) = callback {
+            callback(path, rotation);
+        }
+    }
+
+    /// Reload `path` after its rotation was written to the file, so the
+    /// cached textures and the pending view rotation do not apply it twice.
+    pub fn rotation_saved(&self, path: &Path) {
+        let imp = self.imp();
+        imp.preview_cache.borrow_mut().remove_path(path);
+        if imp.current_path.borrow().as_deref() == Some(path) {
             imp.manual_rotation_cw.set(0);
+            self.show(path, None);
         }
-        let manual_rotation = imp.manual_rotation_cw.get();
+    }
 
-        // Increment generation to invalidate any pending loads
-        let generation = imp.load_generation.get().wrapping_add(1);
-        imp.load_generation.set(generation);
-        imp.load_generation_atomic
-            .store(generation, Ordering::SeqCst);
-        let generation_guard = imp.load_generation_atomic.clone();
-        self.cancel_full_decode_timer();
+    /// Put the current item on the clipboard as image data plus its file URI.
+    /// A cached full-resolution texture is reused; otherwise the file is
+    /// decoded on a background thread first. Videos only copy the URI.
+    pub fn copy_to_clipboard(&self) {
+        let imp = self.imp();
+        let Some(path) = imp.current_path.borrow().clone() else {
+            return;
+        };
+        let filename = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Unknown".to_string());
+        if imp.is_video.get() {
+            self.set_clipboard(&path, None);
+            self.show_message(&format!("> Copied: {}", filename));
+            return;
+        }
 
-        // Reset state
-        imp.scale.set(1.0);
-        imp.pan_x.set(0.0);
-        imp.pan_y.set(0.0);
-        imp.last_req_w.set(-1);
-        imp.last_req_h.set(-1);
-        imp.last_pos_x.set(f64::NAN);
-        imp.last_pos_y.set(f64::NAN);
-        imp.scroll_accum.set(0.0);
-        imp.visible.set(true);
+        let rotation = imp.manual_rotation_cw.get();
+        if let Some(cached) = self
+            .cache_get(&path, rotation)
+            .filter(|cached| cached.kind == PrefetchKind::Full)
+        {
+            self.set_clipboard(&path, Some(&cached.texture));
+            self.show_message(&format!("> Copied: {}", filename));
+            return;
+        }
+
+        self.show_message(&format!("> Copying: {}...", filename));
+        let (tx, rx) = async_channel::bounded::<Option<(Vec<u8>, u32, u32)>>(1);
+        let decode_path = path.clone();
+        std::thread::spawn(move || {
+            let decoded = crate::image_loader::open_image_with_rotation(&decode_path, rotation)
+                .ok()
+                .map(|img| {
+                    let rgba = img.to_rgba8();
+                    let (width, height) = rgba.dimensions();
+                    (rgba.into_raw(), width, height)
+                });
+            let _ = tx.send_blocking(decoded);
+        });
+
+        let viewer_weak = self.downgrade();
+        glib::spawn_future_local(async move {
+            let Ok(decoded) = rx.recv().await else {
+                return;
+            };
+            let Some(viewer) = viewer_weak.upgrade() else {
+                return;
+            };
+            let texture = decoded.and_then(|(data, width, height)| {
+                Self::create_texture_from_rgba(&data, width, height)
+            });
+            match texture {
+                Some(texture) => {
+                    viewer.set_clipboard(&path, Some(&texture));
+                    viewer.show_message(&format!("> Copied: {}", filename));
+                }
+                None => viewer.show_message(&format!("> Copy failed: {}", filename)),
+            }
+        });
+    }
+
+    fn set_clipboard(&self, path: &Path, texture: Option<&Texture>) {
+        let uri = gtk4::gio::File::for_path(path).uri();
+        let uri_list = glib::Bytes::from_owned(format!("{}\r\n", uri).into_bytes());
+        let mut providers = Vec::with_capacity(2);
+        if let Some(texture) = texture {
+            providers.push(ContentProvider::for_value(&texture.to_value()));
+        }
+        providers.push(ContentProvider::for_bytes("text/uri-list", &uri_list));
+        let provider = ContentProvider::new_union(&providers);
+        if let Err(err) = self.widget().clipboard().set_content(Some(&provider)) {
+            tracing::warn!(error = ?err, "Failed to set clipboard content");
+        }
+    }
+
+    /// Replace the info label text until the next redraw of the file details.
+    pub fn show_message(&self, message: &str) {
+        if let Some(label) = self.imp().info_label.borrow().as_ref() {
+            label.set_text(message);
+        }
+    }
+
+    /// Show the viewer with an image or video
+    pub fn show(&self, image_path: &Path, thumbnail_path: Option<&Path>) {
+        let imp = self.imp();
+        let is_new_path = imp.current_path.borrow().as_deref() != Some(image_path);
+        if is_new_path {
+            imp.manual_rotation_cw.set(0);
+        }
+        let manual_rotation = imp.manual_rotation_cw.get();
+
+        // Increment generation to invalidate any pending loads
+        let generation = imp.load_generation.get().wrapping_add(1);
+        imp.load_generation.set(generation);
+        imp.load_generation_atomic
+            .store(generation, Ordering::SeqCst);
+        let generation_guard = imp.load_generation_atomic.clone();
+        self.cancel_full_decode_timer();
+        self.stop_animation();
+        self.stop_inertia();
+
+        // Reset state
+        imp.scale.set(1.0);
+        imp.pan_x.set(0.0);
+        imp.pan_y.set(0.0);
+        imp.last_req_w.set(-1);
+        imp.last_req_h.set(-1);
+        imp.last_pos_x.set(f64::NAN);
+        imp.last_pos_y.set(f64::NAN);
+        imp.scroll_accum.set(0.0);
+        imp.visible.set(true);
         imp.is_video.set(is_video_path(image_path));
         imp.is_loading.set(!imp.is_video.get());
         self.set_preview_loading(!imp.is_video.get());
         imp.user_interacted.set(false);
+        if let Some(scale) = imp.zoom_preference.get() {
+            imp.scale.set(scale);
+            imp.user_interacted.set(true);
+        }
         *imp.current_path.borrow_mut() = Some(image_path.to_path_buf());
+        self.refresh_info_panel();
+        self.refresh_histogram();
 
         // Update info label
         if let Some(label) = imp.info_label.borrow().as_ref() {
@@ -1451,6 +2199,9 @@ impl MediaViewer {
             self.set_texture(Option::<&Texture>::None);
             if let Some(video) = imp.video_area.borrow().as_ref() {
                 let media = MediaFile::for_filename(image_path);
+                media.set_volume(imp.video_volume.get());
+                media.set_muted(imp.video_muted.get());
+                media.set_loop(imp.video_loop.get());
                 self.attach_video_stream_debug(&media);
                 video.set_media_stream(Some(&media));
                 media.play();
@@ -1479,6 +2230,9 @@ impl MediaViewer {
         *imp.video_stream.borrow_mut() = None;
         self.set_video_mode(false);
         self.schedule_layout_retry(generation);
+        if crate::image_loader::is_animation_candidate(image_path) {
+            self.load_animation(image_path, manual_rotation, generation);
+        }
 
         let mut initial_preview_shown = false;
         if let Some(cached) = self.cache_get(image_path, manual_rotation) {
@@ -1493,7 +2247,11 @@ impl MediaViewer {
                 cached.kind == PrefetchKind::Preview,
             );
             if cached.kind == PrefetchKind::Full {
-                self.fit_to_window();
+                if imp.user_interacted.get() {
+                    self.update_transform();
+                } else {
+                    self.apply_fit();
+                }
                 return;
             }
             initial_preview_shown = true;
@@ -1571,6 +2329,149 @@ impl MediaViewer {
         self.schedule_full_decode(generation, FULL_DECODE_IDLE_DELAY_MS);
     }
 
+    /// Decodes every frame of a GIF/WebP in the background; if it turns out
+    /// to be animated, playback replaces the still image once all are ready.
+    fn load_animation(&self, path: &Path, rotation_steps: u8, generation: u64) {
+        let (tx, rx) = async_channel::bounded::<Vec<AnimationFrame>>(1);
+        let path = path.to_path_buf();
+        let generation_guard = self.imp().load_generation_atomic.clone();
+        std::thread::spawn(move || {
+            if generation != generation_guard.load(Ordering::SeqCst) {
+                return;
+            }
+            match crate::image_loader::decode_animation(&path, rotation_steps) {
+                Ok(Some(frames)) => {
+                    let _ = tx.send_blocking(frames);
+                }
+                Ok(None) => {}
+                Err(err) => tracing::warn!("{:#}", err),
+            }
+        });
+
+        let viewer_weak = self.downgrade();
+        glib::spawn_future_local(async move {
+            let Ok(frames) = rx.recv().await else {
+                return;
+            };
+            if let Some(viewer) = viewer_weak.upgrade() {
+                viewer.start_animation(generation, frames);
+            }
+        });
+    }
+
+    fn start_animation(&self, generation: u64, frames: Vec<AnimationFrame>) {
+        let imp = self.imp();
+        if generation != imp.load_generation.get() || !imp.visible.get() {
+            return;
+        }
+        let mut animation = AnimationPlayback {
+            frames: frames
+                .into_iter()
+                .map(|frame| (AnimationTexture::Pending(frame.image), frame.delay))
+                .collect(),
+            index: 0,
+            playing: true,
+        };
+        let Some(first) = animation.current_texture() else {
+            return;
+        };
+        let (width, height) = (first.width() as u32, first.height() as u32);
+        self.cancel_full_decode_timer();
+        self.set_texture(Some(&first));
+        imp.image_width.set(width);
+        imp.image_height.set(height);
+        imp.is_loading.set(false);
+        self.set_preview_loading(false);
+        *imp.animation.borrow_mut() = Some(animation);
+        self.set_animation_controls(true);
+        self.update_video_play_button(true);
+        self.schedule_animation_frame();
+
+        if !imp.user_interacted.get() {
+            self.apply_fit();
+        } else {
+            self.update_transform();
+        }
+        self.update_info_label(Some(width), Some(height), false);
+    }
+
+    fn schedule_animation_frame(&self) {
+        let imp = self.imp();
+        let delay = match imp.animation.borrow().as_ref() {
+            Some(animation) if animation.playing => animation.frames[animation.index].1,
+            _ => return,
+        };
+        let viewer_weak = self.downgrade();
+        let source_id = glib::timeout_add_local_once(delay, move || {
+            if let Some(viewer) = viewer_weak.upgrade() {
+                viewer.imp().animation_timer.borrow_mut().take();
+                viewer.advance_animation_frame();
+            }
+        });
+        *imp.animation_timer.borrow_mut() = Some(source_id);
+    }
+
+    fn advance_animation_frame(&self) {
+        let texture = {
+            let mut animation = self.imp().animation.borrow_mut();
+            let Some(animation) = animation.as_mut() else {
+                return;
+            };
+            animation.index = (animation.index + 1) % animation.frames.len();
+            animation.current_texture()
+        };
+        if let Some(texture) = texture {
+            self.set_texture(Some(&texture));
+        }
+        self.schedule_animation_frame();
+    }
+
+    fn toggle_animation_playback(&self) {
+        let imp = self.imp();
+        let playing = {
+            let mut animation = imp.animation.borrow_mut();
+            let Some(animation) = animation.as_mut() else {
+                return;
+            };
+            animation.playing = !animation.playing;
+            animation.playing
+        };
+        if playing {
+            self.schedule_animation_frame();
+        } else if let Some(source_id) = imp.animation_timer.borrow_mut().take() {
+            source_id.remove();
+        }
+        self.update_video_play_button(playing);
+    }
+
+    fn stop_animation(&self) {
+        let imp = self.imp();
+        if let Some(source_id) = imp.animation_timer.borrow_mut().take() {
+            source_id.remove();
+        }
+        if imp.animation.borrow_mut().take().is_some() {
+            self.set_animation_controls(false);
+        }
+    }
+
+    /// Shows the video bar with only its play button while an animation plays.
+    fn set_animation_controls(&self, animated: bool) {
+        let imp = self.imp();
+        let Some(video_controls) = imp.video_controls.borrow().clone() else {
+            return;
+        };
+        let play_btn = imp.video_play_btn.borrow().clone();
+        let play_btn = play_btn.as_ref().map(|btn| btn.upcast_ref::<Widget>());
+        let mut child = video_controls.first_child();
+        while let Some(widget) = child {
+            if Some(&widget) != play_btn {
+                widget.set_visible(!animated);
+            }
+            child = widget.next_sibling();
+        }
+        video_controls.set_visible(animated || imp.is_video.get());
+    }
+
     fn schedule_layout_retry(&self, generation: u64) {
         let viewer_weak = self.downgrade();
         let mut attempts = 0u8;
@@ -1597,7 +2498,7 @@ impl MediaViewer {
                 && imp.image_height.get() > 0
             {
                 if !imp.user_interacted.get() {
-                    viewer.fit_to_window();
+                    viewer.apply_fit();
                 } else {
                     viewer.update_transform();
                 }
@@ -1628,9 +2529,15 @@ impl MediaViewer {
             return;
         };
         let rotation_steps = imp.manual_rotation_cw.get();
+        let max_size = if crate::svg::is_svg_path(&path) {
+            self.svg_render_target()
+        } else {
+            0
+        };
 
         if let Some(cached) = self.cache_get(&path, rotation_steps) {
-            if cached.kind == PrefetchKind::Full {
+            let rendered = cached.texture.width().max(cached.texture.height()) as u32;
+            if cached.kind == PrefetchKind::Full && rendered >= max_size {
                 return;
             }
         }
@@ -1643,9 +2550,25 @@ impl MediaViewer {
             path,
             generation,
             rotation_steps,
+            max_size,
         });
     }
 
+    /// Raster size that keeps an SVG sharp at the current zoom.
+    fn svg_render_target(&self) -> u32 {
+        let imp = self.imp();
+        let intrinsic = imp.image_width.get().max(imp.image_height.get()) as f64;
+        let scale_factor = imp
+            .overlay
+            .borrow()
+            .as_ref()
+            .map_or(1, |overlay| overlay.scale_factor().max(1));
+        let scale = imp.scale.get().max(1.0) * scale_factor as f64;
+        (intrinsic * scale)
+            .round()
+            .clamp(1.0, SVG_MAX_RENDER_SIZE as f64) as u32
+    }
+
     fn schedule_full_decode(&self, generation: u64, delay_ms: u64) {
         self.cancel_full_decode_timer();
         if delay_ms == 0 {
@@ -1711,8 +2634,9 @@ impl MediaViewer {
 
     /// Load a texture synchronously from a path (for thumbnails)
     fn load_texture_sync(&self, path: &Path) -> Option<Texture> {
-        // Try to load using GDK first (faster for supported formats)
-        Texture::from_filename(path).ok()
+        // GDK first (faster for supported formats), then the image crate for
+        // thumbnails GDK can't decode, e.g. WebP without the pixbuf plugin.
+        crate::thumbnails::cache::load_texture(path).ok()
     }
 
     /// Create a GDK texture from RGBA data
@@ -1748,6 +2672,9 @@ impl MediaViewer {
         if let Some(picture) = self.imp().picture.borrow().as_ref() {
             picture.set_paintable(texture);
         }
+        if let Some(picture) = self.imp().minimap_picture.borrow().as_ref() {
+            picture.set_paintable(texture);
+        }
     }
 
     fn set_preview_loading(&self, loading: bool) {
@@ -1863,6 +2790,11 @@ impl MediaViewer {
     pub fn hide(&self) {
         let imp = self.imp();
         self.cancel_full_decode_timer();
+        self.stop_slideshow();
+        self.stop_animation();
+        self.stop_inertia();
+        self.exit_compare();
+        imp.compare_anchor.borrow_mut().take();
 
         // Increment generation to invalidate pending loads
         let generation = imp.load_generation.get().wrapping_add(1);
@@ -1875,8 +2807,11 @@ impl MediaViewer {
         imp.is_video.set(false);
         imp.manual_rotation_cw.set(0);
         imp.scroll_accum.set(0.0);
+        imp.zoom_preference.set(None);
+        imp.fit_mode.set(FitMode::Window);
         self.set_preview_loading(false);
         self.set_favorite_state(false);
+        self.set_rating_state(None);
         self.set_video_mode(false);
         self.stop_video_info_timer();
 
@@ -1930,104 +2865,455 @@ impl MediaViewer {
         *self.imp().on_toggle_favorite.borrow_mut() = Some(Rc::new(callback));
     }
 
-    pub fn set_favorite_state(&self, is_favorite: bool) {
-        let imp = self.imp();
-        imp.is_favorite.set(is_favorite);
+    pub fn connect_save_rotation<F>(&self, callback: F)
+    where
+        F: Fn(PathBuf, u8) + 'static,
+    {
+        *self.imp().on_save_rotation.borrow_mut() = Some(Rc::new(callback));
+    }
 
-        if let Some(button) = imp.favorite_btn.borrow().as_ref() {
-            button.set_label(if is_favorite { "[FAV +]" } else { "[FAV -]" });
-            if is_favorite {
-                button.add_css_class("favorite-on");
-            } else {
-                button.remove_css_class("favorite-on");
-            }
-        }
+    /// Set callback for the loop toggle.
+    pub fn connect_loop_changed<F>(&self, callback: F)
+    where
+        F: Fn(bool) + 'static,
+    {
+        *self.imp().on_loop_changed.borrow_mut() = Some(Rc::new(callback));
+    }
 
-        if let Some(indicator) = imp.favorite_indicator.borrow().as_ref() {
-            indicator.set_text(if is_favorite { "+" } else { "-" });
-            if is_favorite {
-                indicator.add_css_class("favorite-on");
-            } else {
-                indicator.remove_css_class("favorite-on");
-            }
-        }
+    /// Set callback for volume or mute changes made in the viewer.
+    pub fn connect_volume_changed<F>(&self, callback: F)
+    where
+        F: Fn(f64, bool) + 'static,
+    {
+        *self.imp().on_volume_changed.borrow_mut() = Some(Rc::new(callback));
     }
 
-    /// Get the current path being displayed
-    pub fn current_path(&self) -> Option<PathBuf> {
-        self.imp().current_path.borrow().clone()
+    /// Set callback used by navigation and the slideshow to change items.
+    pub fn connect_advance<F>(&self, callback: F)
+    where
+        F: Fn(i32, bool) -> bool + 'static,
+    {
+        *self.imp().on_advance.borrow_mut() = Some(Rc::new(callback));
     }
 
-    fn emit_context_menu(&self, anchor: &Widget, rect: Rectangle) {
-        let imp = self.imp();
-        let Some(path) = imp.current_path.borrow().clone() else {
-            return;
+    /// Show the next (`step` > 0) or previous media item, keeping the zoom.
+    /// Returns false at either end of the list.
+    pub fn navigate(&self, step: i32) -> bool {
+        self.stop_slideshow();
+        let Some(advance) = self.imp().on_advance.borrow().clone() else {
+            return false;
         };
-        if let Some(ref callback) = *imp.on_context_menu.borrow() {
-            callback(path, anchor.clone(), rect);
+        advance(step, false)
+    }
+
+    /// Set how long each item is shown during a slideshow.
+    /// Size of the decoded image cache, in megabytes
+    pub fn prefetch_budget_mb(&self) -> usize {
+        self.imp().preview_cache.borrow().max_bytes / (1024 * 1024)
+    }
+
+    /// Resize the decoded image cache, dropping the least recently used
+    /// images if it shrank
+    pub fn set_prefetch_budget_mb(&self, mb: usize) {
+        let mb = mb.clamp(MIN_PREFETCH_MB, MAX_PREFETCH_MB);
+        self.imp()
+            .preview_cache
+            .borrow_mut()
+            .set_max_bytes(mb * 1024 * 1024);
+    }
+
+    pub fn set_slideshow_interval(&self, secs: u32) {
+        let imp = self.imp();
+        imp.slideshow_interval_secs.set(secs.max(1));
+        if imp.slideshow_active.get() {
+            self.schedule_slideshow_tick(self.slideshow_interval());
         }
     }
 
-    fn emit_toggle_favorite(&self) {
-        if let Some(ref callback) = *self.imp().on_toggle_favorite.borrow() {
-            callback();
+    pub fn slideshow_interval_secs(&self) -> u32 {
+        self.imp().slideshow_interval_secs.get()
+    }
+
+    pub fn is_slideshow_active(&self) -> bool {
+        self.imp().slideshow_active.get()
+    }
+
+    pub fn toggle_slideshow(&self) {
+        if self.imp().slideshow_active.get() {
+            self.stop_slideshow();
+        } else {
+            self.start_slideshow();
         }
     }
 
-    /// Zoom by a factor (1.0 = no change, 2.0 = double, 0.5 = half)
-    pub fn zoom_by(&self, factor: f64) {
+    pub fn start_slideshow(&self) {
         let imp = self.imp();
-        let current = imp.scale.get();
-        let new_scale = (current * factor).clamp(MIN_SCALE, MAX_SCALE);
-        self.set_scale_internal(new_scale, true);
+        if !imp.visible.get() || imp.on_advance.borrow().is_none() {
+            return;
+        }
+        imp.slideshow_active.set(true);
+        self.update_slideshow_button();
+        self.schedule_slideshow_tick(self.slideshow_interval());
     }
 
-    /// Zoom toward or away from a specific point (for scroll wheel zoom)
-    /// pointer_x, pointer_y are in Fixed container coordinate space.
-    fn zoom_at_point_with_factor(&self, pointer_x: f64, pointer_y: f64, factor: f64) {
+    /// Pause the slideshow; called on close and on any manual navigation or zoom.
+    pub fn stop_slideshow(&self) {
         let imp = self.imp();
+        if let Some(source_id) = imp.slideshow_timer.borrow_mut().take() {
+            source_id.remove();
+        }
+        if imp.slideshow_active.replace(false) {
+            self.update_slideshow_button();
+        }
+    }
 
-        let old_scale = imp.scale.get();
-        let new_scale = (old_scale * factor).clamp(MIN_SCALE, MAX_SCALE);
+    fn slideshow_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.imp().slideshow_interval_secs.get() as u64)
+    }
 
-        if (new_scale - old_scale).abs() < 1e-9 {
-            return;
+    fn schedule_slideshow_tick(&self, delay: std::time::Duration) {
+        if let Some(source_id) = self.imp().slideshow_timer.borrow_mut().take() {
+            source_id.remove();
         }
+        let viewer_weak = self.downgrade();
+        let source_id = glib::timeout_add_local(delay, move || {
+            if let Some(viewer) = viewer_weak.upgrade() {
+                viewer.imp().slideshow_timer.borrow_mut().take();
+                viewer.slideshow_tick();
+            }
+            glib::ControlFlow::Break
+        });
+        *self.imp().slideshow_timer.borrow_mut() = Some(source_id);
+    }
 
-        let Some((container_x, container_y, container_w, container_h)) = self.viewport_rect()
-        else {
+    fn slideshow_tick(&self) {
+        let imp = self.imp();
+        if !imp.slideshow_active.get() || !imp.visible.get() {
+            self.stop_slideshow();
             return;
-        };
+        }
 
-        if container_w <= 0.0 || container_h <= 0.0 {
+        // Let videos play out before moving on; a looping clip never does,
+        // so the slideshow holds on it until navigated away from.
+        let video_running = imp.is_video.get()
+            && imp
+                .video_stream
+                .borrow()
+                .as_ref()
+                .is_some_and(|stream| imp.video_loop.get() || !stream.is_ended());
+        if video_running {
+            self.schedule_slideshow_tick(std::time::Duration::from_millis(SLIDESHOW_VIDEO_POLL_MS));
             return;
         }
 
-        let img_w = imp.image_width.get() as f64;
-        let img_h = imp.image_height.get() as f64;
-        if img_w <= 0.0 || img_h <= 0.0 {
+        let Some(advance) = imp.on_advance.borrow().clone() else {
+            self.stop_slideshow();
             return;
+        };
+        if advance(1, true) && imp.slideshow_active.get() {
+            self.schedule_slideshow_tick(self.slideshow_interval());
+        } else {
+            self.stop_slideshow();
         }
+    }
 
-        let old_pan_x = imp.pan_x.get();
-        let old_pan_y = imp.pan_y.get();
-
-        // Calculate current picture position in container
-        let old_scaled_w = img_w * old_scale;
-        let old_scaled_h = img_h * old_scale;
-        let old_pic_x = container_x + (container_w - old_scaled_w) / 2.0 + old_pan_x;
-        let old_pic_y = container_y + (container_h - old_scaled_h) / 2.0 + old_pan_y;
+    pub fn toggle_info_panel(&self) {
+        let imp = self.imp();
+        let visible = !imp.info_panel_visible.get();
+        imp.info_panel_visible.set(visible);
+        if let Some(panel) = imp.info_panel.borrow().as_ref() {
+            panel.set_visible(visible);
+        }
+        self.refresh_info_panel();
+    }
 
-        // True zoom-to-cursor behavior: keep the anchor point stable.
-        let inside_image = pointer_x >= old_pic_x
-            && pointer_x <= old_pic_x + old_scaled_w
-            && pointer_y >= old_pic_y
-            && pointer_y <= old_pic_y + old_scaled_h;
+    pub fn toggle_histogram(&self) {
+        let imp = self.imp();
+        imp.histogram_visible.set(!imp.histogram_visible.get());
+        self.refresh_histogram();
+    }
 
-        // Clamp to image edge outside bounds to avoid abrupt cursor->center anchor jumps.
-        let anchor_x = if inside_image {
-            pointer_x
-        } else {
+    /// Show the histogram of the current image, computing it from a full
+    /// decode on a background thread the first time the image is shown.
+    /// Videos get none.
+    fn refresh_histogram(&self) {
+        let imp = self.imp();
+        let Some(area) = imp.histogram_area.borrow().clone() else {
+            return;
+        };
+        let path = imp.current_path.borrow().clone();
+        let Some(path) = path.filter(|_| imp.histogram_visible.get() && !imp.is_video.get()) else {
+            area.set_visible(false);
+            return;
+        };
+        area.set_visible(true);
+        let key = ViewerCacheKey::new(&path, imp.manual_rotation_cw.get());
+        let cached = imp.histogram_cache.borrow_mut().get(&key).cloned();
+        let computed = cached.is_some();
+        *imp.histogram.borrow_mut() = cached;
+        area.queue_draw();
+        if computed {
+            return;
+        }
+
+        let (tx, rx) = async_channel::bounded::<Histogram>(1);
+        let generation = imp.load_generation.get();
+        let generation_guard = imp.load_generation_atomic.clone();
+        let decode_key = key.clone();
+        std::thread::spawn(move || {
+            if generation != generation_guard.load(Ordering::SeqCst) {
+                return;
+            }
+            if let Some((data, ..)) = decode_image_full(&decode_key.path, decode_key.rotation_steps)
+            {
+                let _ = tx.send_blocking(Histogram::from_rgba(&data));
+            }
+        });
+
+        let viewer_weak = self.downgrade();
+        glib::spawn_future_local(async move {
+            let Ok(histogram) = rx.recv().await else {
+                return;
+            };
+            let Some(viewer) = viewer_weak.upgrade() else {
+                return;
+            };
+            let imp = viewer.imp();
+            let histogram = Rc::new(histogram);
+            imp.histogram_cache
+                .borrow_mut()
+                .put(key.clone(), histogram.clone());
+            let current = imp
+                .current_path
+                .borrow()
+                .as_deref()
+                .map(|path| ViewerCacheKey::new(path, imp.manual_rotation_cw.get()));
+            if current == Some(key) {
+                *imp.histogram.borrow_mut() = Some(histogram);
+                if let Some(area) = imp.histogram_area.borrow().as_ref() {
+                    area.queue_draw();
+                }
+            }
+        });
+    }
+
+    /// Fill the info panel for the current path, parsing metadata on a
+    /// background thread the first time a path is shown.
+    fn refresh_info_panel(&self) {
+        let imp = self.imp();
+        if !imp.info_panel_visible.get() {
+            return;
+        }
+        let Some(path) = imp.current_path.borrow().clone() else {
+            return;
+        };
+        let cached = imp.info_cache.borrow_mut().get(&path).cloned();
+        if let Some(info) = cached {
+            self.render_info_panel(Some(&info));
+            return;
+        }
+        self.render_info_panel(None);
+
+        let (tx, rx) = async_channel::bounded::<MediaInfo>(1);
+        let is_video = is_video_path(&path);
+        let read_path = path.clone();
+        std::thread::spawn(move || {
+            let _ = tx.send_blocking(read_media_info(&read_path, is_video));
+        });
+
+        let viewer_weak = self.downgrade();
+        glib::spawn_future_local(async move {
+            let Ok(info) = rx.recv().await else {
+                return;
+            };
+            let Some(viewer) = viewer_weak.upgrade() else {
+                return;
+            };
+            let imp = viewer.imp();
+            imp.info_cache.borrow_mut().put(path.clone(), info.clone());
+            if imp.current_path.borrow().as_deref() == Some(path.as_path()) {
+                viewer.render_info_panel(Some(&info));
+            }
+        });
+    }
+
+    /// Rebuild the panel rows; `None` shows a loading placeholder.
+    fn render_info_panel(&self, info: Option<&MediaInfo>) {
+        let Some(panel) = self.imp().info_panel.borrow().clone() else {
+            return;
+        };
+        while let Some(child) = panel.first_child() {
+            panel.remove(&child);
+        }
+
+        let title = Label::new(Some("[ INFO ]"));
+        title.set_xalign(0.0);
+        title.add_css_class("viewer-info-title");
+        panel.append(&title);
+
+        let Some(info) = info else {
+            let loading = Label::new(Some("> Reading metadata..."));
+            loading.set_xalign(0.0);
+            loading.add_css_class("muted");
+            panel.append(&loading);
+            return;
+        };
+
+        for (name, value) in info.rows() {
+            let row = GtkBox::new(Orientation::Horizontal, 12);
+            let name_label = Label::new(Some(name));
+            name_label.set_xalign(0.0);
+            name_label.set_width_chars(12);
+            name_label.add_css_class("muted");
+            let value_label = Label::new(Some(&value));
+            value_label.set_xalign(0.0);
+            value_label.set_max_width_chars(32);
+            value_label.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+            row.append(&name_label);
+            row.append(&value_label);
+            panel.append(&row);
+        }
+        if !info.has_exif() {
+            let empty = Label::new(Some("no metadata"));
+            empty.set_xalign(0.0);
+            empty.add_css_class("muted");
+            panel.append(&empty);
+        }
+        if info.gps.is_some() {
+            let hint = Label::new(Some("P opens the location on a map"));
+            hint.set_xalign(0.0);
+            hint.add_css_class("muted");
+            panel.append(&hint);
+        }
+    }
+
+    fn update_slideshow_button(&self) {
+        let imp = self.imp();
+        if let Some(button) = imp.slideshow_btn.borrow().as_ref() {
+            button.set_label(if imp.slideshow_active.get() {
+                "[|| SHOW]"
+            } else {
+                "[> SHOW]"
+            });
+        }
+    }
+
+    pub fn set_favorite_state(&self, is_favorite: bool) {
+        let imp = self.imp();
+        imp.is_favorite.set(is_favorite);
+
+        if let Some(button) = imp.favorite_btn.borrow().as_ref() {
+            button.set_label(if is_favorite { "[FAV +]" } else { "[FAV -]" });
+            if is_favorite {
+                button.add_css_class("favorite-on");
+            } else {
+                button.remove_css_class("favorite-on");
+            }
+        }
+
+        if let Some(indicator) = imp.favorite_indicator.borrow().as_ref() {
+            indicator.set_text(if is_favorite { "+" } else { "-" });
+            if is_favorite {
+                indicator.add_css_class("favorite-on");
+            } else {
+                indicator.remove_css_class("favorite-on");
+            }
+        }
+    }
+
+    /// Show the current file's star rating, or hide it with `None`.
+    pub fn set_rating_state(&self, rating: Option<u8>) {
+        if let Some(indicator) = self.imp().rating_indicator.borrow().as_ref() {
+            match rating {
+                Some(rating) => {
+                    indicator.set_text(&rating_stars(rating));
+                    indicator.set_visible(true);
+                }
+                None => indicator.set_visible(false),
+            }
+        }
+    }
+
+    /// Whether the user picked a zoom level instead of fit-to-window.
+    pub fn is_zoomed(&self) -> bool {
+        self.imp().zoom_preference.get().is_some()
+    }
+
+    /// Get the current path being displayed
+    pub fn current_path(&self) -> Option<PathBuf> {
+        self.imp().current_path.borrow().clone()
+    }
+
+    fn emit_context_menu(&self, anchor: &Widget, rect: Rectangle) {
+        let imp = self.imp();
+        let Some(path) = imp.current_path.borrow().clone() else {
+            return;
+        };
+        if let Some(ref callback) = *imp.on_context_menu.borrow() {
+            callback(path, anchor.clone(), rect);
+        }
+    }
+
+    fn emit_toggle_favorite(&self) {
+        if let Some(ref callback) = *self.imp().on_toggle_favorite.borrow() {
+            callback();
+        }
+    }
+
+    /// Zoom by a factor (1.0 = no change, 2.0 = double, 0.5 = half)
+    pub fn zoom_by(&self, factor: f64) {
+        let imp = self.imp();
+        let current = imp.scale.get();
+        let new_scale = (current * factor).clamp(MIN_SCALE, MAX_SCALE);
+        self.set_scale_internal(new_scale, true);
+    }
+
+    /// Zoom toward or away from a specific point (for scroll wheel zoom)
+    /// pointer_x, pointer_y are in Fixed container coordinate space.
+    fn zoom_at_point_with_factor(&self, pointer_x: f64, pointer_y: f64, factor: f64) {
+        let imp = self.imp();
+
+        let old_scale = imp.scale.get();
+        let new_scale = (old_scale * factor).clamp(MIN_SCALE, MAX_SCALE);
+
+        if (new_scale - old_scale).abs() < 1e-9 {
+            return;
+        }
+
+        let Some((container_x, container_y, container_w, container_h)) = self.viewport_rect()
+        else {
+            return;
+        };
+
+        if container_w <= 0.0 || container_h <= 0.0 {
+            return;
+        }
+
+        let img_w = imp.image_width.get() as f64;
+        let img_h = imp.image_height.get() as f64;
+        if img_w <= 0.0 || img_h <= 0.0 {
+            return;
+        }
+
+        let old_pan_x = imp.pan_x.get();
+        let old_pan_y = imp.pan_y.get();
+
+        // Calculate current picture position in container
+        let old_scaled_w = img_w * old_scale;
+        let old_scaled_h = img_h * old_scale;
+        let old_pic_x = container_x + (container_w - old_scaled_w) / 2.0 + old_pan_x;
+        let old_pic_y = container_y + (container_h - old_scaled_h) / 2.0 + old_pan_y;
+
+        // True zoom-to-cursor behavior: keep the anchor point stable.
+        let inside_image = pointer_x >= old_pic_x
+            && pointer_x <= old_pic_x + old_scaled_w
+            && pointer_y >= old_pic_y
+            && pointer_y <= old_pic_y + old_scaled_h;
+
+        // Clamp to image edge outside bounds to avoid abrupt cursor->center anchor jumps.
+        let anchor_x = if inside_image {
+            pointer_x
+        } else {
             pointer_x.clamp(old_pic_x, old_pic_x + old_scaled_w)
         };
         let anchor_y = if inside_image {
@@ -2061,6 +3347,58 @@ impl MediaViewer {
         self.set_scale_internal(new_scale, true);
     }
 
+    /// Fit the part of the image under a Ctrl+drag rectangle (overlay
+    /// coordinates) to the viewport.
+    fn zoom_to_band(&self, start: (f64, f64), end: (f64, f64)) {
+        let imp = self.imp();
+        let (Some(overlay), Some(fixed)) =
+            (imp.overlay.borrow().clone(), imp.fixed.borrow().clone())
+        else {
+            return;
+        };
+        // The pane is offset inside the overlay in compare mode
+        let to_pane = |(x, y): (f64, f64)| {
+            overlay
+                .compute_point(&fixed, &gtk4::graphene::Point::new(x as f32, y as f32))
+                .map(|point| (point.x() as f64, point.y() as f64))
+        };
+        let (Some(start), Some(end), Some(viewport)) =
+            (to_pane(start), to_pane(end), self.viewport_rect())
+        else {
+            return;
+        };
+        let Some((scale, pan_x, pan_y)) = zoom_to_region(
+            viewport,
+            (imp.image_width.get() as f64, imp.image_height.get() as f64),
+            imp.scale.get(),
+            (imp.pan_x.get(), imp.pan_y.get()),
+            start,
+            end,
+        ) else {
+            return;
+        };
+        imp.pan_x.set(pan_x);
+        imp.pan_y.set(pan_y);
+        imp.user_interacted.set(true);
+        self.set_scale_internal(scale, true);
+    }
+
+    /// Zoom in around a point in Fixed coordinates, or return to fit if the
+    /// user already zoomed. Images shown below 1:1 go to 1:1, others double.
+    fn toggle_zoom_at(&self, x: f64, y: f64) {
+        let imp = self.imp();
+        if imp.is_video.get() {
+            return;
+        }
+        if imp.zoom_preference.get().is_some() {
+            self.apply_fit();
+            return;
+        }
+        let current = imp.scale.get();
+        let factor = if current < 0.95 { 1.0 / current } else { 2.0 };
+        self.zoom_at_point_with_factor(x, y, factor);
+    }
+
     /// Set the zoom scale directly
     pub fn set_scale(&self, scale: f64) {
         self.set_scale_internal(scale, true);
@@ -2068,7 +3406,27 @@ impl MediaViewer {
 
     /// Fit the image to the window
     pub fn fit_to_window(&self) {
+        self.fit(FitMode::Window);
+    }
+
+    /// Fit the image width to the window, starting at the top
+    pub fn fit_to_width(&self) {
+        self.fit(FitMode::Width);
+    }
+
+    /// Fit the image height to the window, starting at the left
+    pub fn fit_to_height(&self) {
+        self.fit(FitMode::Height);
+    }
+
+    /// Re-apply the last chosen fit
+    fn apply_fit(&self) {
+        self.fit(self.imp().fit_mode.get());
+    }
+
+    fn fit(&self, mode: FitMode) {
         let imp = self.imp();
+        imp.fit_mode.set(mode);
         if imp.is_video.get() {
             self.update_video_layout();
             return;
@@ -2079,23 +3437,115 @@ impl MediaViewer {
             let img_h = imp.image_height.get() as f64;
 
             if img_w > 0.0 && img_h > 0.0 && viewport_w > 0.0 && viewport_h > 0.0 {
-                let scale_w = viewport_w / img_w;
-                let scale_h = viewport_h / img_h;
-                let scale = scale_w.min(scale_h).min(1.0); // Don't upscale beyond 1:1
+                let scale = fit_scale(mode, (viewport_w, viewport_h), (img_w, img_h));
+                // Pan the overflowing axis to its top or left edge
+                let lead = |view: f64, scaled: f64| ((scaled - view) / 2.0).max(0.0);
+                let (pan_x, pan_y) = match mode {
+                    FitMode::Window => (0.0, 0.0),
+                    FitMode::Width => (0.0, lead(viewport_h, img_h * scale)),
+                    FitMode::Height => (lead(viewport_w, img_w * scale), 0.0),
+                };
 
-                imp.pan_x.set(0.0);
-                imp.pan_y.set(0.0);
+                imp.pan_x.set(pan_x);
+                imp.pan_y.set(pan_y);
+                imp.zoom_preference.set(None);
+                // Keep fitting later frames and resizes until the user zooms
+                imp.user_interacted.set(false);
                 self.set_scale_internal(scale, false);
             }
         }
     }
 
+    /// Refit (or re-place) the image when the viewer is resized.
+    fn setup_resize_observer(&self, overlay: &Overlay) {
+        let viewer_weak = self.downgrade();
+        overlay.add_tick_callback(move |overlay, _clock| {
+            let Some(viewer) = viewer_weak.upgrade() else {
+                return glib::ControlFlow::Break;
+            };
+            let imp = viewer.imp();
+            let size = (overlay.width(), overlay.height());
+            if imp.viewport_size.replace(size) != size && imp.visible.get() && !imp.is_video.get() {
+                viewer.schedule_layout_retry(imp.load_generation.get());
+            }
+            glib::ControlFlow::Continue
+        });
+    }
+
+    /// Keep panning after a drag is released, slowing by the pan friction
+    /// each frame and stopping at the image edges.
+    fn start_inertia(&self, velocity: (f64, f64)) {
+        self.stop_inertia();
+        let imp = self.imp();
+        let friction = pan_friction();
+        if imp.is_video.get() || friction <= 0.0 || velocity.0.hypot(velocity.1) < INERTIA_MIN_SPEED
+        {
+            return;
+        }
+        let Some(overlay) = imp.overlay.borrow().clone() else {
+            return;
+        };
+        let viewer_weak = self.downgrade();
+        let state = Cell::new((velocity, None::<i64>));
+        let tick_id = overlay.add_tick_callback(move |_, clock| {
+            let Some(viewer) = viewer_weak.upgrade() else {
+                return glib::ControlFlow::Break;
+            };
+            let (velocity, last_frame) = state.get();
+            let now = clock.frame_time();
+            let dt = last_frame.map_or(1.0 / 60.0, |last| (now - last) as f64 / 1_000_000.0);
+            match viewer.step_inertia(velocity, dt, friction) {
+                Some(velocity) => {
+                    state.set((velocity, Some(now)));
+                    glib::ControlFlow::Continue
+                }
+                None => {
+                    viewer.imp().inertia_tick.borrow_mut().take();
+                    glib::ControlFlow::Break
+                }
+            }
+        });
+        *imp.inertia_tick.borrow_mut() = Some(tick_id);
+    }
+
+    /// Move the pan by one frame of inertia; returns the decayed velocity, or
+    /// None once it is too slow to continue.
+    fn step_inertia(&self, velocity: (f64, f64), dt: f64, friction: f64) -> Option<(f64, f64)> {
+        let imp = self.imp();
+        if imp.is_video.get() {
+            return None;
+        }
+        let (_, _, view_w, view_h) = self.viewport_rect()?;
+        let scale = imp.scale.get();
+        let limit_x = ((imp.image_width.get() as f64 * scale - view_w) / 2.0).max(0.0);
+        let limit_y = ((imp.image_height.get() as f64 * scale - view_h) / 2.0).max(0.0);
+        let (pan_x, stopped_x) = inertia_step(imp.pan_x.get(), velocity.0 * dt, limit_x);
+        let (pan_y, stopped_y) = inertia_step(imp.pan_y.get(), velocity.1 * dt, limit_y);
+        imp.pan_x.set(pan_x);
+        imp.pan_y.set(pan_y);
+        self.update_transform();
+
+        let decay = friction.powf(dt * 60.0);
+        let velocity = (
+            if stopped_x { 0.0 } else { velocity.0 * decay },
+            if stopped_y { 0.0 } else { velocity.1 * decay },
+        );
+        (velocity.0.hypot(velocity.1) >= INERTIA_MIN_SPEED).then_some(velocity)
+    }
+
+    fn stop_inertia(&self) {
+        if let Some(tick_id) = self.imp().inertia_tick.borrow_mut().take() {
+            tick_id.remove();
+        }
+    }
+
     /// Pan by a delta
     pub fn pan_by(&self, dx: f64, dy: f64) {
         let imp = self.imp();
         if imp.is_video.get() {
             return;
         }
+        self.stop_inertia();
         imp.pan_x.set(imp.pan_x.get() + dx);
         imp.pan_y.set(imp.pan_y.get() + dy);
         imp.user_interacted.set(true);
@@ -2109,6 +3559,8 @@ impl MediaViewer {
             self.update_video_layout();
             return;
         }
+        self.update_compare_transform();
+        self.update_minimap();
 
         let fixed = imp.fixed.borrow();
         let picture = imp.picture.borrow();
@@ -2164,6 +3616,264 @@ impl MediaViewer {
         }
     }
 
+    /// Place the reference picture with the main image's scale and pan,
+    /// centred in its own pane.
+    fn update_compare_transform(&self) {
+        let imp = self.imp();
+        if imp.compare_path.borrow().is_none() {
+            return;
+        }
+        let (Some(fixed), Some(picture)) = (
+            imp.compare_fixed.borrow().clone(),
+            imp.compare_picture.borrow().clone(),
+        ) else {
+            return;
+        };
+        let Some((container_x, container_y, container_w, container_h)) = self.viewport_rect()
+        else {
+            return;
+        };
+        let scale = imp.scale.get();
+        let scaled_w = imp.compare_width.get() as f64 * scale;
+        let scaled_h = imp.compare_height.get() as f64 * scale;
+        picture.set_size_request(scaled_w.round() as i32, scaled_h.round() as i32);
+        fixed.move_(
+            &picture,
+            container_x + (container_w - scaled_w) / 2.0 + imp.pan_x.get(),
+            container_y + (container_h - scaled_h) / 2.0 + imp.pan_y.get(),
+        );
+    }
+
+    /// Show the minimap with the visible part outlined, or hide it when the
+    /// whole image fits.
+    fn update_minimap(&self) {
+        let imp = self.imp();
+        let (Some(minimap), Some(frame)) = (
+            imp.minimap.borrow().clone(),
+            imp.minimap_frame.borrow().clone(),
+        ) else {
+            return;
+        };
+        let image = (imp.image_width.get() as f64, imp.image_height.get() as f64);
+        let view = self.viewport_rect().and_then(|(_, _, width, height)| {
+            visible_fraction(
+                (width, height),
+                image,
+                imp.scale.get(),
+                (imp.pan_x.get(), imp.pan_y.get()),
+            )
+        });
+        imp.minimap_view.set(view);
+        minimap.set_visible(view.is_some());
+        if view.is_some() {
+            let longest = image.0.max(image.1);
+            minimap.set_size_request(
+                (MINIMAP_SIZE * image.0 / longest).round() as i32,
+                (MINIMAP_SIZE * image.1 / longest).round() as i32,
+            );
+            frame.queue_draw();
+        }
+    }
+
+    /// Pan so the image point under minimap position (x, y) is centred.
+    fn recenter_from_minimap(&self, x: f64, y: f64) {
+        let imp = self.imp();
+        let Some(minimap) = imp.minimap.borrow().clone() else {
+            return;
+        };
+        let (width, height) = (minimap.width() as f64, minimap.height() as f64);
+        if width <= 0.0 || height <= 0.0 {
+            return;
+        }
+        self.stop_inertia();
+        let scale = imp.scale.get();
+        let scaled_w = imp.image_width.get() as f64 * scale;
+        let scaled_h = imp.image_height.get() as f64 * scale;
+        imp.pan_x
+            .set(scaled_w * (0.5 - (x / width).clamp(0.0, 1.0)));
+        imp.pan_y
+            .set(scaled_h * (0.5 - (y / height).clamp(0.0, 1.0)));
+        imp.user_interacted.set(true);
+        self.stop_slideshow();
+        self.update_transform();
+    }
+
+    pub fn is_comparing(&self) -> bool {
+        self.imp().compare_path.borrow().is_some()
+    }
+
+    /// `C` in the viewer: leave compare mode, or mark the current image,
+    /// or compare the marked image with the current one.
+    pub fn toggle_compare(&self) {
+        let imp = self.imp();
+        if self.is_comparing() {
+            self.exit_compare();
+            return;
+        }
+        let Some(current) = imp.current_path.borrow().clone() else {
+            return;
+        };
+        let anchor = imp.compare_anchor.borrow_mut().take();
+        match anchor {
+            Some(anchor) if anchor != current => self.compare_with(&anchor),
+            _ => {
+                let name = current
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                *imp.compare_anchor.borrow_mut() = Some(current);
+                self.show_message(&format!(
+                    "> Marked {} for compare; open another image and press C",
+                    name
+                ));
+            }
+        }
+    }
+
+    /// Show `path` in a pane left of the current image, zoomed and panned
+    /// together with it, until `exit_compare`. Next/previous keep the
+    /// reference and change the right-hand image.
+    pub fn compare_with(&self, path: &Path) {
+        let imp = self.imp();
+        if imp.is_video.get() || is_video_path(path) {
+            self.show_message("> Compare works with images only");
+            return;
+        }
+        *imp.compare_path.borrow_mut() = Some(path.to_path_buf());
+        imp.compare_anchor.borrow_mut().take();
+        imp.compare_width.set(0);
+        imp.compare_height.set(0);
+        if let Some(picture) = imp.compare_picture.borrow().as_ref() {
+            picture.set_paintable(Option::<&Texture>::None);
+        }
+        if let Some(fixed) = imp.compare_fixed.borrow().as_ref() {
+            fixed.set_visible(true);
+        }
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.show_message(&format!("> Comparing with {} (Esc to exit)", name));
+        // The main pane is now half as wide; refit once it is reallocated.
+        self.schedule_layout_retry(imp.load_generation.get());
+
+        let cached = self.cache_get(path, 0);
+        if let Some(cached) = cached.as_ref() {
+            self.set_compare_texture(path, &cached.texture, cached.orig_width, cached.orig_height);
+            if cached.kind == PrefetchKind::Full {
+                return;
+            }
+        }
+
+        // Same decoders and cache as the main pane: a viewport-sized image
+        // first unless one is cached, then full resolution.
+        let (tx, rx) = async_channel::unbounded::<LoadResult>();
+        let decode_path = path.to_path_buf();
+        let viewport_target = cached.is_none().then(|| self.viewport_decode_target());
+        std::thread::spawn(move || {
+            if let Some(target) = viewport_target {
+                if let Some((data, width, height, orig_width, orig_height)) =
+                    decode_image_viewport(&decode_path, target, 0)
+                {
+                    let result = LoadResult {
+                        data,
+                        width,
+                        height,
+                        orig_width,
+                        orig_height,
+                        is_preview: true,
+                        cache_kind: PrefetchKind::Preview,
+                    };
+                    if tx.send_blocking(result).is_err() {
+                        return;
+                    }
+                }
+            }
+            if let Some((data, width, height, orig_width, orig_height)) =
+                decode_image_full(&decode_path, 0)
+            {
+                let _ = tx.send_blocking(LoadResult {
+                    data,
+                    width,
+                    height,
+                    orig_width,
+                    orig_height,
+                    is_preview: false,
+                    cache_kind: PrefetchKind::Full,
+                });
+            }
+        });
+
+        let viewer_weak = self.downgrade();
+        let path = path.to_path_buf();
+        glib::spawn_future_local(async move {
+            while let Ok(result) = rx.recv().await {
+                let Some(viewer) = viewer_weak.upgrade() else {
+                    return;
+                };
+                let Some(texture) =
+                    Self::create_texture_from_rgba(&result.data, result.width, result.height)
+                else {
+                    continue;
+                };
+                viewer.cache_insert(
+                    path.clone(),
+                    0,
+                    texture.clone(),
+                    result.width,
+                    result.height,
+                    result.orig_width,
+                    result.orig_height,
+                    result.cache_kind,
+                );
+                if viewer.imp().compare_path.borrow().as_deref() == Some(path.as_path()) {
+                    viewer.set_compare_texture(
+                        &path,
+                        &texture,
+                        result.orig_width,
+                        result.orig_height,
+                    );
+                }
+            }
+        });
+    }
+
+    fn set_compare_texture(&self, path: &Path, texture: &Texture, width: u32, height: u32) {
+        let imp = self.imp();
+        if imp.compare_path.borrow().as_deref() != Some(path) {
+            return;
+        }
+        if let Some(picture) = imp.compare_picture.borrow().as_ref() {
+            picture.set_paintable(Some(texture));
+        }
+        imp.compare_width.set(width);
+        imp.compare_height.set(height);
+        self.update_compare_transform();
+    }
+
+    /// Back to the single-image view.
+    pub fn exit_compare(&self) {
+        let imp = self.imp();
+        if imp.compare_path.borrow_mut().take().is_none() {
+            return;
+        }
+        if let Some(picture) = imp.compare_picture.borrow().as_ref() {
+            picture.set_paintable(Option::<&Texture>::None);
+        }
+        if let Some(fixed) = imp.compare_fixed.borrow().as_ref() {
+            fixed.set_visible(false);
+        }
+        if let Some(path) = imp.current_path.borrow().as_ref() {
+            let cached = self.cache_get(path, imp.manual_rotation_cw.get());
+            self.update_info_label(
+                cached.as_ref().map(|c| c.orig_width),
+                cached.as_ref().map(|c| c.orig_height),
+                imp.is_loading.get(),
+            );
+        }
+        self.schedule_layout_retry(imp.load_generation.get());
+    }
+
     // Returns the image viewport within the fixed container as (x, y, width, height).
     fn viewport_rect(&self) -> Option<(f64, f64, f64, f64)> {
         let imp = self.imp();
@@ -2196,10 +3906,13 @@ impl MediaViewer {
 
     fn set_scale_internal(&self, scale: f64, user_interacted: bool) {
         let imp = self.imp();
+        self.stop_inertia();
         let clamped = scale.clamp(MIN_SCALE, MAX_SCALE);
         imp.scale.set(clamped);
         if user_interacted {
             imp.user_interacted.set(true);
+            imp.zoom_preference.set(Some(clamped));
+            self.stop_slideshow();
         }
         self.update_transform();
         if !imp.is_video.get() {
@@ -2209,7 +3922,14 @@ impl MediaViewer {
                 imp.is_loading.get(),
             );
             if clamped > 1.0 {
-                self.schedule_full_decode(imp.load_generation.get(), 0);
+                // SVGs re-render for every zoom step, so wait for the zoom to settle.
+                let is_svg = imp
+                    .current_path
+                    .borrow()
+                    .as_deref()
+                    .is_some_and(crate::svg::is_svg_path);
+                let delay_ms = if is_svg { FULL_DECODE_IDLE_DELAY_MS } else { 0 };
+                self.schedule_full_decode(imp.load_generation.get(), delay_ms);
             }
         }
     }
@@ -2221,8 +3941,116 @@ impl Default for MediaViewer {
     }
 }
 
+/// Scale and pan that fit the image region under the rectangle `a`-`b`
+/// (Fixed coordinates) to the viewport, placing the picture the way
+/// `zoom_at_point_with_factor` does. None for a rectangle too small to be
+/// deliberate or one that misses the image.
+fn zoom_to_region(
+    viewport: (f64, f64, f64, f64),
+    image: (f64, f64),
+    scale: f64,
+    pan: (f64, f64),
+    a: (f64, f64),
+    b: (f64, f64),
+) -> Option<(f64, f64, f64)> {
+    let (container_x, container_y, container_w, container_h) = viewport;
+    if scale <= 0.0 || (a.0 - b.0).abs() < MIN_ZOOM_BAND || (a.1 - b.1).abs() < MIN_ZOOM_BAND {
+        return None;
+    }
+    let pic_x = container_x + (container_w - image.0 * scale) / 2.0 + pan.0;
+    let pic_y = container_y + (container_h - image.1 * scale) / 2.0 + pan.1;
+    let to_image_x = |x: f64| ((x - pic_x) / scale).clamp(0.0, image.0);
+    let to_image_y = |y: f64| ((y - pic_y) / scale).clamp(0.0, image.1);
+    let (x0, x1) = (to_image_x(a.0.min(b.0)), to_image_x(a.0.max(b.0)));
+    let (y0, y1) = (to_image_y(a.1.min(b.1)), to_image_y(a.1.max(b.1)));
+    if x1 - x0 < 1.0 || y1 - y0 < 1.0 {
+        return None;
+    }
+    let new_scale = (container_w / (x1 - x0))
+        .min(container_h / (y1 - y0))
+        .clamp(MIN_SCALE, MAX_SCALE);
+    // Centre the region: pan = picture position minus its centred base
+    let pan_x = new_scale * (image.0 / 2.0 - (x0 + x1) / 2.0);
+    let pan_y = new_scale * (image.1 / 2.0 - (y0 + y1) / 2.0);
+    Some((new_scale, pan_x, pan_y))
+}
+
+/// Scale that fits `image` to `viewport` as a whole or along one axis,
+/// never enlarging past 1:1.
+fn fit_scale(mode: FitMode, viewport: (f64, f64), image: (f64, f64)) -> f64 {
+    let scale_w = viewport.0 / image.0;
+    let scale_h = viewport.1 / image.1;
+    let scale = match mode {
+        FitMode::Window => scale_w.min(scale_h),
+        FitMode::Width => scale_w,
+        FitMode::Height => scale_h,
+    };
+    scale.min(1.0)
+}
+
+/// One inertia step along an axis: moves `pan` by `delta` without passing
+/// `limit` (or going further out when a drag already left it beyond).
+/// Returns the new pan and whether the bound stopped the motion.
+fn inertia_step(pan: f64, delta: f64, limit: f64) -> (f64, bool) {
+    let next = pan + delta;
+    if next.abs() <= limit || next.abs() < pan.abs() {
+        return (next, false);
+    }
+    let bounded = if pan.abs() > limit {
+        pan
+    } else {
+        next.clamp(-limit, limit)
+    };
+    (bounded, true)
+}
+
+/// Part of an image inside a viewport of the given size, as (x, y, width,
+/// height) fractions of the image, for a picture centred and then panned the
+/// way `update_transform` places it. None when the whole image fits.
+fn visible_fraction(
+    viewport: (f64, f64),
+    image: (f64, f64),
+    scale: f64,
+    pan: (f64, f64),
+) -> Option<(f64, f64, f64, f64)> {
+    let (scaled_w, scaled_h) = (image.0 * scale, image.1 * scale);
+    if scaled_w <= 0.0 || scaled_h <= 0.0 {
+        return None;
+    }
+    if scaled_w <= viewport.0 + 0.5 && scaled_h <= viewport.1 + 0.5 {
+        return None;
+    }
+    let axis = |view: f64, scaled: f64, pan: f64| {
+        // Picture edge relative to the viewport edge
+        let offset = (view - scaled) / 2.0 + pan;
+        let start = (-offset / scaled).clamp(0.0, 1.0);
+        let end = ((view - offset) / scaled).clamp(0.0, 1.0);
+        (start, end - start)
+    };
+    let (x, width) = axis(viewport.0, scaled_w, pan.0);
+    let (y, height) = axis(viewport.1, scaled_h, pan.1);
+    Some((x, y, width, height))
+}
+
 // Free functions for image decoding (can be called from any thread)
 
+fn loop_label(looping: bool) -> &'static str {
+    if looping {
+        "[LOOP ON]"
+    } else {
+        "[LOOP OFF]"
+    }
+}
+
+/// Mute button label: `[VOL 80%]`, or `[MUTED]`.
+fn volume_label(volume: f64, muted: bool) -> String {
+    if muted {
+        "[MUTED]".to_string()
+    } else {
+        format!("[VOL {:.0}%]", volume * 100.0)
+    }
+}
+
 fn format_timestamp(seconds: f64) -> String {
     let total = seconds.max(0.0).round() as i64;
     let h = total / 3600;
@@ -2241,6 +4069,9 @@ fn decode_image_downscaled(
     max_size: u32,
     extra_rotation_cw: u8,
 ) -> Option<(Vec<u8>, u32, u32, u32, u32)> {
+    if crate::svg::is_svg_path(path) {
+        return decode_svg(path, max_size, extra_rotation_cw);
+    }
     if let Some((img, orig_w, orig_h)) =
         crate::image_loader::open_embedded_jpeg_preview_with_rotation(path, extra_rotation_cw)
     {
@@ -2301,6 +4132,9 @@ fn decode_image_viewport(
     max_size: u32,
     extra_rotation_cw: u8,
 ) -> Option<(Vec<u8>, u32, u32, u32, u32)> {
+    if crate::svg::is_svg_path(path) {
+        return decode_svg(path, max_size, extra_rotation_cw);
+    }
     if let Some((img, orig_w, orig_h)) =
         crate::image_loader::open_embedded_jpeg_preview_with_rotation(path, extra_rotation_cw)
     {
@@ -2342,13 +4176,60 @@ fn decode_image_viewport(
     Some((rgba.into_raw(), out_w.max(1), out_h.max(1), orig_w, orig_h))
 }
 
-/// Decode an image at full resolution
-fn decode_image_full(path: &Path, extra_rotation_cw: u8) -> Option<(Vec<u8>, u32, u32)> {
-    let img = crate::image_loader::open_image_with_rotation(path, extra_rotation_cw).ok()?;
+/// Rasterize an SVG with its longer side at `max_size`. The reported original
+/// size is the intrinsic one, so zoom levels stay relative to 1:1.
+fn decode_svg(
+    path: &Path,
+    max_size: u32,
+    extra_rotation_cw: u8,
+) -> Option<(Vec<u8>, u32, u32, u32, u32)> {
+    let (mut orig_w, mut orig_h) = crate::svg::read_dimensions(path).ok()?;
+    if extra_rotation_cw % 2 == 1 {
+        std::mem::swap(&mut orig_w, &mut orig_h);
+    }
+    let img =
+        crate::image_loader::open_svg_with_rotation(path, max_size, extra_rotation_cw).ok()?;
     let (width, height) = img.dimensions();
     let rgba = img.to_rgba8();
+    Some((rgba.into_raw(), width.max(1), height.max(1), orig_w, orig_h))
+}
+
+/// Decode an image at full resolution, if it fits the full-decode pixel
+/// budget. The size is read from the header first, so an image over budget
+/// is never decoded and the viewer keeps showing its viewport-sized decode.
+/// Decoder limits also refuse headers that understate the real size.
+fn decode_image_full(path: &Path, extra_rotation_cw: u8) -> Option<(Vec<u8>, u32, u32, u32, u32)> {
+    let budget = full_decode_max_pixels();
+    let (header_w, header_h) = crate::image_loader::read_dimensions(path).ok()?;
+    if exceeds_decode_budget(header_w, header_h, budget) {
+        tracing::info!(
+            "Skipping full decode of {} at {}x{} ({} MP budget)",
+            path.display(),
+            header_w,
+            header_h,
+            budget / 1_000_000
+        );
+        return None;
+    }
+
+    let mut limits = image::Limits::default();
+    // Room for 16-bit RGBA at the budget
+    limits.max_alloc = Some(budget.saturating_mul(8));
+    let img = crate::image_loader::open_image_with_limits(path, extra_rotation_cw, limits).ok()?;
+    let (width, height) = img.dimensions();
+    let rgba = img.to_rgba8();
+
+    Some((
+        rgba.into_raw(),
+        width.max(1),
+        height.max(1),
+        width.max(1),
+        height.max(1),
+    ))
+}
 
-    Some((rgba.into_raw(), width.max(1), height.max(1)))
+fn exceeds_decode_budget(width: u32, height: u32, max_pixels: u64) -> bool {
+    width as u64 * height as u64 > max_pixels
 }
 
 #[cfg(test)]
@@ -2367,4 +4248,111 @@ mod tests {
         let clamped = 1.0_f64.clamp(MIN_SCALE, MAX_SCALE);
         assert_eq!(clamped, 1.0);
     }
+
+    #[test]
+    fn visible_fraction_tracks_zoom_and_pan() {
+        // 400x200 image at 1:1 in a 100x100 viewport, centred
+        assert_eq!(
+            visible_fraction((100.0, 100.0), (400.0, 200.0), 1.0, (0.0, 0.0)),
+            Some((0.375, 0.25, 0.25, 0.5))
+        );
+        // Panned right by 150px shows the left edge
+        assert_eq!(
+            visible_fraction((100.0, 100.0), (400.0, 200.0), 1.0, (150.0, 0.0)),
+            Some((0.0, 0.25, 0.25, 0.5))
+        );
+        assert_eq!(
+            visible_fraction((100.0, 100.0), (400.0, 200.0), 0.25, (0.0, 0.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn fit_scale_matches_the_chosen_axis() {
+        // 1000x4000 portrait in a 500x500 viewport
+        let (viewport, image) = ((500.0, 500.0), (1000.0, 4000.0));
+        assert_eq!(fit_scale(FitMode::Window, viewport, image), 0.125);
+        assert_eq!(fit_scale(FitMode::Width, viewport, image), 0.5);
+        assert_eq!(fit_scale(FitMode::Height, viewport, image), 0.125);
+        // Small images stay at 1:1
+        assert_eq!(fit_scale(FitMode::Width, viewport, (100.0, 50.0)), 1.0);
+    }
+
+    #[test]
+    fn full_decode_budget_counts_pixels() {
+        assert!(!exceeds_decode_budget(4000, 3000, 64_000_000));
+        // 100 MP panorama
+        assert!(exceeds_decode_budget(20000, 5000, 64_000_000));
+        assert!(!exceeds_decode_budget(8000, 8000, 64_000_000));
+        assert!(exceeds_decode_budget(8000, 8001, 64_000_000));
+    }
+
+    #[test]
+    fn inertia_stops_at_the_image_edge() {
+        assert_eq!(inertia_step(10.0, 5.0, 100.0), (15.0, false));
+        assert_eq!(inertia_step(98.0, 5.0, 100.0), (100.0, true));
+        // Already dragged past the edge: never pushed further out, but free
+        // to come back
+        assert_eq!(inertia_step(120.0, 5.0, 100.0), (120.0, true));
+        assert_eq!(inertia_step(120.0, -5.0, 100.0), (115.0, false));
+    }
+
+    #[test]
+    fn zoom_to_region_centres_the_selection() {
+        let viewport = (0.0, 0.0, 100.0, 100.0);
+        // Top-left quarter of a 100x100 image shown at 1:1
+        assert_eq!(
+            zoom_to_region(
+                viewport,
+                (100.0, 100.0),
+                1.0,
+                (0.0, 0.0),
+                (50.0, 50.0),
+                (0.0, 0.0)
+            ),
+            Some((2.0, 50.0, 50.0))
+        );
+        // Tiny rectangles and ones beside the image do nothing
+        assert_eq!(
+            zoom_to_region(
+                viewport,
+                (100.0, 100.0),
+                1.0,
+                (0.0, 0.0),
+                (0.0, 0.0),
+                (4.0, 4.0)
+            ),
+            None
+        );
+        assert_eq!(
+            zoom_to_region(
+                viewport,
+                (50.0, 50.0),
+                1.0,
+                (0.0, 0.0),
+                (0.0, 0.0),
+                (20.0, 20.0)
+            ),
+            None
+        );
+        // Never past the zoom limit
+        assert_eq!(
+            zoom_to_region(
+                viewport,
+                (100.0, 100.0),
+                8.0,
+                (0.0, 0.0),
+                (40.0, 40.0),
+                (60.0, 60.0)
+            )
+            .map(|(scale, _, _)| scale),
+            Some(MAX_SCALE)
+        );
+    }
+
+    #[test]
+    fn test_volume_label() {
+        assert_eq!(volume_label(0.8, false), "[VOL 80%]");
+        assert_eq!(volume_label(0.8, true), "[MUTED]");
+    }
 }
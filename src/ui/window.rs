@@ -1,49 +1,254 @@
 // Main window for idxd media browser
 // GTK4 ApplicationWindow with ListView, Viewer, and terminal aesthetic CSS
 
+use anyhow::Context;
 use gdk4::{Display, Rectangle};
 use gtk4::graphene;
 use gtk4::prelude::*;
 use gtk4::{
-    Align, Application, ApplicationWindow, Box as GtkBox, Button, CheckButton, CssProvider, Entry,
-    Label, Orientation, Paned, ScrolledWindow, Settings, Stack, StackTransitionType, Window,
+    Align, Application, ApplicationWindow, Box as GtkBox, Button, CheckButton, CssProvider,
+    DropDown, Entry, EventControllerKey, GestureClick, Label, Orientation, Paned, ScrolledWindow,
+    SearchEntry, Settings, SpinButton, Stack, StackTransitionType, Window,
     STYLE_PROVIDER_PRIORITY_APPLICATION,
 };
 use std::cell::{Cell, RefCell};
 use std::collections::hash_map::DefaultHasher;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::hash::{Hash, Hasher};
-use std::path::{Path, PathBuf};
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
 use std::rc::{Rc, Weak};
-use std::sync::mpsc;
 use std::time::Duration;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::runtime::Builder as TokioRuntimeBuilder;
 use walkdir::WalkDir;
 
-use super::keybindings::{Keybindings, ViewMode};
+use super::keybindings::{GridJump, Keybindings, ViewMode};
+use super::keymap::KeyMap;
 use super::list_view::MediaListView;
-use super::row_widget::{cached_row_preview_texture, reschedule_row_previews};
-use super::viewer::{MediaViewer, PrefetchItem, PrefetchKind};
-use crate::layout::JustifiedLayout;
-use crate::models::{MediaItem, MediaStore, MediaType, RowModel};
-use crate::scanner::file_scanner::{FileScanner, ScanConfig};
+use super::row_widget::{
+    cached_row_preview_texture, capture_caption, create_texture_from_rgba, forget_row_preview,
+    prioritize_row_preview, rating_stars, reschedule_row_previews, row_preview_progress,
+    set_row_capture_captions, set_row_placeholder_hashes, set_row_preview_visible_range,
+    set_row_thumbnails, take_new_row_phashes, take_new_row_placeholder_hashes, MarkGesture,
+    TileMembership,
+};
+use super::viewer::{MediaViewer, PrefetchItem, PrefetchKind, MAX_PREFETCH_MB, MIN_PREFETCH_MB};
+use crate::external_app;
+use crate::file_ops::{self, TransferMode};
+use crate::layout::{self, GridLayout, JustifiedLayout, LayoutMode, RowBreak};
+use crate::media_info;
+use crate::metadata_editor::{self, EditableField, EditableMetadata};
+use crate::models::{
+    is_video_path, LayoutMeta, LayoutRow, MaintenanceReport, MediaItem, MediaStore, MediaType,
+    RowModel, ShuffleMethod, SortKey,
+};
+use crate::scanner::file_scanner::{FileScanner, ScanConfig, ScanProgress};
+use crate::scanner::watcher::{self, DirectoryWatcher};
+use crate::thumbnails::cache::{is_current_thumbnail, update_moved_path};
+use crate::thumbnails::generator::{ThumbnailGenerator, HIDPI_THUMB_HEIGHT};
 
 const SMALL_IMAGE_MAX_PIXELS: u64 = 2_000_000;
-const PREFETCH_RADIUS: usize = 24;
-const PREFETCH_PRIORITY_COUNT: usize = 8;
+/// Items either side of the selection considered for viewer prefetch.
+const DEFAULT_PREFETCH_RADIUS: usize = 24;
+const MAX_PREFETCH_RADIUS: usize = 1000;
+/// Nearest of those actually decoded ahead into the viewer cache.
+const DEFAULT_PREFETCH_COUNT: usize = 8;
+const MAX_PREFETCH_COUNT: usize = 256;
+const PREFETCH_RADIUS_SETTING: &str = "prefetch_radius";
+const PREFETCH_COUNT_SETTING: &str = "prefetch_count";
+const PREFETCH_MB_SETTING: &str = "prefetch_mb";
 const DIALOG_MARGIN: i32 = 12;
 const SIDEBAR_WIDTH_RATIO: f32 = 0.20;
 const SIDEBAR_FALLBACK_WIDTH_PX: i32 = 240;
+const THUMB_PROGRESS_POLL_MS: u64 = 250;
+const ROW_HEIGHT_SETTING: &str = "grid_row_height";
+const DEFAULT_ROW_HEIGHT: f32 = 220.0;
+const MIN_ROW_HEIGHT: f32 = 60.0;
+const MAX_ROW_HEIGHT: f32 = 600.0;
+const ROW_HEIGHT_STEP: f32 = 20.0;
+/// At or below this row height tiles are too small to identify by eye, so
+/// file names are drawn under them.
+const CAPTION_MAX_ROW_HEIGHT: f32 = 120.0;
+const GUTTER_SETTING: &str = "grid_gutter_px";
+const CAPTURE_CAPTIONS_SETTING: &str = "grid_capture_captions";
+const MAX_GUTTER_PX: f32 = 32.0;
+const MAX_UNDO_HISTORY: usize = 32;
+const CONFIRM_DELETE_SETTING: &str = "confirm_delete";
+/// Seed of the last shuffle, so a shuffled order can be recreated.
+const SHUFFLE_SEED_SETTING: &str = "shuffle_seed";
+const SHUFFLE_SEED_LOCKED_SETTING: &str = "shuffle_seed_locked";
+const SHUFFLE_METHOD_SETTING: &str = "shuffle_method";
+/// File names listed in the delete confirmation before "and N more".
+const DELETE_SUMMARY_NAMES: usize = 5;
+const SLIDESHOW_SETTING: &str = "slideshow_interval_secs";
+const MAX_SLIDESHOW_SECS: f32 = 60.0;
+const VOLUME_SETTING: &str = "video_volume";
+const MUTED_SETTING: &str = "video_muted";
+const LOOP_SETTING: &str = "video_loop";
+/// Perceptual hashes at most this many bits apart count as duplicates.
+const DUPLICATE_MAX_HAMMING: u32 = 6;
+/// Minimum stars for the sidebar's rated source.
+const RATED_SOURCE_MIN: u8 = 3;
+/// Most recently viewed files listed by the sidebar's recent source.
+const RECENT_SOURCE_LIMIT: usize = 200;
+/// Edge of the album cover thumbnails in the sidebar, in logical pixels.
+const SIDEBAR_COVER_SIZE: i32 = 24;
+/// Deeper directory paths fold their middle segments into a `…` menu.
+const BREADCRUMB_MAX_SEGMENTS: usize = 6;
+/// A remembered grid position is dropped once the directory gained or lost
+/// more than this share of its items.
+const POSITION_MAX_DRIFT_PERCENT: usize = 10;
 
 fn default_home_dir() -> Option<PathBuf> {
     directories::UserDirs::new().map(|dirs| dirs.home_dir().to_path_buf())
 }
 
+/// Splits a command-line path into the directory to browse and, for a
+/// supported media file, the file to open in the viewer. Other files just
+/// open their directory.
+fn resolve_initial_path(path: &Path) -> (PathBuf, Option<PathBuf>) {
+    if !path.is_file() {
+        return (path.to_path_buf(), None);
+    }
+    let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) else {
+        return (PathBuf::from("."), None);
+    };
+    let supported = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .and_then(MediaType::from_extension)
+        .is_some();
+    (parent.to_path_buf(), supported.then(|| path.to_path_buf()))
+}
+
+/// Reads a numeric setting, ignoring unparsable values and clamping the rest.
+fn stored_f32_setting(store: Option<&MediaStore>, key: &str, min: f32, max: f32) -> Option<f32> {
+    store?
+        .get_setting(key)
+        .ok()
+        .flatten()?
+        .parse::<f32>()
+        .ok()
+        .filter(|v| v.is_finite())
+        .map(|v| v.clamp(min, max))
+}
+
+/// A whole-number setting from its environment variable if that parses,
+/// else from the stored value, clamped to `range`.
+fn count_setting(
+    env: Option<&str>,
+    stored: Option<&str>,
+    range: std::ops::RangeInclusive<usize>,
+) -> Option<usize> {
+    let parse = |value: &str| value.trim().parse::<usize>().ok();
+    env.and_then(parse)
+        .or_else(|| stored.and_then(parse))
+        .map(|value| value.clamp(*range.start(), *range.end()))
+}
+
+/// Finds the media file `step` positions away from `from` in `flat_paths`,
+/// skipping folders. With `wrap` the search continues from the other end.
+fn step_media_path(
+    flat_paths: &[PathBuf],
+    folders: &HashSet<PathBuf>,
+    from: &Path,
+    step: i32,
+    wrap: bool,
+) -> Option<PathBuf> {
+    let len = flat_paths.len() as i64;
+    let mut idx = flat_paths.iter().position(|p| p == from)? as i64;
+    let direction = step.signum() as i64;
+    let mut remaining = step.unsigned_abs();
+    for _ in 0..len {
+        if remaining == 0 {
+            break;
+        }
+        idx += direction;
+        if wrap {
+            idx = idx.rem_euclid(len);
+        } else if !(0..len).contains(&idx) {
+            return None;
+        }
+        if !folders.contains(&flat_paths[idx as usize]) {
+            remaining -= 1;
+        }
+    }
+    let target = &flat_paths[idx as usize];
+    (remaining == 0 && target != from).then(|| target.clone())
+}
+
 struct DirectoryScanResult {
     items: Vec<MediaItem>,
 }
 
+/// Where the grid was when a directory was left, restored on return.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct DirectoryPosition {
+    selection: (u32, u32),
+    scroll: f64,
+    item_count: usize,
+}
+
+impl DirectoryPosition {
+    /// Row and column only point at the same items while the listing is
+    /// roughly the same length.
+    fn still_applies(&self, item_count: usize) -> bool {
+        let allowed = (self.item_count * POSITION_MAX_DRIFT_PERCENT / 100).max(1);
+        self.item_count.abs_diff(item_count) <= allowed
+    }
+}
+
+/// A change `u` can revert, recording what it takes to invert it.
+#[derive(Debug, Clone, PartialEq)]
+enum UndoEntry {
+    /// Files moved to the trash.
+    Trash(Vec<PathBuf>),
+    /// Paths whose favorite flag was set to `favorite`.
+    Favorite { paths: Vec<PathBuf>, favorite: bool },
+    /// Paths that were not in the album before being added.
+    AlbumAdd { album_id: i64, paths: Vec<PathBuf> },
+    /// Paths taken out of the album.
+    AlbumRemove { album_id: i64, paths: Vec<PathBuf> },
+    /// Each changed path with the rating it had before.
+    Rating(Vec<(PathBuf, Option<u8>)>),
+}
+
+/// Keys that toggle the albums of the album picker, in list order.
+const ALBUM_ACCELERATORS: &str = "123456789bcdefghijklmnopqrstuvwxyz";
+
+/// Accelerator of the `index`-th album in the picker; later ones have none.
+/// `a` is skipped so the key that opened the picker does nothing inside it.
+fn album_accelerator(index: usize) -> Option<char> {
+    ALBUM_ACCELERATORS.chars().nth(index)
+}
+
+/// One album in the album picker popover.
+struct AlbumPickerRow {
+    album_id: i64,
+    name: String,
+    accelerator: Option<char>,
+    button: Button,
+}
+
+/// `name.jpg` for one path, `N items` for several.
+fn targets_label(paths: &[PathBuf]) -> String {
+    match paths {
+        [path] => path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("[item]")
+            .to_string(),
+        _ => format!("{} items", paths.len()),
+    }
+}
+
+/// Messages from the background directory scan, delivered in order.
+enum DirectoryScanEvent {
+    Progress(ScanProgress),
+    Finished(DirectoryScanResult),
+}
+
 struct DialogShell {
     dialog: Window,
     content: GtkBox,
@@ -54,21 +259,28 @@ struct DialogShell {
 enum ContentSource {
     Directory,
     Favorites,
+    Recent,
+    FileList,
+    Duplicates,
+    Rated { min: u8 },
     Album { id: i64, name: String },
+    Tag { name: String },
 }
 
 fn read_media_dimensions(path: &Path) -> (u32, u32) {
-    let ext = path
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or_default();
-    match MediaType::from_extension(ext) {
-        Some(MediaType::Video) => (0, 0),
-        _ => crate::image_loader::read_dimensions(path).unwrap_or((1920, 1080)),
+    if is_video_path(path) {
+        return (0, 0);
     }
+    crate::image_loader::read_dimensions(path).unwrap_or((1920, 1080))
 }
 
-fn scan_directory(path: &Path, recursive: bool) -> DirectoryScanResult {
+fn scan_directory(
+    path: &Path,
+    recursive: bool,
+    sort_key: SortKey,
+    sort_seed: u64,
+    on_progress: impl Fn(ScanProgress) + Send + 'static,
+) -> DirectoryScanResult {
     let mut folders: Vec<MediaItem> = Vec::new();
     let mut media_items: Vec<MediaItem> = Vec::new();
 
@@ -89,7 +301,9 @@ fn scan_directory(path: &Path, recursive: bool) -> DirectoryScanResult {
                 if !recursive {
                     let name = file_path.file_name().and_then(|n| n.to_str());
                     if name.is_some_and(|name| !name.starts_with('.')) {
-                        folders.push(MediaItem::new_folder(file_path));
+                        let mut folder = MediaItem::new_folder(file_path);
+                        folder.mtime = file_mtime(&entry.metadata().ok());
+                        folders.push(folder);
                     }
                 }
             }
@@ -100,12 +314,19 @@ fn scan_directory(path: &Path, recursive: bool) -> DirectoryScanResult {
         let store = MediaStore::open_default()?;
         let scanner = FileScanner::with_config(ScanConfig {
             recursive,
+            incremental: true,
+            sort_key,
             ..ScanConfig::default()
         });
         let runtime = TokioRuntimeBuilder::new_current_thread()
             .enable_all()
             .build()?;
-        let (items, _) = runtime.block_on(scanner.scan_directory(path, store))?;
+        let (items, result) =
+            runtime.block_on(scanner.scan_directory_with_callback(path, store, on_progress))?;
+        if !result.removed_paths.is_empty() {
+            let pruned = MediaStore::open_default()?.delete_media_batch(&result.removed_paths)?;
+            tracing::debug!("Pruned {} deleted files from the media store", pruned);
+        }
         Ok(items)
     })();
 
@@ -141,16 +362,168 @@ fn scan_directory(path: &Path, recursive: bool) -> DirectoryScanResult {
         }
     }
 
-    folders.sort_by(|a, b| a.path.cmp(&b.path));
-    media_items.sort_by(|a, b| a.path.cmp(&b.path));
-
     let mut items: Vec<MediaItem> = Vec::with_capacity(folders.len() + media_items.len());
     items.extend(folders);
     items.extend(media_items);
+    sort_key.sort_items(&mut items, sort_seed);
 
     DirectoryScanResult { items }
 }
 
+/// Tags carried by every one of `paths`.
+fn common_tags(store: &MediaStore, paths: &[PathBuf]) -> anyhow::Result<Vec<String>> {
+    let mut common: Option<Vec<String>> = None;
+    for path in paths {
+        let tags = store.tags_for_path(path)?;
+        common = Some(match common {
+            Some(common) => common
+                .into_iter()
+                .filter(|tag| tags.contains(tag))
+                .collect(),
+            None => tags,
+        });
+    }
+    Ok(common.unwrap_or_default())
+}
+
+/// Splits quick tag entry text into names to add and names to remove
+/// (written with a leading `-`). Empty names are dropped.
+fn parse_tag_input(text: &str) -> (Vec<String>, Vec<String>) {
+    let mut add = Vec::new();
+    let mut remove = Vec::new();
+    for part in text.split(',') {
+        let part = part.trim();
+        match part.strip_prefix('-') {
+            Some(name) if !name.trim().is_empty() => remove.push(name.trim().to_string()),
+            Some(_) => {}
+            None if !part.is_empty() => add.push(part.to_string()),
+            None => {}
+        }
+    }
+    (add, remove)
+}
+
+/// Lays out each run of items sharing a duplicate group on its own rows, so
+/// a cluster never shares a row with the next one.
+fn rows_per_group<F>(
+    items: &[MediaItem],
+    groups: &HashMap<PathBuf, usize>,
+    compute: F,
+) -> Vec<RowModel>
+where
+    F: Fn(&[MediaItem]) -> Vec<RowModel>,
+{
+    let mut rows = Vec::new();
+    let mut start = 0;
+    while start < items.len() {
+        let group = groups.get(&items[start].path);
+        let len = items[start..]
+            .iter()
+            .take_while(|item| groups.get(&item.path) == group)
+            .count();
+        for mut row in compute(&items[start..start + len]) {
+            row.row_index = rows.len() as u32;
+            rows.push(row);
+        }
+        start += len;
+    }
+    rows
+}
+
+/// Splits `path` into clickable segments, root first, each paired with the
+/// directory it leads to.
+fn breadcrumb_segments(path: &Path) -> Vec<(String, PathBuf)> {
+    let mut prefix = PathBuf::new();
+    path.components()
+        .map(|component| {
+            prefix.push(component);
+            let label = match component {
+                Component::RootDir => "/".to_string(),
+                other => other.as_os_str().to_string_lossy().into_owned(),
+            };
+            (label, prefix.clone())
+        })
+        .collect()
+}
+
+/// Segment indices hidden behind the `…` menu: everything between the root
+/// and the last few segments once a path has too many to show.
+fn collapsed_breadcrumbs(len: usize) -> Range<usize> {
+    if len <= BREADCRUMB_MAX_SEGMENTS {
+        0..0
+    } else {
+        1..len - (BREADCRUMB_MAX_SEGMENTS - 2)
+    }
+}
+
+/// Kind, resolution and file size of a grid item, e.g.
+/// `Image 4000x3000 | 2.4 MB`. Unknown dimensions or sizes are left out.
+fn describe_item(item: &MediaItem) -> String {
+    let kind = match item.media_type {
+        MediaType::Image => "Image",
+        MediaType::Video => "Video",
+        MediaType::Folder => return "Folder".to_string(),
+    };
+    let mut text = kind.to_string();
+    if item.width > 0 && item.height > 0 {
+        text.push_str(&format!(" {}x{}", item.width, item.height));
+    }
+    if item.size > 0 {
+        text.push_str(&format!(
+            " | {}",
+            media_info::format_file_size(item.size as u64)
+        ));
+    }
+    text
+}
+
+/// Question asked before trashing `paths`: the file name for one file, or a
+/// count plus the first few names for a batch.
+/// Shuffle seed typed into Settings: a number is used as is, any other text
+/// names a seed by its hash, so "beach trip" always gives the same order.
+fn shuffle_seed_from_text(text: &str) -> Option<u64> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    Some(text.parse().unwrap_or_else(|_| {
+        let mut hasher = DefaultHasher::new();
+        text.hash(&mut hasher);
+        hasher.finish()
+    }))
+}
+
+fn deletion_summary(paths: &[PathBuf]) -> String {
+    let name = |path: &PathBuf| {
+        path.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "[item]".to_string())
+    };
+    if let [path] = paths {
+        return format!("Move {} to the trash?", name(path));
+    }
+    let mut summary = format!("Move {} files to the trash?\n", paths.len());
+    for path in paths.iter().take(DELETE_SUMMARY_NAMES) {
+        summary.push_str(&format!("\n{}", name(path)));
+    }
+    if paths.len() > DELETE_SUMMARY_NAMES {
+        summary.push_str(&format!(
+            "\n…and {} more",
+            paths.len() - DELETE_SUMMARY_NAMES
+        ));
+    }
+    summary
+}
+
+fn file_mtime(metadata: &Option<std::fs::Metadata>) -> i64 {
+    metadata
+        .as_ref()
+        .and_then(|m| m.modified().ok())
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
 /// CSS for terminal aesthetic - embedded as fallback
 const FALLBACK_CSS: &str = r#"
 * {
@@ -197,6 +570,13 @@ button:hover {
     background-color: rgba(0, 255, 136, 0.08);
 }
 
+.media-item.marked {
+    border-color: #00ccff;
+    border-style: solid;
+    border-width: 3px;
+    background-color: rgba(0, 204, 255, 0.12);
+}
+
 .folder-name {
     background-color: rgba(0, 0, 0, 0.7);
     color: #00ff88;
@@ -246,8 +626,13 @@ pub struct MainWindow {
     folder_paths: RefCell<HashSet<PathBuf>>,
     flat_paths: RefCell<Vec<PathBuf>>,
     row_offsets: RefCell<Vec<usize>>,
+    /// Flat index that shift-click and visual selection extend from.
+    mark_anchor: Cell<Option<usize>>,
     status_label: Label,
+    thumb_progress_label: Label,
+    thumb_progress_watch: Cell<bool>,
     dir_label: Label,
+    breadcrumbs: GtkBox,
     parent_button: Button,
     dir_bar: GtkBox,
     sidebar_scroller: ScrolledWindow,
@@ -259,20 +644,65 @@ pub struct MainWindow {
     sidebar_width_px: Cell<i32>,
     sidebar_width_initialized: Cell<bool>,
     active_source: RefCell<ContentSource>,
+    /// Cluster index of every path shown by the Duplicates source.
+    duplicate_groups: RefCell<HashMap<PathBuf, usize>>,
     status_bar: GtkBox,
     shuffle_button: Button,
+    favorites_only_button: Button,
     ui_visible: Cell<bool>,
     last_layout_width: Cell<i32>,
     resize_relayout_pending: Cell<bool>,
     scan_generation: Cell<u64>,
     recursive_scan: Cell<bool>,
+    sort_key: Cell<SortKey>,
+    sort_seed: Cell<u64>,
+    layout_mode: Cell<LayoutMode>,
+    /// Target tile height for grid layouts, changed with +/- in the grid.
+    row_height: Cell<f32>,
+    /// Spacing between grid tiles, set from the settings dialog.
+    gutter_px: Cell<f32>,
+    prefetch_radius: Cell<usize>,
+    prefetch_count: Cell<usize>,
+    /// Overlay capture date + camera on tiles, set from the settings dialog.
+    capture_captions: Cell<bool>,
+    dir_watcher: RefCell<Option<DirectoryWatcher>>,
     prefer_dark: Cell<bool>,
+    metadata_editing: Cell<bool>,
+    /// Ask before `Del` trashes files, set from the settings dialog.
+    confirm_delete: Cell<bool>,
+    /// "Don't ask again this session" was ticked in the delete confirmation.
+    skip_delete_confirm: Cell<bool>,
+    /// A database cleanup is running in the background.
+    maintenance_running: Cell<bool>,
+    /// Folder of the last copy or move, offered again by the next one.
+    last_transfer_dir: RefCell<Option<PathBuf>>,
     shuffle_mode: Cell<bool>,
     shuffle_seed: Cell<u64>,
+    /// Turning shuffle on reuses `shuffle_seed` instead of picking a new one.
+    shuffle_seed_locked: Cell<bool>,
+    shuffle_method: Cell<ShuffleMethod>,
     base_items: RefCell<Vec<MediaItem>>,
+    /// Lowercased file-name substring; empty shows everything.
+    name_filter: RefCell<String>,
+    /// Files whose description or keywords, as written by the metadata
+    /// editor, contain `name_filter`; they pass the filter too.
+    metadata_filter_matches: RefCell<HashSet<PathBuf>>,
+    /// Favorites among `base_items` while only favorites are shown.
+    favorites_only: RefCell<Option<HashSet<PathBuf>>>,
     media_store: RefCell<Option<MediaStore>>,
     pending_viewer_target: RefCell<Option<PathBuf>>,
+    /// Files read from stdin with `--stdin`, shown as their own source.
+    file_list: RefCell<Option<Vec<PathBuf>>>,
+    /// Paths to select once the next load finishes (after an undo).
+    pending_reselect: RefCell<Vec<PathBuf>>,
+    /// Reversible changes, most recent last. Favorite, album and rating
+    /// entries are dropped when the directory changes; trash batches stay.
+    undo_history: RefCell<Vec<UndoEntry>>,
     grid_scroll_before_viewer: Cell<f64>,
+    /// Grid positions of directories visited this session.
+    directory_positions: RefCell<HashMap<PathBuf, DirectoryPosition>>,
+    /// Position to restore once the directory being loaded arrives.
+    pending_position: Cell<Option<DirectoryPosition>>,
 }
 
 impl MainWindow {
@@ -356,6 +786,16 @@ impl MainWindow {
                 continue;
             }
 
+            if !row.gutter_px.is_finite() || row.gutter_px < 0.0 {
+                row.gutter_px = 0.0;
+            }
+            let mut gutters = row.gutter_px * (row.items.len() - 1) as f32;
+            if gutters >= max_width {
+                // Tiles must stay visible even if that costs the spacing
+                row.gutter_px = 0.0;
+                gutters = 0.0;
+            }
+
             let mut total_width = 0.0f32;
             let mut row_height = 1.0f32;
 
@@ -369,13 +809,13 @@ impl MainWindow {
                 row_height = row_height.max(item.display_h + item.offset_top);
             }
 
-            if total_width > max_width {
-                let scale = (max_width / total_width).clamp(0.0, 1.0);
+            if total_width + gutters > max_width {
+                // Only the tiles shrink; gutters keep their size
+                let scale = ((max_width - gutters) / total_width).clamp(0.0, 1.0);
                 row_height = 1.0;
                 for item in &mut row.items {
                     item.display_w = (item.display_w * scale).max(1.0);
                     item.display_h = (item.display_h * scale).max(1.0);
-                    item.offset_top = (item.offset_top * scale).max(0.0);
                     row_height = row_height.max(item.display_h + item.offset_top);
                 }
             }
@@ -386,7 +826,11 @@ impl MainWindow {
         rows
     }
 
-    pub fn new(app: &Application, initial_path: Option<&Path>) -> Rc<Self> {
+    pub fn new(
+        app: &Application,
+        initial_path: Option<&Path>,
+        file_list: Option<Vec<PathBuf>>,
+    ) -> Rc<Self> {
         // Load CSS before creating widgets
         load_css();
         if let Some(settings) = Settings::default() {
@@ -427,8 +871,26 @@ impl MainWindow {
         dir_label.add_css_class("dir-label");
         dir_label.set_ellipsize(gtk4::pango::EllipsizeMode::Start);
 
+        let breadcrumbs = GtkBox::new(Orientation::Horizontal, 0);
+        breadcrumbs.set_halign(gtk4::Align::Start);
+        breadcrumbs.set_hexpand(true);
+        breadcrumbs.set_overflow(gtk4::Overflow::Hidden);
+        breadcrumbs.add_css_class("breadcrumbs");
+        breadcrumbs.set_visible(false);
+
+        let sort_labels: Vec<&str> = SortKey::ALL.iter().map(|key| key.label()).collect();
+        let sort_dropdown = DropDown::from_strings(&sort_labels);
+        sort_dropdown.set_tooltip_text(Some("Sort order"));
+
+        let filter_entry = SearchEntry::new();
+        filter_entry.set_placeholder_text(Some("Filter names"));
+        filter_entry.set_width_chars(18);
+
         dir_bar.append(&parent_button);
         dir_bar.append(&dir_label);
+        dir_bar.append(&breadcrumbs);
+        dir_bar.append(&filter_entry);
+        dir_bar.append(&sort_dropdown);
 
         // Create the media list view
         let list_view = Rc::new(MediaListView::new());
@@ -473,9 +935,14 @@ impl MainWindow {
         status_label.add_css_class("muted");
         status_bar.append(&status_label);
 
+        let thumb_progress_label = Label::new(None);
+        thumb_progress_label.add_css_class("muted");
+        thumb_progress_label.set_visible(false);
+        status_bar.append(&thumb_progress_label);
+
         // Keybinding hints
         let hints_label = Label::new(Some(
-            "[hjkl/arrows] Navigate  [Enter] View  [Esc] Back  [Space] UI  [f] Fullscreen  [o] Open  [r] Recursive  [s] Shuffle  [+] Favorite  [Del] Delete  [Right Click] Album",
+            "[hjkl/arrows] Navigate  [g/G] First/Last  [Enter] View  [Esc] Back  [Space] UI  [f] Fullscreen  [o] Open  [r] Recursive  [s] Shuffle  [+] Favorite  [0-5] Rate  [t] Tag  [Del] Delete  [Right Click] Album",
         ));
         hints_label.set_halign(gtk4::Align::End);
         hints_label.add_css_class("nav-hint");
@@ -486,6 +953,11 @@ impl MainWindow {
         shuffle_button.add_css_class("btn-nav");
         status_bar.append(&shuffle_button);
 
+        let favorites_only_button = Button::with_label("Favs only: OFF");
+        favorites_only_button.set_tooltip_text(Some("Show only favourites (Alt+F)"));
+        favorites_only_button.add_css_class("btn-nav");
+        status_bar.append(&favorites_only_button);
+
         let body_paned = Paned::new(Orientation::Horizontal);
         body_paned.set_vexpand(true);
         body_paned.set_hexpand(true);
@@ -511,9 +983,13 @@ impl MainWindow {
         // Set the stack as window content
         window.set_child(Some(&stack));
 
-        let resolved_initial_path = initial_path
-            .map(|p| p.to_path_buf())
-            .or_else(default_home_dir);
+        let (resolved_initial_path, initial_viewer_target) = match initial_path {
+            Some(path) => {
+                let (dir, target) = resolve_initial_path(path);
+                (Some(dir), target)
+            }
+            None => (default_home_dir(), None),
+        };
         let current_path = RefCell::new(resolved_initial_path.clone());
 
         let sidebar_toggle_button = Button::with_label("[sidebar: ON]");
@@ -530,7 +1006,7 @@ impl MainWindow {
         dir_bar.append(&settings_button);
 
         // Create keybindings
-        let keybindings = Rc::new(Keybindings::new());
+        let keybindings = Rc::new(Keybindings::new(KeyMap::load()));
 
         let media_store = match MediaStore::open_default() {
             Ok(store) => Some(store),
@@ -540,6 +1016,96 @@ impl MainWindow {
             }
         };
 
+        let row_height = stored_f32_setting(
+            media_store.as_ref(),
+            ROW_HEIGHT_SETTING,
+            MIN_ROW_HEIGHT,
+            MAX_ROW_HEIGHT,
+        )
+        .unwrap_or(DEFAULT_ROW_HEIGHT);
+        let gutter_px =
+            stored_f32_setting(media_store.as_ref(), GUTTER_SETTING, 0.0, MAX_GUTTER_PX)
+                .unwrap_or(0.0);
+        let capture_captions = media_store
+            .as_ref()
+            .and_then(|store| store.get_setting(CAPTURE_CAPTIONS_SETTING).ok().flatten())
+            .is_some_and(|value| value == "true");
+        let confirm_delete = media_store
+            .as_ref()
+            .and_then(|store| store.get_setting(CONFIRM_DELETE_SETTING).ok().flatten())
+            .as_deref()
+            != Some("false");
+        let shuffle_seed = media_store
+            .as_ref()
+            .and_then(|store| store.get_setting(SHUFFLE_SEED_SETTING).ok().flatten())
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0);
+        let shuffle_seed_locked = media_store
+            .as_ref()
+            .and_then(|store| {
+                store
+                    .get_setting(SHUFFLE_SEED_LOCKED_SETTING)
+                    .ok()
+                    .flatten()
+            })
+            .is_some_and(|value| value == "true");
+        let shuffle_method = media_store
+            .as_ref()
+            .and_then(|store| store.get_setting(SHUFFLE_METHOD_SETTING).ok().flatten())
+            .and_then(|value| ShuffleMethod::parse(&value))
+            .unwrap_or_default();
+        if let Some(secs) = stored_f32_setting(
+            media_store.as_ref(),
+            SLIDESHOW_SETTING,
+            1.0,
+            MAX_SLIDESHOW_SECS,
+        ) {
+            viewer.set_slideshow_interval(secs as u32);
+        }
+        // Environment variables win over the values saved from Settings
+        let prefetch_setting = |env_key: &str, key: &str, range| {
+            let stored = media_store
+                .as_ref()
+                .and_then(|store| store.get_setting(key).ok().flatten());
+            count_setting(
+                std::env::var(env_key).ok().as_deref(),
+                stored.as_deref(),
+                range,
+            )
+        };
+        let prefetch_radius = prefetch_setting(
+            "IDXD_PREFETCH_RADIUS",
+            PREFETCH_RADIUS_SETTING,
+            1..=MAX_PREFETCH_RADIUS,
+        )
+        .unwrap_or(DEFAULT_PREFETCH_RADIUS);
+        let prefetch_count = prefetch_setting(
+            "IDXD_PREFETCH_COUNT",
+            PREFETCH_COUNT_SETTING,
+            1..=MAX_PREFETCH_COUNT,
+        )
+        .unwrap_or(DEFAULT_PREFETCH_COUNT);
+        if let Some(mb) = prefetch_setting(
+            "IDXD_PREFETCH_MB",
+            PREFETCH_MB_SETTING,
+            MIN_PREFETCH_MB..=MAX_PREFETCH_MB,
+        ) {
+            viewer.set_prefetch_budget_mb(mb);
+        }
+        if let Some(volume) = stored_f32_setting(media_store.as_ref(), VOLUME_SETTING, 0.0, 1.0) {
+            let muted = media_store
+                .as_ref()
+                .and_then(|store| store.get_setting(MUTED_SETTING).ok().flatten())
+                .is_some_and(|value| value == "true");
+            viewer.restore_volume(volume as f64, muted);
+        }
+        viewer.restore_loop(
+            media_store
+                .as_ref()
+                .and_then(|store| store.get_setting(LOOP_SETTING).ok().flatten())
+                .is_some_and(|value| value == "true"),
+        );
+
         let main_window = Rc::new(Self {
             self_weak: RefCell::new(Weak::new()),
             window,
@@ -553,8 +1119,12 @@ impl MainWindow {
             folder_paths: RefCell::new(HashSet::new()),
             flat_paths: RefCell::new(Vec::new()),
             row_offsets: RefCell::new(Vec::new()),
+            mark_anchor: Cell::new(None),
             status_label,
+            thumb_progress_label,
+            thumb_progress_watch: Cell::new(false),
             dir_label,
+            breadcrumbs,
             parent_button: parent_button.clone(),
             dir_bar: dir_bar.clone(),
             sidebar_scroller: sidebar_scroller.clone(),
@@ -566,20 +1136,46 @@ impl MainWindow {
             sidebar_width_px: Cell::new(SIDEBAR_FALLBACK_WIDTH_PX),
             sidebar_width_initialized: Cell::new(false),
             active_source: RefCell::new(ContentSource::Directory),
+            duplicate_groups: RefCell::new(HashMap::new()),
             status_bar: status_bar.clone(),
             shuffle_button: shuffle_button.clone(),
+            favorites_only_button: favorites_only_button.clone(),
             ui_visible: Cell::new(true),
             last_layout_width: Cell::new(0),
             resize_relayout_pending: Cell::new(false),
             scan_generation: Cell::new(0),
             recursive_scan: Cell::new(false),
+            sort_key: Cell::new(SortKey::default()),
+            sort_seed: Cell::new(0),
+            layout_mode: Cell::new(LayoutMode::default()),
+            row_height: Cell::new(row_height),
+            gutter_px: Cell::new(gutter_px),
+            prefetch_radius: Cell::new(prefetch_radius),
+            prefetch_count: Cell::new(prefetch_count),
+            capture_captions: Cell::new(capture_captions),
+            dir_watcher: RefCell::new(None),
             prefer_dark: Cell::new(true),
+            metadata_editing: Cell::new(false),
+            confirm_delete: Cell::new(confirm_delete),
+            skip_delete_confirm: Cell::new(false),
+            maintenance_running: Cell::new(false),
+            last_transfer_dir: RefCell::new(None),
             shuffle_mode: Cell::new(false),
-            shuffle_seed: Cell::new(0),
+            shuffle_seed: Cell::new(shuffle_seed),
+            shuffle_seed_locked: Cell::new(shuffle_seed_locked),
+            shuffle_method: Cell::new(shuffle_method),
             base_items: RefCell::new(Vec::new()),
+            name_filter: RefCell::new(String::new()),
+            metadata_filter_matches: RefCell::new(HashSet::new()),
+            favorites_only: RefCell::new(None),
             media_store: RefCell::new(media_store),
-            pending_viewer_target: RefCell::new(None),
+            pending_viewer_target: RefCell::new(initial_viewer_target),
+            file_list: RefCell::new(file_list),
+            pending_reselect: RefCell::new(Vec::new()),
+            undo_history: RefCell::new(Vec::new()),
             grid_scroll_before_viewer: Cell::new(0.0),
+            directory_positions: RefCell::new(HashMap::new()),
+            pending_position: Cell::new(None),
         });
         *main_window.self_weak.borrow_mut() = Rc::downgrade(&main_window);
         main_window.rebuild_sidebar();
@@ -617,6 +1213,37 @@ impl MainWindow {
             }
         });
 
+        let window_weak = Rc::downgrade(&main_window);
+        filter_entry.connect_search_changed(move |entry| {
+            if let Some(window) = window_weak.upgrade() {
+                window.set_name_filter(&entry.text());
+            }
+        });
+
+        // Enter/Esc hand the keyboard back to the grid; Esc also clears the filter
+        let window_weak = Rc::downgrade(&main_window);
+        filter_entry.connect_activate(move |_| {
+            if let Some(window) = window_weak.upgrade() {
+                window.list_view.widget().grab_focus();
+            }
+        });
+        let window_weak = Rc::downgrade(&main_window);
+        filter_entry.connect_stop_search(move |entry| {
+            entry.set_text("");
+            if let Some(window) = window_weak.upgrade() {
+                window.list_view.widget().grab_focus();
+            }
+        });
+
+        let window_weak = Rc::downgrade(&main_window);
+        sort_dropdown.connect_selected_notify(move |dropdown| {
+            if let Some(window) = window_weak.upgrade() {
+                if let Some(&key) = SortKey::ALL.get(dropdown.selected() as usize) {
+                    window.set_sort_key(key);
+                }
+            }
+        });
+
         let window_weak = Rc::downgrade(&main_window);
         shuffle_button.connect_clicked(move |_| {
             if let Some(window) = window_weak.upgrade() {
@@ -624,6 +1251,13 @@ impl MainWindow {
             }
         });
 
+        let window_weak = Rc::downgrade(&main_window);
+        favorites_only_button.connect_clicked(move |_| {
+            if let Some(window) = window_weak.upgrade() {
+                window.toggle_favorites_only();
+            }
+        });
+
         let window_weak = Rc::downgrade(&main_window);
         sidebar_toggle_button.connect_clicked(move |_| {
             if let Some(window) = window_weak.upgrade() {
@@ -670,6 +1304,15 @@ impl MainWindow {
                 }
             });
 
+        let window_weak = Rc::downgrade(&main_window);
+        main_window
+            .list_view
+            .connect_item_marked(move |row, col, path, gesture| {
+                if let Some(window) = window_weak.upgrade() {
+                    window.mark_item(row, col, &path, gesture);
+                }
+            });
+
         let window_weak = Rc::downgrade(&main_window);
         main_window
             .list_view
@@ -681,7 +1324,9 @@ impl MainWindow {
             });
 
         // If we have an initial path, start loading it
-        if let Some(path) = resolved_initial_path.as_deref() {
+        if main_window.file_list.borrow().is_some() {
+            main_window.switch_content_source(ContentSource::FileList);
+        } else if let Some(path) = resolved_initial_path.as_deref() {
             main_window.load_directory(path);
         } else {
             main_window.set_status("> No directory specified. Use: idxd <path>");
@@ -746,7 +1391,11 @@ impl MainWindow {
         let window_weak = Rc::downgrade(self);
         self.keybindings.connect_close_viewer(move || {
             if let Some(window) = window_weak.upgrade() {
-                window.close_viewer();
+                if window.viewer.is_comparing() {
+                    window.viewer.exit_compare();
+                } else {
+                    window.close_viewer();
+                }
             }
         });
 
@@ -808,6 +1457,13 @@ impl MainWindow {
             }
         });
 
+        let window_weak = Rc::downgrade(self);
+        self.keybindings.connect_reshuffle(move || {
+            if let Some(window) = window_weak.upgrade() {
+                window.reshuffle();
+            }
+        });
+
         let window_weak = Rc::downgrade(self);
         self.keybindings.connect_toggle_favorite(move || {
             if let Some(window) = window_weak.upgrade() {
@@ -816,270 +1472,774 @@ impl MainWindow {
         });
 
         let window_weak = Rc::downgrade(self);
-        self.keybindings.connect_delete_selected(move || {
+        self.keybindings.connect_toggle_favorites_only(move || {
             if let Some(window) = window_weak.upgrade() {
-                window.delete_selected();
+                window.toggle_favorites_only();
             }
         });
 
-        // Connect open directory prompt callback
         let window_weak = Rc::downgrade(self);
-        self.keybindings.connect_open_directory(move || {
+        self.keybindings.connect_set_rating(move |stars| {
+            window_weak
+                .upgrade()
+                .is_some_and(|window| window.set_rating_selected(stars))
+        });
+
+        let window_weak = Rc::downgrade(self);
+        self.keybindings.connect_move_item(move |step| {
+            window_weak
+                .upgrade()
+                .is_some_and(|window| window.move_selected_in_album(step))
+        });
+
+        let window_weak = Rc::downgrade(self);
+        self.keybindings.connect_edit_tags(move || {
             if let Some(window) = window_weak.upgrade() {
-                window.prompt_open_directory();
+                window.edit_tags_selected();
             }
         });
 
-        // Connect viewer close callback
         let window_weak = Rc::downgrade(self);
-        self.viewer.connect_close(move || {
+        self.keybindings.connect_pick_albums(move || {
             if let Some(window) = window_weak.upgrade() {
-                window.close_viewer();
+                window.pick_albums_selected();
             }
         });
 
         let window_weak = Rc::downgrade(self);
-        self.viewer.connect_context_menu(move |path, widget, rect| {
+        self.keybindings.connect_rename(move || {
             if let Some(window) = window_weak.upgrade() {
-                window.show_album_menu(path, &widget, rect);
+                window.rename_selected();
             }
         });
 
         let window_weak = Rc::downgrade(self);
-        self.viewer.connect_toggle_favorite(move || {
+        self.keybindings.connect_pending_count(move |count| {
             if let Some(window) = window_weak.upgrade() {
-                if let Some(path) = window.viewer.current_path() {
-                    if let Some(state) = window.toggle_favorite_for_path(&path) {
-                        window.viewer.set_favorite_state(state);
-                    }
+                match count {
+                    Some(count) => window.set_status(&format!("> {}", count)),
+                    None => window.set_status("> Count cancelled"),
                 }
             }
         });
-    }
-
-    /// Handle selection change in grid
-    fn on_selection_changed(&self, row: u32, col: u32) {
-        // Update status bar
-        self.set_status(&format!("> Selected: row {} col {}", row, col));
-
-        // Scroll to make selection visible
-        self.list_view.scroll_to_row(row);
-        self.list_view.set_selection(row, col);
-    }
 
-    /// Open the viewer for a media item
-    fn open_viewer(&self, path: &Path) {
-        tracing::info!("Opening viewer for: {}", path.display());
-        self.grid_scroll_before_viewer
-            .set(self.list_view.scroll_value());
-        if let Some(texture) = cached_row_preview_texture(path) {
-            let (mut orig_w, mut orig_h) = self
-                .media_dims
-                .borrow()
-                .get(path)
-                .copied()
-                .unwrap_or((0, 0));
-            if orig_w == 0 || orig_h == 0 {
-                if let Ok((w, h)) = crate::image_loader::read_dimensions(path) {
-                    orig_w = w;
-                    orig_h = h;
-                }
+        let window_weak = Rc::downgrade(self);
+        self.keybindings.connect_open_external(move || {
+            if let Some(window) = window_weak.upgrade() {
+                window.open_current_external();
             }
-            if orig_w == 0 || orig_h == 0 {
-                orig_w = texture.width().max(1) as u32;
-                orig_h = texture.height().max(1) as u32;
+        });
+
+        let window_weak = Rc::downgrade(self);
+        self.keybindings.connect_open_location(move || {
+            if let Some(window) = window_weak.upgrade() {
+                window.open_current_location();
             }
-            self.viewer
-                .prime_preview_texture(path, &texture, orig_w, orig_h);
-        }
-        let thumbnail_path = self.thumbnail_path_for(path);
+        });
 
-        // Load first so the viewer widget is visible/ready before stack switches pages.
-        // This avoids a first-click no-op when the stack targets a hidden child.
-        self.viewer.show(path, thumbnail_path.as_deref());
-        self.viewer
-            .set_favorite_state(self.favorite_state_for_path(path).unwrap_or(false));
-        self.stack.set_visible_child_name("viewer");
+        let window_weak = Rc::downgrade(self);
+        self.keybindings.connect_reveal(move || {
+            if let Some(window) = window_weak.upgrade() {
+                window.reveal_current();
+            }
+        });
 
-        // Update status
-        let filename = path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown");
-        self.set_status(&format!("> Viewing: {}", filename));
+        let window_weak = Rc::downgrade(self);
+        self.keybindings.connect_grid_jump(move |jump| {
+            if let Some(window) = window_weak.upgrade() {
+                window.jump_to(jump);
+            }
+        });
 
-        let (row, col) = self.keybindings.selection();
-        self.prefetch_around_selection(row, col);
-    }
+        let window_weak = Rc::downgrade(self);
+        self.keybindings.connect_bookmark_directory(move || {
+            if let Some(window) = window_weak.upgrade() {
+                window.bookmark_current_directory();
+            }
+        });
 
-    fn thumbnail_path_for(&self, path: &Path) -> Option<PathBuf> {
-        if let Some(item_thumb) = self
-            .media_items
-            .borrow()
-            .iter()
-            .find(|item| item.path == path)
-            .and_then(|item| item.thumb_path.clone())
-            .filter(|thumb_path| thumb_path.exists())
-        {
-            return Some(item_thumb);
-        }
+        let window_weak = Rc::downgrade(self);
+        self.keybindings.connect_transfer_files(move |is_move| {
+            if let Some(window) = window_weak.upgrade() {
+                window.transfer_selected(if is_move {
+                    TransferMode::Move
+                } else {
+                    TransferMode::Copy
+                });
+            }
+        });
 
-        let store_ref = self.media_store.borrow();
-        let store = store_ref.as_ref()?;
-        match store.get_media(path) {
-            Ok(Some(item)) => item.thumb_path.filter(|thumb_path| thumb_path.exists()),
-            Ok(None) => None,
-            Err(err) => {
-                tracing::debug!(
-                    error = ?err,
-                    path = %path.display(),
-                    "Failed to resolve thumbnail path for viewer open"
-                );
-                None
+        let window_weak = Rc::downgrade(self);
+        self.keybindings.connect_grid_zoom(move |step| {
+            if let Some(window) = window_weak.upgrade() {
+                window.adjust_row_height(step);
             }
-        }
-    }
+        });
 
-    fn prefetch_around_selection(&self, row: u32, col: u32) {
-        let flat_paths = self.flat_paths.borrow();
-        let row_offsets = self.row_offsets.borrow();
-        let Some(start) = row_offsets.get(row as usize).cloned() else {
-            return;
-        };
-        let idx = start.saturating_add(col as usize);
-        if idx >= flat_paths.len() {
-            return;
-        }
+        let window_weak = Rc::downgrade(self);
+        self.keybindings.connect_visual_mode(move |active| {
+            if let Some(window) = window_weak.upgrade() {
+                window.set_visual_select(active);
+            }
+        });
 
-        let start_idx = idx.saturating_sub(PREFETCH_RADIUS);
-        let end_idx = (idx + PREFETCH_RADIUS + 1).min(flat_paths.len());
+        let window_weak = Rc::downgrade(self);
+        self.keybindings.connect_clear_marks(move || {
+            if let Some(window) = window_weak.upgrade() {
+                if !window.list_view.marked().is_empty() {
+                    window.set_status("> Selection cleared");
+                }
+                window.clear_marks();
+            }
+        });
 
-        let folders = self.folder_paths.borrow();
-        let mut ordered_indices: Vec<usize> = (start_idx..end_idx)
-            .filter(|i| !folders.contains(&flat_paths[*i]))
-            .collect();
-        ordered_indices.sort_by_key(|i| i.abs_diff(idx));
+        let window_weak = Rc::downgrade(self);
+        self.keybindings.connect_delete_selected(move || {
+            if let Some(window) = window_weak.upgrade() {
+                window.delete_selected();
+            }
+        });
 
-        let mut items = Vec::new();
-        for index in ordered_indices.into_iter().take(PREFETCH_PRIORITY_COUNT) {
-            items.push(PrefetchItem {
-                path: flat_paths[index].clone(),
-                kind: PrefetchKind::Preview,
-            });
-        }
+        let window_weak = Rc::downgrade(self);
+        self.keybindings.connect_undo(move || {
+            if let Some(window) = window_weak.upgrade() {
+                window.undo_last();
+            }
+        });
 
-        self.viewer.prefetch(items);
-    }
+        // Connect open directory prompt callback
+        let window_weak = Rc::downgrade(self);
+        self.keybindings.connect_open_directory(move || {
+            if let Some(window) = window_weak.upgrade() {
+                window.prompt_open_directory();
+            }
+        });
 
-    /// Close the viewer and return to grid
-    fn close_viewer(&self) {
-        tracing::info!("Closing viewer");
+        // Connect viewer close callback
+        let window_weak = Rc::downgrade(self);
+        self.viewer.connect_close(move || {
+            if let Some(window) = window_weak.upgrade() {
+                window.close_viewer();
+            }
+        });
 
-        self.viewer.hide();
-        self.stack.set_visible_child_name("grid");
-        let target_scroll = self.grid_scroll_before_viewer.get();
-        self.list_view.set_scroll_value(target_scroll);
-        let list_view = self.list_view.clone();
-        glib::idle_add_local_once(move || {
-            list_view.set_scroll_value(target_scroll);
+        let window_weak = Rc::downgrade(self);
+        self.viewer.connect_advance(move |step, wrap| {
+            window_weak
+                .upgrade()
+                .is_some_and(|window| window.step_viewer(step, wrap))
         });
-        let list_view = self.list_view.clone();
-        glib::timeout_add_local_once(Duration::from_millis(32), move || {
-            list_view.set_scroll_value(target_scroll);
+
+        let window_weak = Rc::downgrade(self);
+        self.keybindings.connect_viewer_step(move |step| {
+            if let Some(window) = window_weak.upgrade() {
+                window.navigate_viewer(step);
+            }
+        });
+
+        let window_weak = Rc::downgrade(self);
+        self.keybindings.connect_toggle_slideshow(move || {
+            if let Some(window) = window_weak.upgrade() {
+                window.viewer.toggle_slideshow();
+            }
+        });
+
+        let window_weak = Rc::downgrade(self);
+        self.keybindings.connect_copy_image(move || {
+            if let Some(window) = window_weak.upgrade() {
+                window.viewer.copy_to_clipboard();
+            }
+        });
+
+        let window_weak = Rc::downgrade(self);
+        self.keybindings.connect_save_rotation(move || {
+            if let Some(window) = window_weak.upgrade() {
+                window.viewer.request_save_rotation();
+            }
+        });
+
+        let window_weak = Rc::downgrade(self);
+        self.viewer.connect_volume_changed(move |volume, muted| {
+            if let Some(window) = window_weak.upgrade() {
+                window.save_setting(VOLUME_SETTING, &format!("{:.2}", volume));
+                window.save_setting(MUTED_SETTING, if muted { "true" } else { "false" });
+            }
+        });
+
+        let window_weak = Rc::downgrade(self);
+        self.viewer.connect_loop_changed(move |looping| {
+            if let Some(window) = window_weak.upgrade() {
+                window.save_setting(LOOP_SETTING, if looping { "true" } else { "false" });
+            }
+        });
+
+        let window_weak = Rc::downgrade(self);
+        self.keybindings.connect_toggle_loop(move || {
+            if let Some(window) = window_weak.upgrade() {
+                if window.viewer.is_video_mode() {
+                    window.viewer.toggle_loop();
+                }
+            }
+        });
+
+        let window_weak = Rc::downgrade(self);
+        self.keybindings.connect_toggle_mute(move || {
+            if let Some(window) = window_weak.upgrade() {
+                if window.viewer.is_video_mode() {
+                    window.viewer.toggle_mute();
+                }
+            }
+        });
+
+        let window_weak = Rc::downgrade(self);
+        self.viewer.connect_save_rotation(move |path, rotation_cw| {
+            if let Some(window) = window_weak.upgrade() {
+                window.save_rotation(&path, rotation_cw);
+            }
+        });
+
+        let window_weak = Rc::downgrade(self);
+        self.keybindings.connect_toggle_info_panel(move || {
+            if let Some(window) = window_weak.upgrade() {
+                window.viewer.toggle_info_panel();
+            }
+        });
+
+        let window_weak = Rc::downgrade(self);
+        self.keybindings.connect_toggle_histogram(move || {
+            if let Some(window) = window_weak.upgrade() {
+                window.viewer.toggle_histogram();
+            }
+        });
+
+        let window_weak = Rc::downgrade(self);
+        self.keybindings.connect_fit_axis(move |width| {
+            if let Some(window) = window_weak.upgrade() {
+                if width {
+                    window.viewer.fit_to_width();
+                } else {
+                    window.viewer.fit_to_height();
+                }
+            }
+        });
+
+        let window_weak = Rc::downgrade(self);
+        self.keybindings.connect_compare(move || {
+            if let Some(window) = window_weak.upgrade() {
+                window.compare_images();
+            }
+        });
+
+        let window_weak = Rc::downgrade(self);
+        self.viewer.connect_context_menu(move |path, widget, rect| {
+            if let Some(window) = window_weak.upgrade() {
+                window.show_album_menu(path, &widget, rect);
+            }
+        });
+
+        let window_weak = Rc::downgrade(self);
+        self.viewer.connect_toggle_favorite(move || {
+            if let Some(window) = window_weak.upgrade() {
+                if let Some(path) = window.viewer.current_path() {
+                    if let Some(state) = window.toggle_favorite_for_path(&path) {
+                        window.viewer.set_favorite_state(state);
+                    }
+                }
+            }
         });
-        self.update_status_for_selection();
     }
 
-    /// Toggle play/pause for video
-    fn toggle_play_pause(&self) {
-        if self.viewer.is_visible() && self.viewer.is_video_mode() {
-            self.viewer.toggle_video_play_pause();
+    /// Handle selection change in grid
+    fn on_selection_changed(&self, row: u32, col: u32) {
+        // Update status bar
+        self.set_status(&format!(
+            "> Selected: row {} col {}{}",
+            row,
+            col,
+            self.selection_details(row, col)
+        ));
+
+        // Scroll to make selection visible
+        self.list_view.scroll_to_row(row);
+        self.list_view.set_selection(row, col);
+
+        if self.keybindings.visual_mode() {
+            if let (Some(anchor), Some(index)) = (self.mark_anchor.get(), self.flat_index(row, col))
+            {
+                self.mark_range(anchor, index);
+                self.report_marked();
+            }
         }
     }
 
-    /// Toggle UI visibility
-    fn toggle_ui(&self) {
-        let visible = !self.ui_visible.get();
-        self.set_ui_visible(visible);
-        tracing::debug!("Toggle UI visibility: {}", visible);
+    fn flat_index(&self, row: u32, col: u32) -> Option<usize> {
+        let start = *self.row_offsets.borrow().get(row as usize)?;
+        let index = start + col as usize;
+        (index < self.flat_paths.borrow().len()).then_some(index)
     }
 
-    fn set_ui_visible(&self, visible: bool) {
-        self.ui_visible.set(visible);
-        self.dir_bar.set_visible(visible);
-        self.status_bar.set_visible(visible);
+    /// Selects the item a `g`/`G`/`%` jump lands on; selecting scrolls its row
+    /// into view.
+    fn jump_to(&self, jump: GridJump) {
+        let total = self.flat_paths.borrow().len();
+        let Some(index) = jump.target_index(total) else {
+            return;
+        };
+        let (row, col) = {
+            let row_offsets = self.row_offsets.borrow();
+            let row = row_offsets
+                .partition_point(|&start| start <= index)
+                .saturating_sub(1);
+            let start = row_offsets.get(row).copied().unwrap_or(0);
+            (row as u32, (index - start) as u32)
+        };
+        self.keybindings.set_selection(row, col);
+        self.set_status(&format!("> Item {} of {}", index + 1, total));
     }
 
-    fn sidebar_width_cap_px(&self) -> i32 {
-        let window_width = self.window.width();
-        if window_width > 0 {
-            ((window_width as f32) * SIDEBAR_WIDTH_RATIO).round() as i32
-        } else {
-            SIDEBAR_FALLBACK_WIDTH_PX
+    /// Apply a ctrl-click (toggle) or shift-click (range) to the multi-selection.
+    fn mark_item(&self, row: u32, col: u32, path: &Path, gesture: MarkGesture) {
+        let Some(index) = self.flat_index(row, col) else {
+            return;
+        };
+        match gesture {
+            MarkGesture::Toggle => {
+                if self.is_folder_path(path) {
+                    self.set_status("> Selection applies to files only");
+                    return;
+                }
+                let mut marked = self.list_view.marked();
+                if !marked.remove(path) {
+                    marked.insert(path.to_path_buf());
+                }
+                self.list_view.set_marked(marked);
+                self.mark_anchor.set(Some(index));
+            }
+            MarkGesture::Range => {
+                let (cur_row, cur_col) = self.keybindings.selection();
+                let anchor = self
+                    .mark_anchor
+                    .get()
+                    .or_else(|| self.flat_index(cur_row, cur_col))
+                    .unwrap_or(index);
+                self.mark_range(anchor, index);
+                self.mark_anchor.set(Some(anchor));
+            }
         }
-        .max(1)
+        self.keybindings.set_selection(row, col);
+        self.report_marked();
     }
 
-    fn clamp_sidebar_width(&self, width: i32) -> i32 {
-        let cap = self.sidebar_width_cap_px().max(1);
-        if width <= 0 {
-            cap
-        } else {
-            width.min(cap).max(1)
-        }
+    /// Replace the multi-selection with the files between two flat indices.
+    fn mark_range(&self, from: usize, to: usize) {
+        let marked: HashSet<PathBuf> = {
+            let flat_paths = self.flat_paths.borrow();
+            let end = to.max(from).min(flat_paths.len().saturating_sub(1));
+            flat_paths
+                .get(to.min(from)..=end)
+                .unwrap_or_default()
+                .iter()
+                .filter(|path| !self.is_folder_path(path))
+                .cloned()
+                .collect()
+        };
+        self.list_view.set_marked(marked);
     }
 
-    fn refresh_sidebar_layout(&self, apply_default: bool) {
-        let cap = self.sidebar_width_cap_px().max(1);
-        self.sidebar_scroller.set_max_content_width(cap);
-
-        if apply_default && !self.sidebar_width_initialized.get() {
-            self.sidebar_width_px.set(cap);
-            self.sidebar_width_initialized.set(true);
-        } else {
-            let clamped = self.clamp_sidebar_width(self.sidebar_width_px.get());
-            self.sidebar_width_px.set(clamped);
+    fn set_visual_select(&self, active: bool) {
+        if !active {
+            self.report_marked();
+            return;
         }
+        let (row, col) = self.keybindings.selection();
+        let Some(index) = self.flat_index(row, col) else {
+            self.keybindings.set_visual_mode(false);
+            return;
+        };
+        self.mark_anchor.set(Some(index));
+        self.mark_range(index, index);
+        self.report_marked();
+    }
 
-        if self.sidebar_visible.get() {
-            let current = self.body_paned.position();
-            let target = if current > 0 {
-                current
-            } else {
-                self.sidebar_width_px.get()
-            };
-            let clamped = self.clamp_sidebar_width(target);
-            self.sidebar_width_px.set(clamped);
-            if clamped != current {
-                self.body_paned.set_position(clamped);
-            }
+    fn clear_marks(&self) {
+        self.keybindings.set_visual_mode(false);
+        self.mark_anchor.set(None);
+        if !self.list_view.marked().is_empty() {
+            self.list_view.set_marked(HashSet::new());
         }
     }
 
-    fn update_sidebar_toggle_button(&self) {
-        let label = if self.sidebar_visible.get() {
-            "[sidebar: ON]"
+    fn report_marked(&self) {
+        let count = self.list_view.marked().len();
+        let visual = if self.keybindings.visual_mode() {
+            " [VISUAL]"
         } else {
-            "[sidebar: OFF]"
+            ""
         };
-        self.sidebar_toggle_button.set_label(label);
+        self.set_status(&format!(
+            "> {} item{} selected{} (Esc to clear)",
+            count,
+            if count == 1 { "" } else { "s" },
+            visual
+        ));
     }
 
-    fn toggle_sidebar(&self) {
-        let visible = !self.sidebar_visible.get();
-        self.set_sidebar_visible(visible);
+    /// Marked paths in grid order.
+    fn marked_paths(&self) -> Vec<PathBuf> {
+        let marked = self.list_view.marked();
+        if marked.is_empty() {
+            return Vec::new();
+        }
+        self.flat_paths
+            .borrow()
+            .iter()
+            .filter(|path| marked.contains(*path))
+            .cloned()
+            .collect()
     }
 
-    fn set_sidebar_visible(&self, visible: bool) {
-        if self.sidebar_visible.get() == visible {
-            return;
+    /// Paths a grid action applies to: the marked items when `path` is one
+    /// of them, otherwise just `path`.
+    fn action_targets(&self, path: &Path) -> Vec<PathBuf> {
+        let in_grid = self.stack.visible_child_name().as_deref() == Some("grid");
+        if in_grid && self.list_view.marked().contains(path) {
+            self.marked_paths()
+        } else {
+            vec![path.to_path_buf()]
         }
+    }
 
-        if visible {
-            self.sidebar_scroller.set_visible(true);
-            self.refresh_sidebar_layout(false);
-            let width = self.clamp_sidebar_width(self.sidebar_width_px.get());
-            self.sidebar_width_px.set(width);
+    /// Open the viewer for a media item
+    fn open_viewer(&self, path: &Path) {
+        tracing::info!("Opening viewer for: {}", path.display());
+        self.grid_scroll_before_viewer
+            .set(self.list_view.scroll_value());
+        if let Some(texture) = cached_row_preview_texture(path) {
+            let (mut orig_w, mut orig_h) = self
+                .media_dims
+                .borrow()
+                .get(path)
+                .copied()
+                .unwrap_or((0, 0));
+            if orig_w == 0 || orig_h == 0 {
+                if let Ok((w, h)) = crate::image_loader::read_dimensions(path) {
+                    orig_w = w;
+                    orig_h = h;
+                }
+            }
+            if orig_w == 0 || orig_h == 0 {
+                orig_w = texture.width().max(1) as u32;
+                orig_h = texture.height().max(1) as u32;
+            }
+            self.viewer
+                .prime_preview_texture(path, &texture, orig_w, orig_h);
+        }
+        let thumbnail_path = self.thumbnail_path_for(path);
+
+        // Load first so the viewer widget is visible/ready before stack switches pages.
+        // This avoids a first-click no-op when the stack targets a hidden child.
+        self.viewer.show(path, thumbnail_path.as_deref());
+        self.viewer
+            .set_favorite_state(self.favorite_state_for_path(path).unwrap_or(false));
+        self.viewer.set_rating_state(self.rating_for_path(path));
+        self.stack.set_visible_child_name("viewer");
+
+        // Update status
+        let filename = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown");
+        self.set_status(&format!("> Viewing: {}", filename));
+        self.record_view(path);
+
+        let (row, col) = self.keybindings.selection();
+        self.prefetch_around_selection(row, col);
+    }
+
+    fn record_view(&self, path: &Path) {
+        if self.is_folder_path(path) {
+            return;
+        }
+        if let Some(store) = self.media_store.borrow().as_ref() {
+            if let Err(err) = store.record_view(path, MediaStore::now()) {
+                tracing::warn!(error = ?err, "Failed to record view");
+            }
+        }
+    }
+
+    fn thumbnail_path_for(&self, path: &Path) -> Option<PathBuf> {
+        let scale_factor = self.window.scale_factor();
+        if scale_factor < 2 {
+            if let Some(item_thumb) = self
+                .media_items
+                .borrow()
+                .iter()
+                .find(|item| item.path == path)
+                .and_then(|item| item.thumb_path.clone())
+                .filter(|thumb_path| thumb_path.exists())
+            {
+                return Some(item_thumb);
+            }
+        }
+
+        let store_ref = self.media_store.borrow();
+        let store = store_ref.as_ref()?;
+        let variants = match store.get_thumbnail_variants(path) {
+            Ok(variants) => variants.unwrap_or_default(),
+            Err(err) => {
+                tracing::debug!(
+                    error = ?err,
+                    path = %path.display(),
+                    "Failed to resolve thumbnail path for viewer open"
+                );
+                return None;
+            }
+        };
+        if scale_factor >= 2 {
+            let thumb_2x = variants
+                .thumb_2x
+                .as_ref()
+                .map(|thumb| thumb.path.clone())
+                .filter(|thumb_path| thumb_path.exists())
+                .or_else(|| self.record_video_thumbnail_2x(store, path));
+            if thumb_2x.is_some() {
+                return thumb_2x;
+            }
+        }
+        variants
+            .thumb_1x
+            .map(|thumb| thumb.path)
+            .filter(|thumb_path| thumb_path.exists())
+            .or_else(|| self.record_video_thumbnail(store, path))
+    }
+
+    /// Point a video's `thumb_path_2x` at the 2x frame the grid already extracted.
+    fn record_video_thumbnail_2x(&self, store: &MediaStore, path: &Path) -> Option<PathBuf> {
+        if !ThumbnailGenerator::can_generate_video(path) {
+            return None;
+        }
+        let thumb_path =
+            crate::thumbnails::cache::thumbnail_path_for_height(path, HIDPI_THUMB_HEIGHT)
+                .filter(|thumb_path| thumb_path.exists())?;
+        let (width, height) = image::image_dimensions(&thumb_path).ok()?;
+        if let Err(err) = store.update_thumbnail_2x(path, &thumb_path, width, height) {
+            tracing::debug!(error = ?err, path = %path.display(), "Failed to record 2x video thumbnail");
+        }
+        Some(thumb_path)
+    }
+
+    /// Point a video's `thumb_path` at the frame the grid already extracted.
+    fn record_video_thumbnail(&self, store: &MediaStore, path: &Path) -> Option<PathBuf> {
+        if !ThumbnailGenerator::can_generate_video(path) {
+            return None;
+        }
+        let thumb_path = crate::thumbnails::cache::default_thumbnail_path(path)
+            .filter(|thumb_path| thumb_path.exists())?;
+        let (width, height) = image::image_dimensions(&thumb_path).ok()?;
+        if let Err(err) = store.update_thumbnail(path, &thumb_path, width, height) {
+            tracing::debug!(error = ?err, path = %path.display(), "Failed to record video thumbnail");
+        }
+        if let Some(item) = self
+            .media_items
+            .borrow_mut()
+            .iter_mut()
+            .find(|item| item.path == path)
+        {
+            item.thumb_path = Some(thumb_path.clone());
+            item.thumb_w = Some(width);
+            item.thumb_h = Some(height);
+        }
+        Some(thumb_path)
+    }
+
+    fn prefetch_around_selection(&self, row: u32, col: u32) {
+        let flat_paths = self.flat_paths.borrow();
+        let row_offsets = self.row_offsets.borrow();
+        let Some(start) = row_offsets.get(row as usize).cloned() else {
+            return;
+        };
+        let idx = start.saturating_add(col as usize);
+        if idx >= flat_paths.len() {
+            return;
+        }
+        prioritize_row_preview(&flat_paths[idx]);
+
+        let radius = self.prefetch_radius.get();
+        let start_idx = idx.saturating_sub(radius);
+        let end_idx = (idx + radius + 1).min(flat_paths.len());
+
+        let folders = self.folder_paths.borrow();
+        let mut ordered_indices: Vec<usize> = (start_idx..end_idx)
+            .filter(|i| !folders.contains(&flat_paths[*i]))
+            .collect();
+        ordered_indices.sort_by_key(|i| i.abs_diff(idx));
+
+        let mut items = Vec::new();
+        for index in ordered_indices.into_iter().take(self.prefetch_count.get()) {
+            items.push(PrefetchItem {
+                path: flat_paths[index].clone(),
+                kind: PrefetchKind::Preview,
+            });
+        }
+
+        self.viewer.prefetch(items);
+    }
+
+    /// Close the viewer and return to grid
+    fn close_viewer(&self) {
+        tracing::info!("Closing viewer");
+
+        self.viewer.hide();
+        self.stack.set_visible_child_name("grid");
+        self.restore_grid_scroll(self.grid_scroll_before_viewer.get());
+        self.update_status_for_selection();
+    }
+
+    /// Scroll the grid, again after the next layout passes in case the rows
+    /// were not allocated yet.
+    fn restore_grid_scroll(&self, target_scroll: f64) {
+        self.list_view.set_scroll_value(target_scroll);
+        let list_view = self.list_view.clone();
+        glib::idle_add_local_once(move || {
+            list_view.set_scroll_value(target_scroll);
+        });
+        let list_view = self.list_view.clone();
+        glib::timeout_add_local_once(Duration::from_millis(32), move || {
+            list_view.set_scroll_value(target_scroll);
+        });
+    }
+
+    /// Remember the grid position in the directory being left and look up
+    /// the one saved for `next`, if it was visited before.
+    fn switch_directory_position(&self, next: &Path) {
+        let current = self.current_path();
+        if current.as_deref() == Some(next) {
+            return;
+        }
+        if let Some(current) =
+            current.filter(|_| matches!(*self.active_source.borrow(), ContentSource::Directory))
+        {
+            let scroll = if self.viewer.is_visible() {
+                self.grid_scroll_before_viewer.get()
+            } else {
+                self.list_view.scroll_value()
+            };
+            let position = DirectoryPosition {
+                selection: self.keybindings.selection(),
+                scroll,
+                item_count: self.media_items.borrow().len(),
+            };
+            self.directory_positions
+                .borrow_mut()
+                .insert(current, position);
+        }
+        self.pending_position
+            .set(self.directory_positions.borrow().get(next).copied());
+    }
+
+    /// Put the grid back where it was when this directory was last left.
+    fn restore_directory_position(&self) {
+        let Some(position) = self.pending_position.take() else {
+            return;
+        };
+        if !position.still_applies(self.media_items.borrow().len()) {
+            return;
+        }
+        let (row, col) = position.selection;
+        self.keybindings.set_selection(row, col);
+        self.restore_grid_scroll(position.scroll);
+    }
+
+    /// Toggle play/pause for video
+    fn toggle_play_pause(&self) {
+        if self.viewer.is_visible() && (self.viewer.is_video_mode() || self.viewer.is_animated()) {
+            self.viewer.toggle_video_play_pause();
+        }
+    }
+
+    /// Toggle UI visibility
+    fn toggle_ui(&self) {
+        let visible = !self.ui_visible.get();
+        self.set_ui_visible(visible);
+        tracing::debug!("Toggle UI visibility: {}", visible);
+    }
+
+    fn set_ui_visible(&self, visible: bool) {
+        self.ui_visible.set(visible);
+        self.dir_bar.set_visible(visible);
+        self.status_bar.set_visible(visible);
+    }
+
+    fn sidebar_width_cap_px(&self) -> i32 {
+        let window_width = self.window.width();
+        if window_width > 0 {
+            ((window_width as f32) * SIDEBAR_WIDTH_RATIO).round() as i32
+        } else {
+            SIDEBAR_FALLBACK_WIDTH_PX
+        }
+        .max(1)
+    }
+
+    fn clamp_sidebar_width(&self, width: i32) -> i32 {
+        let cap = self.sidebar_width_cap_px().max(1);
+        if width <= 0 {
+            cap
+        } else {
+            width.min(cap).max(1)
+        }
+    }
+
+    fn refresh_sidebar_layout(&self, apply_default: bool) {
+        let cap = self.sidebar_width_cap_px().max(1);
+        self.sidebar_scroller.set_max_content_width(cap);
+
+        if apply_default && !self.sidebar_width_initialized.get() {
+            self.sidebar_width_px.set(cap);
+            self.sidebar_width_initialized.set(true);
+        } else {
+            let clamped = self.clamp_sidebar_width(self.sidebar_width_px.get());
+            self.sidebar_width_px.set(clamped);
+        }
+
+        if self.sidebar_visible.get() {
+            let current = self.body_paned.position();
+            let target = if current > 0 {
+                current
+            } else {
+                self.sidebar_width_px.get()
+            };
+            let clamped = self.clamp_sidebar_width(target);
+            self.sidebar_width_px.set(clamped);
+            if clamped != current {
+                self.body_paned.set_position(clamped);
+            }
+        }
+    }
+
+    fn update_sidebar_toggle_button(&self) {
+        let label = if self.sidebar_visible.get() {
+            "[sidebar: ON]"
+        } else {
+            "[sidebar: OFF]"
+        };
+        self.sidebar_toggle_button.set_label(label);
+    }
+
+    fn toggle_sidebar(&self) {
+        let visible = !self.sidebar_visible.get();
+        self.set_sidebar_visible(visible);
+    }
+
+    fn set_sidebar_visible(&self, visible: bool) {
+        if self.sidebar_visible.get() == visible {
+            return;
+        }
+
+        if visible {
+            self.sidebar_scroller.set_visible(true);
+            self.refresh_sidebar_layout(false);
+            let width = self.clamp_sidebar_width(self.sidebar_width_px.get());
+            self.sidebar_width_px.set(width);
             self.body_paned.set_position(width);
         } else {
             let current = self.body_paned.position();
@@ -1096,7 +2256,7 @@ impl MainWindow {
     }
 
     /// Toggle fullscreen mode for the app window
-    fn toggle_fullscreen(&self) {
+    pub fn toggle_fullscreen(&self) {
         if self.window.is_fullscreen() {
             self.window.unfullscreen();
             self.set_status("> Fullscreen: OFF");
@@ -1200,6 +2360,66 @@ impl MainWindow {
         recursive_toggle.set_active(self.recursive_scan.get());
         content.append(&recursive_toggle);
 
+        let grid_toggle = CheckButton::with_label("Uniform grid layout");
+        grid_toggle.set_active(self.layout_mode.get() == LayoutMode::Grid);
+        content.append(&grid_toggle);
+
+        let captions_toggle = CheckButton::with_label("Show capture date + camera on tiles");
+        captions_toggle.set_active(self.capture_captions.get());
+        content.append(&captions_toggle);
+
+        let gutter_row = GtkBox::new(Orientation::Horizontal, 8);
+        let gutter_label = Label::new(Some("Gutter between tiles (px)"));
+        gutter_label.set_hexpand(true);
+        gutter_label.set_xalign(0.0);
+        let gutter_spin = SpinButton::with_range(0.0, MAX_GUTTER_PX as f64, 1.0);
+        gutter_spin.set_value(self.gutter_px.get() as f64);
+        gutter_row.append(&gutter_label);
+        gutter_row.append(&gutter_spin);
+        content.append(&gutter_row);
+
+        let slideshow_row = GtkBox::new(Orientation::Horizontal, 8);
+        let slideshow_label = Label::new(Some("Slideshow interval (s)"));
+        slideshow_label.set_hexpand(true);
+        slideshow_label.set_xalign(0.0);
+        let slideshow_spin = SpinButton::with_range(1.0, MAX_SLIDESHOW_SECS as f64, 1.0);
+        slideshow_spin.set_value(self.viewer.slideshow_interval_secs() as f64);
+        slideshow_row.append(&slideshow_label);
+        slideshow_row.append(&slideshow_spin);
+        content.append(&slideshow_row);
+
+        let prefetch_spin = |label: &str, min: usize, max: usize, value: usize| {
+            let row = GtkBox::new(Orientation::Horizontal, 8);
+            let label = Label::new(Some(label));
+            label.set_hexpand(true);
+            label.set_xalign(0.0);
+            let spin = SpinButton::with_range(min as f64, max as f64, 1.0);
+            spin.set_value(value as f64);
+            row.append(&label);
+            row.append(&spin);
+            content.append(&row);
+            spin
+        };
+        let radius_spin = prefetch_spin(
+            "Viewer prefetch radius (items)",
+            1,
+            MAX_PREFETCH_RADIUS,
+            self.prefetch_radius.get(),
+        );
+        let count_spin = prefetch_spin(
+            "Viewer prefetch count (images)",
+            1,
+            MAX_PREFETCH_COUNT,
+            self.prefetch_count.get(),
+        );
+        let budget_spin = prefetch_spin(
+            "Viewer image cache (MB)",
+            MIN_PREFETCH_MB,
+            MAX_PREFETCH_MB,
+            self.viewer.prefetch_budget_mb(),
+        );
+        budget_spin.set_increments(64.0, 512.0);
+
         let ui_toggle = CheckButton::with_label("Show header + status bars");
         ui_toggle.set_active(self.ui_visible.get());
         content.append(&ui_toggle);
@@ -1208,6 +2428,80 @@ impl MainWindow {
         sidebar_toggle.set_active(self.sidebar_visible.get());
         content.append(&sidebar_toggle);
 
+        let metadata_toggle = CheckButton::with_label("Enable metadata editing (modifies files)");
+        metadata_toggle.set_active(self.metadata_editing.get());
+        content.append(&metadata_toggle);
+
+        let confirm_delete_toggle = CheckButton::with_label("Confirm before deleting files");
+        confirm_delete_toggle.set_active(self.confirm_delete.get());
+        content.append(&confirm_delete_toggle);
+
+        let seed_row = GtkBox::new(Orientation::Horizontal, 8);
+        let seed_label = Label::new(Some("Shuffle seed"));
+        seed_label.set_hexpand(true);
+        seed_label.set_xalign(0.0);
+        let seed_entry = Entry::new();
+        seed_entry.set_text(&self.shuffle_seed.get().to_string());
+        seed_entry.set_tooltip_text(Some("A number, or any name to derive one from"));
+        let seed_set_button = Button::with_label("Set");
+        let seed_copy_button = Button::with_label("Copy");
+        seed_row.append(&seed_label);
+        seed_row.append(&seed_entry);
+        seed_row.append(&seed_set_button);
+        seed_row.append(&seed_copy_button);
+        content.append(&seed_row);
+
+        let seed_lock_toggle = CheckButton::with_label("Keep this seed when turning shuffle on");
+        seed_lock_toggle.set_active(self.shuffle_seed_locked.get());
+        content.append(&seed_lock_toggle);
+
+        let method_row = GtkBox::new(Orientation::Horizontal, 8);
+        let method_label = Label::new(Some("Shuffle order"));
+        method_label.set_hexpand(true);
+        method_label.set_xalign(0.0);
+        let method_labels: Vec<&str> = ShuffleMethod::ALL
+            .iter()
+            .map(|method| method.label())
+            .collect();
+        let method_dropdown = DropDown::from_strings(&method_labels);
+        method_dropdown.set_tooltip_text(Some(
+            "Stable keeps the order when files are added or removed; \
+             Uniform makes every order equally likely",
+        ));
+        if let Some(index) = ShuffleMethod::ALL
+            .iter()
+            .position(|&method| method == self.shuffle_method.get())
+        {
+            method_dropdown.set_selected(index as u32);
+        }
+        method_row.append(&method_label);
+        method_row.append(&method_dropdown);
+        content.append(&method_row);
+
+        let collections_row = GtkBox::new(Orientation::Horizontal, 8);
+        let collections_label = Label::new(Some("Albums + favourites"));
+        collections_label.set_hexpand(true);
+        collections_label.set_xalign(0.0);
+        let export_button = Button::with_label("Export...");
+        let import_button = Button::with_label("Import...");
+        let has_store = self.media_store.borrow().is_some();
+        export_button.set_sensitive(has_store);
+        import_button.set_sensitive(has_store);
+        collections_row.append(&collections_label);
+        collections_row.append(&export_button);
+        collections_row.append(&import_button);
+        content.append(&collections_row);
+
+        let cleanup_row = GtkBox::new(Orientation::Horizontal, 8);
+        let cleanup_label = Label::new(Some("Forget deleted files and compact"));
+        cleanup_label.set_hexpand(true);
+        cleanup_label.set_xalign(0.0);
+        let cleanup_button = Button::with_label("Clean up database");
+        cleanup_button.set_sensitive(has_store && !self.maintenance_running.get());
+        cleanup_row.append(&cleanup_label);
+        cleanup_row.append(&cleanup_button);
+        content.append(&cleanup_row);
+
         let window_weak = self.self_weak.borrow().clone();
         dark_toggle.connect_toggled(move |toggle| {
             if let Some(window) = window_weak.upgrade() {
@@ -1223,19 +2517,156 @@ impl MainWindow {
         });
 
         let window_weak = self.self_weak.borrow().clone();
-        ui_toggle.connect_toggled(move |toggle| {
+        grid_toggle.connect_toggled(move |toggle| {
             if let Some(window) = window_weak.upgrade() {
-                window.set_ui_visible(toggle.is_active());
+                window.set_layout_mode(if toggle.is_active() {
+                    LayoutMode::Grid
+                } else {
+                    LayoutMode::Justified
+                });
             }
         });
 
         let window_weak = self.self_weak.borrow().clone();
-        sidebar_toggle.connect_toggled(move |toggle| {
+        captions_toggle.connect_toggled(move |toggle| {
+            if let Some(window) = window_weak.upgrade() {
+                window.set_capture_captions(toggle.is_active());
+            }
+        });
+
+        let window_weak = self.self_weak.borrow().clone();
+        gutter_spin.connect_value_changed(move |spin| {
+            if let Some(window) = window_weak.upgrade() {
+                window.set_gutter(spin.value() as f32);
+            }
+        });
+
+        let window_weak = self.self_weak.borrow().clone();
+        slideshow_spin.connect_value_changed(move |spin| {
+            if let Some(window) = window_weak.upgrade() {
+                let secs = spin.value_as_int().max(1) as u32;
+                window.viewer.set_slideshow_interval(secs);
+                window.save_setting(SLIDESHOW_SETTING, &secs.to_string());
+            }
+        });
+
+        let window_weak = self.self_weak.borrow().clone();
+        radius_spin.connect_value_changed(move |spin| {
+            if let Some(window) = window_weak.upgrade() {
+                let radius = spin.value_as_int().max(1) as usize;
+                window.prefetch_radius.set(radius);
+                window.save_setting(PREFETCH_RADIUS_SETTING, &radius.to_string());
+            }
+        });
+
+        let window_weak = self.self_weak.borrow().clone();
+        count_spin.connect_value_changed(move |spin| {
+            if let Some(window) = window_weak.upgrade() {
+                let count = spin.value_as_int().max(1) as usize;
+                window.prefetch_count.set(count);
+                window.save_setting(PREFETCH_COUNT_SETTING, &count.to_string());
+            }
+        });
+
+        let window_weak = self.self_weak.borrow().clone();
+        budget_spin.connect_value_changed(move |spin| {
+            if let Some(window) = window_weak.upgrade() {
+                let mb = spin.value_as_int().max(1) as usize;
+                window.viewer.set_prefetch_budget_mb(mb);
+                window.save_setting(PREFETCH_MB_SETTING, &mb.to_string());
+            }
+        });
+
+        let window_weak = self.self_weak.borrow().clone();
+        ui_toggle.connect_toggled(move |toggle| {
+            if let Some(window) = window_weak.upgrade() {
+                window.set_ui_visible(toggle.is_active());
+            }
+        });
+
+        let window_weak = self.self_weak.borrow().clone();
+        sidebar_toggle.connect_toggled(move |toggle| {
             if let Some(window) = window_weak.upgrade() {
                 window.set_sidebar_visible(toggle.is_active());
             }
         });
 
+        let window_weak = self.self_weak.borrow().clone();
+        metadata_toggle.connect_toggled(move |toggle| {
+            if let Some(window) = window_weak.upgrade() {
+                window.metadata_editing.set(toggle.is_active());
+            }
+        });
+
+        let window_weak = self.self_weak.borrow().clone();
+        confirm_delete_toggle.connect_toggled(move |toggle| {
+            if let Some(window) = window_weak.upgrade() {
+                window.set_confirm_delete(toggle.is_active());
+            }
+        });
+
+        let window_weak = self.self_weak.borrow().clone();
+        let apply_seed = Rc::new(move |entry: &Entry| {
+            let Some(window) = window_weak.upgrade() else {
+                return;
+            };
+            match shuffle_seed_from_text(&entry.text()) {
+                Some(seed) => window.set_shuffle_seed(seed),
+                None => window.set_status("> Shuffle seed is empty"),
+            }
+        });
+        let apply_on_click = apply_seed.clone();
+        let entry_for_set = seed_entry.clone();
+        seed_set_button.connect_clicked(move |_| apply_on_click(&entry_for_set));
+        seed_entry.connect_activate(move |entry| apply_seed(entry));
+
+        let window_weak = self.self_weak.borrow().clone();
+        seed_copy_button.connect_clicked(move |button| {
+            if let Some(window) = window_weak.upgrade() {
+                let seed = window.shuffle_seed.get().to_string();
+                button.clipboard().set_text(&seed);
+                window.set_status(&format!("> Copied shuffle seed {}", seed));
+            }
+        });
+
+        let window_weak = self.self_weak.borrow().clone();
+        seed_lock_toggle.connect_toggled(move |toggle| {
+            if let Some(window) = window_weak.upgrade() {
+                window.set_shuffle_seed_locked(toggle.is_active());
+            }
+        });
+
+        let window_weak = self.self_weak.borrow().clone();
+        method_dropdown.connect_selected_notify(move |dropdown| {
+            if let Some(window) = window_weak.upgrade() {
+                if let Some(&method) = ShuffleMethod::ALL.get(dropdown.selected() as usize) {
+                    window.set_shuffle_method(method);
+                }
+            }
+        });
+
+        let window_weak = self.self_weak.borrow().clone();
+        export_button.connect_clicked(move |_| {
+            if let Some(window) = window_weak.upgrade() {
+                window.prompt_collections_file(true);
+            }
+        });
+
+        let window_weak = self.self_weak.borrow().clone();
+        import_button.connect_clicked(move |_| {
+            if let Some(window) = window_weak.upgrade() {
+                window.prompt_collections_file(false);
+            }
+        });
+
+        let window_weak = self.self_weak.borrow().clone();
+        cleanup_button.connect_clicked(move |button| {
+            button.set_sensitive(false);
+            if let Some(window) = window_weak.upgrade() {
+                window.run_database_maintenance();
+            }
+        });
+
         let dialog_weak = dialog.downgrade();
         close_button.connect_clicked(move |_| {
             if let Some(dialog) = dialog_weak.upgrade() {
@@ -1246,6 +2677,177 @@ impl MainWindow {
         dialog.present();
     }
 
+    /// Asks for the JSON file to export albums and favourites to, or to
+    /// import them from.
+    fn prompt_collections_file(&self, export: bool) {
+        let title = if export {
+            "Export collections"
+        } else {
+            "Import collections"
+        };
+        let shell = self.build_dialog_shell(title, 520);
+        let path_label = Label::new(Some("JSON file"));
+        path_label.set_halign(Align::Start);
+        shell.content.append(&path_label);
+
+        let entry = Entry::new();
+        entry.set_hexpand(true);
+        entry.set_placeholder_text(Some("~/idxd-collections.json"));
+        entry.set_text("~/idxd-collections.json");
+        shell.content.append(&entry);
+
+        let actions = GtkBox::new(Orientation::Horizontal, 8);
+        let run_button = Button::with_label(if export { "Export" } else { "Import" });
+        let spacer = GtkBox::new(Orientation::Horizontal, 0);
+        spacer.set_hexpand(true);
+        actions.append(&spacer);
+        actions.append(&run_button);
+        shell.content.append(&actions);
+
+        let dialog = shell.dialog.clone();
+        let window_weak = self.self_weak.borrow().clone();
+        let entry_for_run = entry.clone();
+        let run = Rc::new(move || {
+            let input = entry_for_run.text().trim().to_string();
+            if input.is_empty() {
+                return;
+            }
+            if let Some(window) = window_weak.upgrade() {
+                let path = window.expand_path_input(&input);
+                if export {
+                    window.export_collections(&path);
+                } else {
+                    window.import_collections(&path);
+                }
+            }
+            dialog.close();
+        });
+
+        let run_for_button = run.clone();
+        run_button.connect_clicked(move |_| run_for_button());
+        entry.connect_activate(move |_| run());
+
+        let dialog = shell.dialog.clone();
+        shell.close_button.connect_clicked(move |_| {
+            dialog.close();
+        });
+
+        shell.dialog.set_default_widget(Some(&run_button));
+        shell.dialog.present();
+        entry.grab_focus();
+    }
+
+    fn export_collections(&self, path: &Path) {
+        let result = {
+            let store = self.media_store.borrow();
+            let Some(store) = store.as_ref() else {
+                self.set_status("> Albums unavailable (database error)");
+                return;
+            };
+            file_ops::write_file(path, |writer| store.export_collections(writer))
+        };
+        match result {
+            Ok(()) => self.set_status(&format!("> Exported collections: {}", path.display())),
+            Err(err) => {
+                tracing::warn!(error = ?err, "Failed to export collections");
+                self.set_status(&format!("> Export failed: {:#}", err));
+            }
+        }
+    }
+
+    fn import_collections(&self, path: &Path) {
+        let result = {
+            let mut store = self.media_store.borrow_mut();
+            let Some(store) = store.as_mut() else {
+                self.set_status("> Albums unavailable (database error)");
+                return;
+            };
+            std::fs::File::open(path)
+                .with_context(|| format!("Failed to open {}", path.display()))
+                .and_then(|file| store.import_collections(std::io::BufReader::new(file)))
+        };
+        let report = match result {
+            Ok(report) => report,
+            Err(err) => {
+                tracing::warn!(error = ?err, "Failed to import collections");
+                self.set_status(&format!("> Import failed: {:#}", err));
+                return;
+            }
+        };
+        for missing in &report.missing {
+            tracing::info!(path = %missing.display(), "Skipped missing file during import");
+        }
+        self.rebuild_sidebar();
+        self.refresh_visible_badges();
+        if matches!(
+            *self.active_source.borrow(),
+            ContentSource::Album { .. } | ContentSource::Favorites
+        ) {
+            self.reload_active_source();
+        }
+        let mut status = format!(
+            "> Imported {} albums: {} items, {} favourites, {} ratings",
+            report.albums, report.album_items_added, report.favorites_added, report.ratings_added
+        );
+        if !report.missing.is_empty() {
+            status.push_str(&format!(
+                " ({} missing files skipped)",
+                report.missing.len()
+            ));
+        }
+        self.set_status(&status);
+    }
+
+    /// Runs `MediaStore::maintenance` on its own connection in a worker thread,
+    /// since it stats every stored path, then compacts the file on the window's
+    /// own connection so no write waits on another connection's VACUUM.
+    fn run_database_maintenance(&self) {
+        if self.maintenance_running.replace(true) {
+            return;
+        }
+        self.set_status("> Cleaning up database...");
+        let (tx, rx) = async_channel::bounded::<anyhow::Result<MaintenanceReport>>(1);
+        std::thread::spawn(move || {
+            let result = MediaStore::open_default().and_then(|mut store| store.maintenance());
+            let _ = tx.send_blocking(result);
+        });
+
+        let window_weak = self.self_weak.borrow().clone();
+        glib::spawn_future_local(async move {
+            let Ok(result) = rx.recv().await else {
+                return;
+            };
+            let Some(window) = window_weak.upgrade() else {
+                return;
+            };
+            window.maintenance_running.set(false);
+            let result = result.and_then(|report| {
+                let mut store = window.media_store.borrow_mut();
+                let reclaimed = match store.as_mut() {
+                    Some(store) => store.compact()?,
+                    None => 0,
+                };
+                Ok((report, reclaimed))
+            });
+            match result {
+                Ok((report, reclaimed)) => {
+                    window.set_status(&format!(
+                        "> Database cleaned: {} stale entries removed, {} reclaimed",
+                        report.rows_removed(),
+                        crate::media_info::format_file_size(reclaimed)
+                    ));
+                    if report.rows_removed() > report.media_removed + report.history_removed {
+                        window.rebuild_sidebar();
+                    }
+                }
+                Err(err) => {
+                    tracing::warn!(error = ?err, "Database maintenance failed");
+                    window.set_status(&format!("> Database cleanup failed: {:#}", err));
+                }
+            }
+        });
+    }
+
     fn expand_path_input(&self, input: &str) -> PathBuf {
         if input == "~" || input.starts_with("~/") {
             if let Some(home) = default_home_dir() {
@@ -1311,17 +2913,122 @@ impl MainWindow {
         self.apply_rows(rows);
     }
 
+    fn set_layout_mode(&self, mode: LayoutMode) {
+        if self.layout_mode.replace(mode) == mode {
+            return;
+        }
+        self.relayout_keeping_selection();
+    }
+
+    /// Grow (`step > 0`) or shrink the grid tiles and re-flow the grid.
+    fn adjust_row_height(&self, step: i32) {
+        let current = self.row_height.get();
+        let height =
+            (current + step as f32 * ROW_HEIGHT_STEP).clamp(MIN_ROW_HEIGHT, MAX_ROW_HEIGHT);
+        if (height - current).abs() < f32::EPSILON {
+            return;
+        }
+        self.row_height.set(height);
+        self.save_setting(ROW_HEIGHT_SETTING, &height.to_string());
+        self.relayout_keeping_selection();
+        self.set_status(&format!("> Thumbnail height: {}px", height as i32));
+    }
+
+    fn set_gutter(&self, gutter_px: f32) {
+        let gutter_px = gutter_px.clamp(0.0, MAX_GUTTER_PX);
+        if (self.gutter_px.replace(gutter_px) - gutter_px).abs() < f32::EPSILON {
+            return;
+        }
+        self.save_setting(GUTTER_SETTING, &gutter_px.to_string());
+        self.relayout_keeping_selection();
+    }
+
+    fn set_capture_captions(&self, enabled: bool) {
+        if self.capture_captions.replace(enabled) == enabled {
+            return;
+        }
+        self.save_setting(
+            CAPTURE_CAPTIONS_SETTING,
+            if enabled { "true" } else { "false" },
+        );
+        self.register_capture_captions(&self.media_items.borrow());
+        self.relayout_keeping_selection();
+    }
+
+    fn set_confirm_delete(&self, enabled: bool) {
+        if self.confirm_delete.replace(enabled) == enabled {
+            return;
+        }
+        // Turning it back on asks again even if this session opted out
+        self.skip_delete_confirm.set(false);
+        self.save_setting(
+            CONFIRM_DELETE_SETTING,
+            if enabled { "true" } else { "false" },
+        );
+    }
+
+    /// Hands the date/camera captions of `items` to the grid, or clears them
+    /// while the overlay is off.
+    fn register_capture_captions(&self, items: &[MediaItem]) {
+        let captions = if self.capture_captions.get() {
+            items
+                .iter()
+                .filter_map(|item| {
+                    let caption =
+                        capture_caption(item.date_taken.as_deref(), item.camera.as_deref())?;
+                    Some((item.path.clone(), caption))
+                })
+                .collect()
+        } else {
+            HashMap::new()
+        };
+        set_row_capture_captions(captions);
+    }
+
+    /// Re-flow the grid, keeping the same item selected.
+    fn relayout_keeping_selection(&self) {
+        let (row, col) = self.keybindings.selection();
+        let selected = self.path_at(row, col);
+        self.recalculate_grid_layout();
+        if let Some((row, col)) = selected.and_then(|path| self.selection_for_path(&path)) {
+            self.keybindings.set_selection(row, col);
+            self.list_view.set_selection(row, col);
+        }
+    }
+
+    fn save_setting(&self, key: &str, value: &str) {
+        if let Some(store) = self.media_store.borrow().as_ref() {
+            if let Err(err) = store.set_setting(key, value) {
+                tracing::warn!(error = ?err, "Failed to persist setting {}", key);
+            }
+        }
+    }
+
     /// Update status bar for current selection
     fn update_status_for_selection(&self) {
         let (row, col) = self.keybindings.selection();
         self.set_status(&format!(
-            "> {} rows | Selected: row {} col {}",
+            "> {} rows | Selected: row {} col {}{}",
             self.list_view.row_count(),
             row,
-            col
+            col,
+            self.selection_details(row, col)
         ));
     }
 
+    /// ` | Image 4000x3000 | 2.4 MB` for the item at (row, col), or nothing
+    /// when the cell is empty.
+    fn selection_details(&self, row: u32, col: u32) -> String {
+        let Some(path) = self.path_at(row, col) else {
+            return String::new();
+        };
+        let base_items = self.base_items.borrow();
+        match base_items.iter().find(|item| item.path == path) {
+            Some(item) => format!(" | {}", describe_item(item)),
+            None => String::new(),
+        }
+    }
+
     /// Present the window
     pub fn present(&self) {
         self.window.present();
@@ -1343,6 +3050,8 @@ impl MainWindow {
     }
 
     fn update_source_header(&self) {
+        self.dir_label.set_visible(true);
+        self.breadcrumbs.set_visible(false);
         match self.active_source.borrow().clone() {
             ContentSource::Directory => {
                 let current = self.current_path.borrow().clone();
@@ -1353,6 +3062,9 @@ impl MainWindow {
                         .unwrap_or_else(|| p.display().to_string());
                     self.window.set_title(Some(&format!("idxd - {}", dir_name)));
                     self.dir_label.set_text(&format!("> {}", p.display()));
+                    self.rebuild_breadcrumbs(&p);
+                    self.dir_label.set_visible(false);
+                    self.breadcrumbs.set_visible(true);
                     self.parent_button.set_sensitive(p.parent().is_some());
                 } else {
                     self.window.set_title(Some("idxd - Media Browser"));
@@ -1365,16 +3077,138 @@ impl MainWindow {
                 self.dir_label.set_text("> Favourites");
                 self.parent_button.set_sensitive(false);
             }
+            ContentSource::FileList => {
+                self.window.set_title(Some("idxd - Piped files"));
+                self.dir_label.set_text("> Files from stdin");
+                self.parent_button.set_sensitive(false);
+            }
+            ContentSource::Recent => {
+                self.window.set_title(Some("idxd - Recent"));
+                self.dir_label.set_text("> Recently viewed");
+                self.parent_button.set_sensitive(false);
+            }
+            ContentSource::Duplicates => {
+                self.window.set_title(Some("idxd - Duplicates"));
+                self.dir_label.set_text("> Duplicates");
+                self.parent_button.set_sensitive(false);
+            }
+            ContentSource::Rated { min } => {
+                self.window
+                    .set_title(Some(&format!("idxd - {}+ Stars", min)));
+                self.dir_label.set_text(&format!("> {}+ Stars", min));
+                self.parent_button.set_sensitive(false);
+            }
             ContentSource::Album { name, .. } => {
                 self.window
                     .set_title(Some(&format!("idxd - Album: {}", name)));
                 self.dir_label.set_text(&format!("> Album: {}", name));
                 self.parent_button.set_sensitive(false);
             }
+            ContentSource::Tag { name } => {
+                self.window
+                    .set_title(Some(&format!("idxd - Tag: {}", name)));
+                self.dir_label.set_text(&format!("> Tag: {}", name));
+                self.parent_button.set_sensitive(false);
+            }
+        }
+    }
+
+    /// Fills the directory bar with one button per ancestor of `path`; the
+    /// last segment is the current directory and is not clickable.
+    fn rebuild_breadcrumbs(&self, path: &Path) {
+        while let Some(child) = self.breadcrumbs.first_child() {
+            self.breadcrumbs.remove(&child);
+        }
+        let prompt = Label::new(Some("> "));
+        prompt.add_css_class("breadcrumb-separator");
+        self.breadcrumbs.append(&prompt);
+
+        let segments = breadcrumb_segments(path);
+        let hidden = collapsed_breadcrumbs(segments.len());
+        let root_is_slash = segments.first().is_some_and(|(label, _)| label == "/");
+        for (index, (label, prefix)) in segments.iter().enumerate() {
+            if hidden.contains(&index) && index != hidden.start {
+                continue;
+            }
+            if index > 0 && !(index == 1 && root_is_slash) {
+                let separator = Label::new(Some("/"));
+                separator.add_css_class("breadcrumb-separator");
+                self.breadcrumbs.append(&separator);
+            }
+            if index == hidden.start && !hidden.is_empty() {
+                self.breadcrumbs
+                    .append(&self.breadcrumb_overflow_menu(&segments[hidden.clone()]));
+                continue;
+            }
+
+            let text = Label::new(Some(label));
+            text.set_ellipsize(gtk4::pango::EllipsizeMode::Middle);
+            text.set_max_width_chars(24);
+            if index + 1 == segments.len() {
+                text.add_css_class("breadcrumb-current");
+                text.set_tooltip_text(Some(&prefix.display().to_string()));
+                self.breadcrumbs.append(&text);
+                continue;
+            }
+            let button = Button::new();
+            button.set_child(Some(&text));
+            button.add_css_class("breadcrumb");
+            button.set_tooltip_text(Some(&prefix.display().to_string()));
+            let window_weak = self.self_weak.borrow().clone();
+            let target = prefix.clone();
+            button.connect_clicked(move |_| {
+                if let Some(window) = window_weak.upgrade() {
+                    window.load_directory(&target);
+                }
+            });
+            self.breadcrumbs.append(&button);
+        }
+    }
+
+    /// `…` button listing the ancestors folded out of the breadcrumbs.
+    fn breadcrumb_overflow_menu(&self, hidden: &[(String, PathBuf)]) -> gtk4::MenuButton {
+        let popover = gtk4::Popover::new();
+        popover.add_css_class("context-menu-popover");
+        popover.set_position(gtk4::PositionType::Bottom);
+
+        let content = GtkBox::new(Orientation::Vertical, 6);
+        content.add_css_class("context-menu");
+        content.set_margin_top(6);
+        content.set_margin_bottom(6);
+        content.set_margin_start(8);
+        content.set_margin_end(8);
+        for (_, prefix) in hidden.iter().rev() {
+            let button = Button::with_label(&prefix.display().to_string());
+            button.add_css_class("context-menu-item");
+            button.set_halign(Align::Fill);
+            let window_weak = self.self_weak.borrow().clone();
+            let popover_for_item = popover.clone();
+            let target = prefix.clone();
+            button.connect_clicked(move |_| {
+                popover_for_item.popdown();
+                if let Some(window) = window_weak.upgrade() {
+                    window.load_directory(&target);
+                }
+            });
+            content.append(&button);
         }
+        popover.set_child(Some(&content));
+
+        let menu = gtk4::MenuButton::new();
+        menu.set_label("…");
+        menu.set_tooltip_text(Some("Hidden parent directories"));
+        menu.add_css_class("breadcrumb");
+        menu.set_popover(Some(&popover));
+        menu
     }
 
     fn set_active_source(&self, source: ContentSource) {
+        if !matches!(source, ContentSource::Directory) {
+            self.dir_watcher.borrow_mut().take();
+        }
+        if *self.active_source.borrow() != source {
+            self.clear_marks();
+        }
         *self.active_source.borrow_mut() = source;
         self.update_source_header();
         self.update_sidebar_selection();
@@ -1387,7 +3221,7 @@ impl MainWindow {
         self.sidebar_buttons.borrow_mut().clear();
     }
 
-    fn add_sidebar_entry(&self, label: &str, source: ContentSource, sensitive: bool) {
+    fn add_sidebar_entry(&self, label: &str, source: ContentSource, sensitive: bool) -> Button {
         let button = Button::with_label(label);
         button.set_hexpand(true);
         button.set_halign(Align::Fill);
@@ -1401,11 +3235,82 @@ impl MainWindow {
             }
         });
         self.sidebar_items.append(&button);
-        self.sidebar_buttons.borrow_mut().push((source, button));
+        self.sidebar_buttons
+            .borrow_mut()
+            .push((source, button.clone()));
+        button
     }
 
-    fn update_sidebar_selection(&self) {
-        let active = self.active_source.borrow().clone();
+    /// Small square showing an album's cover. Uses the decoded row preview when
+    /// one is cached, otherwise decodes the disk thumbnail off the UI thread.
+    /// Empty albums and covers without a thumbnail show a placeholder glyph.
+    fn album_cover_widget(&self, store: &MediaStore, cover: Option<&Path>) -> GtkBox {
+        let slot = GtkBox::new(Orientation::Horizontal, 0);
+        slot.add_css_class("sidebar-album-cover");
+        slot.set_size_request(SIDEBAR_COVER_SIZE, SIDEBAR_COVER_SIZE);
+        slot.set_valign(Align::Center);
+
+        let show_texture = |slot: &GtkBox, texture: &gdk4::Texture| {
+            while let Some(child) = slot.first_child() {
+                slot.remove(&child);
+            }
+            let picture = gtk4::Picture::for_paintable(texture);
+            picture.set_content_fit(gtk4::ContentFit::Cover);
+            picture.set_size_request(SIDEBAR_COVER_SIZE, SIDEBAR_COVER_SIZE);
+            slot.append(&picture);
+        };
+
+        let placeholder = Label::new(Some(if cover.is_some() { "[#]" } else { "[ ]" }));
+        placeholder.add_css_class("muted");
+        slot.append(&placeholder);
+
+        let Some(cover) = cover else {
+            return slot;
+        };
+        if let Some(texture) = cached_row_preview_texture(cover) {
+            show_texture(&slot, &texture);
+            return slot;
+        }
+        let scale = self.window.scale_factor().max(1);
+        let thumbnail = match store.get_thumbnail_variants(cover) {
+            Ok(variants) => variants.and_then(|v| v.for_scale(scale).map(|t| t.path.clone())),
+            Err(err) => {
+                tracing::warn!(error = ?err, "Failed to read album cover thumbnail");
+                None
+            }
+        };
+        let Some(thumbnail) = thumbnail else {
+            return slot;
+        };
+
+        let (tx, rx) = async_channel::bounded::<Option<(Vec<u8>, u32, u32)>>(1);
+        let edge = (SIDEBAR_COVER_SIZE * scale) as u32;
+        std::thread::spawn(move || {
+            let decoded = image::open(&thumbnail).ok().map(|img| {
+                let rgba = img.thumbnail(edge * 2, edge * 2).to_rgba8();
+                let (width, height) = rgba.dimensions();
+                (rgba.into_raw(), width, height)
+            });
+            let _ = tx.send_blocking(decoded);
+        });
+        let slot_weak = slot.downgrade();
+        glib::spawn_future_local(async move {
+            let Ok(Some((data, width, height))) = rx.recv().await else {
+                return;
+            };
+            let (Some(slot), Some(texture)) = (
+                slot_weak.upgrade(),
+                create_texture_from_rgba(data, width, height),
+            ) else {
+                return;
+            };
+            show_texture(&slot, &texture);
+        });
+        slot
+    }
+
+    fn update_sidebar_selection(&self) {
+        let active = self.active_source.borrow().clone();
         for (source, button) in self.sidebar_buttons.borrow().iter() {
             if source == &active {
                 button.add_css_class("sidebar-entry-active");
@@ -1419,8 +3324,29 @@ impl MainWindow {
         self.clear_sidebar_items();
 
         self.add_sidebar_entry("Current Directory", ContentSource::Directory, true);
+        if self.file_list.borrow().is_some() {
+            self.add_sidebar_entry("Piped Files", ContentSource::FileList, true);
+        }
         let has_store = self.media_store.borrow().is_some();
         self.add_sidebar_entry("Favourites", ContentSource::Favorites, has_store);
+        self.add_sidebar_entry("Recent", ContentSource::Recent, has_store);
+        self.add_sidebar_entry(
+            &format!("{}+ Stars", RATED_SOURCE_MIN),
+            ContentSource::Rated {
+                min: RATED_SOURCE_MIN,
+            },
+            has_store,
+        );
+        self.add_sidebar_entry("Duplicates", ContentSource::Duplicates, has_store);
+
+        let sep = gtk4::Separator::new(Orientation::Horizontal);
+        self.sidebar_items.append(&sep);
+
+        let bookmarks_label = Label::new(Some("Bookmarks"));
+        bookmarks_label.set_halign(Align::Start);
+        bookmarks_label.add_css_class("label-small");
+        self.sidebar_items.append(&bookmarks_label);
+        self.add_sidebar_bookmarks();
 
         let sep = gtk4::Separator::new(Orientation::Horizontal);
         self.sidebar_items.append(&sep);
@@ -1441,12 +3367,35 @@ impl MainWindow {
                 }
                 Ok(albums) => {
                     for (album_id, name) in albums {
-                        let label = name.clone();
-                        self.add_sidebar_entry(
-                            &label,
-                            ContentSource::Album { id: album_id, name },
+                        let button = self.add_sidebar_entry(
+                            &name,
+                            ContentSource::Album {
+                                id: album_id,
+                                name: name.clone(),
+                            },
                             true,
                         );
+                        let cover = store.album_cover(album_id).unwrap_or_else(|err| {
+                            tracing::warn!(error = ?err, "Failed to read album cover");
+                            None
+                        });
+                        let entry = GtkBox::new(Orientation::Horizontal, 6);
+                        entry.append(&self.album_cover_widget(store, cover.as_deref()));
+                        let label = Label::new(Some(&name));
+                        label.set_halign(Align::Start);
+                        label.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+                        entry.append(&label);
+                        button.set_child(Some(&entry));
+                        let context_click = GestureClick::new();
+                        context_click.set_button(3);
+                        let window_weak = self.self_weak.borrow().clone();
+                        let anchor = button.clone();
+                        context_click.connect_pressed(move |_, _n, _x, _y| {
+                            if let Some(window) = window_weak.upgrade() {
+                                window.show_sidebar_album_menu(album_id, &name, &anchor);
+                            }
+                        });
+                        button.add_controller(context_click);
                     }
                 }
                 Err(err) => {
@@ -1464,9 +3413,243 @@ impl MainWindow {
             self.sidebar_items.append(&unavailable);
         }
 
+        let sep = gtk4::Separator::new(Orientation::Horizontal);
+        self.sidebar_items.append(&sep);
+
+        let tags_label = Label::new(Some("Tags"));
+        tags_label.set_halign(Align::Start);
+        tags_label.add_css_class("label-small");
+        self.sidebar_items.append(&tags_label);
+
+        let tags = store.as_ref().map(|store| store.list_tags());
+        let message = match tags {
+            Some(Ok(tags)) if !tags.is_empty() => {
+                for name in tags {
+                    let label = name.clone();
+                    self.add_sidebar_entry(&label, ContentSource::Tag { name }, true);
+                }
+                None
+            }
+            Some(Ok(_)) => Some("No tags yet"),
+            Some(Err(err)) => {
+                tracing::warn!(error = ?err, "Failed to list tags for sidebar");
+                Some("Failed to load tags")
+            }
+            None => Some("Tags unavailable"),
+        };
+        if let Some(message) = message {
+            let label = Label::new(Some(message));
+            label.set_halign(Align::Start);
+            label.add_css_class("muted");
+            self.sidebar_items.append(&label);
+        }
+
         self.update_sidebar_selection();
     }
 
+    /// Bookmarked directories; they open the directory instead of switching
+    /// to a content source, so they are not tracked in `sidebar_buttons`.
+    fn add_sidebar_bookmarks(&self) {
+        let bookmarks = self
+            .media_store
+            .borrow()
+            .as_ref()
+            .map(|store| store.list_bookmarks());
+        let bookmarks = match bookmarks {
+            Some(Ok(bookmarks)) if !bookmarks.is_empty() => bookmarks,
+            other => {
+                let message = match other {
+                    Some(Err(err)) => {
+                        tracing::warn!(error = ?err, "Failed to list bookmarks for sidebar");
+                        "Failed to load bookmarks"
+                    }
+                    None => "Bookmarks unavailable",
+                    _ => "No bookmarks yet (b)",
+                };
+                let label = Label::new(Some(message));
+                label.set_halign(Align::Start);
+                label.add_css_class("muted");
+                self.sidebar_items.append(&label);
+                return;
+            }
+        };
+
+        let count = bookmarks.len();
+        for (index, (path, label)) in bookmarks.into_iter().enumerate() {
+            let button = Button::with_label(&label);
+            button.set_hexpand(true);
+            button.set_halign(Align::Fill);
+            button.add_css_class("sidebar-entry");
+            button.set_tooltip_text(Some(&path.display().to_string()));
+            let window_weak = self.self_weak.borrow().clone();
+            let path_for_click = path.clone();
+            button.connect_clicked(move |_| {
+                if let Some(window) = window_weak.upgrade() {
+                    window.open_path(&path_for_click);
+                }
+            });
+            let context_click = GestureClick::new();
+            context_click.set_button(3);
+            let window_weak = self.self_weak.borrow().clone();
+            let anchor = button.clone();
+            context_click.connect_pressed(move |_, _n, _x, _y| {
+                if let Some(window) = window_weak.upgrade() {
+                    window.show_sidebar_bookmark_menu(&path, &label, index, count, &anchor);
+                }
+            });
+            button.add_controller(context_click);
+            self.sidebar_items.append(&button);
+        }
+    }
+
+    /// Stores the current directory as a sidebar bookmark.
+    fn bookmark_current_directory(&self) {
+        let current = match *self.active_source.borrow() {
+            ContentSource::Directory => self.current_path(),
+            _ => None,
+        };
+        let Some(path) = current else {
+            self.set_status("> Open a directory to bookmark it");
+            return;
+        };
+        let label = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        let added = {
+            let store_ref = self.media_store.borrow();
+            let Some(store) = store_ref.as_ref() else {
+                self.set_status("> Bookmarks unavailable (database error)");
+                return;
+            };
+            store.add_bookmark(&path, &label)
+        };
+        match added {
+            Ok(true) => {
+                self.rebuild_sidebar();
+                self.set_status(&format!("> Bookmarked: {}", label));
+            }
+            Ok(false) => self.set_status(&format!("> Already bookmarked: {}", label)),
+            Err(err) => {
+                tracing::warn!(error = ?err, "Failed to add bookmark");
+                self.set_status("> Failed to add bookmark");
+            }
+        }
+    }
+
+    /// Move/Remove actions for a bookmark in the sidebar.
+    fn show_sidebar_bookmark_menu(
+        &self,
+        path: &Path,
+        label: &str,
+        index: usize,
+        count: usize,
+        anchor: &Button,
+    ) {
+        let popover = gtk4::Popover::new();
+        popover.add_css_class("context-menu-popover");
+        popover.set_position(gtk4::PositionType::Right);
+        popover.set_autohide(true);
+        popover.set_parent(anchor);
+        popover.connect_closed(|popover| popover.unparent());
+
+        let content = GtkBox::new(Orientation::Vertical, 6);
+        content.add_css_class("context-menu");
+        content.set_margin_top(6);
+        content.set_margin_bottom(6);
+        content.set_margin_start(8);
+        content.set_margin_end(8);
+
+        let header = Label::new(Some(&format!("[ BOOKMARK: {} ]", label)));
+        header.set_halign(Align::Start);
+        header.add_css_class("context-menu-title");
+        content.append(&header);
+
+        let sep = gtk4::Separator::new(Orientation::Horizontal);
+        sep.add_css_class("context-menu-separator");
+        content.append(&sep);
+
+        let moves = [
+            ("Move up", index.checked_sub(1)),
+            ("Move down", (index + 1 < count).then_some(index + 1)),
+        ];
+        for (text, target) in moves {
+            let button = Button::with_label(text);
+            button.add_css_class("context-menu-item");
+            button.set_halign(Align::Fill);
+            button.set_hexpand(true);
+            button.set_sensitive(target.is_some());
+            let window_weak = self.self_weak.borrow().clone();
+            let popover_for_move = popover.clone();
+            let path = path.to_path_buf();
+            button.connect_clicked(move |_| {
+                popover_for_move.popdown();
+                if let (Some(window), Some(target)) = (window_weak.upgrade(), target) {
+                    window.move_bookmark(&path, target);
+                }
+            });
+            content.append(&button);
+        }
+
+        let remove_button = Button::with_label("Remove bookmark");
+        remove_button.add_css_class("context-menu-item");
+        remove_button.set_halign(Align::Fill);
+        remove_button.set_hexpand(true);
+        let window_weak = self.self_weak.borrow().clone();
+        let popover_for_remove = popover.clone();
+        let path_for_remove = path.to_path_buf();
+        let label_for_remove = label.to_string();
+        remove_button.connect_clicked(move |_| {
+            popover_for_remove.popdown();
+            if let Some(window) = window_weak.upgrade() {
+                window.remove_bookmark(&path_for_remove, &label_for_remove);
+            }
+        });
+        content.append(&remove_button);
+
+        popover.set_child(Some(&content));
+        popover.popup();
+    }
+
+    fn move_bookmark(&self, path: &Path, new_index: usize) {
+        let moved = {
+            let mut store_ref = self.media_store.borrow_mut();
+            let Some(store) = store_ref.as_mut() else {
+                self.set_status("> Bookmarks unavailable (database error)");
+                return;
+            };
+            store.move_bookmark(path, new_index)
+        };
+        match moved {
+            Ok(_) => self.rebuild_sidebar(),
+            Err(err) => {
+                tracing::warn!(error = ?err, "Failed to reorder bookmarks");
+                self.set_status("> Failed to reorder bookmarks");
+            }
+        }
+    }
+
+    fn remove_bookmark(&self, path: &Path, label: &str) {
+        let removed = {
+            let store_ref = self.media_store.borrow();
+            let Some(store) = store_ref.as_ref() else {
+                self.set_status("> Bookmarks unavailable (database error)");
+                return;
+            };
+            store.remove_bookmark(path)
+        };
+        match removed {
+            Ok(_) => {
+                self.rebuild_sidebar();
+                self.set_status(&format!("> Removed bookmark: {}", label));
+            }
+            Err(err) => {
+                tracing::warn!(error = ?err, "Failed to remove bookmark");
+                self.set_status("> Failed to remove bookmark");
+            }
+        }
+    }
+
     fn media_item_from_path(path: &Path) -> Option<MediaItem> {
         if path.is_dir() {
             return Some(MediaItem::new_folder(path.to_path_buf()));
@@ -1479,6 +3662,9 @@ impl MainWindow {
         let (width, height) = read_media_dimensions(path);
         let mut item = MediaItem::new(path.to_path_buf(), width, height);
         item.media_type = media_type;
+        let metadata = std::fs::metadata(path).ok();
+        item.mtime = file_mtime(&metadata);
+        item.size = metadata.map(|m| m.len() as i64).unwrap_or(0);
         Some(item)
     }
 
@@ -1521,13 +3707,47 @@ impl MainWindow {
     }
 
     fn apply_items(&self, base_items: Vec<MediaItem>) {
-        *self.base_items.borrow_mut() = base_items.clone();
-        let items = if self.shuffle_mode.get() {
-            self.shuffled_items(&base_items)
-        } else {
-            base_items
-        };
+        if self.favorites_only.borrow().is_some() {
+            let favorites = self.favorites_among(&base_items);
+            *self.favorites_only.borrow_mut() = Some(favorites);
+        }
+        let items = self.visible_items(&base_items);
+        *self.base_items.borrow_mut() = base_items;
         *self.media_items.borrow_mut() = items.clone();
+        set_row_placeholder_hashes(
+            items
+                .iter()
+                .filter_map(|item| Some((item.path.clone(), item.blurhash.clone()?))),
+        );
+        let thumbnails_2x = self
+            .media_store
+            .borrow()
+            .as_ref()
+            .and_then(|store| {
+                let paths: Vec<PathBuf> = items.iter().map(|item| item.path.clone()).collect();
+                store
+                    .thumbnails_2x_for_paths(&paths)
+                    .map_err(|err| tracing::warn!(error = ?err, "Failed to load 2x thumbnails"))
+                    .ok()
+            })
+            .unwrap_or_default();
+        set_row_thumbnails(items.iter().flat_map(|item| {
+            let thumb_1x = item
+                .thumb_path()
+                .cloned()
+                .zip(item.thumb_dimensions().map(|(_, h)| h));
+            let thumb_2x = thumbnails_2x
+                .get(&item.path)
+                .map(|info| (info.path.clone(), info.height));
+            [thumb_1x, thumb_2x]
+                .into_iter()
+                .flatten()
+                .filter(|(thumb_path, _)| {
+                    is_current_thumbnail(thumb_path, &item.path, item.mtime, item.size)
+                })
+                .map(|(thumb_path, height)| (item.path.clone(), thumb_path, height))
+        }));
+        self.register_capture_captions(&items);
 
         let mut dims = HashMap::new();
         let mut folders = HashSet::new();
@@ -1539,6 +3759,7 @@ impl MainWindow {
         }
         *self.media_dims.borrow_mut() = dims;
         *self.folder_paths.borrow_mut() = folders;
+        self.refresh_ratings(&items);
 
         let rows = self.build_rows_for_items(&items);
         self.apply_rows(rows);
@@ -1554,6 +3775,17 @@ impl MainWindow {
             }
         }
 
+        let reselect = std::mem::take(&mut *self.pending_reselect.borrow_mut());
+        if let Some((row, col)) = reselect
+            .first()
+            .and_then(|path| self.selection_for_path(path))
+        {
+            self.keybindings.set_selection(row, col);
+            if reselect.len() > 1 {
+                self.list_view.set_marked(reselect.into_iter().collect());
+            }
+        }
+
         if self.list_view.row_count() > 0 {
             let (row, col) = self.keybindings.selection();
             self.prefetch_around_selection(row, col);
@@ -1588,6 +3820,123 @@ impl MainWindow {
         ));
     }
 
+    /// Files piped in with `--stdin`, in input order. There is no directory
+    /// to navigate, like favourites.
+    fn load_file_list(&self) {
+        let paths = self.file_list.borrow().clone().unwrap_or_default();
+        let items = self.media_items_for_paths(&paths);
+        self.set_active_source(ContentSource::FileList);
+        self.apply_items(items);
+        self.finalize_loaded_items();
+        self.set_status(&format!(
+            "> Piped files: {} items | {} rows | [hjkl/arrows] Navigate  [Enter] Open",
+            self.media_items.borrow().len(),
+            self.list_view.row_count()
+        ));
+    }
+
+    /// Files opened in the viewer, most recent first. Files deleted since are
+    /// dropped by `media_items_for_paths`.
+    fn load_recent(&self) {
+        let paths = {
+            let store_ref = self.media_store.borrow();
+            let Some(store) = store_ref.as_ref() else {
+                self.set_status("> History unavailable (database error)");
+                return;
+            };
+            match store.list_recent_paths(RECENT_SOURCE_LIMIT) {
+                Ok(paths) => paths,
+                Err(err) => {
+                    tracing::warn!(error = ?err, "Failed to list recently viewed files");
+                    self.set_status("> Failed to load history");
+                    return;
+                }
+            }
+        };
+
+        let items = self.media_items_for_paths(&paths);
+        self.set_active_source(ContentSource::Recent);
+        self.apply_items(items);
+        self.finalize_loaded_items();
+        self.set_status(&format!(
+            "> Recent: {} items | {} rows | [hjkl/arrows] Navigate  [Enter] Open",
+            self.media_items.borrow().len(),
+            self.list_view.row_count()
+        ));
+    }
+
+    /// Shows clusters of near-identical images, one cluster per block of rows.
+    /// Hashes come from grid previews and `generate` runs, so only images
+    /// thumbnailed at least once take part. Clustering runs on a worker with
+    /// its own connection; a result for a source the user already left is
+    /// dropped.
+    fn load_duplicates(&self) {
+        if self.media_store.borrow().is_none() {
+            self.set_status("> Duplicates unavailable (database error)");
+            return;
+        }
+        self.set_status("> Finding duplicates...");
+        let generation = self.scan_generation.get();
+        let (tx, rx) = async_channel::bounded::<anyhow::Result<Vec<Vec<PathBuf>>>>(1);
+        std::thread::spawn(move || {
+            let result = MediaStore::open_default()
+                .and_then(|store| store.find_duplicates(DUPLICATE_MAX_HAMMING));
+            let _ = tx.send_blocking(result);
+        });
+
+        let window_weak = self.self_weak.borrow().clone();
+        glib::spawn_future_local(async move {
+            let Ok(result) = rx.recv().await else {
+                return;
+            };
+            let Some(window) = window_weak.upgrade() else {
+                return;
+            };
+            if generation != window.scan_generation.get() {
+                return;
+            }
+            match result {
+                Ok(clusters) => window.show_duplicates(clusters),
+                Err(err) => {
+                    tracing::warn!(error = ?err, "Failed to find duplicates");
+                    window.set_status("> Failed to find duplicates");
+                }
+            }
+        });
+    }
+
+    fn show_duplicates(&self, clusters: Vec<Vec<PathBuf>>) {
+        let groups: HashMap<PathBuf, usize> = clusters
+            .iter()
+            .enumerate()
+            .flat_map(|(group, paths)| paths.iter().map(move |path| (path.clone(), group)))
+            .collect();
+        let paths: Vec<PathBuf> = clusters.into_iter().flatten().collect();
+        let items = self.media_items_for_paths(&paths);
+        *self.duplicate_groups.borrow_mut() = groups;
+        self.set_active_source(ContentSource::Duplicates);
+        self.apply_items(items);
+        self.finalize_loaded_items();
+
+        let group_count = self
+            .media_items
+            .borrow()
+            .iter()
+            .filter_map(|item| self.duplicate_groups.borrow().get(&item.path).copied())
+            .collect::<HashSet<_>>()
+            .len();
+        if group_count == 0 {
+            self.set_status("> No duplicates among thumbnailed images so far");
+            return;
+        }
+        self.set_status(&format!(
+            "> Duplicates: {} groups | {} items | {} rows | [hjkl/arrows] Navigate  [Enter] Open",
+            group_count,
+            self.media_items.borrow().len(),
+            self.list_view.row_count()
+        ));
+    }
+
     fn load_album(&self, album_id: i64, name: &str) {
         let paths = {
             let store_ref = self.media_store.borrow();
@@ -1613,7 +3962,67 @@ impl MainWindow {
         self.apply_items(items);
         self.finalize_loaded_items();
         self.set_status(&format!(
-            "> Album: {} | {} items | {} rows | [hjkl/arrows] Navigate  [Enter] Open",
+            "> Album: {} | {} items | {} rows | [hjkl/arrows] Navigate  [Enter] Open  [J/K] Reorder",
+            name,
+            self.media_items.borrow().len(),
+            self.list_view.row_count()
+        ));
+    }
+
+    fn load_rated(&self, min: u8) {
+        let paths = {
+            let store_ref = self.media_store.borrow();
+            let Some(store) = store_ref.as_ref() else {
+                self.set_status("> Ratings unavailable (database error)");
+                return;
+            };
+            match store.list_rated_paths(min) {
+                Ok(paths) => paths,
+                Err(err) => {
+                    tracing::warn!(error = ?err, "Failed to list rated paths");
+                    self.set_status("> Failed to load rated items");
+                    return;
+                }
+            }
+        };
+
+        let items = self.media_items_for_paths(&paths);
+        self.set_active_source(ContentSource::Rated { min });
+        self.apply_items(items);
+        self.finalize_loaded_items();
+        self.set_status(&format!(
+            "> {}+ Stars: {} items | {} rows | [hjkl/arrows] Navigate  [Enter] Open  [0-5] Rate",
+            min,
+            self.media_items.borrow().len(),
+            self.list_view.row_count()
+        ));
+    }
+
+    fn load_tag(&self, name: &str) {
+        let paths = {
+            let store_ref = self.media_store.borrow();
+            let Some(store) = store_ref.as_ref() else {
+                self.set_status("> Tags unavailable (database error)");
+                return;
+            };
+            match store.paths_for_tag(name) {
+                Ok(paths) => paths,
+                Err(err) => {
+                    tracing::warn!(error = ?err, "Failed to load tagged paths");
+                    self.set_status("> Failed to load tag");
+                    return;
+                }
+            }
+        };
+
+        let items = self.media_items_for_paths(&paths);
+        self.set_active_source(ContentSource::Tag {
+            name: name.to_string(),
+        });
+        self.apply_items(items);
+        self.finalize_loaded_items();
+        self.set_status(&format!(
+            "> Tag: {} | {} items | {} rows | [hjkl/arrows] Navigate  [Enter] Open",
             name,
             self.media_items.borrow().len(),
             self.list_view.row_count()
@@ -1635,10 +4044,36 @@ impl MainWindow {
                     .set(self.scan_generation.get().wrapping_add(1));
                 self.load_favorites();
             }
-            ContentSource::Album { id, name } => {
+            ContentSource::Recent => {
                 self.scan_generation
                     .set(self.scan_generation.get().wrapping_add(1));
-                self.load_album(id, &name);
+                self.load_recent();
+            }
+            ContentSource::FileList => {
+                self.scan_generation
+                    .set(self.scan_generation.get().wrapping_add(1));
+                self.load_file_list();
+            }
+            ContentSource::Duplicates => {
+                self.scan_generation
+                    .set(self.scan_generation.get().wrapping_add(1));
+                self.persist_row_placeholder_hashes();
+                self.load_duplicates();
+            }
+            ContentSource::Rated { min } => {
+                self.scan_generation
+                    .set(self.scan_generation.get().wrapping_add(1));
+                self.load_rated(min);
+            }
+            ContentSource::Album { id, name } => {
+                self.scan_generation
+                    .set(self.scan_generation.get().wrapping_add(1));
+                self.load_album(id, &name);
+            }
+            ContentSource::Tag { name } => {
+                self.scan_generation
+                    .set(self.scan_generation.get().wrapping_add(1));
+                self.load_tag(&name);
             }
         }
     }
@@ -1650,6 +4085,9 @@ impl MainWindow {
 
     /// Set the current directory path
     pub fn set_current_path(&self, path: Option<PathBuf>) {
+        if *self.current_path.borrow() != path {
+            self.clear_marks();
+        }
         *self.current_path.borrow_mut() = path;
         if matches!(*self.active_source.borrow(), ContentSource::Directory) {
             self.update_source_header();
@@ -1689,11 +4127,150 @@ impl MainWindow {
     }
 
     fn build_rows_for_items(&self, items: &[MediaItem]) -> Vec<RowModel> {
+        let show_captions = self.row_height.get() <= CAPTION_MAX_ROW_HEIGHT;
+        self.compute_rows_for_items(items)
+            .into_iter()
+            .map(|row| row.with_captions(show_captions))
+            .collect()
+    }
+
+    fn compute_rows_for_items(&self, items: &[MediaItem]) -> Vec<RowModel> {
         let effective_width = self.current_effective_width(1200.0);
-        let rows = JustifiedLayout::default().compute(items, effective_width);
+        if matches!(*self.active_source.borrow(), ContentSource::Duplicates) {
+            let groups = self.duplicate_groups.borrow();
+            let rows = rows_per_group(items, &groups, |group| {
+                if self.layout_mode.get() == LayoutMode::Grid {
+                    GridLayout {
+                        cell_size: self.row_height.get(),
+                        gutter_px: self.gutter_px.get(),
+                    }
+                    .compute(group, effective_width)
+                } else {
+                    JustifiedLayout::with_target_height(self.row_height.get())
+                        .with_gutter(self.gutter_px.get())
+                        .compute(group, effective_width)
+                }
+            });
+            return Self::clamp_rows_to_width(rows, effective_width);
+        }
+        if self.layout_mode.get() == LayoutMode::Grid {
+            // Cheap enough that caching would cost more than it saves
+            let grid = GridLayout {
+                cell_size: self.row_height.get(),
+                gutter_px: self.gutter_px.get(),
+            };
+            let rows = grid.compute(items, effective_width);
+            return Self::clamp_rows_to_width(rows, effective_width);
+        }
+        let justified = JustifiedLayout::with_target_height(self.row_height.get())
+            .with_gutter(self.gutter_px.get());
+        let rows = self
+            .cached_layout_rows(&justified, items, effective_width)
+            .unwrap_or_else(|| justified.compute(items, effective_width));
         Self::clamp_rows_to_width(rows, effective_width)
     }
 
+    /// Key for the persisted layout of the current source and sort order.
+    fn layout_cache_key(&self) -> String {
+        let source = match &*self.active_source.borrow() {
+            ContentSource::Directory => format!(
+                "dir:{}",
+                self.current_path
+                    .borrow()
+                    .as_deref()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_default()
+            ),
+            ContentSource::Favorites => "favorites".to_string(),
+            ContentSource::Recent => "recent".to_string(),
+            ContentSource::FileList => "stdin".to_string(),
+            ContentSource::Duplicates => "duplicates".to_string(),
+            ContentSource::Rated { min } => format!("rated:{}", min),
+            ContentSource::Album { id, .. } => format!("album:{}", id),
+            ContentSource::Tag { name } => format!("tag:{}", name),
+        };
+        format!(
+            "{}|{}|h{}|g{}",
+            source,
+            self.sort_key.get().as_str(),
+            self.row_height.get().round() as i32,
+            self.gutter_px.get().round() as i32
+        )
+    }
+
+    /// Look up rows persisted for this exact list and width; on a miss,
+    /// compute them and store the result for the next visit.
+    fn cached_layout_rows(
+        &self,
+        justified: &JustifiedLayout,
+        items: &[MediaItem],
+        width: f32,
+    ) -> Option<Vec<RowModel>> {
+        if items.is_empty() {
+            return None;
+        }
+        let mut store_ref = self.media_store.borrow_mut();
+        let store = store_ref.as_mut()?;
+
+        // Rows wrap at the exact width, so whole pixels are the bucket
+        let width_bucket = width.round() as i32;
+        let sort_key = self.layout_cache_key();
+        let list_hash = layout::layout_list_hash(items);
+        let item_count = items.len() as i32;
+
+        match store.get_layout(width_bucket, &sort_key, &list_hash, item_count) {
+            Ok(Some(cached)) => {
+                let breaks: Vec<RowBreak> = cached
+                    .iter()
+                    .map(|row| RowBreak {
+                        start_index: row.start_index.max(0) as usize,
+                        end_index: row.end_index.max(0) as usize,
+                        row_height: row.row_height as f32,
+                    })
+                    .collect();
+                if let Some(rows) = layout::rows_from_cached_breaks(justified, items, &breaks) {
+                    tracing::debug!("Layout cache hit: {} rows for {}", rows.len(), sort_key);
+                    return Some(rows);
+                }
+            }
+            Ok(None) => {}
+            Err(err) => {
+                tracing::warn!(error = ?err, "Failed to read cached layout");
+                return None;
+            }
+        }
+
+        let rows = justified.compute(items, width);
+        let meta = LayoutMeta {
+            width_bucket,
+            sort_key: sort_key.clone(),
+            item_count,
+            list_hash,
+            updated_at: MediaStore::now(),
+        };
+        let mut start = 0;
+        let layout_rows: Vec<LayoutRow> = rows
+            .iter()
+            .map(|row| {
+                let end = start + row.items.len() as i32;
+                let layout_row = LayoutRow {
+                    width_bucket,
+                    sort_key: sort_key.clone(),
+                    row_index: row.row_index as i32,
+                    row_height: row.height_px as f64,
+                    start_index: start,
+                    end_index: end,
+                };
+                start = end;
+                layout_row
+            })
+            .collect();
+        if let Err(err) = store.upsert_layout(&meta, &layout_rows) {
+            tracing::warn!(error = ?err, "Failed to store layout");
+        }
+        Some(rows)
+    }
+
     fn apply_rows(&self, rows: Vec<RowModel>) {
         let (flat_paths, row_offsets) = Self::rows_to_flat_paths(&rows);
         *self.flat_paths.borrow_mut() = flat_paths;
@@ -1702,16 +4279,87 @@ impl MainWindow {
         self.keybindings.set_row_count(self.list_view.row_count());
         let (row, col) = self.keybindings.selection();
         self.list_view.set_selection(row, col);
+        self.watch_thumbnail_progress();
+        self.refresh_visible_badges();
+    }
+
+    /// Badges the files in rows `first..=last` with their favorite and
+    /// album membership.
+    fn annotate_rows(&self, first: u32, last: u32) {
+        let paths: Vec<PathBuf> = {
+            let flat_paths = self.flat_paths.borrow();
+            let row_offsets = self.row_offsets.borrow();
+            let Some(&start) = row_offsets.get(first as usize) else {
+                return;
+            };
+            let end = row_offsets
+                .get(last as usize + 1)
+                .copied()
+                .unwrap_or(flat_paths.len());
+            let folders = self.folder_paths.borrow();
+            flat_paths[start..end.max(start)]
+                .iter()
+                .filter(|path| !folders.contains(*path))
+                .cloned()
+                .collect()
+        };
+        self.refresh_tile_badges(&paths);
+    }
+
+    fn refresh_visible_badges(&self) {
+        if let Some((first, last)) = self.list_view.rows_in_view() {
+            self.annotate_rows(first, last);
+        }
+    }
+
+    /// Re-reads the favorite and album membership of `paths`, one query
+    /// each, and redraws their tile badges without a relayout.
+    fn refresh_tile_badges(&self, paths: &[PathBuf]) {
+        if paths.is_empty() {
+            return;
+        }
+        let result = {
+            let store = self.media_store.borrow();
+            let Some(store) = store.as_ref() else {
+                return;
+            };
+            store.favorites_for_paths(paths).and_then(|favorites| {
+                store
+                    .album_membership_for_paths(paths)
+                    .map(|albums| (favorites, albums))
+            })
+        };
+        let (favorites, albums) = match result {
+            Ok(membership) => membership,
+            Err(err) => {
+                tracing::warn!(error = ?err, "Failed to read favorites and albums");
+                return;
+            }
+        };
+        let mut memberships: HashMap<PathBuf, TileMembership> = albums
+            .into_iter()
+            .map(|(path, album_ids)| {
+                let membership = TileMembership {
+                    favorite: favorites.contains(&path),
+                    albums: album_ids.len(),
+                };
+                (path, membership)
+            })
+            .collect();
+        for path in favorites {
+            memberships.entry(path).or_insert(TileMembership {
+                favorite: true,
+                albums: 0,
+            });
+        }
+        self.list_view.annotate_memberships(paths, &memberships);
     }
 
     fn shuffled_items(&self, items: &[MediaItem]) -> Vec<MediaItem> {
         let mut shuffled = items.to_vec();
-        let seed = self.shuffle_seed.get();
-        shuffled.sort_by(|a, b| {
-            let rank_a = Self::shuffle_rank_for_path(seed, &a.path);
-            let rank_b = Self::shuffle_rank_for_path(seed, &b.path);
-            rank_a.cmp(&rank_b).then_with(|| a.path.cmp(&b.path))
-        });
+        self.shuffle_method
+            .get()
+            .shuffle(&mut shuffled, self.shuffle_seed.get());
         shuffled
     }
 
@@ -1722,13 +4370,6 @@ impl MainWindow {
             .unwrap_or(0x9e3779b97f4a7c15)
     }
 
-    fn shuffle_rank_for_path(seed: u64, path: &Path) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        seed.hash(&mut hasher);
-        path.hash(&mut hasher);
-        hasher.finish()
-    }
-
     fn update_shuffle_button(&self) {
         let label = if self.shuffle_mode.get() {
             "S/Shuffle: ON"
@@ -1741,32 +4382,310 @@ impl MainWindow {
     fn toggle_shuffle(&self) {
         let next = !self.shuffle_mode.get();
         self.shuffle_mode.set(next);
+        if next && !self.shuffle_seed_locked.get() {
+            self.store_shuffle_seed(Self::new_shuffle_seed());
+        }
+        self.relayout_shuffle();
         if next {
-            self.shuffle_seed.set(Self::new_shuffle_seed());
+            self.set_status(&format!("> Shuffle: ON (seed {})", self.shuffle_seed.get()));
+        } else {
+            self.set_status("> Shuffle: OFF");
+        }
+    }
+
+    /// New order with a fresh seed, turning shuffle on if it was off. A
+    /// locked seed is replaced by the new one.
+    fn reshuffle(&self) {
+        self.store_shuffle_seed(Self::new_shuffle_seed());
+        self.shuffle_mode.set(true);
+        self.relayout_shuffle();
+        self.set_status(&format!("> Reshuffled (seed {})", self.shuffle_seed.get()));
+    }
+
+    /// Uses `seed` from now on, reordering right away if shuffle is on.
+    fn set_shuffle_seed(&self, seed: u64) {
+        self.store_shuffle_seed(seed);
+        if self.shuffle_mode.get() {
+            self.relayout_shuffle();
+        }
+        self.set_status(&format!("> Shuffle seed: {}", seed));
+    }
+
+    fn set_shuffle_method(&self, method: ShuffleMethod) {
+        if self.shuffle_method.replace(method) == method {
+            return;
+        }
+        self.save_setting(SHUFFLE_METHOD_SETTING, method.as_str());
+        if self.shuffle_mode.get() {
+            self.relayout_shuffle();
+        }
+        self.set_status(&format!("> Shuffle order: {}", method.label()));
+    }
+
+    fn set_shuffle_seed_locked(&self, locked: bool) {
+        if self.shuffle_seed_locked.replace(locked) == locked {
+            return;
+        }
+        self.save_setting(
+            SHUFFLE_SEED_LOCKED_SETTING,
+            if locked { "true" } else { "false" },
+        );
+    }
+
+    fn store_shuffle_seed(&self, seed: u64) {
+        self.shuffle_seed.set(seed);
+        self.save_setting(SHUFFLE_SEED_SETTING, &seed.to_string());
+    }
+
+    fn relayout_shuffle(&self) {
+        let items = self.visible_items(&self.base_items.borrow());
+        *self.media_items.borrow_mut() = items.clone();
+        let rows = self.build_rows_for_items(&items);
+        reschedule_row_previews();
+        self.apply_rows(rows);
+        self.update_shuffle_button();
+    }
+
+    /// Re-sort the current directory listing in place, keeping folders first.
+    fn set_sort_key(&self, key: SortKey) {
+        if key == SortKey::Random {
+            // Pick a fresh order every time Random is chosen
+            self.sort_seed.set(Self::new_shuffle_seed());
+        } else if self.sort_key.get() == key {
+            return;
+        }
+        self.sort_key.set(key);
+        if !matches!(*self.active_source.borrow(), ContentSource::Directory) {
+            self.set_status(&format!("> Sort: {} (applies to directories)", key.label()));
+            return;
         }
-        let base_items = self.base_items.borrow().clone();
-        let items = if next {
-            self.shuffled_items(&base_items)
+
+        let mut base_items = self.base_items.borrow().clone();
+        key.sort_items(&mut base_items, self.sort_seed.get());
+        let items = self.visible_items(&base_items);
+        *self.base_items.borrow_mut() = base_items;
+        *self.media_items.borrow_mut() = items.clone();
+        let rows = self.build_rows_for_items(&items);
+        reschedule_row_previews();
+        self.apply_rows(rows);
+        self.set_status(&format!("> Sort: {}", key.label()));
+    }
+
+    /// Items to lay out: `base_items` narrowed by the name and
+    /// favorites-only filters, then shuffled if shuffle mode is on. Folders
+    /// are never filtered out.
+    fn visible_items(&self, base_items: &[MediaItem]) -> Vec<MediaItem> {
+        let filter = self.name_filter.borrow();
+        let metadata_matches = self.metadata_filter_matches.borrow();
+        let favorites = self.favorites_only.borrow();
+        let filtered: Vec<MediaItem> = if filter.is_empty() && favorites.is_none() {
+            base_items.to_vec()
         } else {
             base_items
+                .iter()
+                .filter(|item| {
+                    item.is_folder()
+                        || ((Self::name_matches(&item.path, &filter)
+                            || metadata_matches.contains(&item.path))
+                            && favorites
+                                .as_ref()
+                                .is_none_or(|favorites| favorites.contains(&item.path)))
+                })
+                .cloned()
+                .collect()
+        };
+        if self.shuffle_mode.get() {
+            self.shuffled_items(&filtered)
+        } else {
+            filtered
+        }
+    }
+
+    /// Paths of the favorited files in `items`, looked up in one query.
+    fn favorites_among(&self, items: &[MediaItem]) -> HashSet<PathBuf> {
+        let store = self.media_store.borrow();
+        let Some(store) = store.as_ref() else {
+            return HashSet::new();
+        };
+        let paths: Vec<PathBuf> = items
+            .iter()
+            .filter(|item| !item.is_folder())
+            .map(|item| item.path.clone())
+            .collect();
+        store.favorites_for_paths(&paths).unwrap_or_else(|err| {
+            tracing::warn!(error = ?err, "Failed to look up favorites");
+            HashSet::new()
+        })
+    }
+
+    fn toggle_favorites_only(&self) {
+        let enabled = self.favorites_only.borrow().is_none();
+        let favorites = enabled.then(|| self.favorites_among(&self.base_items.borrow()));
+        *self.favorites_only.borrow_mut() = favorites;
+        self.favorites_only_button.set_label(if enabled {
+            "Favs only: ON"
+        } else {
+            "Favs only: OFF"
+        });
+
+        let items = self.visible_items(&self.base_items.borrow());
+        *self.media_items.borrow_mut() = items.clone();
+        let rows = self.build_rows_for_items(&items);
+        reschedule_row_previews();
+        self.keybindings.set_selection(0, 0);
+        self.apply_rows(rows);
+
+        let total = self
+            .base_items
+            .borrow()
+            .iter()
+            .filter(|item| !item.is_folder())
+            .count();
+        if enabled {
+            let shown = items.iter().filter(|item| !item.is_folder()).count();
+            self.set_status(&format!("> Favorites only: {} of {} shown", shown, total));
+        } else {
+            self.set_status(&format!("> Favorites only: OFF | {} files", total));
+        }
+    }
+
+    /// Keeps tile badges and the favorites-only filter in step with favorite
+    /// toggles. The grid is not filtered again, so an unfavorited tile does
+    /// not vanish mid-edit.
+    fn note_favorites(&self, paths: &[PathBuf], favorite: bool) {
+        self.refresh_tile_badges(paths);
+        if let Some(favorites) = self.favorites_only.borrow_mut().as_mut() {
+            for path in paths {
+                if favorite {
+                    favorites.insert(path.clone());
+                } else {
+                    favorites.remove(path);
+                }
+            }
+        }
+    }
+
+    fn name_matches(path: &Path, needle_lower: &str) -> bool {
+        path.file_name()
+            .map(|name| name.to_string_lossy().to_lowercase().contains(needle_lower))
+            .unwrap_or(false)
+    }
+
+    /// Looks up the files whose edited description or keywords match the
+    /// current filter text.
+    fn refresh_metadata_filter_matches(&self) {
+        let needle = self.name_filter.borrow().clone();
+        let matches = match self.media_store.borrow().as_ref() {
+            Some(store) if !needle.is_empty() => store
+                .edited_metadata_matching(&needle)
+                .unwrap_or_else(|err| {
+                    tracing::warn!(error = ?err, "Failed to search edited metadata");
+                    HashSet::new()
+                }),
+            _ => HashSet::new(),
         };
+        *self.metadata_filter_matches.borrow_mut() = matches;
+    }
+
+    fn set_name_filter(&self, text: &str) {
+        let needle = text.trim().to_lowercase();
+        if *self.name_filter.borrow() == needle {
+            return;
+        }
+        *self.name_filter.borrow_mut() = needle;
+        self.refresh_metadata_filter_matches();
+
+        let items = self.visible_items(&self.base_items.borrow());
         *self.media_items.borrow_mut() = items.clone();
         let rows = self.build_rows_for_items(&items);
         reschedule_row_previews();
+        self.keybindings.set_selection(0, 0);
         self.apply_rows(rows);
-        self.update_shuffle_button();
-        self.set_status(&format!("> Shuffle: {}", if next { "ON" } else { "OFF" }));
+
+        let total = self
+            .base_items
+            .borrow()
+            .iter()
+            .filter(|item| !item.is_folder())
+            .count();
+        if self.name_filter.borrow().is_empty() {
+            self.set_status(&format!("> Filter cleared | {} files", total));
+        } else {
+            let shown = items.iter().filter(|item| !item.is_folder()).count();
+            self.set_status(&format!(
+                "> Filter \"{}\": {} of {} shown",
+                text.trim(),
+                shown,
+                total
+            ));
+        }
     }
 
     /// Set up callback for when visible rows change
     fn setup_visible_range_callback(&self) {
+        let window_weak = self.self_weak.borrow().clone();
         self.list_view
             .connect_visible_range_changed(move |first, last| {
-                // This will be used to trigger thumbnail loading for visible rows
                 tracing::debug!("Visible range: {} - {}", first, last);
+                set_row_preview_visible_range(first, last);
+                // Newly bound rows queue preview decodes; report them until drained.
+                if let Some(window) = window_weak.upgrade() {
+                    window.watch_thumbnail_progress();
+                    window.annotate_rows(first, last);
+                }
             });
     }
 
+    /// Store BlurHashes computed by the preview decoders so the next visit
+    /// can paint placeholders before any thumbnail is ready.
+    fn persist_row_placeholder_hashes(&self) {
+        let hashes = take_new_row_placeholder_hashes();
+        let phashes = take_new_row_phashes();
+        if hashes.is_empty() && phashes.is_empty() {
+            return;
+        }
+        if let Some(store) = self.media_store.borrow_mut().as_mut() {
+            if let Err(err) = store.update_blurhashes(&hashes) {
+                tracing::debug!(error = ?err, "Failed to store blurhashes");
+            }
+            if let Err(err) = store.update_phashes(&phashes) {
+                tracing::debug!(error = ?err, "Failed to store perceptual hashes");
+            }
+        }
+        let hashes: HashMap<PathBuf, String> = hashes.into_iter().collect();
+        for item in self.media_items.borrow_mut().iter_mut() {
+            if let Some(hash) = hashes.get(&item.path) {
+                item.blurhash = Some(hash.clone());
+            }
+        }
+    }
+
+    /// Show "> Generating N thumbnails…" in the status bar while previews are decoding.
+    fn watch_thumbnail_progress(&self) {
+        if self.thumb_progress_watch.replace(true) {
+            return;
+        }
+        let window_weak = self.self_weak.borrow().clone();
+        glib::timeout_add_local(Duration::from_millis(THUMB_PROGRESS_POLL_MS), move || {
+            let Some(window) = window_weak.upgrade() else {
+                return glib::ControlFlow::Break;
+            };
+            window.persist_row_placeholder_hashes();
+            let progress = row_preview_progress();
+            if progress.is_drained() {
+                window.thumb_progress_label.set_visible(false);
+                window.thumb_progress_watch.set(false);
+                return glib::ControlFlow::Break;
+            }
+            window.thumb_progress_label.set_text(&format!(
+                "> Generating {} thumbnails…",
+                progress.remaining()
+            ));
+            window.thumb_progress_label.set_visible(true);
+            glib::ControlFlow::Continue
+        });
+    }
+
     fn setup_layout_resize_observer(self: &Rc<Self>) {
         let weak_self = Rc::downgrade(self);
         let scrolled = self.list_view.widget().clone();
@@ -1795,6 +4714,12 @@ impl MainWindow {
         self.set_active_source(ContentSource::Directory);
         self.set_status(&format!("> Scanning: {}", path.display()));
         reschedule_row_previews();
+        if self.current_path().as_deref() != Some(path) {
+            self.undo_history
+                .borrow_mut()
+                .retain(|entry| matches!(entry, UndoEntry::Trash(_)));
+        }
+        self.switch_directory_position(path);
         self.set_current_path(Some(path.to_path_buf()));
         let viewport_width = self.list_view.content_width();
         let (list_alloc, scrolled_alloc, vscrollbar_width, vscrollbar_visible) =
@@ -1814,24 +4739,109 @@ impl MainWindow {
         self.scan_generation.set(generation);
 
         let path_buf = path.to_path_buf();
-        let (tx, rx) = mpsc::channel::<(u64, DirectoryScanResult)>();
+        let (tx, rx) = async_channel::unbounded::<DirectoryScanEvent>();
         let recursive = self.recursive_scan.get();
+        let sort_key = self.sort_key.get();
+        let sort_seed = self.sort_seed.get();
+        self.watch_directory(path, recursive);
         std::thread::spawn(move || {
-            let scanned = scan_directory(&path_buf, recursive);
-            let _ = tx.send((generation, scanned));
+            let progress_tx = tx.clone();
+            let scanned = scan_directory(&path_buf, recursive, sort_key, sort_seed, move |p| {
+                let _ = progress_tx.try_send(DirectoryScanEvent::Progress(p));
+            });
+            let _ = tx.send_blocking(DirectoryScanEvent::Finished(scanned));
         });
         let requested_path = path.to_path_buf();
         let weak_self = self.self_weak.borrow().clone();
 
-        glib::timeout_add_local(Duration::from_millis(16), move || match rx.try_recv() {
-            Ok((result_generation, result)) => {
-                if let Some(window) = weak_self.upgrade() {
-                    window.apply_directory_scan_result(&requested_path, result_generation, result);
+        glib::spawn_future_local(async move {
+            while let Ok(event) = rx.recv().await {
+                let Some(window) = weak_self.upgrade() else {
+                    break;
+                };
+                match event {
+                    DirectoryScanEvent::Progress(progress) => {
+                        window.show_scan_progress(&requested_path, generation, &progress);
+                    }
+                    DirectoryScanEvent::Finished(result) => {
+                        window.apply_directory_scan_result(&requested_path, generation, result);
+                        break;
+                    }
+                }
+            }
+        });
+    }
+
+    fn show_scan_progress(&self, requested_path: &Path, generation: u64, progress: &ScanProgress) {
+        if generation != self.scan_generation.get()
+            || !matches!(*self.active_source.borrow(), ContentSource::Directory)
+        {
+            return;
+        }
+        let ScanProgress::Scanning {
+            seen,
+            total,
+            current,
+        } = progress
+        else {
+            return;
+        };
+        let current = current.strip_prefix(requested_path).unwrap_or(current);
+        let status = match total {
+            Some(total) => format!(
+                "> Reading metadata: {}/{} | {}",
+                seen,
+                total,
+                current.display()
+            ),
+            None => format!("> Scanning: {} files found | {}", seen, current.display()),
+        };
+        self.set_status(&status);
+    }
+
+    /// Replace the directory watcher so external changes reload the grid.
+    fn watch_directory(&self, path: &Path, recursive: bool) {
+        let mut slot = self.dir_watcher.borrow_mut();
+        if slot
+            .as_ref()
+            .is_some_and(|w| w.path() == path && w.recursive() == recursive)
+        {
+            return;
+        }
+        // Drop the old watch before adding a new one
+        slot.take();
+        if !watcher::watching_enabled_from_env() {
+            return;
+        }
+
+        let (tx, rx) = async_channel::bounded::<()>(1);
+        let watcher =
+            match DirectoryWatcher::new(path, recursive, watcher::DEFAULT_DEBOUNCE, move || {
+                let _ = tx.try_send(());
+            }) {
+                Ok(watcher) => watcher,
+                Err(err) => {
+                    tracing::warn!(error = ?err, "Live reload disabled for {}", path.display());
+                    return;
+                }
+            };
+        *slot = Some(watcher);
+
+        let watched = path.to_path_buf();
+        let weak_self = self.self_weak.borrow().clone();
+        glib::spawn_future_local(async move {
+            // Ends once the watcher is dropped and its sender goes away
+            while rx.recv().await.is_ok() {
+                let Some(window) = weak_self.upgrade() else {
+                    break;
+                };
+                let is_current = matches!(*window.active_source.borrow(), ContentSource::Directory)
+                    && window.current_path.borrow().as_deref() == Some(watched.as_path());
+                if is_current {
+                    tracing::debug!("Directory changed on disk: {}", watched.display());
+                    window.reload_active_source();
                 }
-                glib::ControlFlow::Break
             }
-            Err(mpsc::TryRecvError::Empty) => glib::ControlFlow::Continue,
-            Err(mpsc::TryRecvError::Disconnected) => glib::ControlFlow::Break,
         });
     }
 
@@ -1864,9 +4874,27 @@ impl MainWindow {
             self.list_view.row_count()
         ));
 
+        self.restore_directory_position();
         self.finalize_loaded_items();
     }
 
+    /// Opens a directory, or a media file's directory with the file in the
+    /// viewer, leaving the viewer if it was showing something else.
+    pub fn open_path(&self, path: &Path) {
+        let (directory, target) = resolve_initial_path(path);
+        if !directory.is_dir() {
+            self.set_status(&format!("> Not a directory: {}", directory.display()));
+            return;
+        }
+        self.viewer.stop_slideshow();
+        if target.is_none() && self.viewer.is_visible() {
+            self.keybindings.set_view_mode(ViewMode::Grid);
+            self.close_viewer();
+        }
+        *self.pending_viewer_target.borrow_mut() = target;
+        self.load_directory(&directory);
+    }
+
     /// Navigate to the parent directory
     pub fn navigate_to_parent(&self) {
         if let Some(current) = self.current_path() {
@@ -1899,6 +4927,7 @@ impl MainWindow {
 
     /// Handle item activation - either open viewer for media or navigate for folders
     fn handle_item_activation(&self, path: &Path) {
+        self.viewer.stop_slideshow();
         if self.is_folder_path(path) {
             self.keybindings.set_view_mode(ViewMode::Grid);
             self.navigate_into_folder(path);
@@ -1944,6 +4973,47 @@ impl MainWindow {
         None
     }
 
+    /// Steps the viewer like the keyboard does, reporting when it is already
+    /// at an end. Returns false if nothing changed.
+    pub fn navigate_viewer(&self, step: i32) -> bool {
+        if !self.viewer.is_visible() {
+            self.set_status("> Viewer is not open");
+            return false;
+        }
+        let moved = self.viewer.navigate(step);
+        if !moved {
+            self.set_status(if step > 0 {
+                "> Already at the last item"
+            } else {
+                "> Already at the first item"
+            });
+        }
+        moved
+    }
+
+    /// Show the media file `step` positions from the one in the viewer,
+    /// keeping the grid selection in sync. Returns false if there is none.
+    fn step_viewer(&self, step: i32, wrap: bool) -> bool {
+        let Some(current) = self.viewer.current_path() else {
+            return false;
+        };
+        let next = step_media_path(
+            &self.flat_paths.borrow(),
+            &self.folder_paths.borrow(),
+            &current,
+            step,
+            wrap,
+        );
+        let Some(next) = next else {
+            return false;
+        };
+        if let Some((row, col)) = self.selection_for_path(&next) {
+            self.keybindings.set_selection(row, col);
+        }
+        self.open_viewer(&next);
+        true
+    }
+
     fn adjacent_media_path(&self, path: &Path) -> Option<PathBuf> {
         let flat_paths = self.flat_paths.borrow();
         let idx = flat_paths.iter().position(|p| p == path)?;
@@ -1976,16 +5046,238 @@ impl MainWindow {
         }
     }
 
-    fn toggle_favorite_selected(&self) {
-        let (row, col) = self.keybindings.selection();
-        let Some(path) = self.path_at(row, col) else {
-            return;
+    fn rating_for_path(&self, path: &Path) -> Option<u8> {
+        let store = self.media_store.borrow();
+        match store.as_ref()?.get_rating(path) {
+            Ok(rating) => rating,
+            Err(err) => {
+                tracing::warn!(error = ?err, "Failed to read rating");
+                None
+            }
+        }
+    }
+
+    /// Loads the ratings of `items` into the grid badges.
+    fn refresh_ratings(&self, items: &[MediaItem]) {
+        let ratings = {
+            let store = self.media_store.borrow();
+            let Some(store) = store.as_ref() else {
+                return;
+            };
+            let paths: Vec<PathBuf> = items
+                .iter()
+                .filter(|item| !item.is_folder())
+                .map(|item| item.path.clone())
+                .collect();
+            match store.ratings_for_paths(&paths) {
+                Ok(ratings) => ratings,
+                Err(err) => {
+                    tracing::warn!(error = ?err, "Failed to read ratings");
+                    return;
+                }
+            }
         };
-        let _ = self.toggle_favorite_for_path(&path);
+        self.list_view.set_ratings(ratings);
     }
 
-    fn toggle_favorite_for_path(&self, path: &Path) -> Option<bool> {
-        if self.is_folder_path(&path) {
+    /// Rates the viewed file, or the selected/marked files in the grid; `0`
+    /// clears. Returns false when the viewer is zoomed in, leaving the digit
+    /// keys to its zoom shortcuts.
+    fn set_rating_selected(&self, stars: u8) -> bool {
+        let in_viewer = self.stack.visible_child_name().as_deref() == Some("viewer");
+        if in_viewer && self.viewer.is_zoomed() {
+            return false;
+        }
+        let targets = if in_viewer {
+            self.viewer.current_path().into_iter().collect()
+        } else {
+            let (row, col) = self.keybindings.selection();
+            let Some(path) = self.path_at(row, col) else {
+                return true;
+            };
+            self.action_targets(&path)
+        };
+        let files: Vec<PathBuf> = targets
+            .into_iter()
+            .filter(|target| !self.is_folder_path(target))
+            .collect();
+        if files.is_empty() {
+            self.set_status("> Ratings apply to files only");
+            return true;
+        }
+
+        let rating = (stars > 0).then_some(stars);
+        let result = {
+            let mut store = self.media_store.borrow_mut();
+            let Some(store) = store.as_mut() else {
+                self.set_status("> Ratings unavailable (database error)");
+                return true;
+            };
+            store.ratings_for_paths(&files).and_then(|previous| {
+                store
+                    .set_rating(&files, rating)
+                    .map(|count| (count, previous))
+            })
+        };
+        match result {
+            Ok((count, previous)) => {
+                self.push_undo(UndoEntry::Rating(
+                    files
+                        .iter()
+                        .map(|path| (path.clone(), previous.get(path).copied()))
+                        .collect(),
+                ));
+                let what = match files.as_slice() {
+                    [path] => path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("[item]")
+                        .to_string(),
+                    _ => format!("{} items", count),
+                };
+                match rating {
+                    Some(stars) => {
+                        self.set_status(&format!("> Rated {}: {}", what, rating_stars(stars)))
+                    }
+                    None => self.set_status(&format!("> Cleared rating: {}", what)),
+                }
+                self.list_view.update_ratings(&files, rating);
+                if let Some(current) = self.viewer.current_path() {
+                    if files.contains(&current) {
+                        self.viewer.set_rating_state(rating);
+                    }
+                }
+                if !in_viewer && matches!(*self.active_source.borrow(), ContentSource::Rated { .. })
+                {
+                    self.reload_active_source();
+                }
+            }
+            Err(err) => {
+                tracing::warn!(error = ?err, "Failed to set rating");
+                self.set_status("> Failed to update rating");
+            }
+        }
+        true
+    }
+
+    /// Moves the selected item one place later (`step` 1) or earlier (-1) in
+    /// the active album's order. Returns false outside album sources.
+    fn move_selected_in_album(&self, step: i32) -> bool {
+        let album_id = match *self.active_source.borrow() {
+            ContentSource::Album { id, .. } => id,
+            _ => return false,
+        };
+        let (row, col) = self.keybindings.selection();
+        let Some(path) = self.path_at(row, col) else {
+            return true;
+        };
+        let result = {
+            let mut store = self.media_store.borrow_mut();
+            let Some(store) = store.as_mut() else {
+                self.set_status("> Albums unavailable (database error)");
+                return true;
+            };
+            store.list_album_paths(album_id).and_then(|paths| {
+                let Some(index) = paths.iter().position(|p| *p == path) else {
+                    return Ok(None);
+                };
+                let Some(target) = index.checked_add_signed(step as isize) else {
+                    return Ok(None);
+                };
+                if target >= paths.len() {
+                    return Ok(None);
+                }
+                store.move_item_in_album(album_id, &path, target)?;
+                Ok(Some(target))
+            })
+        };
+        match result {
+            Ok(Some(target)) => {
+                *self.pending_reselect.borrow_mut() = vec![path.clone()];
+                self.reload_active_source();
+                self.rebuild_sidebar();
+                self.set_status(&format!(
+                    "> Moved {} to position {}",
+                    path.file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("[item]"),
+                    target + 1
+                ));
+            }
+            Ok(None) => {}
+            Err(err) => {
+                tracing::warn!(error = ?err, "Failed to reorder album");
+                self.set_status("> Failed to reorder album");
+            }
+        }
+        true
+    }
+
+    fn toggle_favorite_selected(&self) {
+        let (row, col) = self.keybindings.selection();
+        let Some(path) = self.path_at(row, col) else {
+            return;
+        };
+        let targets = self.action_targets(&path);
+        if targets.len() > 1 {
+            self.toggle_favorite_for_paths(&targets);
+        } else {
+            let _ = self.toggle_favorite_for_path(&path);
+        }
+    }
+
+    /// Favorite every path, or unfavorite them all if they already are.
+    fn toggle_favorite_for_paths(&self, paths: &[PathBuf]) {
+        let result = {
+            let mut store = self.media_store.borrow_mut();
+            let Some(store) = store.as_mut() else {
+                self.set_status("> Favorites unavailable (database error)");
+                return;
+            };
+            let was_favorite: Vec<bool> = paths
+                .iter()
+                .map(|path| store.is_favorite(path).unwrap_or(false))
+                .collect();
+            let favorite = !was_favorite.iter().all(|&state| state);
+            let changed: Vec<PathBuf> = paths
+                .iter()
+                .zip(&was_favorite)
+                .filter(|(_, &state)| state != favorite)
+                .map(|(path, _)| path.clone())
+                .collect();
+            store
+                .set_favorites(paths, favorite)
+                .map(|_| (favorite, changed))
+        };
+        match result {
+            Ok((favorited, changed)) => {
+                self.note_favorites(&changed, favorited);
+                self.push_undo(UndoEntry::Favorite {
+                    paths: changed,
+                    favorite: favorited,
+                });
+                self.set_status(&format!(
+                    "> {} {} items",
+                    if favorited {
+                        "Favorited"
+                    } else {
+                        "Unfavorited"
+                    },
+                    paths.len()
+                ));
+                if matches!(*self.active_source.borrow(), ContentSource::Favorites) {
+                    self.reload_active_source();
+                }
+            }
+            Err(err) => {
+                tracing::warn!(error = ?err, "Failed to update favorites");
+                self.set_status("> Failed to update favorites");
+            }
+        }
+    }
+
+    fn toggle_favorite_for_path(&self, path: &Path) -> Option<bool> {
+        if self.is_folder_path(&path) {
             self.set_status("> Favorites apply to files only");
             return None;
         }
@@ -1994,7 +5286,15 @@ impl MainWindow {
             self.set_status("> Favorites unavailable (database error)");
             return None;
         };
-        match store.toggle_favorite(&path) {
+        let result = store.toggle_favorite(&path);
+        if let Ok(favorite) = result {
+            self.note_favorites(&[path.to_path_buf()], favorite);
+            self.push_undo(UndoEntry::Favorite {
+                paths: vec![path.to_path_buf()],
+                favorite,
+            });
+        }
+        match result {
             Ok(true) => {
                 self.set_status(&format!(
                     "> Favorited: {}",
@@ -2038,121 +5338,1692 @@ impl MainWindow {
         let Some(path) = self.path_at(row, col) else {
             return;
         };
-        if self.is_folder_path(&path) {
+        let files: Vec<PathBuf> = self
+            .action_targets(&path)
+            .into_iter()
+            .filter(|target| !self.is_folder_path(target))
+            .collect();
+        if files.is_empty() {
             self.set_status("> Delete applies to files only");
             return;
         }
-        let filename = path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("[item]");
-        let was_viewer_open = self.stack.visible_child_name().as_deref() == Some("viewer");
-        let next_media = if was_viewer_open {
-            self.adjacent_media_path(&path)
+        if self.confirm_delete.get() && !self.skip_delete_confirm.get() {
+            self.confirm_delete(files);
         } else {
-            None
+            self.trash_files(files);
+        }
+    }
+
+    fn trash_files(&self, files: Vec<PathBuf>) {
+        match file_ops::move_to_trash(&files) {
+            Ok(()) => self.finish_removal(files, true),
+            Err(err) => {
+                tracing::warn!(error = ?err, "Trash unavailable");
+                self.confirm_permanent_delete(files, &err);
+            }
+        }
+    }
+
+    /// Ask before trashing `paths`. Enter confirms; cancelling hands the
+    /// keyboard back to the grid.
+    fn confirm_delete(&self, paths: Vec<PathBuf>) {
+        let shell = self.build_dialog_shell("Delete?", 380);
+        let message = Label::new(Some(&deletion_summary(&paths)));
+        message.set_wrap(true);
+        message.set_halign(Align::Start);
+        message.set_xalign(0.0);
+        shell.content.append(&message);
+
+        let skip_toggle = CheckButton::with_label("Don't ask again this session");
+        shell.content.append(&skip_toggle);
+
+        let buttons = GtkBox::new(Orientation::Horizontal, 8);
+        buttons.set_halign(Align::End);
+        let cancel_button = Button::with_label("Cancel");
+        let delete_button = Button::with_label("Move to trash");
+        buttons.append(&cancel_button);
+        buttons.append(&delete_button);
+        shell.content.append(&buttons);
+
+        let dialog_weak = shell.dialog.downgrade();
+        let close_dialog = Rc::new(move || {
+            if let Some(dialog) = dialog_weak.upgrade() {
+                dialog.close();
+            }
+        });
+
+        let window_weak = self.self_weak.borrow().clone();
+        let close_dialog_for_delete = close_dialog.clone();
+        delete_button.connect_clicked(move |_| {
+            close_dialog_for_delete();
+            if let Some(window) = window_weak.upgrade() {
+                window.skip_delete_confirm.set(skip_toggle.is_active());
+                window.trash_files(paths.clone());
+            }
+        });
+
+        let close_dialog_for_cancel = close_dialog.clone();
+        cancel_button.connect_clicked(move |_| close_dialog_for_cancel());
+        let close_dialog_for_header = close_dialog.clone();
+        shell
+            .close_button
+            .connect_clicked(move |_| close_dialog_for_header());
+
+        let keys = EventControllerKey::new();
+        keys.connect_key_pressed(move |_, keyval, _, _| {
+            if keyval == gdk4::Key::Escape {
+                close_dialog();
+                return glib::Propagation::Stop;
+            }
+            glib::Propagation::Proceed
+        });
+        shell.dialog.add_controller(keys);
+
+        let window_weak = self.self_weak.borrow().clone();
+        shell.dialog.connect_close_request(move |_| {
+            if let Some(window) = window_weak.upgrade() {
+                if window.stack.visible_child_name().as_deref() == Some("grid") {
+                    window.list_view.widget().grab_focus();
+                }
+            }
+            glib::Propagation::Proceed
+        });
+
+        shell.dialog.set_default_widget(Some(&delete_button));
+        shell.dialog.present();
+        delete_button.grab_focus();
+    }
+
+    /// Trashing failed; only unlink once the user agrees.
+    fn confirm_permanent_delete(&self, paths: Vec<PathBuf>, reason: &anyhow::Error) {
+        let shell = self.build_dialog_shell("Delete permanently?", 380);
+        let what = match paths.as_slice() {
+            [path] => path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("[item]")
+                .to_string(),
+            _ => format!("{} items", paths.len()),
         };
-        match std::fs::remove_file(&path) {
-            Ok(()) => {
-                self.set_status(&format!("> Deleted: {}", filename));
-                *self.pending_viewer_target.borrow_mut() = if was_viewer_open {
-                    next_media.clone()
-                } else {
-                    None
-                };
-                if was_viewer_open && next_media.is_none() {
-                    self.viewer.hide();
-                    self.keybindings.set_view_mode(ViewMode::Grid);
-                    self.stack.set_visible_child_name("grid");
+        let message = Label::new(Some(&format!(
+            "{:#}\n\nPermanently delete {}? This cannot be undone.",
+            reason, what
+        )));
+        message.set_wrap(true);
+        message.set_halign(Align::Start);
+        shell.content.append(&message);
+
+        let actions = GtkBox::new(Orientation::Horizontal, 8);
+        let spacer = GtkBox::new(Orientation::Horizontal, 0);
+        spacer.set_hexpand(true);
+        let delete_button = Button::with_label("Delete permanently");
+        actions.append(&spacer);
+        actions.append(&delete_button);
+        shell.content.append(&actions);
+
+        let dialog = shell.dialog.clone();
+        let window_weak = self.self_weak.borrow().clone();
+        delete_button.connect_clicked(move |_| {
+            dialog.close();
+            let Some(window) = window_weak.upgrade() else {
+                return;
+            };
+            let mut removed = Vec::new();
+            for path in &paths {
+                match std::fs::remove_file(path) {
+                    Ok(()) => removed.push(path.clone()),
+                    Err(err) => tracing::warn!(error = ?err, "Failed to delete {:?}", path),
                 }
-                self.reload_active_source();
             }
-            Err(err) => {
-                tracing::warn!(error = ?err, "Failed to delete file");
-                self.set_status(&format!("> Failed to delete: {}", filename));
+            if removed.is_empty() {
+                window.set_status("> Failed to delete");
+                return;
             }
+            window.finish_removal(removed, false);
+        });
+
+        let dialog = shell.dialog.clone();
+        shell.close_button.connect_clicked(move |_| {
+            dialog.close();
+        });
+
+        shell.dialog.present();
+    }
+
+    /// Update the view after files left the directory, either to the trash
+    /// (undoable with `u`) or for good.
+    fn finish_removal(&self, removed: Vec<PathBuf>, trashed: bool) {
+        let was_viewer_open = self.stack.visible_child_name().as_deref() == Some("viewer");
+        let next_media = match removed.as_slice() {
+            [path] if was_viewer_open => self.adjacent_media_path(path),
+            _ => None,
+        };
+
+        let status = match (removed.as_slice(), trashed) {
+            ([path], true) => format!(
+                "> Trashed: {} (u to undo)",
+                path.file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("[item]")
+            ),
+            ([path], false) => format!(
+                "> Deleted: {}",
+                path.file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("[item]")
+            ),
+            (_, true) => format!("> Trashed {} items (u to undo)", removed.len()),
+            (_, false) => format!("> Deleted {} items", removed.len()),
+        };
+        self.set_status(&status);
+
+        if trashed {
+            self.push_undo(UndoEntry::Trash(removed));
         }
+
+        self.clear_marks();
+        *self.pending_viewer_target.borrow_mut() = next_media.clone();
+        if was_viewer_open && next_media.is_none() {
+            self.viewer.hide();
+            self.keybindings.set_view_mode(ViewMode::Grid);
+            self.stack.set_visible_child_name("grid");
+        }
+        self.reload_active_source();
     }
 
-    fn add_path_to_album(&self, album_id: i64, path: &Path) {
-        let store = self.media_store.borrow();
-        let Some(store) = store.as_ref() else {
-            self.set_status("> Albums unavailable (database error)");
+    fn push_undo(&self, entry: UndoEntry) {
+        let mut history = self.undo_history.borrow_mut();
+        history.push(entry);
+        if history.len() > MAX_UNDO_HISTORY {
+            history.remove(0);
+        }
+    }
+
+    /// Reverts the most recent trash, favorite, album or rating change.
+    fn undo_last(&self) {
+        let Some(entry) = self.undo_history.borrow_mut().pop() else {
+            self.set_status("> Nothing to undo");
             return;
         };
-        match store.add_to_album(album_id, path) {
-            Ok(true) => {
-                self.set_status(&format!(
-                    "> Added to album: {}",
-                    path.file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or("[item]")
-                ));
-                if matches!(
-                    *self.active_source.borrow(),
-                    ContentSource::Album { id, .. } if id == album_id
-                ) {
-                    self.reload_active_source();
-                }
+        match entry {
+            UndoEntry::Trash(batch) => self.undo_trash(&batch),
+            UndoEntry::Favorite { paths, favorite } => self.undo_favorite(&paths, favorite),
+            UndoEntry::AlbumAdd { album_id, paths } => self.undo_album_add(album_id, &paths),
+            UndoEntry::AlbumRemove { album_id, paths } => self.undo_album_remove(album_id, &paths),
+            UndoEntry::Rating(previous) => self.undo_rating(&previous),
+        }
+    }
+
+    fn undo_favorite(&self, paths: &[PathBuf], favorite: bool) {
+        let result = {
+            let mut store = self.media_store.borrow_mut();
+            let Some(store) = store.as_mut() else {
+                self.set_status("> Favorites unavailable (database error)");
+                return;
+            };
+            store.set_favorites(paths, !favorite)
+        };
+        if let Err(err) = result {
+            tracing::warn!(error = ?err, "Failed to undo favorite change");
+            self.set_status("> Failed to undo favorite change");
+            return;
+        }
+        self.note_favorites(paths, !favorite);
+        self.set_status(&format!(
+            "> Undo: {} {}",
+            if favorite { "unfavorited" } else { "favorited" },
+            targets_label(paths)
+        ));
+        if let Some(current) = self.viewer.current_path() {
+            if paths.contains(&current) {
+                self.viewer.set_favorite_state(!favorite);
+            }
+        }
+        if matches!(*self.active_source.borrow(), ContentSource::Favorites) {
+            self.reload_active_source();
+        }
+    }
+
+    fn undo_album_add(&self, album_id: i64, paths: &[PathBuf]) {
+        let result = {
+            let mut store = self.media_store.borrow_mut();
+            let Some(store) = store.as_mut() else {
+                self.set_status("> Albums unavailable (database error)");
+                return;
+            };
+            store.remove_from_album_batch(album_id, paths)
+        };
+        if let Err(err) = result {
+            tracing::warn!(error = ?err, "Failed to undo album add");
+            self.set_status("> Failed to undo album add");
+            return;
+        }
+        self.refresh_tile_badges(paths);
+        self.set_status(&format!(
+            "> Undo: removed {} from album",
+            targets_label(paths)
+        ));
+        self.rebuild_sidebar();
+        if matches!(
+            *self.active_source.borrow(),
+            ContentSource::Album { id, .. } if id == album_id
+        ) {
+            self.reload_active_source();
+        }
+    }
+
+    fn undo_album_remove(&self, album_id: i64, paths: &[PathBuf]) {
+        let result = {
+            let mut store = self.media_store.borrow_mut();
+            let Some(store) = store.as_mut() else {
+                self.set_status("> Albums unavailable (database error)");
+                return;
+            };
+            store.add_to_album_batch(album_id, paths)
+        };
+        if let Err(err) = result {
+            tracing::warn!(error = ?err, "Failed to undo album removal");
+            self.set_status("> Failed to undo album removal");
+            return;
+        }
+        self.refresh_tile_badges(paths);
+        self.set_status(&format!(
+            "> Undo: added {} back to album",
+            targets_label(paths)
+        ));
+        self.rebuild_sidebar();
+        if matches!(
+            *self.active_source.borrow(),
+            ContentSource::Album { id, .. } if id == album_id
+        ) {
+            self.reload_active_source();
+        }
+    }
+
+    fn undo_rating(&self, previous: &[(PathBuf, Option<u8>)]) {
+        let mut by_rating: BTreeMap<Option<u8>, Vec<PathBuf>> = BTreeMap::new();
+        for (path, rating) in previous {
+            by_rating.entry(*rating).or_default().push(path.clone());
+        }
+        let result = {
+            let mut store = self.media_store.borrow_mut();
+            let Some(store) = store.as_mut() else {
+                self.set_status("> Ratings unavailable (database error)");
+                return;
+            };
+            by_rating
+                .iter()
+                .try_for_each(|(rating, paths)| store.set_rating(paths, *rating).map(|_| ()))
+        };
+        if let Err(err) = result {
+            tracing::warn!(error = ?err, "Failed to undo rating change");
+            self.set_status("> Failed to undo rating change");
+            return;
+        }
+        let current = self.viewer.current_path();
+        for (rating, paths) in &by_rating {
+            self.list_view.update_ratings(paths, *rating);
+            if current
+                .as_ref()
+                .is_some_and(|current| paths.contains(current))
+            {
+                self.viewer.set_rating_state(*rating);
+            }
+        }
+        let paths: Vec<PathBuf> = previous.iter().map(|(path, _)| path.clone()).collect();
+        self.set_status(&format!(
+            "> Undo: restored rating of {}",
+            targets_label(&paths)
+        ));
+        if matches!(*self.active_source.borrow(), ContentSource::Rated { .. }) {
+            self.reload_active_source();
+        }
+    }
+
+    /// Bring back the files of a trash operation and select them.
+    fn undo_trash(&self, batch: &[PathBuf]) {
+        match file_ops::restore_from_trash(batch) {
+            Ok(restored) if restored.is_empty() => {
+                self.set_status("> Nothing to restore (trash was emptied?)");
+            }
+            Ok(restored) => {
+                let status = match restored.as_slice() {
+                    [path] => format!(
+                        "> Restored: {}",
+                        path.file_name()
+                            .and_then(|n| n.to_str())
+                            .unwrap_or("[item]")
+                    ),
+                    _ => format!("> Restored {} items", restored.len()),
+                };
+                self.set_status(&status);
+                *self.pending_reselect.borrow_mut() = restored;
+                self.reload_active_source();
             }
-            Ok(false) => self.set_status("> Already in album"),
             Err(err) => {
-                tracing::warn!(error = ?err, "Failed to add to album");
-                self.set_status("> Failed to add to album");
+                tracing::warn!(error = ?err, "Failed to restore from trash");
+                self.set_status(&format!("> Failed to restore: {:#}", err));
             }
         }
     }
 
-    fn create_album_and_add(&self, name: &str, path: &Path) {
-        let album_id = {
+    /// Opens the rename prompt for the selected file.
+    fn rename_selected(&self) {
+        let (row, col) = self.keybindings.selection();
+        let Some(path) = self.path_at(row, col) else {
+            return;
+        };
+        if self.is_folder_path(&path) {
+            self.set_status("> Rename applies to files only");
+            return;
+        }
+        self.prompt_rename(path);
+    }
+
+    /// `C`: in the grid, open the item under the cursor beside the one marked
+    /// item (or the second of two marked items beside the first); in the
+    /// viewer, mark the current image or compare it with the marked one.
+    fn compare_images(&self) {
+        if self.stack.visible_child_name().as_deref() == Some("viewer") {
+            self.viewer.toggle_compare();
+            return;
+        }
+        let marked = self.marked_paths();
+        let (row, col) = self.keybindings.selection();
+        let cursor = self.path_at(row, col);
+        let pair = match (marked.as_slice(), cursor) {
+            ([first, second], _) => Some((first.clone(), second.clone())),
+            ([first], Some(cursor)) if *first != cursor => Some((first.clone(), cursor)),
+            _ => None,
+        };
+        let Some((reference, current)) = pair.filter(|(reference, current)| {
+            !self.is_folder_path(reference) && !self.is_folder_path(current)
+        }) else {
+            self.set_status("> Mark one image, move to another and press C to compare");
+            return;
+        };
+        self.open_viewer(&current);
+        self.viewer.compare_with(&reference);
+    }
+
+    /// The file shown in the viewer, or the selected grid item.
+    fn focused_path(&self) -> Option<PathBuf> {
+        if self.stack.visible_child_name().as_deref() == Some("viewer") {
+            self.viewer.current_path()
+        } else {
+            let (row, col) = self.keybindings.selection();
+            self.path_at(row, col)
+        }
+    }
+
+    /// Opens the viewed file, or the selected one in the grid, in the
+    /// configured external program.
+    fn open_current_external(&self) {
+        if let Some(path) = self.focused_path() {
+            self.open_external(&path);
+        }
+    }
+
+    fn reveal_current(&self) {
+        if let Some(path) = self.focused_path() {
+            self.reveal(&path);
+        }
+    }
+
+    /// Shows `path` selected in the file manager; failures end up in the
+    /// status bar.
+    fn reveal(&self, path: &Path) {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let window_weak = self.self_weak.borrow().clone();
+        let failed_path = path.to_path_buf();
+        external_app::reveal_in_file_manager(path, move |result| {
+            let Some(window) = window_weak.upgrade() else {
+                return;
+            };
+            match result {
+                Ok(handler) => window.set_status(&format!("> Showing {} in {}", name, handler)),
+                Err(err) => {
+                    tracing::warn!(error = ?err, "Failed to reveal {}", failed_path.display());
+                    window.set_status(&format!("> Failed to show in file manager: {:#}", err));
+                }
+            }
+        });
+    }
+
+    fn open_current_location(&self) {
+        if let Some(path) = self.focused_path() {
+            self.open_location(&path);
+        }
+    }
+
+    /// GPS position recorded for `path` by the last scan.
+    fn stored_gps(&self, path: &Path) -> Option<(f64, f64)> {
+        self.base_items
+            .borrow()
+            .iter()
+            .find(|item| item.path == path)
+            .and_then(|item| item.gps)
+    }
+
+    /// Shows where `path` was taken on a map. Files indexed before GPS was
+    /// stored have their EXIF read on the spot.
+    fn open_location(&self, path: &Path) {
+        if self.is_folder_path(path) {
+            self.set_status("> Open location applies to files only");
+            return;
+        }
+        let gps = self
+            .stored_gps(path)
+            .or_else(|| media_info::read_capture_details(path).gps);
+        let Some((lat, lon)) = gps else {
+            self.set_status("> No GPS position in this file");
+            return;
+        };
+        match external_app::open_map(lat, lon) {
+            Ok(_) => self.set_status(&format!(
+                "> Opened {} on the map",
+                media_info::format_coordinates(lat, lon)
+            )),
+            Err(err) => {
+                tracing::warn!(error = ?err, "Failed to open the map for {}", path.display());
+                self.set_status(&format!("> {:#}", err));
+            }
+        }
+    }
+
+    fn open_external(&self, path: &Path) {
+        if self.is_folder_path(path) {
+            self.set_status("> Open externally applies to files only");
+            return;
+        }
+        let is_video = is_video_path(path);
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        match external_app::open_external(path, is_video) {
+            Ok(program) => self.set_status(&format!("> Opened {} in {}", name, program)),
+            Err(err) => {
+                tracing::warn!(error = ?err, "Failed to open {} externally", path.display());
+                self.set_status(&format!("> {:#}", err));
+            }
+        }
+    }
+
+    fn prompt_rename(&self, path: PathBuf) {
+        let shell = self.build_dialog_shell("Rename", 420);
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let entry = Entry::new();
+        entry.set_hexpand(true);
+        entry.set_text(&name);
+        // Select just the stem, like file managers do
+        let stem_chars = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().chars().count())
+            .unwrap_or(0);
+        entry.select_region(0, stem_chars as i32);
+        shell.content.append(&entry);
+
+        let error = Label::new(None);
+        error.set_halign(Align::Start);
+        error.add_css_class("error-text");
+        error.set_visible(false);
+        shell.content.append(&error);
+
+        let buttons = GtkBox::new(Orientation::Horizontal, 8);
+        buttons.set_halign(Align::End);
+        let cancel_button = Button::with_label("Cancel");
+        let rename_button = Button::with_label("Rename");
+        buttons.append(&cancel_button);
+        buttons.append(&rename_button);
+        shell.content.append(&buttons);
+
+        let dialog_weak = shell.dialog.downgrade();
+        let close_dialog = Rc::new(move || {
+            if let Some(dialog) = dialog_weak.upgrade() {
+                dialog.close();
+            }
+        });
+
+        let window_weak = self.self_weak.borrow().clone();
+        let entry_for_rename = entry.clone();
+        let close_dialog_for_rename = close_dialog.clone();
+        let rename = Rc::new(move || {
+            let Some(window) = window_weak.upgrade() else {
+                return;
+            };
+            match window.rename_file(&path, &entry_for_rename.text()) {
+                Ok(()) => close_dialog_for_rename(),
+                // Keep the dialog open so the name can be fixed
+                Err(err) => {
+                    error.set_text(&format!("{:#}", err));
+                    error.set_visible(true);
+                }
+            }
+        });
+
+        let rename_for_button = rename.clone();
+        rename_button.connect_clicked(move |_| rename_for_button());
+        entry.connect_activate(move |_| rename());
+
+        let close_dialog_for_cancel = close_dialog.clone();
+        cancel_button.connect_clicked(move |_| close_dialog_for_cancel());
+        shell.close_button.connect_clicked(move |_| close_dialog());
+
+        shell.dialog.set_default_widget(Some(&rename_button));
+        shell.dialog.present();
+        entry.grab_focus();
+        entry.select_region(0, stem_chars as i32);
+    }
+
+    /// Renames the file on disk, moves its database rows and cached
+    /// thumbnails along, and reselects it under the new name.
+    fn rename_file(&self, path: &Path, input: &str) -> anyhow::Result<()> {
+        let new_path = file_ops::rename_file(path, input)?;
+        if new_path == path {
+            self.set_status("> Name unchanged");
+            return Ok(());
+        }
+        if let Some(store) = self.media_store.borrow_mut().as_mut() {
+            if let Err(err) = update_moved_path(store, path, &new_path) {
+                tracing::warn!(error = ?err, "Failed to update renamed path {:?}", path);
+            }
+        }
+        self.set_status(&format!(
+            "> Renamed: {}",
+            new_path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("[item]")
+        ));
+        let viewing = self.stack.visible_child_name().as_deref() == Some("viewer")
+            && self.viewer.current_path().as_deref() == Some(path);
+        if viewing {
+            *self.pending_viewer_target.borrow_mut() = Some(new_path.clone());
+        }
+        *self.pending_reselect.borrow_mut() = vec![new_path];
+        self.reload_active_source();
+        Ok(())
+    }
+
+    /// Asks where to copy or move the selected item, or the marked items.
+    fn transfer_selected(&self, mode: TransferMode) {
+        let (row, col) = self.keybindings.selection();
+        let Some(path) = self.path_at(row, col) else {
+            return;
+        };
+        let files: Vec<PathBuf> = self
+            .action_targets(&path)
+            .into_iter()
+            .filter(|target| !self.is_folder_path(target))
+            .collect();
+        if files.is_empty() {
+            self.set_status("> Copy and move apply to files only");
+            return;
+        }
+        self.prompt_transfer(files, mode);
+    }
+
+    fn prompt_transfer(&self, files: Vec<PathBuf>, mode: TransferMode) {
+        let (title, action) = match mode {
+            TransferMode::Copy => ("Copy to folder", "Copy"),
+            TransferMode::Move => ("Move to folder", "Move"),
+        };
+        let shell = self.build_dialog_shell(title, 520);
+        let what = match files.as_slice() {
+            [path] => path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("[item]")
+                .to_string(),
+            _ => format!("{} items", files.len()),
+        };
+        let label = Label::new(Some(&format!("{} {} to:", action, what)));
+        label.set_halign(Align::Start);
+        shell.content.append(&label);
+
+        let entry = Entry::new();
+        entry.set_hexpand(true);
+        entry.set_placeholder_text(Some("/path/to/folder"));
+        let initial = self
+            .last_transfer_dir
+            .borrow()
+            .clone()
+            .or_else(|| self.current_path());
+        if let Some(initial) = initial {
+            entry.set_text(initial.to_string_lossy().as_ref());
+            entry.select_region(0, -1);
+        }
+        shell.content.append(&entry);
+
+        let buttons = GtkBox::new(Orientation::Horizontal, 8);
+        buttons.set_halign(Align::End);
+        let cancel_button = Button::with_label("Cancel");
+        let confirm_button = Button::with_label(action);
+        buttons.append(&cancel_button);
+        buttons.append(&confirm_button);
+        shell.content.append(&buttons);
+
+        let dialog_weak = shell.dialog.downgrade();
+        let close_dialog = Rc::new(move || {
+            if let Some(dialog) = dialog_weak.upgrade() {
+                dialog.close();
+            }
+        });
+
+        let window_weak = self.self_weak.borrow().clone();
+        let entry_for_confirm = entry.clone();
+        let close_dialog_for_confirm = close_dialog.clone();
+        let confirm = Rc::new(move || {
+            let input = entry_for_confirm.text().to_string();
+            let input = input.trim();
+            if input.is_empty() {
+                return;
+            }
+            close_dialog_for_confirm();
+            let Some(window) = window_weak.upgrade() else {
+                return;
+            };
+            let dest = window.expand_path_input(input);
+            if dest.is_dir() {
+                window.transfer_files(files.clone(), dest, mode);
+            } else {
+                window.set_status(&format!("> Not a directory: {}", dest.display()));
+            }
+        });
+
+        let confirm_for_button = confirm.clone();
+        confirm_button.connect_clicked(move |_| confirm_for_button());
+        entry.connect_activate(move |_| confirm());
+
+        let close_dialog_for_cancel = close_dialog.clone();
+        cancel_button.connect_clicked(move |_| close_dialog_for_cancel());
+        shell.close_button.connect_clicked(move |_| close_dialog());
+
+        shell.dialog.set_default_widget(Some(&confirm_button));
+        shell.dialog.present();
+        entry.grab_focus();
+    }
+
+    /// Copies or moves files on a worker thread. Moves also re-point the
+    /// database rows, so thumbnails, favorites and albums follow the files.
+    fn transfer_files(&self, files: Vec<PathBuf>, dest: PathBuf, mode: TransferMode) {
+        *self.last_transfer_dir.borrow_mut() = Some(dest.clone());
+        self.set_status(&format!(
+            "> {} {} item(s) to {}...",
+            match mode {
+                TransferMode::Copy => "Copying",
+                TransferMode::Move => "Moving",
+            },
+            files.len(),
+            dest.display()
+        ));
+
+        type TransferResults = Vec<(PathBuf, anyhow::Result<PathBuf>)>;
+        let (tx, rx) = async_channel::bounded::<TransferResults>(1);
+        let dest_for_worker = dest.clone();
+        std::thread::spawn(move || {
+            let results = file_ops::transfer_files(&files, &dest_for_worker, mode);
+            if mode == TransferMode::Move {
+                match MediaStore::open_default() {
+                    Ok(mut store) => {
+                        for (old, result) in &results {
+                            let Ok(new) = result else {
+                                continue;
+                            };
+                            if let Err(err) = update_moved_path(&mut store, old, new) {
+                                tracing::warn!(error = ?err, "Failed to update moved path {:?}", old);
+                            }
+                        }
+                    }
+                    Err(err) => tracing::warn!(error = ?err, "Media store unavailable after move"),
+                }
+            }
+            let _ = tx.send_blocking(results);
+        });
+
+        let window_weak = self.self_weak.borrow().clone();
+        glib::spawn_future_local(async move {
+            let Ok(results) = rx.recv().await else {
+                return;
+            };
+            if let Some(window) = window_weak.upgrade() {
+                window.finish_transfer(results, &dest, mode);
+            }
+        });
+    }
+
+    fn finish_transfer(
+        &self,
+        results: Vec<(PathBuf, anyhow::Result<PathBuf>)>,
+        dest: &Path,
+        mode: TransferMode,
+    ) {
+        let mut done = Vec::new();
+        let mut failures = Vec::new();
+        for (path, result) in results {
+            match result {
+                Ok(new_path) => done.push((path, new_path)),
+                Err(err) => {
+                    tracing::warn!(error = ?err, "Failed to transfer {:?}", path);
+                    failures.push(err);
+                }
+            }
+        }
+
+        let verb = match mode {
+            TransferMode::Copy => "Copied",
+            TransferMode::Move => "Moved",
+        };
+        let status = match (done.as_slice(), failures.first()) {
+            ([], Some(err)) => format!("> {} nothing: {:#}", verb, err),
+            ([], None) => return,
+            ([(_, new_path)], None) => format!(
+                "> {}: {} -> {}",
+                verb,
+                new_path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("[item]"),
+                dest.display()
+            ),
+            (_, None) => format!("> {} {} items to {}", verb, done.len(), dest.display()),
+            (_, Some(err)) => format!(
+                "> {} {} items, {} failed: {:#}",
+                verb,
+                done.len(),
+                failures.len(),
+                err
+            ),
+        };
+        self.set_status(&status);
+        if done.is_empty() {
+            return;
+        }
+
+        match mode {
+            TransferMode::Copy => {
+                if self
+                    .current_path()
+                    .is_some_and(|current| dest.starts_with(current))
+                {
+                    self.reload_active_source();
+                }
+            }
+            TransferMode::Move => {
+                let in_viewer = self.stack.visible_child_name().as_deref() == Some("viewer");
+                let viewed = self.viewer.current_path();
+                let moved_viewed = done
+                    .iter()
+                    .find(|(old, _)| in_viewer && viewed.as_ref() == Some(old));
+                if let Some((old, new_path)) = moved_viewed {
+                    // Directory listings lose the file; collections keep it under its new path
+                    let next = if matches!(*self.active_source.borrow(), ContentSource::Directory) {
+                        self.adjacent_media_path(old)
+                    } else {
+                        Some(new_path.clone())
+                    };
+                    if next.is_none() {
+                        self.viewer.hide();
+                        self.keybindings.set_view_mode(ViewMode::Grid);
+                        self.stack.set_visible_child_name("grid");
+                    }
+                    *self.pending_viewer_target.borrow_mut() = next;
+                }
+                self.clear_marks();
+                self.reload_active_source();
+            }
+        }
+    }
+
+    fn add_paths_to_album(&self, album_id: i64, paths: &[PathBuf]) {
+        let result = {
+            let mut store = self.media_store.borrow_mut();
+            let Some(store) = store.as_mut() else {
+                self.set_status("> Albums unavailable (database error)");
+                return;
+            };
+            store.list_album_paths(album_id).and_then(|members| {
+                let members: HashSet<PathBuf> = members.into_iter().collect();
+                let mut new_paths: Vec<PathBuf> = Vec::new();
+                for path in paths {
+                    if !members.contains(path) && !new_paths.contains(path) {
+                        new_paths.push(path.clone());
+                    }
+                }
+                store
+                    .add_to_album_batch(album_id, paths)
+                    .map(|added| (added, new_paths))
+            })
+        };
+        match result {
+            Ok((0, _)) => self.set_status("> Already in album"),
+            Ok((added, new_paths)) => {
+                self.refresh_tile_badges(&new_paths);
+                self.push_undo(UndoEntry::AlbumAdd {
+                    album_id,
+                    paths: new_paths,
+                });
+                if let [path] = paths {
+                    self.set_status(&format!(
+                        "> Added to album: {}",
+                        path.file_name()
+                            .and_then(|n| n.to_str())
+                            .unwrap_or("[item]")
+                    ));
+                } else {
+                    self.set_status(&format!("> Added {} items to album", added));
+                }
+                // The derived cover follows the first item
+                self.rebuild_sidebar();
+                if matches!(
+                    *self.active_source.borrow(),
+                    ContentSource::Album { id, .. } if id == album_id
+                ) {
+                    self.reload_active_source();
+                }
+            }
+            Err(err) => {
+                tracing::warn!(error = ?err, "Failed to add to album");
+                self.set_status("> Failed to add to album");
+            }
+        }
+    }
+
+    fn remove_paths_from_album(&self, album_id: i64, paths: &[PathBuf]) {
+        let result = {
+            let mut store = self.media_store.borrow_mut();
+            let Some(store) = store.as_mut() else {
+                self.set_status("> Albums unavailable (database error)");
+                return;
+            };
+            store.remove_from_album_batch(album_id, paths)
+        };
+        match result {
+            Ok(0) => self.set_status("> Not in album"),
+            Ok(_) => {
+                self.refresh_tile_badges(paths);
+                self.push_undo(UndoEntry::AlbumRemove {
+                    album_id,
+                    paths: paths.to_vec(),
+                });
+                self.set_status(&format!("> Removed from album: {}", targets_label(paths)));
+                self.rebuild_sidebar();
+                if matches!(
+                    *self.active_source.borrow(),
+                    ContentSource::Album { id, .. } if id == album_id
+                ) {
+                    self.reload_active_source();
+                }
+            }
+            Err(err) => {
+                tracing::warn!(error = ?err, "Failed to remove from album");
+                self.set_status("> Failed to remove from album");
+            }
+        }
+    }
+
+    /// Albums that every one of `paths` is in.
+    fn common_album_ids(&self, paths: &[PathBuf]) -> HashSet<i64> {
+        let store = self.media_store.borrow();
+        let Some(store) = store.as_ref() else {
+            return HashSet::new();
+        };
+        let membership = match store.album_membership_for_paths(paths) {
+            Ok(membership) => membership,
+            Err(err) => {
+                tracing::warn!(error = ?err, "Failed to read album membership");
+                return HashSet::new();
+            }
+        };
+        let mut common: Option<HashSet<i64>> = None;
+        for path in paths {
+            let ids: HashSet<i64> = membership
+                .get(path)
+                .map(|ids| ids.iter().copied().collect())
+                .unwrap_or_default();
+            common = Some(match common {
+                Some(common) => common.intersection(&ids).copied().collect(),
+                None => ids,
+            });
+        }
+        common.unwrap_or_default()
+    }
+
+    /// Takes `paths` out of the album when all of them are in it, otherwise
+    /// adds the missing ones.
+    fn toggle_album_for_paths(&self, album_id: i64, paths: &[PathBuf]) {
+        if self.common_album_ids(paths).contains(&album_id) {
+            self.remove_paths_from_album(album_id, paths);
+        } else {
+            self.add_paths_to_album(album_id, paths);
+        }
+    }
+
+    /// Opens the album picker for the viewed file, or the selected or marked
+    /// files in the grid.
+    fn pick_albums_selected(&self) {
+        let Some(path) = self.focused_path() else {
+            return;
+        };
+        let files: Vec<PathBuf> = self
+            .action_targets(&path)
+            .into_iter()
+            .filter(|target| !self.is_folder_path(target))
+            .collect();
+        if files.is_empty() {
+            self.set_status("> Albums apply to files only");
+            return;
+        }
+        self.show_album_picker(files);
+    }
+
+    /// Keyboard album picker: each album's accelerator key toggles the
+    /// targets' membership and the popover stays open for more; typing a
+    /// name into the entry and pressing Enter creates an album with them.
+    fn show_album_picker(&self, targets: Vec<PathBuf>) {
+        let albums = {
+            let store = self.media_store.borrow();
+            let Some(store) = store.as_ref() else {
+                self.set_status("> Albums unavailable (database error)");
+                return;
+            };
+            match store.list_albums() {
+                Ok(albums) => albums,
+                Err(err) => {
+                    tracing::warn!(error = ?err, "Failed to list albums");
+                    self.set_status("> Failed to load albums");
+                    return;
+                }
+            }
+        };
+
+        let popover = gtk4::Popover::new();
+        popover.add_css_class("context-menu-popover");
+        popover.set_has_arrow(false);
+        popover.set_position(gtk4::PositionType::Bottom);
+        let pointing = Rectangle::new(self.window.width() / 2, self.window.height() / 3, 1, 1);
+        popover.set_pointing_to(Some(&pointing));
+        popover.set_autohide(true);
+        popover.set_parent(&self.window);
+        popover.connect_closed(|popover| popover.unparent());
+
+        let content = GtkBox::new(Orientation::Vertical, 6);
+        content.add_css_class("context-menu");
+        content.set_margin_top(6);
+        content.set_margin_bottom(6);
+        content.set_margin_start(8);
+        content.set_margin_end(8);
+
+        let header = if targets.len() > 1 {
+            Label::new(Some(&format!("[ ALBUMS: {} ITEMS ]", targets.len())))
+        } else {
+            Label::new(Some("[ ALBUMS ]"))
+        };
+        header.set_halign(Align::Start);
+        header.add_css_class("context-menu-title");
+        content.append(&header);
+
+        let hint = Label::new(Some(if albums.is_empty() {
+            "No albums yet"
+        } else {
+            "Press a key to toggle, Esc to close"
+        }));
+        hint.set_halign(Align::Start);
+        hint.add_css_class("context-menu-empty");
+        content.append(&hint);
+
+        let targets = Rc::new(targets);
+        let rows: Rc<RefCell<Vec<AlbumPickerRow>>> = Rc::new(RefCell::new(Vec::new()));
+        let refresh_rows = {
+            let rows = rows.clone();
+            let targets = targets.clone();
+            let window_weak = self.self_weak.borrow().clone();
+            Rc::new(move || {
+                let Some(window) = window_weak.upgrade() else {
+                    return;
+                };
+                let common = window.common_album_ids(&targets);
+                for row in rows.borrow().iter() {
+                    let in_album = common.contains(&row.album_id);
+                    let key = row.accelerator.unwrap_or(' ');
+                    let mark = if in_album { "[x]" } else { "[ ]" };
+                    row.button
+                        .set_label(&format!("{} {} {}", key, mark, row.name));
+                    if in_album {
+                        row.button.add_css_class("context-menu-item-checked");
+                    } else {
+                        row.button.remove_css_class("context-menu-item-checked");
+                    }
+                }
+            })
+        };
+        let toggle = {
+            let targets = targets.clone();
+            let refresh_rows = refresh_rows.clone();
+            let window_weak = self.self_weak.borrow().clone();
+            Rc::new(move |album_id: i64| {
+                if let Some(window) = window_weak.upgrade() {
+                    window.toggle_album_for_paths(album_id, &targets);
+                }
+                refresh_rows();
+            })
+        };
+
+        for (index, (album_id, name)) in albums.into_iter().enumerate() {
+            let button = Button::new();
+            button.add_css_class("context-menu-item");
+            button.set_halign(Align::Fill);
+            button.set_hexpand(true);
+            let toggle = toggle.clone();
+            button.connect_clicked(move |_| toggle(album_id));
+            content.append(&button);
+            rows.borrow_mut().push(AlbumPickerRow {
+                album_id,
+                name,
+                accelerator: album_accelerator(index),
+                button,
+            });
+        }
+        refresh_rows();
+        let first_album = rows.borrow().first().map(|row| row.button.clone());
+
+        let sep = gtk4::Separator::new(Orientation::Horizontal);
+        sep.add_css_class("context-menu-separator");
+        content.append(&sep);
+
+        let entry = Entry::new();
+        entry.set_placeholder_text(Some("New album name, Enter to create"));
+        entry.set_width_chars(28);
+        content.append(&entry);
+
+        let window_weak = self.self_weak.borrow().clone();
+        let popover_for_entry = popover.clone();
+        let targets_for_entry = targets.clone();
+        entry.connect_activate(move |entry| {
+            let name = entry.text().trim().to_string();
+            if name.is_empty() {
+                return;
+            }
+            if let Some(window) = window_weak.upgrade() {
+                window.create_album_and_add(&name, &targets_for_entry);
+            }
+            popover_for_entry.popdown();
+        });
+
+        // Accelerators apply while the entry is not being typed into
+        let keys = EventControllerKey::new();
+        keys.set_propagation_phase(gtk4::PropagationPhase::Capture);
+        let entry_for_keys = entry.clone();
+        keys.connect_key_pressed(move |_, keyval, _, state| {
+            if entry_for_keys.has_focus()
+                || state.intersects(gdk4::ModifierType::CONTROL_MASK | gdk4::ModifierType::ALT_MASK)
+            {
+                return glib::Propagation::Proceed;
+            }
+            let Some(key) = keyval.to_unicode() else {
+                return glib::Propagation::Proceed;
+            };
+            let album_id = rows
+                .borrow()
+                .iter()
+                .find(|row| row.accelerator == Some(key))
+                .map(|row| row.album_id);
+            match album_id {
+                Some(album_id) => {
+                    toggle(album_id);
+                    glib::Propagation::Stop
+                }
+                None => glib::Propagation::Proceed,
+            }
+        });
+        popover.add_controller(keys);
+
+        popover.set_child(Some(&content));
+        popover.popup();
+        match first_album {
+            Some(button) => button.grab_focus(),
+            None => entry.grab_focus(),
+        };
+    }
+
+    fn set_album_cover(&self, album_id: i64, name: &str, path: &Path) {
+        let result = {
+            let store = self.media_store.borrow();
+            let Some(store) = store.as_ref() else {
+                self.set_status("> Albums unavailable (database error)");
+                return;
+            };
+            store.set_album_cover(album_id, path)
+        };
+        match result {
+            Ok(()) => {
+                self.rebuild_sidebar();
+                self.set_status(&format!("> Album cover set: {}", name));
+            }
+            Err(err) => {
+                tracing::warn!(error = ?err, "Failed to set album cover");
+                self.set_status("> Failed to set album cover");
+            }
+        }
+    }
+
+    fn create_album_and_add(&self, name: &str, paths: &[PathBuf]) {
+        let album_id = {
+            let store = self.media_store.borrow();
+            let Some(store) = store.as_ref() else {
+                self.set_status("> Albums unavailable (database error)");
+                return;
+            };
+            match store.create_album(name) {
+                Ok(album_id) => album_id,
+                Err(err) => {
+                    tracing::warn!(error = ?err, "Failed to create album");
+                    self.set_status("> Failed to create album");
+                    return;
+                }
+            }
+        };
+        self.add_paths_to_album(album_id, paths);
+        self.rebuild_sidebar();
+    }
+
+    fn prompt_new_album(&self, paths: Vec<PathBuf>) {
+        let shell = self.build_dialog_shell("New Album", 360);
+        let name_label = Label::new(Some("Album name"));
+        name_label.set_halign(Align::Start);
+        shell.content.append(&name_label);
+
+        let name_entry = Entry::new();
+        name_entry.set_placeholder_text(Some("e.g. Favorites 2026"));
+        shell.content.append(&name_entry);
+
+        let actions = GtkBox::new(Orientation::Horizontal, 8);
+        let create_button = Button::with_label("Create");
+        let spacer = GtkBox::new(Orientation::Horizontal, 0);
+        spacer.set_hexpand(true);
+        actions.append(&spacer);
+        actions.append(&create_button);
+        shell.content.append(&actions);
+
+        let dialog = shell.dialog.clone();
+        let dialog_for_create = dialog.clone();
+        let window_weak = self.self_weak.borrow().clone();
+        create_button.connect_clicked(move |_| {
+            let name = name_entry.text().trim().to_string();
+            if name.is_empty() {
+                return;
+            }
+            if let Some(window) = window_weak.upgrade() {
+                window.create_album_and_add(&name, &paths);
+            }
+            dialog_for_create.close();
+        });
+
+        let dialog_for_close = dialog.clone();
+        shell.close_button.connect_clicked(move |_| {
+            dialog_for_close.close();
+        });
+
+        shell.dialog.present();
+    }
+
+    /// Opens quick tag entry for the selected item, or the marked items.
+    fn edit_tags_selected(&self) {
+        let (row, col) = self.keybindings.selection();
+        let Some(path) = self.path_at(row, col) else {
+            return;
+        };
+        let files: Vec<PathBuf> = self
+            .action_targets(&path)
+            .into_iter()
+            .filter(|target| !self.is_folder_path(target))
+            .collect();
+        if files.is_empty() {
+            self.set_status("> Tags apply to files only");
+            return;
+        }
+        self.show_tag_entry(files, None);
+    }
+
+    /// Popover with a text field: comma-separated names are added to every
+    /// target, names written as `-name` are removed.
+    fn show_tag_entry(&self, targets: Vec<PathBuf>, pointing: Option<Rectangle>) {
+        let common = {
+            let store = self.media_store.borrow();
+            let Some(store) = store.as_ref() else {
+                self.set_status("> Tags unavailable (database error)");
+                return;
+            };
+            match common_tags(store, &targets) {
+                Ok(tags) => tags,
+                Err(err) => {
+                    tracing::warn!(error = ?err, "Failed to read tags");
+                    self.set_status("> Failed to load tags");
+                    return;
+                }
+            }
+        };
+
+        let popover = gtk4::Popover::new();
+        popover.add_css_class("context-menu-popover");
+        popover.set_has_arrow(pointing.is_some());
+        popover.set_position(gtk4::PositionType::Bottom);
+        let pointing = pointing.unwrap_or_else(|| {
+            Rectangle::new(self.window.width() / 2, self.window.height() / 3, 1, 1)
+        });
+        popover.set_pointing_to(Some(&pointing));
+        popover.set_autohide(true);
+        popover.set_parent(&self.window);
+        popover.connect_closed(|popover| popover.unparent());
+
+        let content = GtkBox::new(Orientation::Vertical, 6);
+        content.add_css_class("context-menu");
+        content.set_margin_top(6);
+        content.set_margin_bottom(6);
+        content.set_margin_start(8);
+        content.set_margin_end(8);
+
+        let header = if targets.len() > 1 {
+            Label::new(Some(&format!("[ TAG {} ITEMS ]", targets.len())))
+        } else {
+            Label::new(Some("[ TAGS ]"))
+        };
+        header.set_halign(Align::Start);
+        header.add_css_class("context-menu-title");
+        content.append(&header);
+
+        let current = if common.is_empty() {
+            Label::new(Some("No tags yet"))
+        } else {
+            Label::new(Some(&common.join(", ")))
+        };
+        current.set_halign(Align::Start);
+        current.add_css_class("context-menu-empty");
+        content.append(&current);
+
+        let entry = Entry::new();
+        entry.set_placeholder_text(Some("portrait, export, -reject"));
+        entry.set_width_chars(28);
+        content.append(&entry);
+
+        let window_weak = self.self_weak.borrow().clone();
+        let popover_for_entry = popover.clone();
+        entry.connect_activate(move |entry| {
+            let (add, remove) = parse_tag_input(&entry.text());
+            if let Some(window) = window_weak.upgrade() {
+                window.edit_tags(&targets, &add, &remove);
+            }
+            popover_for_entry.popdown();
+        });
+
+        popover.set_child(Some(&content));
+        popover.popup();
+        entry.grab_focus();
+    }
+
+    fn edit_tags(&self, paths: &[PathBuf], add: &[String], remove: &[String]) {
+        if add.is_empty() && remove.is_empty() {
+            return;
+        }
+        let result = {
+            let mut store = self.media_store.borrow_mut();
+            let Some(store) = store.as_mut() else {
+                self.set_status("> Tags unavailable (database error)");
+                return;
+            };
+            add.iter()
+                .try_for_each(|name| store.add_tag(paths, name).map(|_| ()))
+                .and_then(|()| {
+                    remove
+                        .iter()
+                        .try_for_each(|name| store.remove_tag(paths, name).map(|_| ()))
+                })
+        };
+        if let Err(err) = result {
+            tracing::warn!(error = ?err, "Failed to update tags");
+            self.set_status("> Failed to update tags");
+            return;
+        }
+
+        let what = match paths {
+            [path] => path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("[item]")
+                .to_string(),
+            _ => format!("{} items", paths.len()),
+        };
+        let mut changes: Vec<String> = add.iter().map(|name| format!("+{}", name)).collect();
+        changes.extend(remove.iter().map(|name| format!("-{}", name)));
+        self.set_status(&format!("> Tagged {}: {}", what, changes.join(" ")));
+
+        self.rebuild_sidebar();
+        let active_tag = match &*self.active_source.borrow() {
+            ContentSource::Tag { name } => Some(name.clone()),
+            _ => None,
+        };
+        if let Some(active) = active_tag {
+            let touched = add
+                .iter()
+                .chain(remove)
+                .any(|name| name.eq_ignore_ascii_case(&active));
+            if touched {
+                self.reload_active_source();
+            }
+        }
+    }
+
+    /// Rename/Delete actions for an album entry in the sidebar.
+    fn show_sidebar_album_menu(&self, album_id: i64, name: &str, anchor: &Button) {
+        let popover = gtk4::Popover::new();
+        popover.add_css_class("context-menu-popover");
+        popover.set_position(gtk4::PositionType::Right);
+        popover.set_autohide(true);
+        popover.set_parent(anchor);
+        popover.connect_closed(|popover| popover.unparent());
+
+        let content = GtkBox::new(Orientation::Vertical, 6);
+        content.add_css_class("context-menu");
+        content.set_margin_top(6);
+        content.set_margin_bottom(6);
+        content.set_margin_start(8);
+        content.set_margin_end(8);
+
+        let header = Label::new(Some(&format!("[ ALBUM: {} ]", name)));
+        header.set_halign(Align::Start);
+        header.add_css_class("context-menu-title");
+        content.append(&header);
+
+        let sep = gtk4::Separator::new(Orientation::Horizontal);
+        sep.add_css_class("context-menu-separator");
+        content.append(&sep);
+
+        let rename_button = Button::with_label("Rename...");
+        rename_button.add_css_class("context-menu-item");
+        rename_button.set_halign(Align::Fill);
+        rename_button.set_hexpand(true);
+        let window_weak = self.self_weak.borrow().clone();
+        let popover_for_rename = popover.clone();
+        let name_for_rename = name.to_string();
+        rename_button.connect_clicked(move |_| {
+            popover_for_rename.popdown();
+            if let Some(window) = window_weak.upgrade() {
+                window.prompt_rename_album(album_id, &name_for_rename);
+            }
+        });
+        content.append(&rename_button);
+
+        let delete_button = Button::with_label("Delete album...");
+        delete_button.add_css_class("context-menu-item");
+        delete_button.set_halign(Align::Fill);
+        delete_button.set_hexpand(true);
+        let window_weak = self.self_weak.borrow().clone();
+        let popover_for_delete = popover.clone();
+        let name_for_delete = name.to_string();
+        delete_button.connect_clicked(move |_| {
+            popover_for_delete.popdown();
+            if let Some(window) = window_weak.upgrade() {
+                window.confirm_delete_album(album_id, &name_for_delete);
+            }
+        });
+        content.append(&delete_button);
+
+        popover.set_child(Some(&content));
+        popover.popup();
+    }
+
+    fn prompt_rename_album(&self, album_id: i64, current_name: &str) {
+        let shell = self.build_dialog_shell("Rename Album", 360);
+        let name_label = Label::new(Some("Album name"));
+        name_label.set_halign(Align::Start);
+        shell.content.append(&name_label);
+
+        let name_entry = Entry::new();
+        name_entry.set_text(current_name);
+        shell.content.append(&name_entry);
+
+        let actions = GtkBox::new(Orientation::Horizontal, 8);
+        let rename_button = Button::with_label("Rename");
+        let spacer = GtkBox::new(Orientation::Horizontal, 0);
+        spacer.set_hexpand(true);
+        actions.append(&spacer);
+        actions.append(&rename_button);
+        shell.content.append(&actions);
+
+        let dialog = shell.dialog.clone();
+        let window_weak = self.self_weak.borrow().clone();
+        let current_name = current_name.to_string();
+        rename_button.connect_clicked(move |_| {
+            let name = name_entry.text().trim().to_string();
+            if name.is_empty() {
+                return;
+            }
+            if name != current_name {
+                if let Some(window) = window_weak.upgrade() {
+                    window.rename_album(album_id, &name);
+                }
+            }
+            dialog.close();
+        });
+
+        let dialog = shell.dialog.clone();
+        shell.close_button.connect_clicked(move |_| {
+            dialog.close();
+        });
+
+        shell.dialog.present();
+    }
+
+    fn rename_album(&self, album_id: i64, name: &str) {
+        let result = {
+            let store = self.media_store.borrow();
+            let Some(store) = store.as_ref() else {
+                self.set_status("> Albums unavailable (database error)");
+                return;
+            };
+            store.rename_album(album_id, name)
+        };
+        if let Err(err) = result {
+            tracing::warn!(error = ?err, "Failed to rename album");
+            self.set_status(&format!("> Failed to rename album: {:#}", err));
+            return;
+        }
+        self.set_status(&format!("> Renamed album to: {}", name));
+        let is_active = matches!(
+            *self.active_source.borrow(),
+            ContentSource::Album { id, .. } if id == album_id
+        );
+        if is_active {
+            *self.active_source.borrow_mut() = ContentSource::Album {
+                id: album_id,
+                name: name.to_string(),
+            };
+            self.update_source_header();
+        }
+        self.rebuild_sidebar();
+    }
+
+    fn confirm_delete_album(&self, album_id: i64, name: &str) {
+        let shell = self.build_dialog_shell("Delete album?", 360);
+        let message = Label::new(Some(&format!(
+            "Delete the album \"{}\"? The files in it are not touched.",
+            name
+        )));
+        message.set_wrap(true);
+        message.set_halign(Align::Start);
+        shell.content.append(&message);
+
+        let actions = GtkBox::new(Orientation::Horizontal, 8);
+        let spacer = GtkBox::new(Orientation::Horizontal, 0);
+        spacer.set_hexpand(true);
+        let delete_button = Button::with_label("Delete album");
+        actions.append(&spacer);
+        actions.append(&delete_button);
+        shell.content.append(&actions);
+
+        let dialog = shell.dialog.clone();
+        let window_weak = self.self_weak.borrow().clone();
+        let name = name.to_string();
+        delete_button.connect_clicked(move |_| {
+            dialog.close();
+            if let Some(window) = window_weak.upgrade() {
+                window.delete_album(album_id, &name);
+            }
+        });
+
+        let dialog = shell.dialog.clone();
+        shell.close_button.connect_clicked(move |_| {
+            dialog.close();
+        });
+
+        shell.dialog.present();
+    }
+
+    fn delete_album(&self, album_id: i64, name: &str) {
+        let result = {
             let store = self.media_store.borrow();
             let Some(store) = store.as_ref() else {
                 self.set_status("> Albums unavailable (database error)");
                 return;
             };
-            match store.create_album(name) {
-                Ok(album_id) => album_id,
-                Err(err) => {
-                    tracing::warn!(error = ?err, "Failed to create album");
-                    self.set_status("> Failed to create album");
-                    return;
-                }
-            }
+            store.delete_album(album_id)
         };
-        self.add_path_to_album(album_id, path);
+        if let Err(err) = result {
+            tracing::warn!(error = ?err, "Failed to delete album");
+            self.set_status("> Failed to delete album");
+            return;
+        }
         self.rebuild_sidebar();
+        self.refresh_visible_badges();
+        let was_active = matches!(
+            *self.active_source.borrow(),
+            ContentSource::Album { id, .. } if id == album_id
+        );
+        if was_active {
+            self.switch_content_source(ContentSource::Directory);
+        }
+        self.set_status(&format!("> Deleted album: {}", name));
     }
 
-    fn prompt_new_album(&self, path: PathBuf) {
-        let shell = self.build_dialog_shell("New Album", 360);
-        let name_label = Label::new(Some("Album name"));
-        name_label.set_halign(Align::Start);
-        shell.content.append(&name_label);
+    fn prompt_edit_metadata(&self, path: PathBuf) {
+        let fields = metadata_editor::editable_fields(&path);
+        if fields.is_empty() {
+            self.set_status("> Metadata editing not supported for this format");
+            return;
+        }
+        let current = match metadata_editor::read_metadata(&path) {
+            Ok(current) => current,
+            Err(err) => {
+                tracing::warn!(error = ?err, "Failed to read metadata");
+                self.set_status("> Failed to read metadata");
+                return;
+            }
+        };
 
-        let name_entry = Entry::new();
-        name_entry.set_placeholder_text(Some("e.g. Favorites 2026"));
-        shell.content.append(&name_entry);
+        let shell = self.build_dialog_shell("Edit Metadata", 420);
+        let filename = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("[item]")
+            .to_string();
+        let title = Label::new(Some(&filename));
+        title.set_halign(Align::Start);
+        title.add_css_class("section-title");
+        shell.content.append(&title);
+
+        let warning = Label::new(Some(
+            "> Warning: saving rewrites this file's metadata on disk. Pixel data is not re-encoded.",
+        ));
+        warning.set_halign(Align::Start);
+        warning.set_wrap(true);
+        warning.add_css_class("error-text");
+        shell.content.append(&warning);
+
+        let mut date_entry = None;
+        let mut description_entry = None;
+        let mut keywords_entry = None;
+        for field in fields {
+            let label = Label::new(Some(field.label()));
+            label.set_halign(Align::Start);
+            shell.content.append(&label);
+
+            let entry = Entry::new();
+            match field {
+                EditableField::DateTaken => {
+                    entry.set_placeholder_text(Some("YYYY-MM-DD HH:MM:SS"));
+                    if let Some(date) = current.date_taken.as_deref() {
+                        entry.set_text(&metadata_editor::display_date_taken(date));
+                    }
+                    date_entry = Some(entry.clone());
+                }
+                EditableField::Description => {
+                    if let Some(description) = current.description.as_deref() {
+                        entry.set_text(description);
+                    }
+                    description_entry = Some(entry.clone());
+                }
+                EditableField::Keywords => {
+                    entry.set_placeholder_text(Some("comma, separated, keywords"));
+                    entry.set_text(&current.keywords.join(", "));
+                    keywords_entry = Some(entry.clone());
+                }
+            }
+            shell.content.append(&entry);
+        }
+
+        let error_label = Label::new(None);
+        error_label.set_halign(Align::Start);
+        error_label.add_css_class("error-text");
+        error_label.set_visible(false);
+        shell.content.append(&error_label);
 
         let actions = GtkBox::new(Orientation::Horizontal, 8);
-        let create_button = Button::with_label("Create");
+        let save_button = Button::with_label("Write to file");
         let spacer = GtkBox::new(Orientation::Horizontal, 0);
         spacer.set_hexpand(true);
         actions.append(&spacer);
-        actions.append(&create_button);
+        actions.append(&save_button);
         shell.content.append(&actions);
 
         let dialog = shell.dialog.clone();
-        let dialog_for_create = dialog.clone();
+        let dialog_for_save = dialog.clone();
         let window_weak = self.self_weak.borrow().clone();
-        create_button.connect_clicked(move |_| {
-            let name = name_entry.text().trim().to_string();
-            if name.is_empty() {
-                return;
-            }
+        save_button.connect_clicked(move |_| {
+            let date_taken = match date_entry.as_ref().map(|e| e.text().trim().to_string()) {
+                Some(text) if !text.is_empty() => match metadata_editor::parse_date_taken(&text) {
+                    Some(date) => Some(date),
+                    None => {
+                        error_label.set_text("> Invalid date (expected YYYY-MM-DD HH:MM:SS)");
+                        error_label.set_visible(true);
+                        return;
+                    }
+                },
+                _ => None,
+            };
+            let description = description_entry
+                .as_ref()
+                .map(|e| e.text().trim().to_string())
+                .filter(|text| !text.is_empty());
+            let keywords = keywords_entry
+                .as_ref()
+                .map(|e| metadata_editor::parse_keywords(&e.text()))
+                .unwrap_or_default();
+            let edited = EditableMetadata {
+                date_taken,
+                description,
+                keywords,
+            };
             if let Some(window) = window_weak.upgrade() {
-                window.create_album_and_add(&name, &path);
+                window.write_metadata_for_path(&path, &edited);
             }
-            dialog_for_create.close();
+            dialog_for_save.close();
         });
 
         let dialog_for_close = dialog.clone();
@@ -2163,6 +7034,57 @@ impl MainWindow {
         shell.dialog.present();
     }
 
+    fn write_metadata_for_path(&self, path: &Path, metadata: &EditableMetadata) {
+        let filename = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("[item]");
+        if let Err(err) = metadata_editor::write_metadata(path, metadata) {
+            tracing::warn!(error = ?err, "Failed to write metadata");
+            self.set_status(&format!("> Failed to write metadata: {}", filename));
+            return;
+        }
+        if let Some(store) = self.media_store.borrow().as_ref() {
+            if let Err(err) = store.set_edited_metadata(
+                path,
+                metadata.date_taken.as_deref(),
+                metadata.description.as_deref(),
+                &metadata.keywords,
+            ) {
+                tracing::warn!(error = ?err, "Failed to store edited metadata");
+            }
+        }
+        self.refresh_metadata_filter_matches();
+        self.set_status(&format!("> Metadata written: {}", filename));
+    }
+
+    /// Write the viewer rotation into the file and drop every cached image of it.
+    fn save_rotation(&self, path: &Path, rotation_cw: u8) {
+        let filename = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("[item]");
+        let message = match file_ops::save_rotation(path, rotation_cw) {
+            Ok(()) => {
+                if let Some(store) = self.media_store.borrow().as_ref() {
+                    if let Err(err) = store.clear_thumbnails(path) {
+                        tracing::warn!(error = ?err, "Failed to clear stale thumbnails");
+                    }
+                }
+                forget_row_preview(path);
+                self.viewer.rotation_saved(path);
+                self.reload_active_source();
+                format!("> Rotation saved: {}", filename)
+            }
+            Err(err) => {
+                tracing::warn!(error = ?err, "Failed to save rotation");
+                format!("> Cannot save rotation: {}", err)
+            }
+        };
+        self.set_status(&message);
+        self.viewer.show_message(&message);
+    }
+
     fn show_album_menu(&self, path: PathBuf, anchor: &gtk4::Widget, rect: Rectangle) {
         if self.is_folder_path(&path) {
             return;
@@ -2180,22 +7102,48 @@ impl MainWindow {
                 return;
             }
         };
-        let is_favorite = match store.is_favorite(&path) {
-            Ok(value) => value,
+        let targets = self.action_targets(&path);
+        let is_favorite = match targets
+            .iter()
+            .map(|target| store.is_favorite(target))
+            .collect::<anyhow::Result<Vec<bool>>>()
+        {
+            Ok(values) => values.iter().all(|&value| value),
             Err(err) => {
                 tracing::warn!(error = ?err, "Failed to read favorite status");
                 self.set_status("> Failed to load favourite status");
                 false
             }
         };
-        let album_membership: HashSet<i64> = match store.album_ids_for_path(&path) {
-            Ok(ids) => ids.into_iter().collect(),
-            Err(err) => {
-                tracing::warn!(error = ?err, "Failed to read album membership");
-                self.set_status("> Failed to load album membership");
-                HashSet::new()
-            }
-        };
+        // An album is checked only when every target is already in it
+        let mut album_membership: Option<HashSet<i64>> = None;
+        for target in &targets {
+            let ids: HashSet<i64> = match store.album_ids_for_path(target) {
+                Ok(ids) => ids.into_iter().collect(),
+                Err(err) => {
+                    tracing::warn!(error = ?err, "Failed to read album membership");
+                    self.set_status("> Failed to load album membership");
+                    HashSet::new()
+                }
+            };
+            album_membership = Some(match album_membership {
+                Some(common) => common.intersection(&ids).copied().collect(),
+                None => ids,
+            });
+        }
+        let album_membership = album_membership.unwrap_or_default();
+        let tags = store.list_tags().unwrap_or_else(|err| {
+            tracing::warn!(error = ?err, "Failed to list tags");
+            Vec::new()
+        });
+        let tag_membership: HashSet<String> = common_tags(store, &targets)
+            .unwrap_or_else(|err| {
+                tracing::warn!(error = ?err, "Failed to read tags");
+                Vec::new()
+            })
+            .into_iter()
+            .collect();
+        let count = targets.len();
 
         let popover = gtk4::Popover::new();
         popover.add_css_class("context-menu-popover");
@@ -2223,7 +7171,11 @@ impl MainWindow {
         content.set_margin_start(8);
         content.set_margin_end(8);
 
-        let header = Label::new(Some("[ FILE MENU ]"));
+        let header = if count > 1 {
+            Label::new(Some(&format!("[ {} ITEMS ]", count)))
+        } else {
+            Label::new(Some("[ FILE MENU ]"))
+        };
         header.set_halign(Align::Start);
         header.add_css_class("context-menu-title");
         content.append(&header);
@@ -2232,12 +7184,13 @@ impl MainWindow {
         sep.add_css_class("context-menu-separator");
         content.append(&sep);
 
-        let favourite_label = if is_favorite {
-            "[x] Favourite"
-        } else {
-            "[ ] Favourite"
+        let favourite_label = match (is_favorite, count > 1) {
+            (true, false) => "[x] Favourite".to_string(),
+            (false, false) => "[ ] Favourite".to_string(),
+            (true, true) => format!("[x] Favourite {} items", count),
+            (false, true) => format!("[ ] Favourite {} items", count),
         };
-        let favourite_button = Button::with_label(favourite_label);
+        let favourite_button = Button::with_label(&favourite_label);
         favourite_button.add_css_class("context-menu-item");
         if is_favorite {
             favourite_button.add_css_class("context-menu-item-checked");
@@ -2245,11 +7198,15 @@ impl MainWindow {
         favourite_button.set_halign(Align::Fill);
         favourite_button.set_hexpand(true);
         let window_weak = self.self_weak.borrow().clone();
-        let path_for_favourite = path.clone();
+        let targets_for_favourite = targets.clone();
         let popover_for_favourite = popover.clone();
         favourite_button.connect_clicked(move |_| {
             if let Some(window) = window_weak.upgrade() {
-                window.toggle_favorite_for_path(&path_for_favourite);
+                if let [path] = targets_for_favourite.as_slice() {
+                    window.toggle_favorite_for_path(path);
+                } else {
+                    window.toggle_favorite_for_paths(&targets_for_favourite);
+                }
             }
             popover_for_favourite.popdown();
         });
@@ -2259,6 +7216,13 @@ impl MainWindow {
         sep.add_css_class("context-menu-separator");
         content.append(&sep);
 
+        if count > 1 {
+            let title = Label::new(Some(&format!("Add {} items to album\u{2026}", count)));
+            title.set_halign(Align::Start);
+            title.add_css_class("context-menu-empty");
+            content.append(&title);
+        }
+
         if albums.is_empty() {
             let empty = Label::new(Some("No albums yet"));
             empty.add_css_class("context-menu-empty");
@@ -2281,11 +7245,11 @@ impl MainWindow {
             button.set_halign(Align::Fill);
             button.set_hexpand(true);
             let window_weak = window_weak.clone();
-            let path = path.clone();
+            let targets = targets.clone();
             let popover = popover.clone();
             button.connect_clicked(move |_| {
                 if let Some(window) = window_weak.upgrade() {
-                    window.add_path_to_album(album_id, &path);
+                    window.add_paths_to_album(album_id, &targets);
                 }
                 popover.popdown();
             });
@@ -2302,16 +7266,207 @@ impl MainWindow {
         new_album_btn.set_halign(Align::Fill);
         new_album_btn.set_hexpand(true);
         let window_weak = self.self_weak.borrow().clone();
-        let path_for_new = path.clone();
+        let targets_for_new = targets.clone();
         let popover_for_new = popover.clone();
         new_album_btn.connect_clicked(move |_| {
             if let Some(window) = window_weak.upgrade() {
-                window.prompt_new_album(path_for_new.clone());
+                window.prompt_new_album(targets_for_new.clone());
             }
             popover_for_new.popdown();
         });
         content.append(&new_album_btn);
 
+        let active_album = match &*self.active_source.borrow() {
+            ContentSource::Album { id, name } => Some((*id, name.clone())),
+            _ => None,
+        };
+        if let (Some((album_id, album_name)), [cover]) = (active_album, targets.as_slice()) {
+            let cover_btn = Button::with_label("Set as album cover");
+            cover_btn.add_css_class("context-menu-item");
+            cover_btn.set_halign(Align::Fill);
+            cover_btn.set_hexpand(true);
+            let window_weak = self.self_weak.borrow().clone();
+            let cover = cover.clone();
+            let popover_for_cover = popover.clone();
+            cover_btn.connect_clicked(move |_| {
+                popover_for_cover.popdown();
+                if let Some(window) = window_weak.upgrade() {
+                    window.set_album_cover(album_id, &album_name, &cover);
+                }
+            });
+            content.append(&cover_btn);
+        }
+
+        let sep = gtk4::Separator::new(Orientation::Horizontal);
+        sep.add_css_class("context-menu-separator");
+        content.append(&sep);
+
+        let window_weak = self.self_weak.borrow().clone();
+        for name in tags {
+            let tagged = tag_membership.contains(&name);
+            let label = if tagged {
+                format!("[x] #{}", name)
+            } else {
+                format!("[ ] #{}", name)
+            };
+            let button = Button::with_label(&label);
+            button.add_css_class("context-menu-item");
+            if tagged {
+                button.add_css_class("context-menu-item-checked");
+            }
+            button.set_halign(Align::Fill);
+            button.set_hexpand(true);
+            let window_weak = window_weak.clone();
+            let targets = targets.clone();
+            let popover = popover.clone();
+            button.connect_clicked(move |_| {
+                if let Some(window) = window_weak.upgrade() {
+                    let name = std::slice::from_ref(&name);
+                    if tagged {
+                        window.edit_tags(&targets, &[], name);
+                    } else {
+                        window.edit_tags(&targets, name, &[]);
+                    }
+                }
+                popover.popdown();
+            });
+            content.append(&button);
+        }
+
+        let tag_btn = Button::with_label("+ Tag... (t)");
+        tag_btn.add_css_class("context-menu-item");
+        tag_btn.add_css_class("context-menu-create");
+        tag_btn.set_halign(Align::Fill);
+        tag_btn.set_hexpand(true);
+        let window_weak = self.self_weak.borrow().clone();
+        let targets_for_tag = targets.clone();
+        let popover_for_tag = popover.clone();
+        tag_btn.connect_clicked(move |_| {
+            popover_for_tag.popdown();
+            if let Some(window) = window_weak.upgrade() {
+                window.show_tag_entry(targets_for_tag.clone(), Some(pointing));
+            }
+        });
+        content.append(&tag_btn);
+
+        let sep = gtk4::Separator::new(Orientation::Horizontal);
+        sep.add_css_class("context-menu-separator");
+        content.append(&sep);
+
+        if count == 1 {
+            let rename_btn = Button::with_label("Rename... (F2)");
+            rename_btn.add_css_class("context-menu-item");
+            rename_btn.set_halign(Align::Fill);
+            rename_btn.set_hexpand(true);
+            let window_weak = self.self_weak.borrow().clone();
+            let path_for_rename = path.clone();
+            let popover_for_rename = popover.clone();
+            rename_btn.connect_clicked(move |_| {
+                popover_for_rename.popdown();
+                if let Some(window) = window_weak.upgrade() {
+                    window.prompt_rename(path_for_rename.clone());
+                }
+            });
+            content.append(&rename_btn);
+        }
+
+        if count == 1 && !self.is_folder_path(&path) {
+            let external_btn = Button::with_label("Open externally (e)");
+            external_btn.add_css_class("context-menu-item");
+            external_btn.set_halign(Align::Fill);
+            external_btn.set_hexpand(true);
+            let window_weak = self.self_weak.borrow().clone();
+            let path_for_external = path.clone();
+            let popover_for_external = popover.clone();
+            external_btn.connect_clicked(move |_| {
+                popover_for_external.popdown();
+                if let Some(window) = window_weak.upgrade() {
+                    window.open_external(&path_for_external);
+                }
+            });
+            content.append(&external_btn);
+        }
+
+        if count == 1 {
+            let reveal_btn = Button::with_label("Show in file manager (E)");
+            reveal_btn.add_css_class("context-menu-item");
+            reveal_btn.set_halign(Align::Fill);
+            reveal_btn.set_hexpand(true);
+            let window_weak = self.self_weak.borrow().clone();
+            let path_for_reveal = path.clone();
+            let popover_for_reveal = popover.clone();
+            reveal_btn.connect_clicked(move |_| {
+                popover_for_reveal.popdown();
+                if let Some(window) = window_weak.upgrade() {
+                    window.reveal(&path_for_reveal);
+                }
+            });
+            content.append(&reveal_btn);
+        }
+
+        if count == 1 && self.stored_gps(&path).is_some() {
+            let location_btn = Button::with_label("Open location (P)");
+            location_btn.add_css_class("context-menu-item");
+            location_btn.set_halign(Align::Fill);
+            location_btn.set_hexpand(true);
+            let window_weak = self.self_weak.borrow().clone();
+            let path_for_location = path.clone();
+            let popover_for_location = popover.clone();
+            location_btn.connect_clicked(move |_| {
+                popover_for_location.popdown();
+                if let Some(window) = window_weak.upgrade() {
+                    window.open_location(&path_for_location);
+                }
+            });
+            content.append(&location_btn);
+        }
+
+        let files: Vec<PathBuf> = targets
+            .iter()
+            .filter(|target| !self.is_folder_path(target))
+            .cloned()
+            .collect();
+        for (label, mode) in [
+            ("Copy to... (c)", TransferMode::Copy),
+            ("Move to... (m)", TransferMode::Move),
+        ] {
+            let button = Button::with_label(label);
+            button.add_css_class("context-menu-item");
+            button.set_halign(Align::Fill);
+            button.set_hexpand(true);
+            let window_weak = self.self_weak.borrow().clone();
+            let files = files.clone();
+            let popover = popover.clone();
+            button.connect_clicked(move |_| {
+                popover.popdown();
+                if let Some(window) = window_weak.upgrade() {
+                    window.prompt_transfer(files.clone(), mode);
+                }
+            });
+            content.append(&button);
+        }
+
+        if count == 1 && self.metadata_editing.get() && metadata_editor::can_edit(&path) {
+            let sep = gtk4::Separator::new(Orientation::Horizontal);
+            sep.add_css_class("context-menu-separator");
+            content.append(&sep);
+
+            let metadata_btn = Button::with_label("Edit metadata...");
+            metadata_btn.add_css_class("context-menu-item");
+            metadata_btn.set_halign(Align::Fill);
+            metadata_btn.set_hexpand(true);
+            let window_weak = self.self_weak.borrow().clone();
+            let path_for_metadata = path.clone();
+            let popover_for_metadata = popover.clone();
+            metadata_btn.connect_clicked(move |_| {
+                if let Some(window) = window_weak.upgrade() {
+                    window.prompt_edit_metadata(path_for_metadata.clone());
+                }
+                popover_for_metadata.popdown();
+            });
+            content.append(&metadata_btn);
+        }
+
         popover.set_child(Some(&content));
         popover.popup();
     }
@@ -2323,6 +7478,121 @@ mod tests {
     use crate::models::RowItem;
     use std::path::PathBuf;
 
+    #[test]
+    fn resolve_initial_path_opens_supported_files_in_their_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let photo = dir.path().join("photo.JPG");
+        let notes = dir.path().join("notes.txt");
+        std::fs::write(&photo, b"").unwrap();
+        std::fs::write(&notes, b"").unwrap();
+
+        assert_eq!(
+            resolve_initial_path(&photo),
+            (dir.path().to_path_buf(), Some(photo.clone()))
+        );
+        assert_eq!(
+            resolve_initial_path(&notes),
+            (dir.path().to_path_buf(), None)
+        );
+        assert_eq!(
+            resolve_initial_path(dir.path()),
+            (dir.path().to_path_buf(), None)
+        );
+    }
+
+    #[test]
+    fn breadcrumbs_fold_the_middle_of_deep_paths() {
+        let segments = breadcrumb_segments(Path::new("/home/user/photos"));
+        let labels: Vec<&str> = segments.iter().map(|(label, _)| label.as_str()).collect();
+        assert_eq!(labels, ["/", "home", "user", "photos"]);
+        assert_eq!(segments[2].1, PathBuf::from("/home/user"));
+        assert!(collapsed_breadcrumbs(segments.len()).is_empty());
+
+        let deep = breadcrumb_segments(Path::new("/a/b/c/d/e/f/g/h"));
+        let hidden = collapsed_breadcrumbs(deep.len());
+        assert_eq!(hidden, 1..5);
+        assert_eq!(deep[hidden.end].1, PathBuf::from("/a/b/c/d/e"));
+    }
+
+    #[test]
+    fn album_accelerators_skip_the_picker_key_and_run_out() {
+        assert_eq!(album_accelerator(0), Some('1'));
+        assert_eq!(album_accelerator(9), Some('b'));
+        assert!((0..ALBUM_ACCELERATORS.len()).all(|i| album_accelerator(i) != Some('a')));
+        assert_eq!(album_accelerator(ALBUM_ACCELERATORS.len()), None);
+    }
+
+    #[test]
+    fn shuffle_seed_from_text_keeps_numbers_and_hashes_names() {
+        assert_eq!(shuffle_seed_from_text(" 42 "), Some(42));
+        assert_eq!(shuffle_seed_from_text("  "), None);
+        let named = shuffle_seed_from_text("beach trip").unwrap();
+        assert_eq!(shuffle_seed_from_text("beach trip"), Some(named));
+        assert_ne!(shuffle_seed_from_text("beach trip 2"), Some(named));
+    }
+
+    #[test]
+    fn deletion_summary_names_one_file_or_lists_a_batch() {
+        assert_eq!(
+            deletion_summary(&[PathBuf::from("/p/beach.jpg")]),
+            "Move beach.jpg to the trash?"
+        );
+        let batch: Vec<PathBuf> = (1..=7)
+            .map(|i| PathBuf::from(format!("/p/{}.jpg", i)))
+            .collect();
+        assert_eq!(
+            deletion_summary(&batch),
+            "Move 7 files to the trash?\n\n1.jpg\n2.jpg\n3.jpg\n4.jpg\n5.jpg\n…and 2 more"
+        );
+        assert_eq!(
+            deletion_summary(&batch[..2]),
+            "Move 2 files to the trash?\n\n1.jpg\n2.jpg"
+        );
+    }
+
+    #[test]
+    fn describe_item_reports_kind_resolution_and_size() {
+        let mut photo = MediaItem::new(PathBuf::from("/p/a.jpg"), 4000, 3000);
+        photo.size = 2_516_582;
+        assert_eq!(describe_item(&photo), "Image 4000x3000 | 2.4 MB");
+
+        let mut video = MediaItem::new(PathBuf::from("/p/b.mp4"), 0, 0);
+        video.media_type = MediaType::Video;
+        assert_eq!(describe_item(&video), "Video");
+
+        let folder = MediaItem::new_folder(PathBuf::from("/p/trips"));
+        assert_eq!(describe_item(&folder), "Folder");
+    }
+
+    #[test]
+    fn directory_position_is_dropped_after_large_listing_changes() {
+        let position = DirectoryPosition {
+            selection: (3, 1),
+            scroll: 480.0,
+            item_count: 200,
+        };
+        assert!(position.still_applies(200));
+        assert!(position.still_applies(180));
+        assert!(!position.still_applies(179));
+        assert!(!position.still_applies(260));
+
+        let small = DirectoryPosition {
+            item_count: 4,
+            ..position
+        };
+        assert!(small.still_applies(5));
+        assert!(!small.still_applies(6));
+    }
+
+    #[test]
+    fn count_setting_prefers_env_and_clamps() {
+        assert_eq!(count_setting(Some("40"), Some("10"), 1..=100), Some(40));
+        assert_eq!(count_setting(Some("lots"), Some("10"), 1..=100), Some(10));
+        assert_eq!(count_setting(None, Some(" 500 "), 1..=100), Some(100));
+        assert_eq!(count_setting(Some("0"), None, 1..=100), Some(1));
+        assert_eq!(count_setting(None, None, 1..=100), None);
+    }
+
     #[test]
     fn test_fallback_css_parses() {
         // Verify the fallback CSS is valid
@@ -2382,6 +7652,8 @@ mod tests {
                     is_folder: false,
                 },
             ],
+            gutter_px: 0.0,
+            show_captions: false,
         }];
 
         let clamped = MainWindow::clamp_rows_to_width(rows, 1200.0);
@@ -2389,6 +7661,45 @@ mod tests {
         assert!(total_width <= 1200.0 + 0.01);
     }
 
+    #[test]
+    fn clamp_rows_to_width_leaves_room_for_gutters() {
+        let items = (0..3)
+            .map(|i| RowItem {
+                media_path: PathBuf::from(format!("{}.jpg", i)),
+                display_w: 500.0,
+                display_h: 220.0,
+                offset_top: 12.0,
+                is_folder: false,
+            })
+            .collect();
+        let rows = vec![RowModel::new(0, 232.0, items).with_gutter(12.0)];
+
+        let clamped = MainWindow::clamp_rows_to_width(rows, 1200.0);
+        let row = &clamped[0];
+        let widths: f32 = row.items.iter().map(|item| item.display_w).sum();
+        assert!(widths + 2.0 * row.gutter_px <= 1200.0 + 0.01);
+        assert_eq!(row.gutter_px, 12.0);
+        assert!(row.items.iter().all(|item| item.offset_top == 12.0));
+    }
+
+    #[test]
+    fn clamp_rows_to_width_handles_small_row_heights() {
+        let items: Vec<MediaItem> = [(8000, 100), (100, 8000), (400, 300), (300, 400)]
+            .iter()
+            .enumerate()
+            .map(|(i, &(w, h))| MediaItem::new(PathBuf::from(format!("{}.jpg", i)), w, h))
+            .collect();
+
+        let rows = JustifiedLayout::with_target_height(MIN_ROW_HEIGHT).compute(&items, 320.0);
+        let clamped = MainWindow::clamp_rows_to_width(rows, 320.0);
+        assert_eq!(clamped.iter().map(|r| r.items.len()).sum::<usize>(), 4);
+        for row in &clamped {
+            let total_width: f32 = row.items.iter().map(|item| item.display_w).sum();
+            assert!(total_width <= 320.0 + 0.01);
+            assert!(row.height_px >= 1.0 && row.height_px <= MIN_ROW_HEIGHT + 0.01);
+        }
+    }
+
     #[test]
     fn clamp_rows_to_width_sanitizes_invalid_item_dimensions() {
         let rows = vec![RowModel {
@@ -2410,6 +7721,8 @@ mod tests {
                     is_folder: false,
                 },
             ],
+            gutter_px: 0.0,
+            show_captions: false,
         }];
 
         let clamped = MainWindow::clamp_rows_to_width(rows, 1200.0);
@@ -2420,4 +7733,68 @@ mod tests {
             assert!(item.offset_top.is_finite() && item.offset_top >= 0.0);
         }
     }
+
+    #[test]
+    fn step_media_path_skips_folders_and_wraps() {
+        let flat: Vec<PathBuf> = ["dir", "a.jpg", "b.mp4", "sub", "c.png"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let folders: HashSet<PathBuf> = [PathBuf::from("dir"), PathBuf::from("sub")]
+            .into_iter()
+            .collect();
+        let step = |from: &str, step: i32, wrap: bool| {
+            step_media_path(&flat, &folders, Path::new(from), step, wrap)
+        };
+
+        assert_eq!(step("b.mp4", 1, false), Some(PathBuf::from("c.png")));
+        assert_eq!(step("c.png", 1, false), None);
+        assert_eq!(step("c.png", 1, true), Some(PathBuf::from("a.jpg")));
+        assert_eq!(step("a.jpg", -1, true), Some(PathBuf::from("c.png")));
+        assert_eq!(step("a.jpg", 2, false), Some(PathBuf::from("c.png")));
+        assert_eq!(step("missing.jpg", 1, true), None);
+    }
+
+    #[test]
+    fn step_media_path_needs_another_file() {
+        let flat = vec![PathBuf::from("dir"), PathBuf::from("only.jpg")];
+        let folders: HashSet<PathBuf> = [PathBuf::from("dir")].into_iter().collect();
+        assert_eq!(
+            step_media_path(&flat, &folders, Path::new("only.jpg"), 1, true),
+            None
+        );
+    }
+
+    #[test]
+    fn rows_per_group_starts_each_cluster_on_a_new_row() {
+        let items: Vec<MediaItem> = ["a1", "a2", "b1", "b2", "b3"]
+            .iter()
+            .map(|name| MediaItem::new(PathBuf::from(format!("{}.jpg", name)), 400, 300))
+            .collect();
+        let groups: HashMap<PathBuf, usize> = items
+            .iter()
+            .map(|item| {
+                let group = usize::from(item.path.to_string_lossy().starts_with('b'));
+                (item.path.clone(), group)
+            })
+            .collect();
+        let grid = GridLayout {
+            cell_size: 100.0,
+            gutter_px: 0.0,
+        };
+
+        let rows = rows_per_group(&items, &groups, |group| grid.compute(group, 1000.0));
+        assert_eq!(
+            rows.iter().map(|r| r.items.len()).collect::<Vec<_>>(),
+            [2, 3]
+        );
+        assert_eq!(rows.iter().map(|r| r.row_index).collect::<Vec<_>>(), [0, 1]);
+    }
+
+    #[test]
+    fn parse_tag_input_splits_additions_and_removals() {
+        let (add, remove) = parse_tag_input(" portrait, -reject ,, export,- ");
+        assert_eq!(add, ["portrait", "export"]);
+        assert_eq!(remove, ["reject"]);
+    }
 }